@@ -2,6 +2,9 @@
 """
 Background Embedding Service
 Automatically processes thoughts from Redis → OpenAI → Qdrant
+Also indexes Obsidian vault notes (OBSIDIAN_VAULT_PATH) per section into the
+vault_notes collection so unified-mind can search thoughts and notes together;
+notes obsidian-mcp encrypts (OBSIDIAN_ENCRYPTED_FOLDER) are never indexed
 Does NOT touch UI MCP - keeps it fast
 """
 
@@ -20,6 +23,8 @@ from dataclasses import dataclass, asdict
 from qdrant_client import QdrantClient, models
 from qdrant_client.models import VectorParams, Distance, PointStruct
 import hashlib
+import re
+import uuid
 
 # Configure logging
 logging.basicConfig(
@@ -32,6 +37,81 @@ logging.basicConfig(
 )
 logger = logging.getLogger(__name__)
 
+# Qdrant collection for vault note sections (searched by unified-mind's um_search)
+VAULT_COLLECTION = "vault_notes"
+
+# Sections longer than this are split at paragraph boundaries
+MAX_CHUNK_CHARS = 2000
+
+# First line of notes obsidian-mcp stores encrypted; their text is ciphertext
+ENCRYPTED_NOTE_HEADER = "%%obsidian-mcp:aes-256-gcm%%"
+
+HEADING_RE = re.compile(r'^(#{1,6})\s+(.+?)\s*#*\s*$')
+
+
+def chunk_note(text: str, title: str) -> List[Dict[str, Any]]:
+    """Split a note into sections at its headings; each chunk keeps its heading path.
+
+    Each chunk's key is its heading path, numbered only when a heading path repeats
+    and suffixed when a long section is split, so editing one section leaves the
+    keys of the others unchanged.
+    """
+    # Drop YAML frontmatter
+    if text.startswith('---\n'):
+        end = text.find('\n---', 4)
+        if end != -1:
+            text = text[end + 4:]
+
+    sections = []
+    path: List[str] = []
+    lines: List[str] = []
+    in_fence = False
+
+    def flush():
+        # A heading with nothing under it is not worth a chunk
+        text_lines = lines[1:] if lines and HEADING_RE.match(lines[0]) else lines
+        if '\n'.join(text_lines).strip():
+            sections.append((' > '.join([title] + path), '\n'.join(lines).strip()))
+
+    for line in text.split('\n'):
+        if line.lstrip().startswith(('```', '~~~')):
+            in_fence = not in_fence
+        match = None if in_fence else HEADING_RE.match(line)
+        if match:
+            flush()
+            lines = [line]
+            level = len(match.group(1))
+            path = path[:level - 1] + [match.group(2)]
+        else:
+            lines.append(line)
+    flush()
+
+    chunks = []
+    seen: Dict[str, int] = {}
+    for heading, body in sections:
+        seen[heading] = seen.get(heading, 0) + 1
+        key = heading if seen[heading] == 1 else f"{heading} ({seen[heading]})"
+        # Long sections are split at paragraphs; a single oversized paragraph stays whole
+        parts, current = [], ''
+        for paragraph in body.split('\n\n'):
+            if current and len(current) + len(paragraph) + 2 > MAX_CHUNK_CHARS:
+                parts.append(current)
+                current = paragraph
+            else:
+                current = f"{current}\n\n{paragraph}" if current else paragraph
+        parts.append(current)
+        parts = [part for part in parts if part.strip()]
+        chunks.extend(
+            {'key': key if i == 0 else f"{key} [part {i + 1}]", 'heading': heading, 'content': part}
+            for i, part in enumerate(parts)
+        )
+    return chunks
+
+
+def vault_point_id(chunk_id: str) -> str:
+    """Qdrant point ID of a vault chunk: a UUID built from the full MD5 of its chunk ID"""
+    return str(uuid.UUID(hashlib.md5(chunk_id.encode()).hexdigest()))
+
 @dataclass
 class ProcessingStats:
     """Track processing statistics"""
@@ -100,6 +180,11 @@ class BackgroundEmbeddingService:
         # Known instances to process
         self.instances = ["CC", "CCI", "CCD", "CCS", "DT", "CCB"]
         
+        # Obsidian vault to index alongside thoughts (optional)
+        self.vault_path = os.getenv('OBSIDIAN_VAULT_PATH') or None
+        # Vault folder obsidian-mcp encrypts (its encrypted_folder setting), skipped when indexing
+        self.encrypted_folder = (os.getenv('OBSIDIAN_ENCRYPTED_FOLDER') or '').strip('/') or None
+        
         # State management
         self.running = True
         self.state_file = 'embedding_service_state.json'
@@ -115,37 +200,39 @@ class BackgroundEmbeddingService:
         """Setup instance-specific collections in Qdrant"""
         logger.info("Setting up Qdrant collections...")
         
+        collections = []
         for instance in self.instances:
-            # Create thoughts collection
-            thoughts_collection = f"{instance}_thoughts"
-            identity_collection = f"{instance}_identity"
-            
-            for collection_name in [thoughts_collection, identity_collection]:
-                try:
-                    # Check if collection exists
-                    self.qdrant_client.get_collection(collection_name)
-                    logger.info(f"Collection {collection_name} already exists")
-                except:
-                    # Create collection
-                    self.qdrant_client.create_collection(
-                        collection_name=collection_name,
-                        vectors_config=VectorParams(
-                            size=1536,  # OpenAI text-embedding-3-small
-                            distance=Distance.COSINE
-                        ),
-                        optimizers_config=models.OptimizersConfigDiff(
-                            default_segment_number=2,
-                            memmap_threshold=20000,
-                        ),
-                        hnsw_config=models.HnswConfigDiff(
-                            m=16,
-                            ef_construct=100,
-                            full_scan_threshold=10000,
-                            on_disk=False
-                        )
+            collections.append(f"{instance}_thoughts")
+            collections.append(f"{instance}_identity")
+        if self.vault_path:
+            collections.append(VAULT_COLLECTION)
+        
+        for collection_name in collections:
+            try:
+                # Check if collection exists
+                self.qdrant_client.get_collection(collection_name)
+                logger.info(f"Collection {collection_name} already exists")
+            except:
+                # Create collection
+                self.qdrant_client.create_collection(
+                    collection_name=collection_name,
+                    vectors_config=VectorParams(
+                        size=1536,  # OpenAI text-embedding-3-small
+                        distance=Distance.COSINE
+                    ),
+                    optimizers_config=models.OptimizersConfigDiff(
+                        default_segment_number=2,
+                        memmap_threshold=20000,
+                    ),
+                    hnsw_config=models.HnswConfigDiff(
+                        m=16,
+                        ef_construct=100,
+                        full_scan_threshold=10000,
+                        on_disk=False
                     )
-                    logger.info(f"Created Qdrant collection: {collection_name}")
-    
+                )
+                logger.info(f"Created Qdrant collection: {collection_name}")
+
     def get_processed_thoughts(self) -> Dict[str, Set[str]]:
         """Get set of already processed thought IDs per instance"""
         processed = {}
@@ -547,6 +634,145 @@ class BackgroundEmbeddingService:
             # Rate limiting - respect OpenAI limits
             time.sleep(3)  # 3 seconds between batches
     
+    def get_processed_vault(self) -> Dict[str, Dict[str, Any]]:
+        """Get content hashes and point IDs of already indexed vault chunks, keyed by chunk ID"""
+        processed = {}
+        offset = None
+        try:
+            while True:
+                points, offset = self.qdrant_client.scroll(
+                    collection_name=VAULT_COLLECTION,
+                    limit=1000,
+                    offset=offset,
+                    with_payload=['chunk_id', 'content_hash'],
+                    with_vectors=False
+                )
+                for point in points:
+                    if 'chunk_id' in point.payload:
+                        processed[point.payload['chunk_id']] = {
+                            'content_hash': point.payload.get('content_hash', ''),
+                            'point_id': point.id,
+                        }
+                if offset is None:
+                    break
+            logger.info(f"Found {len(processed)} indexed vault chunks")
+        except Exception as e:
+            logger.warning(f"Could not get indexed vault chunks: {e}")
+        return processed
+    
+    def scan_vault(self) -> List[Dict[str, Any]]:
+        """Split every Markdown note in the vault into section chunks"""
+        chunks = []
+        skipped = 0
+        encrypted_dir = os.path.normpath(os.path.join(self.vault_path, self.encrypted_folder)) if self.encrypted_folder else None
+        for root, dirs, files in os.walk(self.vault_path):
+            # Skip .obsidian, .trash and other hidden folders, and the encrypted folder
+            dirs[:] = [d for d in dirs
+                       if not d.startswith('.') and os.path.normpath(os.path.join(root, d)) != encrypted_dir]
+            for name in files:
+                if not name.endswith('.md'):
+                    continue
+                path = os.path.join(root, name)
+                note_path = os.path.relpath(path, self.vault_path)
+                try:
+                    with open(path, 'r', encoding='utf-8') as f:
+                        text = f.read()
+                    modified_at = datetime.fromtimestamp(os.path.getmtime(path)).isoformat()
+                except Exception as e:
+                    logger.error(f"Error reading note {note_path}: {e}")
+                    continue
+                # Encrypted notes would only embed ciphertext
+                if text.startswith(ENCRYPTED_NOTE_HEADER):
+                    skipped += 1
+                    continue
+                
+                for index, chunk in enumerate(chunk_note(text, name[:-3])):
+                    chunk_id = f"{note_path}#{chunk['key']}"
+                    chunks.append({
+                        'chunk_id': chunk_id,
+                        'note_path': note_path,
+                        'heading': chunk['heading'],
+                        'section': index,
+                        'content': chunk['content'],
+                        'content_hash': hashlib.md5(f"{chunk['heading']}\n{chunk['content']}".encode()).hexdigest(),
+                        'modified_at': modified_at,
+                    })
+        logger.info(f"Found {len(chunks)} sections in vault {self.vault_path} ({skipped} encrypted notes skipped)")
+        return chunks
+    
+    def store_in_qdrant_vault(self, chunks: List[Dict[str, Any]]):
+        """Store vault chunks with embeddings in Qdrant"""
+        points = [
+            PointStruct(
+                id=vault_point_id(chunk['chunk_id']),
+                vector=chunk['embedding'],
+                payload={
+                    "chunk_id": chunk['chunk_id'],
+                    "note_path": chunk['note_path'],
+                    "heading": chunk['heading'],
+                    "section": chunk['section'],
+                    "content": chunk['content'],
+                    "content_hash": chunk['content_hash'],
+                    "modified_at": chunk['modified_at'],
+                    "embedding_model": chunk['embedding_model'],
+                    "processed_at": chunk['processed_at'],
+                    "data_type": "vault"
+                }
+            )
+            for chunk in chunks if 'embedding' in chunk
+        ]
+        if not points:
+            return
+        
+        try:
+            self.qdrant_client.upsert(collection_name=VAULT_COLLECTION, points=points)
+            self.stats.qdrant_writes += len(points)
+            logger.info(f"Stored {len(points)} vault sections in {VAULT_COLLECTION}")
+        except Exception as e:
+            logger.error(f"Error storing vault sections in Qdrant: {e}")
+            self.stats.errors += 1
+    
+    def process_vault(self):
+        """Embed new and changed note sections, and drop sections that no longer exist"""
+        logger.info(f"Processing vault: {self.vault_path}")
+        
+        chunks = self.scan_vault()
+        processed = self.get_processed_vault()
+        
+        changed = [c for c in chunks if processed.get(c['chunk_id'], {}).get('content_hash') != c['content_hash']]
+        current_ids = {c['chunk_id'] for c in chunks}
+        removed = [chunk_id for chunk_id in processed if chunk_id not in current_ids]
+        
+        if removed:
+            try:
+                self.qdrant_client.delete(
+                    collection_name=VAULT_COLLECTION,
+                    # Delete by the stored point IDs, so points written under an older ID scheme go too
+                    points_selector=models.PointIdsList(
+                        points=[processed[chunk_id]['point_id'] for chunk_id in removed]
+                    )
+                )
+                logger.info(f"Removed {len(removed)} vault sections that no longer exist")
+            except Exception as e:
+                logger.error(f"Error removing vault sections: {e}")
+                self.stats.errors += 1
+        
+        if not changed:
+            logger.info("No new or changed vault sections")
+            return
+        
+        for i in range(0, len(changed), self.batch_size):
+            batch = changed[i:i + self.batch_size]
+            
+            logger.info(f"Processing vault batch {i//self.batch_size + 1}: {len(batch)} sections")
+            
+            batch_with_embeddings = self.generate_embeddings_batch(batch)
+            if batch_with_embeddings:
+                self.store_in_qdrant_vault(batch_with_embeddings)
+            
+            # Rate limiting - respect OpenAI limits
+            time.sleep(3)
+    
     def run_single_scan(self):
         """Run a single scan cycle"""
         logger.info("Starting background embedding scan...")
@@ -567,6 +793,9 @@ class BackgroundEmbeddingService:
                 self.process_instance(instance, processed_thoughts.get(instance, set()))
                 self.process_identity(instance, processed_identity.get(instance, set()))
             
+            if self.vault_path:
+                self.process_vault()
+            
             self.stats.last_run = datetime.now()
             
             # Log summary
@@ -1,9 +1,24 @@
 use clap::{Parser, Subcommand};
 use colored::*;
-use redis::Commands;
+use redis::Commands as _;
 use rustyline::DefaultEditor;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+/// True while a response is streaming, so Ctrl-C cancels it instead of exiting
+static GENERATING: AtomicBool = AtomicBool::new(false);
+/// Set by Ctrl-C during generation; checked while waiting for streamed chunks
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// Longest wait for the next streamed chunk before checking for Ctrl-C again
+const CANCEL_POLL: Duration = Duration::from_millis(100);
+
+/// Most characters of earlier messages sent with a prompt; older messages are dropped whole
+const CONTEXT_CHARS: usize = 2000;
 
 #[derive(Parser)]
 #[command(name = "bot")]
@@ -29,6 +44,14 @@ struct Cli {
     #[arg(long)]
     no_context: bool,
 
+    /// Wait for the full response instead of printing it as it streams
+    #[arg(long)]
+    no_stream: bool,
+
+    /// Set the session's system prompt (an empty string removes it)
+    #[arg(long)]
+    system: Option<String>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -45,10 +68,23 @@ enum Commands {
     Context,
 }
 
+/// One chat turn, stored in Redis as JSON in the session's history list
+#[derive(Serialize, Deserialize, Clone)]
+struct Message {
+    role: String,
+    content: String,
+}
+
+impl Message {
+    fn new(role: &str, content: &str) -> Self {
+        Message { role: role.to_string(), content: content.to_string() }
+    }
+}
+
 #[derive(Serialize)]
 struct OllamaRequest {
     model: String,
-    prompt: String,
+    messages: Vec<Message>,
     stream: bool,
     options: OllamaOptions,
 }
@@ -59,9 +95,134 @@ struct OllamaOptions {
     top_p: f32,
 }
 
+// With streaming, Ollama sends one of these per line and `done` on the last
 #[derive(Deserialize)]
 struct OllamaResponse {
-    response: String,
+    message: Option<Message>,
+    #[serde(default)]
+    done: bool,
+    error: Option<String>,
+}
+
+impl OllamaResponse {
+    fn content(&self) -> &str {
+        self.message.as_ref().map(|m| m.content.as_str()).unwrap_or_default()
+    }
+}
+
+/// The latest messages that fit in `max_chars`, dropping older ones whole
+/// so the model never sees a turn cut off mid-message
+fn context_window(history: &[Message], max_chars: usize) -> &[Message] {
+    let mut used = 0;
+    let mut start = history.len();
+    while start > 0 && used + history[start - 1].content.len() <= max_chars {
+        used += history[start - 1].content.len();
+        start -= 1;
+    }
+    // Open on a user turn rather than a reply to a dropped question
+    while start < history.len() && history[start].role != "user" {
+        start += 1;
+    }
+    &history[start..]
+}
+
+/// Messages from the text transcript older versions appended per session
+fn parse_legacy_transcript(transcript: &str) -> Vec<Message> {
+    let mut messages = Vec::new();
+    for exchange in transcript.split("\n---") {
+        let Some(rest) = exchange.trim_start_matches('\n').strip_prefix("USER: ") else {
+            continue;
+        };
+        if let Some((prompt, response)) = rest.split_once("\nBOT: ") {
+            messages.push(Message::new("user", prompt));
+            messages.push(Message::new("assistant", response));
+        }
+    }
+    messages
+}
+
+/// A completed or cancelled response
+#[derive(Debug)]
+struct Generation {
+    text: String,
+    cancelled: bool,
+}
+
+/// What happened next while reading a streamed response
+enum StreamEvent {
+    /// One NDJSON line from Ollama
+    Line(String),
+    /// Ctrl-C was pressed
+    Cancelled,
+    /// The stream ended
+    Closed,
+}
+
+/// Apply one stream event to the response read so far, passing new text to `on_chunk`.
+/// Returns the response once it is done or cancelled, None to keep reading, and an
+/// error for an Ollama error or a stream that ends before `done`.
+fn apply_stream_event(text: &mut String, event: StreamEvent, on_chunk: &mut dyn FnMut(&str)) -> Result<Option<Generation>, Box<dyn Error>> {
+    let line = match event {
+        StreamEvent::Line(line) => line,
+        StreamEvent::Cancelled => return Ok(Some(Generation { text: std::mem::take(text), cancelled: true })),
+        // A truncated reply must not be saved as if it were complete
+        StreamEvent::Closed => return Err("Ollama closed the stream before the response finished".into()),
+    };
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+    let chunk: OllamaResponse = serde_json::from_str(&line)?;
+    if let Some(error) = chunk.error {
+        return Err(format!("Ollama error: {}", error).into());
+    }
+    on_chunk(chunk.content());
+    text.push_str(chunk.content());
+    if chunk.done {
+        return Ok(Some(Generation { text: std::mem::take(text), cancelled: false }));
+    }
+    Ok(None)
+}
+
+/// Marks a generation in progress for the Ctrl-C handler until dropped
+struct GeneratingGuard;
+
+impl GeneratingGuard {
+    fn start() -> Self {
+        CANCELLED.store(false, Ordering::SeqCst);
+        GENERATING.store(true, Ordering::SeqCst);
+        GeneratingGuard
+    }
+}
+
+impl Drop for GeneratingGuard {
+    fn drop(&mut self) {
+        GENERATING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Ctrl-C cancels a streaming response; otherwise it exits as usual
+fn install_interrupt_handler() {
+    std::thread::spawn(|| {
+        let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(runtime) => runtime,
+            Err(_) => return,
+        };
+        runtime.block_on(async {
+            while tokio::signal::ctrl_c().await.is_ok() {
+                if GENERATING.load(Ordering::SeqCst) {
+                    CANCELLED.store(true, Ordering::SeqCst);
+                } else {
+                    std::process::exit(130);
+                }
+            }
+        });
+    });
+}
+
+/// Print a chunk of a response as soon as it arrives
+fn print_chunk(chunk: &str) {
+    print!("{}", chunk);
+    let _ = std::io::stdout().flush();
 }
 
 struct Bot {
@@ -70,43 +231,86 @@ struct Bot {
     redis: redis::Client,
     ollama_url: String,
     redis_prefix: String,
+    stream: bool,
 }
 
 impl Bot {
-    fn new(model: String, session: String) -> Result<Self, Box<dyn Error>> {
+    fn new(model: String, session: String, stream: bool) -> Result<Self, Box<dyn Error>> {
         let redis = redis::Client::open("redis://127.0.0.1/")?;
         
         Ok(Bot {
             model,
             session,
             redis,
-            ollama_url: "http://localhost:11434/api/generate".to_string(),
+            ollama_url: "http://localhost:11434/api/chat".to_string(),
             redis_prefix: "Bot/cli".to_string(),
+            stream,
         })
     }
 
-    fn get_session_context(&self, max_chars: usize) -> Result<String, Box<dyn Error>> {
+    fn history_key(&self) -> String {
+        format!("{}/history/{}", self.redis_prefix, self.session)
+    }
+
+    fn system_key(&self) -> String {
+        format!("{}/system/{}", self.redis_prefix, self.session)
+    }
+
+    // Where older versions appended the session transcript as text
+    fn legacy_session_key(&self) -> String {
+        format!("{}/sessions/{}", self.redis_prefix, self.session)
+    }
+
+    /// The session's messages, oldest first; a legacy text transcript is converted on first read
+    fn get_history(&self) -> Result<Vec<Message>, Box<dyn Error>> {
         let mut conn = self.redis.get_connection()?;
-        let key = format!("{}/sessions/{}", self.redis_prefix, self.session);
-        let context: Option<String> = conn.get(&key)?;
-        
-        match context {
-            Some(ctx) if ctx.len() > max_chars => {
-                // Return last N characters of context
-                Ok(format!("...{}", &ctx[ctx.len() - max_chars..]))
+        let legacy: Option<String> = conn.get(self.legacy_session_key())?;
+        if let Some(transcript) = legacy {
+            for message in parse_legacy_transcript(&transcript) {
+                let _: () = conn.rpush(self.history_key(), serde_json::to_string(&message)?)?;
             }
-            Some(ctx) => Ok(ctx),
-            None => Ok(String::new()),
+            let _: () = conn.del(self.legacy_session_key())?;
+        }
+
+        let entries: Vec<String> = conn.lrange(self.history_key(), 0, -1)?;
+        Ok(entries.iter().filter_map(|entry| serde_json::from_str(entry).ok()).collect())
+    }
+
+    fn get_system_prompt(&self) -> Result<Option<String>, Box<dyn Error>> {
+        let mut conn = self.redis.get_connection()?;
+        Ok(conn.get(self.system_key())?)
+    }
+
+    fn set_system_prompt(&self, prompt: &str) -> Result<(), Box<dyn Error>> {
+        let mut conn = self.redis.get_connection()?;
+        if prompt.trim().is_empty() {
+            let _: () = conn.del(self.system_key())?;
+        } else {
+            let _: () = conn.set(self.system_key(), prompt)?;
+        }
+        Ok(())
+    }
+
+    /// The session history as a readable transcript
+    fn format_history(&self) -> Result<String, Box<dyn Error>> {
+        let mut transcript = String::new();
+        if let Some(system) = self.get_system_prompt()? {
+            transcript.push_str(&format!("SYSTEM: {}\n", system));
+        }
+        for message in self.get_history()? {
+            let speaker = if message.role == "user" { "USER" } else { "BOT" };
+            transcript.push_str(&format!("{}: {}\n", speaker, message.content));
         }
+        Ok(transcript)
     }
 
     fn save_interaction(&self, prompt: &str, response: &str) -> Result<(), Box<dyn Error>> {
         let mut conn = self.redis.get_connection()?;
         
         // Append to session
-        let session_key = format!("{}/sessions/{}", self.redis_prefix, self.session);
-        let interaction = format!("\nUSER: {}\nBOT: {}\n---", prompt, response);
-        let _: () = conn.append(&session_key, &interaction)?;
+        for message in [Message::new("user", prompt), Message::new("assistant", response)] {
+            let _: () = conn.rpush(self.history_key(), serde_json::to_string(&message)?)?;
+        }
         
         // Log interaction
         let timestamp = chrono::Utc::now().timestamp();
@@ -123,39 +327,94 @@ impl Bot {
         Ok(())
     }
 
-    fn generate(&self, prompt: &str, include_context: bool) -> Result<String, Box<dyn Error>> {
-        let full_prompt = if include_context {
-            let context = self.get_session_context(2000)?;
-            if !context.is_empty() {
-                format!("Previous conversation:\n{}\n\nUser: {}", context, prompt)
-            } else {
-                prompt.to_string()
+    /// Generate a response, passing it to `on_chunk` piece by piece when streaming
+    /// (or whole otherwise); Ctrl-C stops a streaming response early
+    fn generate(&self, prompt: &str, include_context: bool, on_chunk: &mut dyn FnMut(&str)) -> Result<Generation, Box<dyn Error>> {
+        // Without context nothing is read from the session, so Redis isn't needed
+        let mut messages = Vec::new();
+        if include_context {
+            if let Some(system) = self.get_system_prompt()? {
+                messages.push(Message::new("system", &system));
             }
-        } else {
-            prompt.to_string()
-        };
+            let history = self.get_history()?;
+            messages.extend_from_slice(context_window(&history, CONTEXT_CHARS));
+        }
+        messages.push(Message::new("user", prompt));
 
         let request = OllamaRequest {
             model: self.model.clone(),
-            prompt: full_prompt,
-            stream: false,
+            messages,
+            stream: self.stream,
             options: OllamaOptions {
                 temperature: 0.7,
                 top_p: 0.9,
             },
         };
 
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .post(&self.ollama_url)
-            .json(&request)
-            .send()?;
-
-        if response.status().is_success() {
-            let ollama_response: OllamaResponse = response.json()?;
-            Ok(ollama_response.response)
+        // A stream lasts as long as the response, so it has no overall timeout
+        let client = if self.stream {
+            reqwest::blocking::Client::builder().timeout(None).build()?
         } else {
-            Err(format!("Ollama error: {}", response.status()).into())
+            reqwest::blocking::Client::new()
+        };
+        if !self.stream {
+            let response = client
+                .post(&self.ollama_url)
+                .json(&request)
+                .send()?;
+            if !response.status().is_success() {
+                return Err(format!("Ollama error: {}", response.status()).into());
+            }
+            let ollama_response: OllamaResponse = response.json()?;
+            if let Some(error) = ollama_response.error {
+                return Err(format!("Ollama error: {}", error).into());
+            }
+            on_chunk(ollama_response.content());
+            return Ok(Generation { text: ollama_response.content().to_string(), cancelled: false });
+        }
+
+        // The request runs on its own thread so Ctrl-C is seen even before the first token
+        let _guard = GeneratingGuard::start();
+        let (sender, lines) = mpsc::channel::<Result<String, String>>();
+        let url = self.ollama_url.clone();
+        std::thread::spawn(move || {
+            let response = match client.post(&url).json(&request).send() {
+                Ok(response) if response.status().is_success() => response,
+                Ok(response) => {
+                    let _ = sender.send(Err(format!("Ollama error: {}", response.status())));
+                    return;
+                }
+                Err(e) => {
+                    let _ = sender.send(Err(e.to_string()));
+                    return;
+                }
+            };
+            for line in BufReader::new(response).lines() {
+                let line = line.map_err(|e| e.to_string());
+                let failed = line.is_err();
+                // Once cancelled nobody receives; dropping the response closes the
+                // connection, which stops Ollama generating
+                if sender.send(line).is_err() || failed {
+                    return;
+                }
+            }
+        });
+
+        let mut text = String::new();
+        loop {
+            let event = if CANCELLED.load(Ordering::SeqCst) {
+                StreamEvent::Cancelled
+            } else {
+                match lines.recv_timeout(CANCEL_POLL) {
+                    Ok(line) => StreamEvent::Line(line?),
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) if CANCELLED.load(Ordering::SeqCst) => StreamEvent::Cancelled,
+                    Err(RecvTimeoutError::Disconnected) => StreamEvent::Closed,
+                }
+            };
+            if let Some(generation) = apply_stream_event(&mut text, event, on_chunk)? {
+                return Ok(generation);
+            }
         }
     }
 
@@ -188,7 +447,7 @@ impl Bot {
                             continue;
                         }
                         "context" => {
-                            let context = self.get_session_context(1000)?;
+                            let context = self.format_history()?;
                             if !context.is_empty() {
                                 println!("{}", "Session context:".blue());
                                 println!("{}", context);
@@ -197,15 +456,32 @@ impl Bot {
                             }
                             continue;
                         }
+                        "system" => {
+                            match self.get_system_prompt()? {
+                                Some(system) => println!("{}: {}", "System prompt".blue(), system),
+                                None => println!("No system prompt; set one with 'system <text>'"),
+                            }
+                            continue;
+                        }
+                        command if command.starts_with("system ") => {
+                            self.set_system_prompt(&command["system ".len()..])?;
+                            println!("System prompt set for session '{}'", self.session);
+                            continue;
+                        }
                         _ => {}
                     }
                     
                     // Generate response
                     print!("{}: ", "Bot".green());
-                    match self.generate(&line, true) {
-                        Ok(response) => {
-                            println!("{}\n", response);
-                            self.save_interaction(&line, &response)?;
+                    let _ = std::io::stdout().flush();
+                    match self.generate(&line, true, &mut print_chunk) {
+                        Ok(generation) if generation.cancelled => {
+                            // Partial responses stay out of the session context
+                            println!(" {}\n", "[cancelled]".yellow());
+                        }
+                        Ok(generation) => {
+                            println!("\n");
+                            self.save_interaction(&line, &generation.text)?;
                         }
                         Err(e) => {
                             println!("{}: {}\n", "Error".red(), e);
@@ -237,16 +513,19 @@ impl Bot {
         println!("  exit     - Exit the program");
         println!("  clear    - Clear screen");
         println!("  context  - Show session context");
+        println!("  system   - Show the session's system prompt; 'system <text>' sets it");
     }
 
     fn list_sessions(&self) -> Result<(), Box<dyn Error>> {
         let mut conn = self.redis.get_connection()?;
-        let pattern = format!("{}/sessions/*", self.redis_prefix);
-        let keys: Vec<String> = conn.keys(pattern)?;
+        let mut keys: Vec<String> = conn.keys(format!("{}/history/*", self.redis_prefix))?;
+        keys.extend(conn.keys::<_, Vec<String>>(format!("{}/sessions/*", self.redis_prefix))?);
+        let mut sessions: Vec<&str> = keys.iter().map(|key| key.split('/').next_back().unwrap_or(key)).collect();
+        sessions.sort();
+        sessions.dedup();
         
         println!("{}", "Active sessions:".blue());
-        for key in keys {
-            let session_id = key.split('/').last().unwrap_or(&key);
+        for session_id in sessions {
             println!("  - {}", session_id);
         }
         
@@ -255,8 +534,8 @@ impl Bot {
 
     fn clear_session(&self) -> Result<(), Box<dyn Error>> {
         let mut conn = self.redis.get_connection()?;
-        let key = format!("{}/sessions/{}", self.redis_prefix, self.session);
-        let _: () = conn.del(&key)?;
+        // The system prompt is session configuration, so it survives a clear
+        let _: () = conn.del(&[self.history_key(), self.legacy_session_key()])?;
         println!("Session '{}' cleared", self.session);
         Ok(())
     }
@@ -264,14 +543,18 @@ impl Bot {
 
 fn main() -> Result<(), Box<dyn Error>> {
     let cli = Cli::parse();
-    let bot = Bot::new(cli.model, cli.session)?;
+    let bot = Bot::new(cli.model, cli.session, !cli.no_stream)?;
+    if let Some(system) = &cli.system {
+        bot.set_system_prompt(system)?;
+    }
+    install_interrupt_handler();
 
     // Handle single prompt mode
     if let Some(prompt) = cli.prompt {
-        let response = bot.generate(&prompt, !cli.no_context)?;
-        println!("{}", response);
-        if !cli.no_context {
-            bot.save_interaction(&prompt, &response)?;
+        let generation = bot.generate(&prompt, !cli.no_context, &mut print_chunk)?;
+        println!();
+        if !cli.no_context && !generation.cancelled {
+            bot.save_interaction(&prompt, &generation.text)?;
         }
         return Ok(());
     }
@@ -280,8 +563,8 @@ fn main() -> Result<(), Box<dyn Error>> {
     if let Some(file_path) = cli.file {
         let content = std::fs::read_to_string(&file_path)?;
         let prompt = format!("File: {}\n\n{}\n\nAnalyze this file.", file_path, content);
-        let response = bot.generate(&prompt, false)?;
-        println!("{}", response);
+        bot.generate(&prompt, false, &mut print_chunk)?;
+        println!();
         return Ok(());
     }
 
@@ -297,7 +580,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             bot.clear_session()?;
         }
         Some(Commands::Context) => {
-            let context = bot.get_session_context(2000)?;
+            let context = bot.format_history()?;
             if !context.is_empty() {
                 println!("{}", context);
             } else {
@@ -307,4 +590,91 @@ fn main() -> Result<(), Box<dyn Error>> {
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(content: &str, done: bool) -> StreamEvent {
+        StreamEvent::Line(serde_json::json!({ "message": { "role": "assistant", "content": content }, "done": done }).to_string())
+    }
+
+    /// Feed events until the response is decided, collecting the printed chunks
+    fn read_stream(events: Vec<StreamEvent>) -> (Result<Generation, String>, Vec<String>) {
+        let mut text = String::new();
+        let mut printed = Vec::new();
+        for event in events {
+            match apply_stream_event(&mut text, event, &mut |chunk: &str| printed.push(chunk.to_string())) {
+                Ok(None) => continue,
+                Ok(Some(generation)) => return (Ok(generation), printed),
+                Err(e) => return (Err(e.to_string()), printed),
+            }
+        }
+        (Err("stream still open".to_string()), printed)
+    }
+
+    #[test]
+    fn test_complete_stream() {
+        let (result, printed) = read_stream(vec![chunk("Hel", false), StreamEvent::Line(String::new()), chunk("lo", true)]);
+        let generation = result.unwrap();
+        assert_eq!(generation.text, "Hello");
+        assert!(!generation.cancelled);
+        assert_eq!(printed, vec!["Hel", "lo"]);
+    }
+
+    #[test]
+    fn test_stream_ending_before_done_is_an_error() {
+        let (result, printed) = read_stream(vec![chunk("Hel", false), StreamEvent::Closed]);
+        assert!(result.unwrap_err().contains("before the response finished"));
+        assert_eq!(printed, vec!["Hel"]);
+    }
+
+    #[test]
+    fn test_cancel_before_first_token() {
+        let (result, printed) = read_stream(vec![StreamEvent::Cancelled, chunk("late", true)]);
+        let generation = result.unwrap();
+        assert!(generation.cancelled);
+        assert_eq!(generation.text, "");
+        assert!(printed.is_empty());
+    }
+
+    fn contents(messages: &[Message]) -> Vec<(&str, &str)> {
+        messages.iter().map(|m| (m.role.as_str(), m.content.as_str())).collect()
+    }
+
+    #[test]
+    fn test_context_window_keeps_latest_messages_that_fit() {
+        let history = vec![
+            Message::new("user", "aaaa"),
+            Message::new("assistant", "bbbb"),
+            Message::new("user", "cc"),
+            Message::new("assistant", "dd"),
+        ];
+        assert_eq!(context_window(&history, 100).len(), 4);
+        // "bbbb" fits too, but a reply without its question is dropped
+        assert_eq!(contents(context_window(&history, 8)), vec![("user", "cc"), ("assistant", "dd")]);
+        assert!(context_window(&history, 1).is_empty());
+        assert!(context_window(&[], 10).is_empty());
+    }
+
+    #[test]
+    fn test_parse_legacy_transcript() {
+        let transcript = "USER: hi\nBOT: hello\n---\nUSER: lost reply\n---\n---\nUSER: again\nBOT: back\nand more\n---\n";
+        assert_eq!(contents(&parse_legacy_transcript(transcript)), vec![
+            ("user", "hi"),
+            ("assistant", "hello"),
+            ("user", "again"),
+            ("assistant", "back\nand more"),
+        ]);
+        assert!(parse_legacy_transcript("").is_empty());
+        assert!(parse_legacy_transcript("USER: only a question").is_empty());
+    }
+
+    #[test]
+    fn test_ollama_error_line() {
+        let error = StreamEvent::Line(r#"{"error":"model not found"}"#.to_string());
+        let (result, _) = read_stream(vec![error]);
+        assert!(result.unwrap_err().contains("model not found"));
+    }
+}
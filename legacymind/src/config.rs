@@ -0,0 +1,235 @@
+//! Services the launcher runs, read from `legacymind.toml`.
+//!
+//! Each `[[service]]` names a command with its arguments, working directory
+//! and environment, and how it is restarted when it exits. Relative paths
+//! are resolved against the directory of the config file. Without a config
+//! file the release builds of unified-intelligence, unified-mind and
+//! obsidian-mcp in this workspace are run.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Config file looked for in the current directory
+pub const DEFAULT_CONFIG: &str = "legacymind.toml";
+
+/// When an exited service is started again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    /// Whatever the exit status
+    Always,
+    /// Only after a non-zero exit or a signal
+    #[default]
+    OnFailure,
+    Never,
+}
+
+impl RestartPolicy {
+    /// Whether a service that exited this way is restarted
+    pub fn restarts(self, success: bool) -> bool {
+        match self {
+            RestartPolicy::Always => true,
+            RestartPolicy::OnFailure => !success,
+            RestartPolicy::Never => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServiceConfig {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Working directory, default the config file's directory
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub restart: RestartPolicy,
+    /// Restarts in a row before the service is given up on; a run longer than
+    /// `stable_secs` starts the count again
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+    /// First delay before a restart, doubled for each restart in a row
+    #[serde(default = "default_backoff_secs")]
+    pub backoff_secs: u64,
+    #[serde(default = "default_stable_secs")]
+    pub stable_secs: u64,
+    /// Disabled services are listed but not started
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_max_restarts() -> u32 {
+    5
+}
+
+fn default_backoff_secs() -> u64 {
+    1
+}
+
+fn default_stable_secs() -> u64 {
+    60
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Longest delay between restarts
+pub const MAX_BACKOFF_SECS: u64 = 60;
+
+impl ServiceConfig {
+    fn new(name: &str, command: &str, cwd: &str) -> Self {
+        ServiceConfig {
+            name: name.to_string(),
+            command: command.to_string(),
+            args: Vec::new(),
+            cwd: Some(PathBuf::from(cwd)),
+            env: BTreeMap::new(),
+            restart: RestartPolicy::default(),
+            max_restarts: default_max_restarts(),
+            backoff_secs: default_backoff_secs(),
+            stable_secs: default_stable_secs(),
+            enabled: default_enabled(),
+        }
+    }
+
+    /// Delay before restart number `attempt` (1-based) in a row
+    pub fn backoff(&self, attempt: u32) -> std::time::Duration {
+        let factor = 1u64 << attempt.saturating_sub(1).min(16);
+        std::time::Duration::from_secs(self.backoff_secs.saturating_mul(factor).min(MAX_BACKOFF_SECS))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// File every service's output is appended to, besides the terminal
+    #[serde(default)]
+    pub log_file: Option<PathBuf>,
+    /// Where the running launcher keeps service states for `legacymind status`
+    #[serde(default = "default_status_file")]
+    pub status_file: PathBuf,
+    #[serde(default, rename = "service")]
+    pub services: Vec<ServiceConfig>,
+}
+
+fn default_status_file() -> PathBuf {
+    PathBuf::from(".legacymind/status.json")
+}
+
+impl Config {
+    /// The three MCP servers of this workspace, as built by `cargo build --release`
+    pub fn workspace() -> Self {
+        Config {
+            log_file: None,
+            status_file: default_status_file(),
+            services: vec![
+                ServiceConfig::new("unified-intelligence", "target/release/unified-intelligence", "unified-intelligence"),
+                ServiceConfig::new("unified-mind", "target/release/unified-mind", "unified-mind"),
+                ServiceConfig::new("obsidian-mcp", "target/release/obsidian-mcp", "obsidian-mcp"),
+            ],
+        }
+    }
+
+    /// Read a config file, or the workspace defaults when `path` is the default and missing
+    pub fn load(path: &Path, explicit: bool) -> Result<Self, String> {
+        let base = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let config = match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && !explicit => Self::workspace(),
+            Err(e) => return Err(format!("Cannot read {}: {}", path.display(), e)),
+        };
+        Ok(config.resolve(base))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let config: Config = toml::from_str(text).map_err(|e| e.to_string())?;
+        let mut names = std::collections::BTreeSet::new();
+        for service in &config.services {
+            if service.name.trim().is_empty() || service.command.trim().is_empty() {
+                return Err("every service needs a name and a command".to_string());
+            }
+            if !names.insert(service.name.as_str()) {
+                return Err(format!("service '{}' is defined twice", service.name));
+            }
+        }
+        Ok(config)
+    }
+
+    /// Paths relative to `base`; commands only when they name a path rather than a program on PATH
+    fn resolve(mut self, base: &Path) -> Self {
+        let join = |path: &Path| if path.is_relative() { base.join(path) } else { path.to_path_buf() };
+        self.log_file = self.log_file.as_deref().map(join);
+        self.status_file = join(&self.status_file);
+        for service in &mut self.services {
+            let cwd = join(service.cwd.as_deref().unwrap_or(Path::new("")));
+            if service.command.contains('/') && Path::new(&service.command).is_relative() {
+                service.command = cwd.join(&service.command).display().to_string();
+            }
+            service.cwd = Some(cwd);
+        }
+        self
+    }
+
+    /// Services to start: the enabled ones, or those named in `only`
+    pub fn selected(&self, only: &[String]) -> Result<Vec<ServiceConfig>, String> {
+        if let Some(unknown) = only.iter().find(|name| !self.services.iter().any(|s| &s.name == *name)) {
+            return Err(format!("no service named '{}'", unknown));
+        }
+        Ok(self.services.iter()
+            .filter(|s| if only.is_empty() { s.enabled } else { only.contains(&s.name) })
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_resolve() {
+        let config = Config::parse(r#"
+            log_file = "logs/stack.log"
+
+            [[service]]
+            name = "ui"
+            command = "target/release/unified-intelligence"
+            cwd = "unified-intelligence"
+            env = { INSTANCE_ID = "CC" }
+            restart = "always"
+
+            [[service]]
+            name = "mind"
+            command = "unified-mind"
+            enabled = false
+        "#).unwrap().resolve(Path::new("/stack"));
+
+        assert_eq!(config.log_file, Some(PathBuf::from("/stack/logs/stack.log")));
+        assert_eq!(config.status_file, PathBuf::from("/stack/.legacymind/status.json"));
+        let ui = &config.services[0];
+        assert_eq!(ui.command, "/stack/unified-intelligence/target/release/unified-intelligence");
+        assert_eq!((ui.restart, ui.max_restarts, ui.env["INSTANCE_ID"].as_str()), (RestartPolicy::Always, 5, "CC"));
+        assert_eq!(config.services[1].command, "unified-mind");
+
+        assert_eq!(config.selected(&[]).unwrap().len(), 1);
+        assert_eq!(config.selected(&["mind".to_string()]).unwrap()[0].name, "mind");
+        assert!(config.selected(&["other".to_string()]).is_err());
+        assert!(Config::parse("[[service]]\nname = \"a\"\ncommand = \"x\"\n[[service]]\nname = \"a\"\ncommand = \"y\"").is_err());
+    }
+
+    #[test]
+    fn test_restart_policy_and_backoff() {
+        assert!(RestartPolicy::OnFailure.restarts(false) && !RestartPolicy::OnFailure.restarts(true));
+        assert!(RestartPolicy::Always.restarts(true) && !RestartPolicy::Never.restarts(false));
+        let service = ServiceConfig { backoff_secs: 2, ..ServiceConfig::new("a", "a", ".") };
+        let delays: Vec<u64> = (1..=7).map(|attempt| service.backoff(attempt).as_secs()).collect();
+        assert_eq!(delays, vec![2, 4, 8, 16, 32, 60, 60]);
+    }
+}
@@ -0,0 +1,72 @@
+mod config;
+mod status;
+mod supervisor;
+
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use config::{Config, DEFAULT_CONFIG};
+
+#[derive(Parser)]
+#[command(name = "legacymind")]
+#[command(about = "Run the LegacyMind MCP servers with one command", long_about = None)]
+struct Cli {
+    /// Config file (default: legacymind.toml, or the workspace's three servers when it doesn't exist)
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Start the services and supervise them until Ctrl-C (default)
+    Up {
+        /// Only these services, whether enabled or not
+        services: Vec<String>,
+    },
+    /// Show the state of each service of the running launcher
+    Status {
+        /// Print the status file as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    if let Err(e) = run(cli).await {
+        eprintln!("legacymind: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), String> {
+    let config = Config::load(cli.config.as_deref().unwrap_or(DEFAULT_CONFIG.as_ref()), cli.config.is_some())?;
+    match cli.command.unwrap_or(Commands::Up { services: Vec::new() }) {
+        Commands::Up { services } => {
+            if let Some(running) = status::read(&config.status_file)?.filter(|s| s.stopped_at.is_none() && status::alive(s.launcher_pid)) {
+                return Err(format!(
+                    "a launcher (pid {}) is already running with {}; see `legacymind status`",
+                    running.launcher_pid,
+                    config.status_file.display()
+                ));
+            }
+            supervisor::run(&config, config.selected(&services)?).await
+        }
+        Commands::Status { json } => {
+            let Some(status) = status::read(&config.status_file)? else {
+                println!("Not running (no {})", config.status_file.display());
+                return Ok(());
+            };
+            if json {
+                println!("{}", serde_json::to_string_pretty(&status).map_err(|e| e.to_string())?);
+            } else {
+                status::print(&status);
+            }
+            Ok(())
+        }
+    }
+}
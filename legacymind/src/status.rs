@@ -0,0 +1,206 @@
+//! Service states kept by the running launcher for `legacymind status`.
+//!
+//! The launcher rewrites the status file whenever a service changes state,
+//! so another terminal can read it without talking to the launcher. A file
+//! left behind by a launcher that is gone is reported as such.
+
+use chrono::{DateTime, Utc};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum State {
+    Starting,
+    Running,
+    /// Waiting to be restarted
+    Backoff,
+    /// Exited and not restarted by its policy
+    Exited,
+    /// Gave up after too many restarts in a row
+    Failed,
+    /// Stopped by the launcher
+    Stopped,
+}
+
+impl State {
+    fn label(self) -> ColoredString {
+        match self {
+            State::Starting => "starting".yellow(),
+            State::Running => "running".green(),
+            State::Backoff => "backoff".yellow(),
+            State::Exited => "exited".normal(),
+            State::Failed => "failed".red(),
+            State::Stopped => "stopped".normal(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceStatus {
+    pub name: String,
+    pub state: State,
+    pub pid: Option<u32>,
+    pub started_at: Option<String>,
+    pub restarts: u32,
+    pub last_exit: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Status {
+    pub launcher_pid: u32,
+    pub started_at: String,
+    pub updated_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stopped_at: Option<String>,
+    pub services: Vec<ServiceStatus>,
+}
+
+/// Shared status of a running launcher, written through to its status file
+#[derive(Clone)]
+pub struct Tracker {
+    status: Arc<Mutex<Status>>,
+    path: PathBuf,
+}
+
+impl Tracker {
+    pub fn new(path: PathBuf, names: &[String]) -> Self {
+        let now = Utc::now().to_rfc3339();
+        let services = names.iter().map(|name| ServiceStatus {
+            name: name.clone(),
+            state: State::Starting,
+            pid: None,
+            started_at: None,
+            restarts: 0,
+            last_exit: None,
+        }).collect();
+        let status = Status { launcher_pid: std::process::id(), started_at: now.clone(), updated_at: now, stopped_at: None, services };
+        let tracker = Tracker { status: Arc::new(Mutex::new(status)), path };
+        tracker.write();
+        tracker
+    }
+
+    /// Change one service's status and rewrite the file
+    pub fn update(&self, name: &str, change: impl FnOnce(&mut ServiceStatus)) {
+        {
+            let mut status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(service) = status.services.iter_mut().find(|s| s.name == name) {
+                change(service);
+            }
+            status.updated_at = Utc::now().to_rfc3339();
+        }
+        self.write();
+    }
+
+    /// Record that the launcher stopped, after its services did
+    pub fn finish(&self) {
+        {
+            let mut status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Utc::now().to_rfc3339();
+            status.stopped_at = Some(now.clone());
+            status.updated_at = now;
+        }
+        self.write();
+    }
+
+    /// Replace the file in one step so readers never see half of it
+    fn write(&self) {
+        let json = {
+            let status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+            serde_json::to_string_pretty(&*status).unwrap_or_default()
+        };
+        let result = self.path.parent().map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(self.path.with_extension("tmp"), json))
+            .and_then(|_| std::fs::rename(self.path.with_extension("tmp"), &self.path));
+        if let Err(e) = result {
+            eprintln!("Cannot write {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Status left in a status file, if any
+pub fn read(path: &Path) -> Result<Option<Status>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json).map(Some).map_err(|e| format!("{}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Cannot read {}: {}", path.display(), e)),
+    }
+}
+
+/// Whether a process is still running
+pub fn alive(pid: u32) -> bool {
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// How long ago an RFC 3339 timestamp was, as `3h12m`
+fn since(timestamp: &str, now: DateTime<Utc>) -> String {
+    let Ok(then) = DateTime::parse_from_rfc3339(timestamp) else {
+        return "-".to_string();
+    };
+    let secs = (now - then.with_timezone(&Utc)).num_seconds().max(0);
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m{}s", s / 60, s % 60),
+        s if s < 86400 => format!("{}h{}m", s / 3600, s % 3600 / 60),
+        s => format!("{}d{}h", s / 86400, s % 86400 / 3600),
+    }
+}
+
+/// Print a status as a table
+pub fn print(status: &Status) {
+    let now = Utc::now();
+    if let Some(stopped_at) = &status.stopped_at {
+        println!("{} (launcher stopped {} ago)", "Not running".red(), since(stopped_at, now));
+    } else if alive(status.launcher_pid) {
+        println!("Launcher pid {} up {}", status.launcher_pid, since(&status.started_at, now));
+    } else {
+        println!("{} (launcher pid {} is gone; states as of {} ago)", "Not running".red(), status.launcher_pid, since(&status.updated_at, now));
+    }
+    let width = status.services.iter().map(|s| s.name.len()).max().unwrap_or(0).max(7);
+    println!("{:width$}  {:8}  {:>7}  {:>7}  {:>8}  LAST EXIT", "SERVICE", "STATE", "PID", "UPTIME", "RESTARTS", width = width);
+    for service in &status.services {
+        let uptime = match (service.state, &service.started_at) {
+            (State::Running, Some(started_at)) => since(started_at, now),
+            _ => "-".to_string(),
+        };
+        println!(
+            "{:width$}  {:8}  {:>7}  {:>7}  {:>8}  {}",
+            service.name,
+            service.state.label(),
+            service.pid.map_or("-".to_string(), |pid| pid.to_string()),
+            uptime,
+            service.restarts,
+            service.last_exit.as_deref().unwrap_or("-"),
+            width = width,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_writes_status() {
+        let path = std::env::temp_dir().join(format!("legacymind-status-{}", std::process::id())).join("status.json");
+        let tracker = Tracker::new(path.clone(), &["ui".to_string(), "mind".to_string()]);
+        tracker.update("ui", |s| {
+            s.state = State::Running;
+            s.pid = Some(42);
+        });
+        let status = read(&path).unwrap().unwrap();
+        assert_eq!((status.services[0].state, status.services[0].pid), (State::Running, Some(42)));
+        assert_eq!(status.services[1].state, State::Starting);
+        assert!(alive(status.launcher_pid));
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        let now = Utc::now();
+        assert_eq!(since(&(now - chrono::Duration::seconds(3725)).to_rfc3339(), now), "1h2m");
+    }
+}
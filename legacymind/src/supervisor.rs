@@ -0,0 +1,229 @@
+//! Starting, watching and restarting the services.
+//!
+//! Every service runs as a child process with its output forwarded line by
+//! line, prefixed with its name, to the terminal and the optional log file.
+//! The MCP servers speak over stdio and exit when their stdin closes, so each
+//! child gets a stdin pipe that is held open while it runs; closing it is
+//! how a child is asked to stop on shutdown, before it is killed after
+//! `STOP_GRACE`. A child that exits is restarted according to its policy,
+//! after a delay that doubles with each restart in a row.
+
+use colored::*;
+use std::io::Write;
+use std::process::{ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::{mpsc, watch};
+
+use crate::config::{Config, ServiceConfig};
+use crate::status::{State, Tracker};
+
+/// Time a child has to exit after its stdin is closed
+const STOP_GRACE: Duration = Duration::from_secs(5);
+
+/// Colours service names cycle through
+const COLORS: &[Color] = &[Color::Cyan, Color::Magenta, Color::Blue, Color::Green, Color::Yellow];
+
+/// A line of output from a service, or from the launcher about it
+struct LogLine {
+    service: String,
+    line: String,
+}
+
+/// Start the services and supervise them until Ctrl-C or SIGTERM
+pub async fn run(config: &Config, services: Vec<ServiceConfig>) -> Result<(), String> {
+    if services.is_empty() {
+        return Err("no services to start".to_string());
+    }
+    let names: Vec<String> = services.iter().map(|s| s.name.clone()).collect();
+    let tracker = Tracker::new(config.status_file.clone(), &names);
+    let (log, lines) = mpsc::unbounded_channel();
+    let logger = tokio::spawn(write_logs(lines, names.clone(), config.log_file.clone()));
+    let (stop, stopping) = watch::channel(false);
+
+    let mut tasks = Vec::new();
+    for service in services {
+        tasks.push(tokio::spawn(supervise(service, tracker.clone(), log.clone(), stopping.clone())));
+    }
+    println!("Supervising {} (status: {})", names.join(", "), config.status_file.display());
+
+    shutdown_signal().await;
+    let _ = log.send(LogLine { service: "legacymind".to_string(), line: "Stopping services".to_string() });
+    let _ = stop.send(true);
+    for task in tasks {
+        let _ = task.await;
+    }
+    drop(log);
+    let _ = logger.await;
+    tracker.finish();
+    Ok(())
+}
+
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to listen for SIGTERM");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+fn spawn(service: &ServiceConfig) -> std::io::Result<Child> {
+    let mut command = Command::new(&service.command);
+    command.args(&service.args)
+        .envs(&service.env)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(cwd) = &service.cwd {
+        command.current_dir(cwd);
+    }
+    command.spawn()
+}
+
+/// Forward a child's output stream as log lines
+fn forward(name: &str, stream: Option<impl AsyncRead + Unpin + Send + 'static>, log: &mpsc::UnboundedSender<LogLine>) {
+    let Some(stream) = stream else {
+        return;
+    };
+    let (service, log) = (name.to_string(), log.clone());
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stream).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if log.send(LogLine { service: service.clone(), line }).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// How a child exited, as `exit code 1` or `signal 9`
+fn describe(status: &ExitStatus) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return format!("signal {}", signal);
+        }
+    }
+    status.code().map_or_else(|| "unknown".to_string(), |code| format!("exit code {}", code))
+}
+
+/// Run one service until shutdown, or until its policy stops restarting it
+async fn supervise(service: ServiceConfig, tracker: Tracker, log: mpsc::UnboundedSender<LogLine>, mut stopping: watch::Receiver<bool>) {
+    let name = service.name.clone();
+    let note = |line: String| {
+        let _ = log.send(LogLine { service: name.clone(), line: format!("[legacymind] {}", line) });
+    };
+    let mut in_a_row = 0;
+    let mut restarts = 0;
+    loop {
+        if *stopping.borrow() {
+            tracker.update(&name, |s| s.state = State::Stopped);
+            return;
+        }
+        let started = Instant::now();
+        let (success, exit) = match spawn(&service) {
+            Ok(mut child) => {
+                let pid = child.id();
+                tracker.update(&name, |s| {
+                    s.state = State::Running;
+                    s.pid = pid;
+                    s.started_at = Some(chrono::Utc::now().to_rfc3339());
+                });
+                note(format!("started (pid {})", pid.map_or("?".to_string(), |pid| pid.to_string())));
+                forward(&name, child.stdout.take(), &log);
+                forward(&name, child.stderr.take(), &log);
+                let stdin = child.stdin.take();
+
+                tokio::select! {
+                    exited = child.wait() => match exited {
+                        Ok(status) => (status.success(), describe(&status)),
+                        Err(e) => (false, format!("wait failed: {}", e)),
+                    },
+                    _ = stopping.changed() => {
+                        drop(stdin);
+                        if tokio::time::timeout(STOP_GRACE, child.wait()).await.is_err() {
+                            note(format!("still running {}s after stdin closed; killing", STOP_GRACE.as_secs()));
+                            let _ = child.kill().await;
+                        }
+                        tracker.update(&name, |s| {
+                            s.state = State::Stopped;
+                            s.pid = None;
+                        });
+                        note("stopped".to_string());
+                        return;
+                    }
+                }
+            }
+            Err(e) => (false, format!("failed to start {}: {}", service.command, e)),
+        };
+
+        if started.elapsed() >= Duration::from_secs(service.stable_secs) {
+            in_a_row = 0;
+        }
+        let wants_restart = service.restart.restarts(success);
+        let restarting = wants_restart && in_a_row < service.max_restarts;
+        if restarting {
+            in_a_row += 1;
+            restarts += 1;
+        }
+        let state = if restarting {
+            State::Backoff
+        } else if wants_restart {
+            State::Failed
+        } else {
+            State::Exited
+        };
+        tracker.update(&name, |s| {
+            s.state = state;
+            s.pid = None;
+            s.restarts = restarts;
+            s.last_exit = Some(exit.clone());
+        });
+        match state {
+            State::Failed => {
+                note(format!("{}; gave up after {} restarts in a row", exit, in_a_row));
+                return;
+            }
+            State::Exited => {
+                note(format!("{}; not restarted ({:?} policy)", exit, service.restart));
+                return;
+            }
+            _ => {}
+        }
+
+        let delay = service.backoff(in_a_row);
+        note(format!("{}; restarting in {}s", exit, delay.as_secs()));
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = stopping.changed() => {}
+        }
+    }
+}
+
+/// Print log lines with a coloured service prefix, and append them to the log file
+async fn write_logs(mut lines: mpsc::UnboundedReceiver<LogLine>, names: Vec<String>, log_file: Option<std::path::PathBuf>) {
+    let width = names.iter().map(String::len).max().unwrap_or(0).max("legacymind".len());
+    let mut file = log_file.and_then(|path| {
+        path.parent().map(std::fs::create_dir_all);
+        std::fs::OpenOptions::new().create(true).append(true).open(&path)
+            .map_err(|e| eprintln!("Cannot open log file {}: {}", path.display(), e))
+            .ok()
+    });
+    while let Some(LogLine { service, line }) = lines.recv().await {
+        let time = chrono::Local::now().format("%H:%M:%S");
+        let color = names.iter().position(|n| *n == service).map_or(Color::White, |i| COLORS[i % COLORS.len()]);
+        println!("{} {} | {}", time, format!("{:width$}", service, width = width).color(color), line);
+        if let Some(file) = file.as_mut() {
+            let _ = writeln!(file, "{} {:width$} | {}", chrono::Local::now().to_rfc3339(), service, line, width = width);
+        }
+    }
+}
@@ -29,6 +29,10 @@ pub struct VaultConfig {
     pub enable_watching: bool,
     /// Whether to parse and include wikilinks in search results
     pub enable_wikilinks: bool,
+    /// Vault-relative folder whose notes are stored AES-256-GCM encrypted
+    /// (key read from OBSIDIAN_VAULT_KEY)
+    #[serde(default)]
+    pub encrypted_folder: Option<String>,
 }
 
 /// Redis configuration for Federation integration
@@ -76,6 +80,7 @@ impl Default for VaultConfig {
             max_file_size: 10 * 1024 * 1024, // 10MB
             enable_watching: false,
             enable_wikilinks: true,
+            encrypted_folder: None,
         }
     }
 }
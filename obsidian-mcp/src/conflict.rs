@@ -0,0 +1,126 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// One line of a line-based diff
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct DiffLine {
+    /// "equal", "delete" (only in current) or "insert" (only in proposed)
+    pub op: String,
+    /// Line text without the trailing newline
+    pub text: String,
+}
+
+/// Details returned when an optimistic write loses a race.
+///
+/// The client already holds the base version it read (identified by
+/// `expected_hash`), so together with `current_content` and `proposed_content`
+/// it has all three sides needed for a merge.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct WriteConflict {
+    /// Vault-relative path of the note
+    pub path: String,
+    /// Hash the client expected the note to have
+    pub expected_hash: String,
+    /// Hash the note actually has (None if it no longer exists)
+    pub current_hash: Option<String>,
+    /// Current note content ("theirs")
+    pub current_content: Option<String>,
+    /// Content the client tried to write ("ours"; None for deletes)
+    pub proposed_content: Option<String>,
+    /// Line diff from current to proposed content
+    pub diff: Vec<DiffLine>,
+}
+
+/// Most LCS table cells a diff may use (about 32 MB); larger changed regions
+/// are reported as a plain replacement
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Line-based diff using the longest common subsequence.
+///
+/// Unchanged leading and trailing lines are matched first, so only the changed
+/// middle needs the quadratic table; when that is still too large it is
+/// reported as all-delete then all-insert.
+pub fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let line = |op: &str, text: &str| DiffLine { op: op.to_string(), text: text.to_string() };
+
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut diff: Vec<DiffLine> = a[..prefix].iter().map(|t| line("equal", t)).collect();
+    if (a_mid.len() + 1).saturating_mul(b_mid.len() + 1) > MAX_DIFF_CELLS {
+        diff.extend(a_mid.iter().map(|t| line("delete", t)));
+        diff.extend(b_mid.iter().map(|t| line("insert", t)));
+    } else {
+        diff.extend(lcs_diff(a_mid, b_mid));
+    }
+    diff.extend(a[a.len() - suffix..].iter().map(|t| line("equal", t)));
+    diff
+}
+
+/// LCS diff of two line slices
+fn lcs_diff(a: &[&str], b: &[&str]) -> Vec<DiffLine> {
+    // lcs[i][j] = LCS length of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let line = |op: &str, text: &str| DiffLine { op: op.to_string(), text: text.to_string() };
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            diff.push(line("equal", a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(line("delete", a[i]));
+            i += 1;
+        } else {
+            diff.push(line("insert", b[j]));
+            j += 1;
+        }
+    }
+    diff.extend(a[i..].iter().map(|t| line("delete", t)));
+    diff.extend(b[j..].iter().map(|t| line("insert", t)));
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_diff() {
+        let diff = line_diff("a\nb\nc", "a\nx\nc\nd");
+        let ops: Vec<(&str, &str)> = diff.iter().map(|d| (d.op.as_str(), d.text.as_str())).collect();
+        assert_eq!(ops, vec![
+            ("equal", "a"),
+            ("delete", "b"),
+            ("insert", "x"),
+            ("equal", "c"),
+            ("insert", "d"),
+        ]);
+    }
+
+    #[test]
+    fn test_large_line_diff_falls_back_to_replacement() {
+        let old: String = (0..3000).map(|i| format!("old {}\n", i)).collect();
+        let new: String = (0..3000).map(|i| format!("new {}\n", i)).collect();
+        let diff = line_diff(&format!("head\n{}tail", old), &format!("head\n{}tail", new));
+        assert_eq!(diff.len(), 6002);
+        assert_eq!((diff[0].op.as_str(), diff[0].text.as_str()), ("equal", "head"));
+        assert!(diff[1..3001].iter().all(|d| d.op == "delete"));
+        assert!(diff[3001..6001].iter().all(|d| d.op == "insert"));
+        assert_eq!((diff[6001].op.as_str(), diff[6001].text.as_str()), ("equal", "tail"));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::error::{ObsidianMcpError, ObsidianResult};
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+
+/// Header written at the top of every encrypted note so other tools see an opaque blob
+pub const ENCRYPTED_NOTE_HEADER: &str = "%%obsidian-mcp:aes-256-gcm%%";
+
+/// Environment variable holding the base64-encoded 32-byte vault key
+pub const VAULT_KEY_ENV: &str = "OBSIDIAN_VAULT_KEY";
+
+const NONCE_LEN: usize = 12;
+
+/// AES-256-GCM cipher for notes stored in the encrypted vault folder
+pub struct VaultCipher {
+    cipher: Aes256Gcm,
+}
+
+impl VaultCipher {
+    /// Create a cipher from a raw 32-byte key
+    pub fn new(key: &[u8]) -> ObsidianResult<Self> {
+        if key.len() != 32 {
+            return Err(ObsidianMcpError::Encryption {
+                reason: format!("Vault key must be 32 bytes, got {}", key.len()),
+            });
+        }
+        Ok(Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+        })
+    }
+
+    /// Load the cipher from OBSIDIAN_VAULT_KEY, if set
+    pub fn from_env() -> ObsidianResult<Option<Self>> {
+        let Ok(encoded) = std::env::var(VAULT_KEY_ENV) else {
+            return Ok(None);
+        };
+        let key = BASE64.decode(encoded.trim()).map_err(|e| ObsidianMcpError::Encryption {
+            reason: format!("{} is not valid base64: {}", VAULT_KEY_ENV, e),
+        })?;
+        Self::new(&key).map(Some)
+    }
+
+    /// Check whether file content is an encrypted note
+    pub fn is_encrypted(content: &str) -> bool {
+        content.starts_with(ENCRYPTED_NOTE_HEADER)
+    }
+
+    /// Encrypt note content into the on-disk format (header line + base64(nonce || ciphertext))
+    pub fn encrypt(&self, plaintext: &str) -> ObsidianResult<String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self.cipher.encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|e| ObsidianMcpError::Encryption { reason: e.to_string() })?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+        Ok(format!("{}\n{}\n", ENCRYPTED_NOTE_HEADER, BASE64.encode(payload)))
+    }
+
+    /// Decrypt note content previously produced by `encrypt`
+    pub fn decrypt(&self, content: &str) -> ObsidianResult<String> {
+        let body = content.strip_prefix(ENCRYPTED_NOTE_HEADER)
+            .ok_or_else(|| ObsidianMcpError::Encryption {
+                reason: "Missing encrypted note header".to_string(),
+            })?
+            .trim();
+        let payload = BASE64.decode(body).map_err(|e| ObsidianMcpError::Encryption {
+            reason: format!("Corrupt encrypted note: {}", e),
+        })?;
+        if payload.len() < NONCE_LEN {
+            return Err(ObsidianMcpError::Encryption {
+                reason: "Corrupt encrypted note: payload too short".to_string(),
+            });
+        }
+
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+        let plaintext = self.cipher.decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| ObsidianMcpError::Encryption {
+                reason: "Decryption failed (wrong key or tampered note)".to_string(),
+            })?;
+
+        String::from_utf8(plaintext).map_err(|e| ObsidianMcpError::Encryption {
+            reason: format!("Decrypted note is not UTF-8: {}", e),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let cipher = VaultCipher::new(&[3u8; 32]).unwrap();
+        let encrypted = cipher.encrypt("# Private\n\nsecret notes").unwrap();
+
+        assert!(VaultCipher::is_encrypted(&encrypted));
+        assert!(!encrypted.contains("secret"));
+        assert_eq!(cipher.decrypt(&encrypted).unwrap(), "# Private\n\nsecret notes");
+    }
+
+    #[test]
+    fn test_wrong_key_fails() {
+        let encrypted = VaultCipher::new(&[1u8; 32]).unwrap().encrypt("data").unwrap();
+        let other = VaultCipher::new(&[2u8; 32]).unwrap();
+
+        assert!(other.decrypt(&encrypted).is_err());
+    }
+
+    #[test]
+    fn test_rejects_short_key() {
+        assert!(VaultCipher::new(&[0u8; 16]).is_err());
+    }
+}
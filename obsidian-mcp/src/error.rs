@@ -35,6 +35,12 @@ pub enum ObsidianMcpError {
     
     #[error("Search failed: {query}")]
     SearchFailed { query: String },
+    
+    #[error("Encryption error: {reason}")]
+    Encryption { reason: String },
+
+    #[error("Write conflict on {}: note changed since it was read", .0.path)]
+    Conflict(Box<crate::conflict::WriteConflict>),
 }
 
 /// Convert ObsidianMcpError to MCP-compatible ErrorData
@@ -52,6 +58,9 @@ impl From<ObsidianMcpError> for rmcp::model::ErrorData {
             ObsidianMcpError::VaultAccessDenied { .. } => {
                 rmcp::model::ErrorData::invalid_request(err.to_string(), None)
             }
+            ObsidianMcpError::Conflict(ref conflict) => {
+                rmcp::model::ErrorData::invalid_request(err.to_string(), serde_json::to_value(conflict).ok())
+            }
             _ => rmcp::model::ErrorData::internal_error(err.to_string(), None),
         }
     }
@@ -0,0 +1,139 @@
+use crate::error::{ObsidianMcpError, ObsidianResult};
+use serde_json::Value;
+use serde_yaml::Mapping;
+
+/// Split a note into its YAML frontmatter (without the `---` lines) and the
+/// rest of the note, untouched. None when the note has no frontmatter block.
+pub fn split(content: &str) -> Option<(&str, &str)> {
+    let rest = content.strip_prefix("---\n").or_else(|| content.strip_prefix("---\r\n"))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Frontmatter fields of a note, in file order, and the rest of the note.
+/// A note without frontmatter has no fields; invalid YAML is an error so
+/// nothing is written over it.
+pub fn parse(content: &str) -> ObsidianResult<(Mapping, &str)> {
+    let Some((yaml, body)) = split(content) else {
+        return Ok((Mapping::new(), content));
+    };
+    if yaml.trim().is_empty() {
+        return Ok((Mapping::new(), body));
+    }
+    match serde_yaml::from_str::<serde_yaml::Value>(yaml) {
+        Ok(serde_yaml::Value::Mapping(fields)) => Ok((fields, body)),
+        Ok(serde_yaml::Value::Null) => Ok((Mapping::new(), body)),
+        Ok(_) => Err(ObsidianMcpError::InvalidMarkdown {
+            reason: "frontmatter is not a set of key: value fields".to_string(),
+        }),
+        Err(e) => Err(ObsidianMcpError::InvalidMarkdown {
+            reason: format!("invalid frontmatter YAML: {}", e),
+        }),
+    }
+}
+
+/// Frontmatter fields as JSON; values JSON can't hold (tagged YAML) become strings
+pub fn to_json(fields: &Mapping) -> serde_json::Map<String, Value> {
+    fields.iter()
+        .filter_map(|(key, value)| {
+            let key = match key {
+                serde_yaml::Value::String(key) => key.clone(),
+                other => serde_yaml::to_string(other).ok()?.trim().to_string(),
+            };
+            let value = serde_json::to_value(value)
+                .unwrap_or_else(|_| Value::String(serde_yaml::to_string(value).unwrap_or_default().trim().to_string()));
+            Some((key, value))
+        })
+        .collect()
+}
+
+/// The note with one frontmatter field set, or removed when `value` is None.
+/// Other fields keep their order and the rest of the note is left as is;
+/// YAML comments in the frontmatter are not preserved.
+pub fn set_field(content: &str, field: &str, value: Option<&Value>) -> ObsidianResult<String> {
+    if field.trim().is_empty() {
+        return Err(ObsidianMcpError::InvalidMarkdown {
+            reason: "frontmatter field name is empty".to_string(),
+        });
+    }
+    let (mut fields, body) = parse(content)?;
+    let key = serde_yaml::Value::String(field.to_string());
+    match value {
+        Some(value) => {
+            let value = serde_yaml::to_value(value).map_err(|e| ObsidianMcpError::InvalidMarkdown {
+                reason: format!("value of {} can't be written as YAML: {}", field, e),
+            })?;
+            fields.insert(key, value);
+        }
+        None => {
+            fields.remove(&key);
+        }
+    }
+    compose(&fields, body)
+}
+
+/// A note from frontmatter fields and the rest of the note; no block when there are no fields
+pub fn compose(fields: &Mapping, body: &str) -> ObsidianResult<String> {
+    if fields.is_empty() {
+        return Ok(body.to_string());
+    }
+    let yaml = serde_yaml::to_string(fields).map_err(|e| ObsidianMcpError::InvalidMarkdown {
+        reason: format!("Failed to serialize frontmatter to YAML: {}", e),
+    })?;
+    Ok(format!("---\n{}---\n{}", yaml, body))
+}
+
+/// Whether a frontmatter value satisfies a query value: equal (strings
+/// ignoring case, numbers by value), one of a list field's items, or, for a
+/// list in the query, matching any of its items
+pub fn matches(actual: &Value, wanted: &Value) -> bool {
+    match (actual, wanted) {
+        (_, Value::Array(options)) if !actual.is_array() => options.iter().any(|option| matches(actual, option)),
+        (Value::Array(items), _) if !wanted.is_array() => items.iter().any(|item| matches(item, wanted)),
+        (Value::String(a), Value::String(b)) => a.eq_ignore_ascii_case(b),
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        // Unquoted YAML like `year: 2024` asked for as "2024"
+        (Value::Number(a), Value::String(b)) | (Value::String(b), Value::Number(a)) => a.to_string() == b.trim(),
+        (Value::Bool(a), Value::String(b)) | (Value::String(b), Value::Bool(a)) => a.to_string().eq_ignore_ascii_case(b.trim()),
+        _ => actual == wanted,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const NOTE: &str = "---\ntitle: Plan\nstatus: draft\ntags: [work, q3]\n---\n# Plan\n\n---\n\nBody stays\n";
+
+    #[test]
+    fn test_set_field_keeps_order_and_body() {
+        let updated = set_field(NOTE, "status", Some(&json!("active"))).unwrap();
+        assert_eq!(updated, "---\ntitle: Plan\nstatus: active\ntags:\n- work\n- q3\n---\n# Plan\n\n---\n\nBody stays\n");
+
+        let added = set_field("# No frontmatter\n", "due", Some(&json!("2024-05-01"))).unwrap();
+        assert_eq!(added, "---\ndue: 2024-05-01\n---\n# No frontmatter\n");
+        assert_eq!(set_field(&added, "due", None).unwrap(), "# No frontmatter\n");
+
+        assert!(set_field("---\n: [broken\n---\nText", "status", Some(&json!("x"))).is_err());
+        assert!(split("---\nunclosed: true\n").is_none());
+    }
+
+    #[test]
+    fn test_matches() {
+        let (fields, _) = parse(NOTE).unwrap();
+        let fields = to_json(&fields);
+        assert!(matches(&fields["status"], &json!("Draft")));
+        assert!(matches(&fields["tags"], &json!("q3")));
+        assert!(matches(&fields["status"], &json!(["active", "draft"])));
+        assert!(!matches(&fields["status"], &json!("active")));
+        assert!(matches(&json!(2024), &json!("2024")));
+        assert!(matches(&json!(true), &json!(true)));
+    }
+}
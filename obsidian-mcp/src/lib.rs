@@ -1,14 +1,25 @@
 // Library exports for testing
 pub mod config;
+pub mod conflict;
+pub mod crypto;
 pub mod error;
+pub mod frontmatter;
+pub mod markdown;
 pub mod models;
 pub mod service;
+pub mod snapshot;
+pub mod template;
 pub mod vault;
 pub mod wikilink;
 
 pub use config::*;
+pub use conflict::*;
+pub use crypto::*;
 pub use error::*;
+pub use markdown::*;
 pub use models::*;
 pub use service::*;
+pub use snapshot::*;
+pub use template::*;
 pub use vault::*;
 pub use wikilink::*;
\ No newline at end of file
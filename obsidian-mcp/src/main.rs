@@ -4,9 +4,15 @@ use tracing_subscriber;
 
 mod models;
 mod error;
+mod frontmatter;
+mod markdown;
 mod config;
+mod conflict;
+mod crypto;
 mod vault;
 mod service;
+mod snapshot;
+mod template;
 mod wikilink;
 
 use crate::service::ObsidianMcpService;
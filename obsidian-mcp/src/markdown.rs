@@ -0,0 +1,344 @@
+use crate::error::{ObsidianMcpError, ObsidianResult};
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::ops::Range;
+
+/// Due-date annotations: Tasks plugin (`📅 2024-05-01`), Dataview (`[due:: 2024-05-01]`) and `due:2024-05-01`
+static DUE_DATE_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:📅\s*|\[due::\s*|\bdue:\s*)(\d{4}-\d{2}-\d{2})").unwrap()
+});
+
+/// A heading and the headings nested beneath it
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct HeadingNode {
+    /// Heading level (1-6)
+    pub level: u8,
+    /// Heading text
+    pub text: String,
+    /// 1-based line number of the heading
+    pub line: usize,
+    /// Nested sub-headings
+    pub children: Vec<HeadingNode>,
+}
+
+/// A checkbox task item within a note
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TaskItem {
+    /// 0-based position among the note's tasks
+    pub index: usize,
+    /// 1-based line number of the task
+    pub line: usize,
+    /// Whether the checkbox is ticked
+    pub checked: bool,
+    /// Task text following the checkbox
+    pub text: String,
+    /// Headings enclosing the task, outermost first
+    pub heading_path: Vec<String>,
+    /// Due date (YYYY-MM-DD) if the task carries a due annotation
+    pub due: Option<String>,
+    /// Inline #tags in the task text
+    pub tags: Vec<String>,
+}
+
+/// Flat heading with its source span
+struct HeadingSpan {
+    level: u8,
+    text: String,
+    range: Range<usize>,
+}
+
+/// Parser options shared by all AST operations
+fn parser_options() -> Options {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_TASKLISTS);
+    // Keeps frontmatter's closing `---` from being read as a setext heading
+    options.insert(Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
+    options
+}
+
+/// 1-based line number of a byte offset
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+/// Collect all headings in document order
+fn heading_spans(content: &str) -> Vec<HeadingSpan> {
+    let mut spans = Vec::new();
+    let mut current: Option<HeadingSpan> = None;
+
+    for (event, range) in Parser::new_ext(content, parser_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                current = Some(HeadingSpan { level: level as u8, text: String::new(), range });
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(heading) = current.as_mut() {
+                    heading.text.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some(mut heading) = current.take() {
+                    heading.text = heading.text.trim().to_string();
+                    spans.push(heading);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    spans
+}
+
+/// Heading texts enclosing each position in `spans`, including the heading itself
+fn heading_paths(spans: &[HeadingSpan]) -> Vec<Vec<String>> {
+    let mut stack: Vec<(u8, String)> = Vec::new();
+    spans.iter()
+        .map(|span| {
+            stack.retain(|(level, _)| *level < span.level);
+            stack.push((span.level, span.text.clone()));
+            stack.iter().map(|(_, text)| text.clone()).collect()
+        })
+        .collect()
+}
+
+/// Extract the heading hierarchy of a note
+pub fn heading_tree(content: &str) -> Vec<HeadingNode> {
+    fn insert(nodes: &mut Vec<HeadingNode>, node: HeadingNode) {
+        match nodes.last_mut() {
+            Some(last) if last.level < node.level => insert(&mut last.children, node),
+            _ => nodes.push(node),
+        }
+    }
+
+    let mut roots = Vec::new();
+    for span in heading_spans(content) {
+        insert(&mut roots, HeadingNode {
+            level: span.level,
+            text: span.text,
+            line: line_of(content, span.range.start),
+            children: Vec::new(),
+        });
+    }
+    roots
+}
+
+/// Replace the body of the section at `path` (heading texts, outermost first).
+/// The heading line itself and any following sections are kept.
+pub fn replace_section(content: &str, path: &[String], new_body: &str) -> ObsidianResult<String> {
+    let spans = heading_spans(content);
+    let paths = heading_paths(&spans);
+
+    let position = paths.iter()
+        .position(|candidate| {
+            candidate.len() == path.len()
+                && candidate.iter().zip(path).all(|(a, b)| a.eq_ignore_ascii_case(b.trim()))
+        })
+        .ok_or_else(|| ObsidianMcpError::InvalidMarkdown {
+            reason: format!("Section not found: {}", path.join(" > ")),
+        })?;
+
+    let heading = &spans[position];
+    let mut body_start = heading.range.end;
+    if !content[..body_start].ends_with('\n') {
+        body_start = content[body_start..].find('\n').map(|i| body_start + i + 1).unwrap_or(content.len());
+    }
+
+    let next_heading = spans[position + 1..].iter().find(|span| span.level <= heading.level);
+    let body_end = next_heading.map(|span| span.range.start).unwrap_or(content.len());
+
+    let mut body = new_body.trim_end().to_string();
+    if !body.is_empty() {
+        body.push('\n');
+    }
+    if next_heading.is_some() {
+        body.push('\n');
+    }
+
+    Ok(format!("{}{}{}", &content[..body_start], body, &content[body_end..]))
+}
+
+/// List checkbox tasks with their enclosing headings
+pub fn tasks(content: &str) -> Vec<TaskItem> {
+    task_markers(content)
+        .into_iter()
+        .enumerate()
+        .map(|(index, (checked, heading_path, marker))| {
+            let line_end = content[marker.end..].find('\n').map(|i| marker.end + i).unwrap_or(content.len());
+            let text = content[marker.end..line_end].trim();
+            TaskItem {
+                index,
+                line: line_of(content, marker.start),
+                checked,
+                text: text.to_string(),
+                heading_path,
+                due: DUE_DATE_PATTERN.captures(text).map(|c| c[1].to_string()),
+                tags: inline_tags(text),
+            }
+        })
+        .collect()
+}
+
+/// Task marker spans (`[ ]` / `[x]`) with their state and heading context
+fn task_markers(content: &str) -> Vec<(bool, Vec<String>, Range<usize>)> {
+    let spans = heading_spans(content);
+    let paths = heading_paths(&spans);
+
+    Parser::new_ext(content, parser_options())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::TaskListMarker(checked) => {
+                let heading_path = spans.iter()
+                    .rposition(|span| span.range.start < range.start)
+                    .map(|i| paths[i].clone())
+                    .unwrap_or_default();
+                Some((checked, heading_path, range))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Inline `#tags` in prose text; frontmatter, code blocks and inline code are ignored
+pub fn inline_tags(content: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut in_code_block = false;
+
+    for event in Parser::new_ext(content, parser_options()) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) | Event::Start(Tag::MetadataBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) | Event::End(TagEnd::MetadataBlock(_)) => in_code_block = false,
+            Event::Text(text) if !in_code_block => {
+                for word in text.split_whitespace() {
+                    if let Some(rest) = word.strip_prefix('#') {
+                        let tag = rest.trim_end_matches(|c: char| !c.is_alphanumeric() && c != '_' && c != '-' && c != '/');
+                        if !tag.is_empty() {
+                            tags.push(tag.to_string());
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    tags
+}
+
+/// Set (or flip, when `checked` is None) the checkbox of the task at `index`
+pub fn set_task(content: &str, index: usize, checked: Option<bool>) -> ObsidianResult<String> {
+    let (current, _, marker) = task_markers(content)
+        .into_iter()
+        .nth(index)
+        .ok_or_else(|| ObsidianMcpError::InvalidMarkdown {
+            reason: format!("Task {} not found", index),
+        })?;
+
+    let mark = if checked.unwrap_or(!current) { "x" } else { " " };
+    Ok(format!("{}[{}]{}", &content[..marker.start], mark, &content[marker.end..]))
+}
+
+/// Replace one cell of a table. `row` 0 is the header row.
+pub fn update_table_cell(content: &str, table: usize, row: usize, column: usize, value: &str) -> ObsidianResult<String> {
+    let mut table_index = None;
+    let mut tables_seen = 0;
+    let mut row_index = 0;
+    let mut column_index = 0;
+    let mut target = None;
+
+    for (event, range) in Parser::new_ext(content, parser_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Table(_)) => {
+                table_index = Some(tables_seen);
+                tables_seen += 1;
+                row_index = 0;
+            }
+            Event::End(TagEnd::Table) => table_index = None,
+            Event::Start(Tag::TableHead) | Event::Start(Tag::TableRow) => column_index = 0,
+            Event::End(TagEnd::TableHead) | Event::End(TagEnd::TableRow) => row_index += 1,
+            Event::Start(Tag::TableCell) => {
+                if table_index == Some(table) && row_index == row && column_index == column {
+                    target = Some(range);
+                    break;
+                }
+                column_index += 1;
+            }
+            _ => {}
+        }
+    }
+
+    let range = target.ok_or_else(|| ObsidianMcpError::InvalidMarkdown {
+        reason: format!("Table {} has no cell at row {}, column {}", table, row, column),
+    })?;
+
+    let cell = value.replace('\n', " ").replace('|', "\\|");
+    Ok(format!("{} {} {}", &content[..range.start], cell.trim(), &content[range.end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "---\ntitle: x\n---\n# Project\n\nintro\n\n## Tasks\n\n- [ ] write docs\n- [x] ship\n\n## Data\n\n| A | B |\n|---|---|\n| 1 | two |\n\n# Later\n";
+
+    #[test]
+    fn test_heading_tree() {
+        let tree = heading_tree(NOTE);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].text, "Project");
+        assert_eq!(tree[0].line, 4);
+        let children: Vec<&str> = tree[0].children.iter().map(|h| h.text.as_str()).collect();
+        assert_eq!(children, vec!["Tasks", "Data"]);
+    }
+
+    #[test]
+    fn test_replace_section() {
+        let path = vec!["Project".to_string(), "Tasks".to_string()];
+        let updated = replace_section(NOTE, &path, "- [ ] new task").unwrap();
+        assert!(updated.contains("## Tasks\n- [ ] new task\n\n## Data"));
+        assert!(!updated.contains("write docs"));
+        assert!(replace_section(NOTE, &["Missing".to_string()], "x").is_err());
+    }
+
+    #[test]
+    fn test_tasks_and_toggle() {
+        let items = tasks(NOTE);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].text, "write docs");
+        assert_eq!(items[0].heading_path, vec!["Project", "Tasks"]);
+        assert_eq!(items[0].due, None);
+
+        let toggled = set_task(NOTE, 0, None).unwrap();
+        assert!(toggled.contains("- [x] write docs"));
+        let unchecked = set_task(NOTE, 1, Some(false)).unwrap();
+        assert!(unchecked.contains("- [ ] ship"));
+    }
+
+    #[test]
+    fn test_task_annotations() {
+        let content = "- [ ] pay rent 📅 2024-06-01 #home\n- [ ] review [due:: 2024-06-03]\n- [x] call due:2024-06-05\n";
+        let due: Vec<Option<String>> = tasks(content).into_iter().map(|t| t.due).collect();
+        assert_eq!(due, vec![
+            Some("2024-06-01".to_string()),
+            Some("2024-06-03".to_string()),
+            Some("2024-06-05".to_string()),
+        ]);
+        assert_eq!(tasks(content)[0].tags, vec!["home"]);
+    }
+
+    #[test]
+    fn test_inline_tags_skip_code() {
+        let content = "Working on #rust and #obsidian/plugins.\n\n```\n#include <stdio.h>\n```\n\nUse `#notatag` here";
+        assert_eq!(inline_tags(content), vec!["rust", "obsidian/plugins"]);
+    }
+
+    #[test]
+    fn test_update_table_cell() {
+        let updated = update_table_cell(NOTE, 0, 1, 1, "a|b").unwrap();
+        assert!(updated.contains("| 1 | a\\|b |"));
+        assert!(update_table_cell(NOTE, 0, 5, 0, "x").is_err());
+    }
+}
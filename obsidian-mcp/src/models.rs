@@ -17,6 +17,8 @@ pub struct VaultFile {
     pub is_directory: bool,
     /// Wikilinks found in the file content (if content is included)
     pub wikilinks: Option<WikilinkSummary>,
+    /// SHA-256 of the file on disk; pass back as expected_hash to write safely
+    pub content_hash: Option<String>,
 }
 
 /// File metadata information
@@ -70,6 +72,9 @@ pub struct CreateFileParams {
     /// Whether to overwrite if file exists
     #[serde(default)]
     pub overwrite: bool,
+    /// Expected content hash of the file being overwritten (optimistic locking)
+    #[serde(default)]
+    pub expected_hash: Option<String>,
 }
 
 /// Parameters for updating an existing file
@@ -86,6 +91,9 @@ pub struct UpdateFileParams {
     /// Whether to create the file if it doesn't exist
     #[serde(default)]
     pub create_if_missing: bool,
+    /// Expected content hash of the file (optimistic locking)
+    #[serde(default)]
+    pub expected_hash: Option<String>,
 }
 
 /// Parameters for moving/renaming files
@@ -108,6 +116,9 @@ pub struct DeleteFileParams {
     /// Whether to delete directories recursively
     #[serde(default)]
     pub recursive: bool,
+    /// Expected content hash of the file (optimistic locking)
+    #[serde(default)]
+    pub expected_hash: Option<String>,
 }
 
 /// Search results container
@@ -208,6 +219,9 @@ pub struct BrowseParams {
     /// Whether to delete directories recursively (delete mode)
     #[serde(default)]
     pub recursive: bool,
+    /// Content hash from a previous read; the write fails with a conflict if the note changed since (create/update/delete modes).
+    /// Other obsidian-mcp servers on the vault wait for the write; Obsidian itself doesn't, so a save it makes at that instant can be lost
+    pub expected_hash: Option<String>,
 }
 
 /// Result of a browse operation
@@ -275,4 +289,290 @@ pub struct BrowseExample {
     pub description: String,
     pub operation: String,
     pub params: serde_json::Value,
-}
\ No newline at end of file
+}
+
+/// Parameters for recording a vault snapshot
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SnapshotParams {
+    /// Optional label describing the snapshot (e.g., "before agent session")
+    pub label: Option<String>,
+}
+
+/// Parameters for comparing vault snapshots
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DiffParams {
+    /// Snapshot id to compare from
+    pub from_snapshot: String,
+    /// Snapshot id to compare to (defaults to the current vault state)
+    pub to_snapshot: Option<String>,
+}
+
+/// Parameters for the get_note_metadata tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct NoteMetadataParams {
+    /// Relative path of the note
+    pub path: String,
+}
+
+/// Parameters for the update_frontmatter_field tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct FrontmatterFieldParams {
+    /// Relative path of the note
+    pub path: String,
+    /// Frontmatter field to set (e.g., "status")
+    pub field: String,
+    /// New value (string, number, boolean, list or object); null or omitted removes the field
+    pub value: Option<serde_json::Value>,
+    /// Content hash from a previous read; the update fails with a conflict if the note changed since
+    pub expected_hash: Option<String>,
+}
+
+/// Parameters for the query_notes_by_frontmatter tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct FrontmatterQueryParams {
+    /// Field values every note must have, e.g. {"status": "active"}. Strings match ignoring case,
+    /// a list field matches when it contains the value, and a list value matches any of its items
+    #[serde(default)]
+    pub filters: HashMap<String, serde_json::Value>,
+    /// Fields every note must have, whatever their value
+    pub has_fields: Option<Vec<String>>,
+    /// Only notes under this folder
+    pub folder: Option<String>,
+    /// Maximum number of notes to return
+    #[serde(default = "default_query_limit")]
+    pub limit: usize,
+}
+
+fn default_query_limit() -> usize {
+    100
+}
+
+/// Frontmatter and tags of a note
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct NoteMetadata {
+    /// Relative path of the note
+    pub path: String,
+    /// Frontmatter fields (empty when the note has none)
+    pub frontmatter: serde_json::Map<String, serde_json::Value>,
+    /// Tags from the frontmatter and inline #tags
+    pub tags: Vec<String>,
+    /// Last modified timestamp
+    pub modified: DateTime<Utc>,
+    /// SHA-256 of the file on disk; pass back as expected_hash to update safely
+    pub content_hash: Option<String>,
+}
+
+/// Notes whose frontmatter matched a query
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct FrontmatterQueryResult {
+    /// Matching notes, by path
+    pub notes: Vec<NoteMetadata>,
+    /// Number of matching notes before the limit was applied
+    pub total_matches: usize,
+    /// Notes skipped because their frontmatter isn't valid YAML
+    pub invalid_frontmatter: Vec<String>,
+}
+
+/// Parameters for the create_daily_note tool
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct DailyNoteParams {
+    /// Date of the note (YYYY-MM-DD); defaults to today
+    pub date: Option<String>,
+    /// Template to use instead of the daily notes template (path, or name in the templates folder)
+    pub template: Option<String>,
+    /// Values for custom {{placeholders}} in the template
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+/// Parameters for the create_from_template tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct TemplateNoteParams {
+    /// Template path, or name in the templates folder (e.g., "Meeting")
+    pub template: String,
+    /// Relative path of the new note; {{title}} is its file name
+    pub path: String,
+    /// Values for custom {{placeholders}} in the template
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+    /// Whether to create parent directories if they don't exist
+    #[serde(default = "default_true")]
+    pub create_dirs: bool,
+    /// Whether to overwrite if the note exists
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+/// A note created from a template, or the existing daily note
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TemplatedNote {
+    /// The note
+    pub file: VaultFile,
+    /// Whether the note was created (false if the daily note already existed)
+    pub created: bool,
+    /// Template the note was created from, if any
+    pub template: Option<String>,
+    /// Placeholders left in the note because no value was given for them
+    pub unresolved_variables: Vec<String>,
+}
+
+/// Parameters for the get_backlinks and get_outgoing_links tools
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct NoteLinksParams {
+    /// Note path (e.g., "Projects/Plan.md") or name as written in a wikilink (e.g., "Plan")
+    pub note: String,
+}
+
+/// Parameters for the find_orphan_notes tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct OrphanNotesParams {
+    /// Also count notes that link out but that nothing links to
+    #[serde(default)]
+    pub incoming_only: bool,
+    /// Only notes under this folder
+    pub folder: Option<String>,
+}
+
+/// Parameters for the shortest_link_path tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct LinkPathParams {
+    /// Starting note, as a path or wikilink name
+    pub from: String,
+    /// Destination note, as a path or wikilink name
+    pub to: String,
+    /// Follow links in either direction instead of only from linking to linked note
+    #[serde(default)]
+    pub undirected: bool,
+}
+
+/// Structural Markdown operations for the vault_markdown tool
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkdownOperation {
+    /// Return the heading tree and task list of a note
+    Outline,
+    /// Replace the body of a section identified by its heading path
+    ReplaceSection,
+    /// Tick, untick or flip a checkbox task
+    ToggleTask,
+    /// Replace a single table cell
+    UpdateCell,
+}
+
+/// Parameters for AST-level Markdown operations on a note
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MarkdownParams {
+    /// Relative path of the note
+    pub path: String,
+    /// Operation to perform
+    pub operation: MarkdownOperation,
+    /// Heading path from the top level, e.g. ["Project", "Tasks"] (replace_section)
+    pub section: Option<Vec<String>>,
+    /// New section body (replace_section)
+    pub content: Option<String>,
+    /// 0-based task index as listed by outline (toggle_task)
+    pub task_index: Option<usize>,
+    /// Desired checkbox state; omit to flip it (toggle_task)
+    pub checked: Option<bool>,
+    /// 0-based table index within the note (update_cell)
+    #[serde(default)]
+    pub table: usize,
+    /// 0-based row, where 0 is the header row (update_cell)
+    pub row: Option<usize>,
+    /// 0-based column (update_cell)
+    pub column: Option<usize>,
+    /// New cell value (update_cell)
+    pub value: Option<String>,
+    /// Content hash from a previous read; the edit fails with a conflict if the note changed since
+    pub expected_hash: Option<String>,
+}
+
+/// Structure of a note as seen by the Markdown AST
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct NoteOutline {
+    /// Relative path of the note
+    pub path: String,
+    /// Heading hierarchy
+    pub headings: Vec<crate::markdown::HeadingNode>,
+    /// Checkbox tasks in document order
+    pub tasks: Vec<crate::markdown::TaskItem>,
+    /// SHA-256 of the file on disk
+    pub content_hash: Option<String>,
+}
+
+/// Task completion filter
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    /// Unchecked tasks only (default)
+    #[default]
+    Open,
+    /// Checked tasks only
+    Done,
+    /// All tasks
+    All,
+}
+
+/// Operation modes for the vault_tasks tool
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TasksOperation {
+    /// Collect tasks across the vault (default)
+    #[default]
+    List,
+    /// Set or flip the checkbox of one task
+    Toggle,
+}
+
+/// Parameters for the vault_tasks tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct VaultTasksParams {
+    /// Operation to perform
+    #[serde(default)]
+    pub operation: TasksOperation,
+    /// Status filter (list mode)
+    #[serde(default)]
+    pub status: TaskStatus,
+    /// Only tasks carrying this tag, inline or via the note's tags (list mode)
+    pub tag: Option<String>,
+    /// Only notes under this folder (list mode)
+    pub folder: Option<String>,
+    /// Maximum number of tasks to return (list mode)
+    #[serde(default = "default_task_limit")]
+    pub limit: usize,
+    /// Note containing the task (toggle mode)
+    pub path: Option<String>,
+    /// 0-based task index within the note, as returned by list (toggle mode)
+    pub task_index: Option<usize>,
+    /// Desired checkbox state; omit to flip it (toggle mode)
+    pub checked: Option<bool>,
+    /// Content hash from a previous read (toggle mode)
+    pub expected_hash: Option<String>,
+}
+
+fn default_task_limit() -> usize {
+    200
+}
+
+/// A task together with the note it lives in
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct VaultTask {
+    /// Relative path of the note
+    pub path: String,
+    /// Task details
+    #[serde(flatten)]
+    pub task: crate::markdown::TaskItem,
+}
+
+/// Tasks collected across the vault
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct VaultTasksResult {
+    /// Matching tasks, ordered by due date then path
+    pub tasks: Vec<VaultTask>,
+    /// Number of matching tasks before the limit was applied
+    pub total_matches: usize,
+    /// Open tasks among the matches
+    pub open_count: usize,
+    /// Completed tasks among the matches
+    pub done_count: usize,
+}
@@ -1,5 +1,6 @@
 use crate::config::ObsidianMcpConfig;
 use crate::error::ObsidianResult;
+use crate::markdown;
 use crate::vault::VaultManager;
 use rmcp::{
     handler::server::{router::tool::ToolRouter, tool::Parameters},
@@ -86,7 +87,7 @@ impl ObsidianMcpService {
     }
 
     /// Browse and perform file operations with embedded modes and help
-    #[tool(description = "Browse files/directories and perform file operations (create/update/delete/move) in the vault. Use operation='help' for detailed usage information.")]
+    #[tool(description = "Browse files/directories and perform file operations (create/update/delete/move) in the vault. Pass expected_hash to guard writes against concurrent edits; only obsidian-mcp servers are locked out, not Obsidian itself. Use operation='help' for detailed usage information.")]
     pub async fn browse(
         &self,
         params: Parameters<BrowseParams>,
@@ -119,6 +120,293 @@ impl ObsidianMcpService {
     }
 
 
+    /// Record a content-hash snapshot of the vault
+    #[tool(description = "Record a content-hash manifest of the vault. Returns a snapshot id for use with vault_diff.")]
+    pub async fn vault_snapshot(
+        &self,
+        params: Parameters<SnapshotParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        tracing::info!("Recording vault snapshot: label={:?}", params.0.label);
+
+        match self.vault_manager().create_snapshot(params.0.label.clone()) {
+            Ok(summary) => {
+                let content = Content::json(summary)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            }
+            Err(e) => {
+                tracing::error!("Snapshot error: {}", e);
+                Err(ErrorData::from(e))
+            }
+        }
+    }
+
+    /// Compare two vault snapshots
+    #[tool(description = "Compare two vault snapshots (or a snapshot against the current vault) and list added, removed and modified notes.")]
+    pub async fn vault_diff(
+        &self,
+        params: Parameters<DiffParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        tracing::info!("Diffing vault snapshots: {} -> {:?}", params.0.from_snapshot, params.0.to_snapshot);
+
+        match self.vault_manager().diff_snapshots(&params.0.from_snapshot, params.0.to_snapshot.as_deref()) {
+            Ok(diff) => {
+                let content = Content::json(diff)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            }
+            Err(e) => {
+                tracing::error!("Diff error: {}", e);
+                Err(ErrorData::from(e))
+            }
+        }
+    }
+
+    /// Structural Markdown operations on a single note
+    #[tool(description = "Markdown AST operations on a note: outline (heading tree and tasks), replace_section (by heading path), toggle_task (by index), update_cell (table cell). Pass expected_hash to guard against concurrent edits.")]
+    pub async fn vault_markdown(
+        &self,
+        params: Parameters<MarkdownParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let params = params.0;
+        tracing::info!("Markdown {:?} on {}", params.operation, params.path);
+
+        let missing = |name: &str| ErrorData::invalid_params(format!("{} is required for this operation", name), None);
+        let vault = self.vault_manager();
+        let expected_hash = params.expected_hash.as_deref();
+
+        let result = match params.operation {
+            MarkdownOperation::Outline => vault.note_outline(&params.path).and_then(|outline| Ok(serde_json::to_value(outline)?)),
+            MarkdownOperation::ReplaceSection => {
+                let section = params.section.as_ref().ok_or_else(|| missing("section"))?;
+                let body = params.content.as_ref().ok_or_else(|| missing("content"))?;
+                vault.edit_note(&params.path, expected_hash, |content| markdown::replace_section(content, section, body))
+                    .and_then(|file| Ok(serde_json::to_value(file)?))
+            }
+            MarkdownOperation::ToggleTask => {
+                let index = params.task_index.ok_or_else(|| missing("task_index"))?;
+                vault.edit_note(&params.path, expected_hash, |content| markdown::set_task(content, index, params.checked))
+                    .and_then(|file| Ok(serde_json::to_value(file)?))
+            }
+            MarkdownOperation::UpdateCell => {
+                let row = params.row.ok_or_else(|| missing("row"))?;
+                let column = params.column.ok_or_else(|| missing("column"))?;
+                let value = params.value.as_ref().ok_or_else(|| missing("value"))?;
+                vault.edit_note(&params.path, expected_hash, |content| markdown::update_table_cell(content, params.table, row, column, value))
+                    .and_then(|file| Ok(serde_json::to_value(file)?))
+            }
+        };
+
+        match result {
+            Ok(value) => {
+                let content = Content::json(value)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            }
+            Err(e) => {
+                tracing::error!("Markdown operation error: {}", e);
+                Err(ErrorData::from(e))
+            }
+        }
+    }
+
+    /// Aggregate and toggle checkbox tasks across the vault
+    #[tool(description = "Collect '- [ ]' / '- [x]' tasks across the vault with note path, heading context and due dates. Filter by status (open/done/all), tag or folder; use operation=toggle with path and task_index to tick a task.")]
+    pub async fn vault_tasks(
+        &self,
+        params: Parameters<VaultTasksParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let params = params.0;
+
+        let result = match params.operation {
+            TasksOperation::List => {
+                tracing::info!("Collecting vault tasks: status={:?}, tag={:?}, folder={:?}", params.status, params.tag, params.folder);
+                self.vault_manager().collect_tasks(&params).and_then(|tasks| Ok(serde_json::to_value(tasks)?))
+            }
+            TasksOperation::Toggle => {
+                let path = params.path.as_ref()
+                    .ok_or_else(|| ErrorData::invalid_params("path is required for toggle operation".to_string(), None))?;
+                let index = params.task_index
+                    .ok_or_else(|| ErrorData::invalid_params("task_index is required for toggle operation".to_string(), None))?;
+                tracing::info!("Toggling task {} in {}", index, path);
+                self.vault_manager()
+                    .edit_note(path, params.expected_hash.as_deref(), |content| markdown::set_task(content, index, params.checked))
+                    .and_then(|file| Ok(serde_json::to_value(file)?))
+            }
+        };
+
+        match result {
+            Ok(value) => {
+                let content = Content::json(value)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            }
+            Err(e) => {
+                tracing::error!("Tasks error: {}", e);
+                Err(ErrorData::from(e))
+            }
+        }
+    }
+
+    /// Read a note's frontmatter
+    #[tool(description = "Get a note's YAML frontmatter as structured fields, with its tags (frontmatter and inline) and content hash.")]
+    pub async fn get_note_metadata(
+        &self,
+        params: Parameters<NoteMetadataParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        tracing::info!("Reading metadata of {}", params.0.path);
+
+        let result = self.vault_manager().note_metadata(&params.0.path);
+        self.frontmatter_result(result)
+    }
+
+    /// Patch one frontmatter field
+    #[tool(description = "Set one frontmatter field of a note (value null or omitted removes it), keeping the other fields in order and the note's text unchanged. Adds a frontmatter block when the note has none; pass expected_hash to guard against concurrent edits.")]
+    pub async fn update_frontmatter_field(
+        &self,
+        params: Parameters<FrontmatterFieldParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        tracing::info!("Updating frontmatter field {} of {}", params.0.field, params.0.path);
+
+        let result = self.vault_manager().update_frontmatter_field(&params.0);
+        self.frontmatter_result(result)
+    }
+
+    /// Find notes by frontmatter values
+    #[tool(description = "Find notes whose frontmatter matches field values, e.g. filters={\"status\": \"active\"}. Strings match ignoring case, list fields match when they contain the value, and a list of values matches any of them. has_fields requires fields to be present; folder limits the search.")]
+    pub async fn query_notes_by_frontmatter(
+        &self,
+        params: Parameters<FrontmatterQueryParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        tracing::info!("Querying notes by frontmatter: filters={:?}, has_fields={:?}, folder={:?}", params.0.filters, params.0.has_fields, params.0.folder);
+
+        let result = self.vault_manager().query_frontmatter(&params.0);
+        self.frontmatter_result(result)
+    }
+
+    /// Turn the result of a frontmatter operation into a tool result
+    fn frontmatter_result<T: serde::Serialize>(&self, result: ObsidianResult<T>) -> std::result::Result<CallToolResult, ErrorData> {
+        match result {
+            Ok(value) => {
+                let content = Content::json(value)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            }
+            Err(e) => {
+                tracing::error!("Frontmatter error: {}", e);
+                Err(ErrorData::from(e))
+            }
+        }
+    }
+
+    /// Create (or open) the daily note
+    #[tool(description = "Create the daily note for a date (default today) in the folder and name format of Obsidian's Daily notes settings, from its template with {{date}}, {{time}}, {{title}} and custom variables filled in. Returns the existing note if it was already created.")]
+    pub async fn create_daily_note(
+        &self,
+        params: Parameters<DailyNoteParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        tracing::info!("Creating daily note: date={:?}, template={:?}", params.0.date, params.0.template);
+
+        match self.vault_manager().create_daily_note(&params.0) {
+            Ok(note) => {
+                let content = Content::json(note)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            }
+            Err(e) => {
+                tracing::error!("Daily note error: {}", e);
+                Err(ErrorData::from(e))
+            }
+        }
+    }
+
+    /// Create a note from a template
+    #[tool(description = "Create a note from a template (path, or name in the templates folder), filling in {{title}}, {{date}}, {{time}}, {{date:FORMAT}} and custom variables. Placeholders without a value are left in place and listed.")]
+    pub async fn create_from_template(
+        &self,
+        params: Parameters<TemplateNoteParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        tracing::info!("Creating {} from template {}", params.0.path, params.0.template);
+
+        match self.vault_manager().create_from_template(&params.0) {
+            Ok(note) => {
+                let content = Content::json(note)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            }
+            Err(e) => {
+                tracing::error!("Template error: {}", e);
+                Err(ErrorData::from(e))
+            }
+        }
+    }
+
+    /// Notes linking to a note
+    #[tool(description = "List the notes that link to a note, with the number of wikilinks from each. The note can be a path or a wikilink name.")]
+    pub async fn get_backlinks(
+        &self,
+        params: Parameters<NoteLinksParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        tracing::info!("Getting backlinks of {}", params.0.note);
+
+        let result = self.vault_manager().resolved_links().and_then(|links| links.backlinks(&params.0.note));
+        self.link_graph_result(result)
+    }
+
+    /// Notes a note links to
+    #[tool(description = "List the notes a note links to, with the number of wikilinks to each, and the link targets that match no note.")]
+    pub async fn get_outgoing_links(
+        &self,
+        params: Parameters<NoteLinksParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        tracing::info!("Getting outgoing links of {}", params.0.note);
+
+        let result = self.vault_manager().resolved_links().and_then(|links| links.outgoing_links(&params.0.note));
+        self.link_graph_result(result)
+    }
+
+    /// Notes without links
+    #[tool(description = "Find notes with no wikilinks to or from them. Set incoming_only to also include notes that only link out; folder limits the notes considered.")]
+    pub async fn find_orphan_notes(
+        &self,
+        params: Parameters<OrphanNotesParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        tracing::info!("Finding orphan notes: incoming_only={}, folder={:?}", params.0.incoming_only, params.0.folder);
+
+        let result = self.vault_manager().resolved_links()
+            .map(|links| links.orphans(params.0.incoming_only, params.0.folder.as_deref()));
+        self.link_graph_result(result)
+    }
+
+    /// Shortest chain of wikilinks between two notes
+    #[tool(description = "Find the shortest chain of wikilinks from one note to another. Links are followed from linking to linked note unless undirected=true.")]
+    pub async fn shortest_link_path(
+        &self,
+        params: Parameters<LinkPathParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let params = params.0;
+        tracing::info!("Finding link path: {} -> {} (undirected={})", params.from, params.to, params.undirected);
+
+        let result = self.vault_manager().resolved_links()
+            .and_then(|links| links.shortest_path(&params.from, &params.to, params.undirected));
+        self.link_graph_result(result)
+    }
+
+    /// Turn the result of a link graph query into a tool result
+    fn link_graph_result<T: serde::Serialize>(&self, result: ObsidianResult<T>) -> std::result::Result<CallToolResult, ErrorData> {
+        match result {
+            Ok(value) => {
+                let content = Content::json(value)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            }
+            Err(e) => {
+                tracing::error!("Link graph error: {}", e);
+                Err(ErrorData::from(e))
+            }
+        }
+    }
+
     /// Handle browse (read) operations
     async fn handle_browse_operation(&self, params: &BrowseParams) -> std::result::Result<CallToolResult, ErrorData> {
         tracing::info!("Browsing vault path: {}", params.path);
@@ -188,6 +476,7 @@ impl ObsidianMcpService {
             tags: params.tags.clone(),
             create_dirs: params.create_dirs,
             overwrite: params.overwrite,
+            expected_hash: params.expected_hash.clone(),
         };
         
         match self.vault_manager().create_file(&create_params) {
@@ -216,6 +505,7 @@ impl ObsidianMcpService {
             frontmatter: params.frontmatter.clone(),
             tags: params.tags.clone(),
             create_if_missing: params.create_if_missing,
+            expected_hash: params.expected_hash.clone(),
         };
         
         match self.vault_manager().update_file(&update_params) {
@@ -238,6 +528,7 @@ impl ObsidianMcpService {
         let delete_params = DeleteFileParams {
             path: params.path.clone(),
             recursive: params.recursive,
+            expected_hash: params.expected_hash.clone(),
         };
         
         match self.vault_manager().delete_file(&delete_params) {
@@ -439,7 +730,9 @@ impl ObsidianMcpService {
                 "Always specify required parameters for each operation".to_string(),
                 "Frontmatter is optional - files work normally without it".to_string(),
                 "Update operations preserve existing frontmatter unless explicitly overridden".to_string(),
+                "Pass the content_hash from a read as expected_hash on update/delete to avoid overwriting concurrent edits".to_string(),
                 "Tags can be specified in frontmatter or as inline #tags in content".to_string(),
+                "Notes in the configured encrypted folder are transparently encrypted on disk".to_string(),
             ],
         }
     }
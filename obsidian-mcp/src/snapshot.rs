@@ -0,0 +1,198 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// Directory (relative to the vault root) where ObsidianMCP keeps its own state
+pub const STATE_DIR: &str = ".obsidian-mcp";
+
+/// Hex-encoded SHA-256 of file content
+pub fn content_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// A single file in a snapshot manifest
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SnapshotEntry {
+    /// SHA-256 of the file content
+    pub hash: String,
+    /// File size in bytes
+    pub size: u64,
+    /// Last modified timestamp
+    pub modified: DateTime<Utc>,
+}
+
+/// Content-hash manifest of the vault at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct VaultSnapshot {
+    /// Snapshot identifier
+    pub id: String,
+    /// Optional human-readable label
+    pub label: Option<String>,
+    /// When the snapshot was taken
+    pub created_at: DateTime<Utc>,
+    /// Files keyed by vault-relative path
+    pub entries: BTreeMap<String, SnapshotEntry>,
+}
+
+/// Summary returned after recording a snapshot
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SnapshotSummary {
+    /// Snapshot identifier (use with vault_diff)
+    pub id: String,
+    /// Optional human-readable label
+    pub label: Option<String>,
+    /// When the snapshot was taken
+    pub created_at: DateTime<Utc>,
+    /// Number of files recorded
+    pub file_count: usize,
+    /// Total size of recorded files in bytes
+    pub total_size: u64,
+}
+
+impl From<&VaultSnapshot> for SnapshotSummary {
+    fn from(snapshot: &VaultSnapshot) -> Self {
+        Self {
+            id: snapshot.id.clone(),
+            label: snapshot.label.clone(),
+            created_at: snapshot.created_at,
+            file_count: snapshot.entries.len(),
+            total_size: snapshot.entries.values().map(|e| e.size).sum(),
+        }
+    }
+}
+
+/// A note that differs between two snapshots
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct NoteChange {
+    /// Vault-relative path
+    pub path: String,
+    /// Size before the change (None if added)
+    pub old_size: Option<u64>,
+    /// Size after the change (None if removed)
+    pub new_size: Option<u64>,
+    /// Short human-readable description of the change
+    pub summary: String,
+}
+
+/// Differences between two snapshots
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct VaultDiff {
+    /// Snapshot the comparison starts from
+    pub from_snapshot: String,
+    /// Snapshot (or "current") the comparison ends at
+    pub to_snapshot: String,
+    /// Notes present only in the newer state
+    pub added: Vec<NoteChange>,
+    /// Notes present only in the older state
+    pub removed: Vec<NoteChange>,
+    /// Notes whose content hash changed
+    pub modified: Vec<NoteChange>,
+    /// Number of notes with identical content
+    pub unchanged_count: usize,
+}
+
+impl VaultSnapshot {
+    /// Compare this (older) snapshot against a newer one
+    pub fn diff(&self, newer: &VaultSnapshot) -> VaultDiff {
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+        let mut modified = Vec::new();
+        let mut unchanged_count = 0;
+
+        for (path, old) in &self.entries {
+            match newer.entries.get(path) {
+                Some(new) if new.hash == old.hash => unchanged_count += 1,
+                Some(new) => modified.push(NoteChange {
+                    path: path.clone(),
+                    old_size: Some(old.size),
+                    new_size: Some(new.size),
+                    summary: format!(
+                        "modified ({} -> {} bytes, {:+})",
+                        old.size,
+                        new.size,
+                        new.size as i64 - old.size as i64
+                    ),
+                }),
+                None => removed.push(NoteChange {
+                    path: path.clone(),
+                    old_size: Some(old.size),
+                    new_size: None,
+                    summary: format!("removed ({} bytes)", old.size),
+                }),
+            }
+        }
+
+        for (path, new) in &newer.entries {
+            if !self.entries.contains_key(path) {
+                added.push(NoteChange {
+                    path: path.clone(),
+                    old_size: None,
+                    new_size: Some(new.size),
+                    summary: format!("added ({} bytes)", new.size),
+                });
+            }
+        }
+
+        VaultDiff {
+            from_snapshot: self.id.clone(),
+            to_snapshot: newer.id.clone(),
+            added,
+            removed,
+            modified,
+            unchanged_count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(hash: &str, size: u64) -> SnapshotEntry {
+        SnapshotEntry {
+            hash: hash.to_string(),
+            size,
+            modified: Utc::now(),
+        }
+    }
+
+    fn snapshot(id: &str, entries: Vec<(&str, SnapshotEntry)>) -> VaultSnapshot {
+        VaultSnapshot {
+            id: id.to_string(),
+            label: None,
+            created_at: Utc::now(),
+            entries: entries.into_iter().map(|(p, e)| (p.to_string(), e)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_diff_classifies_changes() {
+        let old = snapshot("a", vec![
+            ("kept.md", entry("h1", 10)),
+            ("changed.md", entry("h2", 10)),
+            ("gone.md", entry("h3", 10)),
+        ]);
+        let new = snapshot("b", vec![
+            ("kept.md", entry("h1", 10)),
+            ("changed.md", entry("h4", 25)),
+            ("new.md", entry("h5", 5)),
+        ]);
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.unchanged_count, 1);
+        assert_eq!(diff.added[0].path, "new.md");
+        assert_eq!(diff.removed[0].path, "gone.md");
+        assert_eq!(diff.modified[0].path, "changed.md");
+        assert!(diff.modified[0].summary.contains("+15"));
+    }
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(
+            content_hash(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// `{{name}}` and `{{date:FORMAT}}` placeholders
+static PLACEHOLDER_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\{\{\s*([^{}]+?)\s*\}\}").unwrap()
+});
+
+/// Settings of Obsidian's core Daily notes plugin (`.obsidian/daily-notes.json`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DailyNoteSettings {
+    /// Folder daily notes are created in; empty for the vault root
+    pub folder: String,
+    /// Moment.js format of the note name, which may contain folders (e.g. "YYYY/MM/YYYY-MM-DD")
+    pub format: String,
+    /// Template the note is created from, if any
+    pub template: String,
+}
+
+impl Default for DailyNoteSettings {
+    fn default() -> Self {
+        Self {
+            folder: String::new(),
+            format: "YYYY-MM-DD".to_string(),
+            template: String::new(),
+        }
+    }
+}
+
+/// Settings of Obsidian's core Templates plugin (`.obsidian/templates.json`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TemplateSettings {
+    /// Folder holding the templates
+    pub folder: String,
+    /// Moment.js format of `{{date}}`
+    pub date_format: String,
+    /// Moment.js format of `{{time}}`
+    pub time_format: String,
+}
+
+impl Default for TemplateSettings {
+    fn default() -> Self {
+        Self {
+            folder: "Templates".to_string(),
+            date_format: "YYYY-MM-DD".to_string(),
+            time_format: "HH:mm".to_string(),
+        }
+    }
+}
+
+/// Read a core plugin's settings from the vault's `.obsidian` folder; empty
+/// values fall back to the defaults
+fn plugin_settings<T: for<'de> Deserialize<'de> + Default>(vault_root: &Path, file: &str) -> T {
+    let path = vault_root.join(".obsidian").join(file);
+    let Ok(json) = std::fs::read_to_string(&path) else {
+        return T::default();
+    };
+    let mut value: serde_json::Value = match serde_json::from_str(&json) {
+        Ok(value) => value,
+        Err(e) => {
+            tracing::warn!("Ignoring {}: {}", path.display(), e);
+            return T::default();
+        }
+    };
+    if let Some(fields) = value.as_object_mut() {
+        fields.retain(|_, value| value.as_str().is_none_or(|s| !s.trim().is_empty()));
+    }
+    serde_json::from_value(value).unwrap_or_default()
+}
+
+impl DailyNoteSettings {
+    pub fn load(vault_root: &Path) -> Self {
+        plugin_settings(vault_root, "daily-notes.json")
+    }
+}
+
+impl TemplateSettings {
+    pub fn load(vault_root: &Path) -> Self {
+        plugin_settings(vault_root, "templates.json")
+    }
+}
+
+/// Values available to a template
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+    /// Name of the note being created, for `{{title}}`
+    pub title: String,
+    /// Date and time of the note, for `{{date}}` and `{{time}}`
+    pub moment: NaiveDateTime,
+    pub date_format: String,
+    pub time_format: String,
+    /// Caller-supplied values; they take precedence over the built-in ones
+    pub variables: HashMap<String, String>,
+}
+
+/// A template with its placeholders filled in
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rendered {
+    pub content: String,
+    /// Placeholders without a value, left in place
+    pub unresolved: Vec<String>,
+}
+
+/// Fill in a template's placeholders. Besides the caller's variables,
+/// `{{title}}`, `{{date}}` and `{{time}}` are known, and `{{date:FORMAT}}` /
+/// `{{time:FORMAT}}` take a Moment.js format like Obsidian's Templates
+/// plugin. Unknown placeholders are kept so other plugins' syntax survives.
+pub fn render(template: &str, context: &TemplateContext) -> Rendered {
+    let mut unresolved = Vec::new();
+    let content = PLACEHOLDER_PATTERN.replace_all(template, |caps: &regex::Captures| {
+        let name = &caps[1];
+        let value = context.variables.get(name).cloned().or_else(|| match name.split_once(':') {
+            Some(("date" | "time", format)) => Some(format_moment(&context.moment, format.trim())),
+            Some(_) => None,
+            None => match name {
+                "title" => Some(context.title.clone()),
+                "date" => Some(format_moment(&context.moment, &context.date_format)),
+                "time" => Some(format_moment(&context.moment, &context.time_format)),
+                _ => None,
+            },
+        });
+        value.unwrap_or_else(|| {
+            if !unresolved.iter().any(|n| n == name) {
+                unresolved.push(name.to_string());
+            }
+            caps[0].to_string()
+        })
+    }).into_owned();
+    Rendered { content, unresolved }
+}
+
+/// Moment.js tokens understood by `format_moment`, longest first
+const MOMENT_TOKENS: &[&str] = &[
+    "YYYY", "YY", "MMMM", "MMM", "MM", "M", "Do", "DD", "D", "dddd", "ddd", "d",
+    "HH", "H", "hh", "h", "mm", "m", "ss", "s", "A", "a", "ww", "w",
+];
+
+/// Format a date and time with a Moment.js format string (the subset
+/// daily-note names and templates use); `[text]` is copied literally
+pub fn format_moment(moment: &NaiveDateTime, format: &str) -> String {
+    let mut out = String::new();
+    let mut rest = format;
+    while let Some(c) = rest.chars().next() {
+        if c == '[' {
+            if let Some(end) = rest.find(']') {
+                out.push_str(&rest[1..end]);
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        match MOMENT_TOKENS.iter().find(|token| rest.starts_with(**token)) {
+            Some(token) => {
+                out.push_str(&moment_token(moment, token));
+                rest = &rest[token.len()..];
+            }
+            None => {
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    out
+}
+
+fn moment_token(moment: &NaiveDateTime, token: &str) -> String {
+    let hour12 = match moment.hour() % 12 {
+        0 => 12,
+        h => h,
+    };
+    match token {
+        "YYYY" => moment.format("%Y").to_string(),
+        "YY" => moment.format("%y").to_string(),
+        "MMMM" => moment.format("%B").to_string(),
+        "MMM" => moment.format("%b").to_string(),
+        "MM" => format!("{:02}", moment.month()),
+        "M" => moment.month().to_string(),
+        "Do" => {
+            let day = moment.day();
+            let suffix = match (day % 10, day % 100) {
+                (_, 11..=13) => "th",
+                (1, _) => "st",
+                (2, _) => "nd",
+                (3, _) => "rd",
+                _ => "th",
+            };
+            format!("{}{}", day, suffix)
+        }
+        "DD" => format!("{:02}", moment.day()),
+        "D" => moment.day().to_string(),
+        "dddd" => moment.format("%A").to_string(),
+        "ddd" => moment.format("%a").to_string(),
+        "d" => moment.weekday().num_days_from_sunday().to_string(),
+        "HH" => format!("{:02}", moment.hour()),
+        "H" => moment.hour().to_string(),
+        "hh" => format!("{:02}", hour12),
+        "h" => hour12.to_string(),
+        "mm" => format!("{:02}", moment.minute()),
+        "m" => moment.minute().to_string(),
+        "ss" => format!("{:02}", moment.second()),
+        "s" => moment.second().to_string(),
+        "A" => if moment.hour() < 12 { "AM" } else { "PM" }.to_string(),
+        "a" => if moment.hour() < 12 { "am" } else { "pm" }.to_string(),
+        "ww" => format!("{:02}", moment.iso_week().week()),
+        "w" => moment.iso_week().week().to_string(),
+        _ => token.to_string(),
+    }
+}
+
+/// Parse a YYYY-MM-DD date
+pub fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(variables: &[(&str, &str)]) -> TemplateContext {
+        TemplateContext {
+            title: "2024-03-01".to_string(),
+            moment: parse_date("2024-03-01").unwrap().and_hms_opt(14, 5, 0).unwrap(),
+            date_format: "YYYY-MM-DD".to_string(),
+            time_format: "HH:mm".to_string(),
+            variables: variables.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+
+    #[test]
+    fn test_render_fills_builtin_and_custom_variables() {
+        let template = "# {{title}}\nCreated {{date}} at {{ time }}\n{{date:dddd, MMMM Do}} / {{time:h:mm A}}\nProject: {{project}}\n{{tp.file.title}} {{project}}";
+        let rendered = render(template, &context(&[("project", "Atlas")]));
+        assert_eq!(
+            rendered.content,
+            "# 2024-03-01\nCreated 2024-03-01 at 14:05\nFriday, March 1st / 2:05 PM\nProject: Atlas\n{{tp.file.title}} Atlas"
+        );
+        assert_eq!(rendered.unresolved, vec!["tp.file.title"]);
+
+        let overridden = render("{{title}}", &context(&[("title", "Custom")]));
+        assert_eq!(overridden.content, "Custom");
+    }
+
+    #[test]
+    fn test_format_moment() {
+        let moment = parse_date("2024-01-02").unwrap().and_hms_opt(0, 7, 9).unwrap();
+        assert_eq!(format_moment(&moment, "YYYY/MM/YYYY-MM-DD"), "2024/01/2024-01-02");
+        assert_eq!(format_moment(&moment, "[Week] ww, ddd D MMM YY hh:mm:ss a"), "Week 01, Tue 2 Jan 24 12:07:09 am");
+        assert!(parse_date("2024-13-01").is_none());
+    }
+}
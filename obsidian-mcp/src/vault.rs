@@ -1,11 +1,17 @@
 use crate::config::VaultConfig;
+use crate::conflict::{line_diff, WriteConflict};
+use crate::crypto::{VaultCipher, VAULT_KEY_ENV};
+use crate::frontmatter;
+use crate::snapshot::{content_hash, SnapshotEntry, SnapshotSummary, VaultDiff, VaultSnapshot, STATE_DIR};
+use crate::template::{self, DailyNoteSettings, TemplateContext, TemplateSettings};
 use crate::error::{ObsidianMcpError, ObsidianResult};
 use crate::models::*;
-use crate::wikilink::{WikilinkParser, WikilinkSummary};
+use crate::wikilink::{link_targets, GraphNote, LinkGraph, ResolvedLinks, WikilinkParser, WikilinkSummary};
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
 use walkdir::WalkDir;
 
 /// Vault operations manager
@@ -13,6 +19,14 @@ pub struct VaultManager {
     config: VaultConfig,
     root_path: PathBuf,
     wikilink_parser: Option<WikilinkParser>,
+    cipher: Option<VaultCipher>,
+    /// Serializes hash checks with the writes they guard within this process;
+    /// `lock_vault` does the same across obsidian-mcp processes. Obsidian takes
+    /// neither, so an edit it saves between the hash check and the rename can
+    /// still be lost: treat Obsidian as the one writer that isn't coordinated.
+    write_lock: Mutex<()>,
+    /// Link graph as last refreshed, loaded from the state directory on first use
+    link_graph: Mutex<Option<LinkGraph>>,
 }
 
 impl VaultManager {
@@ -46,11 +60,26 @@ impl VaultManager {
             None
         };
 
+        // Load the encryption key only when an encrypted folder is configured
+        let cipher = match &config.encrypted_folder {
+            Some(folder) => {
+                let cipher = VaultCipher::from_env()?;
+                if cipher.is_none() {
+                    tracing::warn!("Encrypted folder '{}' configured but {} is not set; its notes will be inaccessible", folder, VAULT_KEY_ENV);
+                }
+                cipher
+            }
+            None => None,
+        };
+
         tracing::info!("VaultManager initialized with path: {}", root_path.display());
         Ok(Self { 
             config, 
             root_path,
             wikilink_parser,
+            cipher,
+            write_lock: Mutex::new(()),
+            link_graph: Mutex::new(None),
         })
     }
 
@@ -106,7 +135,127 @@ impl VaultManager {
         }
         
         Ok(path)
-    }    /// Check if file extension is allowed
+    }
+
+    /// Check if a vault-relative path lives in the encrypted folder.
+    /// `.` and `..` components are resolved first, and the path is also compared
+    /// on disk, so `./Private/x.md`, `Notes/../Private/x.md` or a symlink into the
+    /// folder can't write plaintext there.
+    fn is_encrypted_path(&self, relative_path: &str) -> bool {
+        let Some(folder) = &self.config.encrypted_folder else {
+            return false;
+        };
+        let folder = self.root_path.join(folder.trim_matches('/'));
+        let note = normalize_path(&self.root_path.join(relative_path));
+        if note.starts_with(normalize_path(&folder)) {
+            return true;
+        }
+        match (canonical_path(&note), folder.canonicalize()) {
+            (Some(note), Ok(folder)) => note.starts_with(folder),
+            _ => false,
+        }
+    }
+
+    /// Get the cipher for a path, or None if the path is stored in plaintext
+    fn cipher_for(&self, relative_path: &str) -> ObsidianResult<Option<&VaultCipher>> {
+        if !self.is_encrypted_path(relative_path) {
+            return Ok(None);
+        }
+        self.cipher.as_ref().map(Some).ok_or_else(|| ObsidianMcpError::VaultAccessDenied {
+            reason: format!("{} is in the encrypted folder but {} is not set", relative_path, VAULT_KEY_ENV),
+        })
+    }
+
+    /// Read note text, decrypting notes stored in the encrypted folder
+    fn read_text(&self, abs_path: &Path, relative_path: &str) -> ObsidianResult<String> {
+        let raw = fs::read_to_string(abs_path)?;
+        match self.cipher_for(relative_path)? {
+            Some(cipher) if VaultCipher::is_encrypted(&raw) => cipher.decrypt(&raw),
+            // Plaintext notes dropped into the folder stay readable until rewritten
+            _ => Ok(raw),
+        }
+    }
+
+    /// Advisory lock on the vault's lock file, released when the file is dropped
+    fn lock_vault(&self) -> ObsidianResult<fs::File> {
+        let dir = self.root_path.join(STATE_DIR);
+        fs::create_dir_all(&dir)?;
+        let file = fs::OpenOptions::new().create(true).truncate(false).write(true).open(dir.join("write.lock"))?;
+        file.lock()?;
+        Ok(file)
+    }
+
+    /// Write note text, encrypting notes stored in the encrypted folder.
+    /// The text is staged in a temporary file beside the note and renamed over
+    /// it, after checking the note still matches `expected_hash` under the
+    /// vault lock.
+    fn write_text(&self, abs_path: &Path, relative_path: &str, content: &str, expected_hash: Option<&str>) -> ObsidianResult<()> {
+        let data = match self.cipher_for(relative_path)? {
+            Some(cipher) => cipher.encrypt(content)?,
+            None => content.to_string(),
+        };
+        let file_name = abs_path.file_name().and_then(|n| n.to_str()).unwrap_or("note");
+        let temp_path = abs_path.with_file_name(format!(
+            ".{}.{}.tmp",
+            file_name,
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        ));
+        fs::write(&temp_path, data)?;
+        let _lock = self.lock_vault()?;
+        let swapped = self.check_expected_hash(abs_path, relative_path, expected_hash, Some(content))
+            .and_then(|_| fs::rename(&temp_path, abs_path).map_err(Into::into));
+        if swapped.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        swapped
+    }
+
+    /// Content hash of a file on disk (None if it doesn't exist or is a directory)
+    fn current_hash(&self, abs_path: &Path) -> ObsidianResult<Option<String>> {
+        if !abs_path.is_file() {
+            return Ok(None);
+        }
+        Ok(Some(content_hash(&fs::read(abs_path)?)))
+    }
+
+    /// Fail with a conflict if the file no longer matches the hash the client expects
+    fn check_expected_hash(
+        &self,
+        abs_path: &Path,
+        relative_path: &str,
+        expected_hash: Option<&str>,
+        proposed_content: Option<&str>,
+    ) -> ObsidianResult<()> {
+        let Some(expected) = expected_hash else {
+            return Ok(());
+        };
+
+        let current_hash = self.current_hash(abs_path)?;
+        if current_hash.as_deref() == Some(expected) {
+            return Ok(());
+        }
+
+        let current_content = match current_hash {
+            Some(_) => Some(self.read_text(abs_path, relative_path)?),
+            None => None,
+        };
+        let diff = line_diff(
+            current_content.as_deref().unwrap_or(""),
+            proposed_content.unwrap_or(""),
+        );
+
+        tracing::warn!("Write conflict on {}: expected {}, found {:?}", relative_path, expected, current_hash);
+        Err(ObsidianMcpError::Conflict(Box::new(WriteConflict {
+            path: relative_path.to_string(),
+            expected_hash: expected.to_string(),
+            current_hash,
+            current_content,
+            proposed_content: proposed_content.map(str::to_string),
+            diff,
+        })))
+    }
+
+    /// Check if file extension is allowed
     fn is_allowed_extension(&self, path: &Path) -> bool {
         if let Some(ext) = path.extension() {
             if let Some(ext_str) = ext.to_str() {
@@ -194,20 +343,7 @@ impl VaultManager {
 
     /// Extract inline tags (#tag format) from content
     fn extract_inline_tags(&self, content: &str) -> Vec<String> {
-        let mut tags = Vec::new();
-        
-        // Simple regex-like extraction for #tag patterns
-        let words: Vec<&str> = content.split_whitespace().collect();
-        for word in words {
-            if word.starts_with('#') && word.len() > 1 {
-                let tag = word[1..].trim_end_matches(|c: char| !c.is_alphanumeric() && c != '_' && c != '-');
-                if !tag.is_empty() {
-                    tags.push(tag.to_string());
-                }
-            }
-        }
-        
-        tags
+        crate::markdown::inline_tags(content)
     }
 
     /// Generate frontmatter YAML and combine with content
@@ -347,19 +483,26 @@ impl VaultManager {
                 });
             }
             
-            let file_content = fs::read_to_string(&abs_path)?;
+            let file_content = self.read_text(&abs_path, relative_path)?;
             let wikilinks = self.parse_wikilinks_if_enabled(&file_content);
             (Some(file_content), wikilinks)
         } else {
             (None, None)
         };
 
+        let content_hash = if is_directory {
+            None
+        } else {
+            self.current_hash(&abs_path)?
+        };
+
         Ok(VaultFile {
             path: relative_path.to_string(),
             content,
             metadata: self.create_file_metadata(&metadata, &abs_path),
             is_directory,
             wikilinks,
+            content_hash,
         })
     }
 
@@ -410,8 +553,11 @@ impl VaultManager {
             params.tags.as_deref()
         )?;
 
-        // Write the file
-        fs::write(&abs_path, &final_content)?;
+        // Write the file, unless someone else changed it since the client read it
+        {
+            let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+            self.write_text(&abs_path, &params.path, &final_content, params.expected_hash.as_deref())?;
+        }
         
         // Return the created file info
         self.read_file(&params.path, false)
@@ -450,7 +596,7 @@ impl VaultManager {
 
         // If file exists, parse existing metadata to preserve it if not overridden
         let (existing_frontmatter, existing_tags) = if abs_path.exists() {
-            let existing_content = fs::read_to_string(&abs_path)?;
+            let existing_content = self.read_text(&abs_path, &params.path)?;
             let (fm, tags, _) = self.parse_frontmatter(&existing_content);
             (fm, tags)
         } else {
@@ -472,13 +618,310 @@ impl VaultManager {
             merged_tags.as_deref()
         )?;
 
-        // Write the file
-        fs::write(&abs_path, &final_content)?;
+        // Write the file, unless someone else changed it since the client read it
+        {
+            let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+            self.write_text(&abs_path, &params.path, &final_content, params.expected_hash.as_deref())?;
+        }
         
         // Return the updated file info
         self.read_file(&params.path, false)
     }
 
+    /// Structural outline (headings and tasks) of a note
+    pub fn note_outline(&self, relative_path: &str) -> ObsidianResult<NoteOutline> {
+        let file = self.read_file(relative_path, true)?;
+        let content = file.content.ok_or_else(|| ObsidianMcpError::InvalidFileOperation {
+            operation: "outline".to_string(),
+            path: format!("Not a readable note: {}", relative_path),
+        })?;
+
+        Ok(NoteOutline {
+            path: relative_path.to_string(),
+            headings: crate::markdown::heading_tree(&content),
+            tasks: crate::markdown::tasks(&content),
+            content_hash: file.content_hash,
+        })
+    }
+
+    /// Collect checkbox tasks across the vault
+    pub fn collect_tasks(&self, params: &VaultTasksParams) -> ObsidianResult<VaultTasksResult> {
+        let walk_root = match &params.folder {
+            Some(folder) => self.to_absolute_path(folder)?,
+            None => self.root_path.clone(),
+        };
+        let wanted_tag = params.tag.as_ref().map(|t| t.trim_start_matches('#').to_lowercase());
+
+        let mut matches = Vec::new();
+        let walker = WalkDir::new(walk_root).into_iter().filter_entry(|e| {
+            e.depth() == 0 || !e.file_name().to_str().map(|n| n.starts_with('.')).unwrap_or(false)
+        });
+
+        for entry in walker {
+            let entry = entry?;
+            let path = entry.path();
+            if !entry.file_type().is_file() || !self.is_allowed_extension(path) {
+                continue;
+            }
+
+            let rel_path = self.to_relative_path(path)?;
+            // Unreadable notes (e.g. encrypted without a key) are skipped like in search
+            let Ok(content) = self.read_text(path, &rel_path) else {
+                continue;
+            };
+
+            // Note-level tags come from frontmatter; inline tags are matched per task
+            let note_tags: Vec<String> = match self.parse_frontmatter(&content).0.and_then(|fm| fm.get("tags").cloned()) {
+                Some(serde_json::Value::Array(tags)) => tags.iter().filter_map(|t| t.as_str()).map(str::to_lowercase).collect(),
+                Some(serde_json::Value::String(tag)) => vec![tag.to_lowercase()],
+                _ => Vec::new(),
+            };
+
+            for task in crate::markdown::tasks(&content) {
+                let status_ok = match params.status {
+                    TaskStatus::Open => !task.checked,
+                    TaskStatus::Done => task.checked,
+                    TaskStatus::All => true,
+                };
+                let tag_ok = wanted_tag.as_ref().is_none_or(|wanted| {
+                    note_tags.contains(wanted) || task.tags.iter().any(|t| t.to_lowercase() == *wanted)
+                });
+
+                if status_ok && tag_ok {
+                    matches.push(VaultTask { path: rel_path.clone(), task });
+                }
+            }
+        }
+
+        // Dated tasks first, soonest due at the top
+        matches.sort_by(|a, b| {
+            (a.task.due.is_none(), &a.task.due, &a.path, a.task.index)
+                .cmp(&(b.task.due.is_none(), &b.task.due, &b.path, b.task.index))
+        });
+
+        let total_matches = matches.len();
+        let done_count = matches.iter().filter(|t| t.task.checked).count();
+        matches.truncate(params.limit);
+
+        Ok(VaultTasksResult {
+            tasks: matches,
+            total_matches,
+            open_count: total_matches - done_count,
+            done_count,
+        })
+    }
+
+    /// Apply an in-place edit to an existing note, guarded by an optional expected hash
+    pub fn edit_note<F>(&self, relative_path: &str, expected_hash: Option<&str>, edit: F) -> ObsidianResult<VaultFile>
+    where
+        F: FnOnce(&str) -> ObsidianResult<String>,
+    {
+        let abs_path = self.to_absolute_path(relative_path)?;
+        if !abs_path.is_file() {
+            return Err(ObsidianMcpError::FileNotFound {
+                path: relative_path.to_string(),
+            });
+        }
+
+        {
+            let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+            let content = self.read_text(&abs_path, relative_path)?;
+            let updated = edit(&content)?;
+            self.write_text(&abs_path, relative_path, &updated, expected_hash)?;
+        }
+
+        self.read_file(relative_path, false)
+    }
+
+    /// Frontmatter and tags of a note, read from disk
+    fn read_note_metadata(&self, abs_path: &Path, relative_path: &str) -> ObsidianResult<NoteMetadata> {
+        let content = self.read_text(abs_path, relative_path)?;
+        let (fields, _) = frontmatter::parse(&content)?;
+        let mut tags = Vec::new();
+        for tag in self.parse_frontmatter(&content).1 {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        let metadata = fs::metadata(abs_path)?;
+
+        Ok(NoteMetadata {
+            path: relative_path.to_string(),
+            frontmatter: frontmatter::to_json(&fields),
+            tags,
+            modified: self.create_file_metadata(&metadata, abs_path).modified,
+            content_hash: self.current_hash(abs_path)?,
+        })
+    }
+
+    /// Frontmatter and tags of a note
+    pub fn note_metadata(&self, relative_path: &str) -> ObsidianResult<NoteMetadata> {
+        let abs_path = self.to_absolute_path(relative_path)?;
+        if !abs_path.is_file() {
+            return Err(ObsidianMcpError::FileNotFound {
+                path: relative_path.to_string(),
+            });
+        }
+        self.read_note_metadata(&abs_path, relative_path)
+    }
+
+    /// Set or remove one frontmatter field, leaving the other fields and the note's text as they are
+    pub fn update_frontmatter_field(&self, params: &FrontmatterFieldParams) -> ObsidianResult<NoteMetadata> {
+        let value = params.value.as_ref().filter(|value| !value.is_null());
+        self.edit_note(&params.path, params.expected_hash.as_deref(), |content| {
+            frontmatter::set_field(content, &params.field, value)
+        })?;
+        self.note_metadata(&params.path)
+    }
+
+    /// Notes whose frontmatter matches every filter
+    pub fn query_frontmatter(&self, params: &FrontmatterQueryParams) -> ObsidianResult<FrontmatterQueryResult> {
+        let walk_root = match &params.folder {
+            Some(folder) => self.to_absolute_path(folder)?,
+            None => self.root_path.clone(),
+        };
+
+        let mut notes = Vec::new();
+        let mut invalid_frontmatter = Vec::new();
+        let walker = WalkDir::new(walk_root).into_iter().filter_entry(|e| {
+            e.depth() == 0 || !e.file_name().to_str().map(|n| n.starts_with('.')).unwrap_or(false)
+        });
+
+        for entry in walker {
+            let entry = entry?;
+            let path = entry.path();
+            if !entry.file_type().is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+                continue;
+            }
+
+            let rel_path = self.to_relative_path(path)?;
+            // Unreadable notes (e.g. encrypted without a key) are skipped like in search
+            let note = match self.read_note_metadata(path, &rel_path) {
+                Ok(note) => note,
+                Err(ObsidianMcpError::InvalidMarkdown { .. }) => {
+                    invalid_frontmatter.push(rel_path);
+                    continue;
+                }
+                Err(_) => continue,
+            };
+
+            let has_fields = params.has_fields.iter().flatten().all(|field| note.frontmatter.contains_key(field));
+            let filters_ok = params.filters.iter().all(|(field, wanted)| {
+                note.frontmatter.get(field).is_some_and(|actual| frontmatter::matches(actual, wanted))
+            });
+            if has_fields && filters_ok {
+                notes.push(note);
+            }
+        }
+
+        notes.sort_by(|a, b| a.path.cmp(&b.path));
+        invalid_frontmatter.sort();
+        let total_matches = notes.len();
+        notes.truncate(params.limit);
+
+        Ok(FrontmatterQueryResult { notes, total_matches, invalid_frontmatter })
+    }
+
+    /// Find a template by its path, or by name in the templates folder
+    fn read_template(&self, name: &str, settings: &TemplateSettings) -> ObsidianResult<(String, String)> {
+        let name = name.trim().trim_start_matches('/');
+        let file = if name.ends_with(".md") { name.to_string() } else { format!("{}.md", name) };
+        let candidates = [file.clone(), format!("{}/{}", settings.folder.trim_matches('/'), file)];
+        for candidate in candidates {
+            let abs_path = self.to_absolute_path(&candidate)?;
+            if abs_path.is_file() {
+                let content = self.read_text(&abs_path, &candidate)?;
+                return Ok((candidate, content));
+            }
+        }
+        Err(ObsidianMcpError::FileNotFound {
+            path: format!("template {}", name),
+        })
+    }
+
+    /// Create a note, from a template when one is given; {{title}} is the note's file name
+    fn create_templated_note(
+        &self,
+        template: Option<&str>,
+        path: &str,
+        moment: chrono::NaiveDateTime,
+        variables: &HashMap<String, String>,
+        create_dirs: bool,
+        overwrite: bool,
+    ) -> ObsidianResult<TemplatedNote> {
+        let settings = TemplateSettings::load(&self.root_path);
+        let (template, content, unresolved_variables) = match template {
+            Some(name) => {
+                let (template_path, text) = self.read_template(name, &settings)?;
+                let rendered = template::render(&text, &TemplateContext {
+                    title: Path::new(path).file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string(),
+                    moment,
+                    date_format: settings.date_format,
+                    time_format: settings.time_format,
+                    variables: variables.clone(),
+                });
+                (Some(template_path), rendered.content, rendered.unresolved)
+            }
+            None => (None, String::new(), Vec::new()),
+        };
+
+        let file = self.create_file(&CreateFileParams {
+            path: path.to_string(),
+            content,
+            frontmatter: None,
+            tags: None,
+            create_dirs,
+            overwrite,
+            expected_hash: None,
+        })?;
+        tracing::info!("Created {} from template {:?}", path, template);
+        Ok(TemplatedNote { file, created: true, template, unresolved_variables })
+    }
+
+    /// Create a note from a template
+    pub fn create_from_template(&self, params: &TemplateNoteParams) -> ObsidianResult<TemplatedNote> {
+        self.create_templated_note(
+            Some(&params.template),
+            &params.path,
+            chrono::Local::now().naive_local(),
+            &params.variables,
+            params.create_dirs,
+            params.overwrite,
+        )
+    }
+
+    /// Create the daily note of a date in the folder and name format set in
+    /// Obsidian's Daily notes settings, or return it if it already exists
+    pub fn create_daily_note(&self, params: &DailyNoteParams) -> ObsidianResult<TemplatedNote> {
+        let now = chrono::Local::now().naive_local();
+        let date = match &params.date {
+            Some(date) => template::parse_date(date).ok_or_else(|| ObsidianMcpError::InvalidFileOperation {
+                operation: "create_daily_note".to_string(),
+                path: format!("Invalid date (expected YYYY-MM-DD): {}", date),
+            })?,
+            None => now.date(),
+        };
+        let moment = date.and_time(now.time());
+
+        let settings = DailyNoteSettings::load(&self.root_path);
+        let name = template::format_moment(&moment, &settings.format);
+        let path = match settings.folder.trim_matches('/') {
+            "" => format!("{}.md", name),
+            folder => format!("{}/{}.md", folder, name),
+        };
+        if self.to_absolute_path(&path)?.is_file() {
+            return Ok(TemplatedNote {
+                file: self.read_file(&path, false)?,
+                created: false,
+                template: None,
+                unresolved_variables: Vec::new(),
+            });
+        }
+
+        let template = params.template.as_deref().or(Some(settings.template.as_str()).filter(|t| !t.is_empty()));
+        self.create_templated_note(template, &path, moment, &params.variables, true, false)
+    }
+
     /// Delete a file or directory
     pub fn delete_file(&self, params: &crate::models::DeleteFileParams) -> ObsidianResult<()> {
         let abs_path = self.to_absolute_path(&params.path)?;
@@ -489,6 +932,10 @@ impl VaultManager {
             });
         }
 
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let _lock = self.lock_vault()?;
+        self.check_expected_hash(&abs_path, &params.path, params.expected_hash.as_deref(), None)?;
+
         if abs_path.is_dir() {
             if params.recursive {
                 fs::remove_dir_all(&abs_path)?;
@@ -540,8 +987,38 @@ impl VaultManager {
             }
         }
 
-        // Perform the move
+        // Notes that cross the encrypted folder boundary, anywhere in a moved directory,
+        // are read before anything moves so a missing key leaves the vault untouched
+        let (from_path, to_path) = (params.from_path.trim_end_matches('/'), params.to_path.trim_end_matches('/'));
+        let moved: Vec<(String, String)> = if from_abs.is_dir() {
+            let mut notes = Vec::new();
+            for entry in WalkDir::new(&from_abs) {
+                let entry = entry?;
+                if !entry.file_type().is_file() || !self.is_allowed_extension(entry.path()) {
+                    continue;
+                }
+                if let Ok(suffix) = entry.path().strip_prefix(&from_abs) {
+                    let suffix = suffix.to_string_lossy().replace('\\', "/");
+                    notes.push((format!("{}/{}", from_path, suffix), format!("{}/{}", to_path, suffix)));
+                }
+            }
+            notes
+        } else {
+            vec![(from_path.to_string(), to_path.to_string())]
+        };
+        let mut reencoded = Vec::new();
+        for (from_note, to_note) in moved {
+            if self.is_encrypted_path(&from_note) != self.is_encrypted_path(&to_note) {
+                let content = self.read_text(&self.to_absolute_path(&from_note)?, &from_note)?;
+                reencoded.push((to_note, content));
+            }
+        }
+
+        // Perform the move, then write the crossing notes in their new encoding
         fs::rename(&from_abs, &to_abs)?;
+        for (to_note, content) in reencoded {
+            self.write_text(&self.to_absolute_path(&to_note)?, &to_note, &content, None)?;
+        }
         
         // Return the moved file info
         self.read_file(&params.to_path, false)
@@ -592,6 +1069,7 @@ impl VaultManager {
                 metadata: self.create_file_metadata(&metadata, &path),
                 is_directory: metadata.is_dir(),
                 wikilinks: None, // Directory listings don't include content, so no wikilinks
+                content_hash: None,
             });
         }
 
@@ -646,6 +1124,12 @@ impl VaultManager {
             }
 
             let rel_path = self.to_relative_path(path)?;
+
+            // Skip ObsidianMCP's own state (snapshots etc.)
+            if Path::new(&rel_path).starts_with(STATE_DIR) {
+                continue;
+            }
+
             let mut is_match = false;
             let mut search_content = None;
 
@@ -656,7 +1140,7 @@ impl VaultManager {
 
             // Search in content if not already matched and it's a text file
             if !is_match && self.is_allowed_extension(path) {
-                if let Ok(content) = fs::read_to_string(path) {
+                if let Ok(content) = self.read_text(path, &rel_path) {
                     search_content = Some(content.clone());
                     if content.to_lowercase().contains(&params.query.to_lowercase()) {
                         is_match = true;
@@ -668,7 +1152,7 @@ impl VaultManager {
             if !is_match && self.wikilink_parser.is_some() {
                 if let Some(ref content) = search_content.or_else(|| {
                     if self.is_allowed_extension(path) {
-                        fs::read_to_string(path).ok()
+                        self.read_text(path, &rel_path).ok()
                     } else {
                         None
                     }
@@ -711,6 +1195,149 @@ impl VaultManager {
         })
     }
 
+    /// Directory holding snapshot manifests
+    fn snapshot_dir(&self) -> PathBuf {
+        self.root_path.join(STATE_DIR).join("snapshots")
+    }
+
+    /// Build a content-hash manifest of the current vault state
+    fn build_manifest(&self, id: String, label: Option<String>) -> ObsidianResult<VaultSnapshot> {
+        let mut entries = BTreeMap::new();
+
+        // Hidden files and folders (.obsidian, our own state dir) are not notes
+        let walker = WalkDir::new(&self.root_path).into_iter().filter_entry(|e| {
+            e.depth() == 0 || !e.file_name().to_str().map(|n| n.starts_with('.')).unwrap_or(false)
+        });
+
+        for entry in walker {
+            let entry = entry?;
+            let path = entry.path();
+            if !entry.file_type().is_file() || !self.is_allowed_extension(path) {
+                continue;
+            }
+
+            let metadata = entry.metadata()?;
+            let bytes = fs::read(path)?;
+            entries.insert(self.to_relative_path(path)?, SnapshotEntry {
+                hash: content_hash(&bytes),
+                size: metadata.len(),
+                modified: self.create_file_metadata(&metadata, path).modified,
+            });
+        }
+
+        Ok(VaultSnapshot {
+            id,
+            label,
+            created_at: Utc::now(),
+            entries,
+        })
+    }
+
+    /// Record a snapshot of the vault and persist its manifest
+    pub fn create_snapshot(&self, label: Option<String>) -> ObsidianResult<SnapshotSummary> {
+        let id = format!(
+            "{}-{}",
+            Utc::now().format("%Y%m%dT%H%M%SZ"),
+            &uuid::Uuid::new_v4().simple().to_string()[..6]
+        );
+        let snapshot = self.build_manifest(id, label)?;
+
+        let dir = self.snapshot_dir();
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(format!("{}.json", snapshot.id)), serde_json::to_string_pretty(&snapshot)?)?;
+
+        tracing::info!("Recorded vault snapshot {} ({} files)", snapshot.id, snapshot.entries.len());
+        Ok(SnapshotSummary::from(&snapshot))
+    }
+
+    /// Load a previously recorded snapshot
+    pub fn load_snapshot(&self, id: &str) -> ObsidianResult<VaultSnapshot> {
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(ObsidianMcpError::InvalidFileOperation {
+                operation: "load_snapshot".to_string(),
+                path: format!("Invalid snapshot id: {}", id),
+            });
+        }
+
+        let path = self.snapshot_dir().join(format!("{}.json", id));
+        if !path.exists() {
+            return Err(ObsidianMcpError::FileNotFound {
+                path: format!("snapshot {}", id),
+            });
+        }
+
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Compare a snapshot against another snapshot, or against the current vault
+    pub fn diff_snapshots(&self, from_id: &str, to_id: Option<&str>) -> ObsidianResult<VaultDiff> {
+        let from = self.load_snapshot(from_id)?;
+        let to = match to_id {
+            Some(id) => self.load_snapshot(id)?,
+            None => self.build_manifest("current".to_string(), None)?,
+        };
+        Ok(from.diff(&to))
+    }
+
+    /// File holding the persisted link graph
+    fn link_graph_path(&self) -> PathBuf {
+        self.root_path.join(STATE_DIR).join("link_graph.json")
+    }
+
+    /// Bring the link graph up to date with the vault and resolve its links.
+    /// Only notes whose size or modified time changed are read again; the
+    /// graph is written back when anything changed.
+    pub fn resolved_links(&self) -> ObsidianResult<ResolvedLinks> {
+        let mut cached = self.link_graph.lock().unwrap_or_else(|e| e.into_inner());
+        let previous = match cached.take() {
+            Some(graph) => graph,
+            // A missing or unreadable graph file is rebuilt from the notes
+            None => fs::read_to_string(self.link_graph_path()).ok()
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+        };
+
+        let mut graph = LinkGraph::default();
+        let mut changed = false;
+        let walker = WalkDir::new(&self.root_path).into_iter().filter_entry(|e| {
+            e.depth() == 0 || !e.file_name().to_str().map(|n| n.starts_with('.')).unwrap_or(false)
+        });
+        for entry in walker {
+            let entry = entry?;
+            let path = entry.path();
+            if !entry.file_type().is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+                continue;
+            }
+
+            let rel_path = self.to_relative_path(path)?;
+            let metadata = entry.metadata()?;
+            let modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+            let note = match previous.notes.get(&rel_path) {
+                Some(note) if modified.is_some() && note.size == metadata.len() && note.modified == modified => note.clone(),
+                _ => {
+                    changed = true;
+                    // Unreadable notes (e.g. encrypted without a key) are nodes without links
+                    let targets = self.read_text(path, &rel_path).map(|content| link_targets(&content)).unwrap_or_default();
+                    GraphNote { size: metadata.len(), modified, targets }
+                }
+            };
+            graph.notes.insert(rel_path, note);
+        }
+        changed |= graph.notes.len() != previous.notes.len();
+
+        if changed {
+            let path = self.link_graph_path();
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(path, serde_json::to_string(&graph)?)?;
+            tracing::info!("Updated link graph ({} notes)", graph.notes.len());
+        }
+        let links = graph.resolve();
+        *cached = Some(graph);
+        Ok(links)
+    }
+
     /// Generate summary of wikilinks across search results
     fn generate_wikilink_search_summary(&self, files: &[VaultFile]) -> Option<WikilinkSearchSummary> {
         let mut files_with_wikilinks = 0;
@@ -740,4 +1367,28 @@ impl VaultManager {
             None
         }
     }
-}
\ No newline at end of file
+}
+
+/// Resolve `.` and `..` components without touching the filesystem
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Path as it resolves on disk: the file itself, or its parent for a file not written yet
+fn canonical_path(path: &Path) -> Option<PathBuf> {
+    if let Ok(canonical) = path.canonicalize() {
+        return Some(canonical);
+    }
+    let parent = path.parent()?.canonicalize().ok()?;
+    Some(parent.join(path.file_name()?))
+}
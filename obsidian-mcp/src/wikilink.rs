@@ -1,10 +1,13 @@
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use std::path::PathBuf;
 use once_cell::sync::Lazy;
 use urlencoding::encode;
 
+use crate::error::{ObsidianMcpError, ObsidianResult};
+
 /// A wikilink found in content
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Wikilink {
@@ -251,6 +254,267 @@ impl WikilinkParser {
     }
 }
 
+/// Name a wikilink target refers to: lowercase, without a `#heading` or
+/// `#^block` anchor, a leading slash or the .md extension
+fn note_key(target: &str) -> String {
+    let target = target.split('#').next().unwrap_or_default().trim().replace('\\', "/");
+    let target = target.trim_start_matches('/');
+    target.strip_suffix(".md").unwrap_or(target).to_lowercase()
+}
+
+/// Wikilink targets of a note in document order, as written (anchors included)
+pub fn link_targets(content: &str) -> Vec<String> {
+    if !WikilinkParser::has_wikilinks(content) {
+        return Vec::new();
+    }
+    let mut targets: Vec<(usize, String)> = WIKILINK_PATTERNS.iter()
+        .flat_map(|pattern| pattern.captures_iter(content))
+        .filter_map(|caps| caps.get(1))
+        .map(|target| (target.start(), target.as_str().trim().to_string()))
+        .collect();
+    targets.sort();
+    targets.into_iter().map(|(_, target)| target).collect()
+}
+
+/// A note in the persisted link graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNote {
+    /// File size when the note was parsed
+    pub size: u64,
+    /// Last modified time when the note was parsed, to the precision the file system keeps
+    pub modified: Option<chrono::DateTime<chrono::Utc>>,
+    /// Wikilink targets as written in the note
+    pub targets: Vec<String>,
+}
+
+/// Wikilinks of every note, kept in the state directory between runs.
+///
+/// Only the targets as written are stored: which note a target resolves to
+/// depends on the other notes, so links are resolved when the graph is
+/// queried. A note is parsed again only when its size or modified time
+/// changed since it was stored.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LinkGraph {
+    /// Notes keyed by vault-relative path
+    pub notes: BTreeMap<String, GraphNote>,
+}
+
+impl LinkGraph {
+    /// Resolve every note's targets against the notes in the graph
+    pub fn resolve(&self) -> ResolvedLinks {
+        let resolver = NoteResolver::new(self.notes.keys());
+        let mut links = ResolvedLinks {
+            forward: self.notes.keys().map(|path| (path.clone(), BTreeMap::new())).collect(),
+            backward: self.notes.keys().map(|path| (path.clone(), BTreeMap::new())).collect(),
+            unresolved: BTreeMap::new(),
+            resolver,
+        };
+        for (path, note) in &self.notes {
+            for target in &note.targets {
+                match links.resolver.resolve(target) {
+                    // [[#Heading]] and other links to the note itself aren't edges
+                    Some(linked) if linked == *path => {}
+                    Some(linked) => {
+                        *links.forward.entry(path.clone()).or_default().entry(linked.clone()).or_default() += 1;
+                        *links.backward.entry(linked).or_default().entry(path.clone()).or_default() += 1;
+                    }
+                    None if note_key(target).is_empty() => {}
+                    None => links.unresolved.entry(path.clone()).or_default().push(target.clone()),
+                }
+            }
+        }
+        links
+    }
+}
+
+/// Finds the note a wikilink target or a tool argument refers to
+#[derive(Debug)]
+struct NoteResolver {
+    /// Notes by lowercase path without the .md extension
+    by_path: HashMap<String, String>,
+    /// Notes by lowercase file name, shallowest first
+    by_name: HashMap<String, Vec<String>>,
+}
+
+impl NoteResolver {
+    fn new<'a>(paths: impl Iterator<Item = &'a String>) -> Self {
+        let mut by_path = HashMap::new();
+        let mut by_name: HashMap<String, Vec<String>> = HashMap::new();
+        for path in paths {
+            let key = note_key(path);
+            let name = key.rsplit('/').next().unwrap_or_default().to_string();
+            by_path.insert(key, path.clone());
+            by_name.entry(name).or_default().push(path.clone());
+        }
+        for candidates in by_name.values_mut() {
+            candidates.sort_by_key(|path| (path.matches('/').count(), path.clone()));
+        }
+        Self { by_path, by_name }
+    }
+
+    /// Like Obsidian: an exact path first, then the shallowest note whose
+    /// path ends with the target ("Plan" or "Projects/Plan")
+    fn resolve(&self, target: &str) -> Option<String> {
+        let key = note_key(target);
+        if key.is_empty() {
+            return None;
+        }
+        if let Some(path) = self.by_path.get(&key) {
+            return Some(path.clone());
+        }
+        let name = key.rsplit('/').next().unwrap_or_default();
+        let suffix = format!("/{}", key);
+        self.by_name.get(name)?.iter()
+            .find(|path| !key.contains('/') || note_key(path).ends_with(&suffix))
+            .cloned()
+    }
+}
+
+/// The link graph with its targets resolved to notes
+#[derive(Debug)]
+pub struct ResolvedLinks {
+    /// Notes each note links to, with the number of links
+    forward: BTreeMap<String, BTreeMap<String, usize>>,
+    /// Notes linking to each note, with the number of links
+    backward: BTreeMap<String, BTreeMap<String, usize>>,
+    /// Targets matching no note, by the note they are in
+    unresolved: BTreeMap<String, Vec<String>>,
+    resolver: NoteResolver,
+}
+
+/// A note linked to or from another, with how many times
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct NoteLink {
+    /// Vault-relative path of the linked note
+    pub path: String,
+    /// Number of wikilinks between the two notes in this direction
+    pub count: usize,
+}
+
+/// Notes linking to a note
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct BacklinksResult {
+    /// Vault-relative path of the note
+    pub note: String,
+    /// Notes linking to it, by path
+    pub backlinks: Vec<NoteLink>,
+}
+
+/// Notes a note links to
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct OutgoingLinksResult {
+    /// Vault-relative path of the note
+    pub note: String,
+    /// Notes it links to, by path
+    pub links: Vec<NoteLink>,
+    /// Targets in the note that match no note in the vault
+    pub unresolved: Vec<String>,
+}
+
+/// Notes with no links
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct OrphanNotesResult {
+    /// Vault-relative paths of the orphans
+    pub orphans: Vec<String>,
+    /// Number of notes considered
+    pub total_notes: usize,
+}
+
+/// Shortest chain of links between two notes
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct LinkPathResult {
+    /// Vault-relative path of the starting note
+    pub from: String,
+    /// Vault-relative path of the destination note
+    pub to: String,
+    /// Notes along the path, both ends included (None if no path exists)
+    pub path: Option<Vec<String>>,
+    /// Number of links followed (None if no path exists)
+    pub hops: Option<usize>,
+}
+
+impl ResolvedLinks {
+    /// Path of the note a tool argument names, as a path or as a wikilink target
+    pub fn find_note(&self, note: &str) -> ObsidianResult<String> {
+        self.resolver.resolve(note).ok_or_else(|| ObsidianMcpError::FileNotFound {
+            path: note.to_string(),
+        })
+    }
+
+    fn counted(links: Option<&BTreeMap<String, usize>>) -> Vec<NoteLink> {
+        links.into_iter().flatten()
+            .map(|(path, count)| NoteLink { path: path.clone(), count: *count })
+            .collect()
+    }
+
+    pub fn backlinks(&self, note: &str) -> ObsidianResult<BacklinksResult> {
+        let note = self.find_note(note)?;
+        Ok(BacklinksResult {
+            backlinks: Self::counted(self.backward.get(&note)),
+            note,
+        })
+    }
+
+    pub fn outgoing_links(&self, note: &str) -> ObsidianResult<OutgoingLinksResult> {
+        let note = self.find_note(note)?;
+        Ok(OutgoingLinksResult {
+            links: Self::counted(self.forward.get(&note)),
+            unresolved: self.unresolved.get(&note).cloned().unwrap_or_default(),
+            note,
+        })
+    }
+
+    /// Notes nothing links to and, unless `incoming_only`, that link nowhere,
+    /// optionally only those under `folder`
+    pub fn orphans(&self, incoming_only: bool, folder: Option<&str>) -> OrphanNotesResult {
+        let prefix = folder.map(|f| format!("{}/", f.trim_matches('/')));
+        let in_folder = |path: &&String| prefix.as_ref().is_none_or(|prefix| path.starts_with(prefix.as_str()));
+        let notes: Vec<&String> = self.forward.keys().filter(in_folder).collect();
+        let orphans = notes.iter()
+            .filter(|path| self.backward.get(**path).is_none_or(BTreeMap::is_empty))
+            .filter(|path| incoming_only || self.forward.get(**path).is_none_or(BTreeMap::is_empty))
+            .map(|path| path.to_string())
+            .collect();
+        OrphanNotesResult { orphans, total_notes: notes.len() }
+    }
+
+    /// Fewest links from one note to another, following links backwards too when `undirected`
+    pub fn shortest_path(&self, from: &str, to: &str, undirected: bool) -> ObsidianResult<LinkPathResult> {
+        let (from, to) = (self.find_note(from)?, self.find_note(to)?);
+        let mut previous: HashMap<&str, &str> = HashMap::new();
+        let mut seen = BTreeSet::from([from.as_str()]);
+        let mut queue = VecDeque::from([from.as_str()]);
+        while let Some(note) = queue.pop_front() {
+            if note == to {
+                break;
+            }
+            let forward = self.forward.get(note).into_iter().flat_map(BTreeMap::keys);
+            let backward = self.backward.get(note).into_iter().flat_map(BTreeMap::keys).filter(|_| undirected);
+            for next in forward.chain(backward) {
+                if seen.insert(next.as_str()) {
+                    previous.insert(next.as_str(), note);
+                    queue.push_back(next.as_str());
+                }
+            }
+        }
+
+        let path = seen.contains(to.as_str()).then(|| {
+            let mut path = vec![to.clone()];
+            while let Some(note) = previous.get(path.last().unwrap().as_str()) {
+                path.push(note.to_string());
+            }
+            path.reverse();
+            path
+        });
+        Ok(LinkPathResult {
+            hops: path.as_ref().map(|path| path.len() - 1),
+            path,
+            from,
+            to,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -346,4 +610,58 @@ mod tests {
         assert!(!WikilinkParser::has_wikilinks("Text without links"));
         assert!(!WikilinkParser::has_wikilinks("Text with [single brackets]"));
     }
+
+    fn graph(notes: &[(&str, &str)]) -> LinkGraph {
+        LinkGraph {
+            notes: notes.iter().map(|(path, content)| {
+                (path.to_string(), GraphNote { size: 0, modified: None, targets: link_targets(content) })
+            }).collect(),
+        }
+    }
+
+    #[test]
+    fn test_link_graph_resolves_like_obsidian() {
+        assert_eq!(link_targets("[[b|B]] then [[a#Part]] and [[c]]"), vec!["b", "a#Part", "c"]);
+
+        let links = graph(&[
+            ("Home.md", "[[Plan]], [[Plan|again]], [[Old/Plan]], [[Ideas#Top]], [[#Intro]], [[Nowhere]]"),
+            ("Projects/Plan.md", "Back to [[home]]"),
+            ("Archive/Old/Plan.md", ""),
+            ("Ideas.md", ""),
+            ("Lonely.md", "no links"),
+        ]).resolve();
+
+        let outgoing = links.outgoing_links("Home").unwrap();
+        let linked: Vec<(&str, usize)> = outgoing.links.iter().map(|l| (l.path.as_str(), l.count)).collect();
+        assert_eq!(linked, vec![("Archive/Old/Plan.md", 1), ("Ideas.md", 1), ("Projects/Plan.md", 2)]);
+        assert_eq!(outgoing.unresolved, vec!["Nowhere"]);
+
+        let backlinks = links.backlinks("Home.md").unwrap();
+        assert_eq!(backlinks.backlinks.iter().map(|l| l.path.as_str()).collect::<Vec<_>>(), vec!["Projects/Plan.md"]);
+        assert!(links.backlinks("Missing").is_err());
+    }
+
+    #[test]
+    fn test_orphans_and_shortest_path() {
+        let links = graph(&[
+            ("a.md", "[[b]]"),
+            ("b.md", "[[c]]"),
+            ("c.md", "[[d]]"),
+            ("d.md", ""),
+            ("e.md", "[[a]]"),
+            ("notes/alone.md", ""),
+            ("notes/loud.md", "[[a]]"),
+        ]).resolve();
+
+        assert_eq!(links.orphans(false, None).orphans, vec!["notes/alone.md"]);
+        let incoming_only = links.orphans(true, Some("notes"));
+        assert_eq!(incoming_only.orphans, vec!["notes/alone.md", "notes/loud.md"]);
+        assert_eq!(incoming_only.total_notes, 2);
+
+        let path = links.shortest_path("e", "d", false).unwrap();
+        assert_eq!(path.path.unwrap(), vec!["e.md", "a.md", "b.md", "c.md", "d.md"]);
+        assert_eq!(path.hops, Some(4));
+        assert!(links.shortest_path("d", "a", false).unwrap().path.is_none());
+        assert_eq!(links.shortest_path("d", "a", true).unwrap().hops, Some(3));
+    }
 }
\ No newline at end of file
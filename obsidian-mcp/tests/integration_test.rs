@@ -12,6 +12,7 @@ async fn test_wikilink_parsing_integration() {
         max_file_size: 10 * 1024 * 1024,
         enable_watching: false,
         enable_wikilinks: true,
+        encrypted_folder: None,
     };
 
     // Create vault manager
@@ -73,4 +74,379 @@ async fn test_wikilink_parsing_integration() {
                  wikilink.obsidian_url,
                  wikilink.is_valid);
     }
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn test_encrypted_folder_round_trip() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::env::set_var(VAULT_KEY_ENV, "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=");
+
+    let vault_config = VaultConfig {
+        root_path: temp_dir.path().to_path_buf(),
+        vault_name: "TestVault".to_string(),
+        allowed_extensions: vec!["md".to_string()],
+        max_file_size: 10 * 1024 * 1024,
+        enable_watching: false,
+        enable_wikilinks: false,
+        encrypted_folder: Some("Private".to_string()),
+    };
+    let vault_manager = VaultManager::new(vault_config).expect("Failed to create vault manager");
+
+    vault_manager.create_file(&CreateFileParams {
+        path: "Private/secret.md".to_string(),
+        content: "# Secret\n\nlaunch codes".to_string(),
+        frontmatter: None,
+        tags: None,
+        create_dirs: true,
+        overwrite: false,
+        expected_hash: None,
+    }).expect("Create failed");
+
+    // On disk the note is opaque
+    let raw = std::fs::read_to_string(temp_dir.path().join("Private/secret.md")).unwrap();
+    assert!(VaultCipher::is_encrypted(&raw));
+    assert!(!raw.contains("launch codes"));
+
+    // Through the vault manager it reads as plaintext
+    let file = vault_manager.read_file("Private/secret.md", true).expect("Read failed");
+    assert_eq!(file.content.as_deref(), Some("# Secret\n\nlaunch codes"));
+
+    // Moving out of the encrypted folder decrypts it
+    vault_manager.move_file(&MoveFileParams {
+        from_path: "Private/secret.md".to_string(),
+        to_path: "public.md".to_string(),
+        overwrite: false,
+    }).expect("Move failed");
+    let raw = std::fs::read_to_string(temp_dir.path().join("public.md")).unwrap();
+    assert!(raw.contains("launch codes"));
+}
+
+fn encrypted_vault(root: &std::path::Path) -> VaultManager {
+    std::env::set_var(VAULT_KEY_ENV, "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=");
+    VaultManager::new(VaultConfig {
+        root_path: root.to_path_buf(),
+        allowed_extensions: vec!["md".to_string()],
+        encrypted_folder: Some("Private".to_string()),
+        ..VaultConfig::default()
+    }).expect("Failed to create vault manager")
+}
+
+#[tokio::test]
+async fn test_moving_directory_into_encrypted_folder_encrypts_its_notes() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(temp_dir.path().join("Projects/Deep")).unwrap();
+    std::fs::write(temp_dir.path().join("Projects/plan.md"), "the plan").unwrap();
+    std::fs::write(temp_dir.path().join("Projects/Deep/notes.md"), "deep notes").unwrap();
+    let vault_manager = encrypted_vault(temp_dir.path());
+
+    vault_manager.move_file(&MoveFileParams {
+        from_path: "Projects".to_string(),
+        to_path: "Private/Projects".to_string(),
+        overwrite: false,
+    }).expect("Move failed");
+
+    for (path, content) in [("Private/Projects/plan.md", "the plan"), ("Private/Projects/Deep/notes.md", "deep notes")] {
+        let raw = std::fs::read_to_string(temp_dir.path().join(path)).unwrap();
+        assert!(VaultCipher::is_encrypted(&raw), "{} is stored in plaintext", path);
+        assert_eq!(vault_manager.read_file(path, true).unwrap().content.as_deref(), Some(content));
+    }
+    assert!(!temp_dir.path().join("Projects").exists());
+}
+
+#[tokio::test]
+async fn test_moving_directory_out_of_encrypted_folder_decrypts_its_notes() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let vault_manager = encrypted_vault(temp_dir.path());
+    for (path, content) in [("Private/Journal/monday.md", "monday"), ("Private/Journal/Old/sunday.md", "sunday")] {
+        vault_manager.create_file(&CreateFileParams {
+            path: path.to_string(),
+            content: content.to_string(),
+            frontmatter: None,
+            tags: None,
+            create_dirs: true,
+            overwrite: false,
+            expected_hash: None,
+        }).expect("Create failed");
+    }
+
+    vault_manager.move_file(&MoveFileParams {
+        from_path: "Private/Journal".to_string(),
+        to_path: "Journal".to_string(),
+        overwrite: false,
+    }).expect("Move failed");
+
+    for (path, content) in [("Journal/monday.md", "monday"), ("Journal/Old/sunday.md", "sunday")] {
+        assert_eq!(std::fs::read_to_string(temp_dir.path().join(path)).unwrap(), content);
+    }
+}
+
+#[tokio::test]
+async fn test_dotted_paths_into_encrypted_folder_are_encrypted() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(temp_dir.path().join("Notes")).unwrap();
+    std::fs::create_dir_all(temp_dir.path().join("Private")).unwrap();
+    let vault_manager = encrypted_vault(temp_dir.path());
+
+    for (path, stored) in [("./Private/dot.md", "Private/dot.md"), ("Notes/../Private/parent.md", "Private/parent.md")] {
+        vault_manager.create_file(&CreateFileParams {
+            path: path.to_string(),
+            content: "launch codes".to_string(),
+            frontmatter: None,
+            tags: None,
+            create_dirs: true,
+            overwrite: false,
+            expected_hash: None,
+        }).expect("Create failed");
+
+        let raw = std::fs::read_to_string(temp_dir.path().join(stored)).unwrap();
+        assert!(VaultCipher::is_encrypted(&raw), "{} is stored in plaintext", path);
+        assert!(!raw.contains("launch codes"));
+        assert_eq!(vault_manager.read_file(stored, true).unwrap().content.as_deref(), Some("launch codes"));
+    }
+}
+
+#[tokio::test]
+async fn test_snapshot_diff_against_current_vault() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(temp_dir.path().join("keep.md"), "# Keep").unwrap();
+    std::fs::write(temp_dir.path().join("edit.md"), "# Edit").unwrap();
+    std::fs::write(temp_dir.path().join("drop.md"), "# Drop").unwrap();
+
+    let vault_manager = VaultManager::new(VaultConfig {
+        root_path: temp_dir.path().to_path_buf(),
+        ..VaultConfig::default()
+    }).expect("Failed to create vault manager");
+
+    let snapshot = vault_manager.create_snapshot(Some("before".to_string())).expect("Snapshot failed");
+    assert_eq!(snapshot.file_count, 3);
+
+    std::fs::write(temp_dir.path().join("edit.md"), "# Edit\n\nmore content").unwrap();
+    std::fs::remove_file(temp_dir.path().join("drop.md")).unwrap();
+    std::fs::write(temp_dir.path().join("new.md"), "# New").unwrap();
+
+    let diff = vault_manager.diff_snapshots(&snapshot.id, None).expect("Diff failed");
+    assert_eq!(diff.added.iter().map(|c| c.path.as_str()).collect::<Vec<_>>(), vec!["new.md"]);
+    assert_eq!(diff.removed.iter().map(|c| c.path.as_str()).collect::<Vec<_>>(), vec!["drop.md"]);
+    assert_eq!(diff.modified.iter().map(|c| c.path.as_str()).collect::<Vec<_>>(), vec!["edit.md"]);
+    assert_eq!(diff.unchanged_count, 1);
+}
+
+#[tokio::test]
+async fn test_update_with_stale_hash_conflicts() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(temp_dir.path().join("shared.md"), "line one\nline two").unwrap();
+
+    let vault_manager = VaultManager::new(VaultConfig {
+        root_path: temp_dir.path().to_path_buf(),
+        ..VaultConfig::default()
+    }).expect("Failed to create vault manager");
+
+    let base_hash = vault_manager.read_file("shared.md", true).unwrap().content_hash.unwrap();
+    let update = |content: &str| UpdateFileParams {
+        path: "shared.md".to_string(),
+        content: content.to_string(),
+        frontmatter: None,
+        tags: None,
+        create_if_missing: false,
+        expected_hash: Some(base_hash.clone()),
+    };
+
+    // First writer wins and gets the new hash back
+    let written = vault_manager.update_file(&update("line one\nline two\nfrom A")).expect("First write failed");
+    assert_ne!(written.content_hash.as_deref(), Some(base_hash.as_str()));
+
+    // Second writer still holds the old hash
+    match vault_manager.update_file(&update("line one\nfrom B")) {
+        Err(ObsidianMcpError::Conflict(conflict)) => {
+            assert_eq!(conflict.current_hash, written.content_hash);
+            assert_eq!(conflict.current_content.as_deref(), Some("line one\nline two\nfrom A"));
+            assert!(conflict.diff.iter().any(|d| d.op == "insert" && d.text == "from B"));
+        }
+        other => panic!("Expected conflict, got {:?}", other),
+    }
+
+    // Writes are staged beside the note and renamed over it; nothing is left behind
+    let leftovers: Vec<_> = std::fs::read_dir(temp_dir.path()).unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|name| name.ends_with(".tmp"))
+        .collect();
+    assert!(leftovers.is_empty(), "{:?}", leftovers);
+}
+
+#[tokio::test]
+async fn test_vault_tasks_filter_and_toggle() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(temp_dir.path().join("Work")).unwrap();
+    std::fs::write(
+        temp_dir.path().join("Work/plan.md"),
+        "# Plan\n\n- [ ] ship release 📅 2024-07-01 #release\n- [x] write changelog\n",
+    ).unwrap();
+    std::fs::write(temp_dir.path().join("home.md"), "- [ ] water plants\n").unwrap();
+
+    let vault_manager = VaultManager::new(VaultConfig {
+        root_path: temp_dir.path().to_path_buf(),
+        ..VaultConfig::default()
+    }).expect("Failed to create vault manager");
+
+    let params = |status: TaskStatus, tag: Option<&str>, folder: Option<&str>| VaultTasksParams {
+        operation: TasksOperation::List,
+        status,
+        tag: tag.map(str::to_string),
+        folder: folder.map(str::to_string),
+        limit: 50,
+        path: None,
+        task_index: None,
+        checked: None,
+        expected_hash: None,
+    };
+
+    let open = vault_manager.collect_tasks(&params(TaskStatus::Open, None, None)).unwrap();
+    assert_eq!(open.total_matches, 2);
+    // Dated task sorts first
+    assert_eq!(open.tasks[0].path, "Work/plan.md");
+    assert_eq!(open.tasks[0].task.due.as_deref(), Some("2024-07-01"));
+    assert_eq!(open.tasks[0].task.heading_path, vec!["Plan"]);
+
+    let tagged = vault_manager.collect_tasks(&params(TaskStatus::All, Some("#release"), None)).unwrap();
+    assert_eq!(tagged.total_matches, 1);
+
+    let work = vault_manager.collect_tasks(&params(TaskStatus::All, None, Some("Work"))).unwrap();
+    assert_eq!((work.open_count, work.done_count), (1, 1));
+
+    vault_manager.edit_note("home.md", None, |content| markdown::set_task(content, 0, Some(true))).unwrap();
+    let done = vault_manager.collect_tasks(&params(TaskStatus::Done, None, None)).unwrap();
+    assert_eq!(done.total_matches, 2);
+}
+
+#[tokio::test]
+async fn test_link_graph_persists_and_follows_edits() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(temp_dir.path().join("Projects")).unwrap();
+    std::fs::write(temp_dir.path().join("Home.md"), "See [[Plan]] and [[Ideas|my ideas]]").unwrap();
+    std::fs::write(temp_dir.path().join("Projects/Plan.md"), "# Plan\n\nBack to [[Home]]").unwrap();
+    std::fs::write(temp_dir.path().join("Ideas.md"), "# Ideas").unwrap();
+    std::fs::write(temp_dir.path().join("Stray.md"), "# Stray").unwrap();
+
+    let config = VaultConfig {
+        root_path: temp_dir.path().to_path_buf(),
+        ..VaultConfig::default()
+    };
+    let vault_manager = VaultManager::new(config.clone()).expect("Failed to create vault manager");
+
+    let links = vault_manager.resolved_links().expect("Link graph failed");
+    let backlinks = links.backlinks("Plan").unwrap();
+    assert_eq!(backlinks.note, "Projects/Plan.md");
+    assert_eq!(backlinks.backlinks.iter().map(|l| l.path.as_str()).collect::<Vec<_>>(), vec!["Home.md"]);
+    assert_eq!(links.orphans(false, None).orphans, vec!["Stray.md"]);
+    assert!(temp_dir.path().join(".obsidian-mcp/link_graph.json").exists());
+
+    // A new manager picks up the stored graph and re-reads only what changed
+    std::fs::write(temp_dir.path().join("Ideas.md"), "# Ideas\n\nSee [[Stray]]").unwrap();
+    let reopened = VaultManager::new(config).expect("Failed to create vault manager");
+    let links = reopened.resolved_links().expect("Link graph failed");
+    assert!(links.orphans(false, None).orphans.is_empty());
+    let path = links.shortest_path("Plan", "Stray", false).unwrap();
+    assert_eq!(path.path.unwrap(), vec!["Projects/Plan.md", "Home.md", "Ideas.md", "Stray.md"]);
+}
+
+#[tokio::test]
+async fn test_daily_note_from_configured_template() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(temp_dir.path().join(".obsidian")).unwrap();
+    std::fs::create_dir_all(temp_dir.path().join("Templates")).unwrap();
+    std::fs::write(
+        temp_dir.path().join(".obsidian/daily-notes.json"),
+        r#"{"folder": "Journal", "format": "YYYY/YYYY-MM-DD", "template": "Templates/Daily"}"#,
+    ).unwrap();
+    std::fs::write(temp_dir.path().join("Templates/Daily.md"), "# {{title}}\n{{date:dddd}}, mood: {{mood}}\n{{weather}}").unwrap();
+    std::fs::write(temp_dir.path().join("Templates/Meeting.md"), "# {{title}} ({{date}})\nWith {{who}}").unwrap();
+
+    let vault_manager = VaultManager::new(VaultConfig {
+        root_path: temp_dir.path().to_path_buf(),
+        ..VaultConfig::default()
+    }).expect("Failed to create vault manager");
+
+    let params = DailyNoteParams {
+        date: Some("2024-03-01".to_string()),
+        variables: [("mood".to_string(), "calm".to_string())].into_iter().collect(),
+        ..DailyNoteParams::default()
+    };
+    let note = vault_manager.create_daily_note(&params).expect("Daily note failed");
+    assert!(note.created);
+    assert_eq!(note.file.path, "Journal/2024/2024-03-01.md");
+    assert_eq!(note.unresolved_variables, vec!["weather"]);
+    let content = std::fs::read_to_string(temp_dir.path().join("Journal/2024/2024-03-01.md")).unwrap();
+    assert_eq!(content, "# 2024-03-01\nFriday, mood: calm\n{{weather}}");
+
+    // The second call finds the note instead of overwriting it
+    let again = vault_manager.create_daily_note(&params).expect("Daily note failed");
+    assert!(!again.created);
+
+    let meeting = vault_manager.create_from_template(&TemplateNoteParams {
+        template: "Meeting".to_string(),
+        path: "Meetings/Kickoff.md".to_string(),
+        variables: [("who".to_string(), "Sam".to_string())].into_iter().collect(),
+        create_dirs: true,
+        overwrite: false,
+    }).expect("Template failed");
+    assert_eq!(meeting.template.as_deref(), Some("Templates/Meeting.md"));
+    let content = std::fs::read_to_string(temp_dir.path().join("Meetings/Kickoff.md")).unwrap();
+    assert!(content.starts_with("# Kickoff (") && content.ends_with("With Sam"));
+}
+
+#[tokio::test]
+async fn test_frontmatter_metadata_update_and_query() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(temp_dir.path().join("Projects")).unwrap();
+    std::fs::write(temp_dir.path().join("Projects/Atlas.md"), "---\nstatus: active\nowner: sam\ntags: [work]\n---\n# Atlas #launch\n").unwrap();
+    std::fs::write(temp_dir.path().join("Projects/Borealis.md"), "---\nstatus: paused\n---\n# Borealis\n").unwrap();
+    std::fs::write(temp_dir.path().join("Projects/Broken.md"), "---\nstatus: [active\n---\n# Broken\n").unwrap();
+    std::fs::write(temp_dir.path().join("Inbox.md"), "# Inbox\n").unwrap();
+
+    let vault_manager = VaultManager::new(VaultConfig {
+        root_path: temp_dir.path().to_path_buf(),
+        ..VaultConfig::default()
+    }).expect("Failed to create vault manager");
+
+    let atlas = vault_manager.note_metadata("Projects/Atlas.md").expect("Metadata failed");
+    assert_eq!(atlas.frontmatter["owner"], "sam");
+    assert_eq!(atlas.tags, vec!["work", "launch"]);
+
+    let query = |filters: serde_json::Value| FrontmatterQueryParams {
+        filters: serde_json::from_value(filters).unwrap(),
+        has_fields: None,
+        folder: None,
+        limit: 100,
+    };
+    let active = vault_manager.query_frontmatter(&query(serde_json::json!({"status": "Active"}))).expect("Query failed");
+    assert_eq!(active.notes.iter().map(|n| n.path.as_str()).collect::<Vec<_>>(), vec!["Projects/Atlas.md"]);
+    assert_eq!(active.invalid_frontmatter, vec!["Projects/Broken.md"]);
+
+    // Patching a field keeps the body, and a stale hash is refused
+    let updated = vault_manager.update_frontmatter_field(&FrontmatterFieldParams {
+        path: "Projects/Borealis.md".to_string(),
+        field: "status".to_string(),
+        value: Some(serde_json::json!("active")),
+        expected_hash: None,
+    }).expect("Update failed");
+    assert_eq!(std::fs::read_to_string(temp_dir.path().join("Projects/Borealis.md")).unwrap(), "---\nstatus: active\n---\n# Borealis\n");
+    let both = vault_manager.query_frontmatter(&query(serde_json::json!({"status": ["active", "done"]}))).expect("Query failed");
+    assert_eq!(both.total_matches, 2);
+
+    let stale = vault_manager.update_frontmatter_field(&FrontmatterFieldParams {
+        path: "Projects/Borealis.md".to_string(),
+        field: "status".to_string(),
+        value: None,
+        expected_hash: atlas.content_hash.clone(),
+    });
+    assert!(matches!(stale, Err(ObsidianMcpError::Conflict(_))));
+    assert!(updated.content_hash.is_some());
+
+    let broken = vault_manager.update_frontmatter_field(&FrontmatterFieldParams {
+        path: "Projects/Broken.md".to_string(),
+        field: "status".to_string(),
+        value: Some(serde_json::json!("done")),
+        expected_hash: None,
+    });
+    assert!(broken.is_err());
+    assert!(std::fs::read_to_string(temp_dir.path().join("Projects/Broken.md")).unwrap().contains("[active"));
+}
@@ -0,0 +1,181 @@
+#!/usr/bin/env python3
+"""
+Unit tests for vault chunking and indexing in the background embedding service
+Run with: python3 -m unittest test_background_embedding_service
+"""
+
+import logging
+import os
+import sys
+import tempfile
+import types
+import unittest
+from unittest import mock
+
+# Stand-ins for the service's third-party clients, so the tests run without them installed
+for name in ('redis', 'openai', 'qdrant_client', 'qdrant_client.models'):
+    sys.modules.setdefault(name, types.ModuleType(name))
+sys.modules['qdrant_client'].QdrantClient = object
+sys.modules['qdrant_client'].models = sys.modules['qdrant_client.models']
+for name in ('VectorParams', 'Distance', 'PointStruct', 'OptimizersConfigDiff'):
+    setattr(sys.modules['qdrant_client.models'], name, lambda **kwargs: kwargs)
+sys.modules['qdrant_client.models'].PointIdsList = lambda points: points
+
+# Keep the service's log file out of the working tree
+with mock.patch.object(logging, 'FileHandler', lambda *args, **kwargs: logging.NullHandler()):
+    import background_embedding_service as service
+from background_embedding_service import chunk_note, vault_point_id, VAULT_COLLECTION
+
+
+class FakeQdrant:
+    """In-memory Qdrant holding one collection of points keyed by ID"""
+
+    def __init__(self):
+        self.points = {}
+
+    def scroll(self, collection_name, limit, offset, with_payload, with_vectors):
+        points = [types.SimpleNamespace(id=point_id, payload=payload) for point_id, payload in self.points.items()]
+        return points, None
+
+    def upsert(self, collection_name, points):
+        assert collection_name == VAULT_COLLECTION
+        for point in points:
+            self.points[point['id']] = point['payload']
+
+    def delete(self, collection_name, points_selector):
+        for point_id in points_selector:
+            del self.points[point_id]
+
+
+def make_service(vault_path):
+    """Service wired to a fake Qdrant, with embeddings stubbed out"""
+    embedding = service.BackgroundEmbeddingService.__new__(service.BackgroundEmbeddingService)
+    embedding.vault_path = vault_path
+    embedding.encrypted_folder = None
+    embedding.batch_size = 50
+    embedding.stats = service.ProcessingStats()
+    embedding.qdrant_client = FakeQdrant()
+    embedding.embedded = []
+
+    def generate_embeddings_batch(chunks):
+        embedding.embedded.extend(chunk['chunk_id'] for chunk in chunks)
+        for chunk in chunks:
+            chunk.update(embedding=[0.0], embedding_model='test', processed_at='now')
+        return chunks
+
+    embedding.generate_embeddings_batch = generate_embeddings_batch
+    return embedding
+
+
+class ChunkNoteTests(unittest.TestCase):
+    def test_sections_keep_heading_path(self):
+        text = "---\ntags: [x]\n---\nIntro line\n# Plans\nShip it\n## Risks\nNone\n# Empty\n"
+        chunks = chunk_note(text, "Note")
+
+        self.assertEqual([c['heading'] for c in chunks], ["Note", "Note > Plans", "Note > Plans > Risks"])
+        self.assertEqual(chunks[0]['content'], "Intro line")
+        self.assertEqual([c['key'] for c in chunks], [c['heading'] for c in chunks])
+
+    def test_preamble_without_heading(self):
+        chunks = chunk_note("Just a paragraph\n\nand another", "Loose")
+
+        self.assertEqual(len(chunks), 1)
+        self.assertEqual(chunks[0]['key'], "Loose")
+        self.assertEqual(chunks[0]['content'], "Just a paragraph\n\nand another")
+
+    def test_repeated_headings_are_numbered(self):
+        chunks = chunk_note("# Log\nfirst\n# Log\nsecond\n# Other\nthird", "Note")
+
+        self.assertEqual([c['key'] for c in chunks], ["Note > Log", "Note > Log (2)", "Note > Other"])
+
+    def test_headings_in_code_fences_are_ignored(self):
+        chunks = chunk_note("# Code\n```\n# not a heading\n```", "Note")
+
+        self.assertEqual(len(chunks), 1)
+        self.assertIn("# not a heading", chunks[0]['content'])
+
+    def test_long_sections_are_split_at_paragraphs(self):
+        paragraph = "word " * 300
+        chunks = chunk_note(f"# Long\n{paragraph}\n\n{paragraph}", "Note")
+
+        self.assertEqual([c['key'] for c in chunks], ["Note > Long", "Note > Long [part 2]"])
+        self.assertTrue(all(c['heading'] == "Note > Long" for c in chunks))
+
+    def test_inserting_a_section_keeps_other_keys(self):
+        before = chunk_note("# A\none\n# B\ntwo", "Note")
+        after = chunk_note("# New\nzero\n# A\none\n# B\ntwo", "Note")
+
+        self.assertTrue({c['key'] for c in before} <= {c['key'] for c in after})
+
+    def test_point_ids_use_the_full_hash(self):
+        point_id = vault_point_id("Note.md#Note > A")
+
+        self.assertEqual(len(point_id), 36)
+        self.assertEqual(point_id, vault_point_id("Note.md#Note > A"))
+        self.assertNotEqual(point_id, vault_point_id("Note.md#Note > B"))
+
+
+class ProcessVaultTests(unittest.TestCase):
+    def setUp(self):
+        self.vault = tempfile.TemporaryDirectory()
+        self.note = os.path.join(self.vault.name, "Note.md")
+        self.service = make_service(self.vault.name)
+        sleep = mock.patch.object(service.time, 'sleep')
+        sleep.start()
+        self.addCleanup(sleep.stop)
+        self.addCleanup(self.vault.cleanup)
+
+    def write(self, text, path=None):
+        path = path or self.note
+        os.makedirs(os.path.dirname(path), exist_ok=True)
+        with open(path, 'w', encoding='utf-8') as f:
+            f.write(text)
+
+    def indexed(self):
+        return sorted(payload['chunk_id'] for payload in self.service.qdrant_client.points.values())
+
+    def test_edited_section_is_the_only_one_reembedded(self):
+        self.write("Preamble\n# A\none\n# B\ntwo")
+        self.service.process_vault()
+        self.assertEqual(self.indexed(), ["Note.md#Note", "Note.md#Note > A", "Note.md#Note > B"])
+
+        self.service.embedded.clear()
+        self.write("Preamble\n# A\none, edited\n# B\ntwo")
+        self.service.process_vault()
+
+        self.assertEqual(self.service.embedded, ["Note.md#Note > A"])
+        self.assertEqual(len(self.indexed()), 3)
+
+    def test_deleted_section_is_removed_and_others_kept(self):
+        self.write("# A\none\n# B\ntwo\n# C\nthree")
+        self.service.process_vault()
+
+        self.service.embedded.clear()
+        self.write("# A\none\n# C\nthree")
+        self.service.process_vault()
+
+        self.assertEqual(self.service.embedded, [])
+        self.assertEqual(self.indexed(), ["Note.md#Note > A", "Note.md#Note > C"])
+
+    def test_points_under_old_ids_are_removed(self):
+        self.service.qdrant_client.points[12345] = {'chunk_id': "Note.md#0", 'content_hash': 'old'}
+        self.write("# A\none")
+        self.service.process_vault()
+
+        self.assertEqual(self.indexed(), ["Note.md#Note > A"])
+
+    def test_encrypted_notes_are_not_indexed(self):
+        self.service.encrypted_folder = "Private"
+        self.write("# A\none")
+        self.write("# Plans\nplaintext left in the encrypted folder", os.path.join(self.vault.name, "Private", "Plans.md"))
+        self.write(f"{service.ENCRYPTED_NOTE_HEADER}\nbm9uY2VjaXBoZXJ0ZXh0\n", os.path.join(self.vault.name, "Moved.md"))
+        # Ciphertext indexed before this check is removed on the next run
+        self.service.qdrant_client.points[vault_point_id("Moved.md#Moved")] = {'chunk_id': "Moved.md#Moved", 'content_hash': 'old'}
+        self.service.process_vault()
+
+        self.assertEqual(self.indexed(), ["Note.md#Note > A"])
+        self.assertEqual(self.service.embedded, ["Note.md#Note > A"])
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -59,6 +59,8 @@ class BackgroundEmbeddingServiceV3:
         self.consumer_group = self.config['embedding_service']['consumer']['group_name']
         self.consumer_name = f"{self.config['embedding_service']['consumer']['consumer_prefix']}_{os.getpid()}"
         self.stream_key = f"{self.instance}:events"
+        # Filled by ui_embedding_staleness; scored by importance
+        self.stale_key = f"{self.instance}:embedding_stale"
         
         # Initialize embedding service (later after getting API keys)
         self.embedding_service = None
@@ -153,6 +155,46 @@ class BackgroundEmbeddingServiceV3:
             logger.error(f"Batch processing error: {e}")
             return 0
     
+    async def process_stale_queue(self):
+        """Re-embed queued stale thoughts, most important first"""
+        try:
+            batch_size = self.config['embedding_service']['processing']['batch_size']
+            queued = await self.redis.zpopmax(self.stale_key, batch_size)
+            
+            processed = 0
+            for thought_id, _priority in queued:
+                thought_data_str = await self.redis.get(f"{self.instance}:Thoughts:{thought_id}")
+                if not thought_data_str:
+                    continue
+                thought_data = json.loads(thought_data_str)
+                content = thought_data.get('thought', '')
+                if not content:
+                    continue
+                
+                timestamp = self._parse_timestamp(thought_data.get('timestamp', ''))
+                result = await asyncio.get_event_loop().run_in_executor(
+                    None,
+                    self.embedding_service.store_thought_embedding,
+                    thought_id,
+                    content,
+                    timestamp
+                )
+                if result.get('success'):
+                    processed += 1
+                    self.metrics.total_processed += 1
+                else:
+                    logger.error(f"❌ Re-embed failed: {thought_id} - {result.get('error')}")
+            
+            if processed > 0:
+                logger.info(f"Re-embedded {processed} stale thoughts")
+                await self.update_metrics()
+            
+            return processed
+            
+        except Exception as e:
+            logger.error(f"Stale queue processing error: {e}")
+            return 0
+    
     async def process_single_event(self, message_id: str, fields: dict):
         """Process a single event with provider fallback"""
         try:
@@ -259,6 +301,10 @@ class BackgroundEmbeddingServiceV3:
         while True:
             processed = await self.process_batch()
             
+            # New thoughts come first; re-embed stale ones while the stream is idle
+            if processed == 0:
+                processed = await self.process_stale_queue()
+            
             if processed == 0:
                 await asyncio.sleep(2)
             else:
@@ -0,0 +1,213 @@
+//! Recall and think latency benchmarks.
+//!
+//! Seeds an instance per dataset size with deterministic thoughts (tags,
+//! importance and category included) and measures `ui_think` writes and the
+//! three `ui_recall` search paths: text, semantic and hybrid (semantic plus
+//! metadata filters, i.e. the enhanced search).
+//!
+//! Environment:
+//! - `UI_STORAGE_BACKEND` - `redis` (default, uses `REDIS_HOST`/`REDIS_PORT`/`REDIS_PASSWORD`) or `memory`
+//! - `UI_BENCH_SIZES` - comma-separated dataset sizes (default `1000,10000,100000`)
+//! - `OPENAI_API_KEY` - required for the semantic and hybrid paths on Redis; skipped without it
+//!
+//! Seeding is idempotent (thought ids derive from their index), so reruns
+//! against the same Redis reuse the data; `ui_think` writes (tagged `bench`)
+//! do accumulate, so flush `bench_*` keys before recording a baseline. Use `test-scripts/bench_regression.sh`
+//! to compare a run against a saved baseline.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use futures_util::stream::{self, StreamExt};
+use serde_json::json;
+use tokio::runtime::Runtime;
+
+use unified_intelligence::handlers::ToolHandlers;
+use unified_intelligence::models::{ThoughtMetadata, ThoughtRecord, UiRecallParams, UiThinkParams};
+use unified_intelligence::repository::{MemoryRepository, Repository, SqliteRepository, StorageBackend};
+use unified_intelligence::search_optimization::SearchCache;
+use unified_intelligence::service::UnifiedIntelligenceService;
+use unified_intelligence::validation::InputValidator;
+
+const SEED_CONCURRENCY: usize = 64;
+
+const TOPICS: &[&str] = &["redis", "rust", "search", "memory", "identity", "embedding", "latency", "schema"];
+const WORDS: &[&str] = &[
+    "cache", "index", "vector", "chain", "thought", "query", "filter", "tenant", "stream", "bloom",
+    "metadata", "feedback", "boost", "recall", "score", "shard", "lua", "script", "pool", "ttl",
+];
+const CATEGORIES: &[&str] = &["technical", "strategic", "operational", "relationship"];
+
+struct Bench {
+    repository: Arc<dyn Repository>,
+    handlers: ToolHandlers<dyn Repository>,
+    search_cache: SearchCache,
+    semantic: bool,
+}
+
+/// Small deterministic generator so every run seeds identical data
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        self.0 >> 33
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.next() as usize % items.len()]
+    }
+}
+
+fn bench_sizes() -> Vec<usize> {
+    std::env::var("UI_BENCH_SIZES")
+        .unwrap_or_else(|_| "1000,10000,100000".to_string())
+        .split(',')
+        .filter_map(|size| size.trim().parse().ok())
+        .collect()
+}
+
+fn seeded_thought(instance: &str, index: usize) -> (ThoughtRecord, ThoughtMetadata) {
+    let mut rng = Lcg(index as u64);
+    let topic = rng.pick(TOPICS);
+    let words: Vec<&str> = (0..12).map(|_| rng.pick(WORDS)).collect();
+
+    let mut thought = ThoughtRecord::new(
+        instance.to_string(),
+        format!("{} notes {}: {}", topic, index, words.join(" ")),
+        1,
+        1,
+        None,
+        false,
+    );
+    thought.id = uuid::Uuid::from_u128(index as u128 + 1).to_string();
+
+    let metadata = ThoughtMetadata::new(
+        thought.id.clone(),
+        instance.to_string(),
+        Some((rng.next() % 10) as i32 + 1),
+        Some((rng.next() % 10) as i32 + 1),
+        Some(vec![topic.to_string(), rng.pick(TOPICS).to_string()]),
+        Some(rng.pick(CATEGORIES).to_string()),
+    );
+    (thought, metadata)
+}
+
+async fn build(instance: &str) -> Bench {
+    let search_cache = SearchCache::from_env();
+    let search_available = Arc::new(AtomicBool::new(false));
+
+    let (repository, semantic): (Arc<dyn Repository>, bool) = match StorageBackend::from_env() {
+        StorageBackend::Redis => {
+            let repository = UnifiedIntelligenceService::redis_repository(instance, None, &search_available, &search_cache, None, None)
+                .await
+                .expect("Redis repository (set UI_STORAGE_BACKEND=memory to bench without Redis)");
+            let semantic = std::env::var("OPENAI_API_KEY").is_ok_and(|key| !key.is_empty());
+            (repository, semantic)
+        }
+        StorageBackend::Sqlite => {
+            // A fresh file per run, so seeded thoughts don't pile up between runs
+            let path = std::env::temp_dir().join(format!("ui_bench_{}.sqlite", instance));
+            let _ = std::fs::remove_file(&path);
+            (Arc::new(SqliteRepository::open(&path, None).expect("SQLite repository")), true)
+        }
+        StorageBackend::Memory => (Arc::new(MemoryRepository::new(None)), true),
+    };
+
+    let handlers = ToolHandlers::new(
+        repository.clone(),
+        instance.to_string(),
+        None,
+        Arc::new(InputValidator::new()),
+        search_cache.clone(),
+        search_available,
+    ).expect("tool handlers");
+    Bench { repository, handlers, search_cache, semantic }
+}
+
+async fn seed(bench: &Bench, instance: &str, size: usize) {
+    let repository = &bench.repository;
+    stream::iter(0..size)
+        .for_each_concurrent(SEED_CONCURRENCY, |index| async move {
+            let (thought, metadata) = seeded_thought(instance, index);
+            repository.save_thought(&thought).await.expect("seed thought");
+            repository.save_thought_metadata(&metadata).await.expect("seed metadata");
+        })
+        .await;
+}
+
+fn recall_params(path: &str, query: &str) -> UiRecallParams {
+    let params = match path {
+        "text" => json!({ "query": query, "limit": 20 }),
+        "semantic" => json!({ "query": query, "limit": 20, "semantic_search": true, "threshold": 0.3 }),
+        _ => json!({
+            "query": query,
+            "limit": 20,
+            "semantic_search": true,
+            "threshold": 0.3,
+            "tags_filter": ["redis"],
+            "min_importance": 5,
+        }),
+    };
+    serde_json::from_value(params).expect("valid recall params")
+}
+
+fn think_params(sequence: u64) -> UiThinkParams {
+    serde_json::from_value(json!({
+        "thought": format!("bench write {}: redis cache latency under load", sequence),
+        "thought_number": 1,
+        "total_thoughts": 1,
+        "next_thought_needed": false,
+        "importance": 5,
+        "tags": ["bench"],
+    }))
+    .expect("valid think params")
+}
+
+fn recall_latency(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("tokio runtime");
+    let writes = AtomicU64::new(0);
+
+    for size in bench_sizes() {
+        let instance = format!("bench_{}", size);
+        let bench = runtime.block_on(async {
+            let bench = build(&instance).await;
+            seed(&bench, &instance, size).await;
+            bench
+        });
+
+        let mut group = c.benchmark_group(format!("thoughts_{}", size));
+        group.sample_size(30).measurement_time(Duration::from_secs(10));
+
+        for path in ["text", "semantic", "hybrid"] {
+            if path != "text" && !bench.semantic {
+                eprintln!("Skipping ui_recall/{} at {} thoughts: OPENAI_API_KEY not set", path, size);
+                continue;
+            }
+            let mut rng = Lcg(size as u64);
+            group.bench_with_input(BenchmarkId::new("ui_recall", path), &path, |b, path| {
+                b.to_async(&runtime).iter(|| {
+                    // Rotate queries and drop cached results so every iteration hits the backend
+                    let query = format!("{} {}", rng.pick(TOPICS), rng.pick(WORDS));
+                    bench.search_cache.clear();
+                    let params = recall_params(path, &query);
+                    async { bench.handlers.ui_recall(params).await.expect("ui_recall") }
+                })
+            });
+        }
+
+        // Writes grow the instance, so they run after the recall paths
+        group.bench_function("ui_think", |b| {
+            b.to_async(&runtime).iter(|| async {
+                let sequence = writes.fetch_add(1, Ordering::Relaxed);
+                bench.handlers.ui_think(think_params(sequence)).await.expect("ui_think");
+            })
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, recall_latency);
+criterion_main!(benches);
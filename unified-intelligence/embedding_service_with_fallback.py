@@ -19,6 +19,11 @@ import re
 logging.basicConfig(level=logging.INFO)
 logger = logging.getLogger(__name__)
 
+# Model recorded with every vector; bump the version to mark existing vectors stale
+EMBEDDING_MODEL = os.getenv('UI_EMBEDDING_MODEL', 'text-embedding-3-small')
+EMBEDDING_MODEL_VERSION = os.getenv('UI_EMBEDDING_MODEL_VERSION', '1')
+GROQ_FALLBACK_MODEL = "llama3-8b-8192"
+
 class EmbeddingServiceWithFallback:
     def __init__(self, redis_url: str, openai_api_key: str, groq_api_key: Optional[str], instance: str):
         """Initialize embedding service with fallback support"""
@@ -68,7 +73,7 @@ class EmbeddingServiceWithFallback:
             processed_text = self._preprocess_text(text)
             
             response = self.openai_client.embeddings.create(
-                model="text-embedding-3-small",
+                model=EMBEDDING_MODEL,
                 input=processed_text
             )
             return response.data[0].embedding
@@ -101,7 +106,7 @@ class EmbeddingServiceWithFallback:
             Respond with just 10 numbers separated by commas."""
             
             response = self.groq_client.chat.completions.create(
-                model="llama3-8b-8192",
+                model=GROQ_FALLBACK_MODEL,
                 messages=[{"role": "user", "content": prompt}],
                 temperature=0.1,
                 max_tokens=50
@@ -195,17 +200,21 @@ class EmbeddingServiceWithFallback:
         # Try primary (OpenAI)
         embedding = self.generate_embedding_openai(embedding_text)
         provider = "openai"
+        model = EMBEDDING_MODEL
         
         # Fallback to Groq if needed
         if embedding is None and self.groq_client:
             logger.warning("OpenAI failed, using Groq fallback")
             embedding = self.generate_embedding_groq_fallback(embedding_text)
             provider = "groq_fallback"
+            model = GROQ_FALLBACK_MODEL
         
         # Return result with metadata
         return {
             "embedding": embedding,
             "provider": provider,
+            "model": model,
+            "model_version": EMBEDDING_MODEL_VERSION,
             "dimensions": len(embedding) if embedding else 0,
             "included_identity": include_identity
         }
@@ -225,6 +234,8 @@ class EmbeddingServiceWithFallback:
                 "timestamp": timestamp,
                 "instance": self.instance,
                 "provider": result["provider"],
+                "model": result["model"],
+                "model_version": result["model_version"],
                 "included_identity": result["included_identity"]
             }
             
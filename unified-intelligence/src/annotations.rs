@@ -0,0 +1,91 @@
+//! Review annotations for ui_annotate.
+//!
+//! Another instance or the human can agree or disagree with a thought, score
+//! it and leave a comment. Annotations live at
+//! `{instance}:annotations:{thought_id}` (newest first, at most
+//! MAX_ANNOTATIONS) so the original thought is never rewritten, and ui_recall
+//! returns them alongside results when asked with `include_annotations`.
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::Annotation;
+
+/// Annotations kept per thought
+pub const MAX_ANNOTATIONS: usize = 100;
+
+/// Reviewer name for annotations left by the human
+pub const HUMAN_REVIEWER: &str = "human";
+
+/// Accepted verdicts
+pub const VERDICTS: &[&str] = &["agree", "disagree"];
+
+/// Longest comment accepted, in characters
+pub const MAX_COMMENT_CHARS: usize = 4000;
+
+/// Normalize and check the parts of an annotation; at least one must be given
+pub fn validate(verdict: Option<&str>, score: Option<i32>, comment: Option<&str>) -> Result<(Option<String>, Option<String>)> {
+    let verdict = verdict.map(|v| v.trim().to_lowercase()).filter(|v| !v.is_empty());
+    if let Some(verdict) = &verdict {
+        if !VERDICTS.contains(&verdict.as_str()) {
+            return Err(UnifiedIntelligenceError::Validation {
+                field: "verdict".to_string(),
+                reason: format!("Unknown verdict '{}'. Use 'agree' or 'disagree'", verdict),
+            });
+        }
+    }
+    if let Some(score) = score {
+        if !(1..=10).contains(&score) {
+            return Err(UnifiedIntelligenceError::Validation {
+                field: "score".to_string(),
+                reason: format!("Score must be between 1 and 10, got {}", score),
+            });
+        }
+    }
+    let comment = comment.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+    if comment.as_ref().is_some_and(|c| c.chars().count() > MAX_COMMENT_CHARS) {
+        return Err(UnifiedIntelligenceError::Validation {
+            field: "comment".to_string(),
+            reason: format!("Comment is longer than {} characters", MAX_COMMENT_CHARS),
+        });
+    }
+    if verdict.is_none() && score.is_none() && comment.is_none() {
+        return Err(UnifiedIntelligenceError::Validation {
+            field: "verdict".to_string(),
+            reason: "An annotation needs a verdict, a score or a comment".to_string(),
+        });
+    }
+    Ok((verdict, comment))
+}
+
+/// Agree and disagree counts and the mean score of a thought's annotations
+pub fn summarize(annotations: &[Annotation]) -> (usize, usize, Option<f64>) {
+    let count = |verdict: &str| annotations.iter().filter(|a| a.verdict.as_deref() == Some(verdict)).count();
+    let scores: Vec<f64> = annotations.iter().filter_map(|a| a.score).map(f64::from).collect();
+    let average = (!scores.is_empty()).then(|| scores.iter().sum::<f64>() / scores.len() as f64);
+    (count("agree"), count("disagree"), average)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_and_summarize() {
+        assert_eq!(validate(Some(" Agree "), None, None).unwrap(), (Some("agree".to_string()), None));
+        assert!(validate(Some("maybe"), None, None).is_err());
+        assert!(validate(None, Some(11), None).is_err());
+        assert!(validate(None, None, Some("  ")).is_err());
+
+        let annotation = |verdict: Option<&str>, score: Option<i32>| Annotation {
+            id: "a".to_string(),
+            thought_id: "t".to_string(),
+            reviewer: HUMAN_REVIEWER.to_string(),
+            verdict: verdict.map(str::to_string),
+            score,
+            comment: None,
+            timestamp: String::new(),
+        };
+        let annotations = vec![annotation(Some("agree"), Some(8)), annotation(Some("disagree"), None), annotation(None, Some(5))];
+        assert_eq!(summarize(&annotations), (1, 1, Some(6.5)));
+        assert_eq!(summarize(&[]), (0, 0, None));
+    }
+}
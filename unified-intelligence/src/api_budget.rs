@@ -0,0 +1,197 @@
+//! OpenAI embedding spend, counted against unified-mind's daily budget.
+//!
+//! ui_recall's semantic search embeds the query with OpenAI through the
+//! RedisVL script. Those calls share unified-mind's caps and counters: caps
+//! come from UM_DAILY_BUDGET_USD and UM_OPENAI_DAILY_BUDGET_USD, overridden by
+//! the `um:config:budget` hash, and each call is priced and added to the
+//! `um:usage:{YYYY-MM-DD}` hash under `openai:embedding:*`, so mind_usage
+//! reports it. Once the budget is spent, semantic recall falls back to text
+//! search. Budget lookups that fail allow the call rather than block recall.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use chrono::{NaiveDate, Utc};
+
+use crate::redis::RedisManager;
+
+/// Redis hash holding budget overrides, shared with unified-mind
+pub const BUDGET_CONFIG_KEY: &str = "um:config:budget";
+
+/// Model the RedisVL script embeds queries with
+pub const EMBEDDING_MODEL: &str = "text-embedding-3-small";
+
+/// USD per million input tokens of EMBEDDING_MODEL, unless `price:{model}` overrides it
+const EMBEDDING_PRICE: f64 = 0.02;
+
+/// Usage hashes are kept this long, as in unified-mind
+const USAGE_TTL_SECS: u64 = 86400 * 90;
+
+/// Providers whose spend counts towards the overall daily cap
+const PROVIDERS: &[&str] = &["openai", "groq"];
+
+/// Daily caps that apply to OpenAI embeddings, and the embedding price
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetConfig {
+    pub daily_usd: Option<f64>,
+    pub openai_usd: Option<f64>,
+    pub price: f64,
+}
+
+fn parse_cap(value: &str) -> Option<f64> {
+    value.trim().parse::<f64>().ok().filter(|cap| cap.is_finite() && *cap >= 0.0)
+}
+
+impl BudgetConfig {
+    pub fn from_env() -> Self {
+        let cap = |name: &str| std::env::var(name).ok().and_then(|value| parse_cap(&value));
+        Self {
+            daily_usd: cap("UM_DAILY_BUDGET_USD"),
+            openai_usd: cap("UM_OPENAI_DAILY_BUDGET_USD"),
+            price: EMBEDDING_PRICE,
+        }
+    }
+
+    /// Apply the `um:config:budget` overrides; an empty value removes a cap
+    pub fn with_overrides(mut self, fields: &HashMap<String, String>) -> Self {
+        for (field, value) in fields {
+            let target = match field.as_str() {
+                "daily" => &mut self.daily_usd,
+                "openai" => &mut self.openai_usd,
+                _ => {
+                    // Input price of "input/output" USD per million tokens
+                    if field.strip_prefix("price:") == Some(EMBEDDING_MODEL) {
+                        match parse_cap(value.split('/').next().unwrap_or_default()) {
+                            Some(price) => self.price = price,
+                            None => tracing::warn!("Ignoring invalid price '{}' for model '{}'", value, EMBEDDING_MODEL),
+                        }
+                    }
+                    continue;
+                }
+            };
+            if value.trim().is_empty() {
+                *target = None;
+            } else if let Some(cap) = parse_cap(value) {
+                *target = Some(cap);
+            }
+        }
+        self
+    }
+
+    /// Whether an OpenAI call may be made given a day's usage
+    pub fn allows(&self, usage: &HashMap<String, String>) -> bool {
+        let spent = |provider: &str| usage.get(&format!("{}:usd", provider)).and_then(|v| v.parse::<f64>().ok()).unwrap_or(0.0);
+        let total: f64 = PROVIDERS.iter().map(|provider| spent(provider)).sum();
+        self.openai_usd.is_none_or(|cap| spent("openai") < cap)
+            && self.daily_usd.is_none_or(|cap| total < cap)
+    }
+
+    /// Cost of embedding this many tokens, in USD
+    pub fn cost(&self, input_tokens: u64) -> f64 {
+        input_tokens as f64 * self.price / 1_000_000.0
+    }
+}
+
+/// Rough token count for calls that report no usage
+pub fn estimate_tokens(text: &str) -> u64 {
+    text.chars().count().div_ceil(4) as u64
+}
+
+fn usage_key(date: NaiveDate) -> String {
+    format!("um:usage:{}", date.format("%Y-%m-%d"))
+}
+
+/// Checks and records OpenAI embedding calls against the shared budget
+pub struct EmbeddingBudget {
+    redis: Arc<RedisManager>,
+    base: BudgetConfig,
+}
+
+impl EmbeddingBudget {
+    pub fn new(redis: Arc<RedisManager>) -> Self {
+        Self { redis, base: BudgetConfig::from_env() }
+    }
+
+    async fn config(&self) -> BudgetConfig {
+        match self.redis.hgetall(BUDGET_CONFIG_KEY).await {
+            Ok(fields) => self.base.clone().with_overrides(&fields),
+            Err(e) => {
+                tracing::warn!("Failed to load budget config, using environment caps: {}", e);
+                self.base.clone()
+            }
+        }
+    }
+
+    /// Whether today's spend leaves room for another embedding
+    pub async fn allows(&self) -> bool {
+        let config = self.config().await;
+        if config.daily_usd.is_none() && config.openai_usd.is_none() {
+            return true;
+        }
+        match self.redis.hgetall(&usage_key(Utc::now().date_naive())).await {
+            Ok(usage) => config.allows(&usage),
+            Err(e) => {
+                tracing::warn!("Failed to read API usage, allowing embedding: {}", e);
+                true
+            }
+        }
+    }
+
+    /// Count a completed embedding call and its cost
+    pub async fn record(&self, input_tokens: u64) {
+        let cost = self.config().await.cost(input_tokens);
+        let floats = [("openai:usd", cost), ("openai:embedding:usd", cost)];
+        let ints = [("openai:embedding:calls", 1), ("openai:embedding:input_tokens", input_tokens as i64)];
+        if let Err(e) = self.redis.increment_hash(&usage_key(Utc::now().date_naive()), &floats, &ints, USAGE_TTL_SECS).await {
+            tracing::warn!("Failed to record embedding usage: {}", e);
+        }
+    }
+
+    /// Count an embedding that was not made because the budget was spent
+    pub async fn record_skip(&self) {
+        let ints = [("openai:embedding:skipped", 1)];
+        if let Err(e) = self.redis.increment_hash(&usage_key(Utc::now().date_naive()), &[], &ints, USAGE_TTL_SECS).await {
+            tracing::warn!("Failed to record skipped embedding: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(field, value)| (field.to_string(), value.to_string())).collect()
+    }
+
+    fn unlimited() -> BudgetConfig {
+        BudgetConfig { daily_usd: None, openai_usd: None, price: EMBEDDING_PRICE }
+    }
+
+    #[test]
+    fn test_overrides() {
+        let config = BudgetConfig { openai_usd: Some(5.0), ..unlimited() }.with_overrides(&fields(&[
+            ("openai", ""),
+            ("daily", "2.5"),
+            ("groq", "1"),
+            ("price:text-embedding-3-small", "0.04/0"),
+            ("price:other-model", "9"),
+        ]));
+        assert_eq!(config, BudgetConfig { daily_usd: Some(2.5), openai_usd: None, price: 0.04 });
+    }
+
+    #[test]
+    fn test_caps_count_openai_and_overall_spend() {
+        let usage = fields(&[("openai:usd", "0.4"), ("groq:usd", "0.7")]);
+        assert!(unlimited().allows(&usage));
+        assert!(BudgetConfig { openai_usd: Some(0.5), ..unlimited() }.allows(&usage));
+        assert!(!BudgetConfig { openai_usd: Some(0.4), ..unlimited() }.allows(&usage));
+        assert!(!BudgetConfig { daily_usd: Some(1.0), ..unlimited() }.allows(&usage));
+    }
+
+    #[test]
+    fn test_cost() {
+        assert!((unlimited().cost(1_000_000) - 0.02).abs() < 1e-12);
+        assert_eq!(estimate_tokens("abcdefgh"), 2);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+}
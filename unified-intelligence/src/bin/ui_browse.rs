@@ -0,0 +1,311 @@
+//! Interactive memory browser for UnifiedIntelligence.
+//!
+//! Usage: `ui_browse` - browse INSTANCE_ID's thoughts from the configured
+//! storage backend (Redis by default) without an LLM in the loop.
+//!
+//! Type words to search; while typing, the best match and the number of
+//! matches are shown after the cursor. Commands start with `:`:
+//! - `:tag NAME`, `:chain ID`, `:since DATE`, `:until DATE` - narrow searches
+//!   (no argument clears that filter), `:clear` clears all filters
+//! - `:show N` - full text and metadata of result N
+//! - `:open N` - the chain of result N, in order
+//! - `:pin N` - tag result N `pinned` (it then appears in persona snapshots)
+//! - `:export N [PATH]` - write the chain of result N as a chain bundle
+//! - `:reload`, `:help`, `:quit`
+//!
+//! Thoughts are loaded once at start; tiered-out thoughts are read from their
+//! cold segments without moving them back into Redis.
+
+use std::borrow::Cow;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Editor, Helper};
+
+use unified_intelligence::browse::{self, Entry, Filters, Index};
+use unified_intelligence::handlers::ToolHandlers;
+use unified_intelligence::models::{ThoughtMetadata, UiExportChainParams};
+use unified_intelligence::persona::PINNED_TAG;
+use unified_intelligence::report;
+use unified_intelligence::repository::{self, MemoryRepository, Repository, SqliteRepository, StorageBackend};
+use unified_intelligence::search_optimization::SearchCache;
+use unified_intelligence::service::UnifiedIntelligenceService;
+use unified_intelligence::tiering::{self, TierConfig};
+use unified_intelligence::validation::InputValidator;
+
+/// Results listed per search
+const RESULT_LIMIT: usize = 20;
+
+/// Characters typed before search-as-you-type hints appear
+const HINT_MIN_CHARS: usize = 2;
+
+const COMMANDS: &[&str] = &[":tag", ":chain", ":since", ":until", ":clear", ":show", ":open", ":pin", ":export", ":reload", ":help", ":quit"];
+
+/// Loaded thoughts, active filters and the last listed results
+struct Browser {
+    index: Index,
+    filters: Filters,
+    results: Vec<String>, // Thought IDs, numbered from 1
+}
+
+impl Browser {
+    fn prompt(&self) -> String {
+        match self.filters.describe() {
+            filters if filters.is_empty() => "recall> ".to_string(),
+            filters => format!("recall [{}]> ", filters),
+        }
+    }
+
+    /// Thought ID of result N
+    fn result(&self, arg: &str) -> Result<String> {
+        let number: usize = arg.trim().parse().map_err(|_| anyhow::anyhow!("Give a result number, e.g. :show 1"))?;
+        match self.results.get(number.wrapping_sub(1)) {
+            Some(id) => Ok(id.clone()),
+            None => bail!("No result {}; search or :open a chain first", number),
+        }
+    }
+
+    fn list(&mut self, entries: Vec<Entry>) {
+        self.results = entries.iter().take(RESULT_LIMIT).map(|e| e.thought.id.clone()).collect();
+        for (number, entry) in entries.iter().take(RESULT_LIMIT).enumerate() {
+            println!("{:>3}. {}", number + 1, browse::preview(entry));
+        }
+        if entries.len() > RESULT_LIMIT {
+            println!("     {}", format!("... {} more; narrow the search or add a filter", entries.len() - RESULT_LIMIT).dimmed());
+        } else if entries.is_empty() {
+            println!("{}", "No matching thoughts".dimmed());
+        }
+    }
+}
+
+impl Hinter for Browser {
+    type Hint = String;
+
+    fn hint(&self, line: &str, pos: usize, _ctx: &rustyline::Context<'_>) -> Option<String> {
+        if pos < line.len() || line.starts_with(':') || line.trim().chars().count() < HINT_MIN_CHARS {
+            return None;
+        }
+        let hits = self.index.search(line, &self.filters);
+        let best = hits.first()?;
+        Some(format!("   {} match{} · {}", hits.len(), if hits.len() == 1 { "" } else { "es" }, browse::preview(best)))
+    }
+}
+
+impl Highlighter for Browser {
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(hint.dimmed().to_string())
+    }
+}
+
+impl Completer for Browser {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &rustyline::Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let pair = |value: &str| Pair { display: value.to_string(), replacement: value.to_string() };
+        if let Some(prefix) = line.strip_prefix(":tag ") {
+            let tags = self.index.tags().into_iter().filter(|tag| tag.starts_with(prefix)).map(|tag| pair(&tag)).collect();
+            return Ok((":tag ".len(), tags));
+        }
+        if line.starts_with(':') && !line.contains(' ') {
+            return Ok((0, COMMANDS.iter().filter(|c| c.starts_with(line)).map(|c| pair(c)).collect()));
+        }
+        Ok((pos, Vec::new()))
+    }
+}
+
+impl Validator for Browser {}
+
+impl Helper for Browser {}
+
+async fn build(instance: &str) -> Result<(Arc<dyn Repository>, ToolHandlers<dyn Repository>)> {
+    let search_cache = SearchCache::from_env();
+    let search_available = Arc::new(AtomicBool::new(false));
+    let repository: Arc<dyn Repository> = match StorageBackend::from_env() {
+        StorageBackend::Redis => {
+            UnifiedIntelligenceService::redis_repository(instance, None, &search_available, &search_cache, None, None).await?
+        }
+        StorageBackend::Sqlite => Arc::new(SqliteRepository::open(&repository::sqlite_path(), None)?),
+        StorageBackend::Memory => Arc::new(MemoryRepository::new(None)),
+    };
+    let handlers = ToolHandlers::new(
+        repository.clone(),
+        instance.to_string(),
+        None,
+        Arc::new(InputValidator::new()),
+        search_cache,
+        search_available,
+    )?;
+    Ok((repository, handlers))
+}
+
+async fn load(repository: &dyn Repository, instance: &str) -> Result<Index> {
+    let cold_dir = TierConfig::from_env().dir;
+    let mut entries = Vec::new();
+    for mut thought in repository.get_instance_thoughts(instance, tiering::SCAN_LIMIT).await? {
+        if let Some(pointer) = thought.cold.clone() {
+            match tiering::read_thought(&cold_dir, &pointer) {
+                Ok(stored) => thought = tiering::rehydrate(&thought, stored),
+                Err(e) => eprintln!("{}", format!("Showing the stub of thought {}: {}", thought.id, e).dimmed()),
+            }
+        }
+        let metadata = repository.get_thought_metadata(instance, &thought.id).await?;
+        entries.push(Entry {
+            tags: metadata.as_ref().and_then(|m| m.tags.clone()).unwrap_or_default(),
+            category: metadata.as_ref().and_then(|m| m.category.clone()),
+            importance: metadata.as_ref().and_then(|m| m.importance),
+            thought,
+        });
+    }
+    Ok(Index::new(entries))
+}
+
+fn print_help() {
+    println!("Type words to search (matches show as you type), or a command:");
+    println!("  :tag NAME  :chain ID  :since DATE  :until DATE   narrow searches; no argument clears one");
+    println!("  :clear                                          clear all filters");
+    println!("  :show N    :open N    :pin N    :export N [PATH]  act on result N");
+    println!("  :reload    :help      :quit");
+}
+
+fn show(entry: &Entry) {
+    let thought = &entry.thought;
+    println!("{}", format!("Thought {} · {}", thought.id, thought.timestamp).bold());
+    if let Some(chain_id) = &thought.chain_id {
+        println!("chain {} #{} of {}", chain_id, thought.thought_number, thought.total_thoughts);
+    }
+    if !entry.tags.is_empty() {
+        println!("tags: {}", entry.tags.join(", "));
+    }
+    if let Some(category) = &entry.category {
+        println!("category: {}", category);
+    }
+    if let Some(importance) = entry.importance {
+        println!("importance: {}", importance);
+    }
+    println!("\n{}\n", thought.thought);
+}
+
+/// Run one `:command`; returns false to quit
+async fn command(
+    browser: &mut Browser,
+    repository: &dyn Repository,
+    handlers: &ToolHandlers<dyn Repository>,
+    instance: &str,
+    line: &str,
+) -> Result<bool> {
+    let (name, arg) = line.split_once(' ').map(|(n, a)| (n, a.trim())).unwrap_or((line, ""));
+    let arg = Some(arg).filter(|a| !a.is_empty());
+    match name {
+        ":quit" | ":q" | ":exit" => return Ok(false),
+        ":help" | ":h" => print_help(),
+        ":tag" => browser.filters.tag = arg.map(str::to_string),
+        ":chain" => browser.filters.chain_id = arg.map(str::to_string),
+        ":since" => browser.filters.since = arg.map(report::parse_since).transpose()?,
+        ":until" => browser.filters.until = arg.map(browse::parse_until).transpose()?,
+        ":clear" => browser.filters = Filters::default(),
+        ":reload" => {
+            browser.index = load(repository, instance).await?;
+            println!("Loaded {} thoughts", browser.index.len());
+        }
+        ":show" => {
+            let id = browser.result(arg.unwrap_or_default())?;
+            if let Some(entry) = browser.index.get(&id) {
+                show(entry);
+            }
+        }
+        ":open" => {
+            let id = browser.result(arg.unwrap_or_default())?;
+            let Some(chain_id) = browser.index.get(&id).and_then(|e| e.thought.chain_id.clone()) else {
+                bail!("That thought is not part of a chain");
+            };
+            println!("{}", format!("Chain {}", chain_id).bold());
+            let chain: Vec<Entry> = browser.index.chain(&chain_id).into_iter().cloned().collect();
+            browser.list(chain);
+        }
+        ":pin" => {
+            let id = browser.result(arg.unwrap_or_default())?;
+            let mut metadata = repository.get_thought_metadata(instance, &id).await?
+                .unwrap_or_else(|| ThoughtMetadata::new(id.clone(), instance.to_string(), None, None, None, None));
+            let mut tags = metadata.tags.take().unwrap_or_default();
+            if !tags.iter().any(|tag| tag == PINNED_TAG) {
+                tags.push(PINNED_TAG.to_string());
+            }
+            metadata.tags = Some(tags.clone());
+            repository.save_thought_metadata(&metadata).await?;
+            if let Some(entry) = browser.index.get_mut(&id) {
+                entry.tags = tags;
+            }
+            println!("Pinned thought {}", id);
+        }
+        ":export" => {
+            let (number, path) = arg.unwrap_or_default().split_once(' ').map(|(n, p)| (n, Some(p.trim()))).unwrap_or((arg.unwrap_or_default(), None));
+            let id = browser.result(number)?;
+            let Some(chain_id) = browser.index.get(&id).and_then(|e| e.thought.chain_id.clone()) else {
+                bail!("That thought is not part of a chain");
+            };
+            let output_path = path.map(str::to_string).unwrap_or_else(|| format!("{}.chain.json", report::slug(&chain_id, "chain")));
+            let exported = handlers.ui_export_chain(UiExportChainParams {
+                chain_id,
+                output_path: Some(output_path.clone()),
+                privacy_level: None,
+            }).await?;
+            println!("Exported {} thoughts of chain {} to {}", exported.thoughts, exported.chain_id, output_path);
+        }
+        other => bail!("Unknown command '{}'; :help lists them", other),
+    }
+    Ok(true)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_target(false)
+        .with_ansi(false)
+        .with_writer(std::io::stderr)
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env().add_directive(tracing::Level::WARN.into()))
+        .init();
+
+    let instance = std::env::var("INSTANCE_ID").unwrap_or_else(|_| "test".to_string());
+    let (repository, handlers) = build(&instance).await?;
+    let index = load(repository.as_ref(), &instance).await?;
+    println!("{}", format!("Memory of '{}' · {} thoughts · :help for commands", instance, index.len()).bold());
+
+    let mut editor: Editor<Browser, rustyline::history::DefaultHistory> = Editor::new()?;
+    editor.set_helper(Some(Browser { index, filters: Filters::default(), results: Vec::new() }));
+    loop {
+        let prompt = editor.helper().map(Browser::prompt).unwrap_or_default();
+        let line = match editor.readline(&prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+        let Some(browser) = editor.helper_mut() else {
+            break;
+        };
+        if line.starts_with(':') {
+            match command(browser, repository.as_ref(), &handlers, &instance, line).await {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => println!("{}", e.to_string().red()),
+            }
+        } else {
+            let hits: Vec<Entry> = browser.index.search(line, &browser.filters).into_iter().cloned().collect();
+            browser.list(hits);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,153 @@
+//! Clipboard capture daemon for UnifiedIntelligence.
+//!
+//! Usage: `ui_clipboard [run|pause|resume|status]` (default `run`).
+//!
+//! `run` polls the clipboard and stores notable snippets as thoughts tagged
+//! `clipboard` (and `app:{name}` when the frontmost application is known) for
+//! INSTANCE_ID, using the configured storage backend. Capturing is paused
+//! until `ui_clipboard resume`; see the `clipboard` module for the privacy
+//! controls and environment variables. With the SQLite backend, don't run it
+//! while a server uses the same file: neither sees the other's writes.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+
+use unified_intelligence::clipboard::{self, ClipboardConfig, CLIPBOARD_TAG};
+use unified_intelligence::handlers::ToolHandlers;
+use unified_intelligence::models::{Provenance, UiThinkParams};
+use unified_intelligence::provenance;
+use unified_intelligence::repository::{self, MemoryRepository, Repository, SqliteRepository, StorageBackend};
+use unified_intelligence::search_optimization::SearchCache;
+use unified_intelligence::service::UnifiedIntelligenceService;
+use unified_intelligence::validation::InputValidator;
+
+async fn build_handlers(instance: &str) -> Result<ToolHandlers<dyn Repository>> {
+    let search_cache = SearchCache::from_env();
+    let search_available = Arc::new(AtomicBool::new(false));
+    let repository: Arc<dyn Repository> = match StorageBackend::from_env() {
+        StorageBackend::Redis => {
+            UnifiedIntelligenceService::redis_repository(instance, None, &search_available, &search_cache, None, None).await?
+        }
+        StorageBackend::Sqlite => Arc::new(SqliteRepository::open(&repository::sqlite_path(), None)?),
+        StorageBackend::Memory => Arc::new(MemoryRepository::new(None)),
+    };
+    Ok(ToolHandlers::new(
+        repository,
+        instance.to_string(),
+        None,
+        Arc::new(InputValidator::new()),
+        search_cache,
+        search_available,
+    )?)
+}
+
+fn app_tag(app: &str) -> String {
+    format!("app:{}", app.trim().to_lowercase().replace(' ', "-"))
+}
+
+async fn run(config: ClipboardConfig) -> Result<()> {
+    let instance = std::env::var("INSTANCE_ID").unwrap_or_else(|_| "test".to_string());
+    let handlers = build_handlers(&instance).await?;
+    tracing::info!(
+        "Clipboard capture for instance '{}' every {:?} ({})",
+        instance,
+        config.interval,
+        if config.is_active() { "active" } else { "paused; run `ui_clipboard resume`" }
+    );
+
+    // Last clipboard contents seen while active; None right after resuming
+    let mut last: Option<String> = None;
+    let mut interval = tokio::time::interval(config.interval);
+    loop {
+        interval.tick().await;
+        if !config.is_active() {
+            last = None;
+            continue;
+        }
+
+        let command = config.read_command.clone();
+        let app_command = config.app_command.clone();
+        let (text, app) = tokio::task::spawn_blocking(move || {
+            let app = app_command.and_then(|command| clipboard::read_command(&command).ok())
+                .map(|app| app.trim().to_string())
+                .filter(|app| !app.is_empty());
+            (clipboard::read_command(&command), app)
+        })
+        .await?;
+        let text = match text {
+            Ok(text) => text,
+            Err(e) => {
+                tracing::warn!("Cannot read clipboard: {}", e);
+                continue;
+            }
+        };
+
+        let is_baseline = last.is_none();
+        if last.as_deref() == Some(text.as_str()) {
+            continue;
+        }
+        last = Some(text.clone());
+        if is_baseline {
+            continue;
+        }
+
+        if let Err(reason) = config.filter.assess(&text, app.as_deref()) {
+            tracing::debug!("Skipped clipboard snippet: {}", reason);
+            continue;
+        }
+        let mut tags = vec![CLIPBOARD_TAG.to_string()];
+        tags.extend(app.as_deref().map(app_tag));
+        let stored = handlers.ui_think(UiThinkParams {
+            thought: text.trim().to_string(),
+            thought_number: 1,
+            total_thoughts: 1,
+            next_thought_needed: false,
+            chain_id: None,
+            framework: None,
+            importance: None,
+            relevance: None,
+            tags: Some(tags),
+            category: None,
+            provenance: Some(Provenance {
+                author: Some(provenance::AUTHOR_HUMAN.to_string()),
+                source_tool: Some(CLIPBOARD_TAG.to_string()),
+                ..Default::default()
+            }),
+            citations: None,
+            thinking_type: None,
+        }).await;
+        match stored {
+            Ok(response) => tracing::info!("Captured clipboard snippet as thought {}", response.thought_id),
+            Err(e) => tracing::warn!("Failed to store clipboard snippet: {}", e),
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_target(false)
+        .with_ansi(false)
+        .with_writer(std::io::stderr)
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env().add_directive(tracing::Level::INFO.into()))
+        .init();
+
+    let config = ClipboardConfig::from_env().map_err(anyhow::Error::msg)?;
+    let command = std::env::args().nth(1).unwrap_or_else(|| "run".to_string());
+    match command.as_str() {
+        "run" => run(config).await,
+        "pause" | "resume" => {
+            config.set_active(command == "resume")
+                .with_context(|| format!("Cannot update {}", config.state_dir.display()))?;
+            println!("Clipboard capture {}", if command == "resume" { "resumed" } else { "paused" });
+            Ok(())
+        }
+        "status" => {
+            println!("Clipboard capture is {} (state: {})", if config.is_active() { "active" } else { "paused" }, config.state_dir.display());
+            Ok(())
+        }
+        other => bail!("Unknown command '{}'. Use run, pause, resume or status", other),
+    }
+}
@@ -0,0 +1,356 @@
+//! Load generator for UnifiedIntelligence.
+//!
+//! Simulates several instances thinking and recalling concurrently against the
+//! configured storage backend, or replays a captured tool-call log, then
+//! reports p50/p95/p99 latencies per tool and Redis CPU/memory usage.
+//!
+//! Environment:
+//! - `UI_STORAGE_BACKEND` - `redis` (default), `memory` or `sqlite`, as for the server;
+//!   a SQLite file must not be in use by a running server
+//! - `UI_LOAD_INSTANCES` - simulated instances (default 4), named `load_0`, `load_1`, ...
+//! - `UI_LOAD_THINK_RATE` / `UI_LOAD_RECALL_RATE` - calls per second per instance (default 5 / 10)
+//! - `UI_LOAD_SEMANTIC_RATIO` - share of recalls using semantic search, 0.0-1.0 (default 0)
+//! - `UI_LOAD_DURATION_SECS` - how long to generate load (default 30)
+//! - `UI_LOAD_REPLAY` - path to a JSONL capture to replay instead of generating load
+//! - `UI_LOAD_REPLAY_SPEED` - replay speed multiplier for timestamped captures (default 1.0)
+//!
+//! A capture holds one JSON-RPC `tools/call` request per line, as sent by an
+//! MCP client (see test_requests.jsonl). Two optional fields are read from
+//! each line: `instance` (defaults to `replay`) and `timestamp` (RFC 3339);
+//! timestamped lines are replayed at their original spacing, the rest
+//! back to back. Only `ui_think` and `ui_recall` calls are replayed.
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use tokio::task::JoinSet;
+
+use unified_intelligence::handlers::ToolHandlers;
+use unified_intelligence::redis::RedisManager;
+use unified_intelligence::repository::{self, MemoryRepository, Repository, SqliteRepository, StorageBackend};
+use unified_intelligence::search_optimization::SearchCache;
+use unified_intelligence::service::UnifiedIntelligenceService;
+use unified_intelligence::validation::InputValidator;
+
+const TOPICS: &[&str] = &["redis", "rust", "search", "memory", "identity", "embedding", "latency", "schema"];
+const WORDS: &[&str] = &[
+    "cache", "index", "vector", "chain", "thought", "query", "filter", "tenant", "stream", "bloom",
+    "metadata", "feedback", "boost", "recall", "score", "shard", "lua", "script", "pool", "ttl",
+];
+
+type Handlers = Arc<ToolHandlers<dyn Repository>>;
+
+/// Latency samples and failures per tool
+#[derive(Default)]
+struct Recorder {
+    latencies: Mutex<HashMap<&'static str, Vec<Duration>>>,
+    errors: Mutex<HashMap<&'static str, usize>>,
+}
+
+impl Recorder {
+    fn record(&self, tool: &'static str, elapsed: Duration, ok: bool) {
+        self.latencies.lock().unwrap().entry(tool).or_default().push(elapsed);
+        if !ok {
+            *self.errors.lock().unwrap().entry(tool).or_default() += 1;
+        }
+    }
+}
+
+/// Redis INFO figures sampled before and after the run
+struct RedisSample {
+    cpu_seconds: f64,
+    used_memory: u64,
+    peak_memory: u64,
+}
+
+struct Config {
+    instances: usize,
+    think_rate: f64,
+    recall_rate: f64,
+    semantic_ratio: f64,
+    duration: Duration,
+    replay: Option<String>,
+    replay_speed: f64,
+}
+
+impl Config {
+    fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str, default: T) -> T {
+            std::env::var(name).ok().and_then(|value| value.parse().ok()).unwrap_or(default)
+        }
+        Self {
+            instances: var("UI_LOAD_INSTANCES", 4usize).max(1),
+            think_rate: var("UI_LOAD_THINK_RATE", 5.0),
+            recall_rate: var("UI_LOAD_RECALL_RATE", 10.0),
+            semantic_ratio: var("UI_LOAD_SEMANTIC_RATIO", 0.0f64).clamp(0.0, 1.0),
+            duration: Duration::from_secs(var("UI_LOAD_DURATION_SECS", 30u64)),
+            replay: std::env::var("UI_LOAD_REPLAY").ok().filter(|path| !path.is_empty()),
+            replay_speed: var("UI_LOAD_REPLAY_SPEED", 1.0f64).max(0.01),
+        }
+    }
+}
+
+/// Deterministic per-task generator for thought text and queries
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        self.0 >> 33
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.next() as usize % items.len()]
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        (self.next() % 10_000) as f64 / 10_000.0 < probability
+    }
+}
+
+async fn handlers_for(backend: StorageBackend, instance: &str) -> Result<Handlers> {
+    let search_cache = SearchCache::from_env();
+    let search_available = Arc::new(AtomicBool::new(false));
+    let repository: Arc<dyn Repository> = match backend {
+        StorageBackend::Redis => {
+            UnifiedIntelligenceService::redis_repository(instance, None, &search_available, &search_cache, None, None).await?
+        }
+        StorageBackend::Sqlite => Arc::new(SqliteRepository::open(&repository::sqlite_path(), None)?),
+        StorageBackend::Memory => Arc::new(MemoryRepository::new(None)),
+    };
+    Ok(Arc::new(ToolHandlers::new(
+        repository,
+        instance.to_string(),
+        None,
+        Arc::new(InputValidator::new()),
+        search_cache,
+        search_available,
+    )?))
+}
+
+/// Run one tool call and record its latency; unsupported tools are ignored
+async fn call(handlers: &Handlers, recorder: &Recorder, tool: &str, arguments: Value) {
+    let start = Instant::now();
+    let (tool, ok) = match tool {
+        "ui_think" => match serde_json::from_value(arguments) {
+            Ok(params) => ("ui_think", handlers.ui_think(params).await.is_ok()),
+            Err(_) => ("ui_think", false),
+        },
+        "ui_recall" => match serde_json::from_value(arguments) {
+            Ok(params) => ("ui_recall", handlers.ui_recall(params).await.is_ok()),
+            Err(_) => ("ui_recall", false),
+        },
+        _ => return,
+    };
+    recorder.record(tool, start.elapsed(), ok);
+}
+
+fn think_arguments(rng: &mut Lcg, instance: &str, sequence: u64) -> Value {
+    let topic = rng.pick(TOPICS);
+    let words: Vec<&str> = (0..10).map(|_| rng.pick(WORDS)).collect();
+    json!({
+        "thought": format!("{} load {} {}: {}", instance, topic, sequence, words.join(" ")),
+        "thought_number": 1,
+        "total_thoughts": 1,
+        "next_thought_needed": false,
+        "importance": (rng.next() % 10) as i32 + 1,
+        "tags": [topic, "load"],
+    })
+}
+
+fn recall_arguments(rng: &mut Lcg, semantic_ratio: f64) -> Value {
+    let query = format!("{} {}", rng.pick(TOPICS), rng.pick(WORDS));
+    if rng.chance(semantic_ratio) {
+        json!({ "query": query, "limit": 20, "semantic_search": true, "threshold": 0.3 })
+    } else {
+        json!({ "query": query, "limit": 20 })
+    }
+}
+
+/// Fire calls at a fixed rate until the deadline; calls overlap when the backend falls behind
+async fn drive(handlers: Handlers, recorder: Arc<Recorder>, instance: String, tool: &'static str, rate: f64, deadline: Instant, semantic_ratio: f64) {
+    if rate <= 0.0 {
+        return;
+    }
+    let mut ticker = tokio::time::interval(Duration::from_secs_f64(1.0 / rate));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Burst);
+    let mut rng = Lcg(instance.bytes().fold(tool.len() as u64, |seed, byte| seed.wrapping_mul(31).wrapping_add(byte as u64)));
+    let mut calls = JoinSet::new();
+    let mut sequence = 0;
+
+    while Instant::now() < deadline {
+        ticker.tick().await;
+        sequence += 1;
+        let arguments = match tool {
+            "ui_think" => think_arguments(&mut rng, &instance, sequence),
+            _ => recall_arguments(&mut rng, semantic_ratio),
+        };
+        let (handlers, recorder) = (handlers.clone(), recorder.clone());
+        calls.spawn(async move { call(&handlers, &recorder, tool, arguments).await });
+    }
+    while calls.join_next().await.is_some() {}
+}
+
+async fn generate(config: &Config, backend: StorageBackend, recorder: Arc<Recorder>) -> Result<()> {
+    let mut drivers = JoinSet::new();
+    let mut handlers = Vec::new();
+    for index in 0..config.instances {
+        let instance = format!("load_{}", index);
+        handlers.push((handlers_for(backend, &instance).await?, instance));
+    }
+
+    println!(
+        "Generating load: {} instances, {}/s ui_think + {}/s ui_recall each ({:.0}% semantic) for {}s",
+        config.instances, config.think_rate, config.recall_rate, config.semantic_ratio * 100.0, config.duration.as_secs()
+    );
+    let deadline = Instant::now() + config.duration;
+    for (handlers, instance) in handlers {
+        drivers.spawn(drive(handlers.clone(), recorder.clone(), instance.clone(), "ui_think", config.think_rate, deadline, 0.0));
+        drivers.spawn(drive(handlers, recorder.clone(), instance, "ui_recall", config.recall_rate, deadline, config.semantic_ratio));
+    }
+    while drivers.join_next().await.is_some() {}
+    Ok(())
+}
+
+async fn replay(path: &str, speed: f64, backend: StorageBackend, recorder: Arc<Recorder>) -> Result<()> {
+    let capture = std::fs::read_to_string(path).with_context(|| format!("reading capture {}", path))?;
+    let mut entries = Vec::new();
+    for (number, line) in capture.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        let request: Value = serde_json::from_str(line).with_context(|| format!("{}:{} is not JSON", path, number + 1))?;
+        if request["method"] != "tools/call" {
+            continue;
+        }
+        let instance = request["instance"].as_str().unwrap_or("replay").to_string();
+        let timestamp = request["timestamp"].as_str()
+            .and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok());
+        let tool = request["params"]["name"].as_str().unwrap_or_default().to_string();
+        let arguments = request["params"]["arguments"].clone();
+        entries.push((instance, timestamp, tool, arguments));
+    }
+
+    let mut handlers: HashMap<String, Handlers> = HashMap::new();
+    for (instance, ..) in &entries {
+        if !handlers.contains_key(instance) {
+            handlers.insert(instance.clone(), handlers_for(backend, instance).await?);
+        }
+    }
+    println!("Replaying {} tool calls from {} across {} instances", entries.len(), path, handlers.len());
+
+    let origin = entries.iter().find_map(|(_, timestamp, ..)| *timestamp);
+    let start = Instant::now();
+    let mut calls = JoinSet::new();
+    for (instance, timestamp, tool, arguments) in entries {
+        let handlers = handlers[&instance].clone();
+        match (origin, timestamp) {
+            (Some(origin), Some(timestamp)) => {
+                // Keep the captured spacing (scaled) and let calls overlap like production
+                let offset = (timestamp - origin).to_std().unwrap_or_default().div_f64(speed);
+                tokio::time::sleep_until((start + offset).into()).await;
+                let recorder = recorder.clone();
+                calls.spawn(async move { call(&handlers, &recorder, &tool, arguments).await });
+            }
+            _ => call(&handlers, &recorder, &tool, arguments).await,
+        }
+    }
+    while calls.join_next().await.is_some() {}
+    Ok(())
+}
+
+async fn redis_sample(redis: &RedisManager) -> Result<RedisSample> {
+    let mut conn = redis.get_connection().await?;
+    let info: String = redis::cmd("INFO").arg("cpu").arg("memory").query_async(&mut *conn).await?;
+    let field = |name: &str| {
+        info.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .unwrap_or(0.0)
+    };
+    Ok(RedisSample {
+        cpu_seconds: field("used_cpu_sys") + field("used_cpu_user"),
+        used_memory: field("used_memory") as u64,
+        peak_memory: field("used_memory_peak") as u64,
+    })
+}
+
+fn percentile(sorted: &[Duration], pct: f64) -> Duration {
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn report(recorder: &Recorder, elapsed: Duration, redis: Option<(RedisSample, RedisSample)>) {
+    println!("\n=== Load test report ({:.1}s) ===", elapsed.as_secs_f64());
+    println!("{:<10} {:>8} {:>7} {:>9} {:>9} {:>9} {:>9} {:>9}", "tool", "calls", "errors", "rps", "p50 ms", "p95 ms", "p99 ms", "max ms");
+
+    let latencies = recorder.latencies.lock().unwrap();
+    let errors = recorder.errors.lock().unwrap();
+    let mut tools: Vec<_> = latencies.keys().copied().collect();
+    tools.sort();
+    for tool in tools {
+        let mut samples = latencies[tool].clone();
+        samples.sort();
+        println!(
+            "{:<10} {:>8} {:>7} {:>9.1} {:>9.2} {:>9.2} {:>9.2} {:>9.2}",
+            tool,
+            samples.len(),
+            errors.get(tool).copied().unwrap_or(0),
+            samples.len() as f64 / elapsed.as_secs_f64(),
+            millis(percentile(&samples, 50.0)),
+            millis(percentile(&samples, 95.0)),
+            millis(percentile(&samples, 99.0)),
+            millis(*samples.last().unwrap()),
+        );
+    }
+
+    if let Some((before, after)) = redis {
+        let mib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+        println!("\nRedis CPU: {:.1}% of one core ({:.2}s)",
+            (after.cpu_seconds - before.cpu_seconds) / elapsed.as_secs_f64() * 100.0,
+            after.cpu_seconds - before.cpu_seconds);
+        println!("Redis memory: {:.1} MiB -> {:.1} MiB (peak {:.1} MiB)",
+            mib(before.used_memory), mib(after.used_memory), mib(after.peak_memory));
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_target(false)
+        .with_ansi(false)
+        .with_writer(std::io::stderr)
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env().add_directive(tracing::Level::WARN.into()))
+        .init();
+
+    let config = Config::from_env();
+    let backend = StorageBackend::from_env();
+    let redis = match backend {
+        StorageBackend::Redis => Some(RedisManager::new().await?),
+        StorageBackend::Sqlite | StorageBackend::Memory => None,
+    };
+
+    let recorder = Arc::new(Recorder::default());
+    let before = match &redis {
+        Some(redis) => Some(redis_sample(redis).await?),
+        None => None,
+    };
+    let start = Instant::now();
+    match &config.replay {
+        Some(path) => replay(path, config.replay_speed, backend, recorder.clone()).await?,
+        None => generate(&config, backend, recorder.clone()).await?,
+    }
+    let elapsed = start.elapsed();
+    let after = match &redis {
+        Some(redis) => Some(redis_sample(redis).await?),
+        None => None,
+    };
+
+    report(&recorder, elapsed, before.zip(after));
+    Ok(())
+}
@@ -0,0 +1,311 @@
+//! Bookmark and highlight imports for ui_import_bookmarks.
+//!
+//! Two formats are understood:
+//!
+//! - `bookmarks`: the Netscape bookmarks HTML every browser exports. Folder
+//!   names and the `TAGS` attribute become tags, `ADD_DATE` the timestamp and
+//!   `<DD>` descriptions the excerpt.
+//! - `highlights`: read-later JSON exports, either an array of documents or an
+//!   object holding one under `results`, `documents`, `items` or `books`. Each
+//!   document has a `url` (or `source_url`/`link`), `title`, `tags` and
+//!   `highlights`, where highlights and tags are strings or objects with
+//!   `text`/`note` and `name`.
+//!
+//! Items become [`CapturedItem`]s so they share ui_capture's dedup set: a URL
+//! already captured from a feed is not imported again. Items on the same
+//! domain are appended to a `web:{domain}` chain and tagged `domain:{domain}`,
+//! and shared folders or tags link items by topic.
+
+use chrono::{DateTime, TimeZone, Utc};
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::capture::{collapse_whitespace, tag_from, CapturedItem};
+
+/// Longest excerpt kept per item, so highlight-heavy documents stay under the thought limit
+const MAX_EXCERPT_CHARS: usize = 8000;
+
+/// Parse an export; `format` is "bookmarks", "highlights" or None to detect from the content
+pub fn parse(content: &str, format: Option<&str>) -> Result<(&'static str, Vec<CapturedItem>), String> {
+    let format = match format {
+        Some("bookmarks") => "bookmarks",
+        Some("highlights") => "highlights",
+        Some(other) => return Err(format!("Unknown format '{}'. Use 'bookmarks' or 'highlights'", other)),
+        None if content.trim_start().starts_with(['[', '{']) => "highlights",
+        None => "bookmarks",
+    };
+    let mut items = match format {
+        "highlights" => parse_highlights(content)?,
+        _ => parse_bookmarks_html(content),
+    };
+    // Oldest first, so domain chains read chronologically
+    items.sort_by(|a, b| a.published.cmp(&b.published));
+    Ok((format, items))
+}
+
+/// Chain that items from one domain are appended to
+pub fn domain_chain_id(domain: &str) -> String {
+    format!("web:{}", domain)
+}
+
+/// Host of a URL without `www.`, port or credentials
+pub fn domain(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once("://")?;
+    let host = rest.split(['/', '?', '#']).next()?;
+    let host = host.rsplit('@').next()?.split(':').next()?.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host).to_string();
+    (!host.is_empty()).then_some(host)
+}
+
+fn item(source: &'static str, id: String, title: &str, url: Option<&str>, excerpt: String, mut tags: Vec<String>, published: Option<String>) -> CapturedItem {
+    let domain = url.and_then(domain);
+    tags.insert(0, source.to_string());
+    if let Some(domain) = &domain {
+        tags.push(format!("domain:{}", domain));
+    }
+    tags.dedup();
+    let title = collapse_whitespace(title);
+    CapturedItem {
+        id,
+        source,
+        title: if title.is_empty() { url.unwrap_or("Untitled").to_string() } else { title },
+        excerpt: excerpt.chars().take(MAX_EXCERPT_CHARS).collect(),
+        link: url.map(str::to_string),
+        tags,
+        published,
+        chain_id: domain.as_deref().map(domain_chain_id),
+        git_commit: None,
+    }
+}
+
+/// Bookmarks from a Netscape bookmarks HTML export
+fn parse_bookmarks_html(html: &str) -> Vec<CapturedItem> {
+    let tag_re = Regex::new(r"(?i)<(/?)([a-z0-9]+)([^>]*)>").expect("valid regex");
+    let tags: Vec<_> = tag_re.captures_iter(html).collect();
+
+    let mut items = Vec::new();
+    let mut folders: Vec<Option<String>> = Vec::new();
+    let mut pending_folder = None;
+    let mut last_item = None;
+    for (index, caps) in tags.iter().enumerate() {
+        let end = caps.get(0).map_or(0, |m| m.end());
+        let next = tags.get(index + 1).and_then(|next| next.get(0)).map_or(html.len(), |m| m.start());
+        let text = decode_entities(html[end..next].trim());
+        let closing = !caps[1].is_empty();
+
+        match (closing, caps[2].to_ascii_uppercase().as_str()) {
+            (false, "H3") => {
+                pending_folder = Some(text);
+                last_item = None;
+            }
+            (false, "DL") => folders.push(pending_folder.take()),
+            (true, "DL") => {
+                folders.pop();
+            }
+            (false, "A") => {
+                last_item = None;
+                let Some(href) = attribute(&caps[3], "HREF").filter(|href| href.contains("://")) else {
+                    continue;
+                };
+                let mut item_tags: Vec<String> = folders.iter().flatten().map(|folder| tag_from(folder)).collect();
+                if let Some(extra) = attribute(&caps[3], "TAGS") {
+                    item_tags.extend(extra.split(',').map(tag_from).filter(|tag| !tag.is_empty()));
+                }
+                let published = attribute(&caps[3], "ADD_DATE")
+                    .and_then(|seconds| seconds.parse::<i64>().ok())
+                    .and_then(|seconds| Utc.timestamp_opt(seconds, 0).single())
+                    .map(|date| date.to_rfc3339());
+                items.push(item("bookmark", href.clone(), &text, Some(&href), String::new(), item_tags, published));
+                last_item = Some(items.len() - 1);
+            }
+            (false, "DD") => {
+                if let Some(item) = last_item.take().and_then(|i| items.get_mut(i)) {
+                    item.excerpt = collapse_whitespace(&text).chars().take(MAX_EXCERPT_CHARS).collect();
+                }
+            }
+            _ => {}
+        }
+    }
+    items
+}
+
+/// Value of a double-quoted attribute, matched case-insensitively
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    // ASCII uppercasing keeps byte offsets, so positions carry over to the original
+    let upper = attributes.to_ascii_uppercase();
+    let start = upper.find(&format!("{}=\"", name))? + name.len() + 2;
+    let end = start + attributes[start..].find('"')?;
+    Some(decode_entities(&attributes[start..end]))
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HighlightExport {
+    Documents(Vec<HighlightDocument>),
+    Wrapped {
+        #[serde(alias = "documents", alias = "items", alias = "books")]
+        results: Vec<HighlightDocument>,
+    },
+}
+
+#[derive(Deserialize)]
+struct HighlightDocument {
+    #[serde(alias = "source_url", alias = "link")]
+    url: Option<String>,
+    title: Option<String>,
+    #[serde(default)]
+    tags: Vec<NamedTag>,
+    #[serde(default)]
+    highlights: Vec<Highlight>,
+    #[serde(alias = "created_at", alias = "saved_at")]
+    date: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Highlight {
+    Text(String),
+    Annotated {
+        text: String,
+        note: Option<String>,
+        #[serde(default)]
+        tags: Vec<NamedTag>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NamedTag {
+    Name(String),
+    Object { name: String },
+}
+
+impl NamedTag {
+    fn tag(&self) -> String {
+        match self {
+            NamedTag::Name(name) | NamedTag::Object { name } => tag_from(name),
+        }
+    }
+}
+
+/// Documents from a read-later highlights JSON export
+fn parse_highlights(json: &str) -> Result<Vec<CapturedItem>, String> {
+    let export: HighlightExport = serde_json::from_str(json)
+        .map_err(|e| format!("Not a highlights export (expected an array of documents): {}", e))?;
+    let documents = match export {
+        HighlightExport::Documents(documents) | HighlightExport::Wrapped { results: documents } => documents,
+    };
+
+    Ok(documents.into_iter().filter_map(|document| {
+        let title = document.title.unwrap_or_default();
+        let id = match &document.url {
+            Some(url) => format!("hl:{}", url),
+            None if !title.trim().is_empty() => format!("hl:title:{}", collapse_whitespace(&title)),
+            None => return None,
+        };
+
+        let mut tags: Vec<String> = document.tags.iter().map(NamedTag::tag).collect();
+        let mut quotes = Vec::with_capacity(document.highlights.len());
+        for highlight in &document.highlights {
+            let (text, note) = match highlight {
+                Highlight::Text(text) => (text, None),
+                Highlight::Annotated { text, note, tags: highlight_tags } => {
+                    tags.extend(highlight_tags.iter().map(NamedTag::tag));
+                    (text, note.as_deref().filter(|note| !note.trim().is_empty()))
+                }
+            };
+            let mut quote = format!("> {}", collapse_whitespace(text));
+            if let Some(note) = note {
+                quote.push_str(&format!("\nNote: {}", collapse_whitespace(note)));
+            }
+            quotes.push(quote);
+        }
+        tags.retain(|tag| !tag.is_empty());
+        tags.sort();
+        tags.dedup();
+
+        let published = document.date.as_ref().and_then(parse_date);
+        Some(item("highlight", id, &title, document.url.as_deref(), quotes.join("\n\n"), tags, published))
+    }).collect())
+}
+
+/// RFC 3339 string or Unix seconds, normalized to UTC so dates sort as strings
+fn parse_date(value: &serde_json::Value) -> Option<String> {
+    let date = match value {
+        serde_json::Value::String(date) => DateTime::parse_from_rfc3339(date).ok()?.with_timezone(&Utc),
+        serde_json::Value::Number(seconds) => Utc.timestamp_opt(seconds.as_i64()?, 0).single()?,
+        _ => return None,
+    };
+    Some(date.to_rfc3339())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOOKMARKS: &str = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=UTF-8">
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
+<DL><p>
+    <DT><H3 ADD_DATE="1700000000">Redis Docs</H3>
+    <DL><p>
+        <DT><A HREF="https://www.redis.io/docs/lua" ADD_DATE="1700000100" TAGS="lua,Scripting">Lua &amp; EVALSHA</A>
+        <DD>Script caching notes
+        <DT><A HREF="https://redis.io/docs/bloom" ADD_DATE="1700000050">Bloom filters</A>
+    </DL><p>
+    <DT><A HREF="javascript:void(0)">Bookmarklet</A>
+    <DT><A HREF="https://blog.rust-lang.org/" ADD_DATE="1600000000">Rust Blog</A>
+</DL><p>"#;
+
+    #[test]
+    fn test_netscape_bookmarks() {
+        let (format, items) = parse(BOOKMARKS, None).unwrap();
+        assert_eq!(format, "bookmarks");
+        let titles: Vec<&str> = items.iter().map(|item| item.title.as_str()).collect();
+        assert_eq!(titles, vec!["Rust Blog", "Bloom filters", "Lua & EVALSHA"]);
+
+        let lua = &items[2];
+        assert_eq!(lua.excerpt, "Script caching notes");
+        assert_eq!(lua.tags, vec!["bookmark", "redis-docs", "lua", "scripting", "domain:redis.io"]);
+        assert_eq!(lua.chain_id.as_deref(), Some("web:redis.io"));
+        assert_eq!(items[1].chain_id, lua.chain_id);
+        assert_eq!(items[0].tags, vec!["bookmark", "domain:blog.rust-lang.org"]);
+        assert!(items[0].published.as_deref().unwrap().starts_with("2020-09-13"));
+    }
+
+    #[test]
+    fn test_highlights_json() {
+        let json = r#"{"results": [
+            {"title": "Designing key schemas", "source_url": "https://example.com/keys", "tags": [{"name": "Redis"}],
+             "created_at": "2025-07-01T10:00:00+02:00",
+             "highlights": [{"text": "Prefix every key", "note": "we do this", "tags": ["schema"]}, "Version your keys"]},
+            {"title": "Untitled note"},
+            {"highlights": ["orphan"]}
+        ]}"#;
+        let (format, items) = parse(json, None).unwrap();
+        assert_eq!(format, "highlights");
+        assert_eq!(items.len(), 2);
+
+        let keys = items.iter().find(|item| item.id == "hl:https://example.com/keys").unwrap();
+        assert_eq!(keys.excerpt, "> Prefix every key\nNote: we do this\n\n> Version your keys");
+        assert_eq!(keys.tags, vec!["highlight", "redis", "schema", "domain:example.com"]);
+        assert_eq!(keys.published.as_deref(), Some("2025-07-01T08:00:00+00:00"));
+        assert!(parse("[1, 2]", Some("highlights")).is_err());
+        assert!(parse("", Some("pdf")).is_err());
+    }
+
+    #[test]
+    fn test_domain() {
+        assert_eq!(domain("https://user@www.Example.com:8080/a?b#c").as_deref(), Some("example.com"));
+        assert_eq!(domain("not a url"), None);
+    }
+}
@@ -0,0 +1,173 @@
+//! Segmentation of free-form brain dumps into chain thoughts.
+//!
+//! The heuristic splits on blank lines and list items, then packs the
+//! sentences of long paragraphs into segments of at most `max_chars`. When
+//! UI_BRAINDUMP_SEGMENTER names a command, `llm` segmentation pipes the text to
+//! it on stdin and expects a JSON array of strings on stdout (typically a small
+//! script calling a model); failures fall back to the heuristic.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Default upper bound for a heuristic segment, in bytes
+pub const DEFAULT_MAX_SEGMENT_CHARS: usize = 600;
+
+/// Split text into thought-sized segments using paragraph and sentence boundaries
+pub fn segment(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut segments = Vec::new();
+    for block in blocks(text) {
+        if block.len() <= max_chars {
+            segments.push(block);
+            continue;
+        }
+        let mut current = String::new();
+        for sentence in sentences(&block) {
+            if !current.is_empty() && current.len() + 1 + sentence.len() > max_chars {
+                segments.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(&sentence);
+        }
+        if !current.is_empty() {
+            segments.push(current);
+        }
+    }
+    segments
+}
+
+/// Paragraphs and list items, whitespace-normalized
+fn blocks(text: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut flush = |current: &mut Vec<&str>| {
+        if !current.is_empty() {
+            blocks.push(current.join(" "));
+            current.clear();
+        }
+    };
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            flush(&mut current);
+        } else if let Some(item) = list_item(line) {
+            flush(&mut current);
+            current.push(item);
+            flush(&mut current);
+        } else {
+            current.push(line);
+        }
+    }
+    flush(&mut current);
+    blocks.retain(|block| !block.trim().is_empty());
+    blocks
+}
+
+/// Text of a bullet (`-`, `*`, `•`) or numbered (`1.`, `2)`) list item
+fn list_item(line: &str) -> Option<&str> {
+    for bullet in ["- ", "* ", "• "] {
+        if let Some(item) = line.strip_prefix(bullet) {
+            return Some(item.trim());
+        }
+    }
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        let rest = &line[digits..];
+        if let Some(item) = rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") ")) {
+            return Some(item.trim());
+        }
+    }
+    None
+}
+
+/// Sentences ending in `.`, `!` or `?` followed by whitespace; a remainder counts as one
+fn sentences(block: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = block.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let at_boundary = matches!(c, '.' | '!' | '?')
+            && chars.peek().is_some_and(|(_, next)| next.is_whitespace());
+        if at_boundary {
+            let end = i + c.len_utf8();
+            sentences.push(block[start..end].trim().to_string());
+            start = end;
+        }
+    }
+    if !block[start..].trim().is_empty() {
+        sentences.push(block[start..].trim().to_string());
+    }
+    sentences.retain(|sentence| !sentence.is_empty());
+    sentences
+}
+
+/// External segmenter command from UI_BRAINDUMP_SEGMENTER
+pub fn segmenter_command() -> Option<String> {
+    std::env::var("UI_BRAINDUMP_SEGMENTER").ok().filter(|cmd| !cmd.trim().is_empty())
+}
+
+/// Run the external segmenter (via `sh -c`) and parse its JSON array of segments
+pub fn segment_with_command(command: &str, text: &str) -> Result<Vec<String>, String> {
+    let mut child = Command::new("sh")
+        .args(["-c", command])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to start segmenter: {}", e))?;
+    child.stdin.take()
+        .ok_or("segmenter stdin unavailable")?
+        .write_all(text.as_bytes())
+        .map_err(|e| format!("failed to write to segmenter: {}", e))?;
+    let output = child.wait_with_output().map_err(|e| format!("segmenter failed: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("segmenter exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    let segments: Vec<String> = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("segmenter output is not a JSON array of strings: {}", e))?;
+    let segments: Vec<String> = segments.into_iter()
+        .map(|segment| segment.trim().to_string())
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    if segments.is_empty() {
+        return Err("segmenter returned no segments".to_string());
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paragraphs_and_list_items_become_segments() {
+        let text = "Redis keys need a schema.\nThey drift otherwise.\n\nTodo:\n- benchmark recall\n2. add a load test\n";
+        assert_eq!(segment(text, DEFAULT_MAX_SEGMENT_CHARS), vec![
+            "Redis keys need a schema. They drift otherwise.",
+            "Todo:",
+            "benchmark recall",
+            "add a load test",
+        ]);
+    }
+
+    #[test]
+    fn test_long_paragraphs_split_at_sentences() {
+        let text = "First point about caching. Second point, v1.2 matters! Third point? Trailing thought";
+        let segments = segment(text, 40);
+        assert_eq!(segments, vec![
+            "First point about caching.",
+            "Second point, v1.2 matters! Third point?",
+            "Trailing thought",
+        ]);
+        assert!(segment("  \n\n ", 40).is_empty());
+    }
+
+    #[test]
+    fn test_segmenter_command_output() {
+        let segments = segment_with_command(r#"cat > /dev/null; echo '["one", " two ", ""]'"#, "text").unwrap();
+        assert_eq!(segments, vec!["one", "two"]);
+        assert!(segment_with_command("echo not-json", "text").is_err());
+    }
+}
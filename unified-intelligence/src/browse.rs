@@ -0,0 +1,219 @@
+//! Search and filters behind the ui_browse REPL.
+//!
+//! ui_browse loads an instance's thoughts and their tags once, then searches
+//! them in memory so results can follow every keystroke. A query matches a
+//! thought when each of its words appears in the text, a tag, the category or
+//! the chain ID; thoughts with more matches rank first and ties go to the
+//! newest. Filters by tag, chain and date narrow every search until cleared.
+
+use chrono::{DateTime, Utc};
+
+use crate::models::ThoughtRecord;
+use crate::report;
+
+/// Characters of a thought shown in result lists
+pub const PREVIEW_CHARS: usize = 90;
+
+/// A thought with the metadata the browser searches and shows
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub thought: ThoughtRecord,
+    pub tags: Vec<String>,
+    pub category: Option<String>,
+    pub importance: Option<i32>,
+}
+
+impl Entry {
+    fn stored_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.thought.timestamp).ok().map(|t| t.with_timezone(&Utc))
+    }
+}
+
+/// Filters applied to every search
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filters {
+    pub tag: Option<String>,
+    pub chain_id: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>, // Exclusive
+}
+
+impl Filters {
+    pub fn is_empty(&self) -> bool {
+        *self == Filters::default()
+    }
+
+    pub fn matches(&self, entry: &Entry) -> bool {
+        if self.tag.as_ref().is_some_and(|tag| !entry.tags.contains(tag)) {
+            return false;
+        }
+        if self.chain_id.is_some() && entry.thought.chain_id != self.chain_id {
+            return false;
+        }
+        if self.since.is_none() && self.until.is_none() {
+            return true;
+        }
+        let Some(stored) = entry.stored_at() else {
+            return false;
+        };
+        self.since.is_none_or(|since| stored >= since) && self.until.is_none_or(|until| stored < until)
+    }
+
+    /// Active filters as `tag:redis chain:c1 since:2025-07-01`
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(tag) = &self.tag {
+            parts.push(format!("tag:{}", tag));
+        }
+        if let Some(chain_id) = &self.chain_id {
+            parts.push(format!("chain:{}", chain_id));
+        }
+        if let Some(since) = self.since {
+            parts.push(format!("since:{}", since.format("%Y-%m-%d")));
+        }
+        if let Some(until) = self.until {
+            parts.push(format!("until:{}", (until - chrono::Duration::days(1)).format("%Y-%m-%d")));
+        }
+        parts.join(" ")
+    }
+}
+
+/// End of an `until` filter: the day after a date, or the timestamp itself
+pub fn parse_until(value: &str) -> crate::error::Result<DateTime<Utc>> {
+    let start = report::parse_since(value)?;
+    Ok(if value.trim().len() == 10 { start + chrono::Duration::days(1) } else { start })
+}
+
+/// Every thought of an instance, newest first
+#[derive(Debug, Default)]
+pub struct Index {
+    entries: Vec<Entry>,
+}
+
+impl Index {
+    pub fn new(mut entries: Vec<Entry>) -> Self {
+        entries.sort_by(|a, b| b.thought.timestamp.cmp(&a.thought.timestamp));
+        Self { entries }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, thought_id: &str) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.thought.id == thought_id)
+    }
+
+    pub fn get_mut(&mut self, thought_id: &str) -> Option<&mut Entry> {
+        self.entries.iter_mut().find(|e| e.thought.id == thought_id)
+    }
+
+    /// Distinct tags, for completion
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self.entries.iter().flat_map(|e| e.tags.iter().cloned()).collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    /// Matching thoughts, best first; an empty query lists the filtered thoughts newest first
+    pub fn search(&self, query: &str, filters: &Filters) -> Vec<&Entry> {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let mut hits: Vec<(usize, &Entry)> = self.entries.iter()
+            .filter(|entry| filters.matches(entry))
+            .filter_map(|entry| score(&terms, entry).map(|score| (score, entry)))
+            .collect();
+        hits.sort_by_key(|(score, _)| std::cmp::Reverse(*score)); // Stable, so ties stay newest first
+        hits.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    /// Thoughts of a chain in order
+    pub fn chain(&self, chain_id: &str) -> Vec<&Entry> {
+        let mut thoughts: Vec<&Entry> = self.entries.iter().filter(|e| e.thought.chain_id.as_deref() == Some(chain_id)).collect();
+        thoughts.sort_by_key(|e| e.thought.thought_number);
+        thoughts
+    }
+}
+
+/// Matches of every term, or None when a term matches nothing
+fn score(terms: &[String], entry: &Entry) -> Option<usize> {
+    let text = entry.thought.thought.to_lowercase();
+    let tags: Vec<String> = entry.tags.iter().map(|t| t.to_lowercase()).collect();
+    let category = entry.category.as_deref().unwrap_or_default().to_lowercase();
+    let chain = entry.thought.chain_id.as_deref().unwrap_or_default().to_lowercase();
+    let mut total = 0;
+    for term in terms {
+        // Tag hits weigh more than words in passing
+        let hits = text.matches(term.as_str()).count()
+            + 3 * tags.iter().filter(|tag| tag.contains(term.as_str())).count()
+            + usize::from(category.contains(term.as_str()))
+            + usize::from(chain.contains(term.as_str()));
+        if hits == 0 {
+            return None;
+        }
+        total += hits;
+    }
+    Some(total)
+}
+
+/// One result line: date, chain position, tags and the start of the text
+pub fn preview(entry: &Entry) -> String {
+    let date = entry.thought.timestamp.get(..10).unwrap_or(&entry.thought.timestamp);
+    let position = match &entry.thought.chain_id {
+        Some(chain_id) => format!(" {}#{}", chain_id, entry.thought.thought_number),
+        None => String::new(),
+    };
+    let tags = if entry.tags.is_empty() { String::new() } else { format!(" [{}]", entry.tags.join(", ")) };
+    format!("{}{}{} {}", date, position, tags, crate::review::snippet(&entry.thought.thought, PREVIEW_CHARS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(text: &str, chain: Option<&str>, tags: &[&str], timestamp: &str) -> Entry {
+        let mut thought = ThoughtRecord::new("CC".to_string(), text.to_string(), 1, 1, chain.map(str::to_string), false);
+        thought.timestamp = timestamp.to_string();
+        Entry { thought, tags: tags.iter().map(|t| t.to_string()).collect(), category: None, importance: None }
+    }
+
+    fn index() -> Index {
+        Index::new(vec![
+            entry("Redis SCAN beats KEYS", Some("c1"), &["redis"], "2025-07-10T09:00:00+00:00"),
+            entry("Lunch with the team", None, &[], "2025-07-12T12:00:00+00:00"),
+            entry("Pipeline the redis writes, redis is fast", Some("c2"), &[], "2025-07-14T09:00:00+00:00"),
+        ])
+    }
+
+    #[test]
+    fn test_search_ranks_and_filters() {
+        let index = index();
+        let texts = |hits: Vec<&Entry>| hits.iter().map(|e| e.thought.thought.clone()).collect::<Vec<_>>();
+
+        // The tagged thought outranks two mentions in passing; every word must match
+        assert_eq!(texts(index.search("redis", &Filters::default())), vec!["Redis SCAN beats KEYS", "Pipeline the redis writes, redis is fast"]);
+        assert!(index.search("redis lunch", &Filters::default()).is_empty());
+        assert_eq!(index.search("", &Filters::default()).len(), 3);
+
+        let recent = Filters { since: Some(report::parse_since("2025-07-11").unwrap()), ..Default::default() };
+        assert_eq!(texts(index.search("redis", &recent)), vec!["Pipeline the redis writes, redis is fast"]);
+        let early = Filters { until: Some(parse_until("2025-07-10").unwrap()), ..Default::default() };
+        assert_eq!(index.search("", &early).len(), 1);
+        let tagged = Filters { tag: Some("redis".to_string()), chain_id: Some("c1".to_string()), ..Default::default() };
+        assert_eq!(index.search("", &tagged).len(), 1);
+        assert_eq!(tagged.describe(), "tag:redis chain:c1");
+        assert_eq!(early.describe(), "until:2025-07-10");
+    }
+
+    #[test]
+    fn test_preview() {
+        let index = index();
+        let hit = index.search("scan", &Filters::default())[0];
+        assert_eq!(preview(hit), "2025-07-10 c1#1 [redis] Redis SCAN beats KEYS");
+        assert_eq!(index.tags(), vec!["redis".to_string()]);
+    }
+}
@@ -0,0 +1,210 @@
+//! Metadata edits applied to many thoughts at once by ui_bulk_update.
+//!
+//! A bulk update applies one edit (tags added or removed, a category set or
+//! cleared, importance set or shifted) to every thought matching a query and
+//! the ui_browse filters. Before anything is saved, the metadata of each
+//! thought the edit changes is recorded before and after in
+//! `{instance}:bulk_undo`; the new metadata is then saved in batches. Reverting
+//! restores the recorded metadata, except on thoughts edited again since,
+//! which are left alone and reported. Dry runs report the same changes
+//! without recording or saving anything.
+
+use crate::embedding_version::DEFAULT_IMPORTANCE;
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::{BulkChange, ThoughtMetadata, ThoughtRecord};
+
+/// Thoughts saved per batch when no batch size is given
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// Undo records kept per instance; older updates can no longer be reverted
+pub const MAX_UNDO_RECORDS: usize = 20;
+
+/// Changes listed in a response
+pub const PREVIEW_CHANGES: usize = 20;
+
+/// How long a revert holds its claim on an undo record; a revert that dies is retryable after this
+pub const CLAIM_TTL_SECONDS: u64 = 300;
+
+/// Characters of a thought shown with its change
+const SNIPPET_CHARS: usize = 80;
+
+const MIN_IMPORTANCE: i32 = 1;
+const MAX_IMPORTANCE: i32 = 10;
+
+/// One metadata edit applied to every matching thought
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Edit {
+    add_tags: Vec<String>,
+    remove_tags: Vec<String>,
+    category: Option<Option<String>>, // Some(None) clears the category
+    importance: Option<i32>,
+    importance_delta: Option<i32>,
+}
+
+impl Edit {
+    pub fn new(
+        add_tags: Option<Vec<String>>,
+        remove_tags: Option<Vec<String>>,
+        category: Option<&str>,
+        importance: Option<i32>,
+        importance_delta: Option<i32>,
+    ) -> Result<Self> {
+        let invalid = |field: &str, reason: &str| UnifiedIntelligenceError::Validation {
+            field: field.to_string(),
+            reason: reason.to_string(),
+        };
+        let edit = Self {
+            add_tags: clean_tags(add_tags),
+            remove_tags: clean_tags(remove_tags),
+            category: category.map(|c| Some(c.trim().to_string()).filter(|c| !c.is_empty())),
+            importance,
+            importance_delta: importance_delta.filter(|delta| *delta != 0),
+        };
+        if let Some(tag) = edit.add_tags.iter().find(|tag| edit.remove_tags.contains(tag)) {
+            return Err(invalid("remove_tags", &format!("'{}' is both added and removed", tag)));
+        }
+        if edit.importance.is_some_and(|i| !(MIN_IMPORTANCE..=MAX_IMPORTANCE).contains(&i)) {
+            return Err(invalid("importance", "Importance must be between 1 and 10"));
+        }
+        if edit.importance.is_some() && edit.importance_delta.is_some() {
+            return Err(invalid("importance_delta", "Give either importance or importance_delta, not both"));
+        }
+        if edit == Self::default() {
+            return Err(invalid("add_tags", "Give tags to add or remove, a category or an importance"));
+        }
+        Ok(edit)
+    }
+
+    /// Metadata after the edit; relevance and citations are kept
+    pub fn apply(&self, metadata: &ThoughtMetadata) -> ThoughtMetadata {
+        let mut edited = metadata.clone();
+        if !self.add_tags.is_empty() || !self.remove_tags.is_empty() {
+            let mut tags: Vec<String> = metadata.tags.iter().flatten()
+                .filter(|tag| !self.remove_tags.contains(tag))
+                .cloned()
+                .collect();
+            for tag in &self.add_tags {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+            edited.tags = Some(tags).filter(|tags| !tags.is_empty());
+        }
+        if let Some(category) = &self.category {
+            edited.category = category.clone();
+        }
+        if let Some(importance) = self.importance {
+            edited.importance = Some(importance);
+        }
+        if let Some(delta) = self.importance_delta {
+            let current = metadata.importance.unwrap_or(DEFAULT_IMPORTANCE);
+            edited.importance = Some(current.saturating_add(delta).clamp(MIN_IMPORTANCE, MAX_IMPORTANCE));
+        }
+        edited
+    }
+
+    /// The edit as `+redis -draft category=research importance+1`
+    pub fn describe(&self) -> String {
+        let mut parts: Vec<String> = self.add_tags.iter().map(|tag| format!("+{}", tag)).collect();
+        parts.extend(self.remove_tags.iter().map(|tag| format!("-{}", tag)));
+        match &self.category {
+            Some(Some(category)) => parts.push(format!("category={}", category)),
+            Some(None) => parts.push("category cleared".to_string()),
+            None => {}
+        }
+        if let Some(importance) = self.importance {
+            parts.push(format!("importance={}", importance));
+        }
+        if let Some(delta) = self.importance_delta {
+            parts.push(format!("importance{:+}", delta));
+        }
+        parts.join(" ")
+    }
+}
+
+/// Trimmed, distinct tags in the order given
+fn clean_tags(tags: Option<Vec<String>>) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for tag in tags.into_iter().flatten() {
+        let tag = tag.trim();
+        if !tag.is_empty() && !cleaned.iter().any(|t| t == tag) {
+            cleaned.push(tag.to_string());
+        }
+    }
+    cleaned
+}
+
+/// Whether two metadata records carry the same tags, category and importance
+pub fn same_fields(a: &ThoughtMetadata, b: &ThoughtMetadata) -> bool {
+    let tags = |m: &ThoughtMetadata| m.tags.clone().filter(|tags| !tags.is_empty());
+    tags(a) == tags(b) && a.category == b.category && a.importance == b.importance
+}
+
+/// How metadata changes from `before` to `after`
+pub fn change(thought: &ThoughtRecord, before: &ThoughtMetadata, after: &ThoughtMetadata) -> BulkChange {
+    let before_tags: Vec<String> = before.tags.clone().unwrap_or_default();
+    let after_tags: Vec<String> = after.tags.clone().unwrap_or_default();
+    BulkChange {
+        thought_id: thought.id.clone(),
+        snippet: crate::review::snippet(&thought.thought, SNIPPET_CHARS),
+        added_tags: after_tags.iter().filter(|tag| !before_tags.contains(tag)).cloned().collect(),
+        removed_tags: before_tags.iter().filter(|tag| !after_tags.contains(tag)).cloned().collect(),
+        category: (before.category != after.category).then(|| after.category.clone().unwrap_or_default()),
+        importance: (before.importance != after.importance).then_some(after.importance).flatten(),
+    }
+}
+
+/// Batches needed to save `changes` thoughts
+pub fn batch_count(changes: usize, batch_size: usize) -> usize {
+    changes.div_ceil(batch_size.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(tags: &[&str], category: Option<&str>, importance: Option<i32>) -> ThoughtMetadata {
+        let tags = Some(tags.iter().map(|t| t.to_string()).collect::<Vec<_>>()).filter(|t| !t.is_empty());
+        ThoughtMetadata::new("t1".to_string(), "CC".to_string(), importance, Some(4), tags, category.map(str::to_string))
+    }
+
+    #[test]
+    fn test_edit_applies_and_describes() {
+        let edit = Edit::new(
+            Some(vec!["redis".to_string(), " redis ".to_string()]),
+            Some(vec!["draft".to_string()]),
+            Some("research"),
+            None,
+            Some(7),
+        ).unwrap();
+        assert_eq!(edit.describe(), "+redis -draft category=research importance+7");
+
+        let before = metadata(&["draft", "ops"], None, Some(6));
+        let after = edit.apply(&before);
+        assert_eq!(after.tags, Some(vec!["ops".to_string(), "redis".to_string()]));
+        assert_eq!((after.category.as_deref(), after.importance, after.relevance), (Some("research"), Some(10), Some(4)));
+        assert!(!same_fields(&before, &after));
+
+        // Removing the last tag and clearing the category leave nothing behind
+        let cleared = Edit::new(None, Some(vec!["ops".to_string()]), Some(""), None, None).unwrap();
+        let after = cleared.apply(&metadata(&["ops"], Some("research"), None));
+        assert_eq!((after.tags, after.category), (None, None));
+        assert_eq!(cleared.describe(), "-ops category cleared");
+    }
+
+    #[test]
+    fn test_edit_validation_and_changes() {
+        assert!(Edit::new(None, None, None, None, Some(0)).is_err());
+        assert!(Edit::new(Some(vec!["a".to_string()]), Some(vec!["a".to_string()]), None, None, None).is_err());
+        assert!(Edit::new(None, None, None, Some(11), None).is_err());
+        assert!(Edit::new(None, None, None, Some(3), Some(1)).is_err());
+
+        let thought = ThoughtRecord::new("CC".to_string(), "Pipeline the writes".to_string(), 1, 1, None, false);
+        let before = metadata(&["draft"], Some("ops"), None);
+        let after = Edit::new(Some(vec!["redis".to_string()]), Some(vec!["draft".to_string()]), None, Some(8), None).unwrap().apply(&before);
+        let change = change(&thought, &before, &after);
+        assert_eq!((change.added_tags, change.removed_tags), (vec!["redis".to_string()], vec!["draft".to_string()]));
+        assert_eq!((change.category, change.importance), (None, Some(8)));
+        assert_eq!((batch_count(0, 100), batch_count(250, 100)), (0, 3));
+    }
+}
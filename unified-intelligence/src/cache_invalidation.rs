@@ -0,0 +1,241 @@
+//! Invalidate in-process caches from Redis keyspace notifications.
+//!
+//! The search cache keeps results for five minutes, so a thought edited or
+//! deleted by another service (or redis-cli) would otherwise keep showing up
+//! in recall. At startup the required `notify-keyspace-events` flags are merged
+//! into the server config and a dedicated pub/sub connection listens for
+//! changes to `*:Thoughts:*` keys, clearing the cache on each one. Changes to
+//! identity documents (`*:identity:*`) and legacy identities (`*:identity`)
+//! bump the identity generation instead, which retires cached identities that
+//! the `identity_version` counter can't, because nothing bumped it.
+//!
+//! Set UI_KEYSPACE_NOTIFICATIONS=off to leave the server config alone; the
+//! search cache then falls back to TTL-only expiry. Cached identities are
+//! only trusted indefinitely while the listener is subscribed on a server
+//! whose notification flags were confirmed; otherwise (notifications off,
+//! CONFIG denied, or the listener reconnecting) they expire after
+//! UI_IDENTITY_CACHE_TTL_SECS so changes made by other processes still show.
+
+use futures_util::StreamExt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::redis::RedisManager;
+use crate::search_optimization::SearchCache;
+
+/// Keyspace events (K), generic commands like DEL/EXPIRE/RENAME (g), string
+/// commands (`$`), expirations (x) and module commands such as JSON.SET (d)
+const REQUIRED_EVENT_FLAGS: &str = "Kg$xd";
+
+/// Keys under the thought pattern that change on reads, not writes
+const IGNORED_SUFFIXES: &[&str] = &[":last_access"];
+
+/// Longest wait between reconnect attempts
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Identity cache lifetime when keyspace notifications aren't active
+const DEFAULT_IDENTITY_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Identity changes seen by the listener, for any instance
+static IDENTITY_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Whether the listener is subscribed on a server known to publish the events
+static NOTIFICATIONS_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Generation of identity data in Redis; a cached identity built under another one is stale
+pub fn identity_generation() -> u64 {
+    IDENTITY_GENERATION.load(Ordering::SeqCst)
+}
+
+fn identity_changed() {
+    IDENTITY_GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Whether identity changes made elsewhere are currently seen through keyspace notifications
+pub fn notifications_active() -> bool {
+    NOTIFICATIONS_ACTIVE.load(Ordering::SeqCst)
+}
+
+/// Identity cache lifetime without notifications, from UI_IDENTITY_CACHE_TTL_SECS
+pub fn identity_ttl_from_env() -> Duration {
+    std::env::var("UI_IDENTITY_CACHE_TTL_SECS").ok()
+        .and_then(|secs| secs.trim().parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_IDENTITY_CACHE_TTL)
+}
+
+/// Whether an identity cached `age` ago may still be served; `ttl` only applies while notifications aren't active
+pub fn identity_fresh(age: Duration, ttl: Duration, notifications_active: bool) -> bool {
+    notifications_active || age < ttl
+}
+
+/// Add any missing required flags to the current notify-keyspace-events value
+pub fn merge_event_flags(current: &str) -> String {
+    let mut flags = current.to_string();
+    for flag in REQUIRED_EVENT_FLAGS.chars() {
+        // 'A' is an alias for every event class except K/E
+        let covered = flags.contains(flag) || (flag != 'K' && flags.contains('A'));
+        if !covered {
+            flags.push(flag);
+        }
+    }
+    flags
+}
+
+/// Keyspace channel pattern for thought keys in a database
+pub fn thought_channel_pattern(db: u32) -> String {
+    format!("__keyspace@{}__:*:Thoughts:*", db)
+}
+
+/// Keyspace channel patterns for identity documents and legacy identities in a database
+pub fn identity_channel_patterns(db: u32) -> [String; 2] {
+    [format!("__keyspace@{}__:*:identity:*", db), format!("__keyspace@{}__:*:identity", db)]
+}
+
+/// Whether a keyspace notification is about identity data rather than a thought
+pub fn is_identity_channel(channel: &str) -> bool {
+    channel.ends_with(":identity") || channel.contains(":identity:")
+}
+
+/// Whether a keyspace notification on this channel should invalidate the search cache
+pub fn should_invalidate(channel: &str) -> bool {
+    !IGNORED_SUFFIXES.iter().any(|suffix| channel.ends_with(suffix))
+}
+
+/// Whether keyspace notifications are enabled (UI_KEYSPACE_NOTIFICATIONS, default on)
+pub fn enabled_from_env() -> bool {
+    !matches!(
+        std::env::var("UI_KEYSPACE_NOTIFICATIONS").unwrap_or_default().to_lowercase().as_str(),
+        "off" | "false" | "0"
+    )
+}
+
+/// Ensure the server publishes the events we need
+async fn enable_notifications(redis: &RedisManager) -> Result<()> {
+    let mut conn = redis.get_connection().await?;
+    let current: Vec<String> = redis::cmd("CONFIG")
+        .arg("GET")
+        .arg("notify-keyspace-events")
+        .query_async(&mut *conn)
+        .await?;
+    let current = current.get(1).cloned().unwrap_or_default();
+
+    let merged = merge_event_flags(&current);
+    if merged != current {
+        redis::cmd("CONFIG")
+            .arg("SET")
+            .arg("notify-keyspace-events")
+            .arg(&merged)
+            .query_async::<()>(&mut *conn)
+            .await?;
+        tracing::info!("Enabled Redis keyspace notifications: '{}' -> '{}'", current, merged);
+    }
+
+    Ok(())
+}
+
+/// Subscribe and clear the cache on every relevant event until the connection drops;
+/// `configured` says whether the server's notification flags were confirmed
+async fn listen(redis: &RedisManager, search_cache: &SearchCache, configured: bool) -> Result<()> {
+    let pattern = thought_channel_pattern(redis.db());
+    let mut pubsub = redis.pubsub().await?;
+    pubsub.psubscribe(&pattern).await?;
+    for identity_pattern in identity_channel_patterns(redis.db()) {
+        pubsub.psubscribe(&identity_pattern).await?;
+    }
+    tracing::info!("Listening for keyspace notifications on {} and identity keys", pattern);
+    NOTIFICATIONS_ACTIVE.store(configured, Ordering::SeqCst);
+
+    let mut messages = pubsub.on_message();
+    while let Some(message) = messages.next().await {
+        let channel = message.get_channel_name();
+        if is_identity_channel(channel) {
+            tracing::debug!("Identity changed on {}, retiring cached identities", channel);
+            identity_changed();
+            continue;
+        }
+        if !should_invalidate(channel) {
+            continue;
+        }
+
+        let event: String = message.get_payload().unwrap_or_default();
+        tracing::debug!("Keyspace event '{}' on {}, clearing search cache", event, channel);
+        search_cache.clear();
+    }
+
+    Ok(())
+}
+
+/// Enable notifications and spawn the listener, reconnecting with backoff
+pub async fn start(redis: Arc<RedisManager>, search_cache: SearchCache) {
+    if !enabled_from_env() {
+        tracing::info!("Keyspace notifications disabled; search cache and cached identities use TTL expiry only");
+        return;
+    }
+
+    let configured = match enable_notifications(&redis).await {
+        Ok(()) => true,
+        Err(e) => {
+            // Managed Redis often disables CONFIG; events may still be enabled server-side, but that can't be confirmed
+            tracing::warn!("Could not configure keyspace notifications ({}); cached identities expire after a TTL", e);
+            false
+        }
+    };
+
+    tokio::spawn(async move {
+        let mut backoff = Duration::from_secs(1);
+        loop {
+            match listen(&redis, &search_cache, configured).await {
+                Ok(()) => tracing::warn!("Keyspace notification stream closed, reconnecting"),
+                Err(e) => tracing::warn!("Keyspace notification listener failed: {}", e),
+            }
+            NOTIFICATIONS_ACTIVE.store(false, Ordering::SeqCst);
+
+            // Events were missed while disconnected
+            search_cache.clear();
+            identity_changed();
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_event_flags() {
+        assert_eq!(merge_event_flags(""), "Kg$xd");
+        assert_eq!(merge_event_flags("Ex"), "ExKg$d");
+        assert_eq!(merge_event_flags("KEA"), "KEA");
+    }
+
+    #[test]
+    fn test_should_invalidate() {
+        assert!(should_invalidate("__keyspace@0__:Claude:Thoughts:abc"));
+        assert!(!should_invalidate("__keyspace@0__:Claude:Thoughts:abc:last_access"));
+        assert_eq!(thought_channel_pattern(2), "__keyspace@2__:*:Thoughts:*");
+    }
+
+    #[test]
+    fn test_identity_channels() {
+        assert!(is_identity_channel("__keyspace@0__:CC:identity:core_info:1"));
+        assert!(is_identity_channel("__keyspace@0__:users:alice:CC:identity"));
+        assert!(!is_identity_channel("__keyspace@0__:CC:identity_version"));
+        assert!(!is_identity_channel("__keyspace@0__:CC:Thoughts:abc"));
+        assert_eq!(identity_channel_patterns(1)[0], "__keyspace@1__:*:identity:*");
+    }
+
+    #[test]
+    fn test_identity_ttl_applies_without_notifications() {
+        let ttl = Duration::from_secs(60);
+        assert!(identity_fresh(Duration::from_secs(59), ttl, false));
+        assert!(!identity_fresh(Duration::from_secs(60), ttl, false));
+        assert!(identity_fresh(Duration::from_secs(3600), ttl, true));
+        assert!(!notifications_active());
+    }
+}
@@ -0,0 +1,120 @@
+//! Redis module detection and the resulting capability matrix.
+//!
+//! Thought storage, search, duplicate detection and metrics lean on RedisJSON,
+//! RediSearch, RedisBloom and RedisTimeSeries. Plain Redis has none of them,
+//! so the modules are detected once at startup (MODULE LIST) and every path
+//! that needs one falls back when it's missing:
+//!
+//! - RedisJSON: documents are stored as JSON strings (SET/GET)
+//! - RediSearch: recall scans `*:Thoughts:*` keys and matches substrings
+//! - RedisBloom: duplicate detection relies on the key existence check alone
+//! - RedisTimeSeries: metrics keys become plain INCR counters
+
+use redis::Value;
+
+use crate::search_index::{as_string, field};
+
+/// Which Redis modules are loaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub json: bool,
+    pub search: bool,
+    pub bloom: bool,
+    pub timeseries: bool,
+}
+
+impl Capabilities {
+    /// Every module available (assumed when MODULE LIST can't be read)
+    pub fn all() -> Self {
+        Self { json: true, search: true, bloom: true, timeseries: true }
+    }
+
+    /// Build the matrix from loaded module names (as reported by MODULE LIST)
+    pub fn from_module_names<S: AsRef<str>>(names: &[S]) -> Self {
+        let has = |candidates: &[&str]| names.iter()
+            .any(|name| candidates.iter().any(|c| name.as_ref().eq_ignore_ascii_case(c)));
+        Self {
+            json: has(&["ReJSON", "json"]),
+            search: has(&["search", "ft", "searchlight"]),
+            bloom: has(&["bf", "bloom"]),
+            timeseries: has(&["timeseries"]),
+        }
+    }
+
+    /// The thought index is defined ON JSON, so it needs both modules
+    pub fn search_index(&self) -> bool {
+        self.json && self.search
+    }
+
+    /// Features running on a fallback path, for logging and status output
+    pub fn degraded_features(&self) -> Vec<&'static str> {
+        let mut degraded = Vec::new();
+        if !self.json {
+            degraded.push("RedisJSON missing: documents stored as plain JSON strings");
+        }
+        if !self.search_index() {
+            degraded.push("RediSearch index unavailable: recall uses SCAN-based substring search");
+        }
+        if !self.bloom {
+            degraded.push("RedisBloom missing: duplicate detection uses key existence only");
+        }
+        if !self.timeseries {
+            degraded.push("RedisTimeSeries missing: metrics kept as plain counters");
+        }
+        degraded
+    }
+
+    /// Log the matrix once at startup
+    pub fn log(&self) {
+        tracing::info!(
+            "Redis modules: json={} search={} bloom={} timeseries={}",
+            self.json, self.search, self.bloom, self.timeseries
+        );
+        for feature in self.degraded_features() {
+            tracing::warn!("Degraded: {}", feature);
+        }
+    }
+}
+
+/// Module names from a MODULE LIST reply
+pub fn module_names(reply: &Value) -> Vec<String> {
+    match reply {
+        Value::Array(modules) | Value::Set(modules) => modules.iter()
+            .filter_map(|module| field(module, "name"))
+            .filter_map(as_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bulk(s: &str) -> Value {
+        Value::BulkString(s.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_module_names_from_reply() {
+        let reply = Value::Array(vec![
+            Value::Array(vec![bulk("name"), bulk("ReJSON"), bulk("ver"), Value::Int(20609)]),
+            Value::Array(vec![bulk("name"), bulk("search"), bulk("ver"), Value::Int(21005)]),
+        ]);
+        let names = module_names(&reply);
+        assert_eq!(names, vec!["ReJSON", "search"]);
+
+        let caps = Capabilities::from_module_names(&names);
+        assert!(caps.json && caps.search && caps.search_index());
+        assert!(!caps.bloom && !caps.timeseries);
+        assert_eq!(caps.degraded_features().len(), 2);
+    }
+
+    #[test]
+    fn test_plain_redis_degrades_everything() {
+        let caps = Capabilities::from_module_names::<&str>(&[]);
+        assert!(!caps.search_index());
+        assert_eq!(caps.degraded_features().len(), 4);
+        assert!(Capabilities::all().degraded_features().is_empty());
+    }
+}
@@ -0,0 +1,445 @@
+//! Capture connectors that turn external reading material into thoughts.
+//!
+//! Feeds and mail are fetched with `curl` so HTTPS, IMAPS and `file://` URLs
+//! work without extra TLS stacks in the server; commits are read with `git`:
+//!
+//! - UI_CAPTURE_FEEDS: comma-separated RSS/Atom feed URLs; every entry is captured
+//! - UI_CAPTURE_IMAP_URL (e.g. `imaps://imap.example.com`), UI_CAPTURE_IMAP_USER,
+//!   UI_CAPTURE_IMAP_PASSWORD and UI_CAPTURE_IMAP_FOLDER (default INBOX): messages
+//!   flagged in the mail client are captured
+//! - UI_CAPTURE_GIT_REPOS: comma-separated repository paths, each optionally
+//!   `path@branch` (default: the checked-out branch); UI_CAPTURE_GIT_DIFFSTAT=1
+//!   appends a diff summary to each commit
+//!
+//! Feed entries and mail become one thought each (title, excerpt, link) tagged
+//! `capture` plus their source and feed categories. Commits are appended in
+//! order to one chain per repository (`git:{repo}`) and tagged `repo:{name}`
+//! and `branch:{name}`; raise MAX_THOUGHTS_PER_CHAIN for busy repositories.
+//! Items are deduplicated per instance by URL, Message-ID or commit hash, so
+//! polling repeatedly is safe. UI_CAPTURE_INTERVAL_SECS turns
+//! on background polling; otherwise capture runs when ui_capture is called.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Longest excerpt kept from an entry or mail body, in characters
+const EXCERPT_CHARS: usize = 400;
+
+/// Most feed categories kept as tags
+const MAX_CATEGORY_TAGS: usize = 5;
+
+/// Tag applied to every captured thought
+pub const CAPTURE_TAG: &str = "capture";
+
+/// Flagged-mail source
+#[derive(Debug, Clone)]
+pub struct ImapSource {
+    pub url: String,
+    pub folder: String,
+    pub user: String,
+    pub password: String,
+}
+
+/// Repository whose commits are captured
+#[derive(Debug, Clone)]
+pub struct GitSource {
+    pub path: PathBuf,
+    pub branch: Option<String>,   // None: the checked-out branch
+    pub diffstat: bool,
+}
+
+impl GitSource {
+    /// Repository name used in the chain id and tags
+    pub fn name(&self) -> String {
+        self.path.canonicalize().unwrap_or_else(|_| self.path.clone())
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "repo".to_string())
+    }
+}
+
+/// Configured capture sources
+#[derive(Debug, Clone, Default)]
+pub struct CaptureConfig {
+    pub feeds: Vec<String>,
+    pub imap: Option<ImapSource>,
+    pub git: Vec<GitSource>,
+}
+
+impl CaptureConfig {
+    /// Read the UI_CAPTURE_* variables; None when no source is configured
+    pub fn from_env() -> Option<Self> {
+        let env = |name: &str| std::env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let feeds: Vec<String> = env("UI_CAPTURE_FEEDS")
+            .map(|feeds| feeds.split(',').map(str::trim).filter(|f| !f.is_empty()).map(str::to_string).collect())
+            .unwrap_or_default();
+        let imap = env("UI_CAPTURE_IMAP_URL").map(|url| ImapSource {
+            url: url.trim_end_matches('/').to_string(),
+            folder: env("UI_CAPTURE_IMAP_FOLDER").unwrap_or_else(|| "INBOX".to_string()),
+            user: env("UI_CAPTURE_IMAP_USER").unwrap_or_default(),
+            password: env("UI_CAPTURE_IMAP_PASSWORD").unwrap_or_default(),
+        });
+        let diffstat = env("UI_CAPTURE_GIT_DIFFSTAT").is_some_and(|v| matches!(v.as_str(), "1" | "true" | "yes"));
+        let git: Vec<GitSource> = env("UI_CAPTURE_GIT_REPOS")
+            .map(|repos| repos.split(',').map(str::trim).filter(|r| !r.is_empty()).map(|repo| {
+                let (path, branch) = match repo.rsplit_once('@') {
+                    Some((path, branch)) if !branch.is_empty() => (path, Some(branch.to_string())),
+                    _ => (repo, None),
+                };
+                GitSource { path: PathBuf::from(path), branch, diffstat }
+            }).collect())
+            .unwrap_or_default();
+        (!feeds.is_empty() || imap.is_some() || !git.is_empty()).then_some(Self { feeds, imap, git })
+    }
+}
+
+/// Background polling interval from UI_CAPTURE_INTERVAL_SECS
+pub fn poll_interval() -> Option<Duration> {
+    std::env::var("UI_CAPTURE_INTERVAL_SECS").ok()
+        .and_then(|secs| secs.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs)
+}
+
+/// An external item ready to be stored as a thought
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedItem {
+    pub id: String,                  // Dedup key: URL, entry id, `mid:{message-id}` or `git:{sha}`
+    pub source: &'static str,        // "rss", "email" or "git"
+    pub title: String,
+    pub excerpt: String,
+    pub link: Option<String>,
+    pub tags: Vec<String>,
+    pub published: Option<String>,   // RFC 3339
+    pub chain_id: Option<String>,    // Chain the item is appended to (commits)
+    pub git_commit: Option<String>,
+}
+
+impl CapturedItem {
+    /// Thought text: title, excerpt and link
+    pub fn thought_text(&self) -> String {
+        let mut text = self.title.clone();
+        if !self.excerpt.is_empty() {
+            text.push_str("\n\n");
+            text.push_str(&self.excerpt);
+        }
+        if let Some(link) = &self.link {
+            text.push_str("\n\n");
+            text.push_str(link);
+        }
+        text
+    }
+}
+
+/// Run curl, optionally feeding it a config file on stdin (used for credentials)
+fn curl(args: &[&str], config: Option<&str>) -> Result<Vec<u8>, String> {
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location", "--max-time", "30"])
+        .args(if config.is_some() { &["--config", "-"][..] } else { &[][..] })
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run curl: {}", e))?;
+    let mut stdin = child.stdin.take().ok_or("curl stdin unavailable")?;
+    if let Some(config) = config {
+        stdin.write_all(config.as_bytes()).map_err(|e| format!("failed to configure curl: {}", e))?;
+    }
+    drop(stdin);
+
+    let output = child.wait_with_output().map_err(|e| format!("curl failed: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(output.stdout)
+}
+
+/// Fetch and parse a feed; newest entries first, at most `limit`
+pub fn fetch_feed(url: &str, limit: usize) -> Result<Vec<CapturedItem>, String> {
+    let body = curl(&[url], None).map_err(|e| format!("{}: {}", url, e))?;
+    let mut items = parse_feed(&body).map_err(|e| format!("{}: {}", url, e))?;
+    items.sort_by(|a, b| b.published.cmp(&a.published));
+    items.truncate(limit);
+    Ok(items)
+}
+
+/// Items of an RSS or Atom document
+pub fn parse_feed(body: &[u8]) -> Result<Vec<CapturedItem>, String> {
+    let feed = feed_rs::parser::parse(body).map_err(|e| format!("not a feed: {}", e))?;
+    Ok(feed.entries.into_iter().map(|entry| {
+        let link = entry.links.first().map(|link| link.href.clone());
+        let body = entry.summary.map(|text| text.content)
+            .or_else(|| entry.content.and_then(|content| content.body))
+            .unwrap_or_default();
+        let mut tags = vec![CAPTURE_TAG.to_string(), "rss".to_string()];
+        tags.extend(entry.categories.iter().map(|c| tag_from(&c.term)).filter(|t| !t.is_empty()).take(MAX_CATEGORY_TAGS));
+        CapturedItem {
+            id: link.clone().unwrap_or_else(|| entry.id.clone()),
+            source: "rss",
+            title: entry.title.map(|t| collapse_whitespace(&t.content)).unwrap_or_else(|| "(untitled)".to_string()),
+            excerpt: excerpt(&body),
+            link,
+            tags,
+            published: entry.published.or(entry.updated).map(|date| date.to_rfc3339()),
+            chain_id: None,
+            git_commit: None,
+        }
+    }).collect())
+}
+
+fn imap_config(source: &ImapSource) -> String {
+    let quote = |value: &str| value.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("user = \"{}:{}\"\n", quote(&source.user), quote(&source.password))
+}
+
+fn folder_url(source: &ImapSource) -> String {
+    format!("{}/{}", source.url, source.folder.replace(' ', "%20"))
+}
+
+/// Fetch flagged messages, newest first, at most `limit`
+pub fn fetch_flagged_mail(source: &ImapSource, limit: usize) -> Result<Vec<CapturedItem>, String> {
+    let config = imap_config(source);
+    let folder = folder_url(source);
+    let search = curl(&["--url", &folder, "--request", "UID SEARCH FLAGGED"], Some(&config))
+        .map_err(|e| format!("{}: {}", source.url, e))?;
+    let mut uids = parse_search_response(&String::from_utf8_lossy(&search));
+    uids.sort_unstable_by(|a, b| b.cmp(a));
+    uids.truncate(limit);
+
+    let mut items = Vec::new();
+    for uid in uids {
+        let message = curl(&["--url", &format!("{};UID={}", folder, uid)], Some(&config))
+            .map_err(|e| format!("{} UID {}: {}", source.url, uid, e))?;
+        items.extend(parse_mail(&message));
+    }
+    Ok(items)
+}
+
+/// UIDs from an IMAP `* SEARCH 1 2 3` response
+fn parse_search_response(response: &str) -> Vec<u64> {
+    response.lines()
+        .filter_map(|line| line.trim().strip_prefix("* SEARCH"))
+        .flat_map(|uids| uids.split_whitespace().filter_map(|uid| uid.parse().ok()))
+        .collect()
+}
+
+/// A raw RFC 822 message as a captured item (None without a Message-ID to dedup on)
+pub fn parse_mail(raw: &[u8]) -> Option<CapturedItem> {
+    let message = mail_parser::MessageParser::default().parse(raw)?;
+    let message_id = message.message_id()?.to_string();
+    let body = message.body_text(0).map(|text| text.into_owned()).unwrap_or_default();
+    let sender = message.from()
+        .and_then(|from| from.first())
+        .and_then(|addr| addr.name().or(addr.address()))
+        .map(str::to_string);
+
+    let title = message.subject().map(collapse_whitespace).unwrap_or_else(|| "(no subject)".to_string());
+    Some(CapturedItem {
+        id: format!("mid:{}", message_id),
+        source: "email",
+        title: match sender {
+            Some(sender) => format!("{} (from {})", title, sender),
+            None => title,
+        },
+        excerpt: excerpt(&body),
+        link: first_url(&body),
+        tags: vec![CAPTURE_TAG.to_string(), "email".to_string()],
+        published: message.date().map(|date| date.to_rfc3339()),
+        chain_id: None,
+        git_commit: None,
+    })
+}
+
+const FIELD_SEPARATOR: char = '\u{1f}';
+const RECORD_SEPARATOR: char = '\u{1e}';
+
+fn git(source: &GitSource, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(&source.path)
+        .args(args)
+        .output()
+        .map_err(|e| format!("failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("{}: {}", source.path.display(), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Chain a repository's commits are appended to
+pub fn git_chain_id(repo: &str) -> String {
+    format!("git:{}", repo)
+}
+
+/// Latest `limit` commits of the source's branch, oldest first
+pub fn fetch_commits(source: &GitSource, limit: usize) -> Result<Vec<CapturedItem>, String> {
+    let branch = match &source.branch {
+        Some(branch) => branch.clone(),
+        None => git(source, &["rev-parse", "--abbrev-ref", "HEAD"])?.trim().to_string(),
+    };
+    let format = format!("--format=%H{f}%aI{f}%an{f}%B{r}", f = "%x1f", r = "%x1e");
+    let log = git(source, &["log", &branch, "-n", &limit.to_string(), &format, "--"])?;
+
+    let mut items = parse_git_log(&log, &source.name(), &branch);
+    if source.diffstat {
+        for item in &mut items {
+            let sha = item.git_commit.clone().unwrap_or_default();
+            let stat = git(source, &["show", "--stat=120", "--format=", &sha])?;
+            item.excerpt = join_paragraphs(&item.excerpt, &summarize_diffstat(&stat));
+        }
+    }
+    items.reverse();
+    Ok(items)
+}
+
+/// Commits from `git log` output in the FIELD/RECORD separated format above, newest first
+fn parse_git_log(log: &str, repo: &str, branch: &str) -> Vec<CapturedItem> {
+    log.split(RECORD_SEPARATOR).filter_map(|record| {
+        let mut fields = record.trim_start_matches('\n').splitn(4, FIELD_SEPARATOR);
+        let (sha, date, author, message) = (fields.next()?, fields.next()?, fields.next()?, fields.next()?);
+        if sha.is_empty() {
+            return None;
+        }
+        let message = message.trim();
+        let (subject, body) = message.split_once('\n').unwrap_or((message, ""));
+        Some(CapturedItem {
+            id: format!("git:{}", sha),
+            source: "git",
+            title: format!("{}@{} {}: {} ({})", repo, branch, &sha[..sha.len().min(8)], subject.trim(), author),
+            excerpt: body.trim().to_string(),
+            link: None,
+            tags: vec![CAPTURE_TAG.to_string(), "git".to_string(), format!("repo:{}", repo), format!("branch:{}", branch)],
+            published: Some(date.to_string()),
+            chain_id: Some(git_chain_id(repo)),
+            git_commit: Some(sha.to_string()),
+        })
+    }).collect()
+}
+
+/// Changed files (at most 10) and the summary line of `git show --stat`
+fn summarize_diffstat(stat: &str) -> String {
+    let lines: Vec<&str> = stat.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    let Some((summary, files)) = lines.split_last() else {
+        return String::new();
+    };
+    let mut out: Vec<String> = files.iter().take(10).map(|line| line.to_string()).collect();
+    if files.len() > 10 {
+        out.push(format!("... {} more files", files.len() - 10));
+    }
+    out.push(summary.to_string());
+    out.join("\n")
+}
+
+fn join_paragraphs(first: &str, second: &str) -> String {
+    match (first.is_empty(), second.is_empty()) {
+        (_, true) => first.to_string(),
+        (true, false) => second.to_string(),
+        (false, false) => format!("{}\n\n{}", first, second),
+    }
+}
+
+fn first_url(text: &str) -> Option<String> {
+    text.split_whitespace()
+        .find(|word| word.starts_with("https://") || word.starts_with("http://"))
+        .map(|url| url.trim_end_matches(['>', ')', ']', '.', ',', '"']).to_string())
+}
+
+/// Plain-text excerpt: markup stripped, whitespace collapsed, cut at a word boundary
+fn excerpt(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                plain.push(' ');
+            }
+            _ if !in_tag => plain.push(c),
+            _ => {}
+        }
+    }
+    let plain = collapse_whitespace(&plain.replace("&nbsp;", " ").replace("&amp;", "&"));
+    if plain.chars().count() <= EXCERPT_CHARS {
+        return plain;
+    }
+    let cut: String = plain.chars().take(EXCERPT_CHARS).collect();
+    let cut = cut.rsplit_once(' ').map(|(head, _)| head).unwrap_or(&cut);
+    format!("{}…", cut)
+}
+
+pub(crate) fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub(crate) fn tag_from(term: &str) -> String {
+    collapse_whitespace(term).to_lowercase().replace(' ', "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel><title>Blog</title>
+  <item>
+    <title>Redis 8 vector sets</title>
+    <link>https://example.com/redis-8</link>
+    <description>&lt;p&gt;Vector sets are a new &lt;b&gt;native&lt;/b&gt; type.&lt;/p&gt;</description>
+    <category>Redis</category><category>Vector Search</category>
+    <pubDate>Fri, 18 Jul 2025 09:00:00 GMT</pubDate>
+  </item>
+</channel></rss>"#;
+
+    #[test]
+    fn test_parse_rss_item() {
+        let items = parse_feed(RSS.as_bytes()).unwrap();
+        assert_eq!(items.len(), 1);
+        let item = &items[0];
+        assert_eq!(item.id, "https://example.com/redis-8");
+        assert_eq!(item.excerpt, "Vector sets are a new native type.");
+        assert_eq!(item.tags, vec!["capture", "rss", "redis", "vector-search"]);
+        assert_eq!(item.published.as_deref(), Some("2025-07-18T09:00:00+00:00"));
+        assert_eq!(item.thought_text(), "Redis 8 vector sets\n\nVector sets are a new native type.\n\nhttps://example.com/redis-8");
+        assert!(parse_feed(b"<html>nope</html>").is_err());
+    }
+
+    #[test]
+    fn test_parse_flagged_mail() {
+        let raw = "From: Sam <sam@example.com>\r\nSubject: Read this\r\nMessage-ID: <abc@example.com>\r\n\
+            Date: Fri, 18 Jul 2025 09:00:00 +0000\r\n\r\nWorth a look: https://example.com/post.\r\n";
+        let item = parse_mail(raw.as_bytes()).unwrap();
+        assert_eq!(item.id, "mid:abc@example.com");
+        assert_eq!(item.title, "Read this (from Sam)");
+        assert_eq!(item.link.as_deref(), Some("https://example.com/post"));
+        assert!(parse_mail(b"Subject: no id\r\n\r\nbody").is_none());
+    }
+
+    #[test]
+    fn test_parse_git_log_and_diffstat() {
+        let log = "aaaaaaaaaa1\u{1f}2025-07-18T09:00:00+02:00\u{1f}Sam\u{1f}Centralize redis key schema\n\nMove keys into keys.rs.\n\u{1e}\n\
+            bbbbbbbbbb2\u{1f}2025-07-17T09:00:00+02:00\u{1f}Sam\u{1f}Initial commit\n\u{1e}\n";
+        let commits = parse_git_log(log, "crate", "main");
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].id, "git:aaaaaaaaaa1");
+        assert_eq!(commits[0].title, "crate@main aaaaaaaa: Centralize redis key schema (Sam)");
+        assert_eq!(commits[0].excerpt, "Move keys into keys.rs.");
+        assert_eq!(commits[0].chain_id.as_deref(), Some("git:crate"));
+        assert!(commits[1].tags.contains(&"branch:main".to_string()) && commits[1].excerpt.is_empty());
+
+        let stat = " src/keys.rs | 100 +++\n src/redis.rs |  10 +-\n 2 files changed, 105 insertions(+), 5 deletions(-)\n";
+        assert_eq!(summarize_diffstat(stat), "src/keys.rs | 100 +++\nsrc/redis.rs |  10 +-\n2 files changed, 105 insertions(+), 5 deletions(-)");
+    }
+
+    #[test]
+    fn test_search_response_and_excerpt() {
+        assert_eq!(parse_search_response("* SEARCH 4 17 9\r\n"), vec![4, 17, 9]);
+        assert!(parse_search_response("* SEARCH\r\n").is_empty());
+        let long = "word ".repeat(200);
+        let cut = excerpt(&long);
+        assert!(cut.ends_with('…') && cut.chars().count() <= EXCERPT_CHARS + 1);
+    }
+}
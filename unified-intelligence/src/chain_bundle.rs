@@ -0,0 +1,292 @@
+//! Portable chain bundles for ui_export_chain and ui_import_chain.
+//!
+//! A bundle is one JSON document with a chain's metadata, its thoughts in
+//! chain order (tiered-out thoughts with their full text), their thought
+//! metadata and feedback boost scores, tagged with a format name and version
+//! so bundles written today stay importable. Import gives every thought a new
+//! ID under the target instance and chain, so a bundle can be imported next
+//! to its source, or twice, without colliding. Related-chain links name
+//! chains of the source instance and are dropped. Custody hashes are
+//! verified as exported, and since they cover thought IDs, sealed chains are
+//! then resealed on import; a redacted export no longer matches its hashes,
+//! so it goes out unsealed. Bundle files are only written to and read from
+//! UI_BUNDLE_DIR, and existing files are never overwritten.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+use crate::custody;
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::{ChainBundle, ChainMetadata, ThoughtMetadata, ThoughtRecord};
+use crate::reconcile;
+
+/// Format name every bundle carries
+pub const FORMAT: &str = "unified-intelligence.chain";
+
+/// Current bundle version; bundles up to this version can be imported
+pub const VERSION: u32 = 1;
+
+/// Bundle directory when UI_BUNDLE_DIR is unset
+pub const DEFAULT_DIR: &str = "bundles";
+
+/// Directory bundle files are written to and read from, from UI_BUNDLE_DIR
+pub fn dir_from_env() -> PathBuf {
+    std::env::var("UI_BUNDLE_DIR").ok()
+        .filter(|dir| !dir.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_DIR))
+}
+
+/// Place a requested bundle file under `dir`; `..` and absolute paths outside it are refused
+pub fn resolve(dir: &Path, field: &str, requested: &str) -> Result<PathBuf> {
+    let refuse = |reason: &str| UnifiedIntelligenceError::Validation {
+        field: field.to_string(),
+        reason: format!("'{}' {}", requested, reason),
+    };
+    let path = Path::new(requested.trim());
+    let relative = if path.is_absolute() {
+        let base = std::path::absolute(dir).map_err(|e| refuse(&format!("can't be checked against the bundle directory: {}", e)))?;
+        path.strip_prefix(&base).map_err(|_| refuse(&format!("is outside the bundle directory {}", base.display())))?
+    } else {
+        path
+    };
+    if !relative.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        || !relative.components().any(|c| matches!(c, Component::Normal(_)))
+    {
+        return Err(refuse("must name a file inside the bundle directory"));
+    }
+    Ok(dir.join(relative))
+}
+
+/// A bundle rebased onto its import target, ready to store
+pub struct Imported {
+    pub metadata: ChainMetadata,
+    pub thoughts: Vec<ThoughtRecord>,
+    pub thought_metadata: Vec<ThoughtMetadata>,
+    pub boost_scores: Vec<(String, f64)>,
+}
+
+/// Bundle a chain; thoughts without metadata or boost are simply absent from those maps
+pub fn bundle(
+    chain_id: &str,
+    instance: &str,
+    chain: Option<ChainMetadata>,
+    thoughts: Vec<ThoughtRecord>,
+    thought_metadata: Vec<ThoughtMetadata>,
+    boost_scores: BTreeMap<String, f64>,
+) -> ChainBundle {
+    ChainBundle {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        source_instance: instance.to_string(),
+        chain_id: chain_id.to_string(),
+        chain,
+        thoughts: thoughts.into_iter().map(|thought| ThoughtRecord { similarity: None, cold: None, ..thought }).collect(),
+        thought_metadata,
+        boost_scores: boost_scores.into_iter().filter(|(_, score)| *score != 0.0).collect(),
+    }
+}
+
+fn invalid(reason: String) -> UnifiedIntelligenceError {
+    UnifiedIntelligenceError::Validation { field: "bundle".to_string(), reason }
+}
+
+/// Refuse bundles of another format, a newer version, with repeated or unknown thought IDs,
+/// or whose custody hashes no longer match the thoughts they hold
+pub fn check(bundle: &ChainBundle) -> Result<()> {
+    if bundle.format != FORMAT {
+        return Err(invalid(format!("format '{}' is not a chain bundle ('{}')", bundle.format, FORMAT)));
+    }
+    if bundle.version == 0 || bundle.version > VERSION {
+        return Err(invalid(format!("version {} is not supported (up to {})", bundle.version, VERSION)));
+    }
+    if bundle.thoughts.is_empty() {
+        return Err(invalid("the bundle holds no thoughts".to_string()));
+    }
+    let mut ids = HashSet::new();
+    if let Some(thought) = bundle.thoughts.iter().find(|t| !ids.insert(t.id.as_str())) {
+        return Err(invalid(format!("thought {} appears more than once", thought.id)));
+    }
+    let mut with_metadata = HashSet::new();
+    if let Some(metadata) = bundle.thought_metadata.iter().find(|m| !with_metadata.insert(m.thought_id.as_str())) {
+        return Err(invalid(format!("thought {} has more than one metadata record", metadata.thought_id)));
+    }
+    let unknown = with_metadata.into_iter()
+        .chain(bundle.boost_scores.keys().map(String::as_str))
+        .find(|id| !ids.contains(id));
+    if let Some(id) = unknown {
+        return Err(invalid(format!("records reference thought {} which the bundle doesn't hold", id)));
+    }
+
+    // Resealing would hide an edit, so the hashes are checked as exported
+    let issues = custody::verify(&bundle.thoughts);
+    if !issues.is_empty() {
+        let found: Vec<String> = issues.iter()
+            .map(|issue| format!("{} at thought {}", issue.kind, issue.thought_number.map(|n| n.to_string()).unwrap_or_else(|| "?".to_string())))
+            .collect();
+        return Err(invalid(format!("custody check failed: {}", found.join(", "))));
+    }
+    Ok(())
+}
+
+/// Move a checked bundle onto `instance` and `chain_id`, minting new thought IDs
+pub fn rebase(bundle: ChainBundle, instance: &str, chain_id: &str, user_id: Option<String>) -> Imported {
+    let ids: HashMap<String, String> = bundle.thoughts.iter()
+        .map(|thought| (thought.id.clone(), uuid::Uuid::new_v4().to_string()))
+        .collect();
+    let sealed = bundle.thoughts.iter().any(|thought| thought.custody.is_some());
+
+    let mut thoughts: Vec<ThoughtRecord> = Vec::with_capacity(bundle.thoughts.len());
+    for thought in bundle.thoughts {
+        let mut thought = ThoughtRecord {
+            id: ids[&thought.id].clone(),
+            instance: instance.to_string(),
+            chain_id: Some(chain_id.to_string()),
+            user_id: user_id.clone(),
+            similarity: None,
+            cold: None,
+            custody: None,
+            ..thought
+        };
+        if sealed {
+            custody::seal(&mut thought, &thoughts);
+        }
+        thoughts.push(thought);
+    }
+
+    let thought_metadata = bundle.thought_metadata.into_iter()
+        .map(|metadata| ThoughtMetadata {
+            thought_id: ids[&metadata.thought_id].clone(),
+            instance: instance.to_string(),
+            ..metadata
+        })
+        .collect();
+    let boost_scores = bundle.boost_scores.into_iter()
+        .map(|(id, score)| (ids[&id].clone(), score))
+        .collect();
+
+    let created_at = custody::ordered(&thoughts).first().map(|t| t.timestamp.clone()).unwrap_or_default();
+    let mut metadata = match bundle.chain {
+        Some(chain) => ChainMetadata {
+            chain_id: chain_id.to_string(),
+            instance: instance.to_string(),
+            user_id,
+            related: Vec::new(),
+            ..chain
+        },
+        None => ChainMetadata {
+            chain_id: chain_id.to_string(),
+            created_at,
+            thought_count: 0,
+            instance: instance.to_string(),
+            user_id,
+            related: Vec::new(),
+            planned_thoughts: None,
+            observed_thoughts: None,
+            last_updated: None,
+            next_hints: None,
+        },
+    };
+    reconcile::apply(&mut metadata, &thoughts);
+
+    Imported { metadata, thoughts, thought_metadata, boost_scores }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(sealed: bool) -> ChainBundle {
+        let mut thoughts: Vec<ThoughtRecord> = Vec::new();
+        for number in 1..=2 {
+            let mut thought = ThoughtRecord::new("CC".to_string(), format!("step {}", number), number, 2, Some("c1".to_string()), number < 2);
+            thought.timestamp = format!("2025-07-18T10:0{}:00+00:00", number);
+            if sealed {
+                custody::seal(&mut thought, &thoughts);
+            }
+            thoughts.push(thought);
+        }
+        let metadata = ThoughtMetadata::new(thoughts[0].id.clone(), "CC".to_string(), Some(8), None, Some(vec!["redis".to_string()]), None);
+        let boosts = BTreeMap::from([(thoughts[1].id.clone(), 0.4)]);
+        bundle("c1", "CC", None, thoughts, vec![metadata], boosts)
+    }
+
+    #[test]
+    fn test_rebase_mints_ids_and_reseals() {
+        let source = chain(true);
+        check(&source).unwrap();
+        let imported = rebase(source.clone(), "CCI", "c2", None);
+
+        assert_eq!(imported.thoughts.len(), 2);
+        assert!(imported.thoughts.iter().all(|t| t.instance == "CCI" && t.chain_id.as_deref() == Some("c2")));
+        assert!(imported.thoughts.iter().zip(&source.thoughts).all(|(new, old)| new.id != old.id && new.thought == old.thought));
+        assert!(custody::verify(&imported.thoughts).is_empty());
+        assert!(imported.thoughts.iter().all(|t| t.custody.is_some()));
+
+        assert_eq!(imported.thought_metadata[0].thought_id, imported.thoughts[0].id);
+        assert_eq!(imported.boost_scores, vec![(imported.thoughts[1].id.clone(), 0.4)]);
+        assert_eq!(imported.metadata.created_at, "2025-07-18T10:01:00+00:00");
+        assert_eq!((imported.metadata.thought_count, imported.metadata.observed_thoughts), (2, Some(2)));
+        assert!(rebase(chain(false), "CCI", "c3", None).thoughts.iter().all(|t| t.custody.is_none()));
+    }
+
+    #[test]
+    fn test_check_refuses_foreign_bundles() {
+        let mut newer = chain(false);
+        newer.version = VERSION + 1;
+        assert!(check(&newer).is_err());
+
+        let mut other = chain(false);
+        other.format = "persona".to_string();
+        assert!(check(&other).is_err());
+
+        let mut dangling = chain(false);
+        dangling.boost_scores.insert("missing".to_string(), 1.0);
+        assert!(check(&dangling).is_err());
+
+        let mut orphaned = chain(false);
+        orphaned.thought_metadata[0].thought_id = "missing".to_string();
+        assert!(check(&orphaned).is_err());
+
+        let mut repeated = chain(false);
+        repeated.thoughts[1].id = repeated.thoughts[0].id.clone();
+        assert!(check(&repeated).is_err());
+
+        let mut twice = chain(false);
+        twice.thought_metadata.push(twice.thought_metadata[0].clone());
+        assert!(check(&twice).is_err());
+    }
+
+    #[test]
+    fn test_resolve_stays_in_bundle_dir() {
+        let dir = Path::new("/srv/bundles");
+        assert_eq!(resolve(dir, "output_path", "c1.json").unwrap(), dir.join("c1.json"));
+        assert_eq!(resolve(dir, "output_path", "./team/c1.json").unwrap(), dir.join("team/c1.json"));
+        assert_eq!(resolve(dir, "input_path", "/srv/bundles/c1.json").unwrap(), dir.join("c1.json"));
+        for outside in ["../c1.json", "team/../../c1.json", "/etc/passwd", "/srv/bundles-other/c1.json", "", "."] {
+            assert!(resolve(dir, "input_path", outside).is_err(), "{}", outside);
+        }
+    }
+
+    #[test]
+    fn test_check_refuses_edited_sealed_bundles() {
+        let mut edited = chain(true);
+        edited.thoughts[0].thought = "step one, rewritten".to_string();
+        let error = check(&edited).unwrap_err().to_string();
+        assert!(error.contains("altered"), "{}", error);
+
+        let mut dropped = chain(true);
+        dropped.thoughts.remove(0);
+        assert!(check(&dropped).is_err());
+
+        // Thoughts added without a seal after sealed ones are caught too
+        let mut appended = chain(true);
+        let mut extra = appended.thoughts[1].clone();
+        extra.id = "extra".to_string();
+        extra.timestamp = "2025-07-18T10:09:00+00:00".to_string();
+        extra.custody = None;
+        appended.thoughts.push(extra);
+        assert!(check(&appended).is_err());
+    }
+}
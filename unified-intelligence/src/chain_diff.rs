@@ -0,0 +1,183 @@
+//! Comparison of two chains, for ui_chain_diff.
+//!
+//! Thoughts are paired across the chains by content: identical once case
+//! and spacing are ignored, or sharing at least `threshold` of their words
+//! (Jaccard similarity, default 0.6). The most similar pairs are taken
+//! first and each thought is paired at most once. A branch's first thought
+//! ("Branching from: ...") is compared without its prefix, so it pairs with
+//! the thought it branched from. A divergence point is a shared thought
+//! after which the chains go on to thoughts that aren't shared with each
+//! other; chains with nothing in common diverge from the start.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::models::{DiffThought, DivergencePoint, SharedThought, ThoughtRecord};
+use crate::persona;
+
+/// Prefix of the first thought of a chain branched off with ui_recall's branch action
+pub const BRANCH_PREFIX: &str = "Branching from: ";
+
+pub const DEFAULT_THRESHOLD: f32 = 0.6;
+
+/// Characters of a thought quoted in the diff
+const PREVIEW_CHARS: usize = 120;
+
+fn content(text: &str) -> &str {
+    let text = text.trim();
+    text.strip_prefix(BRANCH_PREFIX).unwrap_or(text)
+}
+
+fn normalized(text: &str) -> String {
+    content(text).split_whitespace().map(str::to_lowercase).collect::<Vec<_>>().join(" ")
+}
+
+fn words(text: &str) -> BTreeSet<String> {
+    content(text).split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Similarity of two thoughts: 1.0 for the same text, else the share of words they have in common
+pub fn similarity(a: &str, b: &str) -> f32 {
+    if normalized(a) == normalized(b) {
+        return 1.0;
+    }
+    let (a, b) = (words(a), words(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f32 / union as f32
+}
+
+/// Thoughts of two chains paired as shared, by index (left, right, similarity), in left order
+pub fn pairs(left: &[ThoughtRecord], right: &[ThoughtRecord], threshold: f32) -> Vec<(usize, usize, f32)> {
+    let mut candidates: Vec<(usize, usize, f32)> = left.iter().enumerate()
+        .flat_map(|(i, l)| right.iter().enumerate().map(move |(j, r)| (i, j, similarity(&l.thought, &r.thought))))
+        .filter(|(_, _, similarity)| *similarity >= threshold)
+        .collect();
+    // Most similar first; among equals, thoughts at the same place in their chains
+    candidates.sort_by(|a, b| {
+        b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.0.abs_diff(a.1).cmp(&b.0.abs_diff(b.1)))
+            .then_with(|| (a.0, a.1).cmp(&(b.0, b.1)))
+    });
+    let (mut used_left, mut used_right) = (HashSet::new(), HashSet::new());
+    let mut pairs: Vec<(usize, usize, f32)> = candidates.into_iter()
+        .filter(|(i, j, _)| {
+            let free = !used_left.contains(i) && !used_right.contains(j);
+            if free {
+                used_left.insert(*i);
+                used_right.insert(*j);
+            }
+            free
+        })
+        .collect();
+    pairs.sort_by_key(|(i, _, _)| *i);
+    pairs
+}
+
+fn diff_thought(thought: &ThoughtRecord) -> DiffThought {
+    DiffThought {
+        thought_id: thought.id.clone(),
+        thought_number: thought.thought_number,
+        preview: persona::truncate(content(&thought.thought), PREVIEW_CHARS),
+    }
+}
+
+/// What two chains share and where they part
+#[derive(Debug)]
+pub struct ChainComparison {
+    pub shared: Vec<SharedThought>,
+    pub only_left: Vec<DiffThought>,
+    pub only_right: Vec<DiffThought>,
+    pub divergences: Vec<DivergencePoint>,
+}
+
+/// Compare two chains, each in thought order
+pub fn compare(left: &[ThoughtRecord], right: &[ThoughtRecord], threshold: f32) -> ChainComparison {
+    let pairs = pairs(left, right, threshold);
+    let paired: HashMap<usize, usize> = pairs.iter().map(|(i, j, _)| (*i, *j)).collect();
+    let paired_right: HashSet<usize> = paired.values().copied().collect();
+
+    let mut divergences = Vec::new();
+    if pairs.is_empty() && !(left.is_empty() && right.is_empty()) {
+        divergences.push(DivergencePoint {
+            after_left: None,
+            after_right: None,
+            left_next: left.first().map(diff_thought),
+            right_next: right.first().map(diff_thought),
+        });
+    }
+    for (i, j, _) in &pairs {
+        let (next_left, next_right) = (left.get(i + 1), right.get(j + 1));
+        let both_ended = next_left.is_none() && next_right.is_none();
+        if !both_ended && paired.get(&(i + 1)) != Some(&(j + 1)) {
+            divergences.push(DivergencePoint {
+                after_left: Some(left[*i].thought_number),
+                after_right: Some(right[*j].thought_number),
+                left_next: next_left.map(diff_thought),
+                right_next: next_right.map(diff_thought),
+            });
+        }
+    }
+
+    ChainComparison {
+        shared: pairs.iter().map(|(i, j, similarity)| SharedThought {
+            left: diff_thought(&left[*i]),
+            right: diff_thought(&right[*j]),
+            similarity: *similarity,
+            identical: *similarity >= 1.0,
+        }).collect(),
+        only_left: left.iter().enumerate().filter(|(i, _)| !paired.contains_key(i)).map(|(_, t)| diff_thought(t)).collect(),
+        only_right: right.iter().enumerate().filter(|(j, _)| !paired_right.contains(j)).map(|(_, t)| diff_thought(t)).collect(),
+        divergences,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(chain_id: &str, texts: &[&str]) -> Vec<ThoughtRecord> {
+        texts.iter().enumerate()
+            .map(|(i, text)| ThoughtRecord::new("CC".to_string(), text.to_string(), i as i32 + 1, texts.len() as i32, Some(chain_id.to_string()), true))
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_branch_with_original() {
+        let original = chain("a", &[
+            "Latency spiked after the deploy",
+            "Suspect the new Redis connection pool size",
+            "Roll back the pool size to 16",
+        ]);
+        let branch = chain("b", &[
+            "Branching from: Suspect the new Redis connection pool size",
+            "Profile the slow queries instead",
+            "Latency spiked right after the deploy",
+        ]);
+        let diff = compare(&original, &branch, DEFAULT_THRESHOLD);
+
+        let shared: Vec<(i32, i32, bool)> = diff.shared.iter().map(|s| (s.left.thought_number, s.right.thought_number, s.identical)).collect();
+        assert_eq!(shared, vec![(1, 3, false), (2, 1, true)]);
+        assert_eq!(diff.only_left.iter().map(|t| t.thought_number).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(diff.only_right[0].preview, "Profile the slow queries instead");
+
+        let points: Vec<(Option<i32>, Option<i32>)> = diff.divergences.iter().map(|d| (d.after_left, d.after_right)).collect();
+        assert_eq!(points, vec![(Some(1), Some(3)), (Some(2), Some(1))]);
+        assert!(diff.divergences[0].right_next.is_none());
+        assert_eq!(diff.divergences[1].left_next.as_ref().unwrap().thought_number, 3);
+    }
+
+    #[test]
+    fn test_unrelated_chains_diverge_from_the_start() {
+        let diff = compare(&chain("a", &["Plan the offsite"]), &chain("b", &["Fix the flaky test"]), DEFAULT_THRESHOLD);
+        assert!(diff.shared.is_empty());
+        assert_eq!(diff.divergences.len(), 1);
+        assert_eq!(diff.divergences[0].after_left, None);
+        assert_eq!(similarity("Same  text", "same text"), 1.0);
+        assert_eq!(similarity("a b c d", "a b x y"), 2.0 / 6.0);
+    }
+}
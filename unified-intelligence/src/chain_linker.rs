@@ -0,0 +1,162 @@
+//! Background linking of related chains.
+//!
+//! Thinking sessions often fragment into several chains. Chains created within
+//! UI_CHAIN_LINK_WINDOW_HOURS (default 24) of each other whose topics overlap
+//! by at least UI_CHAIN_LINK_MIN_SCORE (Jaccard, default 0.2) get a "related"
+//! edge in both chains' metadata. A chain's topics are the words of its RAKE
+//! keyword phrases plus its thoughts' tags, so entities named in both chains
+//! (components, people, `repo:` tags) count as shared topics.
+//!
+//! The server relinks chains created in the last UI_CHAIN_LINK_LOOKBACK_DAYS
+//! (default 14) every UI_CHAIN_LINK_INTERVAL_SECS (default 900; 0 disables).
+
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::keywords::{self, Language};
+use crate::models::RelatedChain;
+
+/// Source tags shared by unrelated thoughts; they never link chains
+const GENERIC_TAGS: &[&str] = &["capture", "clipboard", "bookmark", "highlight", "git", "rss", "email", "weekly-review"];
+
+/// Keyword phrases extracted per chain
+const KEYWORDS_PER_CHAIN: usize = 15;
+
+/// Shared topics kept per edge
+const SHARED_TOPICS_SHOWN: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct LinkerConfig {
+    pub window: Duration,
+    pub min_score: f64,
+    pub lookback: Duration,
+    pub max_related: usize,
+}
+
+impl Default for LinkerConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::hours(24),
+            min_score: 0.2,
+            lookback: Duration::days(14),
+            max_related: 5,
+        }
+    }
+}
+
+impl LinkerConfig {
+    pub fn from_env() -> Self {
+        let env = |name: &str| std::env::var(name).ok().filter(|value| !value.trim().is_empty());
+        let defaults = Self::default();
+        Self {
+            window: env("UI_CHAIN_LINK_WINDOW_HOURS").and_then(|v| v.parse().ok()).map(Duration::hours).unwrap_or(defaults.window),
+            min_score: env("UI_CHAIN_LINK_MIN_SCORE").and_then(|v| v.parse().ok()).unwrap_or(defaults.min_score),
+            lookback: env("UI_CHAIN_LINK_LOOKBACK_DAYS").and_then(|v| v.parse().ok()).map(Duration::days).unwrap_or(defaults.lookback),
+            max_related: defaults.max_related,
+        }
+    }
+}
+
+/// Linker interval from UI_CHAIN_LINK_INTERVAL_SECS; None when disabled
+pub fn link_interval() -> Option<std::time::Duration> {
+    let seconds = std::env::var("UI_CHAIN_LINK_INTERVAL_SECS").ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(900);
+    (seconds > 0).then(|| std::time::Duration::from_secs(seconds))
+}
+
+/// A chain with the topics used for linking
+#[derive(Debug, Clone)]
+pub struct ChainTopics {
+    pub chain_id: String,
+    pub created_at: DateTime<Utc>,
+    pub topics: BTreeSet<String>,
+}
+
+/// Topics of a chain from its thought texts and tags
+pub fn chain_topics(texts: &[&str], tags: &[String]) -> BTreeSet<String> {
+    let corpus = texts.join("\n");
+    let mut topics: BTreeSet<String> = keywords::extract_keywords(&corpus, Language::for_text(&corpus), KEYWORDS_PER_CHAIN)
+        .into_iter()
+        .flat_map(|keyword| keyword.phrase.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+        .filter(|word| word.chars().count() >= 3)
+        .collect();
+    topics.extend(tags.iter()
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty() && !GENERIC_TAGS.contains(&tag.as_str())));
+    topics
+}
+
+/// Related edges per chain id, both directions, strongest first
+pub fn find_links(chains: &[ChainTopics], config: &LinkerConfig) -> HashMap<String, Vec<RelatedChain>> {
+    let mut chains: Vec<&ChainTopics> = chains.iter().filter(|chain| !chain.topics.is_empty()).collect();
+    chains.sort_by_key(|chain| chain.created_at);
+
+    let mut links: HashMap<String, Vec<RelatedChain>> = HashMap::new();
+    for (i, a) in chains.iter().enumerate() {
+        for b in chains[i + 1..].iter().take_while(|b| b.created_at - a.created_at <= config.window) {
+            let shared: Vec<String> = a.topics.intersection(&b.topics).cloned().collect();
+            let union = a.topics.union(&b.topics).count();
+            let score = shared.len() as f64 / union as f64;
+            if shared.is_empty() || score < config.min_score {
+                continue;
+            }
+            let shown: Vec<String> = shared.into_iter().take(SHARED_TOPICS_SHOWN).collect();
+            for (from, to) in [(a, b), (b, a)] {
+                links.entry(from.chain_id.clone()).or_default().push(RelatedChain {
+                    chain_id: to.chain_id.clone(),
+                    score: (score * 1000.0).round() / 1000.0,
+                    shared_topics: shown.clone(),
+                });
+            }
+        }
+    }
+    for related in links.values_mut() {
+        related.sort_by(|a, b| b.score.total_cmp(&a.score));
+        related.truncate(config.max_related);
+    }
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(id: &str, hours: i64, topics: &[&str]) -> ChainTopics {
+        ChainTopics {
+            chain_id: id.to_string(),
+            created_at: DateTime::parse_from_rfc3339("2025-07-18T09:00:00Z").unwrap().with_timezone(&Utc) + Duration::hours(hours),
+            topics: topics.iter().map(|topic| topic.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_links_close_chains_with_shared_topics() {
+        let chains = vec![
+            chain("morning", 0, &["redis", "schema", "migration"]),
+            chain("afternoon", 5, &["redis", "schema", "keys"]),
+            chain("unrelated", 6, &["garden", "tomatoes"]),
+            chain("next-month", 24 * 30, &["redis", "schema", "migration"]),
+        ];
+        let links = find_links(&chains, &LinkerConfig::default());
+
+        assert_eq!(links.len(), 2);
+        let morning = &links["morning"];
+        assert_eq!(morning.len(), 1);
+        assert_eq!(morning[0].chain_id, "afternoon");
+        assert_eq!(morning[0].score, 0.5);
+        assert_eq!(morning[0].shared_topics, vec!["redis", "schema"]);
+        assert_eq!(links["afternoon"][0].chain_id, "morning");
+    }
+
+    #[test]
+    fn test_chain_topics_skip_generic_tags() {
+        let topics = chain_topics(
+            &["Redis schema migration plan", "Migrate the redis schema keys"],
+            &["capture".to_string(), "repo:crate".to_string()],
+        );
+        assert!(topics.contains("redis") && topics.contains("schema"));
+        assert!(topics.contains("repo:crate") && !topics.contains("capture"));
+    }
+}
@@ -0,0 +1,115 @@
+//! Ordering and pagination of chains for ui_list_chains.
+//!
+//! Chains are listed by activity: the newest stored thought (`last_updated`)
+//! or, for chains that have none recorded, when the chain was created; ties
+//! go to the chain ID so the order is total. A page ends with a cursor naming
+//! the last chain listed, and the next page starts right after that chain's
+//! position. Keyset cursors stay valid while chains are written: a listed
+//! chain that receives a thought moves ahead of the cursor instead of being
+//! listed twice, though an unlisted one that does also moves ahead and only
+//! shows up in a fresh listing.
+
+use std::cmp::Ordering;
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::ChainMetadata;
+
+/// Chains per page when no limit is given
+pub const DEFAULT_LIMIT: usize = 20;
+
+/// Largest page returned
+pub const MAX_LIMIT: usize = 500;
+
+/// Timestamp chains are ordered by
+pub fn last_activity(chain: &ChainMetadata) -> &str {
+    chain.last_updated.as_deref().unwrap_or(&chain.created_at)
+}
+
+/// Most recently active first, then by chain ID
+fn order(a: (&str, &str), b: (&str, &str)) -> Ordering {
+    b.0.cmp(a.0).then(a.1.cmp(b.1))
+}
+
+/// Position of the last chain on the previous page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    pub last_activity: String,
+    pub chain_id: String,
+}
+
+impl Cursor {
+    fn after(chain: &ChainMetadata) -> Self {
+        Self { last_activity: last_activity(chain).to_string(), chain_id: chain.chain_id.clone() }
+    }
+
+    pub fn parse(value: &str) -> Result<Self> {
+        value.split_once('|')
+            .filter(|(activity, chain_id)| !activity.is_empty() && !chain_id.is_empty())
+            .map(|(activity, chain_id)| Self { last_activity: activity.to_string(), chain_id: chain_id.to_string() })
+            .ok_or_else(|| UnifiedIntelligenceError::Validation {
+                field: "cursor".to_string(),
+                reason: "Not a cursor returned by ui_list_chains".to_string(),
+            })
+    }
+
+    pub fn encode(&self) -> String {
+        format!("{}|{}", self.last_activity, self.chain_id)
+    }
+}
+
+/// One page of chains after `cursor`, with the cursor of the next page when more remain
+pub fn page(mut chains: Vec<ChainMetadata>, cursor: Option<&Cursor>, limit: usize) -> (Vec<ChainMetadata>, Option<Cursor>) {
+    chains.sort_by(|a, b| order((last_activity(a), &a.chain_id), (last_activity(b), &b.chain_id)));
+    if let Some(cursor) = cursor {
+        chains.retain(|chain| {
+            order((&cursor.last_activity, &cursor.chain_id), (last_activity(chain), &chain.chain_id)) == Ordering::Less
+        });
+    }
+    let limit = limit.clamp(1, MAX_LIMIT);
+    let next = (chains.len() > limit).then(|| Cursor::after(&chains[limit - 1]));
+    chains.truncate(limit);
+    (chains, next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(chain_id: &str, created_at: &str, last_updated: Option<&str>) -> ChainMetadata {
+        ChainMetadata {
+            chain_id: chain_id.to_string(),
+            created_at: created_at.to_string(),
+            thought_count: 1,
+            instance: "CC".to_string(),
+            user_id: None,
+            related: Vec::new(),
+            planned_thoughts: None,
+            observed_thoughts: None,
+            last_updated: last_updated.map(str::to_string),
+            next_hints: None,
+        }
+    }
+
+    #[test]
+    fn test_pages_by_activity() {
+        let chains = vec![
+            chain("old", "2025-07-01T00:00:00+00:00", Some("2025-07-20T00:00:00+00:00")),
+            chain("new", "2025-07-15T00:00:00+00:00", None),
+            chain("b", "2025-07-10T00:00:00+00:00", Some("2025-07-12T00:00:00+00:00")),
+            chain("a", "2025-07-11T00:00:00+00:00", Some("2025-07-12T00:00:00+00:00")),
+        ];
+        let ids = |page: &[ChainMetadata]| page.iter().map(|c| c.chain_id.clone()).collect::<Vec<_>>();
+
+        let (first, next) = page(chains.clone(), None, 3);
+        assert_eq!(ids(&first), vec!["old", "new", "a"]);
+        let next = Cursor::parse(&next.unwrap().encode()).unwrap();
+        let (second, last) = page(chains.clone(), Some(&next), 3);
+        assert_eq!((ids(&second), last), (vec!["b".to_string()], None));
+
+        // A listed chain written between pages moves ahead of the cursor and isn't listed again
+        let mut written = chains;
+        written[0].last_updated = Some("2025-07-21T00:00:00+00:00".to_string());
+        assert_eq!(ids(&page(written, Some(&next), 3).0), vec!["b"]);
+        assert!(Cursor::parse("no-separator").is_err());
+    }
+}
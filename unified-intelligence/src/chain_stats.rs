@@ -0,0 +1,158 @@
+//! Per-chain quality metrics for ui_chain_stats.
+//!
+//! A chain is measured against its own plan: how many thoughts it has next
+//! to the total the last thought announced, how often that plan changed and
+//! how many thought numbers were written more than once (revisions). The
+//! confidence of a thought is read from its wording, 0.5 plus 0.1 for every
+//! certainty marker ("confirmed", "definitely") and minus 0.1 for every hedge
+//! ("maybe", "not sure"), so the trajectory shows whether a chain converged
+//! or drifted. A chain is `completed` once its last thought needs no
+//! successor, `abandoned` when it has been idle for ABANDONED_AFTER_DAYS and
+//! `open` otherwise. Numbering mismatches come from the reconcile module.
+
+use std::collections::{BTreeMap, HashSet};
+
+use crate::models::{ChainStats, ThoughtRecord};
+use crate::reconcile;
+use crate::training_export;
+
+/// Idle days after which an unfinished chain counts as abandoned
+pub const ABANDONED_AFTER_DAYS: i64 = 7;
+
+/// Chains reported when no chain_id is given
+pub const DEFAULT_CHAIN_LIMIT: usize = 10;
+
+/// Framework recorded for thoughts stored without one
+const SEQUENTIAL: &str = "sequential";
+
+const HEDGES: &[&str] = &[
+    "maybe", "perhaps", "possibly", "might", "probably", "unclear", "unsure", "not sure", "i think",
+    "i guess", "seems", "could be", "hypothesis",
+];
+
+const CERTAINTIES: &[&str] = &[
+    "confirmed", "definitely", "verified", "clearly", "certain", "proven", "fixed", "resolved",
+    "root cause is", "the answer is", "conclusion",
+];
+
+/// Change in confidence between the first and last thought that counts as a trend
+const TREND_THRESHOLD: f64 = 0.1;
+
+/// Confidence of a thought from its wording, 0.0-1.0; a question counts as a hedge
+pub fn confidence(text: &str) -> f64 {
+    let words: Vec<String> = text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect();
+    let padded = format!(" {} ", words.join(" "));
+    let count = |markers: &[&str]| markers.iter().filter(|marker| padded.contains(&format!(" {} ", marker))).count() as f64;
+    let hedges = count(HEDGES) + if text.contains('?') { 1.0 } else { 0.0 };
+    let score = 0.5 + 0.1 * count(CERTAINTIES) - 0.1 * hedges;
+    (score.clamp(0.0, 1.0) * 100.0).round() / 100.0
+}
+
+/// `rising`, `falling` or `steady` from the first to the last value
+pub fn trend(values: &[f64]) -> &'static str {
+    match (values.first(), values.last()) {
+        (Some(first), Some(last)) if last - first > TREND_THRESHOLD => "rising",
+        (Some(first), Some(last)) if first - last > TREND_THRESHOLD => "falling",
+        _ => "steady",
+    }
+}
+
+/// Metrics of one chain's thoughts
+pub fn stats(chain_id: &str, thoughts: &[ThoughtRecord], now: chrono::DateTime<chrono::Utc>) -> ChainStats {
+    let mut ordered: Vec<&ThoughtRecord> = thoughts.iter().collect();
+    ordered.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.thought_number.cmp(&b.thought_number)));
+
+    let mut seen = HashSet::new();
+    let revisions = ordered.iter().filter(|t| !seen.insert(t.thought_number)).count();
+    let plan_changes = ordered.windows(2).filter(|pair| pair[0].total_thoughts != pair[1].total_thoughts).count();
+    let planned_total = ordered.last().map(|t| t.total_thoughts).unwrap_or(0);
+    let distinct = seen.len();
+
+    let mut frameworks = BTreeMap::new();
+    for thought in &ordered {
+        *frameworks.entry(thought.framework.clone().unwrap_or_else(|| SEQUENTIAL.to_string())).or_insert(0) += 1;
+    }
+
+    let parse = |t: Option<&&ThoughtRecord>| t.and_then(|t| chrono::DateTime::parse_from_rfc3339(&t.timestamp).ok());
+    let (started, ended) = (parse(ordered.first()), parse(ordered.last()));
+    let span_seconds = match (started, ended) {
+        (Some(start), Some(end)) => end.signed_duration_since(start).num_seconds().max(0),
+        _ => 0,
+    };
+
+    let status = if training_export::is_concluded(thoughts) {
+        "completed"
+    } else if ended.is_some_and(|end| now.signed_duration_since(end) >= chrono::Duration::days(ABANDONED_AFTER_DAYS)) {
+        "abandoned"
+    } else {
+        "open"
+    };
+
+    let confidence: Vec<f64> = ordered.iter().map(|t| confidence(&t.thought)).collect();
+    let total_chars: usize = ordered.iter().map(|t| t.thought.chars().count()).sum();
+    ChainStats {
+        chain_id: chain_id.to_string(),
+        status: status.to_string(),
+        thought_count: ordered.len(),
+        planned_total,
+        initial_plan: ordered.first().map(|t| t.total_thoughts).unwrap_or(0),
+        completion: if planned_total > 0 { (distinct as f64 / planned_total as f64).min(1.0) } else { 0.0 },
+        average_chars: if ordered.is_empty() { 0.0 } else { total_chars as f64 / ordered.len() as f64 },
+        revisions,
+        plan_changes,
+        started_at: ordered.first().map(|t| t.timestamp.clone()),
+        ended_at: ordered.last().map(|t| t.timestamp.clone()),
+        span_seconds,
+        frameworks,
+        confidence_trend: trend(&confidence).to_string(),
+        confidence,
+        reconciled_total: reconcile::reconciled_total(thoughts),
+        discrepancies: reconcile::audit(thoughts),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thought(number: i32, total: i32, minutes: i64, text: &str, next: bool) -> ThoughtRecord {
+        let mut thought = ThoughtRecord::new("CC".to_string(), text.to_string(), number, total, Some("c1".to_string()), next);
+        thought.timestamp = (chrono::Utc::now() - chrono::Duration::days(10) + chrono::Duration::minutes(minutes)).to_rfc3339();
+        thought
+    }
+
+    #[test]
+    fn test_confidence() {
+        assert_eq!(confidence("Maybe the cache is stale? Not sure."), 0.2);
+        assert_eq!(confidence("Confirmed: the root cause is the missing index, verified in staging."), 0.8);
+        assert_eq!(confidence("Rebuilding the index."), 0.5);
+        assert_eq!(trend(&[0.3, 0.5, 0.7]), "rising");
+        assert_eq!(trend(&[0.5]), "steady");
+    }
+
+    #[test]
+    fn test_chain_stats() {
+        let mut ooda = thought(2, 3, 10, "Maybe the deploy script?", true);
+        ooda.framework = Some("ooda".to_string());
+        let thoughts = vec![
+            thought(1, 3, 0, "Why did the deploy fail?", true),
+            ooda,
+            thought(2, 4, 20, "Perhaps it was the migration", true),
+            thought(3, 4, 30, "Confirmed: the migration timed out", true),
+        ];
+        let stats = stats("c1", &thoughts, chrono::Utc::now());
+        assert_eq!((stats.thought_count, stats.planned_total, stats.initial_plan), (4, 4, 3));
+        assert_eq!((stats.revisions, stats.plan_changes, stats.span_seconds), (1, 1, 1800));
+        assert_eq!(stats.completion, 0.75);
+        assert_eq!(stats.frameworks.get("ooda"), Some(&1));
+        assert_eq!(stats.frameworks.get(SEQUENTIAL), Some(&3));
+        assert_eq!(stats.confidence_trend, "rising");
+        assert_eq!(stats.status, "abandoned");
+        assert_eq!(stats.reconciled_total, 4);
+        assert!(stats.discrepancies.is_empty());
+    }
+}
@@ -0,0 +1,302 @@
+//! Round trip between thought chains and Obsidian notes.
+//!
+//! A chain is exported to `{OBSIDIAN_VAULT_PATH}/{OBSIDIAN_CHAIN_FOLDER}/{chain_id}-{hash}.md`
+//! (the chain ID made file-safe, plus a short hash of the raw ID so distinct
+//! IDs never share a note) with one `## Thought N` section per thought, each tagged with a hidden
+//! `<!-- ui:thought {id} -->` marker. When a human edits the note, anything that
+//! isn't exported thought content (text appended under a thought, or new
+//! sections) is imported back into the chain as a thought tagged
+//! `human-annotation`, and the note is re-rendered so both views agree.
+//!
+//! ui_export_to_vault writes its notes with the same renderer, adding the
+//! instance to the frontmatter, so its exports stay readable here too.
+
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+
+use crate::models::{Citation, ThoughtMetadata, ThoughtRecord};
+
+/// Tag applied to thoughts imported from note edits
+pub const ANNOTATION_TAG: &str = "human-annotation";
+
+/// Default vault folder for exported chains
+const DEFAULT_CHAIN_FOLDER: &str = "Thought Chains";
+
+const MARKER_PREFIX: &str = "<!-- ui:thought ";
+const MARKER_SUFFIX: &str = " -->";
+
+/// Generated line listing a thought's citations
+const CITES_PREFIX: &str = "Cites: ";
+
+/// Frontmatter field holding the hash of the rendered body
+pub const HASH_FIELD: &str = "content_hash: ";
+
+/// Where chain notes live in the vault
+#[derive(Debug, Clone)]
+pub struct ChainSyncConfig {
+    pub vault_path: PathBuf,
+    pub folder: String,
+}
+
+impl ChainSyncConfig {
+    /// Read OBSIDIAN_VAULT_PATH (required) and OBSIDIAN_CHAIN_FOLDER
+    pub fn from_env() -> Option<Self> {
+        let vault_path = std::env::var("OBSIDIAN_VAULT_PATH").ok().filter(|p| !p.trim().is_empty())?;
+        let folder = std::env::var("OBSIDIAN_CHAIN_FOLDER").unwrap_or_else(|_| DEFAULT_CHAIN_FOLDER.to_string());
+        Some(Self { vault_path: PathBuf::from(vault_path), folder })
+    }
+
+    /// Vault-relative path of a chain's note
+    pub fn relative_note_path(&self, chain_id: &str) -> String {
+        let file_name: String = chain_id.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        // Sanitizing can map different IDs to one name; the hash keeps their notes apart
+        format!("{}/{}-{}.md", self.folder.trim_matches('/'), file_name, &note_hash(chain_id)[..8])
+    }
+
+    /// Absolute path of a chain's note
+    pub fn note_path(&self, chain_id: &str) -> PathBuf {
+        self.vault_path.join(self.relative_note_path(chain_id))
+    }
+}
+
+/// Hex SHA-256 of note content, used to detect human edits
+pub fn note_hash(content: &str) -> String {
+    Sha256::digest(content.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Quote a value for YAML frontmatter; JSON strings are valid YAML scalars
+pub fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// What a rendered note is for
+#[derive(Debug, Clone, Copy)]
+pub enum NoteKind<'a> {
+    /// ui_chain_sync's round-trip note
+    Sync,
+    /// ui_export_to_vault's note, linked from the rest of the vault
+    Export { instance: &'a str },
+}
+
+/// A thought with the metadata the note shows
+pub struct NoteThought<'a> {
+    pub thought: &'a ThoughtRecord,
+    pub metadata: Option<&'a ThoughtMetadata>,
+}
+
+/// Wikilink to a cited note, down to the cited heading
+pub fn wikilink(citation: &Citation) -> String {
+    let note = citation.note_path.trim().trim_end_matches(".md");
+    match citation.heading.as_deref().map(str::trim).filter(|heading| !heading.is_empty()) {
+        Some(heading) => {
+            let headings: Vec<&str> = heading.split('>').map(str::trim).filter(|h| !h.is_empty()).collect();
+            format!("[[{}#{}]]", note, headings.join("#"))
+        }
+        None => format!("[[{}]]", note),
+    }
+}
+
+/// Render a chain (thoughts in order) as an Obsidian note
+pub fn render_note(chain_id: &str, kind: NoteKind, thoughts: &[NoteThought]) -> String {
+    let mut body = format!("# Thought chain {}\n", chain_id);
+    if let NoteKind::Sync = kind {
+        body.push_str("\n> Add notes below any thought, or new `##` sections; they sync back to the chain as annotations.\n");
+    }
+    let mut linked: BTreeSet<String> = BTreeSet::new();
+    let mut tags: BTreeSet<String> = BTreeSet::from(["thought-chain".to_string()]);
+    for NoteThought { thought, metadata } in thoughts {
+        body.push_str(&format!("\n## Thought {}\n", thought.thought_number));
+        body.push_str(&format!("{}{}{}\n", MARKER_PREFIX, thought.id, MARKER_SUFFIX));
+        body.push_str(&format!("*{}*\n\n", thought.timestamp));
+        body.push_str(thought.thought.trim());
+        body.push('\n');
+        let Some(metadata) = metadata else {
+            continue;
+        };
+        let links: Vec<String> = metadata.citations.iter().map(wikilink).collect();
+        if !links.is_empty() {
+            body.push_str(&format!("\n{}{}\n", CITES_PREFIX, links.join(", ")));
+        }
+        linked.extend(metadata.citations.iter().map(|c| format!("[[{}]]", c.note_path.trim().trim_end_matches(".md"))));
+        tags.extend(metadata.tags.iter().flatten().map(|tag| tag.trim().replace(' ', "-")).filter(|tag| !tag.is_empty()));
+    }
+
+    let timestamps = || thoughts.iter().map(|t| t.thought.timestamp.as_str());
+    let mut note = String::from("---\n");
+    note.push_str(&format!("chain_id: {}\n", yaml_string(chain_id)));
+    if let NoteKind::Export { instance } = kind {
+        note.push_str(&format!("instance: {}\n", yaml_string(instance)));
+    }
+    note.push_str(&format!("thought_count: {}\n", thoughts.len()));
+    if let (Some(started), Some(updated)) = (timestamps().min(), timestamps().max()) {
+        note.push_str(&format!("started_at: {}\n", yaml_string(started)));
+        note.push_str(&format!("updated_at: {}\n", yaml_string(updated)));
+    }
+    note.push_str(&format!("exported_at: {}\n", yaml_string(&chrono::Utc::now().to_rfc3339())));
+    note.push_str(&format!("tags: [{}]\n", tags.iter().map(|tag| yaml_string(tag)).collect::<Vec<_>>().join(", ")));
+    if !linked.is_empty() {
+        note.push_str("cites:\n");
+        for link in &linked {
+            note.push_str(&format!("  - {}\n", yaml_string(link)));
+        }
+    }
+    note.push_str(&format!("{}{}\n", HASH_FIELD, note_hash(&body)));
+    note.push_str("---\n\n");
+    note.push_str(&body);
+    note
+}
+
+/// Text a human added to the note
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    /// Thought number the annotation was written under (None for new sections)
+    pub on_thought: Option<i32>,
+    /// Heading of a human-created section
+    pub heading: Option<String>,
+    pub text: String,
+}
+
+impl Annotation {
+    /// Content stored for the imported thought
+    pub fn to_thought_content(&self) -> String {
+        match (self.on_thought, &self.heading) {
+            (Some(number), _) => format!("[Annotation on thought {}] {}", number, self.text),
+            (None, Some(heading)) => format!("[Annotation: {}] {}", heading, self.text),
+            (None, None) => format!("[Annotation] {}", self.text),
+        }
+    }
+}
+
+/// Extract human annotations by comparing a note against the chain's thoughts
+pub fn extract_annotations(note: &str, thoughts: &[ThoughtRecord]) -> Vec<Annotation> {
+    let by_id: HashMap<&str, &ThoughtRecord> = thoughts.iter().map(|t| (t.id.as_str(), t)).collect();
+    let mut annotations = Vec::new();
+
+    for (heading, body) in sections(strip_frontmatter(note)) {
+        let Some(heading) = heading else {
+            continue; // Title and intro are ours
+        };
+
+        let mut lines = body.lines().skip_while(|l| l.trim().is_empty());
+        let marker_id = lines.clone().next()
+            .and_then(|l| l.trim().strip_prefix(MARKER_PREFIX))
+            .and_then(|l| l.strip_suffix(MARKER_SUFFIX.trim_start()))
+            .map(str::trim);
+
+        match marker_id.and_then(|id| by_id.get(id)) {
+            Some(thought) => {
+                lines.next();
+                let text = lines.collect::<Vec<_>>().join("\n");
+                let text = text.trim();
+                let timestamp = format!("*{}*", thought.timestamp);
+                let text = text.strip_prefix(timestamp.as_str()).map(str::trim).unwrap_or(text);
+                let original = thought.thought.trim();
+                let added = match text.strip_prefix(original) {
+                    Some(rest) => rest.trim(),
+                    None => text, // Rewritten by the human; keep their version
+                };
+                // The citation line is ours; only what follows it is human text
+                let added = match added.strip_prefix(CITES_PREFIX) {
+                    Some(rest) => rest.split_once('\n').map(|(_, after)| after.trim()).unwrap_or_default(),
+                    None => added,
+                };
+                if !added.is_empty() {
+                    annotations.push(Annotation {
+                        on_thought: Some(thought.thought_number),
+                        heading: None,
+                        text: added.to_string(),
+                    });
+                }
+            }
+            // Sections marked with an unknown id are stale exports, not human text
+            None if marker_id.is_none() => {
+                annotations.push(Annotation {
+                    on_thought: None,
+                    heading: Some(heading.to_string()),
+                    text: body.trim().to_string(),
+                });
+            }
+            None => {}
+        }
+    }
+
+    annotations
+}
+
+/// Drop a leading YAML frontmatter block
+fn strip_frontmatter(note: &str) -> &str {
+    if let Some(rest) = note.strip_prefix("---\n") {
+        if let Some(end) = rest.find("\n---\n") {
+            return &rest[end + 5..];
+        }
+    }
+    note
+}
+
+/// Split a note into (`##` heading, body) sections; the preamble has no heading
+fn sections(note: &str) -> Vec<(Option<&str>, String)> {
+    let mut sections: Vec<(Option<&str>, String)> = vec![(None, String::new())];
+    for line in note.lines() {
+        if let Some(heading) = line.strip_prefix("## ") {
+            sections.push((Some(heading.trim()), String::new()));
+        } else if let Some((_, body)) = sections.last_mut() {
+            body.push_str(line);
+            body.push('\n');
+        }
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thought(number: i32, content: &str) -> ThoughtRecord {
+        ThoughtRecord::new("test".to_string(), content.to_string(), number, 2, Some("c1".to_string()), number < 2)
+    }
+
+    fn sync_note(chain_id: &str, thoughts: &[ThoughtRecord]) -> String {
+        let shown: Vec<NoteThought> = thoughts.iter().map(|thought| NoteThought { thought, metadata: None }).collect();
+        render_note(chain_id, NoteKind::Sync, &shown)
+    }
+
+    #[test]
+    fn test_unedited_note_has_no_annotations() {
+        let thoughts = vec![thought(1, "First idea"), thought(2, "Second idea")];
+        let note = sync_note("c1", &thoughts);
+        assert!(extract_annotations(&note, &thoughts).is_empty());
+    }
+
+    #[test]
+    fn test_extracts_appended_text_and_new_sections() {
+        let thoughts = vec![thought(1, "First idea"), thought(2, "Second idea")];
+        let note = sync_note("c1", &thoughts)
+            .replace("First idea\n", "First idea\n\nI disagree with this.\n")
+            + "\n## Follow-up\nCheck the benchmarks.\n";
+
+        let annotations = extract_annotations(&note, &thoughts);
+        assert_eq!(annotations, vec![
+            Annotation { on_thought: Some(1), heading: None, text: "I disagree with this.".to_string() },
+            Annotation { on_thought: None, heading: Some("Follow-up".to_string()), text: "Check the benchmarks.".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn test_relative_note_path_sanitizes_chain_id() {
+        let config = ChainSyncConfig { vault_path: PathBuf::from("/vault"), folder: "Chains/".to_string() };
+        let path = config.relative_note_path("my chain/1");
+        assert!(path.starts_with("Chains/my_chain_1-") && path.ends_with(".md"), "{}", path);
+        assert_eq!(path, config.relative_note_path("my chain/1"));
+        assert_ne!(config.relative_note_path("a.b"), config.relative_note_path("a_b"));
+    }
+
+    #[test]
+    fn test_frontmatter_quotes_chain_id() {
+        let note = sync_note("say \"hi\"\nagain", &[thought(1, "First idea")]);
+        assert!(note.contains("chain_id: \"say \\\"hi\\\"\\nagain\"\n"), "{}", note);
+        assert!(note.contains("\n# Thought chain"));
+    }
+}
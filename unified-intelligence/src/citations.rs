@@ -0,0 +1,89 @@
+//! Vault note citations for ui_think and ui_citations.
+//!
+//! A thought can cite the vault notes it drew on: the note path relative to
+//! the vault, optionally the section heading and the section's content hash
+//! as returned by um_search, so a later reader can tell whether the section
+//! changed since. Citations are kept in the thought's metadata and indexed in
+//! `{instance}:citations:{note_path}` so ui_citations can list every thought
+//! that cites a note.
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::Citation;
+
+/// Citations accepted per thought
+pub const MAX_CITATIONS: usize = 20;
+
+/// Vault-relative note path with forward slashes and a `.md` extension
+pub fn normalize_path(path: &str) -> String {
+    let path = path.trim().replace('\\', "/");
+    let path = path.trim_start_matches("./").trim_start_matches('/');
+    if path.is_empty() || path.to_lowercase().ends_with(".md") {
+        path.to_string()
+    } else {
+        format!("{}.md", path)
+    }
+}
+
+/// Normalize citations, dropping duplicates; every citation needs a note path
+pub fn validate(citations: Vec<Citation>) -> Result<Vec<Citation>> {
+    if citations.len() > MAX_CITATIONS {
+        return Err(UnifiedIntelligenceError::Validation {
+            field: "citations".to_string(),
+            reason: format!("At most {} citations per thought, got {}", MAX_CITATIONS, citations.len()),
+        });
+    }
+    let mut normalized: Vec<Citation> = Vec::with_capacity(citations.len());
+    for citation in citations {
+        let citation = Citation {
+            note_path: normalize_path(&citation.note_path),
+            heading: citation.heading.map(|h| h.trim().to_string()).filter(|h| !h.is_empty()),
+            hash: citation.hash.map(|h| h.trim().to_string()).filter(|h| !h.is_empty()),
+        };
+        if citation.note_path.is_empty() {
+            return Err(UnifiedIntelligenceError::Validation {
+                field: "citations".to_string(),
+                reason: "Every citation needs a note_path".to_string(),
+            });
+        }
+        if !normalized.contains(&citation) {
+            normalized.push(citation);
+        }
+    }
+    Ok(normalized)
+}
+
+/// Whether a citation points at a note, and at a heading containing `heading` when one is given
+pub fn cites(citation: &Citation, note_path: &str, heading: Option<&str>) -> bool {
+    citation.note_path == note_path
+        && heading.is_none_or(|wanted| {
+            citation.heading.as_ref().is_some_and(|h| h.to_lowercase().contains(&wanted.trim().to_lowercase()))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn citation(path: &str, heading: Option<&str>) -> Citation {
+        Citation { note_path: path.to_string(), heading: heading.map(str::to_string), hash: None }
+    }
+
+    #[test]
+    fn test_validate_citations() {
+        assert_eq!(normalize_path(" /Projects\\Redis "), "Projects/Redis.md");
+        assert_eq!(normalize_path("./Inbox.md"), "Inbox.md");
+
+        let validated = validate(vec![
+            citation("Projects/Redis", Some(" Redis > Ports ")),
+            citation("Projects/Redis.md", Some("Redis > Ports")),
+            citation("Daily/2025-07-18.md", None),
+        ]).unwrap();
+        assert_eq!(validated.len(), 2);
+        assert_eq!(validated[0].heading.as_deref(), Some("Redis > Ports"));
+        assert!(validate(vec![citation("  ", None)]).is_err());
+
+        assert!(cites(&validated[0], "Projects/Redis.md", Some("ports")));
+        assert!(!cites(&validated[1], "Daily/2025-07-18.md", Some("ports")));
+        assert!(cites(&validated[1], "Daily/2025-07-18.md", None));
+    }
+}
@@ -0,0 +1,223 @@
+//! Clipboard capture for the ui_clipboard daemon.
+//!
+//! The daemon is paused by default: it only reads the clipboard while an
+//! `active` marker exists in the state directory (`ui_clipboard resume` creates
+//! it, `ui_clipboard pause` removes it). The first snapshot after resuming is
+//! a baseline and never stored, so nothing copied while paused is captured.
+//!
+//! Environment:
+//! - UI_CLIPBOARD_STATE_DIR: marker and blocklist location (default `~/.unified-intelligence/clipboard`)
+//! - UI_CLIPBOARD_COMMAND: prints the clipboard (default `pbpaste`, `wl-paste --no-newline` or `xclip -selection clipboard -o`)
+//! - UI_CLIPBOARD_APP_COMMAND: prints the frontmost application (default: osascript on macOS, xdotool on X11)
+//! - UI_CLIPBOARD_BLOCK_APPS: comma-separated applications never captured from (default: common password managers)
+//! - UI_CLIPBOARD_INTERVAL_SECS (default 2), UI_CLIPBOARD_MIN_CHARS (default 40),
+//!   UI_CLIPBOARD_MIN_WORDS (default 6), UI_CLIPBOARD_MAX_CHARS (default 4000)
+//!
+//! `{state_dir}/blocklist` holds one regex per line (`#` starts a comment);
+//! snippets matching any of them are skipped, as are snippets containing PII.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use regex::Regex;
+
+use crate::pii::{PiiPolicy, PiiScanner};
+
+/// Tag applied to every clipboard thought
+pub const CLIPBOARD_TAG: &str = "clipboard";
+
+const DEFAULT_BLOCKED_APPS: &str = "1Password,Bitwarden,KeePassXC,Keychain Access,LastPass,Dashlane";
+
+/// Decides which clipboard snippets are worth keeping
+pub struct SignificanceFilter {
+    pub min_chars: usize,
+    pub min_words: usize,
+    pub max_chars: usize,
+    pub blocked_apps: Vec<String>,       // Lowercase; matched as substrings of the app name
+    pub blocked_patterns: Vec<Regex>,
+    pii: PiiScanner,
+}
+
+impl SignificanceFilter {
+    pub fn new(blocked_apps: Vec<String>, blocked_patterns: Vec<Regex>) -> Self {
+        Self {
+            min_chars: 40,
+            min_words: 6,
+            max_chars: 4000,
+            blocked_apps: blocked_apps.into_iter().map(|app| app.trim().to_lowercase()).filter(|app| !app.is_empty()).collect(),
+            blocked_patterns,
+            // Always scan, whatever PII_POLICY says: flagged snippets are dropped, not stored
+            pii: PiiScanner::new(PiiPolicy::Flag, None),
+        }
+    }
+
+    /// Ok when the snippet should be stored, otherwise the reason it was skipped
+    pub fn assess(&self, text: &str, app: Option<&str>) -> Result<(), String> {
+        if let Some(app) = app {
+            let lower = app.to_lowercase();
+            if self.blocked_apps.iter().any(|blocked| lower.contains(blocked.as_str())) {
+                return Err(format!("copied from blocked app '{}'", app));
+            }
+        }
+        let text = text.trim();
+        let chars = text.chars().count();
+        if chars < self.min_chars || chars > self.max_chars {
+            return Err(format!("{} chars, outside {}-{}", chars, self.min_chars, self.max_chars));
+        }
+        let words = text.split_whitespace().count();
+        if words < self.min_words {
+            return Err(format!("only {} words", words));
+        }
+        // Mostly symbols or digits: data dumps, hashes, minified code
+        let letters = text.chars().filter(|c| c.is_alphabetic()).count();
+        if letters * 2 < chars {
+            return Err("mostly non-text".to_string());
+        }
+        if let Some(pattern) = self.blocked_patterns.iter().find(|pattern| pattern.is_match(text)) {
+            return Err(format!("matches blocklist pattern '{}'", pattern.as_str()));
+        }
+        if let Some(finding) = self.pii.scan(text).first() {
+            return Err(format!("contains {}", finding.kind));
+        }
+        Ok(())
+    }
+}
+
+/// Daemon configuration
+pub struct ClipboardConfig {
+    pub state_dir: PathBuf,
+    pub read_command: String,
+    pub app_command: Option<String>,
+    pub interval: Duration,
+    pub filter: SignificanceFilter,
+}
+
+impl ClipboardConfig {
+    pub fn from_env() -> Result<Self, String> {
+        let env = |name: &str| std::env::var(name).ok().filter(|value| !value.trim().is_empty());
+        let number = |name: &str, default: usize| env(name).and_then(|value| value.parse().ok()).unwrap_or(default);
+
+        let state_dir = env("UI_CLIPBOARD_STATE_DIR").map(PathBuf::from).unwrap_or_else(|| {
+            PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| ".".to_string())).join(".unified-intelligence/clipboard")
+        });
+        let read_command = env("UI_CLIPBOARD_COMMAND").unwrap_or_else(|| {
+            if cfg!(target_os = "macos") {
+                "pbpaste".to_string()
+            } else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+                "wl-paste --no-newline".to_string()
+            } else {
+                "xclip -selection clipboard -o".to_string()
+            }
+        });
+        let app_command = env("UI_CLIPBOARD_APP_COMMAND").or_else(|| {
+            if cfg!(target_os = "macos") {
+                Some(r#"osascript -e 'tell application "System Events" to get name of first application process whose frontmost is true'"#.to_string())
+            } else if std::env::var_os("DISPLAY").is_some() {
+                Some("xdotool getactivewindow getwindowclassname".to_string())
+            } else {
+                None
+            }
+        });
+
+        let blocked_apps = env("UI_CLIPBOARD_BLOCK_APPS").unwrap_or_else(|| DEFAULT_BLOCKED_APPS.to_string());
+        let mut filter = SignificanceFilter::new(
+            blocked_apps.split(',').map(str::to_string).collect(),
+            load_blocklist(&state_dir.join("blocklist"))?,
+        );
+        filter.min_chars = number("UI_CLIPBOARD_MIN_CHARS", filter.min_chars);
+        filter.min_words = number("UI_CLIPBOARD_MIN_WORDS", filter.min_words);
+        filter.max_chars = number("UI_CLIPBOARD_MAX_CHARS", filter.max_chars);
+
+        Ok(Self {
+            state_dir,
+            read_command,
+            app_command,
+            interval: Duration::from_secs(number("UI_CLIPBOARD_INTERVAL_SECS", 2).max(1) as u64),
+            filter,
+        })
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.state_dir.join("active").exists()
+    }
+
+    /// Resume (create the marker) or pause (remove it) capturing
+    pub fn set_active(&self, active: bool) -> std::io::Result<()> {
+        let marker = self.state_dir.join("active");
+        if active {
+            std::fs::create_dir_all(&self.state_dir)?;
+            std::fs::write(marker, b"")
+        } else if marker.exists() {
+            std::fs::remove_file(marker)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Blocklist regexes, one per line; a missing file is an empty list
+pub fn load_blocklist(path: &Path) -> Result<Vec<Regex>, String> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Ok(Vec::new());
+    };
+    contents.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| Regex::new(line).map_err(|e| format!("{}: invalid pattern '{}': {}", path.display(), line, e)))
+        .collect()
+}
+
+/// Run a shell command and return its stdout
+pub fn read_command(command: &str) -> Result<String, String> {
+    let output = Command::new("sh")
+        .args(["-c", command])
+        .output()
+        .map_err(|e| format!("failed to run '{}': {}", command, e))?;
+    if !output.status.success() {
+        return Err(format!("'{}' exited with {}: {}", command, output.status, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "Redis memory grows because search cache entries never expire after reindexing.";
+
+    #[test]
+    fn test_significance_filter() {
+        let filter = SignificanceFilter::new(vec!["1Password".to_string()], vec![Regex::new("(?i)confidential").unwrap()]);
+        assert!(filter.assess(NOTE, Some("Safari")).is_ok());
+        assert!(filter.assess(NOTE, Some("1Password 8")).unwrap_err().contains("blocked app"));
+        assert!(filter.assess("short copy", None).is_err());
+        assert!(filter.assess("a0f3c9e1 77b2d4a8 e5f6a7b8 c9d0e1f2 a3b4c5d6 e7f8a9b0 1234", None).is_err());
+        assert!(filter.assess(&format!("CONFIDENTIAL {}", NOTE), None).unwrap_err().contains("blocklist"));
+        assert_eq!(filter.assess(&format!("{} Mail sam@example.com", NOTE), None).unwrap_err(), "contains email");
+    }
+
+    #[test]
+    fn test_pause_marker_and_blocklist() {
+        let state_dir = std::env::temp_dir().join(format!("ui-clipboard-{}", uuid::Uuid::new_v4()));
+        let config = ClipboardConfig {
+            state_dir: state_dir.clone(),
+            read_command: "echo".to_string(),
+            app_command: None,
+            interval: Duration::from_secs(1),
+            filter: SignificanceFilter::new(Vec::new(), Vec::new()),
+        };
+        assert!(!config.is_active());
+        config.set_active(true).unwrap();
+        assert!(config.is_active());
+        config.set_active(false).unwrap();
+        assert!(!config.is_active());
+
+        std::fs::write(state_dir.join("blocklist"), "# secrets\npassword\n\n^ssh-").unwrap();
+        assert_eq!(load_blocklist(&state_dir.join("blocklist")).unwrap().len(), 2);
+        std::fs::write(state_dir.join("blocklist"), "(unclosed").unwrap();
+        assert!(load_blocklist(&state_dir.join("blocklist")).is_err());
+        std::fs::remove_dir_all(&state_dir).unwrap();
+        assert_eq!(read_command("printf snippet").unwrap(), "snippet");
+    }
+}
@@ -0,0 +1,127 @@
+//! Crash reports for panics and fatal errors.
+//!
+//! Reports are kept newest first at `{instance}:crash_reports` (at most
+//! MAX_CRASH_REPORTS) with a backtrace, the last tool calls the server saw and
+//! the masked environment, and the newest few are included in ui_diagnostics.
+//! A panic hook can't await, so it hands its report to a writer task; fatal
+//! errors are written directly while main still has a runtime.
+
+use std::backtrace::Backtrace;
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+
+use crate::diagnostics::{self, Diagnostics};
+use crate::error::Result;
+use crate::models::CrashReport;
+use crate::repository::Repository;
+
+/// Reports kept per instance
+pub const MAX_CRASH_REPORTS: usize = 50;
+
+/// Reports included in a ui_diagnostics bundle
+pub const BUNDLED_CRASH_REPORTS: usize = 5;
+
+/// Writes crash reports through the repository
+pub struct CrashReporter {
+    repository: Arc<dyn Repository>,
+    instance_id: String,
+    diagnostics: Arc<Diagnostics>,
+    sender: mpsc::UnboundedSender<CrashReport>,
+}
+
+impl CrashReporter {
+    /// Start the writer task for panic reports; must be called inside a tokio runtime
+    pub fn start(repository: Arc<dyn Repository>, instance_id: String, diagnostics: Arc<Diagnostics>) -> Arc<Self> {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<CrashReport>();
+        let writer = repository.clone();
+        let instance = instance_id.clone();
+        tokio::spawn(async move {
+            while let Some(report) = receiver.recv().await {
+                if let Err(e) = writer.save_crash_report(&instance, &report).await {
+                    tracing::error!("Failed to store crash report {}: {}", report.id, e);
+                }
+            }
+        });
+        Arc::new(Self { repository, instance_id, diagnostics, sender })
+    }
+
+    /// Report every panic, after the previous hook has printed it to stderr
+    pub fn install_panic_hook(self: &Arc<Self>) {
+        let reporter = self.clone();
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            previous(info);
+            let report = reporter.build_report(
+                "panic",
+                panic_message(info.payload()),
+                info.location().map(|location| location.to_string()),
+            );
+            reporter.diagnostics.record_error("panic", &report.message);
+            // The writer task is gone only when the runtime is shutting down
+            let _ = reporter.sender.send(report);
+        }));
+    }
+
+    /// Store a report for an error that is about to end the process
+    pub async fn report_fatal(&self, message: &str) -> Result<()> {
+        let report = self.build_report("fatal", message.to_string(), None);
+        self.diagnostics.record_error("fatal", message);
+        self.repository.save_crash_report(&self.instance_id, &report).await
+    }
+
+    fn build_report(&self, kind: &str, message: String, location: Option<String>) -> CrashReport {
+        CrashReport {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            kind: kind.to_string(),
+            message,
+            location,
+            thread: std::thread::current().name().map(str::to_string),
+            backtrace: Backtrace::force_capture().to_string(),
+            server_version: env!("CARGO_PKG_VERSION").to_string(),
+            recent_tool_calls: self.diagnostics.recent_tool_calls(diagnostics::MAX_RECENT_TOOL_CALLS),
+            config: diagnostics::masked_env(std::env::vars()),
+        }
+    }
+}
+
+/// Text of a panic payload (`panic!` with a literal or a formatted message)
+pub fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload.downcast_ref::<&str>().map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::{DiagnosticsOperations, MockRepository};
+
+    #[test]
+    fn test_panic_message() {
+        let payload = std::panic::catch_unwind(|| panic!("index out of range")).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "index out of range");
+        let payload = std::panic::catch_unwind(|| panic!("chain {} missing", 7)).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "chain 7 missing");
+    }
+
+    #[tokio::test]
+    async fn test_report_fatal_keeps_recent_tool_calls() {
+        let repository = Arc::new(MockRepository::new());
+        let diagnostics = Arc::new(Diagnostics::new());
+        diagnostics.record_tool_call("ui_think", false);
+        diagnostics.record_tool_call("ui_recall", true);
+        let reporter = CrashReporter::start(repository.clone(), "CC".to_string(), diagnostics.clone());
+
+        reporter.report_fatal("transport closed: broken pipe").await.unwrap();
+
+        let reports = repository.get_crash_reports("CC", 10).await.unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].kind, "fatal");
+        assert_eq!(reports[0].message, "transport closed: broken pipe");
+        let tools: Vec<&str> = reports[0].recent_tool_calls.iter().map(|call| call.tool.as_str()).collect();
+        assert_eq!(tools, vec!["ui_recall", "ui_think"]);
+        assert_eq!(diagnostics.recent_errors(1)[0].source, "fatal");
+    }
+}
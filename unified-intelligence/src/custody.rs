@@ -0,0 +1,183 @@
+//! Chain-of-custody hashes for ui_think and ui_verify_chain.
+//!
+//! With UI_CHAIN_CUSTODY enabled, every thought stored in a chain is sealed:
+//! it keeps the link hash of the thought stored before it in the chain and a
+//! SHA-256 over its own fields and that link. Editing a sealed thought breaks
+//! its own hash, and removing or editing the thought before it breaks the
+//! link, so ui_verify_chain can recompute the chain and report where it was
+//! mutated. Thoughts stored before custody was enabled are linked by a hash
+//! of their current content. Search similarity and cold storage pointers are
+//! left out of the hash since they change without the thought changing.
+
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+use crate::models::{Custody, CustodyIssue, ThoughtRecord};
+
+/// Whether UI_CHAIN_CUSTODY asks for chain thoughts to be sealed
+pub fn enabled() -> bool {
+    std::env::var("UI_CHAIN_CUSTODY")
+        .map(|value| matches!(value.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false)
+}
+
+/// Hex SHA-256 over a thought's stored fields and the hash it links to
+pub fn digest(thought: &ThoughtRecord, prev_hash: Option<&str>) -> String {
+    let fields = serde_json::json!([
+        thought.id,
+        thought.instance,
+        thought.chain_id,
+        thought.thought_number,
+        thought.total_thoughts,
+        thought.next_thought_needed,
+        thought.timestamp,
+        thought.thought,
+        thought.user_id,
+        thought.provenance,
+        thought.framework,
+        prev_hash,
+    ]);
+    Sha256::digest(fields.to_string().as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hash a later thought links to: the sealed hash, or the current content of an unsealed thought
+pub fn link_hash(thought: &ThoughtRecord) -> String {
+    match &thought.custody {
+        Some(custody) => custody.hash.clone(),
+        None => digest(thought, None),
+    }
+}
+
+/// Chain thoughts in the order they were stored
+pub fn ordered(thoughts: &[ThoughtRecord]) -> Vec<&ThoughtRecord> {
+    let mut ordered: Vec<&ThoughtRecord> = thoughts.iter().collect();
+    ordered.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.thought_number.cmp(&b.thought_number)));
+    ordered
+}
+
+/// Seal a thought onto the newest thought already stored in its chain
+pub fn seal(thought: &mut ThoughtRecord, chain: &[ThoughtRecord]) {
+    let others: Vec<ThoughtRecord> = chain.iter().filter(|t| t.id != thought.id).cloned().collect();
+    let prev_hash = ordered(&others).last().map(|previous| link_hash(previous));
+    let hash = digest(thought, prev_hash.as_deref());
+    thought.custody = Some(Custody { prev_hash, hash });
+}
+
+fn issue(thought: &ThoughtRecord, kind: &str, detail: String) -> CustodyIssue {
+    CustodyIssue {
+        thought_id: Some(thought.id.clone()),
+        thought_number: Some(thought.thought_number),
+        kind: kind.to_string(),
+        detail,
+    }
+}
+
+fn short(hash: &str) -> &str {
+    &hash[..hash.len().min(12)]
+}
+
+/// Recompute a chain's hashes, returning every mismatch in storage order
+pub fn verify(thoughts: &[ThoughtRecord]) -> Vec<CustodyIssue> {
+    let ordered = ordered(thoughts);
+    let links: HashMap<String, &str> = ordered.iter().map(|t| (link_hash(t), t.id.as_str())).collect();
+    let mut successors: HashMap<&str, &str> = HashMap::new();
+    let mut issues = Vec::new();
+    let mut sealed_seen = false;
+
+    for (index, thought) in ordered.iter().enumerate() {
+        let Some(custody) = &thought.custody else {
+            if sealed_seen {
+                issues.push(issue(thought, "unsealed", "stored after sealed thoughts without a custody hash".to_string()));
+            }
+            continue;
+        };
+        sealed_seen = true;
+
+        if digest(thought, custody.prev_hash.as_deref()) != custody.hash {
+            issues.push(issue(thought, "altered", "content no longer matches its hash".to_string()));
+        }
+        match custody.prev_hash.as_deref() {
+            Some(prev_hash) if !links.contains_key(prev_hash) => {
+                issues.push(issue(thought, "broken_link", format!("the thought it follows ({}) is missing or was altered", short(prev_hash))));
+            }
+            Some(prev_hash) => {
+                if let Some(first) = successors.insert(prev_hash, &thought.id) {
+                    issues.push(issue(thought, "fork", format!("follows the same thought as {}", first)));
+                }
+            }
+            None if index > 0 => {
+                issues.push(issue(thought, "broken_link", format!("claims to start the chain but {} thought(s) were stored before it", index)));
+            }
+            None => {}
+        }
+    }
+    issues
+}
+
+/// Link hash of the newest sealed thought, which anchors everything before it
+pub fn head_hash(thoughts: &[ThoughtRecord]) -> Option<String> {
+    ordered(thoughts).into_iter().rev().find(|t| t.custody.is_some()).map(link_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(len: i32) -> Vec<ThoughtRecord> {
+        let mut chain = Vec::new();
+        for number in 1..=len {
+            let mut thought = ThoughtRecord::new("CC".to_string(), format!("step {}", number), number, len, Some("c1".to_string()), number < len);
+            thought.timestamp = format!("2025-07-18T10:0{}:00+00:00", number);
+            seal(&mut thought, &chain);
+            chain.push(thought);
+        }
+        chain
+    }
+
+    #[test]
+    fn test_intact_chain_verifies() {
+        let chain = chain(3);
+        assert!(chain[0].custody.as_ref().unwrap().prev_hash.is_none());
+        assert_eq!(chain[2].custody.as_ref().unwrap().prev_hash.as_deref(), Some(chain[1].custody.as_ref().unwrap().hash.as_str()));
+        assert!(verify(&chain).is_empty());
+        assert_eq!(head_hash(&chain), Some(chain[2].custody.clone().unwrap().hash));
+
+        // A stub tiered out to cold storage keeps its hash once rehydrated
+        let mut rehydrated = chain.clone();
+        rehydrated[1].similarity = Some(0.9);
+        assert!(verify(&rehydrated).is_empty());
+    }
+
+    #[test]
+    fn test_tampering_is_reported() {
+        let mut altered = chain(3);
+        altered[1].thought = "rewritten".to_string();
+        let issues = verify(&altered);
+        assert_eq!(issues.len(), 1);
+        assert_eq!((issues[0].kind.as_str(), issues[0].thought_number), ("altered", Some(2)));
+
+        let mut removed = chain(3);
+        removed.remove(1);
+        let issues = verify(&removed);
+        assert_eq!(issues.iter().map(|i| i.kind.as_str()).collect::<Vec<_>>(), vec!["broken_link"]);
+
+        let mut inserted = chain(2);
+        let mut injected = ThoughtRecord::new("CC".to_string(), "injected".to_string(), 3, 3, Some("c1".to_string()), false);
+        injected.timestamp = "2025-07-18T10:09:00+00:00".to_string();
+        inserted.push(injected);
+        assert_eq!(verify(&inserted)[0].kind, "unsealed");
+    }
+
+    #[test]
+    fn test_sealing_onto_legacy_thoughts() {
+        let mut legacy = ThoughtRecord::new("CC".to_string(), "before custody".to_string(), 1, 2, Some("c1".to_string()), true);
+        legacy.timestamp = "2025-07-18T09:00:00+00:00".to_string();
+        let mut sealed = ThoughtRecord::new("CC".to_string(), "after custody".to_string(), 2, 2, Some("c1".to_string()), false);
+        seal(&mut sealed, std::slice::from_ref(&legacy));
+        let mut chain = vec![legacy, sealed];
+        assert!(verify(&chain).is_empty());
+
+        chain[0].thought = "edited before custody".to_string();
+        assert_eq!(verify(&chain)[0].kind, "broken_link");
+    }
+}
@@ -0,0 +1,97 @@
+//! "You've thought this before" notes for ui_think.
+//!
+//! Once ui_think has stored a thought, a semantic search of the instance
+//! looks for earlier thoughts at least UI_DEJA_VU_THRESHOLD similar to it
+//! (default 0.85; 0 disables the check) that belong to another chain, or to
+//! no chain at all. Repeating a thought word for word scores 1.0, so exact
+//! duplicates from earlier sessions are caught the same way. Up to
+//! MAX_OCCURRENCES of them, most similar first, come back in the response's
+//! `deja_vu` with their chain and thought number. The check is skipped under
+//! critical memory pressure, and a failed search only logs a warning.
+
+use crate::models::{DejaVu, DejaVuOccurrence, ThoughtRecord};
+use crate::persona;
+
+/// Earlier occurrences listed in a ui_think response
+pub const MAX_OCCURRENCES: usize = 3;
+
+/// Thoughts asked of the semantic search, leaving room for those of the same chain
+pub const SEARCH_LIMIT: usize = 20;
+
+/// Characters of an earlier thought quoted in the note
+const PREVIEW_CHARS: usize = 120;
+
+const DEFAULT_THRESHOLD: f32 = 0.85;
+
+/// Similarity threshold from UI_DEJA_VU_THRESHOLD; None when set to 0
+pub fn threshold() -> Option<f32> {
+    let threshold = std::env::var("UI_DEJA_VU_THRESHOLD").ok()
+        .and_then(|v| v.trim().parse::<f32>().ok())
+        .unwrap_or(DEFAULT_THRESHOLD);
+    (threshold > 0.0).then_some(threshold.min(1.0))
+}
+
+/// Earlier thoughts of other chains among the search results, or None when there are none
+pub fn detect(thought: &ThoughtRecord, results: Vec<ThoughtRecord>, threshold: f32) -> Option<DejaVu> {
+    let mut earlier: Vec<ThoughtRecord> = results.into_iter()
+        .filter(|past| past.id != thought.id && past.instance == thought.instance)
+        .filter(|past| thought.chain_id.is_none() || past.chain_id != thought.chain_id)
+        .filter(|past| past.similarity.is_some_and(|similarity| similarity >= threshold))
+        .collect();
+    if earlier.is_empty() {
+        return None;
+    }
+    earlier.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap_or(std::cmp::Ordering::Equal));
+    earlier.truncate(MAX_OCCURRENCES);
+
+    let chains = earlier.iter().filter(|past| past.chain_id.is_some()).count();
+    let message = match (earlier.len(), chains) {
+        (1, 1) => "You've thought this before, in another chain".to_string(),
+        (1, _) => "You've thought this before".to_string(),
+        (n, _) => format!("You've thought this before: {} similar earlier thoughts", n),
+    };
+    Some(DejaVu {
+        message,
+        occurrences: earlier.into_iter().map(|past| DejaVuOccurrence {
+            preview: persona::truncate(&past.thought, PREVIEW_CHARS),
+            similarity: past.similarity.unwrap_or_default(),
+            thought_id: past.id,
+            chain_id: past.chain_id,
+            thought_number: past.thought_number,
+            timestamp: past.timestamp,
+        }).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thought(id: &str, chain_id: Option<&str>, similarity: Option<f32>) -> ThoughtRecord {
+        let mut thought = ThoughtRecord::new("CC".to_string(), format!("thought {}", id), 1, 1, chain_id.map(str::to_string), false);
+        thought.id = id.to_string();
+        thought.similarity = similarity;
+        thought
+    }
+
+    #[test]
+    fn test_detect_keeps_similar_thoughts_of_other_chains() {
+        let new = thought("new", Some("c1"), None);
+        let results = vec![
+            new.clone(),
+            thought("same-chain", Some("c1"), Some(0.99)),
+            thought("other-chain", Some("c2"), Some(0.90)),
+            thought("unchained", None, Some(0.95)),
+            thought("weak", Some("c3"), Some(0.50)),
+        ];
+        let deja_vu = detect(&new, results, 0.85).unwrap();
+        let ids: Vec<&str> = deja_vu.occurrences.iter().map(|o| o.thought_id.as_str()).collect();
+        assert_eq!(ids, vec!["unchained", "other-chain"]);
+        assert!(deja_vu.message.contains("2 similar"));
+
+        assert!(detect(&new, vec![thought("weak", Some("c3"), Some(0.50))], 0.85).is_none());
+        // A thought outside any chain is compared with every other thought
+        let unchained = thought("loose", None, None);
+        assert_eq!(detect(&unchained, vec![thought("x", None, Some(0.9))], 0.85).unwrap().occurrences.len(), 1);
+    }
+}
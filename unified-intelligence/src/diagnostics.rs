@@ -0,0 +1,171 @@
+//! Runtime state gathered by ui_diagnostics.
+//!
+//! Tool calls, tool failures and background task runs are recorded as they
+//! happen so a troubleshooting bundle (or a crash report) can show what went
+//! wrong without access to stderr.
+//! Environment values are masked when their name looks like a secret.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::models::{RecentError, TaskState, ToolCallRecord};
+
+/// Errors kept for the bundle
+pub const MAX_RECENT_ERRORS: usize = 100;
+
+/// Tool calls kept for the bundle and crash reports
+pub const MAX_RECENT_TOOL_CALLS: usize = 50;
+
+/// Errors included when no limit is given
+pub const DEFAULT_ERROR_LIMIT: usize = 20;
+
+/// Environment variables included in the bundle, by prefix
+const ENV_PREFIXES: &[&str] = &["UI_", "UM_", "REDIS_", "OPENAI_", "GROQ_", "INSTANCE_ID", "USER_ID", "ALLOW_DEFAULT_REDIS_PASSWORD", "RUST_LOG"];
+
+/// Name fragments of variables whose values are masked
+const SECRET_MARKERS: &[&str] = &["KEY", "PASS", "SECRET", "TOKEN"];
+
+/// Background task states and recent errors
+#[derive(Default)]
+pub struct Diagnostics {
+    tasks: Mutex<BTreeMap<String, TaskState>>,
+    errors: Mutex<VecDeque<RecentError>>,
+    tool_calls: Mutex<VecDeque<ToolCallRecord>>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a periodic background task
+    pub fn task_started(&self, name: &str, interval: Duration) {
+        self.tasks.lock().unwrap_or_else(|e| e.into_inner()).insert(name.to_string(), TaskState {
+            name: name.to_string(),
+            interval_secs: interval.as_secs(),
+            runs: 0,
+            failures: 0,
+            last_run: None,
+            last_error: None,
+        });
+    }
+
+    /// Record one run of a background task, keeping failures as recent errors
+    pub fn task_finished(&self, name: &str, error: Option<String>) {
+        {
+            let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(task) = tasks.get_mut(name) {
+                task.runs += 1;
+                task.last_run = Some(chrono::Utc::now().to_rfc3339());
+                if error.is_some() {
+                    task.failures += 1;
+                    task.last_error = error.clone();
+                }
+            }
+        }
+        if let Some(error) = error {
+            self.record_error(name, &error);
+        }
+    }
+
+    /// Keep an error, dropping the oldest beyond MAX_RECENT_ERRORS
+    pub fn record_error(&self, source: &str, message: &str) {
+        let mut errors = self.errors.lock().unwrap_or_else(|e| e.into_inner());
+        if errors.len() == MAX_RECENT_ERRORS {
+            errors.pop_front();
+        }
+        errors.push_back(RecentError {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            source: source.to_string(),
+            message: message.to_string(),
+        });
+    }
+
+    /// Keep a tool call, dropping the oldest beyond MAX_RECENT_TOOL_CALLS
+    pub fn record_tool_call(&self, tool: &str, failed: bool) {
+        let mut tool_calls = self.tool_calls.lock().unwrap_or_else(|e| e.into_inner());
+        if tool_calls.len() == MAX_RECENT_TOOL_CALLS {
+            tool_calls.pop_front();
+        }
+        tool_calls.push_back(ToolCallRecord {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            tool: tool.to_string(),
+            failed,
+        });
+    }
+
+    /// Newest tool calls first
+    pub fn recent_tool_calls(&self, limit: usize) -> Vec<ToolCallRecord> {
+        self.tool_calls.lock().unwrap_or_else(|e| e.into_inner()).iter().rev().take(limit).cloned().collect()
+    }
+
+    pub fn tasks(&self) -> Vec<TaskState> {
+        self.tasks.lock().unwrap_or_else(|e| e.into_inner()).values().cloned().collect()
+    }
+
+    /// Newest errors first
+    pub fn recent_errors(&self, limit: usize) -> Vec<RecentError> {
+        self.errors.lock().unwrap_or_else(|e| e.into_inner()).iter().rev().take(limit).cloned().collect()
+    }
+}
+
+/// Keep only the ends of a secret: `abc...xyz`, or `***` when too short to show any
+pub fn mask(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 8 {
+        return "***".to_string();
+    }
+    let head: String = chars[..3].iter().collect();
+    let tail: String = chars[chars.len() - 3..].iter().collect();
+    format!("{}...{}", head, tail)
+}
+
+/// Relevant environment variables, secrets masked
+pub fn masked_env<I: IntoIterator<Item = (String, String)>>(vars: I) -> BTreeMap<String, String> {
+    vars.into_iter()
+        .filter(|(name, _)| ENV_PREFIXES.iter().any(|prefix| name.starts_with(prefix)))
+        .map(|(name, value)| {
+            let value = if SECRET_MARKERS.iter().any(|marker| name.contains(marker)) { mask(&value) } else { value };
+            (name, value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_masked_env() {
+        let env = masked_env(vec![
+            ("OPENAI_API_KEY".to_string(), "sk-abcdefghijklmnop".to_string()),
+            ("REDIS_PASSWORD".to_string(), "short".to_string()),
+            ("REDIS_HOST".to_string(), "localhost".to_string()),
+            ("HOME".to_string(), "/root".to_string()),
+        ]);
+        assert_eq!(env["OPENAI_API_KEY"], "sk-...nop");
+        assert_eq!(env["REDIS_PASSWORD"], "***");
+        assert_eq!(env["REDIS_HOST"], "localhost");
+        assert!(!env.contains_key("HOME"));
+    }
+
+    #[test]
+    fn test_tasks_and_recent_errors() {
+        let diagnostics = Diagnostics::new();
+        diagnostics.task_started("capture", Duration::from_secs(60));
+        diagnostics.task_finished("capture", None);
+        diagnostics.task_finished("capture", Some("git log failed".to_string()));
+
+        let tasks = diagnostics.tasks();
+        assert_eq!((tasks[0].runs, tasks[0].failures), (2, 1));
+        assert_eq!(tasks[0].last_error.as_deref(), Some("git log failed"));
+
+        for i in 0..MAX_RECENT_ERRORS {
+            diagnostics.record_error("ui_recall", &format!("error {}", i));
+        }
+        let errors = diagnostics.recent_errors(MAX_RECENT_ERRORS + 10);
+        assert_eq!(errors.len(), MAX_RECENT_ERRORS);
+        assert_eq!(errors[0].message, format!("error {}", MAX_RECENT_ERRORS - 1));
+    }
+}
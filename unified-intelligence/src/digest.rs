@@ -0,0 +1,276 @@
+//! Digests of low-priority notifications, for the notification bridge.
+//!
+//! Each message on a bridged channel is routed by its event type (the
+//! payload's `type`, `event_type` or `event`, else the channel name):
+//! `immediate` sends it on right away, `digest` holds it for the next digest
+//! of its instance (the payload's `instance`) and `off` drops it.
+//! UI_NOTIFY_ROUTES overrides the routes as `name=route` pairs, where a name
+//! is an event type or, failing that, a channel; by default suggested tags,
+//! feed captures and memory pressure warnings go to the digest, and
+//! everything else takes UI_NOTIFY_DEFAULT_ROUTE (default immediate). Urgent
+//! events (priority high, urgent or critical, or level critical) are always
+//! sent at once. Every UI_NOTIFY_DIGEST_SECS (default 900; 0 sends
+//! everything at once) each instance with held events gets one
+//! `digest:{instance}` notification per client, with the events of the
+//! channels that client subscribed to and their counts by type. Held events
+//! are kept in memory, MAX_HELD per instance, and don't survive a restart.
+
+use chrono::{DateTime, Utc};
+use rmcp::model::{LoggingLevel, LoggingMessageNotificationParam};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::Duration;
+
+/// Events held per instance between digests; the oldest make way for new ones
+pub const MAX_HELD: usize = 200;
+
+/// Instance of events that don't name one
+pub const GLOBAL_INSTANCE: &str = "global";
+
+/// Event types sent as digests unless UI_NOTIFY_ROUTES says otherwise
+const DIGEST_TYPES: &[&str] = &["tags_suggested", "feed_captured", "memory_pressure"];
+
+/// Payload fields naming the event type, in order of preference
+const TYPE_FIELDS: &[&str] = &["type", "event_type", "event"];
+
+const DEFAULT_DIGEST_SECS: u64 = 900;
+
+/// How an event reaches clients
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    Immediate,
+    Digest,
+    Off,
+}
+
+impl Route {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "immediate" => Some(Self::Immediate),
+            "digest" => Some(Self::Digest),
+            "off" => Some(Self::Off),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DigestConfig {
+    routes: HashMap<String, Route>,
+    default_route: Route,
+    /// Time between digests; None sends everything at once
+    pub interval: Option<Duration>,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            routes: DIGEST_TYPES.iter().map(|name| (name.to_string(), Route::Digest)).collect(),
+            default_route: Route::Immediate,
+            interval: Some(Duration::from_secs(DEFAULT_DIGEST_SECS)),
+        }
+    }
+}
+
+impl DigestConfig {
+    /// Read UI_NOTIFY_ROUTES, UI_NOTIFY_DEFAULT_ROUTE and UI_NOTIFY_DIGEST_SECS
+    pub fn from_env() -> Self {
+        let env = |name: &str| std::env::var(name).unwrap_or_default();
+        let seconds = env("UI_NOTIFY_DIGEST_SECS").trim().parse().ok();
+        Self::parse(&env("UI_NOTIFY_ROUTES"), &env("UI_NOTIFY_DEFAULT_ROUTE"), seconds)
+    }
+
+    /// Defaults overridden by `name=route` pairs separated by commas; malformed pairs are skipped
+    pub fn parse(routes: &str, default_route: &str, digest_secs: Option<u64>) -> Self {
+        let mut config = Self::default();
+        for pair in routes.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            match pair.split_once('=').and_then(|(name, route)| Some((name.trim(), Route::parse(route)?))) {
+                Some((name, route)) if !name.is_empty() => {
+                    config.routes.insert(name.to_string(), route);
+                }
+                _ => tracing::warn!("Ignoring notification route '{}'", pair),
+            }
+        }
+        if !default_route.trim().is_empty() {
+            match Route::parse(default_route) {
+                Some(route) => config.default_route = route,
+                None => tracing::warn!("Ignoring default notification route '{}'", default_route.trim()),
+            }
+        }
+        if let Some(seconds) = digest_secs {
+            config.interval = (seconds > 0).then(|| Duration::from_secs(seconds));
+        }
+        config
+    }
+
+    /// Route of an event: urgent events go at once, then the route of its type, of its channel, or the default
+    pub fn route(&self, channel: &str, event_type: &str, payload: &Value) -> Route {
+        if is_urgent(payload) {
+            return Route::Immediate;
+        }
+        let route = self.routes.get(event_type)
+            .or_else(|| self.routes.get(channel))
+            .copied()
+            .unwrap_or(self.default_route);
+        match route {
+            Route::Digest if self.interval.is_none() => Route::Immediate,
+            route => route,
+        }
+    }
+}
+
+/// Type of an event, from its payload or else its channel
+pub fn event_type(channel: &str, payload: &Value) -> String {
+    TYPE_FIELDS.iter()
+        .find_map(|field| payload.get(*field)?.as_str().filter(|name| !name.is_empty()))
+        .unwrap_or(channel)
+        .to_string()
+}
+
+/// Whether an event must not wait for a digest
+pub fn is_urgent(payload: &Value) -> bool {
+    let field = |name: &str| payload.get(name).and_then(Value::as_str).map(str::to_lowercase);
+    matches!(field("priority").as_deref(), Some("high" | "urgent" | "critical"))
+        || field("level").as_deref() == Some("critical")
+}
+
+/// An event held for a digest
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestEvent {
+    pub channel: String,
+    pub event_type: String,
+    pub received_at: DateTime<Utc>,
+    pub payload: Value,
+}
+
+/// Events of one instance since its last digest
+#[derive(Debug, Default)]
+pub struct HeldEvents {
+    pub events: Vec<DigestEvent>,
+    /// Events let go to stay within MAX_HELD
+    pub dropped: usize,
+}
+
+/// Held events by instance
+#[derive(Debug, Default)]
+pub struct Digests {
+    held: BTreeMap<String, HeldEvents>,
+}
+
+impl Digests {
+    pub fn hold(&mut self, instance: &str, event: DigestEvent) {
+        let held = self.held.entry(instance.to_string()).or_default();
+        if held.events.len() >= MAX_HELD {
+            held.events.remove(0);
+            held.dropped += 1;
+        }
+        held.events.push(event);
+    }
+
+    /// Take the held events of every instance, leaving none
+    pub fn take(&mut self) -> BTreeMap<String, HeldEvents> {
+        std::mem::take(&mut self.held)
+    }
+
+    pub fn len(&self) -> usize {
+        self.held.values().map(|held| held.events.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.held.is_empty()
+    }
+}
+
+/// Instance an event belongs to
+pub fn instance(payload: &Value) -> &str {
+    payload.get("instance").and_then(Value::as_str).filter(|name| !name.is_empty()).unwrap_or(GLOBAL_INSTANCE)
+}
+
+/// Digest of an instance's events for a client subscribed to `channels`; None when none of them are
+pub fn notification(instance: &str, held: &HeldEvents, channels: &BTreeSet<String>) -> Option<LoggingMessageNotificationParam> {
+    let events: Vec<&DigestEvent> = held.events.iter().filter(|event| channels.contains(&event.channel)).collect();
+    let (first, last) = (events.first()?, events.last()?);
+    let mut by_type: BTreeMap<&str, usize> = BTreeMap::new();
+    for event in &events {
+        *by_type.entry(event.event_type.as_str()).or_default() += 1;
+    }
+    Some(LoggingMessageNotificationParam {
+        level: LoggingLevel::Info,
+        logger: Some(format!("digest:{}", instance)),
+        data: json!({
+            "instance": instance,
+            "count": events.len(),
+            "since": first.received_at,
+            "until": last.received_at,
+            "by_type": by_type,
+            "dropped": held.dropped,
+            "events": events,
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(channel: &str, payload: Value) -> DigestEvent {
+        DigestEvent { channel: channel.to_string(), event_type: event_type(channel, &payload), received_at: Utc::now(), payload }
+    }
+
+    #[test]
+    fn test_routes() {
+        let config = DigestConfig::parse("reminders=digest, deploy=off, bad, x=later", "", None);
+        let low = json!({"type": "memory_pressure", "priority": "medium"});
+        assert_eq!(config.route("intervention_events", &event_type("intervention_events", &low), &low), Route::Digest);
+        let high = json!({"type": "memory_pressure", "priority": "high"});
+        assert_eq!(config.route("intervention_events", "memory_pressure", &high), Route::Immediate);
+        // Types win over channels, channels over the default
+        assert_eq!(config.route("reminders", "reminders", &json!("stand-up")), Route::Digest);
+        assert_eq!(config.route("reminders", "deploy", &json!({})), Route::Off);
+        assert_eq!(config.route("vault_changes", "vault_changes", &json!({})), Route::Immediate);
+        assert_eq!(config.route("deploys", "deploy", &json!({"level": "Critical"})), Route::Immediate);
+
+        let no_digests = DigestConfig::parse("", "off", Some(0));
+        assert_eq!(no_digests.route("ui_events", "tags_suggested", &json!({})), Route::Immediate);
+        assert_eq!(no_digests.route("reminders", "reminders", &json!({})), Route::Off);
+        assert_eq!(event_type("reminders", &json!({"event_type": "due"})), "due");
+    }
+
+    #[test]
+    fn test_digest_per_instance_and_client() {
+        let mut digests = Digests::default();
+        let tags = json!({"type": "tags_suggested", "instance": "CC", "tags": ["redis"]});
+        assert_eq!(instance(&tags), "CC");
+        digests.hold("CC", event("ui_events", tags.clone()));
+        digests.hold("CC", event("ui_events", tags));
+        let pressure = json!({"type": "memory_pressure", "instance": "CC", "priority": "medium"});
+        digests.hold("CC", event("intervention_events", pressure));
+        digests.hold(instance(&json!("stand-up")), event("reminders", json!("stand-up")));
+        assert_eq!(digests.len(), 4);
+
+        let held = digests.take();
+        assert!(digests.is_empty());
+        assert_eq!(held.keys().collect::<Vec<_>>(), vec!["CC", GLOBAL_INSTANCE]);
+
+        let channels = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<BTreeSet<_>>();
+        let both = notification("CC", &held["CC"], &channels(&["ui_events", "intervention_events"])).unwrap();
+        assert_eq!(both.logger.as_deref(), Some("digest:CC"));
+        assert_eq!(both.data["count"], 3);
+        assert_eq!(both.data["by_type"], json!({"memory_pressure": 1, "tags_suggested": 2}));
+        let tags_only = notification("CC", &held["CC"], &channels(&["ui_events"])).unwrap();
+        assert_eq!(tags_only.data["by_type"], json!({"tags_suggested": 2}));
+        assert!(notification("CC", &held["CC"], &channels(&["reminders"])).is_none());
+    }
+
+    #[test]
+    fn test_hold_caps_events() {
+        let mut digests = Digests::default();
+        for n in 0..MAX_HELD + 5 {
+            digests.hold("CC", event("ui_events", json!({"n": n})));
+        }
+        let held = digests.take();
+        assert_eq!((held["CC"].events.len(), held["CC"].dropped), (MAX_HELD, 5));
+        assert_eq!(held["CC"].events[0].payload["n"], 5);
+    }
+}
@@ -0,0 +1,92 @@
+//! Embedding model versioning for ui_embedding_staleness.
+//!
+//! The embedding workers store each vector as JSON at
+//! `{instance}:embeddings:{thought_id}` together with the `model` and
+//! `model_version` that produced it, read from UI_EMBEDDING_MODEL (default
+//! `text-embedding-3-small`) and UI_EMBEDDING_MODEL_VERSION (default `1`).
+//! The server reads the same variables, so bumping either marks every older
+//! vector stale. Vectors written before versioning carry neither field.
+//!
+//! Stale thoughts can be queued in `{instance}:embedding_stale`, scored by
+//! importance; background_service_v3.py re-embeds from it, highest score
+//! first, whenever the event stream is idle.
+
+use crate::models::EmbeddingVersion;
+
+pub const DEFAULT_MODEL: &str = "text-embedding-3-small";
+pub const DEFAULT_MODEL_VERSION: &str = "1";
+
+/// Stale thoughts listed when no limit is given
+pub const DEFAULT_REPORT_LIMIT: usize = 50;
+
+/// Most instance thoughts scanned per report
+pub const SCAN_LIMIT: usize = 10000;
+
+/// Importance assumed for thoughts stored without one
+pub const DEFAULT_IMPORTANCE: i32 = 5;
+
+/// Current model and version from the environment
+pub fn current_model() -> (String, String) {
+    let env = |name: &str, default: &str| std::env::var(name).ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| default.to_string());
+    (env("UI_EMBEDDING_MODEL", DEFAULT_MODEL), env("UI_EMBEDDING_MODEL_VERSION", DEFAULT_MODEL_VERSION))
+}
+
+/// Version fields of a stored embedding record; unreadable records count as unversioned
+pub fn parse_record(json: &str) -> EmbeddingVersion {
+    serde_json::from_str(json).unwrap_or_default()
+}
+
+/// Why an embedding needs refreshing, or None when it is current
+pub fn staleness(stored: Option<&EmbeddingVersion>, model: &str, model_version: &str) -> Option<&'static str> {
+    let stored = match stored {
+        None => return Some("missing"),
+        Some(stored) => stored,
+    };
+    match (stored.model.as_deref(), stored.model_version.as_deref()) {
+        (None, _) => Some("unversioned"),
+        (Some(stored_model), _) if stored_model != model => Some("outdated_model"),
+        (_, Some(stored_version)) if stored_version == model_version => None,
+        _ => Some("outdated_version"),
+    }
+}
+
+/// Re-embedding priority: the thought's importance
+pub fn reembed_priority(importance: Option<i32>) -> f64 {
+    f64::from(importance.unwrap_or(DEFAULT_IMPORTANCE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(model: Option<&str>, model_version: Option<&str>) -> EmbeddingVersion {
+        EmbeddingVersion {
+            model: model.map(str::to_string),
+            model_version: model_version.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_staleness() {
+        let current = version(Some("text-embedding-3-small"), Some("2"));
+        assert_eq!(staleness(Some(&current), "text-embedding-3-small", "2"), None);
+        assert_eq!(staleness(None, "text-embedding-3-small", "2"), Some("missing"));
+        assert_eq!(staleness(Some(&version(None, None)), "text-embedding-3-small", "2"), Some("unversioned"));
+        assert_eq!(staleness(Some(&version(Some("llama3-8b-8192"), Some("2"))), "text-embedding-3-small", "2"), Some("outdated_model"));
+        assert_eq!(staleness(Some(&version(Some("text-embedding-3-small"), Some("1"))), "text-embedding-3-small", "2"), Some("outdated_version"));
+        assert_eq!(staleness(Some(&version(Some("text-embedding-3-small"), None)), "text-embedding-3-small", "2"), Some("outdated_version"));
+    }
+
+    #[test]
+    fn test_parse_record() {
+        let record = r#"{"thought_id":"t1","embedding":[0.1,0.2],"provider":"openai","model":"text-embedding-3-small","model_version":"1"}"#;
+        assert_eq!(parse_record(record), version(Some("text-embedding-3-small"), Some("1")));
+        assert_eq!(parse_record(r#"{"thought_id":"t1","embedding":[0.1],"provider":"openai"}"#), version(None, None));
+        assert_eq!(parse_record("not json"), version(None, None));
+        assert_eq!(reembed_priority(None), 5.0);
+        assert_eq!(reembed_priority(Some(9)), 9.0);
+    }
+}
@@ -6,6 +6,9 @@ pub enum UnifiedIntelligenceError {
     #[error("Redis error: {0}")]
     Redis(#[from] redis::RedisError),
     
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    
     #[error("Connection pool error: {0}")]
     Pool(#[from] deadpool_redis::PoolError),
     
@@ -39,6 +42,9 @@ pub enum UnifiedIntelligenceError {
     #[error("Rate limit exceeded")]
     RateLimit,
     
+    #[error("Daily {0} budget reached")]
+    BudgetExceeded(String),
+    
     #[error("Unauthorized access")]
     #[allow(dead_code)]
     Unauthorized,
@@ -62,6 +68,7 @@ impl From<crate::validation::ValidationError> for UnifiedIntelligenceError {
         UnifiedIntelligenceError::Validation {
             field: match &err {
                 crate::validation::ValidationError::InvalidChainId { .. } => "chain_id".to_string(),
+                crate::validation::ValidationError::InvalidThoughtId { .. } => "thought_id".to_string(),
                 crate::validation::ValidationError::InvalidThoughtNumber { .. } => "thought_number".to_string(),
                 crate::validation::ValidationError::InvalidInstanceId { .. } => "instance_id".to_string(),
                 crate::validation::ValidationError::ThoughtTooLong { .. } => "thought".to_string(),
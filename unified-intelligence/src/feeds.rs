@@ -0,0 +1,232 @@
+//! Atom and JSON feeds of tagged thoughts for ui_feed.
+//!
+//! A feed follows one stream of thinking: the most recent thoughts carrying a
+//! tag, in a category, or both. It is written as an Atom document or a JSON
+//! Feed 1.1 file under UI_FEED_DIR, named after the stream rather than the
+//! time, so regenerating a feed replaces the file a feed reader already
+//! follows. Entry IDs are `urn:uuid:` thought IDs, which keeps readers from
+//! showing a thought twice across regenerations.
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::{ThoughtMetadata, ThoughtRecord};
+use crate::report::{escape, slug};
+
+/// Directory feeds are written to when UI_FEED_DIR is not set
+pub const DEFAULT_DIR: &str = "feeds";
+
+/// Entries in a feed when no limit is given
+pub const DEFAULT_LIMIT: usize = 50;
+
+/// Characters of a thought used as its entry title
+const TITLE_CHARS: usize = 80;
+
+/// Document format of a feed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedFormat {
+    Atom,
+    Json,
+}
+
+impl FeedFormat {
+    pub fn parse(value: Option<&str>) -> Result<Self> {
+        match value.map(|v| v.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("atom") => Ok(FeedFormat::Atom),
+            Some("json") | Some("jsonfeed") => Ok(FeedFormat::Json),
+            Some(other) => Err(UnifiedIntelligenceError::Validation {
+                field: "format".to_string(),
+                reason: format!("Unknown format '{}'. Use 'atom' or 'json'", other),
+            }),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedFormat::Atom => "atom",
+            FeedFormat::Json => "json",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            FeedFormat::Atom => "xml",
+            FeedFormat::Json => "json",
+        }
+    }
+}
+
+/// The stream a feed follows; a thought must match every part given
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stream {
+    pub tag: Option<String>,
+    pub category: Option<String>,
+}
+
+impl Stream {
+    pub fn new(tag: Option<&str>, category: Option<&str>) -> Result<Self> {
+        let clean = |value: Option<&str>| value.map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+        let stream = Self { tag: clean(tag), category: clean(category) };
+        if stream.tag.is_none() && stream.category.is_none() {
+            return Err(UnifiedIntelligenceError::Validation {
+                field: "tag".to_string(),
+                reason: "Give a tag, a category or both".to_string(),
+            });
+        }
+        Ok(stream)
+    }
+
+    pub fn matches(&self, metadata: &ThoughtMetadata) -> bool {
+        let tagged = self.tag.as_ref().is_none_or(|tag| metadata.tags.as_ref().is_some_and(|tags| tags.contains(tag)));
+        let filed = self.category.as_ref().is_none_or(|category| metadata.category.as_ref() == Some(category));
+        tagged && filed
+    }
+
+    /// Feed title, e.g. "#redis in research"
+    pub fn title(&self) -> String {
+        match (&self.tag, &self.category) {
+            (Some(tag), Some(category)) => format!("#{} in {}", tag, category),
+            (Some(tag), None) => format!("#{}", tag),
+            (None, Some(category)) => category.clone(),
+            (None, None) => "Thoughts".to_string(),
+        }
+    }
+
+    /// Stable file name, so a regenerated feed replaces the followed file
+    pub fn file_name(&self, format: FeedFormat) -> String {
+        let mut parts = Vec::new();
+        if let Some(tag) = &self.tag {
+            parts.push(format!("tag-{}", slug(tag, "untitled")));
+        }
+        if let Some(category) = &self.category {
+            parts.push(format!("category-{}", slug(category, "untitled")));
+        }
+        format!("{}.{}", parts.join("-"), format.extension())
+    }
+}
+
+/// Feed directory from UI_FEED_DIR, unless the caller names one
+pub fn output_dir(requested: Option<&str>) -> PathBuf {
+    requested.map(str::trim).filter(|dir| !dir.is_empty()).map(str::to_string)
+        .or_else(|| std::env::var("UI_FEED_DIR").ok().filter(|dir| !dir.trim().is_empty()))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_DIR))
+}
+
+fn entry_title(thought: &ThoughtRecord) -> String {
+    let title = crate::review::snippet(&thought.thought, TITLE_CHARS);
+    if title.is_empty() { format!("Thought {}", thought.thought_number) } else { title }
+}
+
+fn entry_tags(metadata: Option<&ThoughtMetadata>) -> Vec<String> {
+    metadata.and_then(|m| m.tags.clone()).unwrap_or_default()
+}
+
+/// Render a feed of `entries`, newest first; `updated` is the newest entry or the generation time
+pub fn render(
+    format: FeedFormat,
+    stream: &Stream,
+    instance: &str,
+    entries: &[(ThoughtRecord, Option<ThoughtMetadata>)],
+    generated_at: chrono::DateTime<chrono::Utc>,
+) -> String {
+    let updated = entries.first().map(|(t, _)| t.timestamp.clone()).unwrap_or_else(|| generated_at.to_rfc3339());
+    let title = format!("{} — {}", stream.title(), instance);
+    let feed_id = format!("urn:unified-intelligence:{}:{}", instance, stream.file_name(format));
+    match format {
+        FeedFormat::Atom => render_atom(&title, &feed_id, instance, &updated, entries),
+        FeedFormat::Json => render_json(&title, instance, entries),
+    }
+}
+
+fn render_atom(title: &str, feed_id: &str, instance: &str, updated: &str, entries: &[(ThoughtRecord, Option<ThoughtMetadata>)]) -> String {
+    let mut xml = String::new();
+    let _ = writeln!(xml, "<?xml version=\"1.0\" encoding=\"utf-8\"?>");
+    let _ = writeln!(xml, "<feed xmlns=\"http://www.w3.org/2005/Atom\">");
+    let _ = writeln!(xml, "  <title>{}</title>", escape(title));
+    let _ = writeln!(xml, "  <id>{}</id>", escape(feed_id));
+    let _ = writeln!(xml, "  <updated>{}</updated>", escape(updated));
+    let _ = writeln!(xml, "  <author><name>{}</name></author>", escape(instance));
+    let _ = writeln!(xml, "  <generator>unified-intelligence</generator>");
+    for (thought, metadata) in entries {
+        let _ = writeln!(xml, "  <entry>");
+        let _ = writeln!(xml, "    <title>{}</title>", escape(&entry_title(thought)));
+        let _ = writeln!(xml, "    <id>urn:uuid:{}</id>", escape(&thought.id));
+        let _ = writeln!(xml, "    <updated>{}</updated>", escape(&thought.timestamp));
+        let _ = writeln!(xml, "    <published>{}</published>", escape(&thought.timestamp));
+        for tag in entry_tags(metadata.as_ref()) {
+            let _ = writeln!(xml, "    <category term=\"{}\"/>", escape(&tag));
+        }
+        let _ = writeln!(xml, "    <content type=\"text\">{}</content>", escape(&thought.thought));
+        let _ = writeln!(xml, "  </entry>");
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+fn render_json(title: &str, instance: &str, entries: &[(ThoughtRecord, Option<ThoughtMetadata>)]) -> String {
+    let items: Vec<Value> = entries.iter().map(|(thought, metadata)| {
+        let mut item = json!({
+            "id": format!("urn:uuid:{}", thought.id),
+            "title": entry_title(thought),
+            "content_text": thought.thought,
+            "date_published": thought.timestamp,
+        });
+        let tags = entry_tags(metadata.as_ref());
+        if !tags.is_empty() {
+            item["tags"] = json!(tags);
+        }
+        item
+    }).collect();
+    let feed = json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": title,
+        "authors": [{ "name": instance }],
+        "items": items,
+    });
+    serde_json::to_string_pretty(&feed).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(text: &str, tags: &[&str], category: Option<&str>) -> (ThoughtRecord, Option<ThoughtMetadata>) {
+        let thought = ThoughtRecord::new("CC".to_string(), text.to_string(), 1, 1, None, false);
+        let tags = Some(tags.iter().map(|t| t.to_string()).collect());
+        let metadata = ThoughtMetadata::new(thought.id.clone(), "CC".to_string(), None, None, tags, category.map(str::to_string));
+        (thought, Some(metadata))
+    }
+
+    #[test]
+    fn test_stream_matching_and_names() {
+        assert!(Stream::new(Some(" "), None).is_err());
+        let stream = Stream::new(Some("redis"), Some("research")).unwrap();
+        let (_, tagged) = entry("a", &["redis"], Some("research"));
+        let (_, other) = entry("b", &["redis"], Some("ops"));
+        assert!(stream.matches(tagged.as_ref().unwrap()));
+        assert!(!stream.matches(other.as_ref().unwrap()));
+        assert_eq!(stream.file_name(FeedFormat::Atom), "tag-redis-category-research.xml");
+        assert_eq!(Stream::new(None, Some("Deep Work")).unwrap().file_name(FeedFormat::Json), "category-deep-work.json");
+        assert!(FeedFormat::parse(Some("rss")).is_err());
+    }
+
+    #[test]
+    fn test_render_escapes_and_lists_entries() {
+        let stream = Stream::new(Some("redis"), None).unwrap();
+        let entries = vec![entry("Use <SCAN> & not KEYS\nbecause it blocks", &["redis"], None)];
+        let now = chrono::Utc::now();
+
+        let atom = render(FeedFormat::Atom, &stream, "CC", &entries, now);
+        assert!(atom.contains("<title>Use &lt;SCAN&gt; &amp; not KEYS</title>"));
+        assert!(atom.contains(&format!("<id>urn:uuid:{}</id>", entries[0].0.id)));
+        assert!(atom.contains("<category term=\"redis\"/>"));
+
+        let feed: Value = serde_json::from_str(&render(FeedFormat::Json, &stream, "CC", &entries, now)).unwrap();
+        assert_eq!(feed["items"][0]["content_text"], entries[0].0.thought.as_str());
+        assert_eq!(feed["items"][0]["tags"], json!(["redis"]));
+    }
+}
@@ -71,6 +71,19 @@ impl ThinkingFramework {
         Self::from_string(framework).unwrap_or(Self::Sequential)
     }
 
+    /// Identifier accepted by from_string, as stored on thoughts
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::Sequential => "sequential",
+            Self::OODA => "ooda",
+            Self::Socratic => "socratic",
+            Self::FirstPrinciples => "first_principles",
+            Self::Systems => "systems",
+            Self::RootCause => "root_cause",
+            Self::SWOT => "swot",
+        }
+    }
+
     /// Get framework name for display
     pub fn name(&self) -> &'static str {
         match self {
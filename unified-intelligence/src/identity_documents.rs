@@ -123,7 +123,7 @@ impl IdentityDocument {
     
     /// Get Redis key for this document
     pub fn redis_key(&self) -> String {
-        format!("{}:identity:{}:{}", self.instance, self.field_type, self.id)
+        crate::keys::identity_document(&self.instance, &self.field_type, &self.id)
     }
 }
 
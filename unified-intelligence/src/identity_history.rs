@@ -0,0 +1,151 @@
+//! Identity changelog for as-of identity views.
+//!
+//! Every identity document write and delete is recorded with the document
+//! as written in `{instance}:identity_history`, a newest-first list kept
+//! without a TTL. The first change recorded for an instance also records its
+//! existing documents as a baseline, dated by their last update. Replaying
+//! the changes up to a timestamp gives the documents the instance had then,
+//! so `ui_identity` with `as_of` can show the identity the assistant was
+//! operating under. Views before the changelog starts are incomplete.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::identity_documents::IdentityDocument;
+
+/// Changes kept per instance
+pub const MAX_CHANGES: usize = 5000;
+
+/// One recorded identity document change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentityChange {
+    pub at: DateTime<Utc>,
+    pub change: String, // baseline, saved or deleted
+    pub field_type: String,
+    pub document_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document: Option<IdentityDocument>, // None for deletes
+}
+
+/// Copy kept in the changelog; embeddings can be recomputed and would bloat it
+fn recorded(document: &IdentityDocument) -> IdentityDocument {
+    IdentityDocument { embedding: None, ..document.clone() }
+}
+
+impl IdentityChange {
+    pub fn saved(document: &IdentityDocument) -> Self {
+        Self {
+            at: Utc::now(),
+            change: "saved".to_string(),
+            field_type: document.field_type.clone(),
+            document_id: document.id.clone(),
+            document: Some(recorded(document)),
+        }
+    }
+
+    pub fn deleted(field_type: &str, document_id: &str) -> Self {
+        Self {
+            at: Utc::now(),
+            change: "deleted".to_string(),
+            field_type: field_type.to_string(),
+            document_id: document_id.to_string(),
+            document: None,
+        }
+    }
+}
+
+/// Documents that existed before the first recorded change, dated by their last update
+pub fn baseline(documents: Vec<IdentityDocument>) -> Vec<IdentityChange> {
+    documents.into_iter()
+        .map(|document| IdentityChange {
+            at: document.updated_at,
+            change: "baseline".to_string(),
+            field_type: document.field_type.clone(),
+            document_id: document.id.clone(),
+            document: Some(recorded(&document)),
+        })
+        .collect()
+}
+
+/// Parse an `as_of` timestamp; a bare date means the end of that day (UTC)
+pub fn parse_as_of(value: &str) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(23, 59, 59).unwrap().and_utc())
+        .map_err(|_| UnifiedIntelligenceError::Validation {
+            field: "as_of".to_string(),
+            reason: format!("'{}' is neither a date (YYYY-MM-DD) nor an RFC 3339 timestamp", value),
+        })
+}
+
+/// Oldest point the changelog can reconstruct
+pub fn starts_at(changes: &[IdentityChange]) -> Option<DateTime<Utc>> {
+    changes.iter().map(|change| change.at).min()
+}
+
+/// Replay changes up to `as_of`, returning the documents then present and how many changes applied
+pub fn documents_as_of(changes: &[IdentityChange], as_of: DateTime<Utc>) -> (Vec<IdentityDocument>, usize) {
+    let mut ordered: Vec<&IdentityChange> = changes.iter().filter(|change| change.at <= as_of).collect();
+    ordered.sort_by_key(|change| change.at); // Stable, so same-instant changes keep their recorded order
+
+    let mut documents: BTreeMap<(&str, &str), &IdentityDocument> = BTreeMap::new();
+    for change in &ordered {
+        let key = (change.field_type.as_str(), change.document_id.as_str());
+        match &change.document {
+            Some(document) => { documents.insert(key, document); }
+            None => { documents.remove(&key); }
+        }
+    }
+    (documents.into_values().cloned().collect(), ordered.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn document(field_type: &str, tone: &str, updated_at: DateTime<Utc>) -> IdentityDocument {
+        let mut document = IdentityDocument::new(field_type.to_string(), serde_json::json!({ "tone": tone }), "CC".to_string());
+        document.id = format!("{}-doc", field_type);
+        document.updated_at = updated_at;
+        document
+    }
+
+    #[test]
+    fn test_documents_as_of() {
+        let start = Utc::now() - Duration::days(10);
+        let mut changes = baseline(vec![document("communication", "formal", start)]);
+        let mut casual = IdentityChange::saved(&document("communication", "casual", start + Duration::days(2)));
+        casual.at = start + Duration::days(2);
+        let mut notes = IdentityChange::saved(&document("context_awareness", "-", start + Duration::days(3)));
+        notes.at = start + Duration::days(3);
+        let mut dropped = IdentityChange::deleted("context_awareness", "context_awareness-doc");
+        dropped.at = start + Duration::days(4);
+        changes.extend([casual, notes, dropped]);
+
+        let (documents, applied) = documents_as_of(&changes, start + Duration::days(1));
+        assert_eq!((documents.len(), applied), (1, 1));
+        assert_eq!(documents[0].content["tone"], "formal");
+
+        let (documents, _) = documents_as_of(&changes, start + Duration::hours(84));
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0].content["tone"], "casual");
+
+        let (documents, applied) = documents_as_of(&changes, Utc::now());
+        assert_eq!((documents.len(), applied), (1, 4));
+        assert!(documents_as_of(&changes, start - Duration::days(1)).0.is_empty());
+        assert_eq!(starts_at(&changes), Some(start));
+    }
+
+    #[test]
+    fn test_parse_as_of() {
+        assert_eq!(parse_as_of("2025-07-18").unwrap().to_rfc3339(), "2025-07-18T23:59:59+00:00");
+        assert_eq!(parse_as_of("2025-07-18T10:00:00+02:00").unwrap().to_rfc3339(), "2025-07-18T08:00:00+00:00");
+        assert!(parse_as_of("last week").is_err());
+    }
+}
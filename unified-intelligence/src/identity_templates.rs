@@ -0,0 +1,184 @@
+//! Identity templates for new instance roles.
+//!
+//! Built-in templates (`coder_assistant`, `research_copilot`, `ops_agent`) are
+//! written to `identity_template:{name}` the first time they are used, after
+//! which the stored copy wins, so a template can be tuned in Redis without a
+//! release. Setting UI_IDENTITY_TEMPLATE makes a new instance bootstrap from
+//! that template instead of the stock identity.
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::Identity;
+
+/// Names of the built-in templates
+pub const BUILTIN_TEMPLATES: &[&str] = &["coder_assistant", "research_copilot", "ops_agent"];
+
+/// Template chosen for new instances, from UI_IDENTITY_TEMPLATE
+pub fn bootstrap_template() -> Option<String> {
+    std::env::var("UI_IDENTITY_TEMPLATE").ok()
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty())
+}
+
+/// Template names are lowercase words joined by underscores
+pub fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > 64 || !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+        return Err(UnifiedIntelligenceError::Validation {
+            field: "template".to_string(),
+            reason: format!("'{}' is not a template name (lowercase letters, digits and underscores)", name),
+        });
+    }
+    Ok(())
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+/// Built-in template by name, None for unknown names
+pub fn builtin(name: &str) -> Option<Identity> {
+    let mut identity = Identity::default_for_instance("template");
+    identity.relationships.clear();
+    identity.behavioral_patterns.common_mistakes.clear();
+    identity.behavioral_patterns.triggers.clear();
+    identity.context_awareness.current_project = String::new();
+    identity.context_awareness.environment = String::new();
+    identity.context_awareness.active_goals.clear();
+
+    match name {
+        "coder_assistant" => {
+            identity.core_info.instance_type = "Coding Assistant".to_string();
+            identity.core_info.primary_purpose = "software development".to_string();
+            identity.core_info.core_values = strings(&["correctness", "clarity", "craftsmanship"]);
+            identity.communication.tone = "professional".to_string();
+            identity.communication.humor_level = 0.3;
+            identity.work_preferences.error_handling = "fail-fast".to_string();
+            identity.behavioral_patterns.strengths = strings(&["reading code", "incremental changes"]);
+            identity.behavioral_patterns.improvement_areas = strings(&["asking before large refactors"]);
+            identity.technical_profile.tools = strings(&["ui_think", "ui_recall"]);
+            identity.technical_profile.expertise_areas = strings(&["code review", "debugging"]);
+            identity.context_awareness.instance_role = "coding assistant".to_string();
+            identity.memory_preferences.priority_topics = strings(&["codebase conventions", "open bugs", "past mistakes"]);
+        }
+        "research_copilot" => {
+            identity.core_info.instance_type = "Research Copilot".to_string();
+            identity.core_info.primary_purpose = "research and synthesis".to_string();
+            identity.core_info.core_values = strings(&["accuracy", "curiosity", "citing sources"]);
+            identity.communication.tone = "thoughtful".to_string();
+            identity.communication.verbosity = "detailed".to_string();
+            identity.communication.humor_level = 0.2;
+            identity.communication.directness = 0.6;
+            identity.communication.formality = "adaptive".to_string();
+            identity.work_preferences.pace = "thorough".to_string();
+            identity.work_preferences.planning_style = "exploratory".to_string();
+            identity.behavioral_patterns.strengths = strings(&["connecting sources", "summarizing"]);
+            identity.behavioral_patterns.improvement_areas = strings(&["separating evidence from speculation"]);
+            identity.technical_profile.preferred_languages = strings(&["Python"]);
+            identity.technical_profile.frameworks.clear();
+            identity.technical_profile.tools = strings(&["ui_think", "ui_recall", "ui_braindump"]);
+            identity.technical_profile.expertise_areas = strings(&["literature review", "data analysis"]);
+            identity.context_awareness.instance_role = "research copilot".to_string();
+            identity.memory_preferences.priority_topics = strings(&["open questions", "sources", "findings"]);
+            identity.memory_preferences.reference_style = "cited".to_string();
+        }
+        "ops_agent" => {
+            identity.core_info.instance_type = "Operations Agent".to_string();
+            identity.core_info.primary_purpose = "keeping systems running".to_string();
+            identity.core_info.core_values = strings(&["reliability", "caution", "transparency"]);
+            identity.communication.tone = "calm".to_string();
+            identity.communication.humor_level = 0.1;
+            identity.communication.formality = "formal".to_string();
+            identity.work_preferences.autonomy_level = "confirm before acting".to_string();
+            identity.work_preferences.error_handling = "contain and report".to_string();
+            identity.work_preferences.documentation_style = "runbooks".to_string();
+            identity.behavioral_patterns.strengths = strings(&["incident triage", "checklists"]);
+            identity.behavioral_patterns.improvement_areas = strings(&["escalating early"]);
+            identity.technical_profile.preferred_languages = strings(&["Bash", "Python"]);
+            identity.technical_profile.frameworks.clear();
+            identity.technical_profile.tools = strings(&["ui_think", "ui_recall", "ui_weekly_review"]);
+            identity.technical_profile.expertise_areas = strings(&["monitoring", "deployments", "Redis"]);
+            identity.context_awareness.instance_role = "operations".to_string();
+            identity.memory_preferences.priority_topics = strings(&["incidents", "runbooks", "change history"]);
+        }
+        _ => return None,
+    }
+    Some(identity)
+}
+
+/// Identity for an instance from a template, with `customizations` ({category: {field: value}})
+/// merged over the template's categories
+pub fn instantiate(template: &Identity, instance_id: &str, customizations: Option<&serde_json::Value>) -> Result<Identity> {
+    let mut identity = template.clone();
+    identity.core_info.instance_id = instance_id.to_string();
+    identity.context_awareness.federation_position = format!("{} - {}", instance_id, identity.core_info.instance_type);
+
+    if let Some(customizations) = customizations {
+        let overrides = customizations.as_object().ok_or_else(|| UnifiedIntelligenceError::Validation {
+            field: "value".to_string(),
+            reason: "customizations must be an object of {category: {field: value}}".to_string(),
+        })?;
+        let mut json = serde_json::to_value(&identity)?;
+        for (category, fields) in overrides {
+            let target = json.get_mut(category)
+                .filter(|_| category != "metadata")
+                .and_then(|target| target.as_object_mut())
+                .ok_or_else(|| UnifiedIntelligenceError::Validation {
+                    field: "value".to_string(),
+                    reason: format!("'{}' is not an identity category", category),
+                })?;
+            let fields = fields.as_object().ok_or_else(|| UnifiedIntelligenceError::Validation {
+                field: "value".to_string(),
+                reason: format!("customizations for '{}' must be an object", category),
+            })?;
+            for (field, value) in fields {
+                target.insert(field.clone(), value.clone());
+            }
+        }
+        identity = serde_json::from_value(json).map_err(|e| UnifiedIntelligenceError::Validation {
+            field: "value".to_string(),
+            reason: format!("customizations do not fit the identity: {}", e),
+        })?;
+    }
+
+    let now = chrono::Utc::now();
+    identity.metadata.version = 1;
+    identity.metadata.update_count = 0;
+    identity.metadata.created_at = now;
+    identity.metadata.last_updated = now;
+    Ok(identity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_builtin_templates() {
+        for name in BUILTIN_TEMPLATES {
+            let template = builtin(name).unwrap();
+            assert!(template.relationships.is_empty());
+            assert!(template.context_awareness.current_project.is_empty());
+        }
+        assert!(builtin("pirate").is_none());
+        assert!(validate_name("ops_agent").is_ok());
+        assert!(validate_name("../ops").is_err());
+    }
+
+    #[test]
+    fn test_instantiate_with_customizations() {
+        let template = builtin("ops_agent").unwrap();
+        let identity = instantiate(&template, "OPS1", Some(&json!({
+            "core_info": {"name": "Watchman"},
+            "communication": {"humor_level": 0.0},
+        }))).unwrap();
+        assert_eq!(identity.core_info.instance_id, "OPS1");
+        assert_eq!(identity.core_info.name, "Watchman");
+        assert_eq!(identity.core_info.instance_type, "Operations Agent");
+        assert_eq!(identity.communication.humor_level, 0.0);
+        assert_eq!(identity.context_awareness.federation_position, "OPS1 - Operations Agent");
+
+        assert!(instantiate(&template, "OPS1", Some(&json!({"secrets": {"a": 1}}))).is_err());
+        assert!(instantiate(&template, "OPS1", Some(&json!({"metadata": {"version": 9}}))).is_err());
+        assert!(instantiate(&template, "OPS1", Some(&json!({"communication": {"humor_level": "lots"}}))).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+//! Importance computed from usage, for thoughts whose importance wasn't given.
+//!
+//! A thought's computed importance (1-10, kept next to the manual one in its
+//! metadata) combines how the thought has been used: how often ui_recall
+//! returned it (`{instance}:recall_counts`), its feedback boost, whether it
+//! is tagged `pinned`, and how many notes it cites. Each signal saturates so
+//! none dominates, and a thought nobody has used scores the default of 5. Query recalls of an instance rank by the manual importance
+//! when there is one and the computed one otherwise: each point away from
+//! the default adds a fifth of IMPORTANCE_WEIGHT to the score. Cold tiering
+//! and the embedding staleness report fall back to it the same way. Scores are
+//! refreshed by ui_score_importance, every UI_IMPORTANCE_INTERVAL_SECS
+//! (default 3600, 0 disables), and for one thought when it gets feedback.
+
+use crate::embedding_version::DEFAULT_IMPORTANCE;
+use crate::models::{ImportanceSignals, ThoughtMetadata, ThoughtRecord};
+use crate::persona::PINNED_TAG;
+
+/// Changes listed in a ui_score_importance response
+pub const PREVIEW_CHANGES: usize = 20;
+
+/// Score an importance point away from the default adds, times 5
+pub const IMPORTANCE_WEIGHT: f32 = 0.05;
+
+
+/// Recalls at which the recall signal is full
+const FULL_RECALLS: f64 = 20.0;
+
+/// Feedback boost at which the feedback signal is about three quarters full
+const BOOST_SCALE: f64 = 4.0;
+
+/// Cited notes at which the citation signal is full
+const FULL_CITATIONS: usize = 3;
+
+/// Points each signal adds at most
+const RECALL_POINTS: f64 = 2.0;
+const FEEDBACK_POINTS: f64 = 2.0;
+const PINNED_POINTS: f64 = 3.0;
+const CITATION_POINTS: f64 = 1.0;
+
+/// Scoring interval from UI_IMPORTANCE_INTERVAL_SECS; None when set to 0
+pub fn score_interval() -> Option<std::time::Duration> {
+    let seconds = std::env::var("UI_IMPORTANCE_INTERVAL_SECS").ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(3600);
+    (seconds > 0).then(|| std::time::Duration::from_secs(seconds))
+}
+
+/// Signals of a thought, with the pinned tag and citations read from its metadata
+pub fn signals(metadata: Option<&ThoughtMetadata>, recalls: u64, feedback_boost: f64) -> ImportanceSignals {
+    ImportanceSignals {
+        recalls,
+        feedback_boost,
+        pinned: metadata.and_then(|m| m.tags.as_ref()).is_some_and(|tags| tags.iter().any(|tag| tag.eq_ignore_ascii_case(PINNED_TAG))),
+        citations: metadata.map_or(0, |m| m.citations.len()),
+    }
+}
+
+/// Importance (1-10) the signals add up to
+pub fn score(signals: &ImportanceSignals) -> i32 {
+    let recalls = ((signals.recalls as f64).ln_1p() / FULL_RECALLS.ln_1p()).min(1.0);
+    let feedback = (signals.feedback_boost / BOOST_SCALE).tanh();
+    let pinned = if signals.pinned { 1.0 } else { 0.0 };
+    let citations = signals.citations.min(FULL_CITATIONS) as f64 / FULL_CITATIONS as f64;
+    let total = DEFAULT_IMPORTANCE as f64
+        + RECALL_POINTS * recalls
+        + FEEDBACK_POINTS * feedback
+        + PINNED_POINTS * pinned
+        + CITATION_POINTS * citations;
+    (total.round() as i32).clamp(1, 10)
+}
+
+/// Amount an importance adds to a recall score (negative below the default)
+pub fn contribution(importance: Option<i32>) -> f32 {
+    (importance.unwrap_or(DEFAULT_IMPORTANCE) - DEFAULT_IMPORTANCE) as f32 / 5.0 * IMPORTANCE_WEIGHT
+}
+
+/// Add each result's importance contribution to its score and re-rank, keeping the order among equal scores
+pub fn apply(thoughts: &mut [ThoughtRecord], importance: impl Fn(&str) -> Option<i32>) {
+    let mut changed = false;
+    for thought in thoughts.iter_mut() {
+        let amount = contribution(importance(&thought.id));
+        if amount != 0.0 {
+            thought.similarity = Some(thought.similarity.unwrap_or(0.0) + amount);
+            changed = true;
+        }
+    }
+    if changed {
+        thoughts.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap_or(std::cmp::Ordering::Equal));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signals(recalls: u64, feedback_boost: f64, pinned: bool, citations: usize) -> ImportanceSignals {
+        ImportanceSignals { recalls, feedback_boost, pinned, citations }
+    }
+
+    #[test]
+    fn test_score_combines_signals() {
+        assert_eq!(score(&signals(0, 0.0, false, 0)), DEFAULT_IMPORTANCE);
+        assert_eq!(score(&signals(3, 0.0, false, 0)), 6);
+        assert_eq!(score(&signals(0, 0.0, true, 0)), 8);
+        assert_eq!(score(&signals(50, 4.0, false, 1)), 9);
+        assert_eq!(score(&signals(500, 100.0, true, 9)), 10);
+        assert_eq!(score(&signals(0, -10.0, false, 0)), 3);
+    }
+
+    #[test]
+    fn test_apply_reranks_by_importance() {
+        let mut thoughts: Vec<ThoughtRecord> = ["a", "b", "c"].iter().enumerate()
+            .map(|(i, text)| {
+                let mut thought = ThoughtRecord::new("CC".to_string(), text.to_string(), 1, 1, None, false);
+                thought.id = text.to_string();
+                thought.similarity = Some(0.80 - i as f32 * 0.01);
+                thought
+            })
+            .collect();
+        apply(&mut thoughts, |id| match id {
+            "a" => Some(1),
+            "c" => Some(10),
+            _ => None,
+        });
+        let order: Vec<&str> = thoughts.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(order, vec!["c", "b", "a"]);
+        assert_eq!(contribution(None), 0.0);
+    }
+}
@@ -0,0 +1,204 @@
+//! Redis key schema for repository data.
+//!
+//! Every key a repository backend writes is built here so the layout lives in
+//! one place. `instance` is the storage namespace, i.e. already scoped with
+//! `users:{user_id}:` when multi-tenancy is on (see `tenant`). Changing any of
+//! these formats orphans existing production data; the schema snapshot tests
+//! in `schema_stability` fail when one changes.
+
+use crate::tenant;
+
+/// `{instance}:Thoughts:{id}` - thought JSON document
+pub fn thought(instance: &str, thought_id: &str) -> String {
+    format!("{}:Thoughts:{}", instance, thought_id)
+}
+
+/// `{instance}:Thoughts:{id}:last_access` - epoch seconds of the last read
+pub fn thought_last_access(instance: &str, thought_id: &str) -> String {
+    format!("{}:last_access", thought(instance, thought_id))
+}
+
+/// `{instance}:chains:{chain_id}` - list of thought ids in chain order
+pub fn chain(instance: &str, chain_id: &str) -> String {
+    format!("{}:chains:{}", instance, chain_id)
+}
+
+/// `[users:{user_id}:]Chains:metadata:{chain_id}` - chain metadata JSON (not instance-scoped)
+pub fn chain_metadata(user_id: Option<&str>, chain_id: &str) -> String {
+    format!("{}Chains:metadata:{}", tenant::user_prefix(user_id), chain_id)
+}
+
+/// `{instance}:thought_meta:{id}` - feedback metadata JSON
+pub fn thought_metadata(instance: &str, thought_id: &str) -> String {
+    format!("{}:thought_meta:{}", instance, thought_id)
+}
+
+/// `{instance}:tags:{tag}` - set of thought ids carrying the tag
+pub fn tag(instance: &str, tag: &str) -> String {
+    format!("{}:tags:{}", instance, tag)
+}
+
+/// `{instance}:citations:{note_path}` - set of ids of thoughts citing the vault note
+pub fn citations(instance: &str, note_path: &str) -> String {
+    format!("{}:citations:{}", instance, note_path)
+}
+
+/// `{instance}:boost_scores` - sorted set of feedback boost per thought id
+pub fn boost_scores(instance: &str) -> String {
+    format!("{}:boost_scores", instance)
+}
+
+/// `{instance}:bloom:thoughts` - bloom filter of stored thought ids
+pub fn bloom(instance: &str) -> String {
+    format!("{}:bloom:thoughts", instance)
+}
+
+/// `{instance}:metrics:thought_count` - thoughts stored (time series or counter)
+pub fn thought_count(instance: &str) -> String {
+    format!("{}:metrics:thought_count", instance)
+}
+
+/// `{instance}:metrics:access_count` - thought reads (time series or counter)
+pub fn access_count(instance: &str) -> String {
+    format!("{}:metrics:access_count", instance)
+}
+
+/// `{instance}:identity` - legacy monolithic identity JSON
+pub fn identity(instance: &str) -> String {
+    format!("{}:identity", instance)
+}
+
+/// `{instance}:identity:{field_type}:{id}` - identity document JSON
+pub fn identity_document(instance: &str, field_type: &str, document_id: &str) -> String {
+    format!("{}:identity:{}:{}", instance, field_type, document_id)
+}
+
+/// `{instance}:identity_version` - counter bumped on every identity document write
+pub fn identity_version(instance: &str) -> String {
+    format!("{}:identity_version", instance)
+}
+
+/// `{instance}:identity_history` - newest-first list of identity document changes, kept without a TTL
+pub fn identity_history(instance: &str) -> String {
+    format!("{}:identity_history", instance)
+}
+
+/// `{instance}:crash_reports` - newest-first list of panic and fatal error reports
+pub fn crash_reports(instance: &str) -> String {
+    format!("{}:crash_reports", instance)
+}
+
+/// `{instance}:annotations:{thought_id}` - newest-first list of review annotation JSON
+pub fn annotations(instance: &str, thought_id: &str) -> String {
+    format!("{}:annotations:{}", instance, thought_id)
+}
+
+/// `{instance}:persona_snapshots` - hash of persona bundle JSON by version, kept without a TTL
+pub fn persona_snapshots(instance: &str) -> String {
+    format!("{}:persona_snapshots", instance)
+}
+
+/// `{instance}:pii:{thought_id}` - PII findings JSON
+pub fn pii(instance: &str, thought_id: &str) -> String {
+    format!("{}:pii:{}", instance, thought_id)
+}
+
+/// `{instance}:chain_sync:{chain_id}` - Obsidian note sync state JSON
+pub fn chain_sync(instance: &str, chain_id: &str) -> String {
+    format!("{}:chain_sync:{}", instance, chain_id)
+}
+
+/// `{instance}:clients:{name}` - per-client usage hash
+pub fn client(instance: &str, name: &str) -> String {
+    format!("{}:clients:{}", instance, name)
+}
+
+/// `{instance}:capture:seen` - set of captured item ids (URLs, message-ids)
+pub fn capture_seen(instance: &str) -> String {
+    format!("{}:capture:seen", instance)
+}
+
+/// `{instance}:embeddings:{thought_id}` - embedding JSON written by the embedding workers
+pub fn embedding(instance: &str, thought_id: &str) -> String {
+    format!("{}:embeddings:{}", instance, thought_id)
+}
+
+/// `{instance}:embedding_stale` - sorted set of thought ids to re-embed, scored by priority
+pub fn embedding_stale(instance: &str) -> String {
+    format!("{}:embedding_stale", instance)
+}
+
+/// `{instance}:events` - stream of thought and identity events
+pub fn events(instance: &str) -> String {
+    format!("{}:events", instance)
+}
+
+/// `{instance}:feedback_events` - stream of recall feedback
+pub fn feedback_events(instance: &str) -> String {
+    format!("{}:feedback_events", instance)
+}
+
+/// `{instance}:mode` - name of the active operating mode
+pub fn mode(instance: &str) -> String {
+    format!("{}:mode", instance)
+}
+
+/// `{instance}:modes` - hash of defined mode name -> profile JSON
+pub fn modes(instance: &str) -> String {
+    format!("{}:modes", instance)
+}
+
+/// `{instance}:migrations` - hash of applied migration version -> record JSON
+pub fn migrations(instance: &str) -> String {
+    format!("{}:migrations", instance)
+}
+
+/// `{instance}:migrations:lock` - server applying migrations, expires on its own
+pub fn migration_lock(instance: &str) -> String {
+    format!("{}:migrations:lock", instance)
+}
+
+/// `{instance}:bulk_undo` - newest-first list of bulk update undo records, kept without a TTL
+pub fn bulk_undo(instance: &str) -> String {
+    format!("{}:bulk_undo", instance)
+}
+
+/// `{instance}:bulk_undo:{undo_id}:claim` - server reverting a bulk update, expires on its own
+pub fn bulk_undo_claim(instance: &str, undo_id: &str) -> String {
+    format!("{}:bulk_undo:{}:claim", instance, undo_id)
+}
+
+/// `{instance}:recall_tuning` - recall defaults tuned from feedback, with their adjustment history, kept without a TTL
+pub fn recall_tuning(instance: &str) -> String {
+    format!("{}:recall_tuning", instance)
+}
+
+/// `{instance}:recall_counts` - sorted set of how often ui_recall returned each thought
+pub fn recall_counts(instance: &str) -> String {
+    format!("{}:recall_counts", instance)
+}
+
+/// `{instance}:stream_archive` - newest-first list of stream entries kept aside before a trim, kept without a TTL
+pub fn stream_archive(instance: &str) -> String {
+    format!("{}:stream_archive", instance)
+}
+
+/// `{instance}:stream_compaction` - trimmed and archived counts per stream, kept without a TTL
+pub fn stream_compaction(instance: &str) -> String {
+    format!("{}:stream_compaction", instance)
+}
+
+/// `{instance}:rate_limit:{scope}` - sliding window of request times (ms), `all` or per tool
+pub fn rate_limit(instance: &str, scope: &str) -> String {
+    format!("{}:rate_limit:{}", instance, scope)
+}
+
+/// `identity_template:{name}` - identity template shared by all instances
+pub fn identity_template(name: &str) -> String {
+    format!("identity_template:{}", name)
+}
+
+/// `purge:token:{namespace}` - pending purge confirmation token
+pub fn purge_token(namespace: &str) -> String {
+    format!("purge:token:{}", namespace)
+}
@@ -0,0 +1,405 @@
+//! Keyword extraction using RAKE (Rapid Automatic Keyword Extraction).
+//!
+//! Text is split into candidate phrases at stopwords and punctuation. Each
+//! word is scored by degree / frequency across candidates, and a phrase scores
+//! the sum of its words, so multi-word technical terms ("redis vector search")
+//! rank above frequent filler words. Shared by recall analysis, tag
+//! suggestions on ui_think and topic detection in mind_conversation_insights.
+//!
+//! Code keywords (`fn`, `let`, `unwrap`, ...) count as stopwords in every
+//! language. A vocabulary from the environment adjusts the lists for a
+//! project: KEYWORD_STOPWORDS (comma separated) and KEYWORD_STOPWORDS_FILE
+//! (one word per line, `#` comments) add stopwords, KEYWORD_BOOST
+//! (`legacymind=3,vector search`) multiplies the score of phrases containing a
+//! domain term (by 2 unless a weight is given) and keeps its words even when
+//! they are short or listed as stopwords, and KEYWORD_IGNORE drops phrases
+//! containing a noise term.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// Longest candidate phrase kept, in words
+const MAX_PHRASE_WORDS: usize = 3;
+
+const ENGLISH_STOPWORDS: &[&str] = &[
+    "a", "about", "above", "after", "again", "against", "all", "also", "am", "an", "and", "any", "are",
+    "as", "at", "be", "because", "been", "before", "being", "below", "between", "both", "but", "by",
+    "can", "could", "did", "do", "does", "doing", "done", "down", "during", "each", "even", "few",
+    "for", "from", "further", "get", "gets", "got", "had", "has", "have", "having", "he", "her",
+    "here", "hers", "him", "his", "how", "i", "if", "in", "into", "is", "it", "its", "itself", "just",
+    "let", "like", "may", "me", "might", "more", "most", "much", "must", "my", "need", "needs", "no",
+    "nor", "not", "now", "of", "off", "on", "once", "one", "only", "or", "other", "our", "ours", "out",
+    "over", "own", "same", "she", "should", "so", "some", "still", "such", "than", "that", "the",
+    "their", "theirs", "them", "then", "there", "these", "they", "this", "those", "through", "to",
+    "too", "under", "until", "up", "use", "used", "using", "very", "was", "we", "well", "were",
+    "what", "when", "where", "which", "while", "who", "whom", "why", "will", "with", "would", "yet",
+    "you", "your", "yours",
+];
+
+const SPANISH_STOPWORDS: &[&str] = &[
+    "a", "al", "algo", "como", "con", "de", "del", "el", "ella", "ellos", "en", "entre", "era",
+    "es", "esa", "ese", "eso", "esta", "este", "esto", "fue", "ha", "hay", "la", "las", "le", "lo",
+    "los", "más", "me", "mi", "muy", "no", "nos", "o", "para", "pero", "por", "porque", "que", "se",
+    "ser", "si", "sin", "sobre", "son", "su", "sus", "también", "te", "tiene", "un", "una", "uno",
+    "y", "ya", "yo",
+];
+
+const GERMAN_STOPWORDS: &[&str] = &[
+    "aber", "als", "am", "an", "auch", "auf", "aus", "bei", "bin", "bis", "da", "das", "dass", "dem",
+    "den", "der", "des", "die", "dies", "diese", "du", "ein", "eine", "einem", "einen", "einer",
+    "er", "es", "für", "hat", "ich", "ihr", "im", "in", "ist", "ja", "kann", "mit", "nach", "nicht",
+    "noch", "nur", "oder", "sich", "sie", "sind", "so", "um", "und", "uns", "von", "vor", "war",
+    "was", "wie", "wir", "wird", "zu", "zum", "zur",
+];
+
+const FRENCH_STOPWORDS: &[&str] = &[
+    "au", "aux", "avec", "ce", "ces", "cette", "dans", "de", "des", "du", "elle", "en", "est", "et",
+    "il", "ils", "je", "la", "le", "les", "leur", "lui", "mais", "me", "mon", "ne", "nous", "on",
+    "ou", "par", "pas", "pour", "qu", "que", "qui", "sa", "se", "ses", "son", "sur", "ta", "te",
+    "tu", "un", "une", "vous", "y",
+];
+
+/// Code keywords and literals that show up when thoughts quote code
+const CODE_STOPWORDS: &[&str] = &[
+    "async", "await", "bool", "const", "def", "elif", "else", "enum", "err", "false", "fn", "impl",
+    "import", "let", "mut", "none", "null", "pub", "return", "self", "some", "str", "struct", "true",
+    "unwrap", "usize", "var", "vec",
+];
+
+/// Boost of a domain term given without a weight
+pub const DEFAULT_BOOST: f64 = 2.0;
+
+/// Project-specific additions to the stopword lists: extra stopwords, boosted domain terms and ignored noise
+#[derive(Debug, Clone, Default)]
+pub struct Vocabulary {
+    stopwords: HashSet<String>,
+    boosts: Vec<(Vec<String>, f64)>,
+    domain_words: HashSet<String>,
+    ignore: Vec<Vec<String>>,
+}
+
+impl Vocabulary {
+    /// Vocabulary from KEYWORD_STOPWORDS, KEYWORD_STOPWORDS_FILE, KEYWORD_BOOST and KEYWORD_IGNORE, read once
+    pub fn global() -> &'static Vocabulary {
+        static VOCABULARY: OnceLock<Vocabulary> = OnceLock::new();
+        VOCABULARY.get_or_init(Vocabulary::from_env)
+    }
+
+    pub fn from_env() -> Self {
+        let env = |name: &str| std::env::var(name).unwrap_or_default();
+        let mut stopwords = env("KEYWORD_STOPWORDS");
+        if let Ok(path) = std::env::var("KEYWORD_STOPWORDS_FILE") {
+            match std::fs::read_to_string(&path) {
+                Ok(file) => {
+                    let words = file.lines().map(|line| line.split('#').next().unwrap_or("").trim()).filter(|word| !word.is_empty());
+                    for word in words {
+                        stopwords.push(',');
+                        stopwords.push_str(word);
+                    }
+                }
+                Err(e) => tracing::warn!("Could not read KEYWORD_STOPWORDS_FILE {}: {}", path, e),
+            }
+        }
+        Self::parse(&stopwords, &env("KEYWORD_BOOST"), &env("KEYWORD_IGNORE"))
+    }
+
+    /// Vocabulary from comma separated lists; boosts are `term` or `term=weight`
+    pub fn parse(stopwords: &str, boosts: &str, ignore: &str) -> Self {
+        let entries = |list: &str| -> Vec<String> {
+            list.split(',').map(|entry| entry.trim().to_lowercase()).filter(|entry| !entry.is_empty()).collect()
+        };
+
+        let mut vocabulary = Vocabulary {
+            stopwords: entries(stopwords).into_iter().collect(),
+            ignore: entries(ignore).iter().map(|term| tokenize(term)).filter(|words| !words.is_empty()).collect(),
+            ..Default::default()
+        };
+        for entry in entries(boosts) {
+            let (term, weight) = match entry.split_once('=') {
+                Some((term, weight)) => match weight.trim().parse::<f64>() {
+                    Ok(weight) if weight > 0.0 && weight.is_finite() => (term.to_string(), weight),
+                    _ => {
+                        tracing::warn!("Ignoring keyword boost '{}'", entry);
+                        continue;
+                    }
+                },
+                None => (entry, DEFAULT_BOOST),
+            };
+            let words = tokenize(&term);
+            if words.is_empty() {
+                continue;
+            }
+            vocabulary.domain_words.extend(words.iter().cloned());
+            vocabulary.boosts.push((words, weight));
+        }
+        vocabulary
+    }
+
+    /// Whether a word splits candidate phrases under this vocabulary and the language's list
+    fn is_noise(&self, word: &str, language_stopwords: &[&str]) -> bool {
+        if self.domain_words.contains(word) {
+            return false;
+        }
+        language_stopwords.contains(&word)
+            || CODE_STOPWORDS.contains(&word)
+            || self.stopwords.contains(word)
+            || word.chars().count() < 3
+            || word.chars().all(|c| c.is_ascii_digit())
+    }
+
+    /// Largest boost of the domain terms a phrase contains, 1.0 for none
+    fn boost(&self, phrase: &[String]) -> f64 {
+        self.boosts.iter()
+            .filter(|(term, _)| contains_words(phrase, term))
+            .map(|(_, weight)| *weight)
+            .fold(1.0, f64::max)
+    }
+
+    fn is_ignored(&self, phrase: &[String]) -> bool {
+        self.ignore.iter().any(|term| contains_words(phrase, term))
+    }
+}
+
+/// Whether `phrase` contains `term` as consecutive words
+fn contains_words(phrase: &[String], term: &[String]) -> bool {
+    !term.is_empty() && phrase.windows(term.len()).any(|window| window == term)
+}
+
+/// Language whose stopword list is applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    Spanish,
+    German,
+    French,
+}
+
+impl Language {
+    const ALL: [Language; 4] = [Language::English, Language::Spanish, Language::German, Language::French];
+
+    fn stopwords(self) -> &'static [&'static str] {
+        match self {
+            Language::English => ENGLISH_STOPWORDS,
+            Language::Spanish => SPANISH_STOPWORDS,
+            Language::German => GERMAN_STOPWORDS,
+            Language::French => FRENCH_STOPWORDS,
+        }
+    }
+
+    /// Parse an ISO 639-1 code ("en", "es", "de", "fr")
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.trim().to_lowercase().as_str() {
+            "en" | "english" => Some(Language::English),
+            "es" | "spanish" => Some(Language::Spanish),
+            "de" | "german" => Some(Language::German),
+            "fr" | "french" => Some(Language::French),
+            _ => None,
+        }
+    }
+
+    /// Pick the language whose stopwords occur most often (English on ties)
+    pub fn detect(text: &str) -> Self {
+        let words = tokenize(text);
+        Self::ALL.into_iter()
+            .map(|language| {
+                let hits = words.iter().filter(|w| language.stopwords().contains(&w.as_str())).count();
+                (language, hits)
+            })
+            .fold((Language::English, 0), |best, candidate| if candidate.1 > best.1 { candidate } else { best })
+            .0
+    }
+
+    /// KEYWORD_LANGUAGE if set, otherwise detected from the text
+    pub fn for_text(text: &str) -> Self {
+        std::env::var("KEYWORD_LANGUAGE")
+            .ok()
+            .and_then(|code| Self::from_code(&code))
+            .unwrap_or_else(|| Self::detect(text))
+    }
+}
+
+/// A ranked keyword phrase
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyword {
+    pub phrase: String,
+    pub score: f64,
+}
+
+/// Lowercased word tokens (letters, digits, '-' and '_')
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+/// Sentence fragments candidate phrases never cross
+fn fragments(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| ".,;:!?()[]{}\"\n\t".contains(c))
+}
+
+/// Occurrences of a phrase as whole words within a fragment, on the same
+/// word boundaries as extraction ("cache" doesn't count inside "cached")
+pub fn count_phrase(text: &str, phrase: &str) -> usize {
+    let target = tokenize(phrase);
+    if target.is_empty() {
+        return 0;
+    }
+    fragments(text)
+        .map(|fragment| tokenize(fragment).windows(target.len()).filter(|words| *words == target.as_slice()).count())
+        .sum()
+}
+
+/// Candidate phrases: runs of non-stopwords inside a sentence fragment, split into MAX_PHRASE_WORDS pieces
+fn candidate_phrases(text: &str, stopwords: &[&str], vocabulary: &Vocabulary) -> Vec<Vec<String>> {
+    let mut phrases = Vec::new();
+
+    for fragment in fragments(text) {
+        let mut current: Vec<String> = Vec::new();
+        for word in tokenize(fragment) {
+            if vocabulary.is_noise(&word, stopwords) {
+                if !current.is_empty() {
+                    phrases.push(std::mem::take(&mut current));
+                }
+            } else {
+                current.push(word);
+            }
+        }
+        if !current.is_empty() {
+            phrases.push(current);
+        }
+    }
+
+    phrases.into_iter()
+        .flat_map(split_run)
+        .filter(|p| !vocabulary.is_ignored(p))
+        .collect()
+}
+
+/// Split a run longer than MAX_PHRASE_WORDS (common in technical text) into
+/// consecutive phrases of near-equal length, e.g. 4 words into 2 + 2
+fn split_run(run: Vec<String>) -> Vec<Vec<String>> {
+    let pieces = run.len().div_ceil(MAX_PHRASE_WORDS);
+    if pieces <= 1 {
+        return vec![run];
+    }
+    let (base, longer) = (run.len() / pieces, run.len() % pieces);
+    let mut words = run.into_iter();
+    (0..pieces)
+        .map(|piece| words.by_ref().take(base + usize::from(piece < longer)).collect())
+        .collect()
+}
+
+/// Extract the top `limit` keyword phrases from text, using the vocabulary from the environment
+pub fn extract_keywords(text: &str, language: Language, limit: usize) -> Vec<Keyword> {
+    extract_keywords_with(text, language, limit, Vocabulary::global())
+}
+
+/// Extract the top `limit` keyword phrases from text with the given vocabulary
+pub fn extract_keywords_with(text: &str, language: Language, limit: usize, vocabulary: &Vocabulary) -> Vec<Keyword> {
+    let phrases = candidate_phrases(text, language.stopwords(), vocabulary);
+
+    let mut frequency: HashMap<&str, f64> = HashMap::new();
+    let mut degree: HashMap<&str, f64> = HashMap::new();
+    for phrase in &phrases {
+        for word in phrase {
+            *frequency.entry(word).or_insert(0.0) += 1.0;
+            *degree.entry(word).or_insert(0.0) += phrase.len() as f64;
+        }
+    }
+
+    let mut scored: HashMap<String, f64> = HashMap::new();
+    for phrase in &phrases {
+        let score: f64 = phrase.iter().map(|w| degree[w.as_str()] / frequency[w.as_str()]).sum();
+        scored.insert(phrase.join(" "), score * vocabulary.boost(phrase));
+    }
+
+    // Repeated phrases are more salient than one-offs with the same words
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    for phrase in &phrases {
+        *occurrences.entry(phrase.join(" ")).or_insert(0) += 1;
+    }
+
+    let mut keywords: Vec<Keyword> = scored.into_iter()
+        .map(|(phrase, score)| {
+            let boost = occurrences[&phrase] as f64;
+            Keyword { phrase, score: score * boost }
+        })
+        .collect();
+    keywords.sort_by(|a, b| {
+        b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.phrase.cmp(&b.phrase))
+    });
+    keywords.truncate(limit);
+    keywords
+}
+
+/// Suggest tags for a thought: top keywords as lowercase, hyphenated tags
+pub fn suggest_tags(text: &str, limit: usize) -> Vec<String> {
+    extract_keywords(text, Language::for_text(text), limit)
+        .into_iter()
+        .map(|k| k.phrase.replace(' ', "-"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rake_prefers_technical_phrases() {
+        let text = "The redis vector search is slow. We should tune the index of the redis vector search, \
+                    and then we can check the cache hit rate.";
+        let keywords = extract_keywords(text, Language::English, 3);
+        assert_eq!(keywords[0].phrase, "redis vector search");
+        assert!(keywords.iter().all(|k| !k.phrase.split(' ').any(|w| ENGLISH_STOPWORDS.contains(&w))));
+    }
+
+    #[test]
+    fn test_long_runs_are_split_not_dropped() {
+        let keywords = extract_keywords("redis vector set hnsw index", Language::English, 5);
+        let mut phrases: Vec<&str> = keywords.iter().map(|k| k.phrase.as_str()).collect();
+        phrases.sort();
+        assert_eq!(phrases, vec!["hnsw index", "redis vector set"]);
+        let words = |text: &str| text.split(' ').map(String::from).collect::<Vec<_>>();
+        assert_eq!(split_run(words("a b c d")), vec![words("a b"), words("c d")]);
+        assert_eq!(split_run(words("a b c d e f g")), vec![words("a b c"), words("d e"), words("f g")]);
+    }
+
+    #[test]
+    fn test_language_detection() {
+        assert_eq!(Language::detect("the cache is warm and the index is ready"), Language::English);
+        assert_eq!(Language::detect("el índice de la base de datos es muy lento para las consultas"), Language::Spanish);
+        assert_eq!(Language::detect("der Index ist nicht schnell und die Abfrage auch nicht"), Language::German);
+    }
+
+    #[test]
+    fn test_vocabulary() {
+        let text = "Let mut cache = fn unwrap. The UI sync for LegacyMind broke. The deploy script broke again. \
+                    The deploy script needs a retry. Ticket boilerplate here.";
+        let plain = extract_keywords_with(text, Language::English, 10, &Vocabulary::default());
+        assert!(plain.iter().all(|k| !k.phrase.split(' ').any(|w| ["let", "mut", "fn", "unwrap"].contains(&w))));
+        assert_eq!(plain[0].phrase, "deploy script broke");
+
+        let vocabulary = Vocabulary::parse("broke", "legacymind=5, ui sync", "boilerplate, bogus=");
+        let tuned = extract_keywords_with(text, Language::English, 10, &vocabulary);
+        let score = |keywords: &[Keyword], phrase: &str| keywords.iter().find(|k| k.phrase == phrase).map(|k| k.score);
+        assert_eq!(score(&tuned, "legacymind"), Some(5.0));
+        assert_eq!(score(&tuned, "ui sync"), Some(8.0));
+        assert_eq!(score(&plain, "ui sync"), None);
+        assert!(tuned.iter().all(|k| !k.phrase.contains("broke") && !k.phrase.contains("boilerplate")));
+    }
+
+    #[test]
+    fn test_count_phrase_matches_whole_words() {
+        let text = "Cache the cached cachet. Cache misses; the cache misses again";
+        assert_eq!(count_phrase(text, "cache"), 3);
+        assert_eq!(count_phrase(text, "cache misses"), 2);
+        assert_eq!(count_phrase(text, "cachet cache"), 0);
+    }
+
+    #[test]
+    fn test_suggest_tags() {
+        let tags = suggest_tags("Debugging the embedding pipeline. The embedding pipeline drops batches.", 2);
+        assert_eq!(tags[0], "embedding-pipeline");
+    }
+}
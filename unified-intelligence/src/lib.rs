@@ -0,0 +1,78 @@
+//! UnifiedIntelligence MCP server library.
+//!
+//! The binary (main.rs) serves these tools over stdio; benches drive the
+//! handlers and repositories directly.
+
+pub mod models;
+pub mod error;
+pub mod redis;
+pub mod repository;
+pub mod handlers;
+pub mod service;
+pub mod search_optimization;
+pub mod validation;
+pub mod rate_limit;
+pub mod lua_scripts;
+// mod embeddings;
+// mod vector_service;
+pub mod redisvl_service;
+pub mod api_budget;
+pub mod visual;
+pub mod frameworks;
+pub mod identity_documents;
+pub mod tenant;
+pub mod purge;
+pub mod pii;
+pub mod chain_sync;
+pub mod keywords;
+pub mod cache_invalidation;
+pub mod search_index;
+pub mod recall_explain;
+pub mod provenance;
+pub mod capabilities;
+pub mod keys;
+pub mod braindump;
+pub mod voice;
+pub mod capture;
+pub mod bookmarks;
+pub mod clipboard;
+pub mod review;
+pub mod chain_linker;
+pub mod embedding_version;
+pub mod identity_templates;
+pub mod diagnostics;
+pub mod crash_report;
+pub mod timeouts;
+pub mod training_export;
+pub mod persona;
+pub mod annotations;
+pub mod recency;
+pub mod tiering;
+pub mod replay;
+pub mod notification_bridge;
+pub mod digest;
+pub mod chain_stats;
+pub mod reconcile;
+pub mod citations;
+pub mod report;
+pub mod modes;
+pub mod custody;
+pub mod identity_history;
+pub mod memory_guard;
+pub mod migrations;
+pub mod chain_bundle;
+pub mod feeds;
+pub mod redaction;
+pub mod browse;
+pub mod bulk_update;
+pub mod chain_list;
+pub mod recall_tuning;
+pub mod next_hints;
+pub mod stream_compaction;
+pub mod importance;
+pub mod deja_vu;
+pub mod vault_export;
+pub mod chain_diff;
+pub mod thinking_types;
+#[cfg(test)]
+mod schema_stability;
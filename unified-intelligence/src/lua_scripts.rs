@@ -12,6 +12,9 @@
 /// ARGV[2] = thought UUID
 /// ARGV[3] = timestamp (epoch seconds)
 /// ARGV[4] = chain_id (optional)
+/// ARGV[5] = '1' if RedisJSON is loaded (otherwise stored with SET)
+/// ARGV[6] = '1' if RedisBloom is loaded (otherwise the bloom filter is skipped)
+/// ARGV[7] = '1' if RedisTimeSeries is loaded (otherwise KEYS[3] is an INCR counter)
 /// 
 /// Returns: "OK" on success, "DUPLICATE" if already exists
 pub const STORE_THOUGHT_SCRIPT: &str = r#"
@@ -20,35 +23,49 @@ if redis.call('EXISTS', KEYS[1]) == 1 then
     return 'DUPLICATE'
 end
 
--- Check bloom filter for potential duplicate
 local bloom_key = KEYS[2]
 local uuid = ARGV[2]
+local has_json = ARGV[5] == '1'
+local has_bloom = ARGV[6] == '1'
+local has_timeseries = ARGV[7] == '1'
 
--- Check bloom filter using BF.EXISTS
-local bloom_exists = redis.call('BF.EXISTS', bloom_key, uuid)
-if bloom_exists == 1 then
-    -- Potential duplicate, double-check with actual key
-    if redis.call('EXISTS', KEYS[1]) == 1 then
-        return 'DUPLICATE'
+-- Check bloom filter for potential duplicate
+if has_bloom then
+    local bloom_exists = redis.call('BF.EXISTS', bloom_key, uuid)
+    if bloom_exists == 1 then
+        -- Potential duplicate, double-check with actual key
+        if redis.call('EXISTS', KEYS[1]) == 1 then
+            return 'DUPLICATE'
+        end
     end
 end
 
--- Store the thought as JSON
-redis.call('JSON.SET', KEYS[1], '.', ARGV[1])
+-- Store the thought as JSON (a plain string without RedisJSON)
+if has_json then
+    redis.call('JSON.SET', KEYS[1], '.', ARGV[1])
+else
+    redis.call('SET', KEYS[1], ARGV[1])
+end
 
 -- Set 7-day TTL on the thought
 redis.call('EXPIRE', KEYS[1], 604800)
 
--- Add to bloom filter using BF.ADD
-redis.call('BF.ADD', bloom_key, uuid)
+if has_bloom then
+    -- Add to bloom filter using BF.ADD
+    redis.call('BF.ADD', bloom_key, uuid)
 
--- Set 7-day TTL on bloom filter
-redis.call('EXPIRE', bloom_key, 604800)
+    -- Set 7-day TTL on bloom filter
+    redis.call('EXPIRE', bloom_key, 604800)
+end
 
 -- Update time series metrics
 local ts_key = KEYS[3]
 local timestamp = tonumber(ARGV[3])
-redis.call('TS.ADD', ts_key, timestamp, 1)
+if has_timeseries then
+    redis.call('TS.ADD', ts_key, timestamp, 1)
+else
+    redis.call('INCR', ts_key)
+end
 
 -- Set 7-day TTL on time series
 redis.call('EXPIRE', ts_key, 604800)
@@ -71,16 +88,35 @@ return 'OK'
 /// KEYS[3] = last access key ({instance}:Thoughts:{uuid}:last_access)
 /// 
 /// ARGV[1] = timestamp (epoch seconds)
+/// ARGV[2] = '1' if RedisTimeSeries is loaded (otherwise KEYS[2] is an INCR counter)
 /// 
 /// Returns: thought JSON or nil if not found
 pub const GET_THOUGHT_SCRIPT: &str = r#"
-local thought = redis.call('JSON.GET', KEYS[1], '.')
+local function read_thought(key)
+    local key_type = redis.call('TYPE', key)['ok']
+    if key_type == 'ReJSON-RL' then
+        return redis.call('JSON.GET', key, '.')
+    elseif key_type == 'string' then
+        -- Plain JSON string (no RedisJSON); skip siblings such as {thought}:last_access
+        local value = redis.call('GET', key)
+        if string.sub(value, 1, 1) == '{' then
+            return value
+        end
+    end
+    return false
+end
+
+local thought = read_thought(KEYS[1])
 if thought == false then
     return nil
 end
 
 -- Update access metrics
-redis.call('TS.ADD', KEYS[2], ARGV[1], 1)
+if ARGV[2] == '1' then
+    redis.call('TS.ADD', KEYS[2], ARGV[1], 1)
+else
+    redis.call('INCR', KEYS[2])
+end
 redis.call('SET', KEYS[3], ARGV[1])
 
 return thought
@@ -100,6 +136,20 @@ pub const SEARCH_THOUGHTS_SCRIPT: &str = r#"
 -- In production, you'd use RediSearch or similar
 -- For now, we'll scan keys matching a pattern
 
+local function read_thought(key)
+    local key_type = redis.call('TYPE', key)['ok']
+    if key_type == 'ReJSON-RL' then
+        return redis.call('JSON.GET', key, '.')
+    elseif key_type == 'string' then
+        -- Plain JSON string (no RedisJSON); skip siblings such as {thought}:last_access
+        local value = redis.call('GET', key)
+        if string.sub(value, 1, 1) == '{' then
+            return value
+        end
+    end
+    return false
+end
+
 -- KEYS[1] = search pattern (e.g., instance:Thoughts:*)
 -- ARGV[1] = search query (not used in simple scan)
 -- ARGV[2] = offset
@@ -125,7 +175,7 @@ local limit = tonumber(ARGV[3])
 -- Apply pagination
 local results = {total}
 for i = offset + 1, math.min(offset + limit, total) do
-    local thought = redis.call('JSON.GET', all_keys[i], '.')
+    local thought = read_thought(all_keys[i])
     if thought then
         table.insert(results, thought)
     end
@@ -178,6 +228,20 @@ return 1
 /// 
 /// Returns: array of thought JSONs in chain order
 pub const GET_CHAIN_THOUGHTS_SCRIPT: &str = r#"
+local function read_thought(key)
+    local key_type = redis.call('TYPE', key)['ok']
+    if key_type == 'ReJSON-RL' then
+        return redis.call('JSON.GET', key, '.')
+    elseif key_type == 'string' then
+        -- Plain JSON string (no RedisJSON); skip siblings such as {thought}:last_access
+        local value = redis.call('GET', key)
+        if string.sub(value, 1, 1) == '{' then
+            return value
+        end
+    end
+    return false
+end
+
 local chain_ids = redis.call('LRANGE', KEYS[1], 0, -1)
 local thoughts = {}
 
@@ -185,7 +249,7 @@ for _, uuid in ipairs(chain_ids) do
     -- Build key using instance from ARGV
     local instance = ARGV[1]
     local thought_key = instance .. ':Thoughts:' .. uuid
-    local thought = redis.call('JSON.GET', thought_key, '.')
+    local thought = read_thought(thought_key)
     if thought then
         table.insert(thoughts, thought)
     end
@@ -202,6 +266,20 @@ return thoughts
 /// 
 /// Returns: number of keys cleaned up
 pub const CLEANUP_EXPIRED_SCRIPT: &str = r#"
+local function read_thought(key)
+    local key_type = redis.call('TYPE', key)['ok']
+    if key_type == 'ReJSON-RL' then
+        return redis.call('JSON.GET', key, '.')
+    elseif key_type == 'string' then
+        -- Plain JSON string (no RedisJSON); skip siblings such as {thought}:last_access
+        local value = redis.call('GET', key)
+        if string.sub(value, 1, 1) == '{' then
+            return value
+        end
+    end
+    return false
+end
+
 local pattern = KEYS[1]
 local expire_before = tonumber(ARGV[1])
 local cursor = '0'
@@ -212,7 +290,7 @@ repeat
     cursor = result[1]
     
     for _, key in ipairs(result[2]) do
-        local thought = redis.call('JSON.GET', key, '.')
+        local thought = read_thought(key)
         if thought then
             -- Parse JSON to check timestamp
             -- In production, use a proper JSON parser
@@ -235,6 +313,118 @@ until cursor == '0'
 return cleaned
 "#;
 
+/// Script to check and record a request against sliding windows, all or nothing
+/// 
+/// KEYS[1..n] = window keys ({instance}:rate_limit:{scope})
+/// 
+/// ARGV[1] = now (epoch milliseconds)
+/// ARGV[2] = request ID, unique per request
+/// ARGV[3] = '1' to record the request, '0' to only read the windows
+/// ARGV[4..] = window length in milliseconds and request limit, one pair per key
+/// 
+/// Returns: {allowed (1/0), then per key: requests in the window, oldest request ms or -1}
+pub const SLIDING_WINDOW_SCRIPT: &str = r#"
+local now = tonumber(ARGV[1])
+local record = ARGV[3] == '1'
+local counts = {}
+local allowed = 1
+
+for i, key in ipairs(KEYS) do
+    local window = tonumber(ARGV[2 + i * 2])
+    local limit = tonumber(ARGV[3 + i * 2])
+    redis.call('ZREMRANGEBYSCORE', key, '-inf', now - window)
+    counts[i] = redis.call('ZCARD', key)
+    if counts[i] >= limit then
+        allowed = 0
+    end
+end
+
+if record and allowed == 1 then
+    for i, key in ipairs(KEYS) do
+        redis.call('ZADD', key, now, ARGV[2])
+        redis.call('PEXPIRE', key, tonumber(ARGV[2 + i * 2]))
+        counts[i] = counts[i] + 1
+    end
+end
+
+local result = {record and allowed or 0}
+for i, key in ipairs(KEYS) do
+    local oldest = redis.call('ZRANGE', key, 0, 0, 'WITHSCORES')
+    table.insert(result, counts[i])
+    table.insert(result, oldest[2] and tonumber(oldest[2]) or -1)
+end
+return result
+"#;
+
+/// Redis hash recording the loaded version and SHA of each script (name -> "v{version} {sha}")
+pub const SCRIPT_REGISTRY_KEY: &str = "config:lua_scripts";
+
+/// Identifies one of the Lua scripts above
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptKind {
+    StoreThought,
+    GetThought,
+    SearchThoughts,
+    UpdateChain,
+    GetChainThoughts,
+    CleanupExpired,
+    SlidingWindow,
+}
+
+impl ScriptKind {
+    pub const ALL: [ScriptKind; 7] = [
+        ScriptKind::StoreThought,
+        ScriptKind::GetThought,
+        ScriptKind::SearchThoughts,
+        ScriptKind::UpdateChain,
+        ScriptKind::GetChainThoughts,
+        ScriptKind::CleanupExpired,
+        ScriptKind::SlidingWindow,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ScriptKind::StoreThought => "store_thought",
+            ScriptKind::GetThought => "get_thought",
+            ScriptKind::SearchThoughts => "search_thoughts",
+            ScriptKind::UpdateChain => "update_chain",
+            ScriptKind::GetChainThoughts => "get_chain_thoughts",
+            ScriptKind::CleanupExpired => "cleanup_expired",
+            ScriptKind::SlidingWindow => "sliding_window",
+        }
+    }
+
+    /// Script version - bump whenever the script body changes
+    pub fn version(self) -> u32 {
+        match self {
+            ScriptKind::StoreThought => 2,
+            ScriptKind::GetThought => 2,
+            ScriptKind::SearchThoughts => 2,
+            ScriptKind::UpdateChain => 1,
+            ScriptKind::GetChainThoughts => 2,
+            ScriptKind::CleanupExpired => 2,
+            ScriptKind::SlidingWindow => 1,
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            ScriptKind::StoreThought => STORE_THOUGHT_SCRIPT,
+            ScriptKind::GetThought => GET_THOUGHT_SCRIPT,
+            ScriptKind::SearchThoughts => SEARCH_THOUGHTS_SCRIPT,
+            ScriptKind::UpdateChain => UPDATE_CHAIN_SCRIPT,
+            ScriptKind::GetChainThoughts => GET_CHAIN_THOUGHTS_SCRIPT,
+            ScriptKind::CleanupExpired => CLEANUP_EXPIRED_SCRIPT,
+            ScriptKind::SlidingWindow => SLIDING_WINDOW_SCRIPT,
+        }
+    }
+
+    /// Script body prefixed with a name/version header, so each version has its own SHA
+    pub fn versioned_source(self) -> String {
+        format!("-- ui:{} v{}\n{}", self.name(), self.version(), self.source())
+    }
+}
+
 /// Structure to hold loaded script SHAs
 #[derive(Debug, Clone)]
 pub struct LoadedScripts {
@@ -244,6 +434,13 @@ pub struct LoadedScripts {
     pub update_chain: String,
     pub get_chain_thoughts: String,
     pub cleanup_expired: String,
+    pub sliding_window: String,
+}
+
+impl Default for LoadedScripts {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl LoadedScripts {
@@ -255,6 +452,59 @@ impl LoadedScripts {
             update_chain: String::new(),
             get_chain_thoughts: String::new(),
             cleanup_expired: String::new(),
+            sliding_window: String::new(),
         }
     }
-}
\ No newline at end of file
+
+    fn slot(&mut self, kind: ScriptKind) -> &mut String {
+        match kind {
+            ScriptKind::StoreThought => &mut self.store_thought,
+            ScriptKind::GetThought => &mut self.get_thought,
+            ScriptKind::SearchThoughts => &mut self.search_thoughts,
+            ScriptKind::UpdateChain => &mut self.update_chain,
+            ScriptKind::GetChainThoughts => &mut self.get_chain_thoughts,
+            ScriptKind::CleanupExpired => &mut self.cleanup_expired,
+            ScriptKind::SlidingWindow => &mut self.sliding_window,
+        }
+    }
+
+    /// Cached SHA for a script (empty if not loaded)
+    pub fn sha(&self, kind: ScriptKind) -> &str {
+        match kind {
+            ScriptKind::StoreThought => &self.store_thought,
+            ScriptKind::GetThought => &self.get_thought,
+            ScriptKind::SearchThoughts => &self.search_thoughts,
+            ScriptKind::UpdateChain => &self.update_chain,
+            ScriptKind::GetChainThoughts => &self.get_chain_thoughts,
+            ScriptKind::CleanupExpired => &self.cleanup_expired,
+            ScriptKind::SlidingWindow => &self.sliding_window,
+        }
+    }
+
+    pub fn set_sha(&mut self, kind: ScriptKind, sha: String) {
+        *self.slot(kind) = sha;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_versioned_sources_are_distinct() {
+        let sources: HashSet<String> = ScriptKind::ALL.iter().map(|k| k.versioned_source()).collect();
+        assert_eq!(sources.len(), ScriptKind::ALL.len());
+        assert!(ScriptKind::StoreThought.versioned_source().starts_with("-- ui:store_thought v2\n"));
+    }
+
+    #[test]
+    fn test_loaded_scripts_sha_slots() {
+        let mut scripts = LoadedScripts::new();
+        for (i, kind) in ScriptKind::ALL.into_iter().enumerate() {
+            scripts.set_sha(kind, format!("sha{}", i));
+        }
+        assert_eq!(scripts.get_thought, "sha1");
+        assert_eq!(scripts.sha(ScriptKind::CleanupExpired), "sha5");
+    }
+}
@@ -2,24 +2,7 @@ use anyhow::Result;
 use rmcp::{ServiceExt, transport::stdio};
 use tracing_subscriber;
 
-mod models;
-mod error;
-mod redis;
-mod repository;
-mod handlers;
-mod service;
-mod search_optimization;
-mod validation;
-mod rate_limit;
-mod lua_scripts;
-// mod embeddings;
-// mod vector_service;
-mod redisvl_service;
-mod visual;
-mod frameworks;
-mod identity_documents;
-
-use crate::service::UnifiedIntelligenceService;
+use unified_intelligence::service::UnifiedIntelligenceService;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -32,12 +15,26 @@ async fn main() -> Result<()> {
     
     let service = UnifiedIntelligenceService::new().await?;
     
+    // Keep panics and fatal errors in Redis rather than only on stderr
+    let crash_reporter = service.crash_reporter();
+    crash_reporter.install_panic_hook();
+    
+    let result = serve(service).await;
+    if let Err(e) = &result {
+        if let Err(report_error) = crash_reporter.report_fatal(&format!("{:#}", e)).await {
+            eprintln!("Failed to store crash report: {}", report_error);
+        }
+    }
+    
+    eprintln!("Server shutting down");
+    result
+}
+
+async fn serve(service: UnifiedIntelligenceService) -> Result<()> {
     // Start the MCP server on stdio transport
     let server = service.serve(stdio()).await?;
     
     // This keeps the server running until the transport closes
     server.waiting().await?;
-    
-    eprintln!("Server shutting down");
     Ok(())
-}
\ No newline at end of file
+}
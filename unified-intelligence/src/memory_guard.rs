@@ -0,0 +1,163 @@
+//! Redis memory pressure guard.
+//!
+//! Every UI_MEMORY_GUARD_INTERVAL_SECS (default 300, 0 disables) the guard
+//! reads INFO memory and compares `used_memory` with the limit: `maxmemory`,
+//! or UI_REDIS_MEMORY_LIMIT_MB when Redis has none, or the machine's memory.
+//! At UI_MEMORY_ELEVATED_RATIO (default 0.75) it runs the cold tiering job on
+//! every check; at UI_MEMORY_CRITICAL_RATIO (default 0.9) ingest also drops
+//! to essential-only: thoughts are stored but not sent for embedding (they
+//! are queued in `{instance}:embedding_stale` instead) and enrichment such as
+//! tag suggestions and chain linking is deferred. Each escalation publishes
+//! an operator intervention on the `intervention_events` channel, and every
+//! level change is logged as a `memory_pressure` event.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::models::MemoryUsage;
+
+/// Pub/sub channel operator interventions are published on
+pub const INTERVENTION_CHANNEL: &str = "intervention_events";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pressure {
+    #[default]
+    Normal,
+    Elevated,
+    Critical,
+}
+
+impl Pressure {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pressure::Normal => "normal",
+            Pressure::Elevated => "elevated",
+            Pressure::Critical => "critical",
+        }
+    }
+
+    /// Whether ingest is limited to essential work
+    pub fn essential_only(&self) -> bool {
+        *self == Pressure::Critical
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuardConfig {
+    pub elevated_ratio: f64,
+    pub critical_ratio: f64,
+    pub limit_bytes: Option<u64>, // Used when Redis has no maxmemory
+}
+
+impl Default for GuardConfig {
+    fn default() -> Self {
+        Self { elevated_ratio: 0.75, critical_ratio: 0.9, limit_bytes: None }
+    }
+}
+
+impl GuardConfig {
+    pub fn from_env() -> Self {
+        let env = |name: &str| std::env::var(name).ok().filter(|value| !value.trim().is_empty());
+        let ratio = |name: &str| env(name).and_then(|v| v.trim().parse::<f64>().ok()).filter(|r| *r > 0.0 && *r <= 1.0);
+        let defaults = Self::default();
+        Self {
+            elevated_ratio: ratio("UI_MEMORY_ELEVATED_RATIO").unwrap_or(defaults.elevated_ratio),
+            critical_ratio: ratio("UI_MEMORY_CRITICAL_RATIO").unwrap_or(defaults.critical_ratio),
+            limit_bytes: env("UI_REDIS_MEMORY_LIMIT_MB").and_then(|v| v.trim().parse::<u64>().ok()).filter(|mb| *mb > 0).map(|mb| mb * 1024 * 1024),
+        }
+    }
+
+    /// Memory Redis may use: maxmemory, the configured limit, or the machine's memory
+    pub fn limit(&self, usage: &MemoryUsage) -> Option<u64> {
+        Some(usage.maxmemory_bytes).filter(|max| *max > 0)
+            .or(self.limit_bytes)
+            .or(Some(usage.system_bytes).filter(|total| *total > 0))
+    }
+
+    /// Pressure level and used/limit ratio; Normal when no limit is known
+    pub fn assess(&self, usage: &MemoryUsage) -> (Pressure, Option<f64>) {
+        let Some(limit) = self.limit(usage) else {
+            return (Pressure::Normal, None);
+        };
+        let ratio = usage.used_bytes as f64 / limit as f64;
+        let level = if ratio >= self.critical_ratio {
+            Pressure::Critical
+        } else if ratio >= self.elevated_ratio {
+            Pressure::Elevated
+        } else {
+            Pressure::Normal
+        };
+        (level, Some(ratio))
+    }
+}
+
+/// Guard interval from UI_MEMORY_GUARD_INTERVAL_SECS; None when disabled
+pub fn guard_interval() -> Option<std::time::Duration> {
+    let seconds = std::env::var("UI_MEMORY_GUARD_INTERVAL_SECS").ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(300);
+    (seconds > 0).then(|| std::time::Duration::from_secs(seconds))
+}
+
+/// Memory figures from an INFO memory reply
+pub fn parse_info(info: &str) -> MemoryUsage {
+    let field = |name: &str| info.lines()
+        .find_map(|line| line.trim().strip_prefix(name)?.strip_prefix(':'))
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .unwrap_or(0);
+    MemoryUsage {
+        used_bytes: field("used_memory"),
+        maxmemory_bytes: field("maxmemory"),
+        system_bytes: field("total_system_memory"),
+    }
+}
+
+/// Intervention published when pressure escalates
+pub fn intervention(instance: &str, level: Pressure, usage: &MemoryUsage, limit: Option<u64>, ratio: Option<f64>) -> Value {
+    json!({
+        "type": "memory_pressure",
+        "priority": if level == Pressure::Critical { "high" } else { "medium" },
+        "instance": instance,
+        "level": level.name(),
+        "used_bytes": usage.used_bytes,
+        "limit_bytes": limit,
+        "ratio": ratio,
+        "message": format!(
+            "Redis memory is at {}% of its limit ({}): {}",
+            ratio.map(|r| format!("{:.0}", r * 100.0)).unwrap_or_else(|| "?".to_string()),
+            level.name(),
+            if level.essential_only() { "ingest is essential-only and cold tiering is running" } else { "cold tiering is running" },
+        ),
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_info() {
+        let info = "# Memory\r\nused_memory:943718400\r\nused_memory_human:900.00M\r\ntotal_system_memory:8589934592\r\nmaxmemory:1073741824\r\nmaxmemory_human:1.00G\r\n";
+        assert_eq!(parse_info(info), MemoryUsage { used_bytes: 943_718_400, maxmemory_bytes: 1_073_741_824, system_bytes: 8_589_934_592 });
+        assert_eq!(parse_info(""), MemoryUsage::default());
+    }
+
+    #[test]
+    fn test_assess_levels() {
+        let config = GuardConfig::default();
+        let usage = |used: u64, max: u64| MemoryUsage { used_bytes: used, maxmemory_bytes: max, system_bytes: 0 };
+        assert_eq!(config.assess(&usage(50, 100)), (Pressure::Normal, Some(0.5)));
+        assert_eq!(config.assess(&usage(80, 100)).0, Pressure::Elevated);
+        assert_eq!(config.assess(&usage(95, 100)).0, Pressure::Critical);
+        assert_eq!(config.assess(&usage(95, 0)), (Pressure::Normal, None));
+
+        // Without maxmemory the configured limit, then the machine's memory, applies
+        let limited = GuardConfig { limit_bytes: Some(100), ..Default::default() };
+        assert_eq!(limited.assess(&usage(95, 0)).0, Pressure::Critical);
+        let system = MemoryUsage { used_bytes: 95, maxmemory_bytes: 0, system_bytes: 1000 };
+        assert_eq!(config.assess(&system).0, Pressure::Normal);
+        assert!(Pressure::Critical.essential_only() && !Pressure::Elevated.essential_only());
+    }
+}
@@ -0,0 +1,217 @@
+//! Versioned data migrations, applied at startup.
+//!
+//! Stored records outlive the code that wrote them, so fixes for older
+//! layouts are ordered, numbered migrations instead of lazy checks on every
+//! read. At startup the server takes `{instance}:migrations:lock` (it expires
+//! after LOCK_TTL_SECS so a crashed server can't keep it), applies every
+//! migration not yet recorded in `{instance}:migrations` in version order, and
+//! records each with when, by which server and how many records it changed. A
+//! server that finds the lock taken leaves the migrations to its holder, and
+//! the first failing migration stops the run so later ones never see a
+//! half-migrated instance. Migrations must be idempotent: a crash between
+//! applying one and recording it reruns it. ui_migrations lists applied and
+//! pending migrations and can apply pending ones on demand.
+
+use std::collections::BTreeSet;
+use std::time::Instant;
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::identity_documents::conversion;
+use crate::keys;
+use crate::models::{AppliedMigration, PendingMigration};
+use crate::reconcile;
+use crate::repository::Repository;
+use crate::tiering;
+
+/// Seconds the migration lock is held before another server may take it
+pub const LOCK_TTL_SECS: u64 = 300;
+
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// Every migration, in the order they are applied; never renumber or remove one
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "identity_documents",
+        description: "Split a legacy monolithic identity into identity documents when the instance has none",
+    },
+    Migration {
+        version: 2,
+        name: "chain_thought_counts",
+        description: "Reconcile chain metadata written before stored thoughts were counted",
+    },
+    Migration {
+        version: 3,
+        name: "search_field_mirrors",
+        description: "Re-save thought metadata so tags and categories are mirrored onto thoughts for the search index",
+    },
+    Migration {
+        version: 4,
+        name: "chain_last_updated",
+        description: "Record when each chain last received a thought, so ui_list_chains can order chains by activity",
+    },
+];
+
+/// Name this server records as the lock holder and migration author
+pub fn owner() -> String {
+    let host = std::env::var("HOSTNAME").ok().filter(|host| !host.is_empty()).unwrap_or_else(|| "localhost".to_string());
+    format!("{}:{}", host, std::process::id())
+}
+
+/// Migrations not yet applied, in version order
+pub fn pending(applied: &[AppliedMigration]) -> Vec<PendingMigration> {
+    let done: BTreeSet<u32> = applied.iter().map(|m| m.version).collect();
+    MIGRATIONS.iter()
+        .filter(|m| !done.contains(&m.version))
+        .map(|m| PendingMigration { version: m.version, name: m.name.to_string(), description: m.description.to_string() })
+        .collect()
+}
+
+/// Apply pending migrations under the lock; None when another server holds it
+pub async fn run<R: Repository + ?Sized>(repository: &R, instance: &str, owner: &str) -> Result<Option<Vec<AppliedMigration>>> {
+    if !repository.acquire_migration_lock(instance, owner, LOCK_TTL_SECS).await? {
+        return Ok(None);
+    }
+    let result = apply_pending(repository, instance, owner).await;
+    if let Err(e) = repository.release_migration_lock(instance, owner).await {
+        tracing::warn!("Failed to release the migration lock of {}: {}", instance, e);
+    }
+    result.map(Some)
+}
+
+async fn apply_pending<R: Repository + ?Sized>(repository: &R, instance: &str, owner: &str) -> Result<Vec<AppliedMigration>> {
+    let done: BTreeSet<u32> = repository.get_applied_migrations(instance).await?.iter().map(|m| m.version).collect();
+    let mut applied = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| !done.contains(&m.version)) {
+        let started = Instant::now();
+        let changed = apply(migration, repository, instance).await.map_err(|e| {
+            UnifiedIntelligenceError::Internal(format!("Migration {} ({}) failed: {}", migration.version, migration.name, e))
+        })?;
+        let record = AppliedMigration {
+            version: migration.version,
+            name: migration.name.to_string(),
+            applied_at: chrono::Utc::now().to_rfc3339(),
+            applied_by: owner.to_string(),
+            changed,
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+        repository.record_migration(instance, &record).await?;
+        tracing::info!("Applied migration {} ({}) to {}: {} record(s) changed", migration.version, migration.name, instance, changed);
+        applied.push(record);
+    }
+    Ok(applied)
+}
+
+/// Apply one migration, returning how many records it changed
+async fn apply<R: Repository + ?Sized>(migration: &Migration, repository: &R, instance: &str) -> Result<usize> {
+    match migration.name {
+        "identity_documents" => identity_documents(repository, instance).await,
+        "chain_thought_counts" => chain_thought_counts(repository, instance).await,
+        "search_field_mirrors" => search_field_mirrors(repository, instance).await,
+        "chain_last_updated" => chain_last_updated(repository, instance).await,
+        other => Err(UnifiedIntelligenceError::Internal(format!("Unknown migration '{}'", other))),
+    }
+}
+
+async fn identity_documents<R: Repository + ?Sized>(repository: &R, instance: &str) -> Result<usize> {
+    if !repository.get_all_identity_documents(instance).await?.is_empty() {
+        return Ok(0);
+    }
+    let Some(identity) = repository.get_identity(&keys::identity(instance)).await? else {
+        return Ok(0);
+    };
+    let documents = conversion::monolithic_to_documents(serde_json::to_value(&identity)?, instance.to_string())?;
+    for document in &documents {
+        repository.save_identity_document(document).await?;
+    }
+    Ok(documents.len())
+}
+
+async fn chain_thought_counts<R: Repository + ?Sized>(repository: &R, instance: &str) -> Result<usize> {
+    let mut changed = 0;
+    for mut metadata in repository.list_chain_metadata(instance).await? {
+        if metadata.observed_thoughts.is_some() {
+            continue;
+        }
+        let thoughts = repository.get_chain_thoughts(instance, &metadata.chain_id).await?;
+        reconcile::apply(&mut metadata, &thoughts);
+        repository.save_chain_metadata(&metadata).await?;
+        changed += 1;
+    }
+    Ok(changed)
+}
+
+async fn search_field_mirrors<R: Repository + ?Sized>(repository: &R, instance: &str) -> Result<usize> {
+    let mut changed = 0;
+    for thought in repository.get_instance_thoughts(instance, tiering::SCAN_LIMIT).await? {
+        let metadata = repository.get_thought_metadata(instance, &thought.id).await?
+            .filter(|metadata| metadata.tags.is_some() || metadata.category.is_some());
+        if let Some(metadata) = metadata {
+            repository.save_thought_metadata(&metadata).await?;
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+async fn chain_last_updated<R: Repository + ?Sized>(repository: &R, instance: &str) -> Result<usize> {
+    let mut changed = 0;
+    for mut metadata in repository.list_chain_metadata(instance).await? {
+        if metadata.last_updated.is_some() {
+            continue;
+        }
+        let thoughts = repository.get_chain_thoughts(instance, &metadata.chain_id).await?;
+        if let Some(last_updated) = reconcile::last_updated(&thoughts) {
+            metadata.last_updated = Some(last_updated);
+            repository.save_chain_metadata(&metadata).await?;
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ChainMetadata, ThoughtRecord};
+    use crate::repository::{ChainOperations, MemoryRepository, MigrationOperations, ThoughtStorage};
+
+    #[tokio::test]
+    async fn test_run_applies_each_migration_once() {
+        let repo = MemoryRepository::new(None);
+        for number in 1..=3 {
+            repo.save_thought(&ThoughtRecord::new("CC".to_string(), format!("step {}", number), number, 2, Some("c1".to_string()), number < 3)).await.unwrap();
+        }
+        repo.save_chain_metadata(&ChainMetadata {
+            chain_id: "c1".to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            thought_count: 2,
+            instance: "CC".to_string(),
+            user_id: None,
+            related: Vec::new(),
+            planned_thoughts: None,
+            observed_thoughts: None,
+            last_updated: None,
+            next_hints: None,
+        }).await.unwrap();
+
+        let applied = run(&repo, "CC", "host-a:1").await.unwrap().unwrap();
+        assert_eq!(applied.iter().map(|m| m.version).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(applied[1].changed, 1);
+        let metadata = repo.get_chain_metadata("c1").await.unwrap().unwrap();
+        assert_eq!((metadata.thought_count, metadata.observed_thoughts), (3, Some(3)));
+        assert!(metadata.last_updated.is_some());
+
+        assert!(run(&repo, "CC", "host-a:1").await.unwrap().unwrap().is_empty());
+        assert!(pending(&repo.get_applied_migrations("CC").await.unwrap()).is_empty());
+
+        // Another server's lock leaves the migrations to it
+        assert!(repo.acquire_migration_lock("CX", "host-b:2", LOCK_TTL_SECS).await.unwrap());
+        assert!(run(&repo, "CX", "host-a:1").await.unwrap().is_none());
+        assert_eq!(pending(&repo.get_applied_migrations("CX").await.unwrap()).len(), MIGRATIONS.len());
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use crate::pii::{PiiFinding, PiiPolicy};
 
 /// Parameters for the ui_think tool
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -35,6 +36,28 @@ pub struct UiThinkParams {
     
     #[schemars(description = "Category: 'technical', 'strategic', 'operational', or 'relationship'")]
     pub category: Option<String>,
+    
+    #[schemars(description = "Provenance overrides, e.g. {\"author\": \"human\", \"source_tool\": \"bot-cli\"} when relaying human-written text (defaults: author 'model', source_tool 'ui_think', this server's session)")]
+    pub provenance: Option<Provenance>,
+    
+    #[schemars(description = "Vault notes this thought draws on, e.g. [{\"note_path\": \"Projects/Redis.md\", \"heading\": \"Redis > Ports\", \"hash\": \"...\"}] from um_search vault hits")]
+    pub citations: Option<Vec<Citation>>,
+    
+    #[schemars(description = "Kind of thought: 'analysis', 'decision', 'question', 'observation', 'todo', 'reflection', or 'other:<label>' for anything else")]
+    pub thinking_type: Option<String>,
+}
+
+/// A vault note section cited by a thought
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct Citation {
+    #[schemars(description = "Note path relative to the vault, e.g. 'Projects/Redis.md'")]
+    pub note_path: String,
+    
+    #[schemars(description = "Heading path of the cited section, e.g. 'Redis > Ports'")]
+    pub heading: Option<String>,
+    
+    #[schemars(description = "Content hash of the section when it was cited")]
+    pub hash: Option<String>,
 }
 
 /// Parameters for the ui_recall tool
@@ -46,7 +69,7 @@ pub struct UiRecallParams {
     #[schemars(description = "Chain ID to retrieve thoughts from (use this OR query, not both)")]
     pub chain_id: Option<String>,
     
-    #[schemars(description = "Maximum number of results to return (default: 50)")]
+    #[schemars(description = "Maximum number of results to return (default: 50, or the tuned limit for semantic search; see ui_recall_tuning)")]
     pub limit: Option<usize>,
     
     #[schemars(description = "Action to perform on results: search, merge, analyze, branch, continue")]
@@ -58,7 +81,7 @@ pub struct UiRecallParams {
     #[schemars(description = "Use semantic similarity search instead of text search (default: false)")]
     pub semantic_search: Option<bool>,
     
-    #[schemars(description = "Similarity threshold for semantic search (0.0-1.0, default: 0.7)")]
+    #[schemars(description = "Similarity threshold for semantic search (0.0-1.0, default: the tuned threshold, 0.5 until tuned; see ui_recall_tuning)")]
     pub threshold: Option<f32>,
     
     #[schemars(description = "Search across all instances instead of just current instance (default: false)")]
@@ -76,6 +99,39 @@ pub struct UiRecallParams {
     
     #[schemars(description = "Filter by category: 'technical', 'strategic', 'operational', 'relationship'")]
     pub category_filter: Option<String>,
+    
+    #[schemars(description = "Only return thoughts by this author: 'human' or 'model'")]
+    pub author_filter: Option<String>,
+    
+    #[schemars(description = "Only return thoughts ingested through this tool (e.g. 'ui_think', 'ui_chain_sync', 'bot-cli')")]
+    pub source_tool_filter: Option<String>,
+    
+    #[schemars(description = "Only return the first N thoughts of their chain (thought_number <= N)")]
+    pub first_n: Option<i32>,
+    
+    #[schemars(description = "Only return the last N thoughts of their chain, counted from the chain's current end")]
+    pub last_n: Option<i32>,
+    
+    #[schemars(description = "Only return concluding thoughts (next_thought_needed=false), skipping intermediate reasoning. Combined with first_n/last_n, a thought matching any position filter is returned")]
+    pub conclusions_only: Option<bool>,
+    
+    #[schemars(description = "Only return thoughts of these thinking types (e.g. ['decision', 'todo']); 'other' matches every 'other:<label>'")]
+    pub thinking_type_filter: Option<Vec<String>>,
+    
+    #[schemars(description = "Annotate each result with why it matched: similarity, BM25, tags/filters, boost and age (default: false)")]
+    pub explain: Option<bool>,
+    
+    #[schemars(description = "Include reviewer annotations (agree/disagree, score, comment) left with ui_annotate (default: false)")]
+    pub include_annotations: Option<bool>,
+    
+    #[schemars(description = "Ranking profile for query searches: 'default' (no recency decay), 'operational' (recent thoughts outrank stale ones, 7 day half-life), 'research' or 'archival' (no decay), or one configured in UI_RECALL_HALF_LIVES")]
+    pub profile: Option<String>,
+    
+    #[schemars(description = "Recency half-life in days, overriding the profile's; 0 disables decay")]
+    pub half_life_days: Option<f64>,
+    
+    #[schemars(description = "Fields a text search matches in: any of 'content', 'tags', 'category' (default: every field with a UI_SEARCH_WEIGHTS weight above zero)")]
+    pub search_fields: Option<Vec<String>>,
 }
 
 /// Parameters for the ui_recall_feedback tool (Phase 2)
@@ -109,6 +165,56 @@ pub struct ThoughtRecord {
     pub chain_id: Option<String>,
     pub next_thought_needed: bool,
     pub similarity: Option<f32>, // For semantic search results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>, // Owning user when multi-tenancy is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>, // Where the thought came from (absent on legacy records)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub framework: Option<String>, // Thinking framework given to ui_think, absent for sequential thoughts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking_type: Option<String>, // Taxonomy key from ui_think (see thinking_types), absent when untyped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cold: Option<ColdPointer>, // Set on stubs whose full text was tiered out to a segment file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custody: Option<Custody>, // Hash-chain link, set when UI_CHAIN_CUSTODY sealed the thought
+}
+
+/// Location of a tiered-out thought in a cold storage segment
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ColdPointer {
+    pub segment: String,  // Path relative to the cold storage directory
+    pub offset: u64,      // Byte offset of the thought's gzip member
+    pub length: u64,
+}
+
+/// Hash-chain link of a sealed chain thought
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Custody {
+    pub prev_hash: Option<String>, // Link hash of the thought stored before it in the chain, None for the first
+    pub hash: String,              // SHA-256 over the thought's fields and prev_hash
+}
+
+/// Where a thought came from
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, schemars::JsonSchema)]
+pub struct Provenance {
+    #[schemars(description = "'human' or 'model'")]
+    pub author: Option<String>,
+    
+    #[schemars(description = "Tool or client that ingested the thought (e.g. 'ui_think', 'bot-cli', 'import')")]
+    pub source_tool: Option<String>,
+    
+    #[schemars(description = "Session the thought was captured in")]
+    pub source_session: Option<String>,
+    
+    #[schemars(description = "Model that produced the thought")]
+    pub model_used: Option<String>,
+    
+    #[schemars(description = "Git commit of the working tree when the thought was captured")]
+    pub git_commit: Option<String>,
+    
+    #[schemars(description = "MCP client (name/version) connected when the thought was captured")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
 }
 
 impl ThoughtRecord {
@@ -131,6 +237,12 @@ impl ThoughtRecord {
             chain_id,
             next_thought_needed,
             similarity: None,
+            user_id: None,
+            provenance: None,
+            framework: None,
+            thinking_type: None,
+            cold: None,
+            custody: None,
         }
     }
 }
@@ -145,6 +257,10 @@ pub struct ThoughtMetadata {
     pub tags: Option<Vec<String>>,
     pub category: Option<String>,
     pub created_at: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub citations: Vec<Citation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub computed_importance: Option<i32>,  // Scored from usage; the manual importance wins when set
 }
 
 impl ThoughtMetadata {
@@ -164,8 +280,51 @@ impl ThoughtMetadata {
             tags,
             category,
             created_at: Utc::now().to_rfc3339(),
+            citations: Vec::new(),
+            computed_importance: None,
         }
     }
+    
+    /// Importance to rank by: the manual one, or else the one computed from usage
+    pub fn effective_importance(&self) -> Option<i32> {
+        self.importance.or(self.computed_importance)
+    }
+}
+
+/// PII findings recorded for a thought, stored at {instance}:pii:{thought_id}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PiiRecord {
+    pub thought_id: String,
+    pub instance: String,
+    pub policy: PiiPolicy,
+    pub masked: bool,
+    pub findings: Vec<PiiFinding>,
+    pub encrypted_original: Option<String>, // base64(nonce || AES-256-GCM ciphertext)
+    pub detected_at: String,
+}
+
+/// Sync state between a chain and its Obsidian note, stored at {instance}:chain_sync:{chain_id}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChainSyncState {
+    pub chain_id: String,
+    pub instance: String,
+    pub note_path: String,                  // Vault-relative
+    pub last_exported_thought: i32,
+    pub note_hash: String,                  // SHA-256 of the note as last written or imported
+    pub exported_at: Option<String>,
+    pub last_imported_at: Option<String>,
+    pub annotations_imported: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub privacy_level: Option<String>,      // Redaction of the note as last written
+}
+
+/// Coverage of the thought search index
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct SearchIndexStatus {
+    pub registered_prefixes: Vec<String>,
+    pub indexed_prefixes: Option<Vec<String>>,  // None when idx:thoughts doesn't exist
+    pub discovered_instances: Vec<String>,      // Unscoped instances that have thoughts
+    pub degraded_features: Vec<String>,         // Fallback paths in use because Redis modules are missing
 }
 
 /// Response from ui_think tool
@@ -174,6 +333,34 @@ pub struct ThinkResponse {
     pub status: String,
     pub thought_id: String,
     pub next_thought_needed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pii_detected: Option<Vec<String>>, // Kinds of PII found in the thought
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggested_tags: Option<Vec<String>>, // Keyword-based tags, offered when none were given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_total: Option<i32>, // Reconciled chain length, when it differs from total_thoughts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discrepancies: Option<Vec<String>>, // Numbering mismatches against the chain
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deja_vu: Option<DejaVu>, // Similar earlier thoughts from other chains
+}
+
+/// Note that a new thought resembles earlier ones from other chains
+#[derive(Debug, Serialize, Clone)]
+pub struct DejaVu {
+    pub message: String,
+    pub occurrences: Vec<DejaVuOccurrence>, // Most similar first
+}
+
+/// An earlier thought a new one resembles
+#[derive(Debug, Serialize, Clone)]
+pub struct DejaVuOccurrence {
+    pub thought_id: String,
+    pub chain_id: Option<String>,
+    pub thought_number: i32,
+    pub timestamp: String,
+    pub similarity: f32,
+    pub preview: String,
 }
 
 /// Response from ui_recall tool  
@@ -187,6 +374,31 @@ pub struct RecallResponse {
     pub action_result: Option<serde_json::Value>,
     // PHASE 2 FEEDBACK LOOP ENHANCEMENT
     pub search_id: String,  // For tracking this search session
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanations: Option<Vec<RecallExplanation>>,  // Only when explain=true
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<HashMap<String, Vec<Annotation>>>,  // By thought id, only when include_annotations=true
+    pub partial: bool,  // The tool's time budget ran out; later filters, analysis or explanations were cut short
+}
+
+/// Why a thought was returned by ui_recall (explain=true)
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct RecallExplanation {
+    pub thought_id: String,
+    pub rank: usize,
+    pub final_score: Option<f32>,            // Score results were ranked by
+    pub vector_similarity: Option<f32>,      // Cosine similarity before boosts (semantic search)
+    pub bm25_score: Option<f64>,             // Query terms against the returned results
+    pub matched_terms: Vec<String>,
+    pub boost_score: Option<f64>,            // Raw feedback boost for the thought
+    pub boost_contribution: Option<f32>,     // Amount the boost added to final_score
+    pub matched_tags: Vec<String>,
+    pub matched_filters: Vec<String>,
+    pub age_days: Option<f64>,
+    pub decay_factor: Option<f32>,           // Recency weight the score was multiplied by (profile with a half-life)
+    pub importance: Option<i32>,             // Manual importance, or else computed from usage
+    pub importance_contribution: Option<f32>, // Amount the importance added to final_score
+    pub reasons: Vec<String>,                // Human-readable summary
 }
 
 /// Response from ui_recall_feedback tool  
@@ -205,6 +417,35 @@ pub struct ChainMetadata {
     pub created_at: String,
     pub thought_count: i32,
     pub instance: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<RelatedChain>, // Chains linked by the background linker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub planned_thoughts: Option<i32>, // Client's original total when thought_count was extended
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub observed_thoughts: Option<i32>, // Thoughts actually stored in the chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<String>, // Timestamp of the newest stored thought
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_hints: Option<NextHints>, // Cached by ui_next_hint, cleared when a thought is added
+}
+
+/// Directions the next thought of a chain could take, cached by ui_next_hint
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NextHints {
+    pub hints: Vec<String>,
+    pub source: String,             // "generator" (UI_HINT_GENERATOR) or "open_questions"
+    pub generated_at: String,
+    pub observed_thoughts: usize,   // Thoughts in the chain when the hints were generated
+}
+
+/// A chain created close in time with overlapping topics
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RelatedChain {
+    pub chain_id: String,
+    pub score: f64,                 // Topic overlap (Jaccard), 0.0-1.0
+    pub shared_topics: Vec<String>,
 }
 
 // ===== IDENTITY MANAGEMENT STRUCTURES =====
@@ -212,7 +453,7 @@ pub struct ChainMetadata {
 /// Parameters for the ui_identity tool
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct UiIdentityParams {
-    #[schemars(description = "Operation to perform: View, Add, Modify, Delete")]
+    #[schemars(description = "Operation to perform: View, Add, Modify, Delete, Help, FromTemplate")]
     pub operation: Option<IdentityOperation>,
     
     #[schemars(description = "Category to operate on")]
@@ -223,6 +464,15 @@ pub struct UiIdentityParams {
     
     #[schemars(description = "Value to set/add/remove")]
     pub value: Option<serde_json::Value>,
+    
+    #[schemars(description = "View only these categories (e.g. [\"core_info\", \"communication\"]) instead of the whole identity")]
+    pub categories: Option<Vec<String>>,
+    
+    #[schemars(description = "Template for from_template: coder_assistant, research_copilot, ops_agent, or any stored template")]
+    pub template: Option<String>,
+    
+    #[schemars(description = "For view: show the identity as it was at this time (RFC 3339, or YYYY-MM-DD for the end of that day), rebuilt from the identity changelog")]
+    pub as_of: Option<String>,
 }
 
 /// Identity operation types
@@ -234,139 +484,1818 @@ pub enum IdentityOperation {
     Modify,    // Change existing value
     Delete,    // Remove from list/map
     Help,      // Show comprehensive help documentation
+    FromTemplate, // Replace the identity with a customized template
+}
+
+/// Parameters for the ui_diagnostics tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiDiagnosticsParams {
+    #[schemars(description = "Most recent errors to include (default 20)")]
+    pub error_limit: Option<usize>,
+    
+    #[schemars(description = "Also write the bundle as pretty-printed JSON to this file path")]
+    pub output_path: Option<String>,
 }
 
-/// Parameters for the ui_debug_env tool
+/// Parameters for the ui_purge tool
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
-pub struct UiDebugEnvParams {
-    // No parameters needed for this tool
+pub struct UiPurgeParams {
+    #[schemars(description = "What to purge: 'instance' (default) or 'user' (every instance of the configured USER_ID)")]
+    pub scope: Option<String>,
+    
+    #[schemars(description = "Only inventory the data that would be deleted (default: true)")]
+    pub dry_run: Option<bool>,
+    
+    #[schemars(description = "Confirmation token returned by a dry run; required to actually delete")]
+    pub confirmation_token: Option<String>,
 }
 
-/// Parameters for the mind_monitor_status tool
+/// Parameters for the ui_pii_findings tool
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
-pub struct MindMonitorStatusParams {
-    // No fields currently used
+pub struct UiPiiFindingsParams {
+    #[schemars(description = "Maximum number of thoughts with findings to return (default: 50)")]
+    pub limit: Option<usize>,
 }
 
-/// Parameters for the mind_cognitive_metrics tool
+/// Parameters for the ui_chain_sync tool
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
-pub struct MindCognitiveMetricsParams {
-    // No fields currently used
+pub struct UiChainSyncParams {
+    #[schemars(description = "Chain to sync with its Obsidian note")]
+    pub chain_id: String,
+    
+    #[schemars(description = "'export' (write the note), 'import' (pull human annotations into the chain) or 'sync' (default: import then export)")]
+    pub direction: Option<String>,
+    
+    #[serde(default)]
+    #[schemars(description = "Redaction of the note: 'off', 'pii' (mask detected PII) or 'strict' (also withhold thoughts tagged 'private'). Default: the level of the last export, else 'off'")]
+    pub privacy_level: Option<String>,
 }
 
-/// Parameters for the mind_intervention_queue tool
+/// Parameters for the ui_search_index tool
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
-pub struct MindInterventionQueueParams {
-    // No fields currently used
+pub struct UiSearchIndexParams {
+    #[schemars(description = "'status' (default: report coverage) or 'rebuild' (register every instance with thoughts and rebuild idx:thoughts)")]
+    pub action: Option<String>,
 }
 
-/// Parameters for the mind_conversation_insights tool
+/// Parameters for the ui_clients tool
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
-pub struct MindConversationInsightsParams {
-    // No fields currently used
+pub struct UiClientsParams {
+    #[schemars(description = "Only return stats for this client name (e.g. 'claude-ai', 'cursor')")]
+    pub client: Option<String>,
 }
 
-/// Parameters for the mind_entity_tracking tool  
+/// Parameters for the ui_braindump tool
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
-pub struct MindEntityTrackingParams {
-    // No fields currently used
+pub struct UiBraindumpParams {
+    #[schemars(description = "Long free-form text to split into a chain of thoughts")]
+    pub text: String,
+    
+    #[schemars(description = "'heuristic' (paragraphs, list items and sentences; default) or 'llm' (the UI_BRAINDUMP_SEGMENTER command, falling back to heuristic)")]
+    pub segmentation: Option<String>,
+    
+    #[schemars(description = "Longest heuristic segment in characters before paragraphs are split at sentences (default: 600)")]
+    pub max_segment_chars: Option<usize>,
+    
+    #[schemars(description = "Tags applied to every created thought")]
+    pub tags: Option<Vec<String>>,
+    
+    #[schemars(description = "Category applied to every created thought: 'technical', 'strategic', 'operational', or 'relationship'")]
+    pub category: Option<String>,
+    
+    #[schemars(description = "Importance score from 1-10 applied to every created thought")]
+    pub importance: Option<i32>,
+    
+    #[schemars(description = "Provenance overrides, as for ui_think (default source_tool: 'ui_braindump')")]
+    pub provenance: Option<Provenance>,
 }
 
-/// Response from ui_debug_env tool
-#[derive(Debug, Serialize)]
-pub struct DebugEnvResponse {
-    pub openai_api_key: String,    // Masked value
-    pub redis_password: String,    // Masked value
-    pub instance_id: Option<String>, // Full value shown
+/// Parameters for the ui_voice_memo tool (give exactly one of audio_path, transcript or segments)
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct UiVoiceMemoParams {
+    #[schemars(description = "Audio file to transcribe with the UI_TRANSCRIBE_COMMAND hook (e.g. Whisper)")]
+    pub audio_path: Option<String>,
+    
+    #[schemars(description = "Pre-transcribed text; lines may start with a '[mm:ss]' offset into the recording")]
+    pub transcript: Option<String>,
+    
+    #[schemars(description = "Pre-transcribed Whisper segments: [{start, end, text, speaker}] with offsets in seconds")]
+    pub segments: Option<Vec<VoiceSegment>>,
+    
+    #[schemars(description = "When recording started (RFC 3339; default: the audio file's modification time, else now). Thought timestamps are this plus each segment's offset")]
+    pub recorded_at: Option<String>,
+    
+    #[schemars(description = "Names for diarization labels, e.g. {\"SPEAKER_00\": \"sam\"}")]
+    pub speaker_names: Option<std::collections::HashMap<String, String>>,
+    
+    #[schemars(description = "Tags applied to every created thought (speakers are added as 'speaker:{name}')")]
+    pub tags: Option<Vec<String>>,
+    
+    #[schemars(description = "Category applied to every created thought")]
+    pub category: Option<String>,
+    
+    #[schemars(description = "Importance score from 1-10 applied to every created thought")]
+    pub importance: Option<i32>,
+    
+    #[schemars(description = "Provenance overrides, as for ui_think (defaults: author 'human', source_tool 'voice')")]
+    pub provenance: Option<Provenance>,
 }
 
-/// Response from mind_monitor_status tool
-#[derive(Debug, Serialize)]
-pub struct MindMonitorStatusResponse {
-    pub status: String,                // "active", "starting", "stopped"
-    pub uptime_seconds: u64,
-    pub thoughts_processed: usize,
-    pub interventions_pending: usize,
-    pub current_cognitive_load: f32,
-    pub monitoring_enabled: bool,
-    pub detailed_metrics: Option<serde_json::Value>,
+/// Parameters for the ui_capture tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiCaptureParams {
+    #[schemars(description = "'rss', 'email', 'git' or 'all' (default) configured capture sources to poll")]
+    pub source: Option<String>,
+    
+    #[schemars(description = "Newest items to consider per feed, mailbox or repository (default: 20)")]
+    pub limit: Option<usize>,
 }
 
-/// Response from mind_cognitive_metrics tool
-#[derive(Debug, Serialize)]
-pub struct MindCognitiveMetricsResponse {
-    pub cognitive_load: f32,
-    pub pattern_recognition_rate: f32,
-    pub learning_velocity: f32,
-    pub focus_level: f32,
-    pub confidence: f32,
-    pub thinking_velocity: f32,
-    pub uncertainty_level: f32,
-    pub cognitive_fatigue: f32,
-    pub context_switches: usize,
-    pub working_memory_usage: f32,
-    pub trends: Option<serde_json::Value>,
+/// Parameters for the ui_import_bookmarks tool (give exactly one of path or content)
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct UiImportBookmarksParams {
+    #[schemars(description = "Export file to import: Netscape bookmarks HTML or highlights JSON")]
+    pub path: Option<String>,
+    
+    #[schemars(description = "Export contents, instead of a path")]
+    pub content: Option<String>,
+    
+    #[schemars(description = "'bookmarks' (Netscape HTML) or 'highlights' (read-later JSON); detected from the content when omitted")]
+    pub format: Option<String>,
+    
+    #[schemars(description = "Tags applied to every imported thought, besides folders, item tags and 'domain:{host}'")]
+    pub tags: Option<Vec<String>>,
 }
 
-/// Response from mind_intervention_queue tool
-#[derive(Debug, Serialize)]
-pub struct MindInterventionQueueResponse {
-    pub interventions: Vec<InterventionDetail>,
-    pub total_pending: usize,
-    pub priority_breakdown: serde_json::Value,
+/// Parameters for the ui_weekly_review tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiWeeklyReviewParams {
+    #[schemars(description = "Last day of the week to review (YYYY-MM-DD, inclusive) or an RFC 3339 end time (default: now); the review covers the 7 days before")]
+    pub week_ending: Option<String>,
+    
+    #[schemars(description = "Answers to the reflection prompts, stored as the last thoughts of the review chain")]
+    pub reflections: Option<Vec<String>>,
+    
+    #[schemars(description = "Store the review as a chain tagged 'weekly-review' (default: true); false only previews it")]
+    pub store: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct InterventionDetail {
-    pub id: String,
-    pub timestamp: String,
-    pub intervention_type: String,
-    pub priority: String,
-    pub context: String,
-    pub suggested_action: String,
-    pub reason: String,
-    pub confidence: f32,
+/// Parameters for the ui_list_chains tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiListChainsParams {
+    #[schemars(description = "Chains per page, most recently active first (default: 20, at most 500)")]
+    pub limit: Option<usize>,
+    
+    #[schemars(description = "next_cursor from the previous page, to continue after it")]
+    pub cursor: Option<String>,
+    
+    #[schemars(description = "Run the chain linker before listing so related chains are current (default: false)")]
+    pub relink: Option<bool>,
 }
 
-/// Response from mind_conversation_insights tool
-#[derive(Debug, Serialize)]
-pub struct MindConversationInsightsResponse {
-    pub session_id: String,
-    pub message_count: usize,
-    pub conversation_state: String,
-    pub detected_topics: Vec<String>,
-    pub key_entities: Vec<serde_json::Value>,
-    pub flow_patterns: Vec<String>,
-    pub insights: Vec<String>,
+/// Parameters for the ui_embedding_staleness tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiEmbeddingStalenessParams {
+    #[schemars(description = "Most stale thoughts to list, most important first (default: 50)")]
+    pub limit: Option<usize>,
+    
+    #[schemars(description = "Only consider thoughts with at least this importance (1-10)")]
+    pub min_importance: Option<i32>,
+    
+    #[schemars(description = "Queue every stale thought for re-embedding by the background worker, highest importance first (default: false)")]
+    pub enqueue: Option<bool>,
 }
 
-/// Response from mind_entity_tracking tool
-#[derive(Debug, Serialize)]
-pub struct MindEntityTrackingResponse {
-    pub entities: Vec<TrackedEntity>,
-    pub total_detected: usize,
-    pub importance_ranking: Vec<serde_json::Value>,
-    pub enrichment_suggestions: Option<Vec<serde_json::Value>>,
+/// Parameters for the ui_export_training tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiExportTrainingParams {
+    #[schemars(description = "'chat' (system/user/assistant messages, default) or 'prompt_completion'")]
+    pub format: Option<String>,
+    
+    #[schemars(description = "Chains to export (default: every chain of this instance)")]
+    pub chain_ids: Option<Vec<String>>,
+    
+    #[schemars(description = "Skip chains whose last thought still expects a next thought (default: true)")]
+    pub concluded_only: Option<bool>,
+    
+    #[schemars(description = "Only chains whose best thought earned at least this recall feedback boost")]
+    pub min_feedback_boost: Option<f64>,
+    
+    #[schemars(description = "System message for chat examples")]
+    pub system_prompt: Option<String>,
+    
+    #[schemars(description = "Write the JSONL to this file instead of returning the lines")]
+    pub output_path: Option<String>,
+    
+    #[schemars(description = "'pii' (default, mask detected PII), 'strict' (also withhold thoughts tagged 'private') or 'off'")]
+    pub privacy_level: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct TrackedEntity {
-    pub text: String,
-    pub entity_type: String,
-    pub confidence: f32,
-    pub context: String,
-    pub occurrences: usize,
-    pub importance_score: f32,
-    pub metadata: Option<serde_json::Value>,
+/// Parameters for the ui_persona_snapshot tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiPersonaSnapshotParams {
+    #[schemars(description = "Keep the bundle as a new version when it differs from the latest one (default: true)")]
+    pub store: Option<bool>,
+    
+    #[schemars(description = "Dominant topics of recent thoughts to include (default: 10)")]
+    pub topic_limit: Option<usize>,
+    
+    #[schemars(description = "Thoughts tagged 'pinned' to include, newest first (default: 20)")]
+    pub pinned_limit: Option<usize>,
+    
+    #[schemars(description = "Weekly review summaries to include, newest first (default: 4)")]
+    pub summary_limit: Option<usize>,
+    
+    #[schemars(description = "Write the bundle to this file: Markdown for .md paths, JSON otherwise")]
+    pub output_path: Option<String>,
+    
+    #[schemars(description = "Redaction of pinned thoughts and summaries in the returned and written bundle: 'off' (default), 'pii' or 'strict' (also withhold thoughts tagged 'private'); stored versions are never redacted")]
+    pub privacy_level: Option<String>,
 }
 
-/// Response from ui_identity tool
-#[derive(Debug, Serialize)]
-#[serde(untagged)]
-pub enum IdentityResponse {
-    View {
-        identity: Identity,
-        available_categories: Vec<&'static str>,
+/// Parameters for the ui_persona_diff tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiPersonaDiffParams {
+    #[schemars(description = "Older persona version (default: the version before to_version)")]
+    pub from_version: Option<u64>,
+    
+    #[schemars(description = "Newer persona version (default: the latest)")]
+    pub to_version: Option<u64>,
+}
+
+/// Parameters for the ui_annotate tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiAnnotateParams {
+    #[schemars(description = "Thought to annotate")]
+    pub thought_id: String,
+    
+    #[schemars(description = "Instance that owns the thought (default: this instance)")]
+    pub instance: Option<String>,
+    
+    #[schemars(description = "'agree' or 'disagree'")]
+    pub verdict: Option<String>,
+    
+    #[schemars(description = "Quality score from 1 to 10")]
+    pub score: Option<i32>,
+    
+    #[schemars(description = "Review comment")]
+    pub comment: Option<String>,
+    
+    #[schemars(description = "Who is reviewing, e.g. 'human' (default: this instance)")]
+    pub reviewer: Option<String>,
+}
+
+/// Parameters for the ui_tier_cold tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiTierColdParams {
+    #[schemars(description = "Only report what would be moved (default: false)")]
+    pub dry_run: Option<bool>,
+    
+    #[schemars(description = "Minimum thought age in days (default: UI_TIER_MIN_AGE_DAYS or 30)")]
+    pub min_age_days: Option<i64>,
+    
+    #[schemars(description = "Days since the thought was last read (default: UI_TIER_IDLE_DAYS or 30)")]
+    pub idle_days: Option<i64>,
+    
+    #[schemars(description = "Highest importance that may be moved (default: UI_TIER_MAX_IMPORTANCE or 5, the importance of unscored thoughts)")]
+    pub max_importance: Option<i32>,
+    
+    #[schemars(description = "Most thoughts to move in this run (default: UI_TIER_BATCH_SIZE or 1000)")]
+    pub limit: Option<usize>,
+}
+
+/// Parameters for the ui_replay tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiReplayParams {
+    #[schemars(description = "Chain to replay")]
+    pub chain_id: String,
+    
+    #[schemars(description = "Thought number to start from (default: 1; use next_from_thought to continue)")]
+    pub from_thought: Option<i32>,
+    
+    #[schemars(description = "Most steps in this call (default: 20, max: 100)")]
+    pub limit: Option<usize>,
+    
+    #[schemars(description = "Milliseconds to wait between steps (default: 1000, max: 10000)")]
+    pub pace_ms: Option<u64>,
+    
+    #[schemars(description = "Replay the original gaps between thoughts, divided by this factor (e.g. 60 plays a minute in a second); overrides pace_ms")]
+    pub speed: Option<f64>,
+}
+
+/// Parameters for the ui_subscribe tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiSubscribeParams {
+    #[schemars(description = "'subscribe', 'unsubscribe' or 'list' (default: list)")]
+    pub action: Option<String>,
+    
+    #[schemars(description = "Channels to subscribe to or unsubscribe from (default: all bridged channels)")]
+    pub channels: Option<Vec<String>>,
+}
+
+/// Parameters for the ui_chain_stats tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiChainStatsParams {
+    #[schemars(description = "Chain to measure (default: the most recent chains)")]
+    pub chain_id: Option<String>,
+    
+    #[schemars(description = "Most recent chains to measure when no chain_id is given (default: 10)")]
+    pub limit: Option<usize>,
+}
+
+/// Parameters for the ui_citations tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiCitationsParams {
+    #[schemars(description = "Vault-relative path of the note, e.g. 'Projects/Redis.md'")]
+    pub note_path: String,
+    
+    #[schemars(description = "Only thoughts citing a heading containing this text")]
+    pub heading: Option<String>,
+}
+
+/// Parameters for the ui_report tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiReportParams {
+    #[schemars(description = "Chain to report on (default: the most recent thoughts of this instance)")]
+    pub chain_id: Option<String>,
+    
+    #[schemars(description = "Only thoughts carrying all of these tags")]
+    pub tags: Option<Vec<String>>,
+    
+    #[schemars(description = "Only thoughts from this date (YYYY-MM-DD) or RFC 3339 timestamp on")]
+    pub since: Option<String>,
+    
+    #[schemars(description = "Most thoughts to include (default: 200)")]
+    pub limit: Option<usize>,
+    
+    #[schemars(description = "Report title (default: the chain ID or 'Thought report')")]
+    pub title: Option<String>,
+    
+    #[schemars(description = "Directory to write the report to (default: UI_REPORT_DIR or ./reports)")]
+    pub output_dir: Option<String>,
+    
+    #[schemars(description = "Replace detected PII with [REDACTED:<kind>] (default: true); privacy_level takes precedence")]
+    pub mask_pii: Option<bool>,
+    
+    #[schemars(description = "'pii' (mask detected PII), 'strict' (also withhold thoughts tagged 'private') or 'off'")]
+    pub privacy_level: Option<String>,
+}
+
+/// Parameters for the ui_mode tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiModeParams {
+    #[schemars(description = "'get' (default), 'list', 'switch' to a mode or 'define' a custom one")]
+    pub action: Option<String>,
+    
+    #[schemars(description = "Mode to switch to: 'default', 'work', 'personal', 'focus' or a defined one")]
+    pub mode: Option<String>,
+    
+    #[schemars(description = "Profile to define; a custom profile replaces a built-in one of the same name")]
+    pub profile: Option<ModeProfile>,
+}
+
+/// Operating profile of an instance, switched with ui_mode
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct ModeProfile {
+    #[schemars(description = "Mode name, e.g. 'work' (letters, digits, '-' and '_')")]
+    pub name: String,
+    
+    #[serde(default)]
+    #[schemars(description = "Defaults for ui_recall parameters the caller leaves out")]
+    pub recall: RecallDefaults,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "'low', 'normal' or 'high' sensitivity for intervention monitors")]
+    pub intervention_sensitivity: Option<String>,
+    
+    #[serde(default)]
+    #[schemars(description = "Which captured items ui_capture keeps")]
+    pub capture: CaptureFilters,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Notification channels the client is subscribed to on switching ([] mutes; omitted keeps the current subscriptions)")]
+    pub notification_channels: Option<Vec<String>>,
+}
+
+/// ui_recall defaults of a mode
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct RecallDefaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub semantic_search: Option<bool>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f32>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_all_instances: Option<bool>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_importance: Option<i32>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Recency ranking profile, e.g. 'operational'")]
+    pub ranking_profile: Option<String>,
+}
+
+/// ui_capture filters of a mode
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct CaptureFilters {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Sources polled: 'rss', 'email', 'git' ([] pauses capture; omitted polls all)")]
+    pub sources: Option<Vec<String>>,
+    
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Keep only items mentioning one of these words")]
+    pub include_keywords: Vec<String>,
+    
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Drop items mentioning any of these words")]
+    pub exclude_keywords: Vec<String>,
+}
+
+/// Parameters for the ui_verify_chain tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct UiVerifyChainParams {
+    #[schemars(description = "Chain ID whose custody hashes to verify")]
+    pub chain_id: String,
+}
+
+/// Parameters for the ui_migrations tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct UiMigrationsParams {
+    #[serde(default)]
+    #[schemars(description = "Apply pending migrations now instead of only listing them (default false)")]
+    pub apply: Option<bool>,
+}
+
+/// Parameters for the ui_delete_thought tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct UiDeleteThoughtParams {
+    #[schemars(description = "ID of the thought to delete")]
+    pub thought_id: String,
+}
+
+/// Parameters for the ui_delete_chain tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct UiDeleteChainParams {
+    #[schemars(description = "ID of the chain to delete along with all of its thoughts")]
+    pub chain_id: String,
+}
+
+/// Parameters for the ui_export_chain tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct UiExportChainParams {
+    #[schemars(description = "Chain ID to export")]
+    pub chain_id: String,
+    
+    #[serde(default)]
+    #[schemars(description = "Write the bundle to this JSON file under UI_BUNDLE_DIR (default ./bundles) instead of returning it; an existing file is not overwritten")]
+    pub output_path: Option<String>,
+    
+    #[serde(default)]
+    #[schemars(description = "Redaction of thought text: 'off' (default), 'pii' (mask detected PII) or 'strict' (also withhold thoughts tagged 'private')")]
+    pub privacy_level: Option<String>,
+}
+
+/// Parameters for the ui_import_chain tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct UiImportChainParams {
+    #[serde(default)]
+    #[schemars(description = "Chain bundle as returned by ui_export_chain (or give input_path)")]
+    pub bundle: Option<serde_json::Value>,
+    
+    #[serde(default)]
+    #[schemars(description = "JSON file under UI_BUNDLE_DIR (default ./bundles) holding a chain bundle")]
+    pub input_path: Option<String>,
+    
+    #[serde(default)]
+    #[schemars(description = "Instance to import into (default: this instance)")]
+    pub instance: Option<String>,
+    
+    #[serde(default)]
+    #[schemars(description = "Chain ID to import as (default: the bundle's chain ID, or a new one when it is taken)")]
+    pub chain_id: Option<String>,
+}
+
+/// Parameters for the ui_feed tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiFeedParams {
+    #[schemars(description = "Follow thoughts carrying this tag")]
+    pub tag: Option<String>,
+    
+    #[schemars(description = "Follow thoughts in this category; with a tag, thoughts must match both")]
+    pub category: Option<String>,
+    
+    #[schemars(description = "'atom' (default) or 'json' for JSON Feed 1.1")]
+    pub format: Option<String>,
+    
+    #[schemars(description = "Most recent thoughts to include (default: 50)")]
+    pub limit: Option<usize>,
+    
+    #[schemars(description = "Directory to write the feed to (default: UI_FEED_DIR or ./feeds)")]
+    pub output_dir: Option<String>,
+    
+    #[schemars(description = "Replace detected PII with [REDACTED:<kind>] (default: true); privacy_level takes precedence")]
+    pub mask_pii: Option<bool>,
+    
+    #[schemars(description = "'pii' (mask detected PII), 'strict' (also withhold thoughts tagged 'private') or 'off'")]
+    pub privacy_level: Option<String>,
+}
+
+/// Parameters for the ui_bulk_update tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiBulkUpdateParams {
+    #[schemars(description = "Only thoughts containing every word of this query in their text, tags, category or chain ID")]
+    pub query: Option<String>,
+    
+    #[schemars(description = "Only thoughts of this chain")]
+    pub chain_id: Option<String>,
+    
+    #[schemars(description = "Only thoughts carrying this tag")]
+    pub tag: Option<String>,
+    
+    #[schemars(description = "Only thoughts from this date (YYYY-MM-DD) or RFC 3339 timestamp on")]
+    pub since: Option<String>,
+    
+    #[schemars(description = "Only thoughts up to and including this date (YYYY-MM-DD), or before this RFC 3339 timestamp")]
+    pub until: Option<String>,
+    
+    #[schemars(description = "Tags to add to every matching thought")]
+    pub add_tags: Option<Vec<String>>,
+    
+    #[schemars(description = "Tags to remove from every matching thought")]
+    pub remove_tags: Option<Vec<String>>,
+    
+    #[schemars(description = "Category to set; an empty string clears it")]
+    pub category: Option<String>,
+    
+    #[schemars(description = "Importance to set (1-10)")]
+    pub importance: Option<i32>,
+    
+    #[schemars(description = "Amount to raise (or, when negative, lower) importance by, kept within 1-10")]
+    pub importance_delta: Option<i32>,
+    
+    #[schemars(description = "Preview the changes without saving them (default: false)")]
+    pub dry_run: Option<bool>,
+    
+    #[schemars(description = "Thoughts saved per batch (default: 100)")]
+    pub batch_size: Option<usize>,
+    
+    #[schemars(description = "Revert the bulk update with this undo ID instead; filters and changes are ignored")]
+    pub undo_id: Option<String>,
+}
+
+/// Parameters for the ui_recall_tuning tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiRecallTuningParams {
+    #[schemars(description = "'status' (default), 'tune' to apply what the feedback so far supports, 'revert' an adjustment or 'reset' to the baseline")]
+    pub action: Option<String>,
+    
+    #[schemars(description = "Adjustment to revert; its previous threshold and limit are restored")]
+    pub version: Option<u64>,
+}
+
+/// Parameters for the ui_next_hint tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiNextHintParams {
+    #[schemars(description = "Chain to suggest next-thought directions for")]
+    pub chain_id: String,
+    
+    #[schemars(description = "Generate new hints even when cached ones are current (default: false)")]
+    pub refresh: Option<bool>,
+}
+
+/// Parameters for the ui_compact_streams tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiCompactStreamsParams {
+    #[schemars(description = "Report what would be trimmed and archived without changing the streams (default: false)")]
+    pub dry_run: Option<bool>,
+}
+
+/// Parameters for the ui_score_importance tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiScoreImportanceParams {
+    #[schemars(description = "Score only this thought instead of the whole instance")]
+    pub thought_id: Option<String>,
+    
+    #[schemars(description = "Report the scores without storing them (default: false)")]
+    pub dry_run: Option<bool>,
+}
+
+/// Parameters for the ui_export_to_vault tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiExportToVaultParams {
+    #[schemars(description = "Chain to write to the vault as a note")]
+    pub chain_id: String,
+    
+    #[schemars(description = "Vault folder of the note (default: OBSIDIAN_EXPORT_FOLDER, else 'Chain Exports')")]
+    pub folder: Option<String>,
+    
+    #[schemars(description = "Replace the note even if it was edited in Obsidian since the last export (default: false)")]
+    pub overwrite: Option<bool>,
+    
+    #[schemars(description = "'off' (default), 'pii' (mask detected PII) or 'strict' (also withhold thoughts tagged 'private')")]
+    pub privacy_level: Option<String>,
+}
+
+/// Parameters for the ui_rate_limit_status tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiRateLimitStatusParams {
+    #[schemars(description = "Only the windows calls of this tool count against (default: every window)")]
+    pub tool: Option<String>,
+}
+
+/// Parameters for the ui_chain_diff tool
+#[derive(Debug, Default, Deserialize, schemars::JsonSchema)]
+pub struct UiChainDiffParams {
+    #[schemars(description = "First chain, e.g. the original")]
+    pub left_chain_id: String,
+    
+    #[schemars(description = "Second chain, e.g. a branch of the first")]
+    pub right_chain_id: String,
+    
+    #[schemars(description = "Share of words two thoughts must have in common to count as shared, 0-1 (default: 0.6)")]
+    pub threshold: Option<f32>,
+}
+
+/// A timed piece of a transcript, as produced by Whisper
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct VoiceSegment {
+    #[schemars(description = "Offset into the recording in seconds")]
+    pub start: f64,
+    
+    #[schemars(description = "End offset in seconds")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<f64>,
+    
+    #[schemars(description = "Transcribed text")]
+    pub text: String,
+    
+    #[schemars(description = "Speaker label from diarization")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+}
+
+/// Parameters for the mind_monitor_status tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct MindMonitorStatusParams {
+    // No fields currently used
+}
+
+/// Parameters for the mind_cognitive_metrics tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct MindCognitiveMetricsParams {
+    // No fields currently used
+}
+
+/// Parameters for the mind_intervention_queue tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct MindInterventionQueueParams {
+    // No fields currently used
+}
+
+/// Parameters for the mind_conversation_insights tool
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct MindConversationInsightsParams {
+    // No fields currently used
+}
+
+/// Parameters for the mind_entity_tracking tool  
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct MindEntityTrackingParams {
+    // No fields currently used
+}
+
+/// Connection pool usage
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolStats {
+    pub max_size: usize,
+    pub size: usize,       // Connections currently open
+    pub available: usize,  // Idle connections ready for use
+    pub waiting: usize,    // Callers queued for a connection
+}
+
+/// Storage backend state for ui_diagnostics
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendDiagnostics {
+    pub backend: String,                     // "redis" or "memory"
+    pub redis_modules: Option<Vec<String>>,  // None when MODULE LIST is unavailable
+    pub pool: Option<PoolStats>,
+}
+
+/// Backend memory figures from INFO memory, in bytes (0 when not reported)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MemoryUsage {
+    pub used_bytes: u64,
+    pub maxmemory_bytes: u64, // 0 when Redis has no maxmemory
+    pub system_bytes: u64,
+}
+
+/// State of a background task
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskState {
+    pub name: String,
+    pub interval_secs: u64,
+    pub runs: u64,
+    pub failures: u64,
+    pub last_run: Option<String>,
+    pub last_error: Option<String>,
+}
+
+/// A tool or background task failure
+#[derive(Debug, Clone, Serialize)]
+pub struct RecentError {
+    pub timestamp: String,
+    pub source: String,
+    pub message: String,
+}
+
+/// A tool call seen by the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallRecord {
+    pub timestamp: String,
+    pub tool: String,
+    pub failed: bool,
+}
+
+/// Panic or fatal error with the context needed to investigate it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    pub timestamp: String,
+    pub kind: String,                                    // "panic" or "fatal"
+    pub message: String,
+    pub location: Option<String>,                        // file:line:column of a panic
+    pub thread: Option<String>,
+    pub backtrace: String,
+    pub server_version: String,
+    pub recent_tool_calls: Vec<ToolCallRecord>,          // Newest first
+    pub config: std::collections::BTreeMap<String, String>,  // Masked environment
+}
+
+/// Response from ui_diagnostics tool
+#[derive(Debug, Serialize)]
+pub struct DiagnosticsResponse {
+    pub generated_at: String,
+    pub server_version: String,
+    pub instance_id: String,
+    pub user_id: Option<String>,
+    pub env: std::collections::BTreeMap<String, String>,  // Secrets masked
+    pub config: serde_json::Value,                       // Effective settings after defaults
+    pub backend: Option<BackendDiagnostics>,
+    pub search_index: Option<SearchIndexStatus>,
+    pub search_cache: SearchCacheStats,
+    pub tasks: Vec<TaskState>,
+    pub recent_errors: Vec<RecentError>,
+    pub recent_tool_calls: Vec<ToolCallRecord>,
+    pub crash_reports: Vec<CrashReport>,
+    pub section_errors: Vec<String>,                     // Sections that could not be collected
+    pub saved_to: Option<String>,
+}
+
+/// Response from ui_purge tool
+#[derive(Debug, Serialize)]
+pub struct PurgeResponse {
+    pub status: String,                          // "dry_run" or "purged"
+    pub scope: String,
+    pub namespace: String,
+    pub keys_found: usize,
+    pub keys_by_category: BTreeMap<String, usize>,
+    pub sample_keys: Vec<String>,
+    pub confirmation_token: Option<String>,      // Only set for dry runs
+    pub token_expires_in_seconds: Option<u64>,
+    pub keys_removed: usize,
+}
+
+/// Response from ui_pii_findings tool
+#[derive(Debug, Serialize)]
+pub struct PiiFindingsResponse {
+    pub policy: PiiPolicy,
+    pub records: Vec<PiiRecord>,
+    pub total_findings: usize,
+}
+
+/// Response from ui_chain_sync tool
+#[derive(Debug, Serialize)]
+pub struct ChainSyncResponse {
+    pub chain_id: String,
+    pub note_path: String,
+    pub note_changed: bool,                 // Human edits detected since the last sync
+    pub imported_thought_ids: Vec<String>,
+    pub exported: bool,
+    pub state: ChainSyncState,
+    pub masked_pii: usize,
+    pub withheld_thoughts: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redaction_manifest: Option<String>,
+}
+
+/// Response from ui_search_index tool
+#[derive(Debug, Serialize)]
+pub struct SearchIndexResponse {
+    pub action: String,
+    pub rebuilt: bool,
+    pub index_exists: bool,
+    pub status: SearchIndexStatus,
+    pub uncovered_instances: Vec<String>,       // Instances with thoughts missing from search
+    pub cache: SearchCacheStats,
+}
+
+/// Size and effectiveness of the in-process search result cache
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct SearchCacheStats {
+    pub entries: u64,
+    pub size_bytes: u64,                        // Approximate size of the cached results
+    pub max_bytes: u64,
+    pub ttl_seconds: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+    pub evictions: u64,                         // Entries evicted to stay under max_bytes
+    pub expirations: u64,
+    pub clears: u64,                            // Full invalidations (writes, purges, keyspace events)
+}
+
+/// Model that produced a stored embedding, read from {instance}:embeddings:{thought_id}
+///
+/// Both fields are None for vectors written before embeddings were versioned.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct EmbeddingVersion {
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub model_version: Option<String>,
+}
+
+/// Usage stats of one MCP client, stored at {instance}:clients:{name}
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ClientStats {
+    pub name: String,
+    pub version: String,
+    pub protocol_version: String,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub sessions: u64,
+    pub total_calls: u64,
+    pub errors: u64,
+    pub tool_counts: BTreeMap<String, u64>,
+}
+
+impl ClientStats {
+    /// Hash field prefix for per-tool call counts
+    pub const TOOL_FIELD_PREFIX: &'static str = "tool:";
+    
+    /// Parse the stats hash
+    pub fn from_hash(fields: &HashMap<String, String>) -> Self {
+        let text = |field: &str| fields.get(field).cloned().unwrap_or_default();
+        let count = |field: &str| fields.get(field).and_then(|v| v.parse().ok()).unwrap_or(0);
+        
+        Self {
+            name: text("name"),
+            version: text("version"),
+            protocol_version: text("protocol_version"),
+            first_seen: text("first_seen"),
+            last_seen: text("last_seen"),
+            sessions: count("sessions"),
+            total_calls: count("total_calls"),
+            errors: count("errors"),
+            tool_counts: fields.iter()
+                .filter_map(|(field, value)| {
+                    let tool = field.strip_prefix(Self::TOOL_FIELD_PREFIX)?;
+                    Some((tool.to_string(), value.parse().ok()?))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Response from ui_clients tool
+#[derive(Debug, Serialize)]
+pub struct ClientsResponse {
+    pub current_client: Option<String>,
+    pub clients: Vec<ClientStats>,
+}
+
+/// Response from ui_braindump tool
+#[derive(Debug, Serialize)]
+pub struct BraindumpResponse {
+    pub chain_id: String,
+    pub segmentation: String,             // Method actually used: "heuristic" or "llm"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub segmentation_fallback: Option<String>, // Why llm segmentation fell back to the heuristic
+    pub total_thoughts: usize,
+    pub thoughts: Vec<BraindumpThought>,
+}
+
+/// One thought created by ui_braindump
+#[derive(Debug, Serialize)]
+pub struct BraindumpThought {
+    pub thought_id: String,
+    pub thought_number: i32,
+    pub thought: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pii_detected: Option<Vec<String>>,
+}
+
+/// Response from ui_capture tool
+#[derive(Debug, Serialize)]
+pub struct CaptureResponse {
+    pub captured: Vec<CapturedThought>,
+    pub duplicates: usize,                // Items captured on an earlier poll
+    pub filtered: usize,                  // Items dropped by the active mode's capture filters
+    pub errors: Vec<String>,              // Sources or items that failed; the rest still ran
+    pub partial: bool,                    // The tool deadline passed before every item was stored; the rest are stored next poll
+}
+
+/// One thought created by ui_capture
+#[derive(Debug, Serialize)]
+pub struct CapturedThought {
+    pub thought_id: String,
+    pub item_id: String,                  // URL, mid:{message-id}, git:{sha} or hl:{url}
+    pub source: String,                   // "rss", "email", "git", "bookmark" or "highlight"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,         // Repository or domain chain the item was appended to
+    pub title: String,
+}
+
+/// Response from ui_import_bookmarks tool
+#[derive(Debug, Serialize)]
+pub struct ImportBookmarksResponse {
+    pub format: String,                   // "bookmarks" or "highlights"
+    pub imported: Vec<CapturedThought>,
+    pub duplicates: usize,                // Items imported or captured before
+    pub errors: Vec<String>,
+    pub partial: bool,                    // The tool deadline passed before every item was stored; import again for the rest
+}
+
+/// Response from ui_weekly_review tool
+#[derive(Debug, Serialize)]
+pub struct WeeklyReviewResponse {
+    pub week: String,                     // ISO week, e.g. "2025-W29"
+    pub week_start: String,
+    pub week_end: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,         // Review chain, when stored
+    pub thought_ids: Vec<String>,
+    pub thoughts_reviewed: usize,
+    pub chains: Vec<ReviewChain>,
+    pub decisions: Vec<ReviewEntry>,
+    pub completed_actions: Vec<ReviewEntry>,
+    pub open_actions: Vec<ReviewEntry>,
+    pub topics: Vec<TopicTrend>,
+    pub interventions_pending: usize,
+    pub intervention_priorities: serde_json::Value,
+    pub reflection_prompts: Vec<String>,
+    pub document: String,                 // Markdown review
+}
+
+/// A chain active during the reviewed week
+#[derive(Debug, Serialize)]
+pub struct ReviewChain {
+    pub chain_id: String,
+    pub thoughts: usize,                  // Thoughts added during the week
+    pub first_thought: String,
+    pub last_activity: String,
+}
+
+/// A decision or action item with the thought it came from
+#[derive(Debug, Serialize)]
+pub struct ReviewEntry {
+    pub text: String,
+    pub thought_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,
+}
+
+/// A topic of the week compared with the week before
+#[derive(Debug, Serialize)]
+pub struct TopicTrend {
+    pub topic: String,
+    pub score: f64,
+    pub trend: String,                    // "new", "continuing" or "faded"
+}
+
+/// Response from ui_list_chains tool
+#[derive(Debug, Serialize)]
+pub struct ListChainsResponse {
+    pub chains: Vec<ChainMetadata>,       // Most recently active first, with related chains
+    pub total: usize,                     // Chains of the instance, across all pages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,      // Pass as cursor for the next page; absent on the last page
+}
+
+/// Response from ui_embedding_staleness tool
+#[derive(Debug, Serialize)]
+pub struct EmbeddingStalenessResponse {
+    pub model: String,                    // Current model and version
+    pub model_version: String,
+    pub scanned: usize,
+    pub up_to_date: usize,
+    pub missing: usize,
+    pub outdated: usize,                  // Older model, older version or unversioned
+    pub queued: usize,                    // Queued for re-embedding by this call
+    pub thoughts: Vec<StaleEmbedding>,    // Most important first, up to the limit
+}
+
+/// A thought whose embedding is missing or outdated
+#[derive(Debug, Serialize)]
+pub struct StaleEmbedding {
+    pub thought_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub importance: Option<i32>,
+    pub reason: String,                   // "missing", "outdated_model", "outdated_version" or "unversioned"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedded_with: Option<EmbeddingVersion>,
+    pub timestamp: String,
+}
+
+/// Response from ui_export_training tool
+#[derive(Debug, Serialize)]
+pub struct ExportTrainingResponse {
+    pub format: String,
+    pub examples: usize,
+    pub chains_considered: usize,
+    pub skipped_unconcluded: usize,
+    pub skipped_low_feedback: usize,
+    pub skipped_single_thought: usize,
+    pub masked_pii: usize,                    // PII findings replaced with [REDACTED:<kind>]
+    pub withheld_thoughts: usize,             // Private thoughts replaced with [REDACTED:private]
+    pub output_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redaction_manifest: Option<String>,   // Written next to output_path
+    pub lines: Vec<serde_json::Value>,        // Empty when written to output_path
+}
+
+/// A thought quoted in a persona bundle
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PersonaThought {
+    pub thought_id: String,
+    pub chain_id: Option<String>,
+    pub timestamp: String,
+    pub text: String,
+}
+
+/// A dominant topic of recent thinking
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PersonaTopic {
+    pub topic: String,
+    pub score: f64,
+}
+
+/// Versioned persona bundle stored at `{instance}:persona_snapshots`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonaBundle {
+    pub version: u64,
+    pub instance_id: String,
+    pub created_at: String,
+    pub content_hash: String,                 // SHA-256 of everything below, unchanged bundles keep their version
+    pub identity: Identity,
+    pub topics: Vec<PersonaTopic>,
+    pub pinned_thoughts: Vec<PersonaThought>,
+    pub summaries: Vec<PersonaThought>,       // Weekly reviews, one per review chain
+}
+
+/// Response from ui_persona_snapshot tool
+#[derive(Debug, Serialize)]
+pub struct PersonaSnapshotResponse {
+    pub stored: bool,                         // False when not storing or nothing changed since the latest version
+    pub unchanged: bool,
+    pub previous_version: Option<u64>,
+    pub bundle: PersonaBundle,
+    pub system_prompt: String,                // The bundle rendered as Markdown
+    pub output_path: Option<String>,
+    pub masked_pii: usize,
+    pub withheld_thoughts: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redaction_manifest: Option<String>,
+}
+
+/// An identity field that changed between persona versions
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PersonaFieldChange {
+    pub field: String,                        // Dotted path, e.g. "communication.tone"
+    pub before: serde_json::Value,            // Null when the field is new
+    pub after: serde_json::Value,             // Null when the field was removed
+}
+
+/// Items added to and removed from a list between persona versions
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PersonaListChange {
+    pub field: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Response from ui_persona_diff tool
+#[derive(Debug, Serialize)]
+pub struct PersonaDiffResponse {
+    pub from_version: u64,
+    pub to_version: u64,
+    pub from_created_at: String,
+    pub to_created_at: String,
+    pub identity_changes: Vec<PersonaFieldChange>,
+    pub pattern_shifts: Vec<PersonaListChange>,   // behavioral_patterns lists
+    pub new_topics: Vec<String>,
+    pub faded_topics: Vec<String>,
+    pub pinned_added: Vec<String>,
+    pub pinned_removed: Vec<String>,
+    pub new_summaries: usize,
+    pub changelog: String,                        // Markdown
+}
+
+/// A review annotation on a thought, stored apart from the thought itself
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Annotation {
+    pub id: String,
+    pub thought_id: String,
+    pub reviewer: String,                     // Reviewing instance, or "human"
+    pub verdict: Option<String>,              // "agree" or "disagree"
+    pub score: Option<i32>,                   // 1 to 10
+    pub comment: Option<String>,
+    pub timestamp: String,
+}
+
+/// Response from ui_annotate tool
+#[derive(Debug, Serialize)]
+pub struct AnnotateResponse {
+    pub annotation: Annotation,
+    pub total_annotations: usize,
+    pub agree: usize,
+    pub disagree: usize,
+    pub average_score: Option<f64>,
+}
+
+/// Response from ui_tier_cold tool
+#[derive(Debug, Serialize, Default)]
+pub struct TierColdResponse {
+    pub dry_run: bool,
+    pub scanned: usize,
+    pub already_cold: usize,
+    pub candidates: usize,
+    pub moved: usize,
+    pub segment: Option<String>,              // Segment written by this run
+    pub bytes_written: u64,
+    pub chars_moved: usize,                   // Thought text no longer held in Redis
+    pub thought_ids: Vec<String>,
+}
+
+/// Response from ui_replay tool
+#[derive(Debug, Serialize, Default)]
+pub struct ReplayResponse {
+    pub chain_id: String,
+    pub chain_length: usize,
+    pub steps: Vec<ReplayStep>,
+    pub next_from_thought: Option<i32>,   // Start of the next chunk, None once the chain is done
+    pub streamed: bool,                   // Steps were also sent as progress notifications
+    pub partial: bool,                    // Streaming stopped at the tool deadline
+}
+
+/// One step of a replayed chain
+#[derive(Debug, Serialize, Clone)]
+pub struct ReplayStep {
+    pub thought_id: String,
+    pub thought_number: i32,
+    pub total_thoughts: i32,
+    pub thought: String,
+    pub timestamp: String,
+    pub gap_ms: Option<i64>,              // Time since the previous thought when it was recorded
+    pub delay_ms: u64,                    // Wait before showing this step
+}
+
+/// Response from ui_subscribe tool
+#[derive(Debug, Serialize)]
+pub struct SubscribeResponse {
+    pub client: String,
+    pub channels: Vec<String>,            // Channels this client now receives
+    pub available: Vec<String>,           // Channels bridged from Redis
+    pub bridge_active: bool,              // Redis subscription is up (false on the memory backend)
+}
+
+/// Response from ui_chain_stats tool
+#[derive(Debug, Serialize)]
+pub struct ChainStatsResponse {
+    pub chains: Vec<ChainStats>,          // Newest first
+    pub completed: usize,
+    pub average_completion: f64,
+}
+
+/// Quality metrics of one chain
+#[derive(Debug, Serialize)]
+pub struct ChainStats {
+    pub chain_id: String,
+    pub status: String,                   // "completed", "open" or "abandoned"
+    pub thought_count: usize,
+    pub planned_total: i32,               // total_thoughts of the latest thought
+    pub initial_plan: i32,                // total_thoughts of the first thought
+    pub completion: f64,                  // Distinct thought numbers / planned_total, 0.0-1.0
+    pub average_chars: f64,
+    pub revisions: usize,                 // Thoughts reusing an earlier thought number
+    pub plan_changes: usize,              // Times total_thoughts changed
+    pub started_at: Option<String>,
+    pub ended_at: Option<String>,
+    pub span_seconds: i64,
+    pub frameworks: BTreeMap<String, usize>,
+    pub confidence: Vec<f64>,             // Per thought in order, from wording
+    pub confidence_trend: String,         // "rising", "falling" or "steady"
+    pub reconciled_total: i32,            // Chain length by the server's count, see planned_total for the client's
+    pub discrepancies: Vec<String>,       // Numbering mismatches in the order the thoughts were stored
+}
+
+/// Response from ui_citations tool
+#[derive(Debug, Serialize)]
+pub struct CitationsResponse {
+    pub note_path: String,                // Normalized path that was looked up
+    pub thoughts: Vec<CitingThought>,     // Newest first
+    pub total: usize,
+}
+
+/// A thought citing a note
+#[derive(Debug, Serialize)]
+pub struct CitingThought {
+    pub thought_id: String,
+    pub chain_id: Option<String>,
+    pub thought_number: i32,
+    pub thought: String,
+    pub timestamp: String,
+    pub citations: Vec<Citation>,         // This thought's citations of the note
+}
+
+/// Response from ui_report tool
+#[derive(Debug, Serialize)]
+pub struct ReportResponse {
+    pub path: String,
+    pub title: String,
+    pub thoughts: usize,
+    pub chains: usize,
+    pub decisions: usize,
+    pub masked_pii: usize,                // PII findings replaced with [REDACTED:<kind>]
+    pub withheld_thoughts: usize,         // Private thoughts replaced with [REDACTED:private]
+    pub bytes: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redaction_manifest: Option<String>,
+}
+
+/// Response from ui_mode tool
+#[derive(Debug, Serialize)]
+pub struct ModeResponse {
+    pub mode: String,                     // Active mode after the action
+    pub profile: ModeProfile,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous: Option<String>,         // Mode before a switch
+    pub available: Vec<String>,           // Built-in and defined modes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notification_channels: Option<Vec<String>>, // This client's channels after a switch
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<ModeProfile>,       // Every profile, for 'list'
+}
+
+/// A mismatch found by ui_verify_chain
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct CustodyIssue {
+    pub thought_id: Option<String>,
+    pub thought_number: Option<i32>,
+    pub kind: String, // altered, broken_link, fork, unsealed or missing
+    pub detail: String,
+}
+
+/// Response from ui_verify_chain tool
+#[derive(Debug, Serialize)]
+pub struct VerifyChainResponse {
+    pub chain_id: String,
+    pub intact: bool,
+    pub thoughts: usize,
+    pub sealed: usize,
+    pub head_hash: Option<String>, // Link hash of the newest sealed thought
+    pub issues: Vec<CustodyIssue>,
+}
+
+/// A migration recorded in `{instance}:migrations`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub name: String,
+    pub applied_at: String,
+    pub applied_by: String, // Server that applied it, as host:pid
+    pub changed: usize,     // Records the migration changed
+    pub duration_ms: u64,
+}
+
+/// A migration not yet applied to an instance
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingMigration {
+    pub version: u32,
+    pub name: String,
+    pub description: String,
+}
+
+/// Response from ui_migrations tool
+#[derive(Debug, Serialize)]
+pub struct MigrationsResponse {
+    pub instance: String,
+    pub applied: Vec<AppliedMigration>,
+    pub pending: Vec<PendingMigration>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub applied_now: Vec<AppliedMigration>, // Applied by this call
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lock_holder: Option<String>, // Server applying migrations right now
+}
+
+/// Response from ui_delete_thought tool
+#[derive(Debug, Serialize)]
+pub struct DeleteThoughtResponse {
+    pub thought_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining_in_chain: Option<usize>, // Thoughts left in its chain
+}
+
+/// Response from ui_delete_chain tool
+#[derive(Debug, Serialize)]
+pub struct DeleteChainResponse {
+    pub chain_id: String,
+    pub thoughts_deleted: usize,
+    pub unlinked_chains: usize, // Chains that listed it as related
+}
+
+/// A chain with its thoughts, thought metadata and boost scores, as written by ui_export_chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainBundle {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    pub source_instance: String,
+    pub chain_id: String,
+    pub chain: Option<ChainMetadata>, // None for chains stored without metadata
+    pub thoughts: Vec<ThoughtRecord>, // Chain order
+    #[serde(default)]
+    pub thought_metadata: Vec<ThoughtMetadata>,
+    #[serde(default)]
+    pub boost_scores: BTreeMap<String, f64>, // Thought ID -> feedback boost
+}
+
+/// Response from ui_export_chain tool
+#[derive(Debug, Serialize)]
+pub struct ExportChainResponse {
+    pub chain_id: String,
+    pub thoughts: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bundle: Option<ChainBundle>, // None when written to output_path
+    pub masked_pii: usize,
+    pub withheld_thoughts: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redaction_manifest: Option<String>,
+}
+
+/// Response from ui_import_chain tool
+#[derive(Debug, Serialize)]
+pub struct ImportChainResponse {
+    pub instance: String,
+    pub chain_id: String,
+    pub source_instance: String,
+    pub source_chain_id: String,
+    pub thoughts: usize,
+    pub thought_metadata: usize,
+    pub boost_scores: usize,
+    pub resealed: bool, // Custody hashes were recomputed for the new IDs
+    pub pii_detected: usize, // Thoughts with PII findings, masked when PII_POLICY=mask
+}
+
+/// Response from ui_feed tool
+#[derive(Debug, Serialize)]
+pub struct FeedResponse {
+    pub path: String,                     // Same path on every run for the same stream and format
+    pub format: String,
+    pub title: String,
+    pub entries: usize,
+    pub masked_pii: usize,
+    pub withheld_thoughts: usize,
+    pub bytes: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redaction_manifest: Option<String>,
+}
+
+/// Metadata of a thought before and after a bulk update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkUndoEntry {
+    pub before: ThoughtMetadata,
+    pub after: ThoughtMetadata,
+}
+
+/// A bulk update recorded in `{instance}:bulk_undo`, so ui_bulk_update can revert it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkUndoRecord {
+    pub undo_id: String,
+    pub created_at: String,
+    pub summary: String,             // The changes, e.g. "+redis -draft category=research"
+    pub entries: Vec<BulkUndoEntry>,
+}
+
+/// How a bulk update changes (or changed) one thought
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkChange {
+    pub thought_id: String,
+    pub snippet: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,    // New category, empty when cleared
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub importance: Option<i32>,     // New importance
+}
+
+/// Response from ui_bulk_update tool
+#[derive(Debug, Serialize)]
+pub struct BulkUpdateResponse {
+    pub dry_run: bool,
+    pub summary: String,
+    pub matched: usize,              // Thoughts matching the filters
+    pub changed: usize,              // Thoughts whose metadata changes
+    pub batches: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub undo_id: Option<String>,     // Pass back as undo_id to revert this update
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reverted: Option<String>,    // Undo ID of the update this call reverted
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<String>,        // Thoughts left alone by a revert because they were edited since
+    pub changes: Vec<BulkChange>,    // The first changes, as a preview
+    pub partial: bool,               // The tool deadline stopped the update between batches; changed counts what was applied
+}
+
+/// Feedback on semantic searches run with the tuned recall defaults, since the last adjustment
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TuningWindow {
+    pub searches: usize,             // Searches that used both tuned defaults
+    pub empty_searches: usize,       // ... that returned nothing
+    pub full_searches: usize,        // ... that filled the limit
+    pub results: usize,              // Results returned across them
+    pub helpful: usize,              // 'helpful' or 'used' feedback on their results
+    pub irrelevant: usize,           // 'irrelevant' feedback on their results
+}
+
+/// A change of the tuned recall defaults and why it was made
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TuningAdjustment {
+    pub version: u64,
+    pub at: String,
+    pub trigger: String,             // "auto", "manual", "revert" or "reset"
+    pub threshold_from: f32,
+    pub threshold_to: f32,
+    pub limit_from: usize,
+    pub limit_to: usize,
+    pub justification: String,
+    pub window: TuningWindow,        // Feedback the adjustment was based on
+}
+
+/// Recall defaults tuned from feedback, stored in `{instance}:recall_tuning`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecallTuning {
+    pub threshold: f32,
+    pub limit: usize,
+    #[serde(default)]
+    pub window: TuningWindow,
+    #[serde(default)]
+    pub recent_searches: Vec<String>,    // Search IDs counted in the window, oldest first
+    #[serde(default)]
+    pub history: Vec<TuningAdjustment>,  // Oldest first
+}
+
+/// Response from ui_recall_tuning tool
+#[derive(Debug, Serialize)]
+pub struct RecallTuningResponse {
+    pub action: String,
+    pub threshold: f32,
+    pub limit: usize,
+    pub baseline_threshold: f32,
+    pub baseline_limit: usize,
+    pub autotune: bool,                  // Adjusted on feedback without a 'tune' call (UI_RECALL_AUTOTUNE)
+    pub window: TuningWindow,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending: Option<String>,         // What 'tune' would change now, and why
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adjustment: Option<TuningAdjustment>,  // Made by this call
+    pub history: Vec<TuningAdjustment>,  // Newest first
+}
+
+/// Response from ui_next_hint tool
+#[derive(Debug, Serialize)]
+pub struct NextHintResponse {
+    pub chain_id: String,
+    pub hints: Vec<String>,
+    pub source: String,                   // "generator" or "open_questions"
+    pub generated_at: String,
+    pub cached: bool,                     // Served from the chain metadata without generating
+    pub open_questions: Vec<String>,      // Questions asked in the chain, newest first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generator_fallback: Option<String>,  // Why the generator's hints weren't used
+}
+
+/// An entry of an event stream, as read for compaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamEntry {
+    pub id: String,                  // Ordered within its stream
+    pub timestamp_ms: Option<i64>,   // When it was added, from the Redis ID or its timestamp field
+    pub fields: serde_json::Value,
+}
+
+/// A stream entry kept aside before its stream was trimmed, because an open session refers to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedStreamEntry {
+    pub stream: String,
+    pub id: String,
+    pub archived_at: String,
+    pub sessions: Vec<String>,       // Open search or chain IDs it refers to
+    pub fields: serde_json::Value,
+}
+
+/// Compaction counts of one stream, kept across runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamCompactionStats {
+    pub runs: u64,
+    pub trimmed: u64,
+    pub archived: u64,
+    pub last_trimmed: usize,
+    pub last_run: Option<String>,
+}
+
+/// Compaction counts per stream name, stored in `{instance}:stream_compaction`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamCompaction {
+    pub streams: std::collections::BTreeMap<String, StreamCompactionStats>,
+}
+
+/// What a compaction run did to one stream
+#[derive(Debug, Serialize)]
+pub struct StreamCompactionReport {
+    pub stream: String,
+    pub length: usize,               // Entries before the run
+    pub max_len: Option<usize>,
+    pub max_age_days: Option<u64>,
+    pub trimmed: usize,              // Would be, on a dry run
+    pub archived: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oldest_kept: Option<String>, // ID of the first entry left
+}
+
+/// Response from ui_compact_streams tool
+#[derive(Debug, Serialize)]
+pub struct CompactStreamsResponse {
+    pub dry_run: bool,
+    pub session_hours: u64,
+    pub streams: Vec<StreamCompactionReport>,
+    pub metrics: StreamCompaction,   // Totals after this run
+}
+
+/// Usage signals an importance score is computed from
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ImportanceSignals {
+    pub recalls: u64,                // Times ui_recall returned the thought
+    pub feedback_boost: f64,
+    pub pinned: bool,                // Tagged 'pinned'
+    pub citations: usize,            // Notes the thought cites
+}
+
+/// A computed importance that changed
+#[derive(Debug, Serialize)]
+pub struct ImportanceChange {
+    pub thought_id: String,
+    pub from: Option<i32>,
+    pub to: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manual: Option<i32>,         // Ranks instead of the computed value
+    pub signals: ImportanceSignals,
+}
+
+/// Response from ui_score_importance tool
+#[derive(Debug, Serialize)]
+pub struct ScoreImportanceResponse {
+    pub dry_run: bool,
+    pub scanned: usize,
+    pub changed: usize,
+    pub changes: Vec<ImportanceChange>,  // The first changes, as a preview
+}
+
+/// Response from ui_export_to_vault tool
+#[derive(Debug, Serialize)]
+pub struct ExportToVaultResponse {
+    pub chain_id: String,
+    pub note_path: String,                  // Relative to the vault
+    pub thought_count: usize,
+    pub cited_notes: Vec<String>,           // Vault notes the chain links to
+    pub replaced: bool,                     // An earlier export was rewritten
+    pub masked_pii: usize,
+    pub withheld_thoughts: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redaction_manifest: Option<String>,
+}
+
+/// One rate limit window of an instance
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitWindow {
+    pub scope: String,                      // "all" for every call of the instance, else a tool
+    pub limit: usize,
+    pub window_secs: u64,
+    pub used: usize,
+    pub remaining: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resets_in_secs: Option<f64>,        // Until the oldest counted call leaves the window
+}
+
+/// Response from ui_rate_limit_status tool
+#[derive(Debug, Serialize)]
+pub struct RateLimitStatusResponse {
+    pub instance: String,
+    pub shared: bool,                       // Windows kept in Redis for every process of the instance
+    pub windows: Vec<RateLimitWindow>,
+}
+
+/// A thought named in a chain diff
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffThought {
+    pub thought_id: String,
+    pub thought_number: i32,
+    pub preview: String,
+}
+
+/// A thought both chains have
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedThought {
+    pub left: DiffThought,
+    pub right: DiffThought,
+    pub similarity: f32,
+    pub identical: bool,                    // Same text apart from case and spacing
+}
+
+/// Where two chains part after a shared thought
+#[derive(Debug, Clone, Serialize)]
+pub struct DivergencePoint {
+    pub after_left: Option<i32>,            // Shared thought's number in each chain; None: from the start
+    pub after_right: Option<i32>,
+    pub left_next: Option<DiffThought>,     // None when the chain ends there
+    pub right_next: Option<DiffThought>,
+}
+
+/// One chain of a ui_chain_diff, with what it led to
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainDiffSide {
+    pub chain_id: String,
+    pub thoughts: usize,
+    pub unique: usize,
+    pub completed: bool,                    // Last thought needs no next thought
+    pub feedback_boost: f64,                // Recall feedback earned by the chain's thoughts
+}
+
+/// Response from ui_chain_diff tool
+#[derive(Debug, Serialize)]
+pub struct ChainDiffResponse {
+    pub left: ChainDiffSide,
+    pub right: ChainDiffSide,
+    pub overlap: f32,                       // Shared thoughts over all distinct thoughts
+    pub shared: Vec<SharedThought>,
+    pub only_left: Vec<DiffThought>,
+    pub only_right: Vec<DiffThought>,
+    pub divergences: Vec<DivergencePoint>,
+}
+
+/// Response from ui_voice_memo tool
+#[derive(Debug, Serialize)]
+pub struct VoiceMemoResponse {
+    pub chain_id: String,
+    pub source: String,                   // "audio", "transcript" or "segments"
+    pub recorded_at: String,
+    pub total_thoughts: usize,
+    pub thoughts: Vec<VoiceMemoThought>,
+}
+
+/// One thought created by ui_voice_memo
+#[derive(Debug, Serialize)]
+pub struct VoiceMemoThought {
+    pub thought_id: String,
+    pub thought_number: i32,
+    pub timestamp: String,                // recorded_at + offset
+    pub offset_seconds: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    pub thought: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pii_detected: Option<Vec<String>>,
+}
+
+/// Response from mind_monitor_status tool
+#[derive(Debug, Serialize)]
+pub struct MindMonitorStatusResponse {
+    pub status: String,                // "active", "starting", "stopped"
+    pub uptime_seconds: u64,
+    pub thoughts_processed: usize,
+    pub interventions_pending: usize,
+    pub current_cognitive_load: f32,
+    pub monitoring_enabled: bool,
+    pub detailed_metrics: Option<serde_json::Value>,
+}
+
+/// Response from mind_cognitive_metrics tool
+#[derive(Debug, Serialize)]
+pub struct MindCognitiveMetricsResponse {
+    pub cognitive_load: f32,
+    pub pattern_recognition_rate: f32,
+    pub learning_velocity: f32,
+    pub focus_level: f32,
+    pub confidence: f32,
+    pub thinking_velocity: f32,
+    pub uncertainty_level: f32,
+    pub cognitive_fatigue: f32,
+    pub context_switches: usize,
+    pub working_memory_usage: f32,
+    pub trends: Option<serde_json::Value>,
+    /// Recent thoughts per thinking type; untyped thoughts are not counted
+    pub thinking_types: BTreeMap<String, usize>,
+}
+
+/// Response from mind_intervention_queue tool
+#[derive(Debug, Serialize)]
+pub struct MindInterventionQueueResponse {
+    pub interventions: Vec<InterventionDetail>,
+    pub total_pending: usize,
+    pub priority_breakdown: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InterventionDetail {
+    pub id: String,
+    pub timestamp: String,
+    pub intervention_type: String,
+    pub priority: String,
+    pub context: String,
+    pub suggested_action: String,
+    pub reason: String,
+    pub confidence: f32,
+}
+
+/// Response from mind_conversation_insights tool
+#[derive(Debug, Serialize)]
+pub struct MindConversationInsightsResponse {
+    pub session_id: String,
+    pub message_count: usize,
+    pub conversation_state: String,
+    pub detected_topics: Vec<String>,
+    pub key_entities: Vec<serde_json::Value>,
+    pub flow_patterns: Vec<String>,
+    pub insights: Vec<String>,
+}
+
+/// Response from mind_entity_tracking tool
+#[derive(Debug, Serialize)]
+pub struct MindEntityTrackingResponse {
+    pub entities: Vec<TrackedEntity>,
+    pub total_detected: usize,
+    pub importance_ranking: Vec<serde_json::Value>,
+    pub enrichment_suggestions: Option<Vec<serde_json::Value>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrackedEntity {
+    pub text: String,
+    pub entity_type: String,
+    pub confidence: f32,
+    pub context: String,
+    pub occurrences: usize,
+    pub importance_score: f32,
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// Response from ui_identity tool
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum IdentityResponse {
+    View {
+        identity: Identity,
+        available_categories: Vec<&'static str>,
+    },
+    PartialView {
+        identity: serde_json::Map<String, serde_json::Value>,
+        available_categories: Vec<&'static str>,
+    },
+    AsOf {
+        as_of: String,
+        identity: serde_json::Map<String, serde_json::Value>,
+        changes_applied: usize,
+        history_starts: Option<String>,
+        complete: bool, // false when as_of is before the changelog starts
+        available_categories: Vec<&'static str>,
+    },
+    FromTemplate {
+        template: String,
+        identity: Identity,
+        replaced_documents: usize,
     },
     Updated {
         operation: String,
@@ -0,0 +1,170 @@
+//! Operating modes for ui_mode.
+//!
+//! A mode bundles the settings that change with what the instance is used
+//! for: ui_recall defaults (filled in only where the caller leaves a
+//! parameter out), the intervention sensitivity kept for intervention
+//! monitors, which captured items ui_capture keeps, and the notification
+//! channels a client is subscribed to when it switches. `default`, `work`,
+//! `personal` and `focus` are built in; ui_mode can define more per
+//! instance, and a defined mode replaces a built-in one of the same name.
+//! The active mode is kept in `{instance}:mode` and every switch is
+//! recorded as a `mode_changed` event in `{instance}:events`.
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::{CaptureFilters, ModeProfile, RecallDefaults, UiRecallParams};
+
+/// Mode of an instance that never switched
+pub const DEFAULT_MODE: &str = "default";
+
+pub const SENSITIVITIES: &[&str] = &["low", "normal", "high"];
+
+pub const CAPTURE_SOURCES: &[&str] = &["rss", "email", "git"];
+
+fn names(names: &[&str]) -> Option<Vec<String>> {
+    Some(names.iter().map(|name| name.to_string()).collect())
+}
+
+/// Built-in modes
+pub fn builtin() -> Vec<ModeProfile> {
+    vec![
+        ModeProfile { name: DEFAULT_MODE.to_string(), ..Default::default() },
+        ModeProfile {
+            name: "work".to_string(),
+            recall: RecallDefaults { limit: Some(20), semantic_search: Some(true), ranking_profile: Some("operational".to_string()), ..Default::default() },
+            intervention_sensitivity: Some("normal".to_string()),
+            capture: CaptureFilters { sources: names(&["email", "git"]), ..Default::default() },
+            notification_channels: names(&["intervention_events", "reminders"]),
+        },
+        ModeProfile {
+            name: "personal".to_string(),
+            recall: RecallDefaults { semantic_search: Some(true), ranking_profile: Some("research".to_string()), ..Default::default() },
+            intervention_sensitivity: Some("low".to_string()),
+            capture: CaptureFilters { sources: names(&["rss"]), ..Default::default() },
+            notification_channels: names(&["reminders", "vault_changes"]),
+        },
+        ModeProfile {
+            name: "focus".to_string(),
+            recall: RecallDefaults { limit: Some(10), min_importance: Some(6), ..Default::default() },
+            intervention_sensitivity: Some("high".to_string()),
+            capture: CaptureFilters { sources: Some(Vec::new()), ..Default::default() },
+            notification_channels: Some(Vec::new()),
+        },
+    ]
+}
+
+/// Mode by name, defined modes before built-in ones
+pub fn resolve(name: &str, defined: &[ModeProfile]) -> Option<ModeProfile> {
+    let name = name.trim().to_lowercase();
+    defined.iter().find(|profile| profile.name == name).cloned()
+        .or_else(|| builtin().into_iter().find(|profile| profile.name == name))
+}
+
+/// Names of every mode, built-in first
+pub fn available(defined: &[ModeProfile]) -> Vec<String> {
+    let mut names: Vec<String> = builtin().into_iter().map(|profile| profile.name).collect();
+    for profile in defined {
+        if !names.contains(&profile.name) {
+            names.push(profile.name.clone());
+        }
+    }
+    names
+}
+
+fn invalid(field: &str, reason: String) -> UnifiedIntelligenceError {
+    UnifiedIntelligenceError::Validation { field: field.to_string(), reason }
+}
+
+/// Check a profile to define, lowercasing its name, sensitivity and sources
+pub fn validate(mut profile: ModeProfile) -> Result<ModeProfile> {
+    profile.name = profile.name.trim().to_lowercase();
+    if profile.name.is_empty() || !profile.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(invalid("profile.name", format!("'{}' must be letters, digits, '-' or '_'", profile.name)));
+    }
+    if let Some(sensitivity) = &mut profile.intervention_sensitivity {
+        *sensitivity = sensitivity.trim().to_lowercase();
+        if !SENSITIVITIES.contains(&sensitivity.as_str()) {
+            return Err(invalid("profile.intervention_sensitivity", format!("Unknown sensitivity '{}'. Use one of: {}", sensitivity, SENSITIVITIES.join(", "))));
+        }
+    }
+    for source in profile.capture.sources.iter_mut().flatten() {
+        *source = source.trim().to_lowercase();
+        if !CAPTURE_SOURCES.contains(&source.as_str()) {
+            return Err(invalid("profile.capture.sources", format!("Unknown source '{}'. Use one of: {}", source, CAPTURE_SOURCES.join(", "))));
+        }
+    }
+    if let Some(threshold) = profile.recall.threshold.filter(|t| !(0.0..=1.0).contains(t)) {
+        return Err(invalid("profile.recall.threshold", format!("Threshold must be between 0.0 and 1.0, got {}", threshold)));
+    }
+    Ok(profile)
+}
+
+/// Fill the recall parameters the caller left out from the mode's defaults
+pub fn apply_recall_defaults(defaults: &RecallDefaults, params: &mut UiRecallParams) {
+    if params.limit.is_none() {
+        params.limit = defaults.limit;
+    }
+    if params.semantic_search.is_none() {
+        params.semantic_search = defaults.semantic_search;
+    }
+    if params.threshold.is_none() {
+        params.threshold = defaults.threshold;
+    }
+    if params.search_all_instances.is_none() {
+        params.search_all_instances = defaults.search_all_instances;
+    }
+    if params.min_importance.is_none() {
+        params.min_importance = defaults.min_importance;
+    }
+    if params.profile.is_none() && params.half_life_days.is_none() {
+        params.profile = defaults.ranking_profile.clone();
+    }
+}
+
+/// Whether a capture source is polled
+pub fn polls(filters: &CaptureFilters, source: &str) -> bool {
+    filters.sources.as_ref().is_none_or(|sources| sources.iter().any(|s| s == source))
+}
+
+/// Whether a captured item's text passes the keyword filters
+pub fn keeps(filters: &CaptureFilters, text: &str) -> bool {
+    let text = text.to_lowercase();
+    let mentions = |keyword: &String| text.contains(&keyword.trim().to_lowercase());
+    (filters.include_keywords.is_empty() || filters.include_keywords.iter().any(mentions))
+        && !filters.exclude_keywords.iter().any(mentions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_and_validate() {
+        assert_eq!(resolve("Focus", &[]).unwrap().recall.limit, Some(10));
+        let defined = validate(ModeProfile {
+            name: " Focus ".to_string(),
+            intervention_sensitivity: Some("LOW".to_string()),
+            ..Default::default()
+        }).unwrap();
+        assert_eq!((defined.name.as_str(), defined.intervention_sensitivity.as_deref()), ("focus", Some("low")));
+        assert_eq!(resolve("focus", std::slice::from_ref(&defined)).unwrap().recall.limit, None);
+        assert_eq!(available(&[defined, ModeProfile { name: "travel".to_string(), ..Default::default() }]), vec!["default", "work", "personal", "focus", "travel"]);
+
+        assert!(validate(ModeProfile { name: "deep work".to_string(), ..Default::default() }).is_err());
+        let bad_source = ModeProfile { name: "x".to_string(), capture: CaptureFilters { sources: names(&["slack"]), ..Default::default() }, ..Default::default() };
+        assert!(validate(bad_source).is_err());
+    }
+
+    #[test]
+    fn test_capture_filters() {
+        let filters = CaptureFilters {
+            sources: names(&["rss"]),
+            include_keywords: vec!["Redis".to_string()],
+            exclude_keywords: vec!["sponsored".to_string()],
+        };
+        assert!(polls(&filters, "rss") && !polls(&filters, "git"));
+        assert!(polls(&CaptureFilters::default(), "git"));
+        assert!(keeps(&filters, "Redis 8 released"));
+        assert!(!keeps(&filters, "Sponsored: Redis hosting"));
+        assert!(!keeps(&filters, "Postgres 17 released"));
+    }
+}
@@ -0,0 +1,170 @@
+//! Continuation hints for ui_next_hint.
+//!
+//! A hint is one direction the next thought of a chain could take, so a
+//! resumed session doesn't start from a blank page. When UI_HINT_GENERATOR
+//! names a command (typically a small script calling a model), it gets the
+//! chain's context as JSON on stdin (topics, the first and latest thoughts,
+//! the open questions and how many hints are wanted) and prints a JSON array
+//! of strings. Without a generator, or when it fails, the chain's open
+//! questions and its last thought become the hints. Hints are cached in the
+//! chain metadata with the number of thoughts they were generated from;
+//! ui_think clears them when it adds a thought, and hints generated from a
+//! different number of thoughts are regenerated.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use serde_json::json;
+
+use crate::chain_linker;
+use crate::models::ThoughtRecord;
+use crate::review;
+
+/// Hints asked of the generator and kept
+pub const MAX_HINTS: usize = 3;
+
+/// Fewest hints a generator must return to be used
+pub const MIN_HINTS: usize = 2;
+
+/// Open questions listed and passed to the generator
+const MAX_QUESTIONS: usize = 5;
+
+/// Latest thoughts passed to the generator
+const RECENT_THOUGHTS: usize = 3;
+
+/// Characters of a thought passed to the generator
+const THOUGHT_CHARS: usize = 400;
+
+/// Characters of a thought quoted in a fallback hint
+const SNIPPET_CHARS: usize = 120;
+
+/// External hint generator command from UI_HINT_GENERATOR
+pub fn generator_command() -> Option<String> {
+    std::env::var("UI_HINT_GENERATOR").ok().filter(|cmd| !cmd.trim().is_empty())
+}
+
+/// Questions asked in the chain, newest first and without repeats
+pub fn open_questions(thoughts: &[ThoughtRecord]) -> Vec<String> {
+    let mut questions: Vec<String> = Vec::new();
+    for thought in thoughts.iter().rev() {
+        for question in questions_in(&thought.thought).into_iter().rev() {
+            if questions.len() < MAX_QUESTIONS && !questions.iter().any(|q| q.eq_ignore_ascii_case(&question)) {
+                questions.push(question);
+            }
+        }
+    }
+    questions
+}
+
+/// Sentences of a text that end in a question mark
+fn questions_in(text: &str) -> Vec<String> {
+    let mut questions = Vec::new();
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        if matches!(c, '.' | '!' | '?' | '\n') {
+            let sentence = text[start..i].trim();
+            if c == '?' && !sentence.is_empty() {
+                questions.push(format!("{}?", sentence.trim_start_matches(['-', '*']).trim_start()));
+            }
+            start = i + c.len_utf8();
+        }
+    }
+    questions
+}
+
+/// What the generator is told about the chain
+pub fn context(chain_id: &str, thoughts: &[ThoughtRecord], questions: &[String]) -> serde_json::Value {
+    let clip = |text: &str| text.chars().take(THOUGHT_CHARS).collect::<String>();
+    let texts: Vec<&str> = thoughts.iter().map(|t| t.thought.as_str()).collect();
+    let topics: Vec<String> = chain_linker::chain_topics(&texts, &[]).into_iter().collect();
+    let recent: Vec<serde_json::Value> = thoughts.iter().rev().take(RECENT_THOUGHTS).rev()
+        .map(|t| json!({ "thought_number": t.thought_number, "thought": clip(&t.thought) }))
+        .collect();
+    json!({
+        "chain_id": chain_id,
+        "thought_count": thoughts.len(),
+        "topics": topics,
+        "first_thought": thoughts.first().map(|t| clip(&t.thought)),
+        "recent_thoughts": recent,
+        "open_questions": questions,
+        "hints_wanted": MAX_HINTS,
+    })
+}
+
+/// Hints from the open questions, topped up with the chain's last thought
+pub fn fallback(thoughts: &[ThoughtRecord], questions: &[String]) -> Vec<String> {
+    let mut hints: Vec<String> = questions.iter().take(MAX_HINTS).map(|q| format!("Answer the open question: {}", q)).collect();
+    if hints.len() < MIN_HINTS {
+        if let Some(last) = thoughts.last() {
+            hints.push(format!("Pick up where thought {} left off: {}", last.thought_number, review::snippet(&last.thought, SNIPPET_CHARS)));
+        }
+    }
+    hints
+}
+
+/// Run the external generator (via `sh -c`) and parse its JSON array of hints
+pub fn generate_with_command(command: &str, context: &serde_json::Value) -> Result<Vec<String>, String> {
+    let mut child = Command::new("sh")
+        .args(["-c", command])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to start hint generator: {}", e))?;
+    child.stdin.take()
+        .ok_or("hint generator stdin unavailable")?
+        .write_all(context.to_string().as_bytes())
+        .map_err(|e| format!("failed to write to hint generator: {}", e))?;
+    let output = child.wait_with_output().map_err(|e| format!("hint generator failed: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("hint generator exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    let hints: Vec<String> = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("hint generator output is not a JSON array of strings: {}", e))?;
+    let mut hints: Vec<String> = hints.into_iter()
+        .map(|hint| hint.trim().to_string())
+        .filter(|hint| !hint.is_empty())
+        .collect();
+    if hints.len() < MIN_HINTS {
+        return Err(format!("hint generator returned {} hints, at least {} are needed", hints.len(), MIN_HINTS));
+    }
+    hints.truncate(MAX_HINTS);
+    Ok(hints)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thought(number: i32, text: &str) -> ThoughtRecord {
+        ThoughtRecord::new("CC".to_string(), text.to_string(), number, 3, Some("c1".to_string()), true)
+    }
+
+    #[test]
+    fn test_open_questions_and_fallback() {
+        let thoughts = vec![
+            thought(1, "Should keys expire? Probably not for chains."),
+            thought(2, "- Does pipelining help here? should keys expire?\nMeasured 2x."),
+            thought(3, "Next: benchmark the bulk path"),
+        ];
+        let questions = open_questions(&thoughts);
+        assert_eq!(questions, vec!["should keys expire?", "Does pipelining help here?"]);
+        assert_eq!(fallback(&thoughts, &questions), vec![
+            "Answer the open question: should keys expire?",
+            "Answer the open question: Does pipelining help here?",
+        ]);
+        assert_eq!(fallback(&thoughts[2..], &[]), vec!["Pick up where thought 3 left off: Next: benchmark the bulk path"]);
+
+        let context = context("c1", &thoughts, &questions);
+        assert_eq!((context["thought_count"].as_u64(), context["recent_thoughts"].as_array().map(Vec::len)), (Some(3), Some(3)));
+    }
+
+    #[test]
+    fn test_generator_command_output() {
+        let hints = generate_with_command(r#"cat > /dev/null; echo '["one", " two ", "", "three", "four"]'"#, &json!({})).unwrap();
+        assert_eq!(hints, vec!["one", "two", "three"]);
+        assert!(generate_with_command(r#"cat > /dev/null; echo '["only"]'"#, &json!({})).is_err());
+        assert!(generate_with_command("echo not-json", &json!({})).is_err());
+    }
+}
@@ -0,0 +1,347 @@
+//! Forward Redis pub/sub messages to MCP clients.
+//!
+//! Other services publish on Redis channels (intervention events, vault
+//! changes, reminders). A dedicated pub/sub connection subscribes to the
+//! channels in UI_NOTIFY_CHANNELS and each message is sent as a
+//! `notifications/message` log notification, logger `redis:{channel}`, to the
+//! clients that subscribed to that channel with ui_subscribe. Only the
+//! configured channels can be subscribed to; clients are keyed by the name
+//! they gave at initialize and are dropped once a notification to them fails.
+//! Low-priority events are held for periodic digests instead (see digest.rs);
+//! the server's own events, like suggested tags and feed captures, are
+//! published on EVENTS_CHANNEL.
+
+use futures_util::StreamExt;
+use rmcp::model::{LoggingLevel, LoggingMessageNotificationParam};
+use rmcp::{Peer, RoleServer};
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::digest::{self, DigestConfig, DigestEvent, Digests, Route};
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::redis::RedisManager;
+
+/// Channel the server publishes its own events on
+pub const EVENTS_CHANNEL: &str = "ui_events";
+
+/// Channels bridged when UI_NOTIFY_CHANNELS is not set
+pub const DEFAULT_CHANNELS: &[&str] = &["intervention_events", "vault_changes", "reminders", EVENTS_CHANNEL];
+
+/// Longest wait between reconnect attempts
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Channels each client subscribed to, with a handle to reach the client
+#[derive(Debug)]
+pub struct Subscriptions<P> {
+    clients: HashMap<String, (P, BTreeSet<String>)>,
+}
+
+impl<P: Clone> Subscriptions<P> {
+    pub fn new() -> Self {
+        Self { clients: HashMap::new() }
+    }
+
+    /// Add channels for a client, refreshing its handle; returns its channels
+    pub fn subscribe(&mut self, client: &str, peer: P, channels: &[String]) -> Vec<String> {
+        let entry = self.clients.entry(client.to_string()).or_insert_with(|| (peer.clone(), BTreeSet::new()));
+        entry.0 = peer;
+        entry.1.extend(channels.iter().cloned());
+        entry.1.iter().cloned().collect()
+    }
+
+    /// Remove channels from a client, forgetting it once none are left; returns its channels
+    pub fn unsubscribe(&mut self, client: &str, channels: &[String]) -> Vec<String> {
+        let Some((_, subscribed)) = self.clients.get_mut(client) else {
+            return Vec::new();
+        };
+        for channel in channels {
+            subscribed.remove(channel);
+        }
+        let remaining: Vec<String> = subscribed.iter().cloned().collect();
+        if remaining.is_empty() {
+            self.clients.remove(client);
+        }
+        remaining
+    }
+
+    pub fn channels(&self, client: &str) -> Vec<String> {
+        self.clients.get(client).map(|(_, channels)| channels.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Clients subscribed to a channel
+    pub fn recipients(&self, channel: &str) -> Vec<(String, P)> {
+        self.clients.iter()
+            .filter(|(_, (_, channels))| channels.contains(channel))
+            .map(|(client, (peer, _))| (client.clone(), peer.clone()))
+            .collect()
+    }
+
+    /// Every client with its channels
+    pub fn clients(&self) -> Vec<(String, P, BTreeSet<String>)> {
+        self.clients.iter()
+            .map(|(client, (peer, channels))| (client.clone(), peer.clone(), channels.clone()))
+            .collect()
+    }
+
+    pub fn remove(&mut self, client: &str) {
+        self.clients.remove(client);
+    }
+}
+
+impl<P: Clone> Default for Subscriptions<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Bridged channels from a comma separated list, the defaults when empty
+pub fn parse_channels(value: &str) -> Vec<String> {
+    let mut channels: Vec<String> = Vec::new();
+    for channel in value.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+        if !channels.iter().any(|c| c == channel) {
+            channels.push(channel.to_string());
+        }
+    }
+    if channels.is_empty() {
+        channels = DEFAULT_CHANNELS.iter().map(|c| c.to_string()).collect();
+    }
+    channels
+}
+
+/// Notification for a message; JSON payloads are forwarded as JSON, anything else as a string
+pub fn notification(channel: &str, payload: &str) -> LoggingMessageNotificationParam {
+    bridged(channel, parse_payload(payload))
+}
+
+fn parse_payload(payload: &str) -> Value {
+    serde_json::from_str::<Value>(payload).unwrap_or_else(|_| Value::String(payload.to_string()))
+}
+
+fn bridged(channel: &str, payload: Value) -> LoggingMessageNotificationParam {
+    LoggingMessageNotificationParam {
+        level: LoggingLevel::Info,
+        logger: Some(format!("redis:{}", channel)),
+        data: json!({ "channel": channel, "payload": payload }),
+    }
+}
+
+/// Subscriptions of connected clients and the Redis listener feeding them
+pub struct NotificationBridge {
+    channels: Vec<String>,
+    subscriptions: Mutex<Subscriptions<Peer<RoleServer>>>,
+    active: AtomicBool,
+    digest: DigestConfig,
+    held: Mutex<Digests>,
+}
+
+impl NotificationBridge {
+    /// Bridge for the channels in UI_NOTIFY_CHANNELS, routed as the UI_NOTIFY_* digest settings say
+    pub fn from_env() -> Self {
+        Self::new(parse_channels(&std::env::var("UI_NOTIFY_CHANNELS").unwrap_or_default()), DigestConfig::from_env())
+    }
+
+    pub fn new(channels: Vec<String>, digest: DigestConfig) -> Self {
+        Self {
+            channels,
+            subscriptions: Mutex::new(Subscriptions::new()),
+            active: AtomicBool::new(false),
+            digest,
+            held: Mutex::new(Digests::default()),
+        }
+    }
+
+    /// Channels clients may subscribe to
+    pub fn channels(&self) -> &[String] {
+        &self.channels
+    }
+
+    /// Whether the Redis subscription is currently up
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// Check requested channels against the bridged ones
+    pub fn resolve(&self, requested: &[String]) -> Result<Vec<String>> {
+        requested.iter().map(|channel| {
+            let channel = channel.trim();
+            if self.channels.iter().any(|c| c == channel) {
+                Ok(channel.to_string())
+            } else {
+                Err(UnifiedIntelligenceError::Validation {
+                    field: "channels".to_string(),
+                    reason: format!("Channel '{}' is not bridged. Available: {}", channel, self.channels.join(", ")),
+                })
+            }
+        }).collect()
+    }
+
+    pub fn subscribe(&self, client: &str, peer: Peer<RoleServer>, channels: &[String]) -> Vec<String> {
+        self.subscriptions.lock().unwrap().subscribe(client, peer, channels)
+    }
+
+    pub fn unsubscribe(&self, client: &str, channels: &[String]) -> Vec<String> {
+        self.subscriptions.lock().unwrap().unsubscribe(client, channels)
+    }
+
+    pub fn subscribed(&self, client: &str) -> Vec<String> {
+        self.subscriptions.lock().unwrap().channels(client)
+    }
+
+    /// Events held for the next digest
+    pub fn held(&self) -> usize {
+        self.held.lock().unwrap().len()
+    }
+
+    /// Send a message to every client subscribed to its channel, or hold it for a digest
+    fn dispatch(&self, channel: &str, payload: &str) -> Vec<(String, Peer<RoleServer>, LoggingMessageNotificationParam)> {
+        let payload = parse_payload(payload);
+        let event_type = digest::event_type(channel, &payload);
+        match self.digest.route(channel, &event_type, &payload) {
+            Route::Immediate => {
+                let notification = bridged(channel, payload);
+                self.subscriptions.lock().unwrap().recipients(channel).into_iter()
+                    .map(|(client, peer)| (client, peer, notification.clone()))
+                    .collect()
+            }
+            Route::Digest => {
+                let instance = digest::instance(&payload).to_string();
+                self.held.lock().unwrap().hold(&instance, DigestEvent {
+                    channel: channel.to_string(),
+                    event_type,
+                    received_at: chrono::Utc::now(),
+                    payload,
+                });
+                Vec::new()
+            }
+            Route::Off => Vec::new(),
+        }
+    }
+
+    /// Send one digest per instance with held events to each client subscribed to their channels
+    async fn flush_digests(&self) {
+        let held = self.held.lock().unwrap().take();
+        if held.is_empty() {
+            return;
+        }
+        let clients = self.subscriptions.lock().unwrap().clients();
+        let mut sends = Vec::new();
+        for (instance, events) in &held {
+            for (client, peer, channels) in &clients {
+                if let Some(notification) = digest::notification(instance, events, channels) {
+                    sends.push((client.clone(), peer.clone(), notification));
+                }
+            }
+        }
+        self.send(sends).await;
+    }
+
+    async fn send(&self, sends: Vec<(String, Peer<RoleServer>, LoggingMessageNotificationParam)>) {
+        for (client, peer, notification) in sends {
+            if let Err(e) = peer.notify_logging_message(notification).await {
+                tracing::info!("Dropping notification subscriptions of {}: {}", client, e);
+                self.subscriptions.lock().unwrap().remove(&client);
+            }
+        }
+    }
+
+    /// Subscribe and forward messages until the connection drops
+    async fn listen(&self, redis: &RedisManager) -> Result<()> {
+        let mut pubsub = redis.pubsub().await?;
+        for channel in &self.channels {
+            pubsub.subscribe(channel).await?;
+        }
+        self.active.store(true, Ordering::SeqCst);
+        tracing::info!("Bridging Redis channels to MCP notifications: {}", self.channels.join(", "));
+
+        let mut messages = pubsub.on_message();
+        while let Some(message) = messages.next().await {
+            let payload: String = message.get_payload().unwrap_or_default();
+            let sends = self.dispatch(message.get_channel_name(), &payload);
+            self.send(sends).await;
+        }
+        Ok(())
+    }
+
+    /// Spawn the listener, reconnecting with backoff, and the digest schedule
+    pub fn start(self: &Arc<Self>, redis: Arc<RedisManager>) {
+        if let Some(interval) = self.digest.interval {
+            let bridge = self.clone();
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(interval);
+                ticker.tick().await;
+                loop {
+                    ticker.tick().await;
+                    bridge.flush_digests().await;
+                }
+            });
+        }
+        let bridge = self.clone();
+        tokio::spawn(async move {
+            let mut backoff = Duration::from_secs(1);
+            loop {
+                match bridge.listen(&redis).await {
+                    Ok(()) => tracing::warn!("Notification bridge stream closed, reconnecting"),
+                    Err(e) => tracing::warn!("Notification bridge failed: {}", e),
+                }
+                bridge.active.store(false, Ordering::SeqCst);
+
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscriptions() {
+        let mut subscriptions = Subscriptions::new();
+        let channels = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(subscriptions.subscribe("claude-code", 1, &channels(&["reminders"])), channels(&["reminders"]));
+        subscriptions.subscribe("claude-code", 2, &channels(&["vault_changes"]));
+        subscriptions.subscribe("cursor", 3, &channels(&["reminders"]));
+
+        let mut recipients = subscriptions.recipients("reminders");
+        recipients.sort();
+        assert_eq!(recipients, vec![("claude-code".to_string(), 2), ("cursor".to_string(), 3)]);
+        assert!(subscriptions.recipients("intervention_events").is_empty());
+
+        assert_eq!(subscriptions.unsubscribe("claude-code", &channels(&["reminders"])), channels(&["vault_changes"]));
+        assert!(subscriptions.unsubscribe("cursor", &channels(&["reminders"])).is_empty());
+        assert!(subscriptions.channels("cursor").is_empty());
+    }
+
+    #[test]
+    fn test_channels_and_notification() {
+        assert_eq!(parse_channels(""), DEFAULT_CHANNELS.iter().map(|c| c.to_string()).collect::<Vec<_>>());
+        assert_eq!(parse_channels(" reminders, deploys,reminders "), vec!["reminders", "deploys"]);
+
+        let bridge = NotificationBridge::new(parse_channels("reminders"), DigestConfig::default());
+        assert_eq!(bridge.resolve(&[" reminders".to_string()]).unwrap(), vec!["reminders"]);
+        assert!(bridge.resolve(&["__keyspace@0__:*".to_string()]).is_err());
+        assert!(!bridge.is_active());
+
+        let json = notification("reminders", r#"{"due":"09:00"}"#);
+        assert_eq!(json.logger.as_deref(), Some("redis:reminders"));
+        assert_eq!(json.data["payload"]["due"], "09:00");
+        assert_eq!(notification("reminders", "stand-up").data["payload"], "stand-up");
+    }
+
+    #[test]
+    fn test_dispatch_holds_digest_events() {
+        let bridge = NotificationBridge::new(parse_channels(""), DigestConfig::parse("reminders=off", "", None));
+        assert!(bridge.dispatch(EVENTS_CHANNEL, r#"{"type":"tags_suggested","instance":"CC"}"#).is_empty());
+        assert!(bridge.dispatch("intervention_events", r#"{"type":"memory_pressure","priority":"medium"}"#).is_empty());
+        assert_eq!(bridge.held(), 2);
+        // Urgent events skip the digest; off events are dropped
+        bridge.dispatch("intervention_events", r#"{"type":"memory_pressure","priority":"high"}"#);
+        bridge.dispatch("reminders", "stand-up");
+        assert_eq!(bridge.held(), 2);
+    }
+}
@@ -0,0 +1,374 @@
+//! Persona bundles for ui_persona_snapshot.
+//!
+//! A bundle gathers what makes an instance itself - identity, dominant
+//! topics, thoughts tagged `pinned` and recent weekly review summaries - into
+//! one artifact that renders as a Markdown system prompt for a new model or
+//! instance. Bundles are versioned at `{instance}:persona_snapshots`; a
+//! snapshot whose content hash matches the latest version is not stored again.
+//! Two versions can be compared into a Markdown changelog for ui_persona_diff.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::keywords::{self, Language};
+use crate::models::{Identity, PersonaBundle, PersonaDiffResponse, PersonaFieldChange, PersonaListChange, PersonaThought, PersonaTopic};
+use crate::review;
+
+/// Tag that pins a thought into the persona
+pub const PINNED_TAG: &str = "pinned";
+
+/// Topics included when no limit is given
+pub const DEFAULT_TOPIC_LIMIT: usize = 10;
+
+/// Pinned thoughts included when no limit is given
+pub const DEFAULT_PINNED_LIMIT: usize = 20;
+
+/// Weekly review summaries included when no limit is given
+pub const DEFAULT_SUMMARY_LIMIT: usize = 4;
+
+/// Newest thoughts the dominant topics are drawn from
+pub const TOPIC_SOURCE_THOUGHTS: usize = 500;
+
+/// Longest summary kept, in characters
+pub const SUMMARY_MAX_CHARS: usize = 2000;
+
+/// Dominant topics of a corpus of thoughts
+pub fn dominant_topics(corpus: &str, limit: usize) -> Vec<PersonaTopic> {
+    keywords::extract_keywords(corpus, Language::for_text(corpus), limit).into_iter()
+        .map(|keyword| PersonaTopic { topic: keyword.phrase, score: keyword.score })
+        .collect()
+}
+
+/// Text cut to `max_chars`, marked with an ellipsis when shortened
+pub fn truncate(text: &str, max_chars: usize) -> String {
+    let text = text.trim();
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// Hash of a bundle's content; identity metadata (timestamps, counters) is left out
+pub fn content_hash(identity: &Identity, topics: &[PersonaTopic], pinned: &[PersonaThought], summaries: &[PersonaThought]) -> String {
+    let mut identity = serde_json::to_value(identity).unwrap_or(Value::Null);
+    if let Some(fields) = identity.as_object_mut() {
+        fields.remove("metadata");
+    }
+    let content = serde_json::json!([identity, topics, pinned, summaries]);
+    let mut hasher = Sha256::new();
+    hasher.update(content.to_string().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn list(values: &[String]) -> String {
+    if values.is_empty() { "none recorded".to_string() } else { values.join(", ") }
+}
+
+/// The bundle as a Markdown system prompt
+pub fn render_markdown(bundle: &PersonaBundle) -> String {
+    let identity = &bundle.identity;
+    let core = &identity.core_info;
+    let communication = &identity.communication;
+    let work = &identity.work_preferences;
+    let patterns = &identity.behavioral_patterns;
+    let technical = &identity.technical_profile;
+    let context = &identity.context_awareness;
+
+    let mut sections = vec![
+        format!(
+            "# Persona: {} ({})\n\nPersona bundle v{} for instance {}, generated {}.\n\nYou are {}, a {} whose primary purpose is {}. Your core values: {}.",
+            core.name, core.instance_type, bundle.version, bundle.instance_id, bundle.created_at,
+            core.name, core.instance_type, core.primary_purpose, list(&core.core_values),
+        ),
+        format!(
+            "## Communication\n- Tone: {}\n- Verbosity: {}\n- Formality: {}\n- Humor: {:.1}, directness: {:.1}",
+            communication.tone, communication.verbosity, communication.formality,
+            communication.humor_level, communication.directness,
+        ),
+        format!(
+            "## Working style\n- Planning: {}\n- Pace: {}\n- Autonomy: {}\n- Error handling: {}\n- Documentation: {}",
+            work.planning_style, work.pace, work.autonomy_level, work.error_handling, work.documentation_style,
+        ),
+        format!(
+            "## Patterns\n- Strengths: {}\n- Common mistakes: {}\n- Triggers: {}\n- Improving: {}",
+            list(&patterns.strengths), list(&patterns.common_mistakes), list(&patterns.triggers), list(&patterns.improvement_areas),
+        ),
+        format!(
+            "## Technical profile\n- Languages: {}\n- Frameworks: {}\n- Tools: {}\n- Expertise: {}\n- Learning: {}",
+            list(&technical.preferred_languages), list(&technical.frameworks), list(&technical.tools),
+            list(&technical.expertise_areas), list(&technical.learning_interests),
+        ),
+        format!(
+            "## Context\n- Project: {}\n- Environment: {}\n- Role: {}\n- Goals: {}",
+            context.current_project, context.environment, context.instance_role, list(&context.active_goals),
+        ),
+    ];
+
+    if !identity.relationships.is_empty() {
+        let mut names: Vec<&String> = identity.relationships.keys().collect();
+        names.sort();
+        let lines: Vec<String> = names.into_iter().map(|name| {
+            let relationship = &identity.relationships[name];
+            let mut line = format!("- {}: {} ({}, trust {:.1})", name, relationship.interaction_style, relationship.current_standing, relationship.trust_level);
+            if !relationship.boundaries.is_empty() {
+                line.push_str(&format!("; boundaries: {}", relationship.boundaries.join("; ")));
+            }
+            line
+        }).collect();
+        sections.push(format!("## Relationships\n{}", lines.join("\n")));
+    }
+    if !bundle.topics.is_empty() {
+        let topics: Vec<String> = bundle.topics.iter().map(|topic| topic.topic.clone()).collect();
+        sections.push(format!("## Dominant topics\n{}", list(&topics)));
+    }
+    if !bundle.pinned_thoughts.is_empty() {
+        let lines: Vec<String> = bundle.pinned_thoughts.iter().map(|thought| format!("- {}", thought.text.trim())).collect();
+        sections.push(format!("## Pinned thoughts\n{}", lines.join("\n")));
+    }
+    if !bundle.summaries.is_empty() {
+        let summaries: Vec<String> = bundle.summaries.iter().map(|summary| summary.text.trim().to_string()).collect();
+        sections.push(format!("## Recent summaries\n\n{}", summaries.join("\n\n---\n\n")));
+    }
+    sections.join("\n\n")
+}
+
+/// Identity category compared as list shifts rather than field changes
+const PATTERN_CATEGORY: &str = "behavioral_patterns";
+
+/// Leaf values of an identity by dotted path, without metadata and patterns
+fn identity_fields(identity: &Identity) -> BTreeMap<String, Value> {
+    fn flatten(value: &Value, path: String, fields: &mut BTreeMap<String, Value>) {
+        match value.as_object() {
+            Some(object) => {
+                for (key, child) in object {
+                    let child_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                    flatten(child, child_path, fields);
+                }
+            }
+            None => {
+                fields.insert(path, value.clone());
+            }
+        }
+    }
+
+    let mut json = serde_json::to_value(identity).unwrap_or(Value::Null);
+    if let Some(categories) = json.as_object_mut() {
+        categories.remove("metadata");
+        categories.remove(PATTERN_CATEGORY);
+    }
+    let mut fields = BTreeMap::new();
+    flatten(&json, String::new(), &mut fields);
+    fields
+}
+
+/// Items of `after` missing from `before`
+fn added(before: &[String], after: &[String]) -> Vec<String> {
+    after.iter().filter(|item| !before.contains(item)).cloned().collect()
+}
+
+fn strings(value: &Value) -> Option<Vec<String>> {
+    value.as_array()?.iter().map(|item| item.as_str().map(str::to_string)).collect()
+}
+
+fn show(value: &Value) -> String {
+    match value {
+        Value::Null => "(none)".to_string(),
+        Value::String(text) => format!("\"{}\"", text),
+        Value::Number(number) => match number.as_f64() {
+            Some(float) if number.is_f64() => format!("{:.2}", float),
+            _ => number.to_string(),
+        },
+        other => other.to_string(),
+    }
+}
+
+/// One changelog line for an identity field
+fn describe(change: &PersonaFieldChange) -> String {
+    match (strings(&change.before), strings(&change.after)) {
+        (Some(before), Some(after)) => {
+            let mut parts: Vec<String> = added(&before, &after).iter().map(|item| format!("+{}", item)).collect();
+            parts.extend(added(&after, &before).iter().map(|item| format!("-{}", item)));
+            format!("- {}: {}", change.field, parts.join(", "))
+        }
+        _ => format!("- {}: {} -> {}", change.field, show(&change.before), show(&change.after)),
+    }
+}
+
+/// Changes from one persona version to a later one
+pub fn diff(from: &PersonaBundle, to: &PersonaBundle) -> PersonaDiffResponse {
+    let before = identity_fields(&from.identity);
+    let after = identity_fields(&to.identity);
+    let mut paths: Vec<&String> = before.keys().chain(after.keys()).collect();
+    paths.sort();
+    paths.dedup();
+    let identity_changes: Vec<PersonaFieldChange> = paths.into_iter()
+        .filter(|path| before.get(*path) != after.get(*path))
+        .map(|path| PersonaFieldChange {
+            field: path.clone(),
+            before: before.get(path).cloned().unwrap_or(Value::Null),
+            after: after.get(path).cloned().unwrap_or(Value::Null),
+        })
+        .collect();
+
+    let (old, new) = (&from.identity.behavioral_patterns, &to.identity.behavioral_patterns);
+    let pattern_shifts: Vec<PersonaListChange> = [
+        ("strengths", &old.strengths, &new.strengths),
+        ("common_mistakes", &old.common_mistakes, &new.common_mistakes),
+        ("triggers", &old.triggers, &new.triggers),
+        ("improvement_areas", &old.improvement_areas, &new.improvement_areas),
+    ].into_iter()
+        .map(|(field, old, new)| PersonaListChange { field: field.to_string(), added: added(old, new), removed: added(new, old) })
+        .filter(|change| !change.added.is_empty() || !change.removed.is_empty())
+        .collect();
+
+    let topics = |bundle: &PersonaBundle| -> Vec<String> { bundle.topics.iter().map(|topic| topic.topic.clone()).collect() };
+    let (old_topics, new_topics) = (topics(from), topics(to));
+    let pinned = |bundle: &PersonaBundle, other: &PersonaBundle| -> Vec<String> {
+        bundle.pinned_thoughts.iter()
+            .filter(|thought| !other.pinned_thoughts.iter().any(|o| o.thought_id == thought.thought_id))
+            .map(|thought| review::snippet(&thought.text, 120))
+            .collect()
+    };
+    let new_summaries = to.summaries.iter()
+        .filter(|summary| !from.summaries.iter().any(|old| old.thought_id == summary.thought_id))
+        .count();
+
+    let mut response = PersonaDiffResponse {
+        from_version: from.version,
+        to_version: to.version,
+        from_created_at: from.created_at.clone(),
+        to_created_at: to.created_at.clone(),
+        identity_changes,
+        pattern_shifts,
+        new_topics: added(&old_topics, &new_topics),
+        faded_topics: added(&new_topics, &old_topics),
+        pinned_added: pinned(to, from),
+        pinned_removed: pinned(from, to),
+        new_summaries,
+        changelog: String::new(),
+    };
+    response.changelog = render_changelog(&response);
+    response
+}
+
+/// A diff as a Markdown changelog
+pub fn render_changelog(diff: &PersonaDiffResponse) -> String {
+    let mut sections = vec![format!(
+        "# Persona changes v{} -> v{}\n\n{} to {}",
+        diff.from_version, diff.to_version, diff.from_created_at, diff.to_created_at,
+    )];
+    if !diff.identity_changes.is_empty() {
+        let lines: Vec<String> = diff.identity_changes.iter().map(describe).collect();
+        sections.push(format!("## Identity\n{}", lines.join("\n")));
+    }
+    if !diff.pattern_shifts.is_empty() {
+        let lines: Vec<String> = diff.pattern_shifts.iter().map(|shift| {
+            let mut parts: Vec<String> = shift.added.iter().map(|item| format!("+{}", item)).collect();
+            parts.extend(shift.removed.iter().map(|item| format!("-{}", item)));
+            format!("- {}: {}", shift.field, parts.join(", "))
+        }).collect();
+        sections.push(format!("## Pattern shifts\n{}", lines.join("\n")));
+    }
+    if !diff.new_topics.is_empty() || !diff.faded_topics.is_empty() {
+        sections.push(format!("## Topics\n- New: {}\n- Faded: {}", list(&diff.new_topics), list(&diff.faded_topics)));
+    }
+    if !diff.pinned_added.is_empty() || !diff.pinned_removed.is_empty() {
+        let mut lines: Vec<String> = diff.pinned_added.iter().map(|text| format!("- Pinned: {}", text)).collect();
+        lines.extend(diff.pinned_removed.iter().map(|text| format!("- Unpinned: {}", text)));
+        sections.push(format!("## Pinned thoughts\n{}", lines.join("\n")));
+    }
+    if diff.new_summaries > 0 {
+        sections.push(format!("## Summaries\n{} new weekly review(s)", diff.new_summaries));
+    }
+    if sections.len() == 1 {
+        sections.push("No changes.".to_string());
+    }
+    sections.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pinned(text: &str) -> PersonaThought {
+        PersonaThought { thought_id: "t1".to_string(), chain_id: None, timestamp: "2025-07-18T00:00:00Z".to_string(), text: text.to_string() }
+    }
+
+    #[test]
+    fn test_content_hash_ignores_identity_metadata() {
+        let identity = Identity::default_for_instance("CC");
+        let mut touched = identity.clone();
+        touched.metadata.update_count += 3;
+        let pins = vec![pinned("Always run clippy")];
+        assert_eq!(content_hash(&identity, &[], &pins, &[]), content_hash(&touched, &[], &pins, &[]));
+
+        touched.communication.tone = "calm".to_string();
+        assert_ne!(content_hash(&identity, &[], &pins, &[]), content_hash(&touched, &[], &pins, &[]));
+        assert_ne!(content_hash(&identity, &[], &pins, &[]), content_hash(&identity, &[], &[], &[]));
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let identity = Identity::default_for_instance("CC");
+        let bundle = PersonaBundle {
+            version: 3,
+            instance_id: "CC".to_string(),
+            created_at: "2025-07-18T00:00:00Z".to_string(),
+            content_hash: String::new(),
+            identity,
+            topics: vec![PersonaTopic { topic: "redis cache".to_string(), score: 4.0 }],
+            pinned_thoughts: vec![pinned("Always run clippy")],
+            summaries: Vec::new(),
+        };
+        let markdown = render_markdown(&bundle);
+        assert!(markdown.starts_with("# Persona: Claude (Claude Code)\n\nPersona bundle v3 for instance CC"));
+        assert!(markdown.contains("## Patterns\n- Strengths: fast execution, creative solutions"));
+        assert!(markdown.contains("## Dominant topics\nredis cache"));
+        assert!(markdown.contains("## Pinned thoughts\n- Always run clippy"));
+        assert!(!markdown.contains("## Recent summaries"));
+        assert!(!markdown.contains("## Relationships"));
+        assert_eq!(truncate("abcdef", 4), "abc…");
+    }
+
+    #[test]
+    fn test_diff_changelog() {
+        let bundle = |version: u64, identity: Identity, topics: &[&str], pins: Vec<PersonaThought>| PersonaBundle {
+            version,
+            instance_id: "CC".to_string(),
+            created_at: format!("2025-0{}-01T00:00:00Z", version),
+            content_hash: String::new(),
+            identity,
+            topics: topics.iter().map(|topic| PersonaTopic { topic: topic.to_string(), score: 1.0 }).collect(),
+            pinned_thoughts: pins,
+            summaries: Vec::new(),
+        };
+        let before = Identity::default_for_instance("CC");
+        let mut after = before.clone();
+        after.communication.tone = "calm".to_string();
+        after.communication.humor_level = 0.5;
+        after.core_info.core_values.push("patience".to_string());
+        after.behavioral_patterns.strengths.push("planning".to_string());
+        after.behavioral_patterns.improvement_areas.retain(|area| area != "planning");
+        after.metadata.update_count = 9;
+
+        let diff = diff(
+            &bundle(1, before.clone(), &["redis cache", "deploys"], vec![pinned("Always run clippy")]),
+            &bundle(2, after, &["redis cache", "vector search"], Vec::new()),
+        );
+        let fields: Vec<&str> = diff.identity_changes.iter().map(|change| change.field.as_str()).collect();
+        assert_eq!(fields, vec!["communication.humor_level", "communication.tone", "core_info.core_values"]);
+        assert_eq!(diff.pattern_shifts.len(), 2);
+        assert_eq!((diff.new_topics.clone(), diff.faded_topics.clone()), (vec!["vector search".to_string()], vec!["deploys".to_string()]));
+        assert_eq!(diff.pinned_removed, vec!["Always run clippy"]);
+        assert!(diff.changelog.contains("- communication.tone: \"sarcastic\" -> \"calm\""));
+        assert!(diff.changelog.contains("- core_info.core_values: +patience"));
+        assert!(diff.changelog.contains("- strengths: +planning"));
+        assert!(diff.changelog.contains("- improvement_areas: -planning"));
+
+        let same = bundle(1, before, &[], Vec::new());
+        assert!(super::diff(&same, &same).changelog.ends_with("No changes."));
+    }
+}
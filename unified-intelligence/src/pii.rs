@@ -0,0 +1,281 @@
+//! PII detection and masking for thought content on ingest.
+//!
+//! Policy is read from PII_POLICY:
+//! - `off`  - no scanning
+//! - `flag` - (default) store content unchanged, record findings
+//! - `mask` - replace detected PII with `[REDACTED:<kind>]`, record findings and
+//!   keep the original content AES-256-GCM encrypted with PII_ENCRYPTION_KEY
+//!   (base64, 32 bytes). The key is required: without it masking would discard
+//!   the original, so startup fails instead.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+use crate::error::{Result, UnifiedIntelligenceError};
+
+/// Minimum Shannon entropy (bits per char) for an unprefixed token to count as a secret
+const SECRET_ENTROPY_THRESHOLD: f64 = 4.0;
+
+static EMAIL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap()
+});
+
+static PHONE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:\+\d{1,3}[\s.-]?)?\(?\b\d{3}\)?[\s.-]?\d{3}[\s.-]?\d{4}\b").unwrap()
+});
+
+static CARD_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b\d(?:[ -]?\d){12,18}\b").unwrap()
+});
+
+static KNOWN_KEY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:sk-[A-Za-z0-9_-]{20,}|AKIA[0-9A-Z]{16}|gh[pousr]_[A-Za-z0-9]{36,}|xox[abprs]-[A-Za-z0-9-]{10,}|gsk_[A-Za-z0-9]{20,})").unwrap()
+});
+
+static TOKEN_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b[A-Za-z0-9_\-+/=]{32,}\b").unwrap()
+});
+
+/// How detected PII is handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PiiPolicy {
+    Off,
+    Flag,
+    Mask,
+}
+
+impl PiiPolicy {
+    pub fn from_env() -> Self {
+        match std::env::var("PII_POLICY").unwrap_or_default().to_lowercase().as_str() {
+            "off" => PiiPolicy::Off,
+            "mask" => PiiPolicy::Mask,
+            _ => PiiPolicy::Flag,
+        }
+    }
+}
+
+/// A single PII match within a piece of content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PiiFinding {
+    pub kind: String,     // "email", "phone", "credit_card", "api_key"
+    pub start: usize,     // Byte offset in the original content
+    pub end: usize,
+    pub preview: String,  // Partially masked value, safe to display
+}
+
+/// Scanner applying the configured PII policy
+pub struct PiiScanner {
+    policy: PiiPolicy,
+    cipher: Option<Aes256Gcm>,
+}
+
+impl PiiScanner {
+    pub fn new(policy: PiiPolicy, key: Option<&[u8]>) -> Self {
+        let cipher = key
+            .filter(|k| k.len() == 32)
+            .map(|k| Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(k)));
+        Self { policy, cipher }
+    }
+
+    /// Build a scanner from PII_POLICY and PII_ENCRYPTION_KEY
+    pub fn from_env() -> Result<Self> {
+        let policy = PiiPolicy::from_env();
+        let key = std::env::var("PII_ENCRYPTION_KEY")
+            .ok()
+            .and_then(|k| BASE64.decode(k.trim()).ok());
+        Self::with_key(policy, key.as_deref())
+    }
+
+    /// Scanner for a policy, refusing to mask without a key to keep the originals
+    pub fn with_key(policy: PiiPolicy, key: Option<&[u8]>) -> Result<Self> {
+        if policy == PiiPolicy::Mask && key.is_none_or(|k| k.len() != 32) {
+            return Err(UnifiedIntelligenceError::Configuration(
+                "PII_POLICY=mask needs PII_ENCRYPTION_KEY set to a base64 32-byte key, or masked originals would be lost".to_string(),
+            ));
+        }
+        Ok(Self::new(policy, key))
+    }
+
+    pub fn policy(&self) -> PiiPolicy {
+        self.policy
+    }
+
+    /// Detect PII in content. Findings are sorted and never overlap.
+    pub fn scan(&self, content: &str) -> Vec<PiiFinding> {
+        if self.policy == PiiPolicy::Off {
+            return Vec::new();
+        }
+
+        let mut candidates: Vec<(&str, usize, usize)> = Vec::new();
+
+        // Most specific detectors first so they win overlaps
+        for m in KNOWN_KEY_RE.find_iter(content) {
+            candidates.push(("api_key", m.start(), m.end()));
+        }
+        for m in TOKEN_RE.find_iter(content) {
+            let token = m.as_str();
+            let has_digit = token.chars().any(|c| c.is_ascii_digit());
+            let has_alpha = token.chars().any(|c| c.is_ascii_alphabetic());
+            // UUIDs and content hashes are identifiers, not secrets
+            let is_hex_id = token.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+            if has_digit && has_alpha && !is_hex_id && shannon_entropy(token) >= SECRET_ENTROPY_THRESHOLD {
+                candidates.push(("api_key", m.start(), m.end()));
+            }
+        }
+        for m in CARD_RE.find_iter(content) {
+            if luhn_valid(m.as_str()) {
+                candidates.push(("credit_card", m.start(), m.end()));
+            }
+        }
+        for m in EMAIL_RE.find_iter(content) {
+            candidates.push(("email", m.start(), m.end()));
+        }
+        for m in PHONE_RE.find_iter(content) {
+            candidates.push(("phone", m.start(), m.end()));
+        }
+
+        let mut findings: Vec<PiiFinding> = Vec::new();
+        for (kind, start, end) in candidates {
+            if findings.iter().any(|f| start < f.end && end > f.start) {
+                continue;
+            }
+            findings.push(PiiFinding {
+                kind: kind.to_string(),
+                start,
+                end,
+                preview: preview(&content[start..end]),
+            });
+        }
+
+        findings.sort_by_key(|f| f.start);
+        findings
+    }
+
+    /// Replace each finding with a `[REDACTED:<kind>]` marker
+    pub fn mask(content: &str, findings: &[PiiFinding]) -> String {
+        let mut masked = String::with_capacity(content.len());
+        let mut cursor = 0;
+        for finding in findings {
+            masked.push_str(&content[cursor..finding.start]);
+            masked.push_str(&format!("[REDACTED:{}]", finding.kind));
+            cursor = finding.end;
+        }
+        masked.push_str(&content[cursor..]);
+        masked
+    }
+
+    /// Encrypt content for retention; returns base64(nonce || ciphertext)
+    pub fn encrypt(&self, plaintext: &str) -> Option<String> {
+        let cipher = self.cipher.as_ref()?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher.encrypt(&nonce, plaintext.as_bytes()).ok()?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+        Some(BASE64.encode(payload))
+    }
+}
+
+/// Shannon entropy in bits per character
+fn shannon_entropy(s: &str) -> f64 {
+    let mut counts = std::collections::HashMap::new();
+    for c in s.chars() {
+        *counts.entry(c).or_insert(0usize) += 1;
+    }
+    let len = s.chars().count() as f64;
+    counts.values()
+        .map(|&n| {
+            let p = n as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Luhn checksum over the digits of a candidate card number
+fn luhn_valid(candidate: &str) -> bool {
+    let digits: Vec<u32> = candidate.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+    let sum: u32 = digits.iter().rev().enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// Keep the first and last two characters of a value
+fn preview(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 6 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..2].iter().collect();
+    let tail: String = chars[chars.len() - 2..].iter().collect();
+    format!("{}{}{}", head, "*".repeat(chars.len() - 4), tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes_gcm::Nonce;
+
+    #[test]
+    fn test_detects_common_pii() {
+        let scanner = PiiScanner::new(PiiPolicy::Flag, None);
+        let content = "Mail sam@example.com or call 555-123-4567, card 4111 1111 1111 1111, key sk-abcdefghijklmnopqrstuvwxyz123456";
+        let kinds: Vec<String> = scanner.scan(content).into_iter().map(|f| f.kind).collect();
+
+        assert_eq!(kinds, vec!["email", "phone", "credit_card", "api_key"]);
+    }
+
+    #[test]
+    fn test_ignores_plain_text() {
+        let scanner = PiiScanner::new(PiiPolicy::Flag, None);
+        assert!(scanner.scan("Redis performance tuning for 1234 keys").is_empty());
+        assert!(scanner.scan("number 1234 5678 9012 3456 fails luhn").is_empty());
+        assert!(scanner.scan("chain 550e8400-e29b-41d4-a716-446655440000").is_empty());
+    }
+
+    #[test]
+    fn test_mask_and_encrypt_round_trip() {
+        let key = [7u8; 32];
+        let scanner = PiiScanner::new(PiiPolicy::Mask, Some(&key));
+        let content = "Contact sam@example.com today";
+        let findings = scanner.scan(content);
+
+        assert_eq!(PiiScanner::mask(content, &findings), "Contact [REDACTED:email] today");
+
+        let encrypted = scanner.encrypt(content).unwrap();
+        let payload = BASE64.decode(encrypted).unwrap();
+        let (nonce, ciphertext) = payload.split_at(12);
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+        let plaintext = cipher.decrypt(Nonce::from_slice(nonce), ciphertext).unwrap();
+        assert_eq!(plaintext, content.as_bytes());
+    }
+
+    #[test]
+    fn test_mask_requires_a_valid_key() {
+        assert!(PiiScanner::with_key(PiiPolicy::Mask, None).is_err());
+        assert!(PiiScanner::with_key(PiiPolicy::Mask, Some(&[7u8; 16])).is_err());
+        assert!(PiiScanner::with_key(PiiPolicy::Mask, Some(&[7u8; 32])).is_ok());
+        assert!(PiiScanner::with_key(PiiPolicy::Flag, None).is_ok());
+    }
+
+    #[test]
+    fn test_off_policy_skips_scanning() {
+        let scanner = PiiScanner::new(PiiPolicy::Off, None);
+        assert!(scanner.scan("sam@example.com").is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+//! Provenance defaults and filters for ingested thoughts.
+//!
+//! Every thought stored by this server records who wrote it and how it got
+//! here. Defaults come from the environment once per process; callers relaying
+//! text from elsewhere (bot-cli, imports) override individual fields per call.
+//!
+//! - UI_DEFAULT_AUTHOR: 'model' (default) or 'human'
+//! - UI_SOURCE_SESSION: session id (default: generated per server process)
+//! - UI_MODEL_NAME: model recorded as model_used
+//! - GIT_COMMIT: commit recorded as git_commit (default: `git rev-parse HEAD` of the working directory)
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::{Provenance, ThoughtRecord};
+
+pub const AUTHOR_HUMAN: &str = "human";
+pub const AUTHOR_MODEL: &str = "model";
+
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+/// Current commit of the working directory, if it is a git checkout
+fn detect_git_commit() -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    output.status.success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|commit| !commit.is_empty())
+}
+
+/// Per-process provenance defaults (source_tool is set per ingest path)
+pub fn defaults_from_env() -> Provenance {
+    Provenance {
+        author: Some(env_value("UI_DEFAULT_AUTHOR").unwrap_or_else(|| AUTHOR_MODEL.to_string())),
+        source_tool: None,
+        source_session: Some(env_value("UI_SOURCE_SESSION").unwrap_or_else(|| uuid::Uuid::new_v4().to_string())),
+        model_used: env_value("UI_MODEL_NAME"),
+        git_commit: env_value("GIT_COMMIT").or_else(detect_git_commit),
+        client: None, // Known once the MCP client initializes
+    }
+}
+
+/// Validate an author value from a request
+pub fn validate_author(field: &str, author: &str) -> Result<()> {
+    if author == AUTHOR_HUMAN || author == AUTHOR_MODEL {
+        Ok(())
+    } else {
+        Err(UnifiedIntelligenceError::Validation {
+            field: field.to_string(),
+            reason: format!("Unknown author '{}'. Use '{}' or '{}'", author, AUTHOR_HUMAN, AUTHOR_MODEL),
+        })
+    }
+}
+
+/// Fill unset fields of `overrides` from `defaults`
+pub fn merge(overrides: Option<Provenance>, defaults: &Provenance, source_tool: &str) -> Provenance {
+    let overrides = overrides.unwrap_or_default();
+    Provenance {
+        author: overrides.author.or_else(|| defaults.author.clone()),
+        source_tool: overrides.source_tool.or_else(|| Some(source_tool.to_string())),
+        source_session: overrides.source_session.or_else(|| defaults.source_session.clone()),
+        model_used: overrides.model_used.or_else(|| defaults.model_used.clone()),
+        git_commit: overrides.git_commit.or_else(|| defaults.git_commit.clone()),
+        client: overrides.client.or_else(|| defaults.client.clone()),
+    }
+}
+
+/// Whether a thought passes the recall provenance filters (legacy thoughts never do)
+pub fn matches(thought: &ThoughtRecord, author: Option<&str>, source_tool: Option<&str>) -> bool {
+    let Some(provenance) = &thought.provenance else {
+        return author.is_none() && source_tool.is_none();
+    };
+    author.is_none_or(|a| provenance.author.as_deref() == Some(a))
+        && source_tool.is_none_or(|s| provenance.source_tool.as_deref() == Some(s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_prefers_overrides() {
+        let defaults = Provenance {
+            author: Some(AUTHOR_MODEL.to_string()),
+            source_session: Some("s1".to_string()),
+            model_used: Some("model-x".to_string()),
+            ..Default::default()
+        };
+        let merged = merge(
+            Some(Provenance { author: Some(AUTHOR_HUMAN.to_string()), source_tool: Some("bot-cli".to_string()), ..Default::default() }),
+            &defaults,
+            "ui_think",
+        );
+        assert_eq!(merged.author.as_deref(), Some(AUTHOR_HUMAN));
+        assert_eq!(merged.source_tool.as_deref(), Some("bot-cli"));
+        assert_eq!(merged.source_session.as_deref(), Some("s1"));
+        assert_eq!(merged.model_used.as_deref(), Some("model-x"));
+    }
+
+    #[test]
+    fn test_matches_filters() {
+        let mut thought = ThoughtRecord::new("test".to_string(), "x".to_string(), 1, 1, None, false);
+        assert!(matches(&thought, None, None));
+        assert!(!matches(&thought, Some(AUTHOR_HUMAN), None));
+
+        thought.provenance = Some(merge(None, &Provenance { author: Some(AUTHOR_HUMAN.to_string()), ..Default::default() }, "import"));
+        assert!(matches(&thought, Some(AUTHOR_HUMAN), Some("import")));
+        assert!(!matches(&thought, Some(AUTHOR_MODEL), None));
+    }
+}
@@ -0,0 +1,129 @@
+//! Helpers for the ui_purge tool: key classification and inventory summaries.
+
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+use crate::tenant;
+
+/// How long a dry-run confirmation token stays valid
+pub const PURGE_TOKEN_TTL_SECONDS: u64 = 600;
+
+/// Number of example keys returned with an inventory
+pub const PURGE_SAMPLE_SIZE: usize = 20;
+
+/// Prefixes of module keys (time series, vector sets, their metadata) in front of the owning namespace
+const MODULE_KEY_PREFIXES: &[&str] = &["ts:", "vset:", "metadata:"];
+
+/// Whether a key found under a namespace belongs to the same tenant, so a prefix
+/// match never reaches into another user's data
+pub fn same_tenant(namespace: &str, key: &str) -> bool {
+    let key = MODULE_KEY_PREFIXES.iter()
+        .find_map(|prefix| key.strip_prefix(prefix))
+        .unwrap_or(key);
+    tenant::user_of(key) == tenant::user_of(namespace)
+}
+
+/// Classify a Redis key into the data category reported by ui_purge
+pub fn categorize_key(key: &str) -> &'static str {
+    if key.contains(":Thoughts:") {
+        "thoughts"
+    } else if key.contains(":chains:") || key.contains("Chains:metadata:") || key.contains(":chain_sync:") {
+        "chains"
+    } else if key.contains(":embeddings:") || key.starts_with("vset:") {
+        "embeddings"
+    } else if key.contains(":identity") {
+        "identity"
+    } else if key.contains(":pii:") {
+        "pii"
+    } else if key.contains(":thought_meta:")
+        || key.contains(":tags:")
+        || key.ends_with(":boost_scores")
+        || key.ends_with(":feedback_events")
+    {
+        "feedback"
+    } else if key.ends_with(":events") {
+        "logs"
+    } else if key.contains(":metrics:") || key.starts_with("ts:") || key.contains(":bloom:") || key.contains(":clients:") {
+        "metrics"
+    } else if key.contains(":archive") {
+        "archives"
+    } else {
+        "other"
+    }
+}
+
+/// Hash of an inventory, stored with the dry-run token so a confirm only
+/// deletes the keys the dry run showed
+pub fn inventory_hash(keys: &[String]) -> String {
+    let mut sorted: Vec<&str> = keys.iter().map(String::as_str).collect();
+    sorted.sort_unstable();
+    Sha256::digest(sorted.join("\n").as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Stored token value: the token handed to the client and the inventory it covers
+pub fn token_record(token: &str, keys: &[String]) -> String {
+    format!("{}:{}", token, inventory_hash(keys))
+}
+
+/// Split a stored token value into (token, inventory hash)
+pub fn parse_token_record(record: &str) -> Option<(&str, &str)> {
+    record.split_once(':')
+}
+
+/// Count keys per category
+pub fn summarize(keys: &[String]) -> BTreeMap<String, usize> {
+    let mut summary = BTreeMap::new();
+    for key in keys {
+        *summary.entry(categorize_key(key).to_string()).or_insert(0) += 1;
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_tenant() {
+        assert!(same_tenant("users:alice", "users:alice:CC:Thoughts:1"));
+        assert!(same_tenant("users:alice", "ts:users:alice:CC:metrics"));
+        assert!(!same_tenant("users:alice", "users:alice-x:CC:Thoughts:1"));
+        assert!(!same_tenant("CC", "users:CC:Thoughts:1"));
+        assert!(same_tenant("CC", "Chains:metadata:c1"));
+    }
+
+    #[test]
+    fn test_categorize_key() {
+        assert_eq!(categorize_key("CC:Thoughts:abc"), "thoughts");
+        assert_eq!(categorize_key("CC:chains:c1"), "chains");
+        assert_eq!(categorize_key("Chains:metadata:c1"), "chains");
+        assert_eq!(categorize_key("CC:embeddings:abc"), "embeddings");
+        assert_eq!(categorize_key("vset:CC:thoughts"), "embeddings");
+        assert_eq!(categorize_key("CC:identity:core_info:1"), "identity");
+        assert_eq!(categorize_key("CC:boost_scores"), "feedback");
+        assert_eq!(categorize_key("CC:events"), "logs");
+        assert_eq!(categorize_key("ts:CC:thought_count"), "metrics");
+        assert_eq!(categorize_key("CC:archive:2024"), "archives");
+    }
+
+    #[test]
+    fn test_inventory_hash_ignores_order() {
+        let keys = vec!["CC:Thoughts:1".to_string(), "CC:events".to_string()];
+        let reversed: Vec<String> = keys.iter().rev().cloned().collect();
+        assert_eq!(inventory_hash(&keys), inventory_hash(&reversed));
+        assert_ne!(inventory_hash(&keys), inventory_hash(&keys[..1]));
+        assert_eq!(parse_token_record(&token_record("t1", &keys)), Some(("t1", inventory_hash(&keys).as_str())));
+    }
+
+    #[test]
+    fn test_summarize() {
+        let keys = vec![
+            "CC:Thoughts:1".to_string(),
+            "CC:Thoughts:2".to_string(),
+            "CC:events".to_string(),
+        ];
+        let summary = summarize(&keys);
+        assert_eq!(summary.get("thoughts"), Some(&2));
+        assert_eq!(summary.get("logs"), Some(&1));
+    }
+}
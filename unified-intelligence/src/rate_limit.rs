@@ -1,67 +1,230 @@
-use std::collections::HashMap;
-use std::sync::Arc;
+//! Sliding-window rate limits on tool calls.
+//!
+//! Every call counts against its instance's window (UI_RATE_LIMIT,
+//! `requests/seconds`, default 100/60) and, for tools listed in
+//! UI_RATE_LIMIT_TOOLS (`tool=requests/seconds` pairs), against that tool's
+//! window too; a call is refused unless every window has room. With the Redis
+//! backend the windows are sorted sets (`{instance}:rate_limit:{scope}`)
+//! checked by a Lua script, so every server process of an instance shares one
+//! budget. Otherwise, or while Redis can't be reached, each process keeps
+//! its own windows in memory.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, OnceLock};
 use tokio::sync::Mutex;
 use tokio::time::{Duration, Instant};
 use crate::error::{UnifiedIntelligenceError, Result};
+use crate::keys;
+use crate::models::RateLimitWindow;
+use crate::redis::RedisManager;
+
+/// Scope of the window every call of an instance counts against
+pub const INSTANCE_SCOPE: &str = "all";
+
+/// Requests allowed in a window of time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limit {
+    pub max_requests: usize,
+    pub window: Duration,
+}
+
+impl Limit {
+    /// `requests/seconds`, e.g. `100/60`
+    pub fn parse(value: &str) -> Option<Self> {
+        let (requests, seconds) = value.trim().split_once('/')?;
+        let (max_requests, seconds) = (requests.trim().parse().ok()?, seconds.trim().parse::<u64>().ok()?);
+        (seconds > 0).then(|| Self { max_requests, window: Duration::from_secs(seconds) })
+    }
+}
 
-/// Simple in-memory rate limiter for protecting against runaway processes
+/// Limits of an instance and of individual tools
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub instance: Limit,
+    pub tools: BTreeMap<String, Limit>,
+}
+
+impl RateLimitConfig {
+    /// Read UI_RATE_LIMIT and UI_RATE_LIMIT_TOOLS
+    pub fn from_env() -> Self {
+        let env = |name: &str| std::env::var(name).unwrap_or_default();
+        Self::parse(&env("UI_RATE_LIMIT"), &env("UI_RATE_LIMIT_TOOLS"))
+    }
+    
+    /// The instance limit (100/60 when empty) and `tool=requests/seconds` pairs; malformed values are skipped
+    pub fn parse(instance: &str, tools: &str) -> Self {
+        let default = Limit { max_requests: 100, window: Duration::from_secs(60) };
+        let instance = if instance.trim().is_empty() {
+            default
+        } else {
+            Limit::parse(instance).unwrap_or_else(|| {
+                tracing::warn!("Ignoring UI_RATE_LIMIT '{}'", instance.trim());
+                default
+            })
+        };
+        let tools = tools.split(',')
+            .filter(|pair| !pair.trim().is_empty())
+            .filter_map(|pair| {
+                let parsed = pair.split_once('=')
+                    .and_then(|(tool, limit)| Some((tool.trim().to_string(), Limit::parse(limit)?)));
+                if parsed.is_none() {
+                    tracing::warn!("Ignoring tool rate limit '{}'", pair.trim());
+                }
+                parsed
+            })
+            .collect();
+        Self { instance, tools }
+    }
+    
+    /// Windows a call of a tool counts against, by scope
+    pub fn scopes(&self, tool: &str) -> Vec<(&str, Limit)> {
+        let mut scopes = vec![(INSTANCE_SCOPE, self.instance)];
+        if let Some((tool, limit)) = self.tools.get_key_value(tool) {
+            scopes.push((tool.as_str(), *limit));
+        }
+        scopes
+    }
+}
+
+/// Request timestamps by instance_id and scope
+type Windows = HashMap<(String, String), Vec<Instant>>;
+
+/// Sliding-window rate limiter for protecting against runaway processes
 #[derive(Clone)]
 pub struct RateLimiter {
-    /// Map of instance_id to their request timestamps
-    windows: Arc<Mutex<HashMap<String, Vec<Instant>>>>,
-    /// Maximum requests allowed per window
-    max_requests: usize,
-    /// Time window duration
-    window_duration: Duration,
+    /// Map of instance_id and scope to their request timestamps, when not shared through Redis
+    windows: Arc<Mutex<Windows>>,
+    config: Arc<RateLimitConfig>,
+    redis: Arc<OnceLock<Arc<RedisManager>>>,
 }
 
 impl RateLimiter {
     /// Create a new rate limiter
-    /// 
+    ///
     /// # Arguments
     /// * `max_requests` - Maximum number of requests allowed per window
     /// * `window_seconds` - Duration of the sliding window in seconds
     pub fn new(max_requests: usize, window_seconds: u64) -> Self {
+        Self::with_config(RateLimitConfig {
+            instance: Limit { max_requests, window: Duration::from_secs(window_seconds) },
+            tools: BTreeMap::new(),
+        })
+    }
+    
+    pub fn with_config(config: RateLimitConfig) -> Self {
         Self {
             windows: Arc::new(Mutex::new(HashMap::new())),
-            max_requests,
-            window_duration: Duration::from_secs(window_seconds),
+            config: Arc::new(config),
+            redis: Arc::new(OnceLock::new()),
         }
     }
     
-    /// Check if an instance is allowed to make a request
-    /// 
+    /// Keep the windows in Redis from now on, shared with other processes
+    pub fn share_through(&self, redis: Arc<RedisManager>) {
+        if self.redis.set(redis).is_ok() {
+            tracing::info!("Rate limits are shared through Redis");
+        }
+    }
+    
+    /// Whether the windows are kept in Redis
+    pub fn is_shared(&self) -> bool {
+        self.redis.get().is_some()
+    }
+    
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+    
+    /// Check if an instance is allowed to call a tool, counting the call when it is
+    ///
     /// # Arguments
     /// * `instance_id` - The instance identifier to check
-    /// 
+    /// * `tool` - The tool being called
+    ///
     /// # Returns
     /// * `Ok(())` if the request is allowed
     /// * `Err(UnifiedIntelligenceError::RateLimit)` if rate limit exceeded
-    pub async fn check_rate_limit(&self, instance_id: &str) -> Result<()> {
+    pub async fn check_rate_limit(&self, instance_id: &str, tool: &str) -> Result<()> {
+        let scopes = self.config.scopes(tool);
+        let (allowed, counts) = self.windows(instance_id, &scopes, true).await;
+        if !allowed {
+            for ((scope, limit), (count, _)) in scopes.iter().zip(&counts) {
+                if *count >= limit.max_requests {
+                    tracing::warn!(
+                        "Rate limit exceeded for instance '{}' ({}): {} requests in {:?}",
+                        instance_id,
+                        scope,
+                        count,
+                        limit.window
+                    );
+                }
+            }
+            return Err(UnifiedIntelligenceError::RateLimit);
+        }
+        Ok(())
+    }
+    
+    /// Current state of an instance's windows: its own and each limited tool's
+    pub async fn status(&self, instance_id: &str) -> Vec<RateLimitWindow> {
+        let mut scopes = vec![(INSTANCE_SCOPE, self.config.instance)];
+        scopes.extend(self.config.tools.iter().map(|(tool, limit)| (tool.as_str(), *limit)));
+        let (_, counts) = self.windows(instance_id, &scopes, false).await;
+        scopes.iter().zip(counts)
+            .map(|((scope, limit), (used, oldest_age))| RateLimitWindow {
+                scope: scope.to_string(),
+                limit: limit.max_requests,
+                window_secs: limit.window.as_secs(),
+                used,
+                remaining: limit.max_requests.saturating_sub(used),
+                resets_in_secs: oldest_age.map(|age| limit.window.saturating_sub(age).as_secs_f64()),
+            })
+            .collect()
+    }
+    
+    /// Check (and with `record`, count) a request against windows; returns whether
+    /// it was allowed and each window's requests and the age of its oldest one
+    async fn windows(&self, instance_id: &str, scopes: &[(&str, Limit)], record: bool) -> (bool, Vec<(usize, Option<Duration>)>) {
+        if let Some(redis) = self.redis.get() {
+            let keys: Vec<String> = scopes.iter().map(|(scope, _)| keys::rate_limit(instance_id, scope)).collect();
+            let windows: Vec<(&str, i64, usize)> = keys.iter().zip(scopes)
+                .map(|(key, (_, limit))| (key.as_str(), limit.window.as_millis() as i64, limit.max_requests))
+                .collect();
+            let now_ms = chrono::Utc::now().timestamp_millis();
+            let request_id = uuid::Uuid::new_v4().to_string();
+            match redis.sliding_window(&windows, now_ms, &request_id, record).await {
+                Ok((allowed, counts)) => {
+                    let counts = counts.into_iter()
+                        .map(|(count, oldest_ms)| (count, (oldest_ms >= 0).then(|| Duration::from_millis((now_ms - oldest_ms).max(0) as u64))))
+                        .collect();
+                    return (allowed, counts);
+                }
+                Err(e) => tracing::warn!("Shared rate limit unavailable, limiting this process only: {}", e),
+            }
+        }
+        self.local_windows(instance_id, scopes, record).await
+    }
+    
+    async fn local_windows(&self, instance_id: &str, scopes: &[(&str, Limit)], record: bool) -> (bool, Vec<(usize, Option<Duration>)>) {
         let mut windows = self.windows.lock().await;
         let now = Instant::now();
         
-        // Get or create the window for this instance
-        let timestamps = windows.entry(instance_id.to_string()).or_insert_with(Vec::new);
-        
-        // Remove timestamps outside the window
-        timestamps.retain(|&timestamp| now.duration_since(timestamp) < self.window_duration);
-        
-        // Check if we're at the limit
-        if timestamps.len() >= self.max_requests {
-            tracing::warn!(
-                "Rate limit exceeded for instance '{}': {} requests in {:?}", 
-                instance_id, 
-                timestamps.len(),
-                self.window_duration
-            );
-            return Err(UnifiedIntelligenceError::RateLimit);
+        // Remove timestamps outside each window
+        for (scope, limit) in scopes {
+            let timestamps = windows.entry((instance_id.to_string(), scope.to_string())).or_default();
+            timestamps.retain(|&timestamp| now.duration_since(timestamp) < limit.window);
         }
+        let allowed = scopes.iter().all(|(scope, limit)| {
+            windows[&(instance_id.to_string(), scope.to_string())].len() < limit.max_requests
+        });
         
-        // Add the current timestamp
-        timestamps.push(now);
-        
-        Ok(())
+        let counts = scopes.iter().map(|(scope, _)| {
+            let timestamps = windows.get_mut(&(instance_id.to_string(), scope.to_string())).expect("window created above");
+            if record && allowed {
+                timestamps.push(now);
+            }
+            (timestamps.len(), timestamps.first().map(|oldest| now.duration_since(*oldest)))
+        }).collect();
+        (record && allowed, counts)
     }
     
     /// Get current usage statistics for monitoring
@@ -70,11 +233,13 @@ impl RateLimiter {
         let mut windows = self.windows.lock().await;
         let now = Instant::now();
         let mut stats = HashMap::new();
+        let window = self.config.instance.window;
         
-        // Clean up old entries and collect stats
-        for (instance_id, timestamps) in windows.iter_mut() {
-            timestamps.retain(|&timestamp| now.duration_since(timestamp) < self.window_duration);
-            if !timestamps.is_empty() {
+        // Clean up old entries and collect stats of the instance windows
+        for ((instance_id, scope), timestamps) in windows.iter_mut() {
+            let window = self.config.tools.get(scope).map_or(window, |limit| limit.window);
+            timestamps.retain(|&timestamp| now.duration_since(timestamp) < window);
+            if !timestamps.is_empty() && scope == INSTANCE_SCOPE {
                 stats.insert(instance_id.clone(), timestamps.len());
             }
         }
@@ -89,7 +254,7 @@ impl RateLimiter {
     #[allow(dead_code)]
     pub async fn clear_instance(&self, instance_id: &str) {
         let mut windows = self.windows.lock().await;
-        windows.remove(instance_id);
+        windows.retain(|(instance, _), _| instance != instance_id);
     }
 }
 
@@ -104,7 +269,7 @@ mod tests {
         // Should allow 5 requests
         for i in 0..5 {
             assert!(
-                limiter.check_rate_limit("test-instance").await.is_ok(),
+                limiter.check_rate_limit("test-instance", "ui_think").await.is_ok(),
                 "Request {} should be allowed", i + 1
             );
         }
@@ -116,13 +281,13 @@ mod tests {
         
         // Allow first 3 requests
         for _ in 0..3 {
-            assert!(limiter.check_rate_limit("test-instance").await.is_ok());
+            assert!(limiter.check_rate_limit("test-instance", "ui_think").await.is_ok());
         }
         
         // 4th request should be blocked
         assert!(
             matches!(
-                limiter.check_rate_limit("test-instance").await,
+                limiter.check_rate_limit("test-instance", "ui_recall").await,
                 Err(UnifiedIntelligenceError::RateLimit)
             ),
             "4th request should be rate limited"
@@ -134,14 +299,14 @@ mod tests {
         let limiter = RateLimiter::new(2, 60); // 2 requests per minute
         
         // Instance A uses its limit
-        assert!(limiter.check_rate_limit("instance-a").await.is_ok());
-        assert!(limiter.check_rate_limit("instance-a").await.is_ok());
-        assert!(limiter.check_rate_limit("instance-a").await.is_err());
+        assert!(limiter.check_rate_limit("instance-a", "ui_think").await.is_ok());
+        assert!(limiter.check_rate_limit("instance-a", "ui_think").await.is_ok());
+        assert!(limiter.check_rate_limit("instance-a", "ui_think").await.is_err());
         
         // Instance B should still be allowed
-        assert!(limiter.check_rate_limit("instance-b").await.is_ok());
-        assert!(limiter.check_rate_limit("instance-b").await.is_ok());
-        assert!(limiter.check_rate_limit("instance-b").await.is_err());
+        assert!(limiter.check_rate_limit("instance-b", "ui_think").await.is_ok());
+        assert!(limiter.check_rate_limit("instance-b", "ui_think").await.is_ok());
+        assert!(limiter.check_rate_limit("instance-b", "ui_think").await.is_err());
     }
     
     #[tokio::test]
@@ -149,14 +314,36 @@ mod tests {
         let limiter = RateLimiter::new(2, 1); // 2 requests per second
         
         // Use up the limit
-        assert!(limiter.check_rate_limit("test").await.is_ok());
-        assert!(limiter.check_rate_limit("test").await.is_ok());
-        assert!(limiter.check_rate_limit("test").await.is_err());
+        assert!(limiter.check_rate_limit("test", "ui_think").await.is_ok());
+        assert!(limiter.check_rate_limit("test", "ui_think").await.is_ok());
+        assert!(limiter.check_rate_limit("test", "ui_think").await.is_err());
         
         // Wait for window to pass
         tokio::time::sleep(Duration::from_millis(1100)).await;
         
         // Should be allowed again
-        assert!(limiter.check_rate_limit("test").await.is_ok());
+        assert!(limiter.check_rate_limit("test", "ui_think").await.is_ok());
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_tool_limits_and_status() {
+        let config = RateLimitConfig::parse("5/60", "ui_capture=2/30, ui_think=x, bad");
+        assert_eq!(config.tools.keys().collect::<Vec<_>>(), vec!["ui_capture"]);
+        let limiter = RateLimiter::with_config(config);
+        
+        assert!(limiter.check_rate_limit("CC", "ui_capture").await.is_ok());
+        assert!(limiter.check_rate_limit("CC", "ui_capture").await.is_ok());
+        assert!(limiter.check_rate_limit("CC", "ui_capture").await.is_err());
+        // A refused call isn't counted; other tools still have the instance budget
+        assert!(limiter.check_rate_limit("CC", "ui_think").await.is_ok());
+        
+        let status = limiter.status("CC").await;
+        let state: Vec<(&str, usize, usize)> = status.iter().map(|w| (w.scope.as_str(), w.used, w.remaining)).collect();
+        assert_eq!(state, vec![(INSTANCE_SCOPE, 3, 2), ("ui_capture", 2, 0)]);
+        assert!(status[1].resets_in_secs.is_some_and(|secs| secs > 29.0 && secs <= 30.0));
+        assert!(!limiter.is_shared());
+        
+        assert_eq!(RateLimitConfig::parse("", "").instance, Limit { max_requests: 100, window: Duration::from_secs(60) });
+        assert_eq!(RateLimitConfig::parse("10/0", "").instance.max_requests, 100);
+    }
+}
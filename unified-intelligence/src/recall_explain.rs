@@ -0,0 +1,231 @@
+//! Per-result explanations for ui_recall (`explain: true`).
+//!
+//! Recall ranks by `ThoughtRecord::similarity`, which for boosted searches is
+//! the vector similarity plus `BOOST_WEIGHT` times the feedback boost (or the
+//! boost alone for text search). The explanation splits that score back into
+//! its parts and adds what the stored score doesn't capture: a BM25 score of
+//! the query terms over the returned results, the tags and metadata filters a
+//! thought satisfied, and its age. Query results of an instance also carry
+//! their importance contribution (see `importance`). When a ranking profile
+//! decays scores by age the decay factor is reported and undone before the
+//! split.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::importance;
+use crate::models::{RecallExplanation, ThoughtMetadata, ThoughtRecord, UiRecallParams};
+use crate::recency;
+use crate::search_optimization::BOOST_WEIGHT;
+
+/// BM25 term saturation
+const BM25_K1: f64 = 1.2;
+/// BM25 length normalization
+const BM25_B: f64 = 0.75;
+
+/// How the results were retrieved, as far as scoring is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecallScoring {
+    /// similarity = vector similarity (+ boost when boosted)
+    Semantic { boosted: bool },
+    /// similarity = boost when boosted, otherwise unset
+    Text { boosted: bool },
+    /// Chain retrieval or plain listing; no scores
+    Listing,
+}
+
+/// Lowercased query terms of at least two characters
+fn terms(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 2)
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+/// BM25 score of the query against each document, using the documents themselves as the corpus
+pub fn bm25_scores(query: &str, documents: &[&str]) -> Vec<f64> {
+    let query_terms: HashSet<String> = terms(query).into_iter().collect();
+    let tokenized: Vec<Vec<String>> = documents.iter().map(|d| terms(d)).collect();
+    if tokenized.is_empty() {
+        return Vec::new();
+    }
+
+    let n = tokenized.len() as f64;
+    let avg_len = (tokenized.iter().map(Vec::len).sum::<usize>() as f64 / n).max(1.0);
+    let doc_freq: HashMap<&str, f64> = query_terms.iter()
+        .map(|term| (term.as_str(), tokenized.iter().filter(|doc| doc.contains(term)).count() as f64))
+        .collect();
+
+    tokenized.iter()
+        .map(|doc| {
+            let len = doc.len() as f64;
+            query_terms.iter()
+                .map(|term| {
+                    let tf = doc.iter().filter(|w| *w == term).count() as f64;
+                    if tf == 0.0 {
+                        return 0.0;
+                    }
+                    let df = doc_freq[term.as_str()];
+                    let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln();
+                    idf * tf * (BM25_K1 + 1.0) / (tf + BM25_K1 * (1.0 - BM25_B + BM25_B * len / avg_len))
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Query terms that appear in the text
+fn matched_terms(query: &str, text: &str) -> Vec<String> {
+    let words: HashSet<String> = terms(text).into_iter().collect();
+    let mut matched: Vec<String> = terms(query).into_iter().filter(|t| words.contains(t)).collect();
+    matched.dedup();
+    matched
+}
+
+/// Metadata filters from the request that this thought satisfied
+fn matched_filters(params: &UiRecallParams, metadata: Option<&ThoughtMetadata>) -> (Vec<String>, Vec<String>) {
+    let Some(metadata) = metadata else {
+        return (Vec::new(), Vec::new());
+    };
+
+    let tags = match (&params.tags_filter, &metadata.tags) {
+        (Some(wanted), Some(tags)) => wanted.iter()
+            .filter(|w| tags.iter().any(|t| t.eq_ignore_ascii_case(w)))
+            .cloned()
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let mut filters = Vec::new();
+    if let (Some(min), Some(importance)) = (params.min_importance, metadata.importance) {
+        if importance >= min {
+            filters.push(format!("importance {} >= {}", importance, min));
+        }
+    }
+    if let (Some(min), Some(relevance)) = (params.min_relevance, metadata.relevance) {
+        if relevance >= min {
+            filters.push(format!("relevance {} >= {}", relevance, min));
+        }
+    }
+    if let (Some(wanted), Some(category)) = (&params.category_filter, &metadata.category) {
+        if wanted.eq_ignore_ascii_case(category) {
+            filters.push(format!("category = {}", category));
+        }
+    }
+
+    (tags, filters)
+}
+
+/// Build explanations for ranked recall results; `half_life_days` is the recency decay applied, if any,
+/// and `importance_ranked` whether importance contributed to the scores
+pub fn explain(
+    thoughts: &[ThoughtRecord],
+    params: &UiRecallParams,
+    scoring: RecallScoring,
+    metadata: &HashMap<String, ThoughtMetadata>,
+    boosts: &HashMap<String, f64>,
+    half_life_days: Option<f64>,
+    importance_ranked: bool,
+) -> Vec<RecallExplanation> {
+    let query = params.query.as_deref().unwrap_or("");
+    let documents: Vec<&str> = thoughts.iter().map(|t| t.thought.as_str()).collect();
+    let bm25 = if query.is_empty() { Vec::new() } else { bm25_scores(query, &documents) };
+
+    thoughts.iter().enumerate()
+        .map(|(index, thought)| {
+            let boost_score = boosts.get(&thought.id).copied();
+            let age_days = recency::age_days(&thought.timestamp);
+            let decay_factor = match (scoring, half_life_days, age_days) {
+                (RecallScoring::Listing, _, _) => None,
+                (_, Some(half_life), Some(age)) => Some(recency::decay_factor(age, half_life)),
+                _ => None,
+            };
+            let similarity = match decay_factor {
+                Some(factor) => thought.similarity.map(|s| recency::undecayed(s, factor)),
+                None => thought.similarity,
+            };
+            let thought_metadata = metadata.get(&thought.id);
+            let importance = thought_metadata.and_then(ThoughtMetadata::effective_importance);
+            let importance_contribution = (importance_ranked && scoring != RecallScoring::Listing)
+                .then(|| importance::contribution(importance));
+            let similarity = similarity.map(|s| s - importance_contribution.unwrap_or(0.0));
+            let (vector_similarity, boost_contribution) = match scoring {
+                RecallScoring::Semantic { boosted: true } => {
+                    let contribution = boost_score.unwrap_or(0.0) as f32 * BOOST_WEIGHT;
+                    (similarity.map(|s| s - contribution), Some(contribution))
+                }
+                RecallScoring::Semantic { boosted: false } => (similarity, None),
+                RecallScoring::Text { boosted: true } => (None, boost_score.map(|b| b as f32)),
+                RecallScoring::Text { boosted: false } | RecallScoring::Listing => (None, None),
+            };
+
+            let terms = if query.is_empty() { Vec::new() } else { matched_terms(query, &thought.thought) };
+            let (matched_tags, matched_filters) = matched_filters(params, thought_metadata);
+
+            let mut reasons = Vec::new();
+            if let Some(similarity) = vector_similarity {
+                reasons.push(format!("vector similarity {:.3}", similarity));
+            }
+            if !terms.is_empty() {
+                reasons.push(format!("matched terms: {}", terms.join(", ")));
+            }
+            if let Some(contribution) = boost_contribution.filter(|c| *c != 0.0) {
+                reasons.push(format!("feedback boost {:+.3}", contribution));
+            }
+            if let (Some(contribution), Some(importance)) = (importance_contribution.filter(|c| *c != 0.0), importance) {
+                let source = if thought_metadata.is_some_and(|m| m.importance.is_some()) { "" } else { ", computed from usage" };
+                reasons.push(format!("importance {}{} {:+.3}", importance, source, contribution));
+            }
+            reasons.extend(matched_tags.iter().map(|tag| format!("tag '{}'", tag)));
+            reasons.extend(matched_filters.iter().cloned());
+            if let (Some(factor), Some(age)) = (decay_factor, age_days) {
+                reasons.push(format!("recency decay x{:.2} ({:.1} days old)", factor, age));
+            }
+            if scoring == RecallScoring::Listing {
+                reasons.push(match (&params.chain_id, &thought.chain_id) {
+                    (Some(_), Some(chain_id)) => format!("member of chain {}", chain_id),
+                    _ => "recent thought (no query)".to_string(),
+                });
+            }
+
+            RecallExplanation {
+                thought_id: thought.id.clone(),
+                rank: index + 1,
+                final_score: thought.similarity,
+                vector_similarity,
+                bm25_score: bm25.get(index).copied(),
+                matched_terms: terms,
+                boost_score,
+                boost_contribution,
+                matched_tags,
+                matched_filters,
+                age_days: age_days.map(|age| (age * 100.0).round() / 100.0),
+                decay_factor,
+                importance,
+                importance_contribution,
+                reasons,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bm25_ranks_matching_documents() {
+        let scores = bm25_scores("redis cache", &[
+            "Redis cache eviction is tuned",
+            "Cache warming for the index",
+            "Unrelated deployment notes",
+        ]);
+        assert!(scores[0] > scores[1]);
+        assert!(scores[1] > 0.0);
+        assert_eq!(scores[2], 0.0);
+    }
+
+    #[test]
+    fn test_matched_terms() {
+        assert_eq!(matched_terms("Redis performance", "redis is slow"), vec!["redis".to_string()]);
+    }
+}
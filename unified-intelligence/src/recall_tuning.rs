@@ -0,0 +1,231 @@
+//! Recall defaults tuned from feedback, for ui_recall and ui_recall_tuning.
+//!
+//! When a semantic ui_recall leaves out the threshold and limit (and the
+//! active mode doesn't set them), the instance's tuned values in
+//! `{instance}:recall_tuning` are used and the search is counted: how many
+//! results it returned and whether it filled the limit. ui_recall_feedback on
+//! those results counts as helpful ('helpful', 'used') or irrelevant. Once
+//! enough of both is gathered, the tuner weighs the helpful rate against the
+//! result counts: plenty of results but few helpful ones tighten the
+//! threshold, helpful results that are scarce loosen it, and the limit
+//! follows searches that keep filling it. Steps are small and the values
+//! stay within fixed rails. Every adjustment is kept with its justification
+//! and the feedback behind it, and can be reverted or reset to the baseline.
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::{RecallTuning, TuningAdjustment, TuningWindow};
+
+/// Threshold and limit of an instance that was never tuned
+pub const BASELINE_THRESHOLD: f32 = 0.5;
+pub const BASELINE_LIMIT: usize = 50;
+
+/// Rails the tuned values stay within
+pub const MIN_THRESHOLD: f32 = 0.3;
+pub const MAX_THRESHOLD: f32 = 0.85;
+pub const MIN_LIMIT: usize = 10;
+pub const MAX_LIMIT: usize = 100;
+
+const THRESHOLD_STEP: f32 = 0.05;
+const LIMIT_STEP: usize = 10;
+
+/// Searches and rated results needed before anything is adjusted
+const MIN_SEARCHES: usize = 10;
+const MIN_RATED: usize = 10;
+
+/// Helpful rates below which results count as noisy, and from which as good
+const LOW_HELPFUL_RATE: f64 = 0.4;
+const HIGH_HELPFUL_RATE: f64 = 0.7;
+
+/// Adjustments kept per instance
+const MAX_HISTORY: usize = 50;
+
+/// Search IDs remembered so feedback can be matched to tuned searches
+const MAX_RECENT_SEARCHES: usize = 200;
+
+/// Whether feedback adjusts the defaults as it arrives (UI_RECALL_AUTOTUNE, default on)
+pub fn autotune() -> bool {
+    !std::env::var("UI_RECALL_AUTOTUNE")
+        .map(|value| matches!(value.trim().to_lowercase().as_str(), "0" | "false" | "no" | "off"))
+        .unwrap_or(false)
+}
+
+/// Untuned defaults
+pub fn baseline() -> RecallTuning {
+    RecallTuning {
+        threshold: BASELINE_THRESHOLD,
+        limit: BASELINE_LIMIT,
+        window: TuningWindow::default(),
+        recent_searches: Vec::new(),
+        history: Vec::new(),
+    }
+}
+
+/// Thresholds are kept to two decimals so steps add up exactly
+fn round(threshold: f32) -> f32 {
+    (threshold * 100.0).round() / 100.0
+}
+
+/// Count a search that ran with the tuned threshold and limit
+pub fn record_search(tuning: &mut RecallTuning, search_id: &str, results: usize) {
+    let window = &mut tuning.window;
+    window.searches += 1;
+    window.results += results;
+    if results == 0 {
+        window.empty_searches += 1;
+    }
+    if results >= tuning.limit {
+        window.full_searches += 1;
+    }
+    tuning.recent_searches.push(search_id.to_string());
+    let excess = tuning.recent_searches.len().saturating_sub(MAX_RECENT_SEARCHES);
+    tuning.recent_searches.drain(..excess);
+}
+
+/// Count feedback on a result of a tuned search; false when it doesn't bear on the tuning
+pub fn record_feedback(tuning: &mut RecallTuning, search_id: &str, action: &str) -> bool {
+    if !tuning.recent_searches.iter().any(|id| id == search_id) {
+        return false;
+    }
+    match action {
+        "helpful" | "used" => tuning.window.helpful += 1,
+        "irrelevant" => tuning.window.irrelevant += 1,
+        _ => return false,
+    }
+    true
+}
+
+/// Threshold, limit and justification the feedback so far supports; None while it is
+/// too thin or supports no change within the rails
+pub fn propose(tuning: &RecallTuning) -> Option<(f32, usize, String)> {
+    let window = &tuning.window;
+    let rated = window.helpful + window.irrelevant;
+    if window.searches < MIN_SEARCHES || rated < MIN_RATED {
+        return None;
+    }
+    let helpful_rate = window.helpful as f64 / rated as f64;
+    let mean_results = window.results as f64 / window.searches as f64;
+    let fill = mean_results / tuning.limit.max(1) as f64;
+    let empty_rate = window.empty_searches as f64 / window.searches as f64;
+    let full_rate = window.full_searches as f64 / window.searches as f64;
+
+    let mut reasons = Vec::new();
+    let mut threshold = tuning.threshold;
+    if helpful_rate < LOW_HELPFUL_RATE && fill >= 0.5 {
+        threshold = round(threshold + THRESHOLD_STEP).min(MAX_THRESHOLD);
+        reasons.push("plenty of results but few helpful: tighten the threshold");
+    } else if helpful_rate >= HIGH_HELPFUL_RATE && (empty_rate >= 0.3 || fill < 0.2) {
+        threshold = round(threshold - THRESHOLD_STEP).max(MIN_THRESHOLD);
+        reasons.push("helpful results but few of them: loosen the threshold");
+    }
+    let mut limit = tuning.limit;
+    if full_rate >= 0.5 && helpful_rate >= HIGH_HELPFUL_RATE {
+        limit = (limit + LIMIT_STEP).min(MAX_LIMIT);
+        reasons.push("helpful searches keep filling the limit: raise it");
+    } else if full_rate >= 0.5 && helpful_rate < LOW_HELPFUL_RATE {
+        limit = limit.saturating_sub(LIMIT_STEP).max(MIN_LIMIT);
+        reasons.push("unhelpful searches keep filling the limit: lower it");
+    }
+    if threshold == tuning.threshold && limit == tuning.limit {
+        return None;
+    }
+
+    let justification = format!(
+        "helpful rate {:.2} over {} rated results from {} searches (mean {:.1} results, {:.0}% empty, {:.0}% filled the limit); {}",
+        helpful_rate, rated, window.searches, mean_results, empty_rate * 100.0, full_rate * 100.0, reasons.join("; "),
+    );
+    Some((threshold, limit, justification))
+}
+
+/// Apply new defaults, recording the adjustment with the feedback it was based on and starting a new window
+pub fn adjust(tuning: &mut RecallTuning, threshold: f32, limit: usize, trigger: &str, justification: String) -> TuningAdjustment {
+    let adjustment = TuningAdjustment {
+        version: tuning.history.last().map_or(1, |last| last.version + 1),
+        at: chrono::Utc::now().to_rfc3339(),
+        trigger: trigger.to_string(),
+        threshold_from: tuning.threshold,
+        threshold_to: threshold,
+        limit_from: tuning.limit,
+        limit_to: limit,
+        justification,
+        window: std::mem::take(&mut tuning.window),
+    };
+    tuning.threshold = threshold;
+    tuning.limit = limit;
+    tuning.recent_searches.clear();
+    tuning.history.push(adjustment.clone());
+    let excess = tuning.history.len().saturating_sub(MAX_HISTORY);
+    tuning.history.drain(..excess);
+    adjustment
+}
+
+/// Restore the values an adjustment replaced, as a new adjustment
+pub fn revert(tuning: &mut RecallTuning, version: u64) -> Result<TuningAdjustment> {
+    let reverted = tuning.history.iter().find(|a| a.version == version).cloned().ok_or_else(|| {
+        UnifiedIntelligenceError::Validation {
+            field: "version".to_string(),
+            reason: format!("No adjustment {} is kept for this instance", version),
+        }
+    })?;
+    let justification = format!(
+        "reverts adjustment {} ({}: threshold {:.2} -> {:.2}, limit {} -> {})",
+        reverted.version, reverted.trigger, reverted.threshold_from, reverted.threshold_to, reverted.limit_from, reverted.limit_to,
+    );
+    Ok(adjust(tuning, reverted.threshold_from, reverted.limit_from, "revert", justification))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn searches(tuning: &mut RecallTuning, count: usize, results: usize) {
+        for i in 0..count {
+            record_search(tuning, &format!("search_{}_{}", results, i), results);
+        }
+    }
+
+    fn feedback(tuning: &mut RecallTuning, action: &str, count: usize) {
+        let search_id = tuning.recent_searches[0].clone();
+        for _ in 0..count {
+            assert!(record_feedback(tuning, &search_id, action));
+        }
+    }
+
+    #[test]
+    fn test_noisy_results_tighten_and_revert() {
+        let mut tuning = baseline();
+        searches(&mut tuning, 10, 50);
+        feedback(&mut tuning, "irrelevant", 8);
+        assert_eq!(propose(&tuning), None, "too little feedback yet");
+        feedback(&mut tuning, "used", 2);
+        assert!(!record_feedback(&mut tuning, "unknown", "helpful"));
+        assert!(!record_feedback(&mut tuning, "search_50_0", "viewed"));
+
+        let (threshold, limit, justification) = propose(&tuning).unwrap();
+        assert_eq!((threshold, limit), (0.55, 40));
+        assert!(justification.starts_with("helpful rate 0.20 over 10 rated results from 10 searches"));
+        let first = adjust(&mut tuning, threshold, limit, "auto", justification);
+        assert_eq!((first.version, first.window.searches), (1, 10));
+        assert_eq!((tuning.window.clone(), tuning.recent_searches.len()), (TuningWindow::default(), 0));
+
+        let reverted = revert(&mut tuning, 1).unwrap();
+        assert_eq!((reverted.version, reverted.trigger.as_str()), (2, "revert"));
+        assert_eq!((tuning.threshold, tuning.limit), (BASELINE_THRESHOLD, BASELINE_LIMIT));
+        assert!(revert(&mut tuning, 9).is_err());
+    }
+
+    #[test]
+    fn test_scarce_helpful_results_loosen_within_rails() {
+        let mut tuning = RecallTuning { threshold: 0.32, ..baseline() };
+        searches(&mut tuning, 6, 0);
+        searches(&mut tuning, 6, 3);
+        feedback(&mut tuning, "helpful", 9);
+        feedback(&mut tuning, "irrelevant", 1);
+        let (threshold, limit, justification) = propose(&tuning).unwrap();
+        assert_eq!((threshold, limit), (MIN_THRESHOLD, BASELINE_LIMIT));
+        assert!(justification.ends_with("loosen the threshold"));
+
+        // At the rail there is nothing left to loosen
+        tuning.threshold = MIN_THRESHOLD;
+        assert_eq!(propose(&tuning), None);
+    }
+}
@@ -0,0 +1,169 @@
+//! Recency decay for ui_recall ranking.
+//!
+//! A ranking profile decides how much a thought's age counts: its score is
+//! multiplied by 0.5^(age / half-life), so under a 7 day half-life a week-old
+//! thought needs twice the similarity of a fresh one to rank alongside it.
+//! `default` keeps the historical, age-blind ranking, `operational` decays
+//! with a 7 day half-life and `research`/`archival` never decay.
+//! UI_RECALL_HALF_LIVES (`operational=3,default=30,research=off`) changes
+//! half-lives or adds profiles. Decay re-ranks the retrieved candidates; it
+//! does not widen retrieval.
+
+use std::collections::HashMap;
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::ThoughtRecord;
+
+/// Profile used when none is given
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Half-life of the `operational` profile
+pub const OPERATIONAL_HALF_LIFE_DAYS: f64 = 7.0;
+
+/// Base score of a text search result, which has no relevance score of its own
+const TEXT_BASE_SCORE: f32 = 1.0;
+
+/// Half-life in days of each ranking profile, None for no decay
+#[derive(Debug, Clone)]
+pub struct DecayProfiles {
+    half_lives: HashMap<String, Option<f64>>,
+}
+
+impl DecayProfiles {
+    /// Built-in profiles with UI_RECALL_HALF_LIVES applied
+    pub fn from_env() -> Self {
+        Self::parse(&std::env::var("UI_RECALL_HALF_LIVES").unwrap_or_default())
+    }
+
+    /// Overrides from `profile=days` pairs separated by commas; `off` or 0 disables decay
+    pub fn parse(overrides: &str) -> Self {
+        let mut half_lives: HashMap<String, Option<f64>> = HashMap::from([
+            (DEFAULT_PROFILE.to_string(), None),
+            ("operational".to_string(), Some(OPERATIONAL_HALF_LIFE_DAYS)),
+            ("research".to_string(), None),
+            ("archival".to_string(), None),
+        ]);
+        for pair in overrides.split(',').filter(|pair| !pair.trim().is_empty()) {
+            let Some((profile, days)) = pair.split_once('=') else {
+                tracing::warn!("Ignoring recall half-life '{}'", pair.trim());
+                continue;
+            };
+            let days = days.trim();
+            let half_life = if days.eq_ignore_ascii_case("off") {
+                None
+            } else {
+                match days.parse::<f64>() {
+                    Ok(days) if days > 0.0 && days.is_finite() => Some(days),
+                    Ok(0.0) => None,
+                    _ => {
+                        tracing::warn!("Ignoring recall half-life '{}'", pair.trim());
+                        continue;
+                    }
+                }
+            };
+            half_lives.insert(profile.trim().to_lowercase(), half_life);
+        }
+        Self { half_lives }
+    }
+
+    /// Half-life for a request: an explicit `half_life_days` (0 disables decay) wins over the profile
+    pub fn half_life(&self, profile: Option<&str>, half_life_days: Option<f64>) -> Result<Option<f64>> {
+        if let Some(days) = half_life_days {
+            if !days.is_finite() || days < 0.0 {
+                return Err(UnifiedIntelligenceError::Validation {
+                    field: "half_life_days".to_string(),
+                    reason: format!("Half-life must be a positive number of days (0 disables decay), got {}", days),
+                });
+            }
+            return Ok((days > 0.0).then_some(days));
+        }
+        let profile = profile.map(|p| p.trim().to_lowercase()).filter(|p| !p.is_empty()).unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+        self.half_lives.get(&profile).copied().ok_or_else(|| {
+            let mut known: Vec<&str> = self.half_lives.keys().map(String::as_str).collect();
+            known.sort_unstable();
+            UnifiedIntelligenceError::Validation {
+                field: "profile".to_string(),
+                reason: format!("Unknown ranking profile '{}'. Use one of: {}", profile, known.join(", ")),
+            }
+        })
+    }
+}
+
+/// Days since an RFC 3339 timestamp
+pub fn age_days(timestamp: &str) -> Option<f64> {
+    let created = chrono::DateTime::parse_from_rfc3339(timestamp).ok()?;
+    let age = chrono::Utc::now().signed_duration_since(created);
+    Some(age.num_seconds().max(0) as f64 / 86_400.0)
+}
+
+/// Weight of a thought of the given age, 1.0 when new and 0.5 one half-life later
+pub fn decay_factor(age_days: f64, half_life_days: f64) -> f32 {
+    0.5f64.powf(age_days.max(0.0) / half_life_days) as f32
+}
+
+/// Score after decay; negative scores (net downvoted) move further down rather than towards zero
+pub fn decayed(score: f32, factor: f32) -> f32 {
+    if score >= 0.0 { score * factor } else { score / factor.max(f32::EPSILON) }
+}
+
+/// Score before decay, for explanations
+pub fn undecayed(score: f32, factor: f32) -> f32 {
+    if score >= 0.0 { score / factor.max(f32::EPSILON) } else { score * factor }
+}
+
+/// Decay every result's score by its age and re-rank, keeping the retrieval order among equal scores.
+/// Semantic results decay their similarity; text results decay 1.0 plus any feedback boost.
+pub fn apply(thoughts: &mut [ThoughtRecord], half_life_days: f64, semantic: bool) {
+    for thought in thoughts.iter_mut() {
+        let factor = age_days(&thought.timestamp).map(|age| decay_factor(age, half_life_days)).unwrap_or(1.0);
+        let score = thought.similarity.unwrap_or(0.0) + if semantic { 0.0 } else { TEXT_BASE_SCORE };
+        thought.similarity = Some(decayed(score, factor));
+    }
+    thoughts.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap_or(std::cmp::Ordering::Equal));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thought(text: &str, days_old: i64, similarity: Option<f32>) -> ThoughtRecord {
+        let mut thought = ThoughtRecord::new("CC".to_string(), text.to_string(), 1, 1, None, false);
+        thought.timestamp = (chrono::Utc::now() - chrono::Duration::days(days_old)).to_rfc3339();
+        thought.similarity = similarity;
+        thought
+    }
+
+    #[test]
+    fn test_profiles() {
+        let profiles = DecayProfiles::parse("operational=3, research=off, incident=0.5, bogus, broken=-1");
+        assert_eq!(profiles.half_life(None, None).unwrap(), None);
+        assert_eq!(profiles.half_life(Some("Operational"), None).unwrap(), Some(3.0));
+        assert_eq!(profiles.half_life(Some("incident"), None).unwrap(), Some(0.5));
+        assert_eq!(profiles.half_life(Some("archival"), None).unwrap(), None);
+        assert_eq!(profiles.half_life(Some("research"), Some(14.0)).unwrap(), Some(14.0));
+        assert_eq!(profiles.half_life(Some("operational"), Some(0.0)).unwrap(), None);
+        assert!(profiles.half_life(Some("broken"), None).is_err());
+        assert!(profiles.half_life(None, Some(-2.0)).is_err());
+    }
+
+    #[test]
+    fn test_apply_reranks_by_age() {
+        assert!((decay_factor(7.0, 7.0) - 0.5).abs() < 1e-6);
+        assert!(decayed(-0.2, 0.5) < -0.2);
+        assert!((undecayed(decayed(0.8, 0.25), 0.25) - 0.8).abs() < 1e-6);
+
+        let mut semantic = vec![
+            thought("stale but similar", 30, Some(0.9)),
+            thought("fresh", 0, Some(0.6)),
+            thought("week old", 7, Some(0.8)),
+        ];
+        apply(&mut semantic, 7.0, true);
+        let order: Vec<&str> = semantic.iter().map(|t| t.thought.as_str()).collect();
+        assert_eq!(order, vec!["fresh", "week old", "stale but similar"]);
+
+        let mut text = vec![thought("old match", 10, None), thought("new match", 1, Some(0.0))];
+        apply(&mut text, 7.0, false);
+        assert_eq!(text[0].thought, "new match");
+        assert!(text[0].similarity.unwrap() < 1.0);
+    }
+}
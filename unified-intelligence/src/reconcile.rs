@@ -0,0 +1,118 @@
+//! Reconcile client-provided thought numbering with what a chain holds.
+//!
+//! `thought_number` and `total_thoughts` come from the client and drift:
+//! chains run past their planned total, skip numbers or restate a smaller
+//! plan than they already have. Thoughts are stored as given, but the
+//! chain's metadata keeps the server's view: `thought_count` is extended to
+//! the largest of the latest plan, the highest thought number and the number
+//! of distinct thoughts stored (the client's first plan is kept in
+//! `planned_thoughts`), `observed_thoughts` counts what was stored and
+//! `last_updated` is the newest stored thought's timestamp, which orders
+//! ui_list_chains. Mismatches are returned from ui_think and listed by
+//! ui_chain_stats.
+
+use std::collections::BTreeSet;
+
+use crate::models::{ChainMetadata, ThoughtRecord};
+
+/// Mismatches of one thought against the thoughts before it in its chain
+pub fn flags(thought_number: i32, total_thoughts: i32, previous: &[&ThoughtRecord]) -> Vec<String> {
+    let mut flags = Vec::new();
+    if thought_number > total_thoughts {
+        flags.push(format!("thought {} exceeds its total_thoughts of {}", thought_number, total_thoughts));
+    }
+    let highest = previous.iter().map(|t| t.thought_number).max().unwrap_or(0);
+    if thought_number > highest + 1 {
+        flags.push(format!("thought {} skips {} number(s) after thought {}", thought_number, thought_number - highest - 1, highest));
+    }
+    let distinct = previous.iter().map(|t| t.thought_number).chain(std::iter::once(thought_number)).collect::<BTreeSet<_>>().len() as i32;
+    if thought_number <= total_thoughts && total_thoughts < distinct {
+        flags.push(format!("total_thoughts of {} is below the {} thoughts already in the chain", total_thoughts, distinct));
+    }
+    flags
+}
+
+/// Mismatches across a whole chain, in the order the thoughts were stored
+pub fn audit(thoughts: &[ThoughtRecord]) -> Vec<String> {
+    let mut ordered: Vec<&ThoughtRecord> = thoughts.iter().collect();
+    ordered.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.thought_number.cmp(&b.thought_number)));
+    (0..ordered.len())
+        .flat_map(|index| flags(ordered[index].thought_number, ordered[index].total_thoughts, &ordered[..index]))
+        .collect()
+}
+
+/// Chain length the thoughts support: the latest plan, the highest number or the distinct count, whichever is largest
+pub fn reconciled_total(thoughts: &[ThoughtRecord]) -> i32 {
+    let latest_plan = thoughts.iter().max_by(|a, b| a.timestamp.cmp(&b.timestamp)).map(|t| t.total_thoughts).unwrap_or(0);
+    let highest = thoughts.iter().map(|t| t.thought_number).max().unwrap_or(0);
+    let distinct = thoughts.iter().map(|t| t.thought_number).collect::<BTreeSet<_>>().len() as i32;
+    latest_plan.max(highest).max(distinct)
+}
+
+/// Timestamp of the newest thought
+pub fn last_updated(thoughts: &[ThoughtRecord]) -> Option<String> {
+    thoughts.iter().map(|t| &t.timestamp).max().cloned()
+}
+
+/// Bring chain metadata in line with its stored thoughts; returns the previous total when it was extended
+pub fn apply(metadata: &mut ChainMetadata, thoughts: &[ThoughtRecord]) -> Option<i32> {
+    metadata.observed_thoughts = Some(thoughts.len() as i32);
+    metadata.last_updated = last_updated(thoughts).or(metadata.last_updated.take());
+    let total = reconciled_total(thoughts);
+    if total <= metadata.thought_count {
+        return None;
+    }
+    let previous = metadata.thought_count;
+    metadata.planned_thoughts.get_or_insert(previous);
+    metadata.thought_count = total;
+    Some(previous)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thought(number: i32, total: i32, minutes: i64) -> ThoughtRecord {
+        let mut thought = ThoughtRecord::new("CC".to_string(), format!("step {}", number), number, total, Some("c1".to_string()), true);
+        thought.timestamp = (chrono::Utc::now() + chrono::Duration::minutes(minutes)).to_rfc3339();
+        thought
+    }
+
+    #[test]
+    fn test_audit_flags_mismatches() {
+        let chain = vec![thought(1, 3, 0), thought(2, 3, 1), thought(5, 3, 2), thought(3, 2, 3)];
+        let flags = audit(&chain);
+        assert_eq!(flags, vec![
+            "thought 5 exceeds its total_thoughts of 3".to_string(),
+            "thought 5 skips 2 number(s) after thought 2".to_string(),
+            "thought 3 exceeds its total_thoughts of 2".to_string(),
+        ]);
+        let restated = vec![thought(1, 3, 0), thought(2, 3, 1), thought(3, 3, 2), thought(2, 2, 3)];
+        assert_eq!(audit(&restated), vec!["total_thoughts of 2 is below the 3 thoughts already in the chain".to_string()]);
+        assert!(audit(&chain[..2]).is_empty());
+    }
+
+    #[test]
+    fn test_apply_extends_total() {
+        let mut metadata = ChainMetadata {
+            chain_id: "c1".to_string(),
+            created_at: String::new(),
+            thought_count: 3,
+            instance: "CC".to_string(),
+            user_id: None,
+            related: Vec::new(),
+            planned_thoughts: None,
+            observed_thoughts: None,
+            last_updated: None,
+            next_hints: None,
+        };
+        let started = vec![thought(2, 3, 1), thought(1, 3, 0)];
+        assert_eq!(apply(&mut metadata, &started), None);
+        assert_eq!((metadata.thought_count, metadata.observed_thoughts), (3, Some(2)));
+        assert_eq!(metadata.last_updated.as_ref(), Some(&started[0].timestamp));
+
+        let overrun = vec![thought(1, 3, 0), thought(2, 3, 1), thought(3, 3, 2), thought(4, 3, 3), thought(5, 3, 4)];
+        assert_eq!(apply(&mut metadata, &overrun), Some(3));
+        assert_eq!((metadata.thought_count, metadata.planned_thoughts, metadata.observed_thoughts), (5, Some(3), Some(5)));
+    }
+}
@@ -0,0 +1,234 @@
+//! Privacy redaction for exports.
+//!
+//! Every export (vault notes, HTML reports, training JSONL, feeds, persona
+//! and chain bundles) runs thought text through a Redactor at a privacy
+//! level: `off` exports text as stored, `pii` replaces what the PII scanner
+//! finds with `[REDACTED:<kind>]`, and `strict` also withholds the whole text
+//! of thoughts tagged `private`, leaving `[REDACTED:private]`. Exports written
+//! to a file get a manifest next to them (`<file>.redactions.json`) listing
+//! each redaction by thought, kind and byte range in the stored text, so a
+//! reader can tell what was removed without seeing it. Stored thoughts are
+//! never changed.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::{ThoughtMetadata, ThoughtRecord};
+use crate::pii::{PiiPolicy, PiiScanner};
+
+/// Tag that withholds a thought's text from strict exports
+pub const PRIVATE_TAG: &str = "private";
+
+/// Suffix of the manifest written next to an export file
+pub const MANIFEST_SUFFIX: &str = ".redactions.json";
+
+/// How much an export redacts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivacyLevel {
+    Off,
+    Pii,
+    Strict,
+}
+
+impl PrivacyLevel {
+    /// Level from a tool parameter, or `default` when none is given
+    pub fn parse(value: Option<&str>, default: PrivacyLevel) -> Result<Self> {
+        match value.map(|v| v.trim().to_lowercase()).as_deref() {
+            None | Some("") => Ok(default),
+            Some("off") | Some("none") => Ok(PrivacyLevel::Off),
+            Some("pii") => Ok(PrivacyLevel::Pii),
+            Some("strict") => Ok(PrivacyLevel::Strict),
+            Some(other) => Err(UnifiedIntelligenceError::Validation {
+                field: "privacy_level".to_string(),
+                reason: format!("Unknown privacy level '{}'. Use 'off', 'pii' or 'strict'", other),
+            }),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PrivacyLevel::Off => "off",
+            PrivacyLevel::Pii => "pii",
+            PrivacyLevel::Strict => "strict",
+        }
+    }
+}
+
+/// One span removed from an export
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Redaction {
+    pub thought_id: String,
+    pub kind: String,                     // PII kind, or "private" for a withheld thought
+    pub start: usize,                     // Byte range in the stored text
+    pub end: usize,
+    pub placeholder: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,          // Partially masked value from the PII scanner
+}
+
+/// What an export redacted, written next to the export file
+#[derive(Debug, Clone, Serialize)]
+pub struct RedactionManifest {
+    pub export: String,                   // Tool that wrote the export
+    pub output: String,
+    pub generated_at: String,
+    pub privacy_level: PrivacyLevel,
+    pub masked_pii: usize,
+    pub withheld_thoughts: Vec<String>,
+    pub redactions: Vec<Redaction>,
+}
+
+/// Redacts thought text at one privacy level and records what it removed
+pub struct Redactor {
+    level: PrivacyLevel,
+    scanner: PiiScanner,
+    private: HashSet<String>,
+    redactions: Vec<Redaction>,
+}
+
+impl Redactor {
+    pub fn new(level: PrivacyLevel) -> Self {
+        let policy = if level == PrivacyLevel::Off { PiiPolicy::Off } else { PiiPolicy::Mask };
+        Self { level, scanner: PiiScanner::new(policy, None), private: HashSet::new(), redactions: Vec::new() }
+    }
+
+    pub fn level(&self) -> PrivacyLevel {
+        self.level
+    }
+
+    /// Whether thoughts must be marked private from their metadata before redacting
+    pub fn needs_metadata(&self) -> bool {
+        self.level == PrivacyLevel::Strict
+    }
+
+    /// Withhold the thought's text if its metadata tags it private
+    pub fn observe(&mut self, metadata: &ThoughtMetadata) {
+        if metadata.tags.as_ref().is_some_and(|tags| tags.iter().any(|tag| tag == PRIVATE_TAG)) {
+            self.private.insert(metadata.thought_id.clone());
+        }
+    }
+
+    /// Text of a thought as it may be exported
+    pub fn text(&mut self, thought_id: &str, text: &str) -> String {
+        if self.level == PrivacyLevel::Off {
+            return text.to_string();
+        }
+        if self.level == PrivacyLevel::Strict && self.private.contains(thought_id) {
+            let placeholder = format!("[REDACTED:{}]", PRIVATE_TAG);
+            self.redactions.push(Redaction {
+                thought_id: thought_id.to_string(),
+                kind: PRIVATE_TAG.to_string(),
+                start: 0,
+                end: text.len(),
+                placeholder: placeholder.clone(),
+                preview: None,
+            });
+            return placeholder;
+        }
+        let findings = self.scanner.scan(text);
+        for finding in &findings {
+            self.redactions.push(Redaction {
+                thought_id: thought_id.to_string(),
+                kind: finding.kind.clone(),
+                start: finding.start,
+                end: finding.end,
+                placeholder: format!("[REDACTED:{}]", finding.kind),
+                preview: Some(finding.preview.clone()),
+            });
+        }
+        PiiScanner::mask(text, &findings)
+    }
+
+    /// Redact exported copies of thoughts in place
+    pub fn thoughts(&mut self, thoughts: &mut [ThoughtRecord]) {
+        for thought in thoughts {
+            thought.thought = self.text(&thought.id, &thought.thought);
+        }
+    }
+
+    /// PII spans replaced so far
+    pub fn masked_pii(&self) -> usize {
+        self.redactions.iter().filter(|r| r.kind != PRIVATE_TAG).count()
+    }
+
+    /// Thoughts withheld so far, in the order they were redacted
+    pub fn withheld(&self) -> Vec<String> {
+        let mut withheld: Vec<String> = Vec::new();
+        for redaction in self.redactions.iter().filter(|r| r.kind == PRIVATE_TAG) {
+            if !withheld.contains(&redaction.thought_id) {
+                withheld.push(redaction.thought_id.clone());
+            }
+        }
+        withheld
+    }
+
+    pub fn manifest(&self, export: &str, output: &str) -> RedactionManifest {
+        RedactionManifest {
+            export: export.to_string(),
+            output: output.to_string(),
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            privacy_level: self.level,
+            masked_pii: self.masked_pii(),
+            withheld_thoughts: self.withheld(),
+            redactions: self.redactions.clone(),
+        }
+    }
+
+    /// Write the manifest next to `output`; nothing is written when redaction is off
+    pub fn write_manifest(&self, export: &str, output: &Path) -> Result<Option<PathBuf>> {
+        if self.level == PrivacyLevel::Off {
+            return Ok(None);
+        }
+        let path = manifest_path(output);
+        let manifest = self.manifest(export, &output.display().to_string());
+        std::fs::write(&path, serde_json::to_string_pretty(&manifest)?).map_err(|e| UnifiedIntelligenceError::Validation {
+            field: "output_path".to_string(),
+            reason: format!("could not write {}: {}", path.display(), e),
+        })?;
+        Ok(Some(path))
+    }
+}
+
+/// Manifest path for an export file
+pub fn manifest_path(output: &Path) -> PathBuf {
+    let mut path = output.as_os_str().to_owned();
+    path.push(MANIFEST_SUFFIX);
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_redact_and_record() {
+        let text = "Mail ops@example.com about the outage";
+        let mut off = Redactor::new(PrivacyLevel::Off);
+        assert_eq!(off.text("t1", text), text);
+        assert_eq!(off.masked_pii(), 0);
+
+        let mut pii = Redactor::new(PrivacyLevel::Pii);
+        assert_eq!(pii.text("t1", text), "Mail [REDACTED:email] about the outage");
+        let manifest = pii.manifest("ui_report", "report.html");
+        assert_eq!((manifest.masked_pii, manifest.redactions[0].start, manifest.redactions[0].end), (1, 5, 20));
+
+        // Strict withholds private thoughts whole and masks the rest
+        let mut strict = Redactor::new(PrivacyLevel::Strict);
+        strict.observe(&ThoughtMetadata::new("t1".to_string(), "CC".to_string(), None, None, Some(vec![PRIVATE_TAG.to_string()]), None));
+        assert_eq!(strict.text("t1", text), "[REDACTED:private]");
+        assert_eq!(strict.text("t2", text), "Mail [REDACTED:email] about the outage");
+        assert_eq!((strict.masked_pii(), strict.withheld()), (1, vec!["t1".to_string()]));
+    }
+
+    #[test]
+    fn test_parse_and_manifest_path() {
+        assert_eq!(PrivacyLevel::parse(None, PrivacyLevel::Pii).unwrap(), PrivacyLevel::Pii);
+        assert_eq!(PrivacyLevel::parse(Some("Strict"), PrivacyLevel::Off).unwrap(), PrivacyLevel::Strict);
+        assert!(PrivacyLevel::parse(Some("high"), PrivacyLevel::Off).is_err());
+        assert_eq!(manifest_path(Path::new("out/chain.json")), PathBuf::from("out/chain.json.redactions.json"));
+    }
+}
@@ -4,14 +4,19 @@ use std::time::Duration;
 use std::future::Future;
 use deadpool_redis::{Config, Runtime, Pool};
 use deadpool::{managed::{PoolConfig, Timeouts, QueueMode}};
-use redis::{AsyncCommands, JsonAsyncCommands, Script};
+use redis::{AsyncCommands, ErrorKind, FromRedisValue, JsonAsyncCommands};
 use tracing;
 use sha2::{Sha256, Digest};
 use tokio::time::timeout;
 use chrono;
 
+use crate::capabilities::{self, Capabilities};
 use crate::error::{Result, UnifiedIntelligenceError};
-use crate::lua_scripts::{self, LoadedScripts};
+use crate::lua_scripts::{self, LoadedScripts, ScriptKind};
+use crate::models::{MemoryUsage, PoolStats};
+use crate::memory_guard;
+use crate::keys;
+use crate::search_index;
 
 /// Default TTL for all Redis writes (7 days in seconds)
 const DEFAULT_TTL_SECONDS: i64 = 604800;
@@ -21,6 +26,10 @@ const DEFAULT_TTL_SECONDS: i64 = 604800;
 pub struct RedisManager {
     pool: Arc<Pool>,
     scripts: Arc<tokio::sync::RwLock<LoadedScripts>>,
+    redis_url: String,
+    db: u32,
+    capabilities: Capabilities,
+    modules: Option<Vec<String>>,
 }
 
 impl RedisManager {
@@ -72,15 +81,34 @@ impl RedisManager {
         let _: String = redis::cmd("PING").query_async(&mut conn).await?;
         tracing::info!("Redis connection established");
         
+        // Detect modules so features without them take their fallback paths
+        let (capabilities, modules) = Self::detect_capabilities(&mut conn).await;
+        capabilities.log();
+        drop(conn);
+        
         // Create instance with empty scripts for now
         let instance = Self {
             pool: Arc::new(pool),
             scripts: Arc::new(tokio::sync::RwLock::new(LoadedScripts::new())),
+            redis_url,
+            db: redis_db.parse().unwrap_or(0),
+            capabilities,
+            modules,
         };
         
         // Load Lua scripts
         instance.load_scripts().await?;
         
+        // Validate scripts at startup: UI_SCRIPT_SELF_TEST=off|warn|strict (default warn)
+        match env::var("UI_SCRIPT_SELF_TEST").unwrap_or_default().to_lowercase().as_str() {
+            "off" | "false" | "0" => {}
+            mode => match instance.self_test_scripts().await {
+                Ok(()) => tracing::info!("Lua script self-test passed"),
+                Err(e) if mode == "strict" => return Err(e),
+                Err(e) => tracing::warn!("{}", e),
+            },
+        }
+        
         Ok(instance)
     }
     
@@ -94,6 +122,71 @@ impl RedisManager {
         &self.pool
     }
     
+    /// Database number the pool is connected to
+    pub fn db(&self) -> u32 {
+        self.db
+    }
+    
+    /// Redis modules detected at startup
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+    
+    /// Module names from MODULE LIST, None if the command was refused
+    pub fn modules(&self) -> Option<&[String]> {
+        self.modules.as_deref()
+    }
+    
+    /// Current connection pool usage
+    pub fn pool_stats(&self) -> PoolStats {
+        let status = self.pool.status();
+        PoolStats {
+            max_size: status.max_size,
+            size: status.size,
+            available: status.available,
+            waiting: status.waiting,
+        }
+    }
+    
+    /// Read MODULE LIST, assuming every module is present if the command is refused (e.g. by ACLs)
+    async fn detect_capabilities(conn: &mut deadpool_redis::Connection) -> (Capabilities, Option<Vec<String>>) {
+        let reply: std::result::Result<redis::Value, _> = redis::cmd("MODULE")
+            .arg("LIST")
+            .query_async(&mut **conn)
+            .await;
+        
+        match reply {
+            Ok(reply) => {
+                let names = capabilities::module_names(&reply);
+                (Capabilities::from_module_names(&names), Some(names))
+            }
+            Err(e) => {
+                tracing::warn!("MODULE LIST failed ({}), assuming all Redis modules are available", e);
+                (Capabilities::all(), None)
+            }
+        }
+    }
+    
+    /// Memory figures from INFO memory
+    pub async fn memory_info(&self) -> Result<MemoryUsage> {
+        let mut conn = self.get_connection().await?;
+        let info: String = redis::cmd("INFO").arg("memory").query_async(&mut *conn).await?;
+        Ok(memory_guard::parse_info(&info))
+    }
+    
+    /// Publish a message on a pub/sub channel
+    pub async fn publish(&self, channel: &str, message: &str) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        conn.publish::<_, _, ()>(channel, message).await?;
+        Ok(())
+    }
+    
+    /// Open a dedicated pub/sub connection (pooled connections can't subscribe)
+    pub async fn pubsub(&self) -> Result<redis::aio::PubSub> {
+        let client = redis::Client::open(self.redis_url.as_str())?;
+        Ok(client.get_async_pubsub().await?)
+    }
+    
     /// Store API key in Redis for secure access
     pub async fn store_api_key(&self, key_name: &str, api_key: &str) -> Result<()> {
         let mut conn = self.get_connection().await?;
@@ -120,33 +213,115 @@ impl RedisManager {
         Ok(result)
     }
     
-    /// Create search index for thoughts
-    pub async fn create_search_index(&self) -> Result<bool> {
+    /// Key prefixes registered for the thought search index, seeded with the defaults
+    pub async fn search_prefixes(&self) -> Result<Vec<String>> {
+        let mut conn = self.get_connection().await?;
+        let mut prefixes: Vec<String> = conn.smembers(search_index::PREFIX_REGISTRY_KEY).await?;
+        
+        if prefixes.is_empty() {
+            prefixes = search_index::DEFAULT_INSTANCES.iter().map(|i| search_index::thought_prefix(i)).collect();
+            conn.sadd::<_, _, ()>(search_index::PREFIX_REGISTRY_KEY, &prefixes).await?;
+        }
+        
+        prefixes.sort();
+        Ok(prefixes)
+    }
+    
+    /// Register a key prefix for the thought search index, returning true if it was new
+    pub async fn add_search_prefix(&self, prefix: &str) -> Result<bool> {
+        // Seed the defaults first so registering doesn't hide them
+        self.search_prefixes().await?;
+        
         let mut conn = self.get_connection().await?;
+        let added: i64 = conn.sadd(search_index::PREFIX_REGISTRY_KEY, prefix).await?;
+        Ok(added > 0)
+    }
+    
+    /// FT.INFO reply for the thought search index, or None if it doesn't exist
+    async fn thoughts_index_info(&self) -> Result<Option<redis::Value>> {
+        if !self.capabilities.search {
+            return Ok(None);
+        }
         
-        // Check if index already exists
-        let index_exists: std::result::Result<Vec<String>, _> = redis::cmd("FT._LIST")
+        let mut conn = self.get_connection().await?;
+        
+        let info: std::result::Result<redis::Value, _> = redis::cmd("FT.INFO")
+            .arg(search_index::THOUGHTS_INDEX)
             .query_async(&mut *conn)
             .await;
         
-        if let Ok(indices) = index_exists {
-            if indices.contains(&"idx:thoughts".to_string()) {
-                tracing::info!("Search index already exists");
-                return Ok(true);
+        match info {
+            Ok(info) => Ok(Some(info)),
+            Err(e) if e.kind() == ErrorKind::ResponseError => Ok(None), // Unknown index name
+            Err(e) => Err(e.into()),
+        }
+    }
+    
+    /// Prefixes the thought search index currently covers, or None if it doesn't exist
+    pub async fn indexed_prefixes(&self) -> Result<Option<Vec<String>>> {
+        Ok(self.thoughts_index_info().await?.map(|info| search_index::prefixes_from_info(&info)))
+    }
+    
+    /// Create search index for thoughts, rebuilding it if registered prefixes aren't covered
+    /// or its field weights changed
+    pub async fn create_search_index(&self) -> Result<bool> {
+        if !self.capabilities.search_index() {
+            tracing::info!("Skipping search index creation: RediSearch and RedisJSON are both required");
+            return Ok(false);
+        }
+        
+        let prefixes = self.search_prefixes().await?;
+        
+        // FT.INFO fails when RediSearch isn't loaded; FT.CREATE below reports that
+        if let Ok(Some(info)) = self.thoughts_index_info().await {
+            let indexed = search_index::prefixes_from_info(&info);
+            let missing = search_index::uncovered(&prefixes, &indexed);
+            if !missing.is_empty() {
+                tracing::info!("Search index is missing prefixes {:?}, rebuilding", missing);
+                return self.rebuild_search_index().await;
             }
+            if !search_index::schema_current(&info, &search_index::FieldWeights::from_env()) {
+                tracing::info!("Search index fields or weights changed, rebuilding");
+                return self.rebuild_search_index().await;
+            }
+            tracing::info!("Search index already exists");
+            return Ok(true);
+        }
+        
+        self.ft_create_thoughts_index(&prefixes).await
+    }
+    
+    /// Drop (keeping documents) and recreate the thought index over every registered prefix
+    pub async fn rebuild_search_index(&self) -> Result<bool> {
+        if !self.capabilities.search_index() {
+            return Ok(false);
+        }
+        
+        let prefixes = self.search_prefixes().await?;
+        let mut conn = self.get_connection().await?;
+        
+        let dropped: std::result::Result<String, _> = redis::cmd("FT.DROPINDEX")
+            .arg(search_index::THOUGHTS_INDEX)
+            .query_async(&mut *conn)
+            .await;
+        if let Err(e) = dropped {
+            tracing::debug!("FT.DROPINDEX before rebuild: {}", e);
         }
         
+        self.ft_create_thoughts_index(&prefixes).await
+    }
+    
+    async fn ft_create_thoughts_index(&self, prefixes: &[String]) -> Result<bool> {
+        let mut conn = self.get_connection().await?;
+        
         // Create the index on JSON fields
-        // Updated to handle multiple instance prefixes with proper pattern
         let result: std::result::Result<String, _> = redis::cmd("FT.CREATE")
-            .arg("idx:thoughts")
+            .arg(search_index::THOUGHTS_INDEX)
             .arg("ON").arg("JSON")
-            .arg("PREFIX").arg("3")
-            .arg("Claude:Thoughts:")
-            .arg("CC:Thoughts:")
-            .arg("CCI:Thoughts:")
+            .arg("PREFIX").arg(prefixes.len())
+            .arg(prefixes)
             .arg("SCHEMA")
-            .arg("$.thought").arg("AS").arg("content").arg("TEXT")
+            .arg(search_index::FieldWeights::from_env().schema_args())
             .arg("$.instance").arg("AS").arg("instance").arg("TAG")
             .arg("$.chain_id").arg("AS").arg("chain_id").arg("TAG")
             .arg("$.timestamp").arg("AS").arg("timestamp").arg("TEXT")
@@ -155,7 +330,7 @@ impl RedisManager {
         
         match result {
             Ok(_) => {
-                tracing::info!("Search index created successfully");
+                tracing::info!("Search index created successfully over {} prefixes", prefixes.len());
                 Ok(true)
             }
             Err(e) => {
@@ -173,7 +348,13 @@ impl RedisManager {
         value: &T,
     ) -> Result<()> {
         let mut conn = self.get_connection().await?;
-        conn.json_set::<_, _, _, ()>(key, path, value).await?;
+        if self.capabilities.json {
+            conn.json_set::<_, _, _, ()>(key, path, value).await?;
+        } else {
+            // Without RedisJSON the whole document is a string; only root paths are used
+            let json = serde_json::to_string(value)?;
+            conn.set::<_, _, ()>(key, json).await?;
+        }
         
         // Set TTL for the key (7 days)
         conn.expire::<_, ()>(key, DEFAULT_TTL_SECONDS).await?;
@@ -188,6 +369,12 @@ impl RedisManager {
     ) -> Result<Option<T>> {
         let mut conn = self.get_connection().await?;
         
+        if !self.capabilities.json {
+            // Plain string document (root paths only)
+            let result: Option<String> = conn.get(key).await?;
+            return result.map(|json_str| serde_json::from_str(&json_str)).transpose().map_err(Into::into);
+        }
+        
         // Use raw command to handle RedisJSON response
         let result: Option<String> = redis::cmd("JSON.GET")
             .arg(key)
@@ -226,6 +413,11 @@ impl RedisManager {
     pub async fn json_del(&self, key: &str, path: &str) -> Result<()> {
         let mut conn = self.get_connection().await?;
         
+        if !self.capabilities.json {
+            conn.del::<_, ()>(key).await?;
+            return Ok(());
+        }
+        
         redis::cmd("JSON.DEL")
             .arg(key)
             .arg(path)
@@ -248,6 +440,68 @@ impl RedisManager {
         Ok(())
     }
     
+    /// Delete many keys in batches, returning how many were removed
+    pub async fn del_many(&self, keys: &[String]) -> Result<usize> {
+        let mut conn = self.get_connection().await?;
+        let mut removed = 0;
+        for batch in keys.chunks(500) {
+            let count: usize = conn.del(batch).await?;
+            removed += count;
+        }
+        Ok(removed)
+    }
+    
+    /// Set a string value without expiration
+    pub async fn set(&self, key: &str, value: &str) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        conn.set::<_, _, ()>(key, value).await?;
+        Ok(())
+    }
+    
+    /// Set a string value with expiration
+    pub async fn set_ex(&self, key: &str, value: &str, ttl_seconds: u64) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        conn.set_ex::<_, _, ()>(key, value, ttl_seconds).await?;
+        Ok(())
+    }
+    
+    /// Set a value with expiration only if the key doesn't exist; false when it does
+    pub async fn set_nx_ex(&self, key: &str, value: &str, ttl_seconds: u64) -> Result<bool> {
+        let mut conn = self.get_connection().await?;
+        let reply: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(value)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl_seconds)
+            .query_async(&mut *conn)
+            .await?;
+        Ok(reply.is_some())
+    }
+    
+    /// Delete a key only if it still holds `value`
+    pub async fn del_if_eq(&self, key: &str, value: &str) -> Result<bool> {
+        let mut conn = self.get_connection().await?;
+        let deleted: i64 = redis::cmd("EVAL")
+            .arg("if redis.call('GET', KEYS[1]) == ARGV[1] then return redis.call('DEL', KEYS[1]) else return 0 end")
+            .arg(1)
+            .arg(key)
+            .arg(value)
+            .query_async(&mut *conn)
+            .await?;
+        Ok(deleted > 0)
+    }
+    
+    /// Get a string value and delete the key atomically
+    pub async fn get_del(&self, key: &str) -> Result<Option<String>> {
+        let mut conn = self.get_connection().await?;
+        let value: Option<String> = redis::cmd("GETDEL")
+            .arg(key)
+            .query_async(&mut *conn)
+            .await?;
+        Ok(value)
+    }
+    
     // NOTE: The dangerous keys() method has been removed to prevent blocking operations.
     // Use scan_match() instead for pattern matching, which is non-blocking and production-safe.
     
@@ -257,6 +511,47 @@ impl RedisManager {
         Ok(conn.get(key).await?)
     }
     
+    /// Increment a counter, returning the new value
+    pub async fn incr(&self, key: &str) -> Result<u64> {
+        let mut conn = self.get_connection().await?;
+        Ok(conn.incr(key, 1).await?)
+    }
+    
+    /// Prepend to a list, keeping only the newest `max_len` entries
+    pub async fn lpush_capped(&self, key: &str, value: &str, max_len: usize) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .lpush(key, value).ignore()
+            .ltrim(key, 0, max_len as isize - 1).ignore()
+            .expire(key, DEFAULT_TTL_SECONDS).ignore();
+        pipe.query_async::<()>(&mut *conn).await?;
+        Ok(())
+    }
+    
+    /// Prepend to a list kept without a TTL, keeping only the newest `max_len` entries
+    pub async fn lpush_capped_persistent(&self, key: &str, values: &[String], max_len: usize) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .lpush(key, values).ignore()
+            .ltrim(key, 0, max_len as isize - 1).ignore();
+        pipe.query_async::<()>(&mut *conn).await?;
+        Ok(())
+    }
+    
+    /// Get a range of a list
+    pub async fn lrange(&self, key: &str, start: isize, stop: isize) -> Result<Vec<String>> {
+        let mut conn = self.get_connection().await?;
+        Ok(conn.lrange(key, start, stop).await?)
+    }
+    
+    /// Remove every occurrence of a value from a list, returning how many were removed
+    pub async fn lrem(&self, key: &str, value: &str) -> Result<usize> {
+        let mut conn = self.get_connection().await?;
+        Ok(conn.lrem(key, 0, value).await?)
+    }
+    
     /// Increment a value in a sorted set
     pub async fn zadd(&self, key: &str, member: &str, score: f64) -> Result<()> {
         let mut conn = self.get_connection().await?;
@@ -267,6 +562,13 @@ impl RedisManager {
         Ok(())
     }
     
+    /// Remove a member from a sorted set
+    pub async fn zrem(&self, key: &str, member: &str) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        conn.zrem::<_, _, ()>(key, member).await?;
+        Ok(())
+    }
+    
     /// Get members of a sorted set
     pub async fn zrange(&self, key: &str, start: isize, stop: isize) -> Result<Vec<String>> {
         let mut conn = self.get_connection().await?;
@@ -283,6 +585,96 @@ impl RedisManager {
         Ok(())
     }
     
+    /// Add member to a set without a TTL; true if it was not already a member
+    pub async fn sadd_new(&self, key: &str, member: &str) -> Result<bool> {
+        let mut conn = self.get_connection().await?;
+        let added: i64 = conn.sadd(key, member).await?;
+        Ok(added == 1)
+    }
+    
+    /// Remove member from a set
+    pub async fn srem(&self, key: &str, member: &str) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        conn.srem::<_, _, ()>(key, member).await?;
+        Ok(())
+    }
+    
+    /// Atomically set, set-if-missing and increment hash fields, refreshing the key's TTL
+    pub async fn update_hash(
+        &self,
+        key: &str,
+        set: &[(&str, String)],
+        set_if_missing: &[(&str, String)],
+        increments: &[(&str, i64)],
+    ) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        
+        if !set.is_empty() {
+            pipe.hset_multiple(key, set).ignore();
+        }
+        for (field, value) in set_if_missing {
+            pipe.hset_nx(key, *field, value).ignore();
+        }
+        for (field, delta) in increments {
+            pipe.hincr(key, *field, *delta).ignore();
+        }
+        pipe.expire(key, DEFAULT_TTL_SECONDS).ignore();
+        
+        pipe.query_async::<()>(&mut *conn).await?;
+        Ok(())
+    }
+    
+    /// Atomically increment float and integer hash fields, setting the key's TTL
+    pub async fn increment_hash(
+        &self,
+        key: &str,
+        floats: &[(&str, f64)],
+        increments: &[(&str, i64)],
+        ttl_seconds: u64,
+    ) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        
+        for (field, delta) in floats {
+            pipe.cmd("HINCRBYFLOAT").arg(key).arg(*field).arg(*delta).ignore();
+        }
+        for (field, delta) in increments {
+            pipe.hincr(key, *field, *delta).ignore();
+        }
+        pipe.expire(key, ttl_seconds as i64).ignore();
+        
+        pipe.query_async::<()>(&mut *conn).await?;
+        Ok(())
+    }
+    
+    /// Set a hash field without a TTL
+    pub async fn hset(&self, key: &str, field: &str, value: &str) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        conn.hset::<_, _, _, ()>(key, field, value).await?;
+        Ok(())
+    }
+    
+    /// Get a hash field
+    pub async fn hget(&self, key: &str, field: &str) -> Result<Option<String>> {
+        let mut conn = self.get_connection().await?;
+        Ok(conn.hget(key, field).await?)
+    }
+    
+    /// Get the field names of a hash
+    pub async fn hkeys(&self, key: &str) -> Result<Vec<String>> {
+        let mut conn = self.get_connection().await?;
+        Ok(conn.hkeys(key).await?)
+    }
+    
+    /// Get all fields of a hash
+    pub async fn hgetall(&self, key: &str) -> Result<std::collections::HashMap<String, String>> {
+        let mut conn = self.get_connection().await?;
+        Ok(conn.hgetall(key).await?)
+    }
+    
     // ===== BOOST SCORE METHODS (Phase 3) =====
     
     /// Increment score in sorted set (for boost scores)
@@ -352,6 +744,50 @@ impl RedisManager {
         Ok(result)
     }
     
+    /// Delete the stream entries whose `field` equals `value`, returning how many were removed
+    pub async fn xdel_matching(&self, key: &str, field: &str, value: &str) -> Result<usize> {
+        let mut conn = self.get_connection().await?;
+        let entries: Vec<(String, Vec<String>)> = redis::cmd("XRANGE")
+            .arg(key)
+            .arg("-")
+            .arg("+")
+            .query_async(&mut *conn)
+            .await?;
+        let ids: Vec<String> = entries.into_iter()
+            .filter(|(_, fields)| fields.chunks(2).any(|pair| pair.len() == 2 && pair[0] == field && pair[1] == value))
+            .map(|(id, _)| id)
+            .collect();
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let removed: usize = redis::cmd("XDEL").arg(key).arg(&ids).query_async(&mut *conn).await?;
+        Ok(removed)
+    }
+    
+    /// All entries of a stream, oldest first, with their fields as flat name/value pairs
+    pub async fn xrange_all(&self, key: &str) -> Result<Vec<(String, Vec<String>)>> {
+        let mut conn = self.get_connection().await?;
+        let entries: Vec<(String, Vec<String>)> = redis::cmd("XRANGE")
+            .arg(key)
+            .arg("-")
+            .arg("+")
+            .query_async(&mut *conn)
+            .await?;
+        Ok(entries)
+    }
+    
+    /// Remove the stream entries with IDs below `min_id` (XTRIM MINID), returning how many were removed
+    pub async fn xtrim_minid(&self, key: &str, min_id: &str) -> Result<usize> {
+        let mut conn = self.get_connection().await?;
+        let removed: usize = redis::cmd("XTRIM")
+            .arg(key)
+            .arg("MINID")
+            .arg(min_id)
+            .query_async(&mut *conn)
+            .await?;
+        Ok(removed)
+    }
+    
     /// Get intersection of multiple sets (SINTER)
     pub async fn sinter(&self, keys: &[String]) -> Result<Vec<String>> {
         if keys.is_empty() {
@@ -781,53 +1217,128 @@ impl RedisManager {
         let mut conn = self.get_connection().await?;
         let mut scripts = LoadedScripts::new();
         
-        // Load store thought script
-        let store_script = Script::new(lua_scripts::STORE_THOUGHT_SCRIPT);
-        scripts.store_thought = store_script.prepare_invoke()
-            .load_async(&mut *conn)
-            .await
-            .map_err(|e| UnifiedIntelligenceError::Internal(format!("Failed to load store thought script: {}", e)))?;
+        for kind in ScriptKind::ALL {
+            let sha = Self::script_load(&mut conn, kind).await?;
+            scripts.set_sha(kind, sha);
+        }
         
-        // Load get thought script
-        let get_script = Script::new(lua_scripts::GET_THOUGHT_SCRIPT);
-        scripts.get_thought = get_script.prepare_invoke()
-            .load_async(&mut *conn)
-            .await
-            .map_err(|e| UnifiedIntelligenceError::Internal(format!("Failed to load get thought script: {}", e)))?;
+        // Record versions so operators can see which script revisions are live
+        let registry: Vec<(&str, String)> = ScriptKind::ALL.iter()
+            .map(|kind| (kind.name(), format!("v{} {}", kind.version(), scripts.sha(*kind))))
+            .collect();
+        conn.hset_multiple::<_, _, _, ()>(lua_scripts::SCRIPT_REGISTRY_KEY, &registry).await?;
         
-        // Load search thoughts script
-        let search_script = Script::new(lua_scripts::SEARCH_THOUGHTS_SCRIPT);
-        scripts.search_thoughts = search_script.prepare_invoke()
-            .load_async(&mut *conn)
-            .await
-            .map_err(|e| UnifiedIntelligenceError::Internal(format!("Failed to load search thoughts script: {}", e)))?;
+        // Update the scripts in the instance
+        let mut script_store = self.scripts.write().await;
+        *script_store = scripts;
         
-        // Load update chain script
-        let update_chain_script = Script::new(lua_scripts::UPDATE_CHAIN_SCRIPT);
-        scripts.update_chain = update_chain_script.prepare_invoke()
-            .load_async(&mut *conn)
-            .await
-            .map_err(|e| UnifiedIntelligenceError::Internal(format!("Failed to load update chain script: {}", e)))?;
+        tracing::info!("Successfully loaded all Lua scripts");
         
-        // Load get chain thoughts script
-        let get_chain_script = Script::new(lua_scripts::GET_CHAIN_THOUGHTS_SCRIPT);
-        scripts.get_chain_thoughts = get_chain_script.prepare_invoke()
-            .load_async(&mut *conn)
+        Ok(())
+    }
+    
+    /// SCRIPT LOAD a single script, returning its SHA
+    async fn script_load(conn: &mut deadpool_redis::Connection, kind: ScriptKind) -> Result<String> {
+        redis::cmd("SCRIPT")
+            .arg("LOAD")
+            .arg(kind.versioned_source())
+            .query_async(&mut **conn)
             .await
-            .map_err(|e| UnifiedIntelligenceError::Internal(format!("Failed to load get chain thoughts script: {}", e)))?;
+            .map_err(|e| UnifiedIntelligenceError::Internal(format!("Failed to load {} script: {}", kind.name(), e)))
+    }
+    
+    /// Run a script with EVALSHA, reloading it once if Redis has dropped it (NOSCRIPT)
+    async fn eval_script<T: FromRedisValue>(&self, kind: ScriptKind, keys: &[&str], args: &[&str]) -> Result<T> {
+        let mut conn = self.get_connection().await?;
         
-        // Load cleanup expired script
-        let cleanup_script = Script::new(lua_scripts::CLEANUP_EXPIRED_SCRIPT);
-        scripts.cleanup_expired = cleanup_script.prepare_invoke()
-            .load_async(&mut *conn)
-            .await
-            .map_err(|e| UnifiedIntelligenceError::Internal(format!("Failed to load cleanup expired script: {}", e)))?;
+        let script_sha = {
+            let scripts = self.scripts.read().await;
+            scripts.sha(kind).to_string()
+        };
         
-        // Update the scripts in the instance
-        let mut script_store = self.scripts.write().await;
-        *script_store = scripts;
+        let evalsha = |sha: &str| {
+            let mut cmd = redis::cmd("EVALSHA");
+            cmd.arg(sha).arg(keys.len()).arg(keys).arg(args);
+            cmd
+        };
         
-        tracing::info!("Successfully loaded all Lua scripts");
+        match evalsha(&script_sha).query_async(&mut *conn).await {
+            Err(e) if e.kind() == ErrorKind::NoScriptError => {
+                // Script cache was flushed (restart, SCRIPT FLUSH, failover) - reload and retry once
+                tracing::warn!("Lua script {} not in Redis script cache, reloading", kind.name());
+                let sha = Self::script_load(&mut conn, kind).await?;
+                self.scripts.write().await.set_sha(kind, sha.clone());
+                Ok(evalsha(&sha).query_async(&mut *conn).await?)
+            }
+            result => Ok(result?),
+        }
+    }
+    
+    /// Exercise every script against a throwaway key namespace.
+    ///
+    /// Catches scripts that load but fail at runtime (missing Redis modules,
+    /// wrong key layout) at startup instead of on the first tool call. All keys
+    /// written under the namespace are deleted afterwards.
+    pub async fn self_test_scripts(&self) -> Result<()> {
+        let namespace = format!("ui:selftest:{}", uuid::Uuid::new_v4());
+        let result = self.run_script_self_test(&namespace).await;
+        
+        let mut conn = self.get_connection().await?;
+        let keys = self.scan_match(&format!("{}:*", namespace), 100).await?;
+        if !keys.is_empty() {
+            conn.del::<_, ()>(&keys).await?;
+        }
+        
+        result
+    }
+    
+    async fn run_script_self_test(&self, namespace: &str) -> Result<()> {
+        fn check(kind: ScriptKind, ok: bool, detail: &str) -> Result<()> {
+            if ok {
+                Ok(())
+            } else {
+                Err(UnifiedIntelligenceError::Internal(format!("Lua script self-test failed for {}: {}", kind.name(), detail)))
+            }
+        }
+        
+        let uuid = "selftest";
+        let thought_key = format!("{}:Thoughts:{}", namespace, uuid);
+        let bloom_key = format!("{}:bloom:thoughts", namespace);
+        let ts_key = format!("{}:metrics:thought_count", namespace);
+        let access_key = format!("{}:metrics:access_count", namespace);
+        let last_access_key = format!("{}:last_access", thought_key);
+        let chain_key = format!("{}:chains:{}", namespace, uuid);
+        let pattern = format!("{}:Thoughts:*", namespace);
+        let thought_json = format!(r#"{{"id":"{}","thought":"script self-test","timestamp":0}}"#, uuid);
+        let now = chrono::Utc::now().timestamp();
+        
+        let stored = self.store_thought_atomic(&thought_key, &bloom_key, &ts_key, None, &thought_json, uuid, now, None).await?;
+        check(ScriptKind::StoreThought, stored, "first store was reported as duplicate")?;
+        let stored_again = self.store_thought_atomic(&thought_key, &bloom_key, &ts_key, None, &thought_json, uuid, now, None).await?;
+        check(ScriptKind::StoreThought, !stored_again, "second store was not reported as duplicate")?;
+        
+        // Search and cleanup scan {namespace}:Thoughts:*, so run them before
+        // get_thought creates the :last_access key under the same pattern
+        let search: Vec<redis::Value> = self.eval_script(ScriptKind::SearchThoughts, &[&pattern], &["", "0", "10"]).await?;
+        check(ScriptKind::SearchThoughts, search.len() == 2, "expected a count and one thought")?;
+        
+        let cleaned: i64 = self.eval_script(ScriptKind::CleanupExpired, &[&pattern], &["0"]).await?;
+        check(ScriptKind::CleanupExpired, cleaned == 0, "removed a thought newer than the cutoff")?;
+        
+        let fetched = self.get_thought_atomic(&thought_key, &access_key, &last_access_key, now).await?;
+        check(ScriptKind::GetThought, fetched.is_some_and(|json| json.contains(uuid)), "stored thought not returned")?;
+        
+        let added = self.update_chain_atomic(&chain_key, &thought_key, "add", uuid).await?;
+        check(ScriptKind::UpdateChain, added, "add to chain failed")?;
+        
+        let chain = self.get_chain_thoughts_atomic(&chain_key, namespace).await?;
+        check(ScriptKind::GetChainThoughts, chain.len() == 1, "expected one thought in chain")?;
+        
+        let window_key = format!("{}:rate_limit:all", namespace);
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let first = self.sliding_window(&[(window_key.as_str(), 60_000, 1)], now_ms, "a", true).await?;
+        let second = self.sliding_window(&[(window_key.as_str(), 60_000, 1)], now_ms, "b", true).await?;
+        check(ScriptKind::SlidingWindow, first.0 && !second.0 && second.1 == vec![(1, now_ms)], "expected the second request over a limit of 1 to be refused")?;
         
         Ok(())
     }
@@ -844,46 +1355,23 @@ impl RedisManager {
         timestamp: i64,
         chain_id: Option<&str>,
     ) -> Result<bool> {
-        let mut conn = self.get_connection().await?;
-        
         // Prepare keys
-        let mut keys = vec![thought_key, bloom_key, ts_key];
-        if let Some(chain) = chain_key {
-            keys.push(chain);
-        } else {
-            keys.push("");  // Placeholder
-        }
+        let keys = [thought_key, bloom_key, ts_key, chain_key.unwrap_or("")];
         
         // Prepare arguments
-        let args = vec![
-            thought_json.to_string(),
-            uuid.to_string(),
-            timestamp.to_string(),
-            chain_id.unwrap_or("").to_string(),
+        let timestamp = timestamp.to_string();
+        let flag = |loaded: bool| if loaded { "1" } else { "0" };
+        let args = [
+            thought_json,
+            uuid,
+            timestamp.as_str(),
+            chain_id.unwrap_or(""),
+            flag(self.capabilities.json),
+            flag(self.capabilities.bloom),
+            flag(self.capabilities.timeseries),
         ];
         
-        // Get script SHA
-        let script_sha = {
-            let scripts = self.scripts.read().await;
-            scripts.store_thought.clone()
-        };
-        
-        // Execute script
-        let result: String = redis::cmd("EVALSHA")
-            .arg(&script_sha)
-            .arg(keys.len())
-            .arg(&keys)
-            .arg(&args)
-            .query_async(&mut *conn)
-            .await
-            .or_else(|e| {
-                // If script not loaded, reload and retry
-                if e.to_string().contains("NOSCRIPT") {
-                    tracing::warn!("Script not found in cache, reloading...");
-                    return Err(UnifiedIntelligenceError::Internal("Script needs reloading".to_string()));
-                }
-                Err(UnifiedIntelligenceError::Redis(e))
-            })?;
+        let result: String = self.eval_script(ScriptKind::StoreThought, &keys, &args).await?;
         
         match result.as_str() {
             "OK" => Ok(true),
@@ -900,33 +1388,12 @@ impl RedisManager {
         last_access_key: &str,
         timestamp: i64,
     ) -> Result<Option<String>> {
-        let mut conn = self.get_connection().await?;
+        let keys = [thought_key, access_count_key, last_access_key];
+        let timestamp = timestamp.to_string();
         
-        let keys = vec![thought_key, access_count_key, last_access_key];
-        let args = vec![timestamp.to_string()];
+        let timeseries = if self.capabilities.timeseries { "1" } else { "0" };
         
-        // Get script SHA
-        let script_sha = {
-            let scripts = self.scripts.read().await;
-            scripts.get_thought.clone()
-        };
-        
-        let result: Option<String> = redis::cmd("EVALSHA")
-            .arg(&script_sha)
-            .arg(keys.len())
-            .arg(&keys)
-            .arg(&args)
-            .query_async(&mut *conn)
-            .await
-            .or_else(|e| {
-                if e.to_string().contains("NOSCRIPT") {
-                    tracing::warn!("Get thought script not found in cache");
-                    return Err(UnifiedIntelligenceError::Internal("Script needs reloading".to_string()));
-                }
-                Err(UnifiedIntelligenceError::Redis(e))
-            })?;
-        
-        Ok(result)
+        self.eval_script(ScriptKind::GetThought, &keys, &[&timestamp, timeseries]).await
     }
     
     /// Execute atomic chain update using Lua script
@@ -937,31 +1404,7 @@ impl RedisManager {
         operation: &str,
         uuid: &str,
     ) -> Result<bool> {
-        let mut conn = self.get_connection().await?;
-        
-        let keys = vec![chain_key, thought_key];
-        let args = vec![operation, uuid];
-        
-        // Get script SHA
-        let script_sha = {
-            let scripts = self.scripts.read().await;
-            scripts.update_chain.clone()
-        };
-        
-        let result: i32 = redis::cmd("EVALSHA")
-            .arg(&script_sha)
-            .arg(keys.len())
-            .arg(&keys)
-            .arg(&args)
-            .query_async(&mut *conn)
-            .await
-            .or_else(|e| {
-                if e.to_string().contains("NOSCRIPT") {
-                    tracing::warn!("Update chain script not found in cache");
-                    return Err(UnifiedIntelligenceError::Internal("Script needs reloading".to_string()));
-                }
-                Err(UnifiedIntelligenceError::Redis(e))
-            })?;
+        let result: i32 = self.eval_script(ScriptKind::UpdateChain, &[chain_key, thought_key], &[operation, uuid]).await?;
         
         Ok(result == 1)
     }
@@ -972,32 +1415,33 @@ impl RedisManager {
         chain_key: &str,
         instance: &str,
     ) -> Result<Vec<String>> {
-        let mut conn = self.get_connection().await?;
-        
-        let keys = vec![chain_key];
-        
-        // Get script SHA
-        let script_sha = {
-            let scripts = self.scripts.read().await;
-            scripts.get_chain_thoughts.clone()
-        };
-        
-        let result: Vec<String> = redis::cmd("EVALSHA")
-            .arg(&script_sha)
-            .arg(keys.len())
-            .arg(&keys)
-            .arg(instance) // ARGV[1]
-            .query_async(&mut *conn)
-            .await
-            .or_else(|e| {
-                if e.to_string().contains("NOSCRIPT") {
-                    tracing::warn!("Get chain thoughts script not found in cache");
-                    return Err(UnifiedIntelligenceError::Internal("Script needs reloading".to_string()));
-                }
-                Err(UnifiedIntelligenceError::Redis(e))
-            })?;
-        
-        Ok(result)
+        // ARGV[1] = instance
+        self.eval_script(ScriptKind::GetChainThoughts, &[chain_key], &[instance]).await
+    }
+    
+    /// Check a request against sliding windows (key, window ms, limit) and, when
+    /// `record` is set and every window has room, count it in all of them.
+    /// Returns whether it was counted and each window's requests and oldest request (ms)
+    pub async fn sliding_window(
+        &self,
+        windows: &[(&str, i64, usize)],
+        now_ms: i64,
+        request_id: &str,
+        record: bool,
+    ) -> Result<(bool, Vec<(usize, i64)>)> {
+        let keys: Vec<&str> = windows.iter().map(|(key, _, _)| *key).collect();
+        let mut args = vec![now_ms.to_string(), request_id.to_string(), if record { "1" } else { "0" }.to_string()];
+        for (_, window_ms, limit) in windows {
+            args.push(window_ms.to_string());
+            args.push(limit.to_string());
+        }
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let result: Vec<i64> = self.eval_script(ScriptKind::SlidingWindow, &keys, &args).await?;
+        let allowed = result.first().is_some_and(|allowed| *allowed == 1);
+        let windows = result[1.min(result.len())..].chunks(2)
+            .map(|pair| (pair[0].max(0) as usize, pair.get(1).copied().unwrap_or(-1)))
+            .collect();
+        Ok((allowed, windows))
     }
     
     // Event Stream Methods
@@ -1005,7 +1449,7 @@ impl RedisManager {
     /// Initialize event stream for an instance with max length
     pub async fn init_event_stream(&self, instance: &str) -> Result<()> {
         let mut conn = self.get_connection().await?;
-        let stream_key = format!("{}:events", instance);
+        let stream_key = keys::events(instance);
         
         // Check if stream exists by trying to get info
         let exists: std::result::Result<Vec<Vec<String>>, _> = redis::cmd("XINFO")
@@ -1056,7 +1500,7 @@ impl RedisManager {
         data: Vec<(&str, &str)>,
     ) -> Result<String> {
         let mut conn = self.get_connection().await?;
-        let stream_key = format!("{}:events", instance);
+        let stream_key = keys::events(instance);
         
         // Build arguments for XADD
         let mut args = vec![];
@@ -1595,7 +2039,7 @@ impl RedisManager {
         event_type: &str,
         data: &serde_json::Value,
     ) -> Result<String> {
-        let stream_key = format!("{}:events", instance);
+        let stream_key = keys::events(instance);
         let event_id = "*"; // Auto-generate timestamp
         
         let fields = vec![
@@ -4,6 +4,7 @@
 use std::process::Command;
 use std::sync::Arc;
 use serde_json::Value;
+use crate::api_budget::{self, EmbeddingBudget};
 use crate::error::{Result, UnifiedIntelligenceError};
 use crate::models::ThoughtRecord;
 use crate::redis::RedisManager;
@@ -12,6 +13,7 @@ pub struct RedisVLService {
     instance_id: String,
     script_path: String,
     redis_manager: Arc<RedisManager>,
+    budget: EmbeddingBudget,
 }
 
 impl RedisVLService {
@@ -20,10 +22,27 @@ impl RedisVLService {
         Self {
             instance_id,
             script_path,
+            budget: EmbeddingBudget::new(redis_manager.clone()),
             redis_manager,
         }
     }
     
+    /// Refuse an OpenAI embedding once the shared daily budget is spent
+    async fn check_budget(&self) -> Result<()> {
+        if self.budget.allows().await {
+            return Ok(());
+        }
+        self.budget.record_skip().await;
+        Err(UnifiedIntelligenceError::BudgetExceeded("openai".to_string()))
+    }
+    
+    /// Record the embedding a script call made, from the usage it reports or else estimated from the text
+    async fn record_embedding(&self, response: &Value, text: &str) {
+        let input_tokens = response["usage"]["prompt_tokens"].as_u64()
+            .unwrap_or_else(|| api_budget::estimate_tokens(text));
+        self.budget.record(input_tokens).await;
+    }
+    
     /// Get the OpenAI API key from Redis or environment
     async fn get_openai_api_key(&self) -> Result<String> {
         // First try to get from Redis
@@ -55,6 +74,7 @@ impl RedisVLService {
     ) -> Result<bool> {
         // Get API key from Redis or environment
         let api_key = self.get_openai_api_key().await?;
+        self.check_budget().await?;
         
         let output = Command::new("python3")
             .arg(&self.script_path)
@@ -76,6 +96,7 @@ impl RedisVLService {
         let stdout = String::from_utf8_lossy(&output.stdout);
         let response: Value = serde_json::from_str(&stdout)
             .map_err(|e| UnifiedIntelligenceError::Python(format!("Failed to parse Python response: {}", e)))?;
+        self.record_embedding(&response, content).await;
         
         Ok(response["success"].as_bool().unwrap_or(false))
     }
@@ -89,6 +110,7 @@ impl RedisVLService {
     ) -> Result<Vec<ThoughtRecord>> {
         // Get API key from Redis or environment
         let api_key = self.get_openai_api_key().await?;
+        self.check_budget().await?;
         
         tracing::info!("RedisVL semantic_search - Starting search");
         tracing::info!("  Instance ID: {}", &self.instance_id);
@@ -127,6 +149,7 @@ impl RedisVLService {
         
         let response: Value = serde_json::from_str(&stdout)
             .map_err(|e| UnifiedIntelligenceError::Python(format!("Failed to parse Python response: {}. Raw output: {}", e, stdout)))?;
+        self.record_embedding(&response, query).await;
         
         let results = response["results"].as_array()
             .ok_or_else(|| UnifiedIntelligenceError::Python(format!("No results array in response. Full response: {}", response)))?;
@@ -141,7 +164,9 @@ impl RedisVLService {
                 let content = result["content"].as_str().unwrap_or("").to_string();
                 let thought = ThoughtRecord {
                     id: thought_id.to_string(),
-                    instance: self.instance_id.clone(),
+                    instance: result["instance"].as_str()
+                        .map(|i| i.to_string())
+                        .unwrap_or_else(|| self.instance_id.clone()),
                     thought: content,
                     timestamp: chrono::Utc::now().to_rfc3339(),
                     thought_number: 1,
@@ -149,6 +174,12 @@ impl RedisVLService {
                     next_thought_needed: false,
                     chain_id: None,
                     similarity: result["similarity"].as_f64().map(|f| f as f32),
+                    user_id: None,
+                    provenance: None, // Filled from the stored record when recall filters on it
+                    framework: None,
+                    thinking_type: None,
+                    cold: None,
+                    custody: None,
                 };
                 thoughts.push(thought);
             }
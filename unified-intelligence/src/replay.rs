@@ -0,0 +1,181 @@
+//! Step-by-step chain replay for ui_replay.
+//!
+//! A chain is replayed in thought order, one step at a time. Each step
+//! carries the delay to wait before showing it: a fixed pace, or the
+//! original gap between the thoughts divided by `speed`, capped at
+//! MAX_DELAY_MS. When the client sends a progress token the server waits out
+//! those delays and sends every step as a progress notification; otherwise
+//! the steps come back in chunks of `limit` with a cursor for the next
+//! chunk, and the client paces them itself. Streaming stops at the tool's
+//! deadline and the response then points at the first step not sent.
+
+use std::future::Future;
+use std::time::Duration;
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::{ReplayResponse, ReplayStep, ThoughtRecord};
+use crate::timeouts;
+
+/// Delay between steps when neither pace_ms nor speed is given
+pub const DEFAULT_PACE_MS: u64 = 1000;
+
+/// Longest wait before a single step
+pub const MAX_DELAY_MS: u64 = 10_000;
+
+/// Steps per chunk when no limit is given
+pub const DEFAULT_CHUNK: usize = 20;
+
+/// Most steps returned or streamed by one call
+pub const MAX_CHUNK: usize = 100;
+
+/// How long to wait before each step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pacing {
+    /// The same delay before every step
+    Fixed(u64),
+    /// The original gap between thoughts, divided by this factor
+    Recorded(f64),
+}
+
+impl Pacing {
+    pub fn from_params(pace_ms: Option<u64>, speed: Option<f64>) -> Result<Self> {
+        if let Some(speed) = speed {
+            if !speed.is_finite() || speed <= 0.0 {
+                return Err(UnifiedIntelligenceError::Validation {
+                    field: "speed".to_string(),
+                    reason: format!("Speed must be a positive number, got {}", speed),
+                });
+            }
+            return Ok(Pacing::Recorded(speed));
+        }
+        let pace_ms = pace_ms.unwrap_or(DEFAULT_PACE_MS);
+        if pace_ms > MAX_DELAY_MS {
+            return Err(UnifiedIntelligenceError::Validation {
+                field: "pace_ms".to_string(),
+                reason: format!("Pace must be at most {}ms, got {}", MAX_DELAY_MS, pace_ms),
+            });
+        }
+        Ok(Pacing::Fixed(pace_ms))
+    }
+
+    /// Delay before a step that came `gap_ms` after the previous thought
+    fn delay_ms(&self, gap_ms: i64) -> u64 {
+        match self {
+            Pacing::Fixed(pace_ms) => *pace_ms,
+            Pacing::Recorded(speed) => ((gap_ms.max(0) as f64 / speed).round() as u64).min(MAX_DELAY_MS),
+        }
+    }
+}
+
+fn gap_ms(previous: &ThoughtRecord, thought: &ThoughtRecord) -> Option<i64> {
+    let previous = chrono::DateTime::parse_from_rfc3339(&previous.timestamp).ok()?;
+    let current = chrono::DateTime::parse_from_rfc3339(&thought.timestamp).ok()?;
+    Some(current.signed_duration_since(previous).num_milliseconds())
+}
+
+/// Steps of a chain from thought `from_thought` on, at most `limit`, with the number of the next step if any remain
+pub fn steps(thoughts: &[ThoughtRecord], from_thought: i32, limit: usize, pacing: Pacing) -> (Vec<ReplayStep>, Option<i32>) {
+    let mut ordered: Vec<&ThoughtRecord> = thoughts.iter().collect();
+    ordered.sort_by_key(|t| t.thought_number);
+
+    let mut remaining = ordered.iter().enumerate()
+        .filter(|(_, thought)| thought.thought_number >= from_thought)
+        .map(|(index, thought)| {
+            let gap_ms = index.checked_sub(1).and_then(|previous| gap_ms(ordered[previous], thought));
+            // The first step of the chain shows at once; later ones wait even when their timestamps are unreadable
+            let delay_ms = if index == 0 { 0 } else { pacing.delay_ms(gap_ms.unwrap_or(0)) };
+            ReplayStep {
+                thought_id: thought.id.clone(),
+                thought_number: thought.thought_number,
+                total_thoughts: thought.total_thoughts,
+                thought: thought.thought.clone(),
+                timestamp: thought.timestamp.clone(),
+                gap_ms,
+                delay_ms,
+            }
+        });
+    let chunk: Vec<ReplayStep> = remaining.by_ref().take(limit).collect();
+    let next = remaining.next().map(|step| step.thought_number);
+    (chunk, next)
+}
+
+/// Wait out each step's delay and hand it to `emit`, stopping at the tool deadline or when `emit` returns false.
+/// Returns the number of steps sent.
+pub async fn stream<F, Fut>(steps: &[ReplayStep], mut emit: F) -> usize
+where
+    F: FnMut(usize, &ReplayStep) -> Fut,
+    Fut: Future<Output = bool>,
+{
+    for (index, step) in steps.iter().enumerate() {
+        if timeouts::within(tokio::time::sleep(Duration::from_millis(step.delay_ms))).await.is_none() || timeouts::expired() {
+            return index;
+        }
+        if !emit(index, step).await {
+            return index;
+        }
+    }
+    steps.len()
+}
+
+/// Mark a response as streamed, keeping only the steps that were sent
+pub fn finish_stream(response: &mut ReplayResponse, sent: usize) {
+    response.streamed = true;
+    if sent < response.steps.len() {
+        response.partial = true;
+        response.next_from_thought = Some(response.steps[sent].thought_number);
+        response.steps.truncate(sent);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain() -> Vec<ThoughtRecord> {
+        let start = chrono::Utc::now() - chrono::Duration::minutes(10);
+        [(2, 60), (1, 0), (3, 600)].iter().map(|(number, seconds)| {
+            let mut thought = ThoughtRecord::new("CC".to_string(), format!("step {}", number), *number, 3, Some("c1".to_string()), *number < 3);
+            thought.timestamp = (start + chrono::Duration::seconds(*seconds)).to_rfc3339();
+            thought
+        }).collect()
+    }
+
+    #[test]
+    fn test_steps_and_pacing() {
+        let (all, next) = steps(&chain(), 1, DEFAULT_CHUNK, Pacing::Fixed(250));
+        assert_eq!(all.iter().map(|s| s.thought_number).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(all.iter().map(|s| s.delay_ms).collect::<Vec<_>>(), vec![0, 250, 250]);
+        assert_eq!(all[1].gap_ms, Some(60_000));
+        assert_eq!(next, None);
+
+        let (chunk, next) = steps(&chain(), 2, 1, Pacing::Recorded(60.0));
+        assert_eq!((chunk[0].thought_number, chunk[0].delay_ms, next), (2, 1000, Some(3)));
+        let (last, _) = steps(&chain(), 3, 1, Pacing::Recorded(1.0));
+        assert_eq!(last[0].delay_ms, MAX_DELAY_MS);
+
+        assert_eq!(Pacing::from_params(None, None).unwrap(), Pacing::Fixed(DEFAULT_PACE_MS));
+        assert!(Pacing::from_params(Some(MAX_DELAY_MS + 1), None).is_err());
+        assert!(Pacing::from_params(None, Some(0.0)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stream_stops_at_deadline() {
+        let (all, _) = steps(&chain(), 1, DEFAULT_CHUNK, Pacing::Fixed(0));
+        let mut seen = Vec::new();
+        let sent = stream(&all, |_, step| {
+            seen.push(step.thought_number);
+            async { true }
+        }).await;
+        assert_eq!((sent, seen), (3, vec![1, 2, 3]));
+
+        let (slow, _) = steps(&chain(), 1, DEFAULT_CHUNK, Pacing::Fixed(5_000));
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(50);
+        let sent = timeouts::with_deadline(deadline, stream(&slow, |_, _| async { true })).await;
+        assert_eq!(sent, 1);
+
+        let mut response = ReplayResponse { chain_id: "c1".to_string(), chain_length: 3, steps: slow, ..Default::default() };
+        finish_stream(&mut response, sent);
+        assert!(response.streamed && response.partial);
+        assert_eq!((response.steps.len(), response.next_from_thought), (1, Some(2)));
+    }
+}
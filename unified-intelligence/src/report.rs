@@ -0,0 +1,343 @@
+//! Standalone HTML reports of thoughts for ui_report.
+//!
+//! A report covers one chain or a filtered set of thoughts and is a single
+//! HTML file with inline CSS and SVG, so it can be shared outside the MCP
+//! ecosystem and opened without a server. It has four parts: a graph with one
+//! lane per chain, thoughts linked in order and chains joined by the links the
+//! background linker found; the decisions (thoughts that conclude a chain or
+//! state a decision); a timeline grouped by day; and a card per thought with
+//! its tags, importance and citations. Every graph node and timeline entry
+//! links to its card. Reports are written to UI_REPORT_DIR.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::{ChainMetadata, ThoughtMetadata, ThoughtRecord};
+
+/// Directory reports are written to when UI_REPORT_DIR is not set
+pub const DEFAULT_DIR: &str = "reports";
+
+/// Thoughts rendered when no limit is given
+pub const DEFAULT_LIMIT: usize = 200;
+
+/// Lane label of thoughts without a chain
+const UNCHAINED: &str = "unchained";
+
+const DECISION_MARKERS: &[&str] = &[
+    "decided", "decision", "we will", "going with", "chose", "settled on", "conclusion", "the answer is",
+];
+
+/// Graph geometry in pixels
+const LANE_HEIGHT: usize = 56;
+const NODE_SPACING: usize = 44;
+const LABEL_WIDTH: usize = 180;
+const NODE_RADIUS: usize = 9;
+
+/// Report directory from UI_REPORT_DIR, unless the caller names one
+pub fn output_dir(requested: Option<&str>) -> PathBuf {
+    requested.map(str::trim).filter(|dir| !dir.is_empty()).map(str::to_string)
+        .or_else(|| std::env::var("UI_REPORT_DIR").ok().filter(|dir| !dir.trim().is_empty()))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_DIR))
+}
+
+/// Start of a `since` filter, from a date (`2025-07-18`) or an RFC 3339 timestamp
+pub fn parse_since(value: &str) -> Result<chrono::DateTime<chrono::Utc>> {
+    let value = value.trim();
+    if let Ok(timestamp) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&chrono::Utc));
+    }
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+        .map_err(|_| UnifiedIntelligenceError::Validation {
+            field: "since".to_string(),
+            reason: format!("'{}' is neither a date (YYYY-MM-DD) nor an RFC 3339 timestamp", value),
+        })
+}
+
+/// Whether a thought ends its chain or states a decision
+pub fn is_decision(thought: &ThoughtRecord) -> bool {
+    if thought.chain_id.is_some() && !thought.next_thought_needed {
+        return true;
+    }
+    let text = thought.thought.to_lowercase();
+    DECISION_MARKERS.iter().any(|marker| text.contains(marker))
+}
+
+/// File name for a report: the title as a slug plus the generation time
+pub fn file_name(title: &str, generated_at: chrono::DateTime<chrono::Utc>) -> String {
+    format!("{}-{}.html", slug(title, "report"), generated_at.format("%Y%m%d-%H%M%S"))
+}
+
+/// Lowercase ASCII slug of at most 60 characters, or `fallback` when nothing is left
+pub fn slug(text: &str, fallback: &str) -> String {
+    let mut slug = String::new();
+    for c in text.to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug: String = slug.trim_matches('-').chars().take(60).collect();
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() { fallback.to_string() } else { slug.to_string() }
+}
+
+/// Text escaped for HTML content and attribute values
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn anchor(thought: &ThoughtRecord) -> String {
+    format!("t-{}", escape(&thought.id))
+}
+
+fn label(thought: &ThoughtRecord) -> String {
+    match &thought.chain_id {
+        Some(chain_id) => format!("{} #{}", chain_id, thought.thought_number),
+        None => format!("#{}", thought.thought_number),
+    }
+}
+
+/// First line of a thought for graph tooltips and lists
+fn summary(thought: &ThoughtRecord) -> String {
+    crate::review::snippet(&thought.thought, 100)
+}
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:1100px;margin:2rem auto;padding:0 1rem;color:#222}\
+h1{margin-bottom:.2rem}.meta{color:#666;font-size:.9rem}section{margin-top:2rem}\
+.graph{overflow-x:auto;border:1px solid #ddd;border-radius:6px;padding:.5rem}\
+.graph text{font-size:12px;fill:#444}.graph circle{fill:#6b8cce}.graph circle.decision{fill:#d9822b}\
+.graph line{stroke:#999;stroke-width:2}.graph path.related{stroke:#b07cc6;stroke-dasharray:4 3;fill:none}\
+.timeline li{margin:.2rem 0}.day{font-weight:600;margin-top:.8rem}\
+.card{border:1px solid #ddd;border-radius:6px;padding:.8rem 1rem;margin:.8rem 0}\
+.card.decision{border-left:4px solid #d9822b}.card header{font-size:.85rem;color:#666}\
+.card .body{white-space:pre-wrap;margin:.5rem 0}.tag{background:#eef;border-radius:3px;padding:0 .3rem;margin-right:.3rem;font-size:.8rem}\
+.citations{font-size:.85rem;color:#555}";
+
+/// Distinct chains among the thoughts
+pub fn chain_count(thoughts: &[ThoughtRecord]) -> usize {
+    thoughts.iter().filter_map(|t| t.chain_id.as_deref()).collect::<HashSet<_>>().len()
+}
+
+/// Thoughts grouped into chain lanes, lanes ordered by their first thought
+fn lanes(thoughts: &[ThoughtRecord]) -> Vec<(String, Vec<&ThoughtRecord>)> {
+    let mut lanes: Vec<(String, Vec<&ThoughtRecord>)> = Vec::new();
+    for thought in thoughts {
+        let lane = thought.chain_id.clone().unwrap_or_else(|| UNCHAINED.to_string());
+        match lanes.iter_mut().find(|(name, _)| *name == lane) {
+            Some((_, members)) => members.push(thought),
+            None => lanes.push((lane, vec![thought])),
+        }
+    }
+    lanes
+}
+
+fn render_graph(html: &mut String, thoughts: &[ThoughtRecord], chains: &[ChainMetadata]) {
+    let lanes = lanes(thoughts);
+    let widest = lanes.iter().map(|(_, members)| members.len()).max().unwrap_or(0);
+    let width = LABEL_WIDTH + widest * NODE_SPACING + NODE_SPACING;
+    let height = lanes.len() * LANE_HEIGHT + LANE_HEIGHT / 2;
+    let position = |lane: usize, index: usize| (LABEL_WIDTH + index * NODE_SPACING + NODE_SPACING / 2, lane * LANE_HEIGHT + LANE_HEIGHT / 2);
+
+    let _ = write!(html, "<svg width=\"{}\" height=\"{}\" role=\"img\" aria-label=\"Thought graph\">", width, height);
+    // Links between related chains, drawn once per pair from the first thought of each lane
+    let lane_index: HashMap<&str, usize> = lanes.iter().enumerate().map(|(i, (name, _))| (name.as_str(), i)).collect();
+    let mut drawn = HashSet::new();
+    for chain in chains {
+        let Some(&a) = lane_index.get(chain.chain_id.as_str()) else { continue };
+        for related in &chain.related {
+            let Some(&b) = lane_index.get(related.chain_id.as_str()) else { continue };
+            let (from, to) = (a.min(b), a.max(b));
+            if from == to || !drawn.insert((from, to)) {
+                continue;
+            }
+            let ((x1, y1), (x2, y2)) = (position(from, 0), position(to, 0));
+            let _ = write!(
+                html,
+                "<path class=\"related\" d=\"M{} {} C{} {} {} {} {} {}\"><title>{}</title></path>",
+                x1, y1, x1 - NODE_SPACING, y1, x2 - NODE_SPACING, y2, x2, y2,
+                escape(&format!("related ({:.2}): {}", related.score, related.shared_topics.join(", ")))
+            );
+        }
+    }
+    for (lane, (name, members)) in lanes.iter().enumerate() {
+        let (_, y) = position(lane, 0);
+        let _ = write!(html, "<text x=\"4\" y=\"{}\">{}</text>", y + 4, escape(name));
+        for index in 1..members.len() {
+            let ((x1, y1), (x2, y2)) = (position(lane, index - 1), position(lane, index));
+            let _ = write!(html, "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>", x1 + NODE_RADIUS, y1, x2 - NODE_RADIUS, y2);
+        }
+        for (index, thought) in members.iter().enumerate() {
+            let (x, y) = position(lane, index);
+            let class = if is_decision(thought) { " class=\"decision\"" } else { "" };
+            let _ = write!(
+                html,
+                "<a href=\"#{}\"><circle cx=\"{}\" cy=\"{}\" r=\"{}\"{}><title>{}</title></circle></a>",
+                anchor(thought), x, y, NODE_RADIUS, class, escape(&format!("{}: {}", label(thought), summary(thought)))
+            );
+        }
+    }
+    html.push_str("</svg>");
+}
+
+fn render_card(html: &mut String, thought: &ThoughtRecord, metadata: Option<&ThoughtMetadata>) {
+    let class = if is_decision(thought) { "card decision" } else { "card" };
+    let _ = write!(html, "<article class=\"{}\" id=\"{}\"><header>{} of {}", class, anchor(thought), escape(&label(thought)), thought.total_thoughts);
+    if let Some(framework) = &thought.framework {
+        let _ = write!(html, " · {}", escape(framework));
+    }
+    let _ = write!(html, " · <time>{}</time>", escape(&thought.timestamp));
+    if let Some(importance) = metadata.and_then(|m| m.importance) {
+        let _ = write!(html, " · importance {}", importance);
+    }
+    html.push_str("</header>");
+    let _ = write!(html, "<div class=\"body\">{}</div>", escape(thought.thought.trim()));
+    if let Some(tags) = metadata.and_then(|m| m.tags.as_ref()).filter(|tags| !tags.is_empty()) {
+        html.push_str("<div>");
+        for tag in tags {
+            let _ = write!(html, "<span class=\"tag\">{}</span>", escape(tag));
+        }
+        html.push_str("</div>");
+    }
+    if let Some(citations) = metadata.map(|m| &m.citations).filter(|c| !c.is_empty()) {
+        html.push_str("<ul class=\"citations\">");
+        for citation in citations {
+            let heading = citation.heading.as_deref().map(|h| format!(" — {}", h)).unwrap_or_default();
+            let _ = write!(html, "<li>{}{}</li>", escape(&citation.note_path), escape(&heading));
+        }
+        html.push_str("</ul>");
+    }
+    html.push_str("</article>");
+}
+
+/// The whole report; thoughts are rendered in timestamp order
+pub fn render(
+    title: &str,
+    thoughts: &[ThoughtRecord],
+    metadata: &HashMap<String, ThoughtMetadata>,
+    chains: &[ChainMetadata],
+    generated_at: chrono::DateTime<chrono::Utc>,
+) -> String {
+    let mut ordered: Vec<ThoughtRecord> = thoughts.to_vec();
+    ordered.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.thought_number.cmp(&b.thought_number)));
+    let decisions: Vec<&ThoughtRecord> = ordered.iter().filter(|t| is_decision(t)).collect();
+
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\"><head><meta charset=\"utf-8\"><title>{}</title><style>{}</style></head><body>",
+        escape(title), STYLE
+    );
+    let _ = write!(
+        html,
+        "<h1>{}</h1><p class=\"meta\">{} thoughts in {} chains · generated {}</p>",
+        escape(title), ordered.len(), chain_count(&ordered), generated_at.to_rfc3339()
+    );
+
+    html.push_str("<section><h2>Graph</h2><div class=\"graph\">");
+    render_graph(&mut html, &ordered, chains);
+    html.push_str("</div></section>");
+
+    let _ = write!(html, "<section><h2>Decisions ({})</h2><ul>", decisions.len());
+    for thought in &decisions {
+        let _ = write!(html, "<li><a href=\"#{}\">{}</a>: {}</li>", anchor(thought), escape(&label(thought)), escape(&summary(thought)));
+    }
+    html.push_str("</ul></section>");
+
+    html.push_str("<section><h2>Timeline</h2><ul class=\"timeline\">");
+    let mut days: BTreeMap<String, Vec<&ThoughtRecord>> = BTreeMap::new();
+    for thought in &ordered {
+        days.entry(thought.timestamp.chars().take(10).collect()).or_default().push(thought);
+    }
+    for (day, members) in &days {
+        let _ = write!(html, "<li class=\"day\">{}</li>", escape(day));
+        for thought in members {
+            let time: String = thought.timestamp.chars().skip(11).take(5).collect();
+            let _ = write!(html, "<li>{} <a href=\"#{}\">{}</a> {}</li>", escape(&time), anchor(thought), escape(&label(thought)), escape(&summary(thought)));
+        }
+    }
+    html.push_str("</ul></section>");
+
+    html.push_str("<section><h2>Thoughts</h2>");
+    for thought in &ordered {
+        render_card(&mut html, thought, metadata.get(&thought.id));
+    }
+    html.push_str("</section></body></html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::RelatedChain;
+
+    fn thought(chain: &str, number: i32, text: &str, next: bool) -> ThoughtRecord {
+        let mut thought = ThoughtRecord::new("CC".to_string(), text.to_string(), number, 2, Some(chain.to_string()), next);
+        thought.timestamp = format!("2025-07-18T10:0{}:00+00:00", number);
+        thought
+    }
+
+    #[test]
+    fn test_helpers() {
+        assert_eq!(escape("<a href=\"x\">&'"), "&lt;a href=&quot;x&quot;&gt;&amp;&#39;");
+        let at = chrono::DateTime::parse_from_rfc3339("2025-07-18T09:30:00Z").unwrap().with_timezone(&chrono::Utc);
+        assert_eq!(file_name("Redis: port migration!", at), "redis-port-migration-20250718-093000.html");
+        assert_eq!(file_name("???", at), "report-20250718-093000.html");
+        assert_eq!(parse_since("2025-07-18").unwrap(), chrono::DateTime::parse_from_rfc3339("2025-07-18T00:00:00Z").unwrap());
+        assert!(parse_since("last week").is_err());
+        assert!(is_decision(&thought("c1", 2, "Checked the logs", false)));
+        assert!(is_decision(&thought("c1", 1, "We decided to keep Redis", true)));
+        assert!(!is_decision(&thought("c1", 1, "Checked the logs", true)));
+    }
+
+    #[test]
+    fn test_render() {
+        let thoughts = vec![
+            thought("redis", 1, "Port <6379> is taken", true),
+            thought("redis", 2, "Going with 6380", false),
+            thought("deploy", 1, "Update the compose file", true),
+        ];
+        let mut metadata = HashMap::new();
+        let mut tagged = ThoughtMetadata::new(thoughts[0].id.clone(), "CC".to_string(), Some(8), None, Some(vec!["redis".to_string()]), None);
+        tagged.citations = vec![crate::models::Citation { note_path: "Projects/Redis.md".to_string(), heading: Some("Ports".to_string()), hash: None }];
+        metadata.insert(thoughts[0].id.clone(), tagged);
+        let chains = vec![ChainMetadata {
+            chain_id: "redis".to_string(),
+            created_at: String::new(),
+            thought_count: 2,
+            instance: "CC".to_string(),
+            user_id: None,
+            related: vec![RelatedChain { chain_id: "deploy".to_string(), score: 0.4, shared_topics: vec!["compose".to_string()] }],
+            planned_thoughts: None,
+            observed_thoughts: None,
+            last_updated: None,
+            next_hints: None,
+        }];
+
+        let html = render("Redis <ports>", &thoughts, &metadata, &chains, chrono::Utc::now());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Redis &lt;ports&gt;</title>"));
+        assert!(html.contains("Port &lt;6379&gt; is taken"));
+        assert!(html.contains(&format!("id=\"t-{}\"", thoughts[1].id)));
+        assert!(html.contains("<h2>Decisions (1)</h2>"));
+        assert_eq!(html.matches("<circle").count(), 3);
+        assert_eq!(html.matches("class=\"related\"").count(), 1);
+        assert!(html.contains("Projects/Redis.md — Ports"));
+        assert!(html.contains("importance 8"));
+    }
+}
@@ -0,0 +1,1446 @@
+//! In-memory implementation of all repository traits.
+//!
+//! Selected with UI_STORAGE_BACKEND=memory so the server runs without Redis
+//! (demos, CI, offline development). Records are kept in maps keyed by the same
+//! keys RedisRepository writes, so purge inventories look the same on both
+//! backends. Text search is a case-insensitive substring match, and "semantic"
+//! search ranks by the share of query terms a thought contains. Nothing is
+//! persisted across restarts.
+
+use async_trait::async_trait;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::error::Result;
+use crate::models::{ThoughtRecord, ChainMetadata, Identity, ThoughtMetadata, UiRecallFeedbackParams, PiiRecord, ChainSyncState, SearchIndexStatus, ClientStats, EmbeddingVersion, BackendDiagnostics, CrashReport, PersonaBundle, Annotation, ModeProfile, MemoryUsage, AppliedMigration, BulkUndoRecord, RecallTuning, StreamEntry, ArchivedStreamEntry, StreamCompaction};
+use crate::search_optimization::{boost_increment, BOOST_WEIGHT};
+use crate::identity_documents::IdentityDocument;
+use crate::identity_history::{self, IdentityChange};
+use crate::keys;
+use crate::search_index::{self, SearchField};
+use crate::crash_report;
+use crate::annotations;
+use crate::tenant;
+use crate::purge;
+use crate::stream_compaction;
+use super::*;
+
+/// Entries kept per event stream, mirroring the Redis stream MAXLEN
+pub(super) const MAX_STREAM_EVENTS: usize = 10_000;
+
+#[derive(Default)]
+struct MemoryStore {
+    thoughts: BTreeMap<String, ThoughtRecord>,           // {instance}:Thoughts:{id}
+    chains: BTreeMap<String, Vec<String>>,               // {instance}:chains:{chain_id}
+    chain_metadata: BTreeMap<String, ChainMetadata>,     // [users:{user}:]Chains:metadata:{chain_id}
+    thought_metadata: BTreeMap<String, ThoughtMetadata>, // {instance}:thought_meta:{id}
+    tags: BTreeMap<String, BTreeSet<String>>,            // {instance}:tags:{tag}
+    citations: BTreeMap<String, BTreeSet<String>>,       // {instance}:citations:{note_path}
+    boost_scores: BTreeMap<String, HashMap<String, f64>>, // {instance}:boost_scores
+    identities: BTreeMap<String, Identity>,              // {instance}:identity
+    identity_documents: BTreeMap<String, IdentityDocument>, // {instance}:identity:{field}:{id}
+    identity_versions: BTreeMap<String, u64>,            // {instance}:identity_version
+    identity_history: BTreeMap<String, VecDeque<IdentityChange>>, // {instance}:identity_history, oldest first
+    identity_templates: BTreeMap<String, Identity>,      // identity_template:{name}
+    crash_reports: BTreeMap<String, VecDeque<CrashReport>>, // {instance}:crash_reports, newest first
+    persona_snapshots: BTreeMap<String, BTreeMap<u64, PersonaBundle>>, // {instance}:persona_snapshots
+    annotations: BTreeMap<String, VecDeque<Annotation>>, // {instance}:annotations:{thought_id}, newest first
+    pii_records: BTreeMap<String, PiiRecord>,            // {instance}:pii:{thought_id}
+    chain_sync: BTreeMap<String, ChainSyncState>,        // {instance}:chain_sync:{chain_id}
+    clients: BTreeMap<String, ClientStats>,              // {instance}:clients:{name}
+    captured: BTreeMap<String, BTreeSet<String>>,        // {instance}:capture:seen
+    embedding_stale: BTreeMap<String, HashMap<String, f64>>, // {instance}:embedding_stale
+    streams: BTreeMap<String, VecDeque<(u64, serde_json::Value)>>, // {instance}:events, {instance}:feedback_events, with entry IDs
+    next_event_id: u64,
+    purge_tokens: HashMap<String, (String, Instant)>,    // namespace -> (token, expiry)
+    active_modes: BTreeMap<String, String>,              // {instance}:mode
+    modes: BTreeMap<String, BTreeMap<String, ModeProfile>>, // {instance}:modes
+    migrations: BTreeMap<String, BTreeMap<u32, AppliedMigration>>, // {instance}:migrations
+    migration_locks: HashMap<String, (String, Instant)>, // {instance}:migrations:lock -> (owner, expiry)
+    bulk_undo_claims: HashMap<String, (String, Instant)>, // {instance}:bulk_undo:{undo_id}:claim -> (owner, expiry)
+    bulk_undo: BTreeMap<String, VecDeque<BulkUndoRecord>>, // {instance}:bulk_undo, newest first
+    recall_tuning: BTreeMap<String, RecallTuning>,      // {instance}:recall_tuning
+    stream_archive: BTreeMap<String, VecDeque<ArchivedStreamEntry>>, // {instance}:stream_archive, newest first
+    stream_compaction: BTreeMap<String, StreamCompaction>, // {instance}:stream_compaction
+    recall_counts: BTreeMap<String, HashMap<String, f64>>, // {instance}:recall_counts
+    search_prefixes: BTreeSet<String>,
+}
+
+impl MemoryStore {
+    /// Every stored key, for purge inventories
+    fn keys(&self) -> Vec<&String> {
+        self.thoughts.keys()
+            .chain(self.chains.keys())
+            .chain(self.chain_metadata.keys())
+            .chain(self.thought_metadata.keys())
+            .chain(self.tags.keys())
+            .chain(self.citations.keys())
+            .chain(self.boost_scores.keys())
+            .chain(self.identities.keys())
+            .chain(self.identity_documents.keys())
+            .chain(self.identity_versions.keys())
+            .chain(self.identity_history.keys())
+            .chain(self.identity_templates.keys())
+            .chain(self.crash_reports.keys())
+            .chain(self.persona_snapshots.keys())
+            .chain(self.annotations.keys())
+            .chain(self.pii_records.keys())
+            .chain(self.chain_sync.keys())
+            .chain(self.clients.keys())
+            .chain(self.captured.keys())
+            .chain(self.embedding_stale.keys())
+            .chain(self.streams.keys())
+            .chain(self.active_modes.keys())
+            .chain(self.modes.keys())
+            .chain(self.migrations.keys())
+            .chain(self.bulk_undo.keys())
+            .chain(self.recall_tuning.keys())
+            .chain(self.stream_archive.keys())
+            .chain(self.stream_compaction.keys())
+            .chain(self.recall_counts.keys())
+            .collect()
+    }
+
+    /// Remove a key from whichever map holds it
+    fn remove(&mut self, key: &str) -> bool {
+        self.thoughts.remove(key).is_some()
+            || self.chains.remove(key).is_some()
+            || self.chain_metadata.remove(key).is_some()
+            || self.thought_metadata.remove(key).is_some()
+            || self.tags.remove(key).is_some()
+            || self.citations.remove(key).is_some()
+            || self.boost_scores.remove(key).is_some()
+            || self.identities.remove(key).is_some()
+            || self.identity_documents.remove(key).is_some()
+            || self.identity_versions.remove(key).is_some()
+            || self.identity_history.remove(key).is_some()
+            || self.identity_templates.remove(key).is_some()
+            || self.crash_reports.remove(key).is_some()
+            || self.persona_snapshots.remove(key).is_some()
+            || self.annotations.remove(key).is_some()
+            || self.pii_records.remove(key).is_some()
+            || self.chain_sync.remove(key).is_some()
+            || self.clients.remove(key).is_some()
+            || self.captured.remove(key).is_some()
+            || self.embedding_stale.remove(key).is_some()
+            || self.streams.remove(key).is_some()
+            || self.active_modes.remove(key).is_some()
+            || self.modes.remove(key).is_some()
+            || self.migrations.remove(key).is_some()
+            || self.bulk_undo.remove(key).is_some()
+            || self.recall_tuning.remove(key).is_some()
+            || self.stream_archive.remove(key).is_some()
+            || self.stream_compaction.remove(key).is_some()
+            || self.recall_counts.remove(key).is_some()
+    }
+
+    /// Store a thought and append it to its chain
+    fn insert_thought(&mut self, thought: ThoughtRecord) {
+        if let Some(chain_id) = &thought.chain_id {
+            self.chains.entry(keys::chain(&thought.instance, chain_id))
+                .or_default()
+                .push(thought.id.clone());
+        }
+        self.thoughts.insert(keys::thought(&thought.instance, &thought.id), thought);
+    }
+
+    /// Store thought metadata and index its tags and citations, dropping tags the replaced metadata had
+    fn insert_thought_metadata(&mut self, metadata: ThoughtMetadata) {
+        let key = keys::thought_metadata(&metadata.instance, &metadata.thought_id);
+        if let Some(previous) = self.thought_metadata.get(&key) {
+            let dropped: Vec<String> = previous.tags.iter().flatten()
+                .filter(|tag| !metadata.tags.iter().flatten().any(|t| t == *tag))
+                .cloned()
+                .collect();
+            for tag in dropped {
+                Self::remove_member(&mut self.tags, &keys::tag(&metadata.instance, &tag), &metadata.thought_id);
+            }
+        }
+        for tag in metadata.tags.iter().flatten() {
+            self.tags.entry(keys::tag(&metadata.instance, tag))
+                .or_default()
+                .insert(metadata.thought_id.clone());
+        }
+        for citation in &metadata.citations {
+            self.citations.entry(keys::citations(&metadata.instance, &citation.note_path))
+                .or_default()
+                .insert(metadata.thought_id.clone());
+        }
+        self.thought_metadata.insert(key, metadata);
+    }
+
+    /// Remove a thought and everything indexed under it, returning it with the number of feedback events dropped
+    fn remove_thought(&mut self, instance: &str, thought_id: &str) -> Option<(ThoughtRecord, usize)> {
+        let thought = self.thoughts.remove(&keys::thought(instance, thought_id))?;
+        // Emptied lists and sets disappear, as they do in Redis
+        if let Some(chain_id) = &thought.chain_id {
+            let key = keys::chain(instance, chain_id);
+            if let Some(chain) = self.chains.get_mut(&key) {
+                chain.retain(|id| id != thought_id);
+                if chain.is_empty() {
+                    self.chains.remove(&key);
+                }
+            }
+        }
+        if let Some(metadata) = self.thought_metadata.remove(&keys::thought_metadata(instance, thought_id)) {
+            for tag in metadata.tags.iter().flatten() {
+                Self::remove_member(&mut self.tags, &keys::tag(instance, tag), thought_id);
+            }
+            for citation in &metadata.citations {
+                Self::remove_member(&mut self.citations, &keys::citations(instance, &citation.note_path), thought_id);
+            }
+        }
+        for (scores, key) in [
+            (&mut self.boost_scores, keys::boost_scores(instance)),
+            (&mut self.embedding_stale, keys::embedding_stale(instance)),
+            (&mut self.recall_counts, keys::recall_counts(instance)),
+        ] {
+            if let Some(map) = scores.get_mut(&key) {
+                map.remove(thought_id);
+                if map.is_empty() {
+                    scores.remove(&key);
+                }
+            }
+        }
+        self.pii_records.remove(&keys::pii(instance, thought_id));
+        self.annotations.remove(&keys::annotations(instance, thought_id));
+
+        let mut feedback = 0;
+        if let Some(stream) = self.streams.get_mut(&keys::feedback_events(instance)) {
+            let before = stream.len();
+            stream.retain(|(_, event)| event.get("thought_id").and_then(|id| id.as_str()) != Some(thought_id));
+            feedback = before - stream.len();
+        }
+        Some((thought, feedback))
+    }
+
+    fn remove_member(sets: &mut BTreeMap<String, BTreeSet<String>>, key: &str, member: &str) {
+        if let Some(set) = sets.get_mut(key) {
+            set.remove(member);
+            if set.is_empty() {
+                sets.remove(key);
+            }
+        }
+    }
+
+    fn append_event(&mut self, stream_key: String, event: serde_json::Value) {
+        self.next_event_id += 1;
+        let stream = self.streams.entry(stream_key).or_default();
+        stream.push_back((self.next_event_id, event));
+        if stream.len() > MAX_STREAM_EVENTS {
+            stream.pop_front();
+        }
+    }
+
+    /// Whether a thought passes the metadata filters (thoughts without metadata always do, as in Redis)
+    fn passes_metadata_filters(
+        &self,
+        instance: &str,
+        thought_id: &str,
+        min_importance: Option<i32>,
+        min_relevance: Option<i32>,
+        category_filter: Option<&str>,
+    ) -> bool {
+        let Some(metadata) = self.thought_metadata.get(&keys::thought_metadata(instance, thought_id)) else {
+            return true;
+        };
+        min_importance.is_none_or(|min| metadata.importance.is_some_and(|imp| imp >= min))
+            && min_relevance.is_none_or(|min| metadata.relevance.is_some_and(|rel| rel >= min))
+            && category_filter.is_none_or(|category| metadata.category.as_deref() == Some(category))
+    }
+
+    fn tagged_ids(&self, instance: &str, tags: &[String]) -> Vec<String> {
+        let mut sets = tags.iter().map(|tag| self.tags.get(&keys::tag(instance, tag)));
+        let Some(Some(first)) = sets.next() else {
+            return Vec::new();
+        };
+        let mut ids = first.clone();
+        for set in sets {
+            match set {
+                Some(set) => ids.retain(|id| set.contains(id)),
+                None => return Vec::new(),
+            }
+        }
+        ids.into_iter().collect()
+    }
+}
+
+/// A record persisted by SqliteRepository, keyed like the map it is restored into
+pub(super) enum Restored {
+    Thought(ThoughtRecord),
+    ChainMetadata(String, ChainMetadata),
+    ThoughtMetadata(ThoughtMetadata),
+    BoostScores(String, HashMap<String, f64>),
+    IdentityDocument(IdentityDocument),
+    IdentityHistory(String, Vec<IdentityChange>),
+    IdentityVersion(String, u64),
+    Event(String, serde_json::Value),
+    Migrations(String, Vec<AppliedMigration>),
+    BulkUndo(String, Vec<BulkUndoRecord>),
+    RecallTuning(String, RecallTuning),
+    StreamArchive(String, Vec<ArchivedStreamEntry>),
+    StreamCompaction(String, StreamCompaction),
+    Pii(String, PiiRecord),
+    ChainSync(String, ChainSyncState),
+    PersonaSnapshots(String, Vec<PersonaBundle>),
+    Annotations(String, Vec<Annotation>),
+}
+
+/// Lowercased words of at least two characters
+fn terms(text: &str) -> BTreeSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 2)
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+/// Share of the query's terms that appear in the text
+fn term_overlap(query_terms: &BTreeSet<String>, text: &str) -> f32 {
+    if query_terms.is_empty() {
+        return 0.0;
+    }
+    let words = terms(text);
+    query_terms.iter().filter(|t| words.contains(*t)).count() as f32 / query_terms.len() as f32
+}
+
+fn newest_first(thoughts: &mut [ThoughtRecord]) {
+    thoughts.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+}
+
+/// In-memory implementation of all repository traits
+pub struct MemoryRepository {
+    store: Mutex<MemoryStore>,
+    user_id: Option<String>,
+}
+
+impl MemoryRepository {
+    pub fn new(user_id: Option<String>) -> Self {
+        let store = MemoryStore {
+            search_prefixes: search_index::DEFAULT_INSTANCES.iter().map(|i| search_index::thought_prefix(i)).collect(),
+            ..Default::default()
+        };
+        Self {
+            store: Mutex::new(store),
+            user_id,
+        }
+    }
+
+    fn store(&self) -> MutexGuard<'_, MemoryStore> {
+        self.store.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record an identity change, with a baseline of the documents before it when it's the first
+    fn record_identity_change(store: &mut MemoryStore, instance: &str, change: IdentityChange) {
+        let key = keys::identity_history(instance);
+        if !store.identity_history.contains_key(&key) {
+            let existing = store.identity_documents.values().filter(|d| d.instance == instance).cloned().collect();
+            store.identity_history.insert(key.clone(), identity_history::baseline(existing).into());
+        }
+        let history = store.identity_history.entry(key).or_default();
+        history.push_back(change);
+        while history.len() > identity_history::MAX_CHANGES {
+            history.pop_front();
+        }
+    }
+
+    pub(super) fn chain_metadata_key(&self, chain_id: &str) -> String {
+        keys::chain_metadata(self.user_id.as_deref(), chain_id)
+    }
+
+    /// Undo records of an instance, newest first, as SqliteRepository persists them
+    pub(super) fn bulk_undo_records(&self, instance: &str) -> Vec<BulkUndoRecord> {
+        self.store().bulk_undo.get(&keys::bulk_undo(instance)).map(|records| records.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Archived stream entries of an instance, newest first, as SqliteRepository persists them
+    pub(super) fn stream_archive_entries(&self, instance: &str) -> Vec<ArchivedStreamEntry> {
+        self.store().stream_archive.get(&keys::stream_archive(instance)).map(|entries| entries.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Persona snapshots of an instance, oldest version first, as SqliteRepository persists them
+    pub(super) fn persona_snapshot_list(&self, instance: &str) -> Vec<PersonaBundle> {
+        self.store().persona_snapshots.get(&keys::persona_snapshots(instance)).map(|snapshots| snapshots.values().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Load persisted records, in the order they were first written, without logging events or recording identity changes
+    pub(super) fn restore(&self, records: Vec<Restored>) {
+        let mut store = self.store();
+        for record in records {
+            match record {
+                Restored::Thought(thought) => store.insert_thought(thought),
+                Restored::ChainMetadata(key, metadata) => { store.chain_metadata.insert(key, metadata); }
+                Restored::ThoughtMetadata(metadata) => store.insert_thought_metadata(metadata),
+                Restored::BoostScores(key, scores) => { store.boost_scores.insert(key, scores); }
+                Restored::IdentityDocument(document) => { store.identity_documents.insert(document.redis_key(), document); }
+                Restored::IdentityHistory(key, history) => { store.identity_history.insert(key, history.into()); }
+                Restored::IdentityVersion(key, version) => { store.identity_versions.insert(key, version); }
+                Restored::Event(key, event) => store.append_event(key, event),
+                Restored::Migrations(key, applied) => {
+                    store.migrations.insert(key, applied.into_iter().map(|m| (m.version, m)).collect());
+                }
+                Restored::BulkUndo(key, records) => { store.bulk_undo.insert(key, records.into()); }
+                Restored::RecallTuning(key, tuning) => { store.recall_tuning.insert(key, tuning); }
+                Restored::StreamArchive(key, entries) => { store.stream_archive.insert(key, entries.into()); }
+                Restored::StreamCompaction(key, compaction) => { store.stream_compaction.insert(key, compaction); }
+                Restored::Pii(key, record) => { store.pii_records.insert(key, record); }
+                Restored::ChainSync(key, state) => { store.chain_sync.insert(key, state); }
+                Restored::PersonaSnapshots(key, bundles) => {
+                    store.persona_snapshots.insert(key, bundles.into_iter().map(|b| (b.version, b)).collect());
+                }
+                Restored::Annotations(key, annotations) => { store.annotations.insert(key, annotations.into()); }
+            }
+        }
+    }
+
+    /// Thoughts visible to this tenant that satisfy `keep`
+    fn tenant_thoughts(&self, keep: impl Fn(&ThoughtRecord) -> bool) -> Vec<ThoughtRecord> {
+        self.store().thoughts.values()
+            .filter(|t| tenant::belongs_to(self.user_id.as_deref(), &t.instance) && keep(t))
+            .cloned()
+            .collect()
+    }
+
+    /// Thoughts visible to this tenant that satisfy `keep` and match `query` in `fields`
+    fn text_matches(&self, query: &str, fields: &[SearchField], keep: impl Fn(&ThoughtRecord) -> bool) -> Vec<ThoughtRecord> {
+        let fields = search_index::resolve_fields(fields);
+        let store = self.store();
+        store.thoughts.values()
+            .filter(|t| tenant::belongs_to(self.user_id.as_deref(), &t.instance) && keep(t))
+            .filter(|t| {
+                let metadata = store.thought_metadata.get(&keys::thought_metadata(&t.instance, &t.id));
+                search_index::matches(t, metadata, query, &fields)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Rank by term overlap, dropping results below the threshold
+    fn rank_by_overlap(mut thoughts: Vec<ThoughtRecord>, query: &str, limit: usize, threshold: f32) -> Vec<ThoughtRecord> {
+        let query_terms = terms(query);
+        thoughts.retain_mut(|thought| {
+            let similarity = term_overlap(&query_terms, &thought.thought);
+            thought.similarity = Some(similarity);
+            similarity >= threshold && similarity > 0.0
+        });
+        thoughts.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap_or(std::cmp::Ordering::Equal));
+        thoughts.truncate(limit);
+        thoughts
+    }
+}
+
+// ===== THOUGHT STORAGE IMPLEMENTATION =====
+#[async_trait]
+impl ThoughtStorage for MemoryRepository {
+    async fn save_thought(&self, thought: &ThoughtRecord) -> Result<()> {
+        let key = keys::thought(&thought.instance, &thought.id);
+        let mut store = self.store();
+
+        if store.thoughts.contains_key(&key) {
+            tracing::warn!("Duplicate thought detected for instance {}: {}", thought.instance, thought.id);
+            return Ok(());
+        }
+
+        store.insert_thought(thought.clone());
+        store.append_event(keys::events(&thought.instance), serde_json::json!({
+            "event_type": "thought_created",
+            "thought_id": thought.id,
+            "chain_id": thought.chain_id,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        }));
+
+        Ok(())
+    }
+
+    async fn get_thought(&self, instance: &str, thought_id: &str) -> Result<Option<ThoughtRecord>> {
+        Ok(self.store().thoughts.get(&keys::thought(instance, thought_id)).cloned())
+    }
+
+    async fn get_chain_thoughts(&self, instance: &str, chain_id: &str) -> Result<Vec<ThoughtRecord>> {
+        let store = self.store();
+        let Some(ids) = store.chains.get(&keys::chain(instance, chain_id)) else {
+            return Ok(Vec::new());
+        };
+
+        // Chain order, skipping thoughts that have since been purged
+        Ok(ids.iter()
+            .filter_map(|id| store.thoughts.get(&keys::thought(instance, id)))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_instance_thoughts(&self, instance: &str, limit: usize) -> Result<Vec<ThoughtRecord>> {
+        let mut thoughts = self.tenant_thoughts(|t| t.instance == instance);
+        newest_first(&mut thoughts);
+        thoughts.truncate(limit);
+        Ok(thoughts)
+    }
+
+    async fn get_all_thoughts(&self, limit: usize) -> Result<Vec<ThoughtRecord>> {
+        let mut thoughts = self.tenant_thoughts(|_| true);
+        newest_first(&mut thoughts);
+        thoughts.truncate(limit);
+        Ok(thoughts)
+    }
+
+    async fn delete_thought(&self, instance: &str, thought_id: &str) -> Result<bool> {
+        let mut store = self.store();
+        let Some((thought, feedback)) = store.remove_thought(instance, thought_id) else {
+            return Ok(false);
+        };
+        store.append_event(keys::events(instance), serde_json::json!({
+            "event_type": "thought_deleted",
+            "thought_id": thought_id,
+            "chain_id": thought.chain_id,
+            "feedback_events": feedback,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        }));
+        Ok(true)
+    }
+}
+
+// ===== THOUGHT SEARCH IMPLEMENTATION =====
+#[async_trait]
+impl ThoughtSearch for MemoryRepository {
+    async fn search_thoughts(&self, instance: &str, query: &str, fields: &[SearchField], limit: usize) -> Result<Vec<ThoughtRecord>> {
+        let mut thoughts = self.text_matches(query, fields, |t| t.instance == instance);
+        newest_first(&mut thoughts);
+        thoughts.truncate(limit);
+        Ok(thoughts)
+    }
+
+    async fn search_thoughts_semantic(&self, instance: &str, query: &str, limit: usize, threshold: f32) -> Result<Vec<ThoughtRecord>> {
+        let thoughts = self.tenant_thoughts(|t| t.instance == instance);
+        Ok(Self::rank_by_overlap(thoughts, query, limit, threshold))
+    }
+
+    async fn search_thoughts_global(&self, query: &str, fields: &[SearchField], limit: usize) -> Result<Vec<ThoughtRecord>> {
+        let mut thoughts = self.text_matches(query, fields, |_| true);
+        newest_first(&mut thoughts);
+        thoughts.truncate(limit);
+        Ok(thoughts)
+    }
+
+    async fn search_thoughts_semantic_global(&self, query: &str, limit: usize, threshold: f32) -> Result<Vec<ThoughtRecord>> {
+        let thoughts = self.tenant_thoughts(|_| true);
+        Ok(Self::rank_by_overlap(thoughts, query, limit, threshold))
+    }
+
+    async fn generate_search_id(&self) -> Result<String> {
+        let timestamp = chrono::Utc::now().timestamp();
+        let uuid = uuid::Uuid::new_v4().to_string()[..8].to_string();
+        Ok(format!("search_{}_{}", timestamp, uuid))
+    }
+}
+
+// ===== ENHANCED SEARCH IMPLEMENTATION =====
+#[async_trait]
+impl EnhancedSearch for MemoryRepository {
+    async fn search_thoughts_semantic_enhanced(
+        &self,
+        instance: &str,
+        query: &str,
+        limit: usize,
+        threshold: f32,
+        tags_filter: Option<Vec<String>>,
+        min_importance: Option<i32>,
+        min_relevance: Option<i32>,
+        category_filter: Option<String>,
+    ) -> Result<Vec<ThoughtRecord>> {
+        let mut thoughts = self.search_thoughts_semantic(instance, query, usize::MAX, threshold).await?;
+
+        let store = self.store();
+        let tagged = tags_filter.map(|tags| store.tagged_ids(instance, &tags));
+        thoughts.retain(|t| {
+            tagged.as_ref().is_none_or(|ids| ids.contains(&t.id))
+                && store.passes_metadata_filters(instance, &t.id, min_importance, min_relevance, category_filter.as_deref())
+        });
+
+        thoughts.truncate(limit);
+        Ok(thoughts)
+    }
+
+    async fn search_thoughts_semantic_global_enhanced(
+        &self,
+        query: &str,
+        limit: usize,
+        threshold: f32,
+        tags_filter: Option<Vec<String>>,
+        min_importance: Option<i32>,
+        min_relevance: Option<i32>,
+        category_filter: Option<String>,
+    ) -> Result<Vec<ThoughtRecord>> {
+        let mut thoughts = self.search_thoughts_semantic_global(query, usize::MAX, threshold).await?;
+
+        // Tags are per instance, so check each thought against its own instance's tag sets
+        let store = self.store();
+        thoughts.retain(|t| {
+            tags_filter.as_ref().is_none_or(|tags| store.tagged_ids(&t.instance, tags).contains(&t.id))
+                && store.passes_metadata_filters(&t.instance, &t.id, min_importance, min_relevance, category_filter.as_deref())
+        });
+
+        thoughts.truncate(limit);
+        Ok(thoughts)
+    }
+
+    async fn get_thoughts_by_tags(&self, instance: &str, tags: &[String]) -> Result<Vec<String>> {
+        Ok(self.store().tagged_ids(instance, tags))
+    }
+}
+
+// ===== CHAIN OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl ChainOperations for MemoryRepository {
+    async fn save_chain_metadata(&self, metadata: &ChainMetadata) -> Result<()> {
+        let key = self.chain_metadata_key(&metadata.chain_id);
+        self.store().chain_metadata.insert(key, metadata.clone());
+        Ok(())
+    }
+
+    async fn chain_exists(&self, chain_id: &str) -> Result<bool> {
+        Ok(self.store().chain_metadata.contains_key(&self.chain_metadata_key(chain_id)))
+    }
+
+    async fn get_chain_metadata(&self, chain_id: &str) -> Result<Option<ChainMetadata>> {
+        Ok(self.store().chain_metadata.get(&self.chain_metadata_key(chain_id)).cloned())
+    }
+
+    async fn list_chain_metadata(&self, instance: &str) -> Result<Vec<ChainMetadata>> {
+        let prefix = self.chain_metadata_key("");
+        Ok(self.store().chain_metadata.iter()
+            .filter(|(key, metadata)| key.starts_with(&prefix) && metadata.instance == instance)
+            .map(|(_, metadata)| metadata.clone())
+            .collect())
+    }
+
+    async fn delete_chain(&self, instance: &str, chain_id: &str) -> Result<usize> {
+        let mut deleted = 0;
+        for thought in self.get_chain_thoughts(instance, chain_id).await? {
+            if self.delete_thought(instance, &thought.id).await? {
+                deleted += 1;
+            }
+        }
+        {
+            let mut store = self.store();
+            for key in [keys::chain(instance, chain_id), keys::chain_sync(instance, chain_id), self.chain_metadata_key(chain_id)] {
+                store.remove(&key);
+            }
+        }
+        self.log_event(instance, "chain_deleted", vec![("chain_id", chain_id), ("thoughts_deleted", &deleted.to_string())]).await?;
+        Ok(deleted)
+    }
+}
+
+// ===== FEEDBACK OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl FeedbackOperations for MemoryRepository {
+    async fn save_thought_metadata(&self, metadata: &ThoughtMetadata) -> Result<()> {
+        self.store().insert_thought_metadata(metadata.clone());
+        Ok(())
+    }
+
+    async fn get_thought_metadata(&self, instance: &str, thought_id: &str) -> Result<Option<ThoughtMetadata>> {
+        Ok(self.store().thought_metadata.get(&keys::thought_metadata(instance, thought_id)).cloned())
+    }
+
+    async fn record_feedback(&self, feedback: &UiRecallFeedbackParams, instance: &str) -> Result<()> {
+        let feedback_event = serde_json::json!({
+            "event_type": "feedback_provided",
+            "search_id": feedback.search_id,
+            "thought_id": feedback.thought_id,
+            "instance": instance,
+            "action": feedback.action,
+            "dwell_time": feedback.dwell_time,
+            "relevance_rating": feedback.relevance_rating,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        });
+        self.publish_feedback_event(&feedback_event).await?;
+
+        self.update_boost_score(
+            instance,
+            &feedback.thought_id,
+            &feedback.action,
+            feedback.relevance_rating,
+            feedback.dwell_time,
+        ).await?;
+        Ok(())
+    }
+
+    async fn update_boost_score(&self, instance: &str, thought_id: &str, feedback_action: &str, relevance_rating: Option<i32>, dwell_time: Option<i32>) -> Result<f64> {
+        let increment = boost_increment(feedback_action, relevance_rating, dwell_time);
+        self.add_boost_score(instance, thought_id, increment).await
+    }
+
+    async fn get_boost_score(&self, instance: &str, thought_id: &str) -> Result<f64> {
+        Ok(self.store().boost_scores.get(&keys::boost_scores(instance))
+            .and_then(|scores| scores.get(thought_id))
+            .copied()
+            .unwrap_or(0.0))
+    }
+
+    async fn add_boost_score(&self, instance: &str, thought_id: &str, amount: f64) -> Result<f64> {
+        let mut store = self.store();
+        let score = store.boost_scores.entry(keys::boost_scores(instance))
+            .or_default()
+            .entry(thought_id.to_string())
+            .or_insert(0.0);
+        *score += amount;
+        Ok(*score)
+    }
+
+    async fn apply_boost_scores(&self, instance: &str, thoughts: &mut Vec<ThoughtRecord>) -> Result<()> {
+        if thoughts.is_empty() {
+            return Ok(());
+        }
+
+        {
+            let store = self.store();
+            let scores = store.boost_scores.get(&keys::boost_scores(instance));
+            for thought in thoughts.iter_mut() {
+                let boost_score = scores.and_then(|s| s.get(&thought.id)).copied().unwrap_or(0.0) as f32;
+                thought.similarity = Some(match thought.similarity {
+                    Some(similarity) => similarity + boost_score * BOOST_WEIGHT,
+                    None => boost_score,
+                });
+            }
+        }
+
+        thoughts.sort_by(|a, b| {
+            let score_a = a.similarity.unwrap_or(0.0);
+            let score_b = b.similarity.unwrap_or(0.0);
+            score_b.partial_cmp(&score_a).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        Ok(())
+    }
+
+    async fn get_citing_thoughts(&self, instance: &str, note_path: &str) -> Result<Vec<String>> {
+        Ok(self.store().citations.get(&keys::citations(instance, note_path))
+            .map(|ids| ids.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+}
+
+// ===== IDENTITY OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl IdentityOperations for MemoryRepository {
+    async fn get_identity(&self, identity_key: &str) -> Result<Option<Identity>> {
+        Ok(self.store().identities.get(identity_key).cloned())
+    }
+}
+
+// ===== IDENTITY DOCUMENT OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl IdentityDocumentOperations for MemoryRepository {
+    async fn get_identity_documents_by_field(&self, instance_id: &str, field_type: &str) -> Result<Vec<IdentityDocument>> {
+        // Same matching as the Redis key pattern: "relationships" also finds "relationships:{person}"
+        let nested = format!("{}:", field_type);
+        let mut documents: Vec<IdentityDocument> = self.store().identity_documents.values()
+            .filter(|d| d.instance == instance_id && (d.field_type == field_type || d.field_type.starts_with(&nested)))
+            .cloned()
+            .collect();
+
+        // Sort by creation date (newest first)
+        documents.sort_by_key(|d| std::cmp::Reverse(d.created_at));
+        Ok(documents)
+    }
+
+    async fn save_identity_document(&self, document: &IdentityDocument) -> Result<()> {
+        {
+            let mut store = self.store();
+            Self::record_identity_change(&mut store, &document.instance, IdentityChange::saved(document));
+            store.identity_documents.insert(document.redis_key(), document.clone());
+            *store.identity_versions.entry(keys::identity_version(&document.instance)).or_default() += 1;
+        }
+        self.log_event(
+            &document.instance,
+            "identity_document_saved",
+            vec![("field_type", &document.field_type), ("document_id", &document.id)],
+        ).await
+    }
+
+    async fn delete_identity_document(&self, instance_id: &str, field_type: &str, document_id: &str) -> Result<()> {
+        let key = keys::identity_document(instance_id, field_type, document_id);
+        {
+            let mut store = self.store();
+            Self::record_identity_change(&mut store, instance_id, IdentityChange::deleted(field_type, document_id));
+            store.identity_documents.remove(&key);
+            *store.identity_versions.entry(keys::identity_version(instance_id)).or_default() += 1;
+        }
+        self.log_event(
+            instance_id,
+            "identity_document_deleted",
+            vec![("field_type", field_type), ("document_id", document_id)],
+        ).await
+    }
+
+    async fn get_all_identity_documents(&self, instance_id: &str) -> Result<Vec<IdentityDocument>> {
+        Ok(self.store().identity_documents.values()
+            .filter(|d| d.instance == instance_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_identity_document_by_id(&self, instance_id: &str, document_id: &str) -> Result<Option<IdentityDocument>> {
+        Ok(self.store().identity_documents.values()
+            .find(|d| d.instance == instance_id && d.id == document_id)
+            .cloned())
+    }
+
+    async fn get_identity_version(&self, instance_id: &str) -> Result<u64> {
+        Ok(self.store().identity_versions.get(&keys::identity_version(instance_id)).copied().unwrap_or(0))
+    }
+
+    async fn get_identity_history(&self, instance_id: &str) -> Result<Vec<IdentityChange>> {
+        Ok(self.store().identity_history.get(&keys::identity_history(instance_id))
+            .map(|history| history.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+}
+
+// ===== EVENT OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl EventOperations for MemoryRepository {
+    async fn log_event(&self, instance: &str, event_type: &str, fields: Vec<(&str, &str)>) -> Result<()> {
+        let mut event = serde_json::Map::new();
+        event.insert("event_type".to_string(), event_type.into());
+        event.insert("timestamp".to_string(), chrono::Utc::now().to_rfc3339().into());
+        for (field, value) in fields {
+            event.insert(field.to_string(), value.into());
+        }
+        self.store().append_event(keys::events(instance), event.into());
+        Ok(())
+    }
+
+    async fn publish_feedback_event(&self, event: &serde_json::Value) -> Result<()> {
+        let instance = event.get("instance")
+            .and_then(|v| v.as_str())
+            .unwrap_or("global");
+        self.store().append_event(keys::feedback_events(instance), event.clone());
+        Ok(())
+    }
+
+    async fn publish_notification(&self, _channel: &str, _event: &serde_json::Value) -> Result<()> {
+        Ok(())
+    }
+}
+
+// ===== PURGE OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl PurgeOperations for MemoryRepository {
+    async fn purge_inventory(&self, namespace: &str) -> Result<Vec<String>> {
+        let prefix = format!("{}:", namespace);
+        let store = self.store();
+        let mut keys: BTreeSet<String> = store.keys().into_iter()
+            .filter(|key| key.starts_with(&prefix) && purge::same_tenant(namespace, key))
+            .cloned()
+            .collect();
+
+        // Chain metadata lives outside the instance prefix; resolve it from the chain lists
+        let chain_prefix = format!("{}:chains:", namespace);
+        let chain_metadata_keys: Vec<String> = keys.iter()
+            .filter_map(|key| key.strip_prefix(&chain_prefix))
+            .map(|chain_id| self.chain_metadata_key(chain_id))
+            .filter(|key| store.chain_metadata.contains_key(key))
+            .collect();
+        keys.extend(chain_metadata_keys);
+
+        Ok(keys.into_iter().collect())
+    }
+
+    async fn purge_keys(&self, keys: &[String]) -> Result<usize> {
+        let mut store = self.store();
+        Ok(keys.iter().filter(|key| store.remove(key)).count())
+    }
+
+    async fn save_purge_token(&self, namespace: &str, token: &str, ttl_seconds: u64) -> Result<()> {
+        let expires = Instant::now() + Duration::from_secs(ttl_seconds);
+        self.store().purge_tokens.insert(namespace.to_string(), (token.to_string(), expires));
+        Ok(())
+    }
+
+    async fn take_purge_token(&self, namespace: &str) -> Result<Option<String>> {
+        Ok(self.store().purge_tokens.remove(namespace)
+            .filter(|(_, expires)| Instant::now() < *expires)
+            .map(|(token, _)| token))
+    }
+}
+
+// ===== PII OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl PiiOperations for MemoryRepository {
+    async fn save_pii_record(&self, record: &PiiRecord) -> Result<()> {
+        let key = keys::pii(&record.instance, &record.thought_id);
+        self.store().pii_records.insert(key, record.clone());
+        Ok(())
+    }
+
+    async fn get_pii_records(&self, instance: &str, limit: usize) -> Result<Vec<PiiRecord>> {
+        let mut records: Vec<PiiRecord> = self.store().pii_records.values()
+            .filter(|r| r.instance == instance)
+            .cloned()
+            .collect();
+
+        // Most recent first
+        records.sort_by(|a, b| b.detected_at.cmp(&a.detected_at));
+        records.truncate(limit);
+        Ok(records)
+    }
+}
+
+// ===== CHAIN SYNC OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl ChainSyncOperations for MemoryRepository {
+    async fn save_chain_sync_state(&self, state: &ChainSyncState) -> Result<()> {
+        let key = keys::chain_sync(&state.instance, &state.chain_id);
+        self.store().chain_sync.insert(key, state.clone());
+        Ok(())
+    }
+
+    async fn get_chain_sync_state(&self, instance: &str, chain_id: &str) -> Result<Option<ChainSyncState>> {
+        Ok(self.store().chain_sync.get(&keys::chain_sync(instance, chain_id)).cloned())
+    }
+}
+
+// ===== SEARCH INDEX OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl SearchIndexOperations for MemoryRepository {
+    async fn register_search_instance(&self, instance: &str) -> Result<bool> {
+        Ok(self.store().search_prefixes.insert(search_index::thought_prefix(instance)))
+    }
+
+    async fn search_index_status(&self) -> Result<SearchIndexStatus> {
+        let store = self.store();
+        let mut discovered: Vec<String> = store.thoughts.keys()
+            .filter_map(|key| search_index::instance_from_key(key))
+            .map(str::to_string)
+            .collect();
+        discovered.dedup();
+
+        Ok(SearchIndexStatus {
+            registered_prefixes: store.search_prefixes.iter().cloned().collect(),
+            indexed_prefixes: None, // There is no index; search scans the maps
+            discovered_instances: discovered,
+            degraded_features: vec!["In-memory backend: nothing is persisted and recall uses substring search".to_string()],
+        })
+    }
+
+    async fn rebuild_search_index(&self) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+// ===== CLIENT OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl ClientOperations for MemoryRepository {
+    async fn record_client_session(&self, instance: &str, name: &str, version: &str, protocol_version: &str) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut store = self.store();
+        let stats = store.clients.entry(keys::client(instance, name)).or_default();
+        stats.name = name.to_string();
+        stats.version = version.to_string();
+        stats.protocol_version = protocol_version.to_string();
+        if stats.first_seen.is_empty() {
+            stats.first_seen = now.clone();
+        }
+        stats.last_seen = now;
+        stats.sessions += 1;
+        Ok(())
+    }
+
+    async fn record_client_tool_call(&self, instance: &str, name: &str, tool: &str, failed: bool) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut store = self.store();
+        let stats = store.clients.entry(keys::client(instance, name)).or_default();
+        stats.name = name.to_string();
+        if stats.first_seen.is_empty() {
+            stats.first_seen = now.clone();
+        }
+        stats.last_seen = now;
+        stats.total_calls += 1;
+        stats.errors += u64::from(failed);
+        *stats.tool_counts.entry(tool.to_string()).or_insert(0) += 1;
+        Ok(())
+    }
+
+    async fn get_client_stats(&self, instance: &str) -> Result<Vec<ClientStats>> {
+        let prefix = format!("{}:clients:", instance);
+        let mut clients: Vec<ClientStats> = self.store().clients.iter()
+            .filter(|(key, _)| key.starts_with(&prefix))
+            .map(|(_, stats)| stats.clone())
+            .collect();
+
+        // Most recently active first
+        clients.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+        Ok(clients)
+    }
+}
+
+// ===== CAPTURE OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl CaptureOperations for MemoryRepository {
+    async fn mark_captured(&self, instance: &str, item_id: &str) -> Result<bool> {
+        Ok(self.store().captured.entry(keys::capture_seen(instance)).or_default().insert(item_id.to_string()))
+    }
+
+    async fn unmark_captured(&self, instance: &str, item_id: &str) -> Result<()> {
+        if let Some(seen) = self.store().captured.get_mut(&keys::capture_seen(instance)) {
+            seen.remove(item_id);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EmbeddingOperations for MemoryRepository {
+    async fn get_embedding_version(&self, _instance: &str, _thought_id: &str) -> Result<Option<EmbeddingVersion>> {
+        // No embedding worker writes to the memory backend
+        Ok(None)
+    }
+
+    async fn queue_reembedding(&self, instance: &str, thought_id: &str, priority: f64) -> Result<()> {
+        self.store().embedding_stale.entry(keys::embedding_stale(instance)).or_default().insert(thought_id.to_string(), priority);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl IdentityTemplateOperations for MemoryRepository {
+    async fn get_identity_template(&self, name: &str) -> Result<Option<Identity>> {
+        Ok(self.store().identity_templates.get(&keys::identity_template(name)).cloned())
+    }
+
+    async fn save_identity_template(&self, name: &str, template: &Identity) -> Result<()> {
+        self.store().identity_templates.insert(keys::identity_template(name), template.clone());
+        Ok(())
+    }
+
+    async fn list_identity_templates(&self) -> Result<Vec<String>> {
+        let prefix = keys::identity_template("");
+        Ok(self.store().identity_templates.keys()
+            .filter_map(|key| key.strip_prefix(&prefix).map(str::to_string))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl DiagnosticsOperations for MemoryRepository {
+    async fn backend_diagnostics(&self) -> Result<BackendDiagnostics> {
+        Ok(BackendDiagnostics {
+            backend: "memory".to_string(),
+            redis_modules: None,
+            pool: None,
+        })
+    }
+
+    async fn save_crash_report(&self, instance: &str, report: &CrashReport) -> Result<()> {
+        let mut store = self.store();
+        let reports = store.crash_reports.entry(keys::crash_reports(instance)).or_default();
+        reports.push_front(report.clone());
+        reports.truncate(crash_report::MAX_CRASH_REPORTS);
+        Ok(())
+    }
+
+    async fn get_crash_reports(&self, instance: &str, limit: usize) -> Result<Vec<CrashReport>> {
+        Ok(self.store().crash_reports.get(&keys::crash_reports(instance))
+            .map(|reports| reports.iter().take(limit).cloned().collect())
+            .unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl PersonaOperations for MemoryRepository {
+    async fn save_persona_snapshot(&self, instance: &str, bundle: &PersonaBundle) -> Result<()> {
+        self.store().persona_snapshots.entry(keys::persona_snapshots(instance)).or_default()
+            .insert(bundle.version, bundle.clone());
+        Ok(())
+    }
+
+    async fn get_persona_snapshot(&self, instance: &str, version: u64) -> Result<Option<PersonaBundle>> {
+        Ok(self.store().persona_snapshots.get(&keys::persona_snapshots(instance))
+            .and_then(|snapshots| snapshots.get(&version).cloned()))
+    }
+
+    async fn list_persona_versions(&self, instance: &str) -> Result<Vec<u64>> {
+        Ok(self.store().persona_snapshots.get(&keys::persona_snapshots(instance))
+            .map(|snapshots| snapshots.keys().copied().collect())
+            .unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl AnnotationOperations for MemoryRepository {
+    async fn save_annotation(&self, instance: &str, annotation: &Annotation) -> Result<()> {
+        let mut store = self.store();
+        let stored = store.annotations.entry(keys::annotations(instance, &annotation.thought_id)).or_default();
+        stored.push_front(annotation.clone());
+        stored.truncate(annotations::MAX_ANNOTATIONS);
+        Ok(())
+    }
+
+    async fn get_annotations(&self, instance: &str, thought_id: &str) -> Result<Vec<Annotation>> {
+        Ok(self.store().annotations.get(&keys::annotations(instance, thought_id))
+            .map(|annotations| annotations.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl TieringOperations for MemoryRepository {
+    async fn replace_thought(&self, thought: &ThoughtRecord) -> Result<()> {
+        self.store().thoughts.insert(keys::thought(&thought.instance, &thought.id), thought.clone());
+        Ok(())
+    }
+
+    async fn get_last_access(&self, _instance: &str, _thought_id: &str) -> Result<Option<i64>> {
+        // Reads are not tracked in memory
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl ModeOperations for MemoryRepository {
+    async fn get_active_mode(&self, instance: &str) -> Result<Option<String>> {
+        Ok(self.store().active_modes.get(&keys::mode(instance)).cloned())
+    }
+
+    async fn set_active_mode(&self, instance: &str, mode: &str) -> Result<()> {
+        self.store().active_modes.insert(keys::mode(instance), mode.to_string());
+        Ok(())
+    }
+
+    async fn save_mode_profile(&self, instance: &str, profile: &ModeProfile) -> Result<()> {
+        self.store().modes.entry(keys::modes(instance)).or_default().insert(profile.name.clone(), profile.clone());
+        Ok(())
+    }
+
+    async fn get_mode_profiles(&self, instance: &str) -> Result<Vec<ModeProfile>> {
+        Ok(self.store().modes.get(&keys::modes(instance))
+            .map(|profiles| profiles.values().cloned().collect())
+            .unwrap_or_default())
+    }
+}
+
+/// Process memory isn't tracked, so the memory guard never engages
+#[async_trait]
+impl PressureOperations for MemoryRepository {
+    async fn memory_usage(&self) -> Result<Option<MemoryUsage>> {
+        Ok(None)
+    }
+
+    async fn set_essential_ingest(&self, _essential: bool) {}
+
+    async fn publish_intervention(&self, _intervention: &serde_json::Value) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MigrationOperations for MemoryRepository {
+    async fn get_applied_migrations(&self, instance: &str) -> Result<Vec<AppliedMigration>> {
+        Ok(self.store().migrations.get(&keys::migrations(instance))
+            .map(|applied| applied.values().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn record_migration(&self, instance: &str, migration: &AppliedMigration) -> Result<()> {
+        self.store().migrations.entry(keys::migrations(instance)).or_default().insert(migration.version, migration.clone());
+        Ok(())
+    }
+
+    async fn acquire_migration_lock(&self, instance: &str, owner: &str, ttl_seconds: u64) -> Result<bool> {
+        let mut store = self.store();
+        let key = keys::migration_lock(instance);
+        if store.migration_locks.get(&key).is_some_and(|(_, expires)| Instant::now() < *expires) {
+            return Ok(false);
+        }
+        store.migration_locks.insert(key, (owner.to_string(), Instant::now() + Duration::from_secs(ttl_seconds)));
+        Ok(true)
+    }
+
+    async fn release_migration_lock(&self, instance: &str, owner: &str) -> Result<()> {
+        let mut store = self.store();
+        let key = keys::migration_lock(instance);
+        if store.migration_locks.get(&key).is_some_and(|(holder, _)| holder == owner) {
+            store.migration_locks.remove(&key);
+        }
+        Ok(())
+    }
+
+    async fn get_migration_lock(&self, instance: &str) -> Result<Option<String>> {
+        Ok(self.store().migration_locks.get(&keys::migration_lock(instance))
+            .filter(|(_, expires)| Instant::now() < *expires)
+            .map(|(owner, _)| owner.clone()))
+    }
+}
+
+// ===== BULK UPDATE OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl BulkUpdateOperations for MemoryRepository {
+    async fn save_bulk_undo(&self, instance: &str, record: &BulkUndoRecord, max_records: usize) -> Result<()> {
+        let mut store = self.store();
+        let records = store.bulk_undo.entry(keys::bulk_undo(instance)).or_default();
+        records.push_front(record.clone());
+        records.truncate(max_records);
+        Ok(())
+    }
+
+    async fn get_bulk_undo(&self, instance: &str, undo_id: &str) -> Result<Option<BulkUndoRecord>> {
+        Ok(self.store().bulk_undo.get(&keys::bulk_undo(instance))
+            .and_then(|records| records.iter().find(|r| r.undo_id == undo_id).cloned()))
+    }
+
+    async fn delete_bulk_undo(&self, instance: &str, undo_id: &str) -> Result<bool> {
+        let mut store = self.store();
+        let key = keys::bulk_undo(instance);
+        let Some(records) = store.bulk_undo.get_mut(&key) else {
+            return Ok(false);
+        };
+        let before = records.len();
+        records.retain(|r| r.undo_id != undo_id);
+        let deleted = records.len() < before;
+        if records.is_empty() {
+            store.bulk_undo.remove(&key);
+        }
+        Ok(deleted)
+    }
+
+    async fn claim_bulk_undo(&self, instance: &str, undo_id: &str, owner: &str, ttl_seconds: u64) -> Result<bool> {
+        let mut store = self.store();
+        let key = keys::bulk_undo_claim(instance, undo_id);
+        if store.bulk_undo_claims.get(&key).is_some_and(|(_, expires)| Instant::now() < *expires) {
+            return Ok(false);
+        }
+        store.bulk_undo_claims.insert(key, (owner.to_string(), Instant::now() + Duration::from_secs(ttl_seconds)));
+        Ok(true)
+    }
+
+    async fn release_bulk_undo(&self, instance: &str, undo_id: &str, owner: &str) -> Result<()> {
+        let mut store = self.store();
+        let key = keys::bulk_undo_claim(instance, undo_id);
+        if store.bulk_undo_claims.get(&key).is_some_and(|(holder, _)| holder == owner) {
+            store.bulk_undo_claims.remove(&key);
+        }
+        Ok(())
+    }
+}
+
+// ===== RECALL TUNING OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl RecallTuningOperations for MemoryRepository {
+    async fn get_recall_tuning(&self, instance: &str) -> Result<Option<RecallTuning>> {
+        Ok(self.store().recall_tuning.get(&keys::recall_tuning(instance)).cloned())
+    }
+
+    async fn save_recall_tuning(&self, instance: &str, tuning: &RecallTuning) -> Result<()> {
+        self.store().recall_tuning.insert(keys::recall_tuning(instance), tuning.clone());
+        Ok(())
+    }
+}
+
+// ===== STREAM OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl StreamOperations for MemoryRepository {
+    async fn read_stream(&self, key: &str) -> Result<Vec<StreamEntry>> {
+        Ok(self.store().streams.get(key)
+            .map(|stream| stream.iter()
+                .map(|(id, event)| StreamEntry {
+                    id: id.to_string(),
+                    timestamp_ms: stream_compaction::timestamp_ms(event),
+                    fields: event.clone(),
+                })
+                .collect())
+            .unwrap_or_default())
+    }
+
+    async fn trim_stream(&self, key: &str, last_id: &str) -> Result<usize> {
+        let last_id: u64 = last_id.parse().map_err(|_| crate::error::UnifiedIntelligenceError::Validation {
+            field: "last_id".to_string(),
+            reason: format!("'{}' is not a stream ID", last_id),
+        })?;
+        let mut store = self.store();
+        let Some(stream) = store.streams.get_mut(key) else {
+            return Ok(0);
+        };
+        let before = stream.len();
+        while stream.front().is_some_and(|(id, _)| *id <= last_id) {
+            stream.pop_front();
+        }
+        Ok(before - stream.len())
+    }
+
+    async fn archive_stream_entries(&self, instance: &str, entries: &[ArchivedStreamEntry], max_entries: usize) -> Result<()> {
+        let mut store = self.store();
+        let archive = store.stream_archive.entry(keys::stream_archive(instance)).or_default();
+        for entry in entries {
+            archive.push_front(entry.clone());
+        }
+        archive.truncate(max_entries);
+        Ok(())
+    }
+
+    async fn get_stream_compaction(&self, instance: &str) -> Result<Option<StreamCompaction>> {
+        Ok(self.store().stream_compaction.get(&keys::stream_compaction(instance)).cloned())
+    }
+
+    async fn save_stream_compaction(&self, instance: &str, compaction: &StreamCompaction) -> Result<()> {
+        self.store().stream_compaction.insert(keys::stream_compaction(instance), compaction.clone());
+        Ok(())
+    }
+}
+
+// ===== IMPORTANCE OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl ImportanceOperations for MemoryRepository {
+    async fn record_recalls(&self, instance: &str, thought_ids: &[String]) -> Result<()> {
+        let mut store = self.store();
+        let counts = store.recall_counts.entry(keys::recall_counts(instance)).or_default();
+        for thought_id in thought_ids {
+            *counts.entry(thought_id.clone()).or_insert(0.0) += 1.0;
+        }
+        Ok(())
+    }
+
+    async fn get_recall_count(&self, instance: &str, thought_id: &str) -> Result<u64> {
+        Ok(self.store().recall_counts.get(&keys::recall_counts(instance))
+            .and_then(|counts| counts.get(thought_id))
+            .map_or(0, |count| *count as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thought(instance: &str, text: &str, chain_id: Option<&str>) -> ThoughtRecord {
+        ThoughtRecord::new(instance.to_string(), text.to_string(), 1, 1, chain_id.map(str::to_string), false)
+    }
+
+    #[tokio::test]
+    async fn test_save_search_and_chain_order() {
+        let repo = MemoryRepository::new(None);
+        let first = thought("CC", "Redis cache eviction policy", Some("c1"));
+        let second = thought("CC", "Deployment checklist", Some("c1"));
+        repo.save_thought(&first).await.unwrap();
+        repo.save_thought(&second).await.unwrap();
+        repo.save_thought(&first).await.unwrap(); // Duplicate is ignored
+
+        let chain = repo.get_chain_thoughts("CC", "c1").await.unwrap();
+        assert_eq!(chain.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), vec![first.id.as_str(), second.id.as_str()]);
+
+        let found = repo.search_thoughts("CC", "CACHE", &[], 10).await.unwrap();
+        assert_eq!(found.len(), 1);
+
+        let ranked = repo.search_thoughts_semantic_global("redis eviction", 10, 0.5).await.unwrap();
+        assert_eq!(ranked[0].id, first.id);
+        assert_eq!(ranked[0].similarity, Some(1.0));
+    }
+
+    #[tokio::test]
+    async fn test_purge_removes_namespace_keys() {
+        let repo = MemoryRepository::new(None);
+        let t = thought("DT", "to be purged", Some("c9"));
+        repo.save_thought(&t).await.unwrap();
+        repo.save_chain_metadata(&ChainMetadata {
+            chain_id: "c9".to_string(),
+            created_at: t.timestamp.clone(),
+            thought_count: 1,
+            instance: "DT".to_string(),
+            user_id: None,
+            related: Vec::new(),
+            planned_thoughts: None,
+            observed_thoughts: None,
+            last_updated: None,
+            next_hints: None,
+        }).await.unwrap();
+        repo.save_thought(&thought("DTX", "other instance", None)).await.unwrap();
+
+        let keys = repo.purge_inventory("DT").await.unwrap();
+        assert!(keys.contains(&format!("DT:Thoughts:{}", t.id)));
+        assert!(keys.contains(&"Chains:metadata:c9".to_string()));
+        assert!(keys.iter().all(|k| !k.starts_with("DTX:")));
+
+        assert_eq!(repo.purge_keys(&keys).await.unwrap(), keys.len());
+        assert!(repo.get_thought("DT", &t.id).await.unwrap().is_none());
+        assert!(!repo.chain_exists("c9").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_purge_user_keeps_users_sharing_a_prefix() {
+        let repo = MemoryRepository::new(None);
+        let alice = thought("users:alice:CC", "alice's thought", None);
+        repo.save_thought(&alice).await.unwrap();
+        repo.save_thought(&thought("users:alice-x:CC", "alice-x's thought", None)).await.unwrap();
+        repo.save_thought(&thought("users:alicex:CC", "alicex's thought", None)).await.unwrap();
+
+        let keys = repo.purge_inventory("users:alice").await.unwrap();
+        assert!(keys.contains(&format!("users:alice:CC:Thoughts:{}", alice.id)));
+        assert!(keys.iter().all(|key| tenant::user_of(key) == Some("alice")), "{:?}", keys);
+    }
+
+    #[tokio::test]
+    async fn test_delete_cascades() {
+        let repo = MemoryRepository::new(None);
+        let kept = thought("DL", "kept thought", Some("c1"));
+        let dropped = thought("DL", "dropped thought", Some("c1"));
+        repo.save_thought(&kept).await.unwrap();
+        repo.save_thought(&dropped).await.unwrap();
+        let metadata = ThoughtMetadata::new(dropped.id.clone(), "DL".to_string(), None, None, Some(vec!["gone".to_string()]), None);
+        repo.save_thought_metadata(&metadata).await.unwrap();
+        let feedback = UiRecallFeedbackParams {
+            search_id: "s1".to_string(),
+            thought_id: dropped.id.clone(),
+            action: "used".to_string(),
+            dwell_time: None,
+            relevance_rating: Some(4),
+        };
+        repo.record_feedback(&feedback, "DL").await.unwrap();
+
+        assert!(repo.delete_thought("DL", &dropped.id).await.unwrap());
+        assert!(!repo.delete_thought("DL", &dropped.id).await.unwrap());
+        let inventory = repo.purge_inventory("DL").await.unwrap();
+        assert!(inventory.iter().all(|key| !key.contains(&dropped.id) && !key.ends_with(":tags:gone") && !key.ends_with(":boost_scores")));
+        assert!(repo.store().streams[&keys::feedback_events("DL")].is_empty());
+        assert_eq!(repo.get_chain_thoughts("DL", "c1").await.unwrap().len(), 1);
+
+        assert_eq!(repo.delete_chain("DL", "c1").await.unwrap(), 1);
+        let inventory = repo.purge_inventory("DL").await.unwrap();
+        assert_eq!(inventory, vec![keys::events("DL"), keys::feedback_events("DL")]);
+    }
+
+    #[tokio::test]
+    async fn test_replaced_metadata_reindexes_tags_and_keeps_undo_records() {
+        let repo = MemoryRepository::new(None);
+        let t = thought("BU", "retagged thought", None);
+        repo.save_thought(&t).await.unwrap();
+        let mut metadata = ThoughtMetadata::new(t.id.clone(), "BU".to_string(), None, None, Some(vec!["draft".to_string()]), None);
+        repo.save_thought_metadata(&metadata).await.unwrap();
+        metadata.tags = Some(vec!["final".to_string()]);
+        repo.save_thought_metadata(&metadata).await.unwrap();
+        assert!(repo.get_thoughts_by_tags("BU", &["draft".to_string()]).await.unwrap().is_empty());
+        assert_eq!(repo.get_thoughts_by_tags("BU", &["final".to_string()]).await.unwrap(), vec![t.id.clone()]);
+
+        for n in 0..3 {
+            let record = BulkUndoRecord { undo_id: format!("u{}", n), created_at: String::new(), summary: String::new(), entries: Vec::new() };
+            repo.save_bulk_undo("BU", &record, 2).await.unwrap();
+        }
+        assert!(repo.get_bulk_undo("BU", "u0").await.unwrap().is_none());
+        assert!(repo.delete_bulk_undo("BU", "u2").await.unwrap());
+        assert!(!repo.delete_bulk_undo("BU", "u2").await.unwrap());
+        assert_eq!(repo.bulk_undo_records("BU").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_streams_are_read_and_trimmed_by_id() {
+        let repo = MemoryRepository::new(None);
+        for n in 0..3 {
+            repo.log_event("ST", "step", vec![("n", &n.to_string())]).await.unwrap();
+        }
+        let entries = repo.read_stream(&keys::events("ST")).await.unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|entry| entry.timestamp_ms.is_some()));
+        assert_eq!(repo.trim_stream(&keys::events("ST"), &entries[1].id).await.unwrap(), 2);
+        let left = repo.read_stream(&keys::events("ST")).await.unwrap();
+        assert_eq!((left.len(), left[0].id.as_str(), left[0].fields["n"].as_str()), (1, entries[2].id.as_str(), Some("2")));
+        assert!(repo.trim_stream(&keys::events("ST"), "not-an-id").await.is_err());
+    }
+}
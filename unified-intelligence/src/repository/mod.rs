@@ -1,5 +1,7 @@
 mod traits;
 mod redis_impl;
+mod memory_impl;
+mod sqlite_impl;
 
 #[cfg(test)]
 mod test_mock;
@@ -14,11 +16,55 @@ pub use traits::{
     IdentityOperations,
     IdentityDocumentOperations,
     EventOperations,
+    PurgeOperations,
+    PiiOperations,
+    ChainSyncOperations,
+    SearchIndexOperations,
+    ClientOperations,
+    CaptureOperations,
+    EmbeddingOperations,
+    IdentityTemplateOperations,
+    DiagnosticsOperations,
+    PersonaOperations,
+    AnnotationOperations,
+    TieringOperations,
+    ModeOperations,
+    PressureOperations,
+    MigrationOperations,
+    BulkUpdateOperations,
+    RecallTuningOperations,
+    StreamOperations,
+    ImportanceOperations,
     Repository,
 };
 
-// Re-export the implementation
+// Re-export the implementations
 pub use redis_impl::RedisRepository;
+pub use memory_impl::MemoryRepository;
+pub use sqlite_impl::{sqlite_fallback, sqlite_path, SqliteRepository};
+
+/// Storage backend selected with UI_STORAGE_BACKEND (or STORAGE_BACKEND): "redis", the default, "sqlite" or "memory"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageBackend {
+    Redis,
+    Sqlite,
+    Memory,
+}
+
+impl StorageBackend {
+    pub fn from_env() -> Self {
+        let backend = std::env::var("UI_STORAGE_BACKEND").or_else(|_| std::env::var("STORAGE_BACKEND")).unwrap_or_default();
+        match backend.trim().to_lowercase().as_str() {
+            "sqlite" | "sqlite3" => StorageBackend::Sqlite,
+            "memory" | "in-memory" | "inmemory" => StorageBackend::Memory,
+            "" | "redis" => StorageBackend::Redis,
+            other => {
+                tracing::warn!("Unknown UI_STORAGE_BACKEND '{}', using redis", other);
+                StorageBackend::Redis
+            }
+        }
+    }
+}
 
 // For backwards compatibility, keep ThoughtRepository as an alias
 
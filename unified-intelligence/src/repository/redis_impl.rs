@@ -2,42 +2,109 @@ use async_trait::async_trait;
 use std::sync::Arc;
 
 use crate::error::Result;
-use crate::models::{ThoughtRecord, ChainMetadata, Identity, ThoughtMetadata, UiRecallFeedbackParams};
+use crate::models::{ThoughtRecord, ChainMetadata, Identity, ThoughtMetadata, UiRecallFeedbackParams, PiiRecord, ChainSyncState, SearchIndexStatus, ClientStats, EmbeddingVersion, BackendDiagnostics, CrashReport, PersonaBundle, Annotation, ModeProfile, MemoryUsage, AppliedMigration, BulkUndoRecord, RecallTuning, StreamEntry, ArchivedStreamEntry, StreamCompaction};
 use crate::redis::RedisManager;
-use crate::search_optimization::SearchCache;
+use crate::search_optimization::{boost_increment, SearchCache, BOOST_WEIGHT};
 use crate::redisvl_service::RedisVLService;
 use crate::identity_documents::IdentityDocument;
+use crate::identity_history::{self, IdentityChange};
+use crate::keys;
+use crate::search_index::{self, SearchField};
+use crate::embedding_version;
+use crate::memory_guard;
+use crate::crash_report;
+use crate::annotations;
+use crate::tenant;
+use crate::purge;
+use crate::stream_compaction;
 use super::*;
 
 /// Redis implementation of all repository traits
 pub struct RedisRepository {
     redis: Arc<RedisManager>,
     search_available: Arc<std::sync::atomic::AtomicBool>,
-    search_cache: Arc<std::sync::Mutex<SearchCache>>,
+    search_cache: SearchCache,
     vector_service: Arc<RedisVLService>,
+    user_id: Option<String>,
+    essential_ingest: std::sync::atomic::AtomicBool, // Set by the memory guard under critical pressure
 }
 
 impl RedisRepository {
     pub fn new(
         redis: Arc<RedisManager>, 
         search_available: Arc<std::sync::atomic::AtomicBool>,
-        search_cache: Arc<std::sync::Mutex<SearchCache>>,
+        search_cache: SearchCache,
         instance_id: String,
+        user_id: Option<String>,
     ) -> Self {
         Self {
             redis: redis.clone(),
             search_available,
             search_cache,
             vector_service: Arc::new(RedisVLService::new(instance_id, redis)),
+            user_id,
+            essential_ingest: std::sync::atomic::AtomicBool::new(false),
         }
     }
     
     fn thought_key(&self, instance: &str, thought_id: &str) -> String {
-        format!("{}:Thoughts:{}", instance, thought_id)
+        keys::thought(instance, thought_id)
     }
     
     fn chain_metadata_key(&self, chain_id: &str) -> String {
-        format!("Chains:metadata:{}", chain_id)
+        keys::chain_metadata(self.user_id.as_deref(), chain_id)
+    }
+    
+    /// Key pattern matching thoughts of every instance visible to this tenant
+    fn global_thoughts_pattern(&self) -> String {
+        format!("{}*:Thoughts:*", tenant::user_prefix(self.user_id.as_deref()))
+    }
+    
+    /// idx:thoughts only covers unscoped instance prefixes, so user-scoped tenants scan instead
+    fn search_index_usable(&self) -> bool {
+        self.user_id.is_none() && self.search_available.load(std::sync::atomic::Ordering::SeqCst)
+    }
+    
+    /// Stored JSON and record of an undo record
+    async fn bulk_undo_entry(&self, instance: &str, undo_id: &str) -> Result<Option<(String, BulkUndoRecord)>> {
+        for json in self.redis.lrange(&keys::bulk_undo(instance), 0, -1).await? {
+            if let Ok(record) = serde_json::from_str::<BulkUndoRecord>(&json) {
+                if record.undo_id == undo_id {
+                    return Ok(Some((json, record)));
+                }
+            }
+        }
+        Ok(None)
+    }
+    
+    /// Documents to record as the changelog baseline, when the instance has no changelog yet
+    async fn identity_baseline(&self, instance: &str) -> Result<Vec<IdentityChange>> {
+        if self.redis.lrange(&keys::identity_history(instance), 0, 0).await?.is_empty() {
+            Ok(identity_history::baseline(self.get_all_identity_documents(instance).await?))
+        } else {
+            Ok(Vec::new())
+        }
+    }
+    
+    /// Append identity changes (oldest first) to the instance's changelog
+    async fn record_identity_changes(&self, instance: &str, changes: Vec<IdentityChange>) -> Result<()> {
+        let values = changes.iter().map(serde_json::to_string).collect::<std::result::Result<Vec<_>, _>>()?;
+        self.redis.lpush_capped_persistent(&keys::identity_history(instance), &values, identity_history::MAX_CHANGES).await
+    }
+    
+    /// Drop cross-instance results that belong to a different user
+    fn retain_tenant_thoughts(&self, thoughts: &mut Vec<ThoughtRecord>) {
+        thoughts.retain(|t| tenant::belongs_to(self.user_id.as_deref(), &t.instance));
+    }
+    
+    /// Scan-search match, loading the thought's metadata only when tags or category are searched
+    async fn fallback_matches(&self, thought: &ThoughtRecord, query: &str, fields: &[SearchField]) -> bool {
+        let metadata = if fields.iter().any(|field| *field != SearchField::Content) {
+            self.get_thought_metadata(&thought.instance, &thought.id).await.ok().flatten()
+        } else {
+            None
+        };
+        search_index::matches(thought, metadata.as_ref(), query, fields)
     }
     
     /// Fallback search implementation when Redis Search is not available
@@ -45,6 +112,7 @@ impl RedisRepository {
         &self,
         instance: &str,
         query: &str,
+        fields: &[SearchField],
         limit: usize,
     ) -> Result<Vec<ThoughtRecord>> {
         let pattern = format!("{}:Thoughts:*", instance);
@@ -68,7 +136,7 @@ impl RedisRepository {
             };
             
             if let Ok(thought) = serde_json::from_str::<ThoughtRecord>(&json_str) {
-                if thought.thought.to_lowercase().contains(&query.to_lowercase()) {
+                if self.fallback_matches(&thought, query, fields).await {
                     thoughts.push(thought);
                     if thoughts.len() >= limit {
                         break;
@@ -83,14 +151,19 @@ impl RedisRepository {
     async fn fallback_search_global(
         &self,
         query: &str,
+        fields: &[SearchField],
         limit: usize,
     ) -> Result<Vec<ThoughtRecord>> {
-        // Search across all instances using wildcard pattern
-        let pattern = "*:Thoughts:*";
+        // Search across all instances of this tenant using wildcard pattern
+        let pattern = self.global_thoughts_pattern();
         let keys = self.redis.scan_match(&pattern, 200).await?; // Get more keys since we're searching globally
         
         let mut thoughts = Vec::new();
         for key in keys {
+            if !tenant::belongs_to(self.user_id.as_deref(), &key) {
+                continue;
+            }
+            
             // Try to get as JSON first, fallback to string
             let json_str = match self.redis.json_get::<serde_json::Value>(&key, ".").await {
                 Ok(Some(json_val)) => json_val.to_string(),
@@ -104,7 +177,7 @@ impl RedisRepository {
             };
             
             if let Ok(thought) = serde_json::from_str::<ThoughtRecord>(&json_str) {
-                if thought.thought.to_lowercase().contains(&query.to_lowercase()) {
+                if self.fallback_matches(&thought, query, fields).await {
                     thoughts.push(thought);
                     if thoughts.len() >= limit {
                         break;
@@ -238,10 +311,10 @@ impl RedisRepository {
 impl ThoughtStorage for RedisRepository {
     async fn save_thought(&self, thought: &ThoughtRecord) -> Result<()> {
         let thought_key = self.thought_key(&thought.instance, &thought.id);
-        let bloom_key = format!("{}:bloom:thoughts", thought.instance);
-        let ts_key = format!("{}:metrics:thought_count", thought.instance);
+        let bloom_key = keys::bloom(&thought.instance);
+        let ts_key = keys::thought_count(&thought.instance);
         let chain_key = thought.chain_id.as_ref()
-            .map(|id| format!("{}:chains:{}", thought.instance, id));
+            .map(|id| keys::chain(&thought.instance, id));
         
         // Serialize thought to JSON
         let thought_json = serde_json::to_string(thought)
@@ -276,27 +349,32 @@ impl ThoughtStorage for RedisRepository {
                 thought.thought.chars().take(50).collect::<String>()
             );
         } else {
-            // Publish thought_created event to Redis Streams for background embedding processing
-            let timestamp = chrono::DateTime::parse_from_rfc3339(&thought.timestamp)
-                .map(|dt| dt.timestamp())
-                .unwrap_or_else(|_| {
-                    std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs() as i64
-                });
+            if self.essential_ingest.load(std::sync::atomic::Ordering::SeqCst) {
+                // Under memory pressure the thought waits in the re-embedding queue instead of being embedded now
+                self.redis.zadd(&keys::embedding_stale(&thought.instance), &thought.id, embedding_version::reembed_priority(None)).await?;
+            } else {
+                // Publish thought_created event to Redis Streams for background embedding processing
+                let timestamp = chrono::DateTime::parse_from_rfc3339(&thought.timestamp)
+                    .map(|dt| dt.timestamp())
+                    .unwrap_or_else(|_| {
+                        std::time::SystemTime::now()
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .unwrap()
+                            .as_secs() as i64
+                    });
             
-            // Publish to Redis Streams for background embedding service
-            let event_data = serde_json::json!({
-                "type": "thought_created",
-                "thought_id": thought.id,
-                "instance": thought.instance,
-                "timestamp": timestamp,
-                "content_preview": thought.thought.chars().take(100).collect::<String>()
-            });
+                // Publish to Redis Streams for background embedding service
+                let event_data = serde_json::json!({
+                    "type": "thought_created",
+                    "thought_id": thought.id,
+                    "instance": thought.instance,
+                    "timestamp": timestamp,
+                    "content_preview": thought.thought.chars().take(100).collect::<String>()
+                });
             
-            if let Err(e) = self.redis.publish_stream_event(&thought.instance, "thought_created", &event_data).await {
-                tracing::debug!("Failed to publish thought_created event: {}. Background embedding may not be triggered.", e);
+                if let Err(e) = self.redis.publish_stream_event(&thought.instance, "thought_created", &event_data).await {
+                    tracing::debug!("Failed to publish thought_created event: {}. Background embedding may not be triggered.", e);
+                }
             }
             
             // Log thought created event
@@ -318,8 +396,8 @@ impl ThoughtStorage for RedisRepository {
     
     async fn get_thought(&self, instance: &str, thought_id: &str) -> Result<Option<ThoughtRecord>> {
         let thought_key = self.thought_key(instance, thought_id);
-        let access_count_key = format!("{}:metrics:access_count", instance);
-        let last_access_key = format!("{}:last_access", thought_key);
+        let access_count_key = keys::access_count(instance);
+        let last_access_key = keys::thought_last_access(instance, thought_id);
         
         // Get current timestamp
         let timestamp = std::time::SystemTime::now()
@@ -356,7 +434,7 @@ impl ThoughtStorage for RedisRepository {
     }
     
     async fn get_chain_thoughts(&self, _instance: &str, chain_id: &str) -> Result<Vec<ThoughtRecord>> {
-        let chain_key = format!("{}:chains:{}", _instance, chain_id);
+        let chain_key = keys::chain(_instance, chain_id);
         
         // Use atomic script to get all thoughts in chain
         let json_results = self.redis.get_chain_thoughts_atomic(&chain_key, _instance).await?;
@@ -406,12 +484,14 @@ impl ThoughtStorage for RedisRepository {
     }
     
     async fn get_all_thoughts(&self, limit: usize) -> Result<Vec<ThoughtRecord>> {
-        // Search for all thought keys across all instances
-        let pattern = "*:Thoughts:*";
-        let keys = self.redis.scan_match(pattern, limit * 2).await?; // Get more keys to ensure we have enough
+        // Search for all thought keys across all instances of this tenant
+        let pattern = self.global_thoughts_pattern();
+        let keys = self.redis.scan_match(&pattern, limit * 2).await?; // Get more keys to ensure we have enough
         
         let mut thoughts = Vec::new();
-        for key in keys.into_iter().take(limit) {
+        let tenant_keys = keys.into_iter()
+            .filter(|key| tenant::belongs_to(self.user_id.as_deref(), key));
+        for key in tenant_keys.take(limit) {
             // Try to get as JSON first, fallback to string
             let json_str = match self.redis.json_get::<serde_json::Value>(&key, ".").await {
                 Ok(Some(json_val)) => json_val.to_string(),
@@ -434,6 +514,50 @@ impl ThoughtStorage for RedisRepository {
         
         Ok(thoughts)
     }
+    
+    async fn delete_thought(&self, instance: &str, thought_id: &str) -> Result<bool> {
+        let thought_key = self.thought_key(instance, thought_id);
+        let Some(thought) = self.redis.json_get::<ThoughtRecord>(&thought_key, ".").await? else {
+            return Ok(false);
+        };
+        
+        // Tag and citation sets are indexed from the thought's metadata
+        if let Some(metadata) = self.get_thought_metadata(instance, thought_id).await? {
+            for tag in metadata.tags.iter().flatten() {
+                self.redis.srem(&keys::tag(instance, tag), thought_id).await?;
+            }
+            for citation in &metadata.citations {
+                self.redis.srem(&keys::citations(instance, &citation.note_path), thought_id).await?;
+            }
+        }
+        if let Some(chain_id) = &thought.chain_id {
+            self.redis.lrem(&keys::chain(instance, chain_id), thought_id).await?;
+        }
+        self.redis.zrem(&keys::boost_scores(instance), thought_id).await?;
+        self.redis.zrem(&keys::embedding_stale(instance), thought_id).await?;
+        self.redis.zrem(&keys::recall_counts(instance), thought_id).await?;
+        let feedback = self.redis.xdel_matching(&keys::feedback_events(instance), "thought_id", thought_id).await?;
+        self.redis.del_many(&[
+            keys::thought_metadata(instance, thought_id),
+            keys::thought_last_access(instance, thought_id),
+            keys::embedding(instance, thought_id),
+            keys::pii(instance, thought_id),
+            keys::annotations(instance, thought_id),
+            thought_key,
+        ]).await?;
+        
+        // Cached search results may still reference the thought
+        self.search_cache.clear();
+        
+        let _ = self.redis.log_thought_event(
+            instance,
+            "thought_deleted",
+            thought_id,
+            thought.chain_id.as_deref(),
+            Some(vec![("feedback_events", &feedback.to_string())]),
+        ).await;
+        Ok(true)
+    }
 }
 
 // ===== THOUGHT SEARCH IMPLEMENTATION =====
@@ -443,26 +567,28 @@ impl ThoughtSearch for RedisRepository {
         &self,
         instance: &str,
         query: &str,
+        fields: &[SearchField],
         limit: usize,
     ) -> Result<Vec<ThoughtRecord>> {
+        let fields = search_index::resolve_fields(fields);
+        let field_names: Vec<&str> = fields.iter().map(SearchField::name).collect();
+        
         // Create cache key
-        let cache_key = format!("{}_{}_{}", query, instance, limit);
+        let cache_key = format!("{}_{}_{}_{}", query, instance, field_names.join("|"), limit);
         
         // Check cache first
-        if let Ok(cache) = self.search_cache.lock() {
-            if let Some(cached_results) = cache.get(&cache_key) {
-                tracing::debug!("Cache hit for search: {}", cache_key);
-                return Ok(cached_results.clone());
-            }
+        if let Some(cached_results) = self.search_cache.get(&cache_key).await {
+            tracing::debug!("Cache hit for search: {}", cache_key);
+            return Ok(cached_results);
         }
         
         tracing::debug!("Cache miss for search: {}", cache_key);
         
         // Perform search
-        let thoughts = if self.search_available.load(std::sync::atomic::Ordering::SeqCst) {
-            let search_query = format!("(@content:{}) (@instance:{{{}}})", query, instance);
+        let thoughts = if self.search_index_usable() {
+            let search_query = format!("{} (@instance:{{{}}})", search_index::field_query(query, &fields), instance);
             
-            match self.redis.search_with_timeout("idx:thoughts", &search_query, limit).await {
+            match self.redis.search_with_timeout(search_index::THOUGHTS_INDEX, &search_query, limit).await {
                 Ok(results) => {
                     let mut thoughts = Vec::new();
                     for (key, _score) in results {
@@ -489,18 +615,16 @@ impl ThoughtSearch for RedisRepository {
                 }
                 Err(_) => {
                     // Fall through to scan-based search
-                    self.fallback_search(instance, query, limit).await?
+                    self.fallback_search(instance, query, &fields, limit).await?
                 }
             }
         } else {
             // Use fallback search
-            self.fallback_search(instance, query, limit).await?
+            self.fallback_search(instance, query, &fields, limit).await?
         };
         
         // Store in cache
-        if let Ok(mut cache) = self.search_cache.lock() {
-            cache.insert(cache_key, thoughts.clone());
-        }
+        self.search_cache.insert(cache_key, thoughts.clone()).await;
         
         Ok(thoughts)
     }
@@ -520,27 +644,29 @@ impl ThoughtSearch for RedisRepository {
     async fn search_thoughts_global(
         &self,
         query: &str,
+        fields: &[SearchField],
         limit: usize,
     ) -> Result<Vec<ThoughtRecord>> {
+        let fields = search_index::resolve_fields(fields);
+        let field_names: Vec<&str> = fields.iter().map(SearchField::name).collect();
+        
         // Create cache key for global search
-        let cache_key = format!("global_{}_{}", query, limit);
+        let cache_key = format!("global_{}{}_{}_{}", tenant::user_prefix(self.user_id.as_deref()), query, field_names.join("|"), limit);
         
         // Check cache first
-        if let Ok(cache) = self.search_cache.lock() {
-            if let Some(cached_results) = cache.get(&cache_key) {
-                tracing::debug!("Cache hit for global search: {}", cache_key);
-                return Ok(cached_results.clone());
-            }
+        if let Some(cached_results) = self.search_cache.get(&cache_key).await {
+            tracing::debug!("Cache hit for global search: {}", cache_key);
+            return Ok(cached_results);
         }
         
         tracing::debug!("Cache miss for global search: {}", cache_key);
         
         // Perform search across all instances
-        let thoughts = if self.search_available.load(std::sync::atomic::Ordering::SeqCst) {
+        let thoughts = if self.search_index_usable() {
             // Search without instance filter to get results from all instances
-            let search_query = format!("(@content:{})", query);
+            let search_query = search_index::field_query(query, &fields);
             
-            match self.redis.search_with_timeout("idx:thoughts", &search_query, limit).await {
+            match self.redis.search_with_timeout(search_index::THOUGHTS_INDEX, &search_query, limit).await {
                 Ok(results) => {
                     tracing::info!("Global text search found {} results", results.len());
                     let mut thoughts = Vec::new();
@@ -565,17 +691,15 @@ impl ThoughtSearch for RedisRepository {
                 }
                 Err(e) => {
                     tracing::warn!("Global Redis search failed: {}, falling back to scan", e);
-                    self.fallback_search_global(query, limit).await?
+                    self.fallback_search_global(query, &fields, limit).await?
                 }
             }
         } else {
-            self.fallback_search_global(query, limit).await?
+            self.fallback_search_global(query, &fields, limit).await?
         };
         
         // Cache results
-        if let Ok(mut cache) = self.search_cache.lock() {
-            cache.insert(cache_key, thoughts.clone());
-        }
+        self.search_cache.insert(cache_key, thoughts.clone()).await;
         
         Ok(thoughts)
     }
@@ -590,7 +714,9 @@ impl ThoughtSearch for RedisRepository {
         
         // Use RedisVL service but with wildcard instance pattern
         let redisvl_service = RedisVLService::new("*".to_string(), self.redis.clone());
-        redisvl_service.semantic_search(query, limit, threshold).await
+        let mut thoughts = redisvl_service.semantic_search(query, limit, threshold).await?;
+        self.retain_tenant_thoughts(&mut thoughts);
+        Ok(thoughts)
     }
     
     async fn generate_search_id(&self) -> Result<String> {
@@ -674,7 +800,7 @@ impl EnhancedSearch for RedisRepository {
         
         // Build tag set keys
         let tag_keys: Vec<String> = tags.iter()
-            .map(|tag| format!("{}:tags:{}", instance, tag))
+            .map(|tag| keys::tag(instance, tag))
             .collect();
         
         // Get intersection of all tag sets
@@ -719,13 +845,62 @@ impl ChainOperations for RedisRepository {
         let key = self.chain_metadata_key(chain_id);
         self.redis.exists(&key).await
     }
+    
+    async fn get_chain_metadata(&self, chain_id: &str) -> Result<Option<ChainMetadata>> {
+        let key = self.chain_metadata_key(chain_id);
+        self.redis.json_get::<ChainMetadata>(&key, ".").await
+    }
+    
+    async fn list_chain_metadata(&self, instance: &str) -> Result<Vec<ChainMetadata>> {
+        let pattern = self.chain_metadata_key("*");
+        let keys = self.redis.scan_match(&pattern, 100).await?;
+        
+        let mut chains = Vec::new();
+        for key in keys {
+            match self.redis.json_get::<ChainMetadata>(&key, ".").await {
+                Ok(Some(metadata)) if metadata.instance == instance => chains.push(metadata),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Skipping unreadable chain metadata {}: {}", key, e),
+            }
+        }
+        Ok(chains)
+    }
+    
+    async fn delete_chain(&self, instance: &str, chain_id: &str) -> Result<usize> {
+        let mut deleted = 0;
+        for thought in self.get_chain_thoughts(instance, chain_id).await? {
+            if self.delete_thought(instance, &thought.id).await? {
+                deleted += 1;
+            }
+        }
+        self.redis.del_many(&[
+            keys::chain(instance, chain_id),
+            keys::chain_sync(instance, chain_id),
+            self.chain_metadata_key(chain_id),
+        ]).await?;
+        
+        let _ = self.redis.log_event(
+            instance,
+            "chain_deleted",
+            vec![("chain_id", chain_id), ("thoughts_deleted", &deleted.to_string())],
+        ).await;
+        Ok(deleted)
+    }
 }
 
 // ===== FEEDBACK OPERATIONS IMPLEMENTATION =====
 #[async_trait]
 impl FeedbackOperations for RedisRepository {
     async fn save_thought_metadata(&self, metadata: &ThoughtMetadata) -> Result<()> {
-        let key = format!("{}:thought_meta:{}", metadata.instance, metadata.thought_id);
+        let key = keys::thought_metadata(&metadata.instance, &metadata.thought_id);
+        let previous = self.get_thought_metadata(&metadata.instance, &metadata.thought_id).await?;
+        
+        // Replaced metadata leaves the tag sets of tags it no longer carries
+        if let Some(previous) = &previous {
+            for tag in previous.tags.iter().flatten().filter(|tag| !metadata.tags.iter().flatten().any(|t| t == *tag)) {
+                self.redis.srem(&keys::tag(&metadata.instance, tag), &metadata.thought_id).await?;
+            }
+        }
         
         // Store metadata as JSON
         let metadata_json = serde_json::to_string(metadata)
@@ -736,17 +911,44 @@ impl FeedbackOperations for RedisRepository {
         // Build tag indexes if tags are provided
         if let Some(ref tags) = metadata.tags {
             for tag in tags {
-                let tag_key = format!("{}:tags:{}", metadata.instance, tag);
+                let tag_key = keys::tag(&metadata.instance, tag);
                 self.redis.sadd(&tag_key, &metadata.thought_id).await?;
             }
         }
         
+        // Mirror tags and category onto the thought document so idx:thoughts can search them
+        let mirrored_before = previous.as_ref().is_some_and(|p| p.tags.is_some() || p.category.is_some());
+        if self.search_index_usable() && (metadata.tags.is_some() || metadata.category.is_some() || mirrored_before) {
+            let thought_key = keys::thought(&metadata.instance, &metadata.thought_id);
+            let mirrored = async {
+                match metadata.tags {
+                    Some(ref tags) => self.redis.json_set(&thought_key, "$.tags", tags).await?,
+                    None if mirrored_before => self.redis.json_set(&thought_key, "$.tags", &Vec::<String>::new()).await?,
+                    None => {}
+                }
+                match metadata.category {
+                    Some(ref category) => self.redis.json_set(&thought_key, "$.category", category).await?,
+                    None if mirrored_before => self.redis.json_set(&thought_key, "$.category", &String::new()).await?,
+                    None => {}
+                }
+                Ok::<(), crate::error::UnifiedIntelligenceError>(())
+            }.await;
+            if let Err(e) = mirrored {
+                tracing::debug!("Could not mirror tags onto thought {}: {}", metadata.thought_id, e);
+            }
+        }
+        
+        // Citation indexes live as long as the thoughts citing the note
+        for citation in &metadata.citations {
+            self.redis.sadd_new(&keys::citations(&metadata.instance, &citation.note_path), &metadata.thought_id).await?;
+        }
+        
         tracing::debug!("Saved metadata for thought {} in instance {}", metadata.thought_id, metadata.instance);
         Ok(())
     }
     
     async fn get_thought_metadata(&self, instance: &str, thought_id: &str) -> Result<Option<ThoughtMetadata>> {
-        let key = format!("{}:thought_meta:{}", instance, thought_id);
+        let key = keys::thought_metadata(instance, thought_id);
         
         match self.redis.json_get::<serde_json::Value>(&key, ".").await? {
             Some(json_val) => {
@@ -789,27 +991,9 @@ impl FeedbackOperations for RedisRepository {
     }
     
     async fn update_boost_score(&self, instance: &str, thought_id: &str, feedback_action: &str, relevance_rating: Option<i32>, dwell_time: Option<i32>) -> Result<f64> {
-        let boost_key = format!("{}:boost_scores", instance);
-        
-        // Calculate boost increment based on feedback action
-        let mut base_increment = match feedback_action {
-            "helpful" => 2.0,
-            "used" => 1.5,
-            "viewed" => {
-                // Award viewing boost based on dwell time
-                if let Some(dwell) = dwell_time {
-                    if dwell >= 30 { 0.5 } else if dwell >= 15 { 0.3 } else { 0.1 }
-                } else { 0.1 }
-            },
-            "irrelevant" => -1.0,
-            _ => 0.0,
-        };
+        let boost_key = keys::boost_scores(instance);
         
-        // Apply relevance rating multiplier if provided
-        if let Some(rating) = relevance_rating {
-            let multiplier = rating as f64 / 10.0; // Scale 1-10 to 0.1-1.0
-            base_increment *= multiplier;
-        }
+        let base_increment = boost_increment(feedback_action, relevance_rating, dwell_time);
         
         // Increment the boost score in Redis sorted set
         let new_score = self.redis.zincrby(&boost_key, thought_id, base_increment).await?;
@@ -821,12 +1005,21 @@ impl FeedbackOperations for RedisRepository {
     }
     
     
+    async fn get_boost_score(&self, instance: &str, thought_id: &str) -> Result<f64> {
+        let boost_key = keys::boost_scores(instance);
+        Ok(self.redis.zscore(&boost_key, thought_id).await?.unwrap_or(0.0))
+    }
+    
+    async fn add_boost_score(&self, instance: &str, thought_id: &str, amount: f64) -> Result<f64> {
+        self.redis.zincrby(&keys::boost_scores(instance), thought_id, amount).await
+    }
+    
     async fn apply_boost_scores(&self, instance: &str, thoughts: &mut Vec<ThoughtRecord>) -> Result<()> {
         if thoughts.is_empty() {
             return Ok(());
         }
         
-        let boost_key = format!("{}:boost_scores", instance);
+        let boost_key = keys::boost_scores(instance);
         
         // Get boost scores for all thoughts
         for thought in thoughts.iter_mut() {
@@ -835,7 +1028,7 @@ impl FeedbackOperations for RedisRepository {
             // Apply boost to similarity score (if present) or create composite score
             if let Some(sim_score) = thought.similarity {
                 // Combine semantic similarity + boost: similarity gets 90% weight, boost gets 10%
-                let boosted_score = sim_score + (boost_score as f32 * 0.1);
+                let boosted_score = sim_score + (boost_score as f32 * BOOST_WEIGHT);
                 thought.similarity = Some(boosted_score);
             } else {
                 // For non-semantic searches, use boost score directly
@@ -853,6 +1046,10 @@ impl FeedbackOperations for RedisRepository {
         tracing::debug!("Applied boost scores to {} thoughts in instance {}", thoughts.len(), instance);
         Ok(())
     }
+    
+    async fn get_citing_thoughts(&self, instance: &str, note_path: &str) -> Result<Vec<String>> {
+        self.redis.sinter(&[keys::citations(instance, note_path)]).await
+    }
 }
 
 // ===== IDENTITY OPERATIONS IMPLEMENTATION =====
@@ -889,9 +1086,13 @@ impl IdentityDocumentOperations for RedisRepository {
     async fn save_identity_document(&self, document: &IdentityDocument) -> Result<()> {
         let key = document.redis_key();
         let value = serde_json::to_value(document)?;
+        let mut changes = self.identity_baseline(&document.instance).await?;
         
         // Save the document
         self.redis.json_set(&key, ".", &value).await?;
+        self.redis.incr(&keys::identity_version(&document.instance)).await?;
+        changes.push(IdentityChange::saved(document));
+        self.record_identity_changes(&document.instance, changes).await?;
         
         // Log the event
         self.log_event(
@@ -907,10 +1108,14 @@ impl IdentityDocumentOperations for RedisRepository {
     }
     
     async fn delete_identity_document(&self, instance_id: &str, field_type: &str, document_id: &str) -> Result<()> {
-        let key = format!("{}:identity:{}:{}", instance_id, field_type, document_id);
+        let key = keys::identity_document(instance_id, field_type, document_id);
+        let mut changes = self.identity_baseline(instance_id).await?;
         
         // Delete the document
         self.redis.json_del(&key, ".").await?;
+        self.redis.incr(&keys::identity_version(instance_id)).await?;
+        changes.push(IdentityChange::deleted(field_type, document_id));
+        self.record_identity_changes(instance_id, changes).await?;
         
         // Log the event
         self.log_event(
@@ -981,6 +1186,25 @@ impl IdentityDocumentOperations for RedisRepository {
         Ok(None)
     }
     
+    async fn get_identity_version(&self, instance_id: &str) -> Result<u64> {
+        let version = self.redis.get(&keys::identity_version(instance_id)).await?;
+        Ok(version.and_then(|v| v.parse().ok()).unwrap_or(0))
+    }
+    
+    async fn get_identity_history(&self, instance_id: &str) -> Result<Vec<IdentityChange>> {
+        let entries = self.redis.lrange(&keys::identity_history(instance_id), 0, -1).await?;
+        let mut changes: Vec<IdentityChange> = entries.iter()
+            .filter_map(|entry| match serde_json::from_str(entry) {
+                Ok(change) => Some(change),
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable identity change for {}: {}", instance_id, e);
+                    None
+                }
+            })
+            .collect();
+        changes.reverse(); // Stored newest first
+        Ok(changes)
+    }
 }
 
 // ===== EVENT OPERATIONS IMPLEMENTATION =====
@@ -998,7 +1222,7 @@ impl EventOperations for RedisRepository {
             .and_then(|v| v.as_str())
             .unwrap_or("global");
         
-        let stream_key = format!("{}:feedback_events", instance);
+        let stream_key = keys::feedback_events(instance);
         
         // Convert JSON object to Redis Stream fields with owned strings
         let mut field_pairs = Vec::new();
@@ -1025,5 +1249,498 @@ impl EventOperations for RedisRepository {
         tracing::debug!("Published feedback event to stream {} with {} fields", stream_key, field_count);
         Ok(())
     }
+    
+    async fn publish_notification(&self, channel: &str, event: &serde_json::Value) -> Result<()> {
+        self.redis.publish(channel, &event.to_string()).await
+    }
+}
+
+
+// ===== PURGE OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl PurgeOperations for RedisRepository {
+    async fn purge_inventory(&self, namespace: &str) -> Result<Vec<String>> {
+        let patterns = [
+            format!("{}:*", namespace),
+            format!("ts:{}:*", namespace),
+            format!("vset:{}:*", namespace),
+            format!("metadata:{}:*", namespace),
+        ];
+        
+        let mut inventory = std::collections::BTreeSet::new();
+        for pattern in &patterns {
+            let keys = self.redis.scan_match(pattern, 1000).await?;
+            inventory.extend(keys.into_iter().filter(|key| purge::same_tenant(namespace, key)));
+        }
+        
+        // Chain metadata lives outside the instance prefix; resolve it from the chain lists
+        let chain_prefix = format!("{}:chains:", namespace);
+        let chain_metadata_keys: Vec<String> = inventory.iter()
+            .filter_map(|key| key.strip_prefix(&chain_prefix))
+            .map(|chain_id| self.chain_metadata_key(chain_id))
+            .collect();
+        for key in chain_metadata_keys {
+            if self.redis.exists(&key).await? {
+                inventory.insert(key);
+            }
+        }
+        
+        // Never report the pending confirmation token as user data
+        inventory.remove(&keys::purge_token(namespace));
+        
+        Ok(inventory.into_iter().collect())
+    }
+    
+    async fn purge_keys(&self, keys: &[String]) -> Result<usize> {
+        let removed = self.redis.del_many(keys).await?;
+        
+        // Cached search results may still reference purged thoughts
+        self.search_cache.clear();
+        
+        Ok(removed)
+    }
+    
+    async fn save_purge_token(&self, namespace: &str, token: &str, ttl_seconds: u64) -> Result<()> {
+        let key = keys::purge_token(namespace);
+        self.redis.set_ex(&key, token, ttl_seconds).await
+    }
+    
+    async fn take_purge_token(&self, namespace: &str) -> Result<Option<String>> {
+        let key = keys::purge_token(namespace);
+        self.redis.get_del(&key).await
+    }
+}
+
+// ===== PII OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl PiiOperations for RedisRepository {
+    async fn save_pii_record(&self, record: &PiiRecord) -> Result<()> {
+        let key = keys::pii(&record.instance, &record.thought_id);
+        self.redis.json_set(&key, ".", record).await?;
+        
+        tracing::debug!("Saved {} PII findings for thought {}", record.findings.len(), record.thought_id);
+        Ok(())
+    }
+    
+    async fn get_pii_records(&self, instance: &str, limit: usize) -> Result<Vec<PiiRecord>> {
+        let pattern = format!("{}:pii:*", instance);
+        let keys = self.redis.scan_match(&pattern, 100).await?;
+        
+        let mut records = Vec::new();
+        for key in keys {
+            if let Some(json_val) = self.redis.json_get::<serde_json::Value>(&key, ".").await? {
+                if let Ok(record) = serde_json::from_value::<PiiRecord>(json_val) {
+                    records.push(record);
+                }
+            }
+        }
+        
+        // Most recent first
+        records.sort_by(|a, b| b.detected_at.cmp(&a.detected_at));
+        records.truncate(limit);
+        Ok(records)
+    }
+}
+
+// ===== CHAIN SYNC OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl ChainSyncOperations for RedisRepository {
+    async fn save_chain_sync_state(&self, state: &ChainSyncState) -> Result<()> {
+        let key = keys::chain_sync(&state.instance, &state.chain_id);
+        self.redis.json_set(&key, ".", state).await?;
+        
+        tracing::debug!("Saved sync state for chain {} (last exported thought {})", state.chain_id, state.last_exported_thought);
+        Ok(())
+    }
+    
+    async fn get_chain_sync_state(&self, instance: &str, chain_id: &str) -> Result<Option<ChainSyncState>> {
+        let key = keys::chain_sync(instance, chain_id);
+        match self.redis.json_get::<serde_json::Value>(&key, ".").await? {
+            Some(json_val) => Ok(serde_json::from_value(json_val).ok()),
+            None => Ok(None),
+        }
+    }
+}
+
+// ===== SEARCH INDEX OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl SearchIndexOperations for RedisRepository {
+    async fn register_search_instance(&self, instance: &str) -> Result<bool> {
+        self.redis.add_search_prefix(&search_index::thought_prefix(instance)).await
+    }
+    
+    async fn search_index_status(&self) -> Result<SearchIndexStatus> {
+        let keys = self.redis.scan_match("*:Thoughts:*", 1000).await?;
+        let mut discovered: Vec<String> = keys.iter()
+            .filter_map(|key| search_index::instance_from_key(key))
+            .map(str::to_string)
+            .collect();
+        discovered.sort();
+        discovered.dedup();
+        
+        Ok(SearchIndexStatus {
+            registered_prefixes: self.redis.search_prefixes().await?,
+            indexed_prefixes: self.redis.indexed_prefixes().await?,
+            discovered_instances: discovered,
+            degraded_features: self.redis.capabilities().degraded_features().into_iter().map(str::to_string).collect(),
+        })
+    }
+    
+    async fn rebuild_search_index(&self) -> Result<bool> {
+        let rebuilt = self.redis.rebuild_search_index().await?;
+        self.search_available.store(rebuilt, std::sync::atomic::Ordering::SeqCst);
+        
+        // Results cached before the rebuild may be missing newly covered instances
+        self.search_cache.clear();
+        
+        Ok(rebuilt)
+    }
+}
+
+// ===== CLIENT OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl ClientOperations for RedisRepository {
+    async fn record_client_session(&self, instance: &str, name: &str, version: &str, protocol_version: &str) -> Result<()> {
+        let key = keys::client(instance, name);
+        let now = chrono::Utc::now().to_rfc3339();
+        self.redis.update_hash(
+            &key,
+            &[("name", name.to_string()), ("version", version.to_string()), ("protocol_version", protocol_version.to_string()), ("last_seen", now.clone())],
+            &[("first_seen", now)],
+            &[("sessions", 1)],
+        ).await
+    }
+    
+    async fn record_client_tool_call(&self, instance: &str, name: &str, tool: &str, failed: bool) -> Result<()> {
+        let key = keys::client(instance, name);
+        let now = chrono::Utc::now().to_rfc3339();
+        let tool_field = format!("{}{}", ClientStats::TOOL_FIELD_PREFIX, tool);
+        let mut increments = vec![("total_calls", 1), (tool_field.as_str(), 1)];
+        if failed {
+            increments.push(("errors", 1));
+        }
+        self.redis.update_hash(
+            &key,
+            &[("name", name.to_string()), ("last_seen", now.clone())],
+            &[("first_seen", now)],
+            &increments,
+        ).await
+    }
+    
+    async fn get_client_stats(&self, instance: &str) -> Result<Vec<ClientStats>> {
+        let pattern = format!("{}:clients:*", instance);
+        let keys = self.redis.scan_match(&pattern, 100).await?;
+        
+        let mut clients = Vec::new();
+        for key in keys {
+            let fields = self.redis.hgetall(&key).await?;
+            if !fields.is_empty() {
+                clients.push(ClientStats::from_hash(&fields));
+            }
+        }
+        
+        // Most recently active first
+        clients.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+        Ok(clients)
+    }
+}
+
+// ===== CAPTURE OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl CaptureOperations for RedisRepository {
+    async fn mark_captured(&self, instance: &str, item_id: &str) -> Result<bool> {
+        self.redis.sadd_new(&keys::capture_seen(instance), item_id).await
+    }
+    
+    async fn unmark_captured(&self, instance: &str, item_id: &str) -> Result<()> {
+        self.redis.srem(&keys::capture_seen(instance), item_id).await
+    }
+}
+
+// ===== EMBEDDING OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl EmbeddingOperations for RedisRepository {
+    async fn get_embedding_version(&self, instance: &str, thought_id: &str) -> Result<Option<EmbeddingVersion>> {
+        let record = self.redis.get(&keys::embedding(instance, thought_id)).await?;
+        Ok(record.as_deref().map(embedding_version::parse_record))
+    }
+    
+    async fn queue_reembedding(&self, instance: &str, thought_id: &str, priority: f64) -> Result<()> {
+        self.redis.zadd(&keys::embedding_stale(instance), thought_id, priority).await
+    }
+}
+
+// ===== IDENTITY TEMPLATE OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl IdentityTemplateOperations for RedisRepository {
+    async fn get_identity_template(&self, name: &str) -> Result<Option<Identity>> {
+        self.redis.json_get(&keys::identity_template(name), ".").await
+    }
+    
+    async fn save_identity_template(&self, name: &str, template: &Identity) -> Result<()> {
+        self.redis.json_set(&keys::identity_template(name), ".", template).await
+    }
+    
+    async fn list_identity_templates(&self) -> Result<Vec<String>> {
+        let prefix = keys::identity_template("");
+        let mut names: Vec<String> = self.redis.scan_match(&format!("{}*", prefix), 100).await?
+            .into_iter()
+            .filter_map(|key| key.strip_prefix(&prefix).map(str::to_string))
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+}
+
+// ===== DIAGNOSTICS OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl DiagnosticsOperations for RedisRepository {
+    async fn backend_diagnostics(&self) -> Result<BackendDiagnostics> {
+        Ok(BackendDiagnostics {
+            backend: "redis".to_string(),
+            redis_modules: self.redis.modules().map(<[String]>::to_vec),
+            pool: Some(self.redis.pool_stats()),
+        })
+    }
+    
+    async fn save_crash_report(&self, instance: &str, report: &CrashReport) -> Result<()> {
+        let json = serde_json::to_string(report)?;
+        self.redis.lpush_capped(&keys::crash_reports(instance), &json, crash_report::MAX_CRASH_REPORTS).await
+    }
+    
+    async fn get_crash_reports(&self, instance: &str, limit: usize) -> Result<Vec<CrashReport>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let reports = self.redis.lrange(&keys::crash_reports(instance), 0, limit as isize - 1).await?;
+        Ok(reports.iter().filter_map(|json| serde_json::from_str(json).ok()).collect())
+    }
+}
+
+// ===== PERSONA OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl PersonaOperations for RedisRepository {
+    async fn save_persona_snapshot(&self, instance: &str, bundle: &PersonaBundle) -> Result<()> {
+        let json = serde_json::to_string(bundle)?;
+        self.redis.hset(&keys::persona_snapshots(instance), &bundle.version.to_string(), &json).await
+    }
+    
+    async fn get_persona_snapshot(&self, instance: &str, version: u64) -> Result<Option<PersonaBundle>> {
+        match self.redis.hget(&keys::persona_snapshots(instance), &version.to_string()).await? {
+            Some(json) => Ok(Some(serde_json::from_str(&json)?)),
+            None => Ok(None),
+        }
+    }
+    
+    async fn list_persona_versions(&self, instance: &str) -> Result<Vec<u64>> {
+        let mut versions: Vec<u64> = self.redis.hkeys(&keys::persona_snapshots(instance)).await?
+            .iter()
+            .filter_map(|version| version.parse().ok())
+            .collect();
+        versions.sort_unstable();
+        Ok(versions)
+    }
+}
+
+// ===== ANNOTATION OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl AnnotationOperations for RedisRepository {
+    async fn save_annotation(&self, instance: &str, annotation: &Annotation) -> Result<()> {
+        let json = serde_json::to_string(annotation)?;
+        self.redis.lpush_capped(&keys::annotations(instance, &annotation.thought_id), &json, annotations::MAX_ANNOTATIONS).await
+    }
+    
+    async fn get_annotations(&self, instance: &str, thought_id: &str) -> Result<Vec<Annotation>> {
+        let annotations = self.redis.lrange(&keys::annotations(instance, thought_id), 0, -1).await?;
+        Ok(annotations.iter().filter_map(|json| serde_json::from_str(json).ok()).collect())
+    }
+}
+
+// ===== TIERING OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl TieringOperations for RedisRepository {
+    async fn replace_thought(&self, thought: &ThoughtRecord) -> Result<()> {
+        let thought_key = self.thought_key(&thought.instance, &thought.id);
+        self.redis.json_set(&thought_key, ".", thought).await
+    }
+    
+    async fn get_last_access(&self, instance: &str, thought_id: &str) -> Result<Option<i64>> {
+        let last_access = self.redis.get(&keys::thought_last_access(instance, thought_id)).await?;
+        Ok(last_access.and_then(|at| at.parse().ok()))
+    }
+}
+
+// ===== MODE OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl ModeOperations for RedisRepository {
+    async fn get_active_mode(&self, instance: &str) -> Result<Option<String>> {
+        self.redis.get(&keys::mode(instance)).await
+    }
+    
+    async fn set_active_mode(&self, instance: &str, mode: &str) -> Result<()> {
+        self.redis.set(&keys::mode(instance), mode).await
+    }
+    
+    async fn save_mode_profile(&self, instance: &str, profile: &ModeProfile) -> Result<()> {
+        self.redis.hset(&keys::modes(instance), &profile.name, &serde_json::to_string(profile)?).await
+    }
+    
+    async fn get_mode_profiles(&self, instance: &str) -> Result<Vec<ModeProfile>> {
+        let mut profiles: Vec<ModeProfile> = self.redis.hgetall(&keys::modes(instance)).await?
+            .values()
+            .filter_map(|json| serde_json::from_str(json).ok())
+            .collect();
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(profiles)
+    }
+}
+
+// ===== PRESSURE OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl PressureOperations for RedisRepository {
+    async fn memory_usage(&self) -> Result<Option<MemoryUsage>> {
+        Ok(Some(self.redis.memory_info().await?))
+    }
+    
+    async fn set_essential_ingest(&self, essential: bool) {
+        self.essential_ingest.store(essential, std::sync::atomic::Ordering::SeqCst);
+    }
+    
+    async fn publish_intervention(&self, intervention: &serde_json::Value) -> Result<()> {
+        self.redis.publish(memory_guard::INTERVENTION_CHANNEL, &intervention.to_string()).await
+    }
+}
+
+// ===== MIGRATION OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl MigrationOperations for RedisRepository {
+    async fn get_applied_migrations(&self, instance: &str) -> Result<Vec<AppliedMigration>> {
+        let mut applied: Vec<AppliedMigration> = self.redis.hgetall(&keys::migrations(instance)).await?
+            .values()
+            .filter_map(|json| serde_json::from_str(json).ok())
+            .collect();
+        applied.sort_by_key(|migration| migration.version);
+        Ok(applied)
+    }
+    
+    async fn record_migration(&self, instance: &str, migration: &AppliedMigration) -> Result<()> {
+        self.redis.hset(&keys::migrations(instance), &migration.version.to_string(), &serde_json::to_string(migration)?).await
+    }
+    
+    async fn acquire_migration_lock(&self, instance: &str, owner: &str, ttl_seconds: u64) -> Result<bool> {
+        self.redis.set_nx_ex(&keys::migration_lock(instance), owner, ttl_seconds).await
+    }
+    
+    async fn release_migration_lock(&self, instance: &str, owner: &str) -> Result<()> {
+        self.redis.del_if_eq(&keys::migration_lock(instance), owner).await?;
+        Ok(())
+    }
+    
+    async fn get_migration_lock(&self, instance: &str) -> Result<Option<String>> {
+        self.redis.get(&keys::migration_lock(instance)).await
+    }
 }
 
+// ===== BULK UPDATE OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl BulkUpdateOperations for RedisRepository {
+    async fn save_bulk_undo(&self, instance: &str, record: &BulkUndoRecord, max_records: usize) -> Result<()> {
+        self.redis.lpush_capped_persistent(&keys::bulk_undo(instance), &[serde_json::to_string(record)?], max_records).await
+    }
+    
+    async fn get_bulk_undo(&self, instance: &str, undo_id: &str) -> Result<Option<BulkUndoRecord>> {
+        Ok(self.bulk_undo_entry(instance, undo_id).await?.map(|(_, record)| record))
+    }
+    
+    async fn delete_bulk_undo(&self, instance: &str, undo_id: &str) -> Result<bool> {
+        let Some((json, _)) = self.bulk_undo_entry(instance, undo_id).await? else {
+            return Ok(false);
+        };
+        // LREM reports the removal to one caller only
+        Ok(self.redis.lrem(&keys::bulk_undo(instance), &json).await? > 0)
+    }
+    
+    async fn claim_bulk_undo(&self, instance: &str, undo_id: &str, owner: &str, ttl_seconds: u64) -> Result<bool> {
+        self.redis.set_nx_ex(&keys::bulk_undo_claim(instance, undo_id), owner, ttl_seconds).await
+    }
+    
+    async fn release_bulk_undo(&self, instance: &str, undo_id: &str, owner: &str) -> Result<()> {
+        self.redis.del_if_eq(&keys::bulk_undo_claim(instance, undo_id), owner).await?;
+        Ok(())
+    }
+}
+
+// ===== RECALL TUNING OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl RecallTuningOperations for RedisRepository {
+    async fn get_recall_tuning(&self, instance: &str) -> Result<Option<RecallTuning>> {
+        match self.redis.get(&keys::recall_tuning(instance)).await? {
+            Some(json) => Ok(Some(serde_json::from_str(&json)?)),
+            None => Ok(None),
+        }
+    }
+    
+    async fn save_recall_tuning(&self, instance: &str, tuning: &RecallTuning) -> Result<()> {
+        self.redis.set(&keys::recall_tuning(instance), &serde_json::to_string(tuning)?).await
+    }
+}
+
+// ===== STREAM OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl StreamOperations for RedisRepository {
+    async fn read_stream(&self, key: &str) -> Result<Vec<StreamEntry>> {
+        Ok(self.redis.xrange_all(key).await?.into_iter()
+            .map(|(id, fields)| {
+                let fields: serde_json::Map<String, serde_json::Value> = fields.chunks(2)
+                    .filter(|pair| pair.len() == 2)
+                    .map(|pair| (pair[0].clone(), pair[1].clone().into()))
+                    .collect();
+                StreamEntry { timestamp_ms: stream_compaction::id_ms(&id), id, fields: fields.into() }
+            })
+            .collect())
+    }
+    
+    async fn trim_stream(&self, key: &str, last_id: &str) -> Result<usize> {
+        let min_id = stream_compaction::next_id(last_id)
+            .ok_or_else(|| crate::error::UnifiedIntelligenceError::Validation {
+                field: "last_id".to_string(),
+                reason: format!("'{}' is not a stream ID", last_id),
+            })?;
+        self.redis.xtrim_minid(key, &min_id).await
+    }
+    
+    async fn archive_stream_entries(&self, instance: &str, entries: &[ArchivedStreamEntry], max_entries: usize) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let values = entries.iter().map(serde_json::to_string).collect::<std::result::Result<Vec<_>, _>>()?;
+        self.redis.lpush_capped_persistent(&keys::stream_archive(instance), &values, max_entries).await
+    }
+    
+    async fn get_stream_compaction(&self, instance: &str) -> Result<Option<StreamCompaction>> {
+        match self.redis.get(&keys::stream_compaction(instance)).await? {
+            Some(json) => Ok(Some(serde_json::from_str(&json)?)),
+            None => Ok(None),
+        }
+    }
+    
+    async fn save_stream_compaction(&self, instance: &str, compaction: &StreamCompaction) -> Result<()> {
+        self.redis.set(&keys::stream_compaction(instance), &serde_json::to_string(compaction)?).await
+    }
+}
+
+// ===== IMPORTANCE OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl ImportanceOperations for RedisRepository {
+    async fn record_recalls(&self, instance: &str, thought_ids: &[String]) -> Result<()> {
+        let key = keys::recall_counts(instance);
+        for thought_id in thought_ids {
+            self.redis.zincrby(&key, thought_id, 1.0).await?;
+        }
+        Ok(())
+    }
+    
+    async fn get_recall_count(&self, instance: &str, thought_id: &str) -> Result<u64> {
+        Ok(self.redis.zscore(&keys::recall_counts(instance), thought_id).await?.unwrap_or(0.0) as u64)
+    }
+}
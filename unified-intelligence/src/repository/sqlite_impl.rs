@@ -0,0 +1,1006 @@
+//! SQLite-backed implementation of all repository traits.
+//!
+//! Selected with UI_STORAGE_BACKEND=sqlite, and used instead of Redis when
+//! Redis can't be reached at startup (unless UI_SQLITE_FALLBACK=false), so
+//! the server stays usable offline. The working set lives in a
+//! MemoryRepository, which answers every read and search; thoughts, chain
+//! metadata, thought metadata, feedback (boost scores and feedback events)
+//! identity documents with their version and changelog, applied migrations,
+//! bulk update undo records, recall tuning, the stream archive with its
+//! compaction counts, PII records (with their encrypted originals), chain sync
+//! state, persona snapshots and annotations are written through to the file at
+//! UI_SQLITE_PATH and restored on the next start.
+//! Rows are keyed by the same keys RedisRepository writes, so purges remove
+//! them like any other key. Client stats, modes, recall counts and the other
+//! bookkeeping stay in memory only. Writes run on tokio's blocking pool, one
+//! at a time and in the order they were made. A record reaches the working set
+//! only once its row is written; state derived in memory first (undo records,
+//! applied migrations, the stream archive, persona snapshots, annotations) is
+//! rolled back when its write fails, so reads never return unsaved data.
+//!
+//! The file is read once, when the backend opens, so only one process may use
+//! it at a time: writes from another process (a second server, ui_clipboard,
+//! ui_loadtest) are not seen until the next start and may be overwritten by it.
+
+use async_trait::async_trait;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::{ThoughtRecord, ChainMetadata, Identity, ThoughtMetadata, UiRecallFeedbackParams, PiiRecord, ChainSyncState, SearchIndexStatus, ClientStats, EmbeddingVersion, BackendDiagnostics, CrashReport, PersonaBundle, Annotation, ModeProfile, MemoryUsage, AppliedMigration, BulkUndoRecord, RecallTuning, StreamEntry, ArchivedStreamEntry, StreamCompaction};
+use crate::identity_documents::IdentityDocument;
+use crate::identity_history::IdentityChange;
+use crate::keys;
+use crate::search_index::SearchField;
+use super::memory_impl::{Restored, MAX_STREAM_EVENTS};
+use super::*;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS records (
+        key TEXT PRIMARY KEY,
+        kind TEXT NOT NULL,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS boost_scores (
+        key TEXT NOT NULL,
+        thought_id TEXT NOT NULL,
+        score REAL NOT NULL,
+        PRIMARY KEY (key, thought_id)
+    );
+    CREATE TABLE IF NOT EXISTS events (
+        stream TEXT NOT NULL,
+        event TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS events_stream ON events (stream);
+";
+
+// Kinds of rows in the records table
+const THOUGHT: &str = "thought";
+const CHAIN_METADATA: &str = "chain_metadata";
+const THOUGHT_METADATA: &str = "thought_metadata";
+const IDENTITY_DOCUMENT: &str = "identity_document";
+const IDENTITY_HISTORY: &str = "identity_history";
+const IDENTITY_VERSION: &str = "identity_version";
+const MIGRATIONS: &str = "migrations";
+const BULK_UNDO: &str = "bulk_undo";
+const RECALL_TUNING: &str = "recall_tuning";
+const STREAM_ARCHIVE: &str = "stream_archive";
+const STREAM_COMPACTION: &str = "stream_compaction";
+const PII: &str = "pii";
+const CHAIN_SYNC: &str = "chain_sync";
+const PERSONA_SNAPSHOTS: &str = "persona_snapshots";
+const ANNOTATIONS: &str = "annotations";
+
+/// SQLite file from UI_SQLITE_PATH
+pub fn sqlite_path() -> PathBuf {
+    std::env::var("UI_SQLITE_PATH").ok()
+        .filter(|path| !path.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("unified_intelligence.sqlite"))
+}
+
+/// Whether an unreachable Redis falls back to SQLite at startup (UI_SQLITE_FALLBACK, default on)
+pub fn sqlite_fallback() -> bool {
+    !std::env::var("UI_SQLITE_FALLBACK")
+        .map(|value| matches!(value.trim().to_lowercase().as_str(), "0" | "false" | "no" | "off"))
+        .unwrap_or(false)
+}
+
+/// SQLite implementation of all repository traits
+pub struct SqliteRepository {
+    memory: MemoryRepository,
+    /// Only used on the blocking pool, through with_db; waiters take turns in call order
+    db: Arc<Mutex<Connection>>,
+    path: PathBuf,
+}
+
+impl SqliteRepository {
+    /// Open (or create) the database and restore everything persisted in it
+    pub fn open(path: &Path, user_id: Option<String>) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                UnifiedIntelligenceError::Configuration(format!("Cannot create {}: {}", parent.display(), e))
+            })?;
+        }
+        let db = Connection::open(path)?;
+        db.execute_batch(SCHEMA)?;
+
+        let records = Self::load(&db)?;
+        tracing::info!("Restored {} records from {}", records.len(), path.display());
+        let memory = MemoryRepository::new(user_id);
+        memory.restore(records);
+
+        Ok(Self {
+            memory,
+            db: Arc::new(Mutex::new(db)),
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Run database work on the blocking pool, so SQLite I/O never stalls the runtime
+    async fn with_db<T, F>(&self, work: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+    {
+        let mut db = self.db.clone().lock_owned().await;
+        tokio::task::spawn_blocking(move || work(&mut db))
+            .await
+            .map_err(|e| UnifiedIntelligenceError::Internal(format!("SQLite task failed: {}", e)))?
+    }
+
+    /// Delete records by key
+    async fn delete_records(&self, keys: Vec<String>) -> Result<()> {
+        self.with_db(move |db| {
+            let transaction = db.transaction()?;
+            for key in &keys {
+                transaction.execute("DELETE FROM records WHERE key = ?1", params![key])?;
+            }
+            transaction.commit()?;
+            Ok(())
+        }).await
+    }
+
+    /// Every persisted record in the order it was first written; unreadable rows are skipped
+    fn load(db: &Connection) -> Result<Vec<Restored>> {
+        let mut records = Vec::new();
+
+        let mut statement = db.prepare("SELECT kind, key, value FROM records ORDER BY rowid")?;
+        let rows = statement.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?;
+        for row in rows {
+            let (kind, key, value) = row?;
+            match Self::restored(&kind, key.clone(), &value) {
+                Ok(Some(record)) => records.push(record),
+                Ok(None) => tracing::warn!("Skipping record {} of unknown kind '{}'", key, kind),
+                Err(e) => tracing::warn!("Skipping unreadable {} record {}: {}", kind, key, e),
+            }
+        }
+
+        let mut boosts: BTreeMap<String, HashMap<String, f64>> = BTreeMap::new();
+        let mut statement = db.prepare("SELECT key, thought_id, score FROM boost_scores")?;
+        let rows = statement.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, f64>(2)?)))?;
+        for row in rows {
+            let (key, thought_id, score) = row?;
+            boosts.entry(key).or_default().insert(thought_id, score);
+        }
+        records.extend(boosts.into_iter().map(|(key, scores)| Restored::BoostScores(key, scores)));
+
+        let mut statement = db.prepare("SELECT stream, event FROM events ORDER BY rowid")?;
+        let rows = statement.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+        for row in rows {
+            let (stream, event) = row?;
+            if let Ok(event) = serde_json::from_str(&event) {
+                records.push(Restored::Event(stream, event));
+            }
+        }
+        Ok(records)
+    }
+
+    fn restored(kind: &str, key: String, value: &str) -> serde_json::Result<Option<Restored>> {
+        Ok(Some(match kind {
+            THOUGHT => Restored::Thought(serde_json::from_str(value)?),
+            CHAIN_METADATA => Restored::ChainMetadata(key, serde_json::from_str(value)?),
+            THOUGHT_METADATA => Restored::ThoughtMetadata(serde_json::from_str(value)?),
+            IDENTITY_DOCUMENT => Restored::IdentityDocument(serde_json::from_str(value)?),
+            IDENTITY_HISTORY => Restored::IdentityHistory(key, serde_json::from_str(value)?),
+            IDENTITY_VERSION => Restored::IdentityVersion(key, serde_json::from_str(value)?),
+            MIGRATIONS => Restored::Migrations(key, serde_json::from_str(value)?),
+            BULK_UNDO => Restored::BulkUndo(key, serde_json::from_str(value)?),
+            RECALL_TUNING => Restored::RecallTuning(key, serde_json::from_str(value)?),
+            STREAM_ARCHIVE => Restored::StreamArchive(key, serde_json::from_str(value)?),
+            STREAM_COMPACTION => Restored::StreamCompaction(key, serde_json::from_str(value)?),
+            PII => Restored::Pii(key, serde_json::from_str(value)?),
+            CHAIN_SYNC => Restored::ChainSync(key, serde_json::from_str(value)?),
+            PERSONA_SNAPSHOTS => Restored::PersonaSnapshots(key, serde_json::from_str(value)?),
+            ANNOTATIONS => Restored::Annotations(key, serde_json::from_str(value)?),
+            _ => return Ok(None),
+        }))
+    }
+
+    /// Write a record, keeping its original position when it is replaced
+    async fn put(&self, kind: &'static str, key: &str, value: &impl Serialize) -> Result<()> {
+        let (key, json) = (key.to_string(), serde_json::to_string(value)?);
+        self.with_db(move |db| {
+            db.execute(
+                "INSERT INTO records (key, kind, value) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(key) DO UPDATE SET kind = excluded.kind, value = excluded.value",
+                params![key, kind, json],
+            )?;
+            Ok(())
+        }).await
+    }
+
+    async fn put_boost_score(&self, instance: &str, thought_id: &str, score: f64) -> Result<()> {
+        let (key, thought_id) = (keys::boost_scores(instance), thought_id.to_string());
+        self.with_db(move |db| {
+            db.execute(
+                "INSERT INTO boost_scores (key, thought_id, score) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(key, thought_id) DO UPDATE SET score = excluded.score",
+                params![key, thought_id, score],
+            )?;
+            Ok(())
+        }).await
+    }
+
+    /// Append an event to a persisted stream, dropping the oldest beyond the stream limit
+    async fn append_event(&self, stream: &str, event: &serde_json::Value) -> Result<()> {
+        let (stream, event) = (stream.to_string(), event.to_string());
+        self.with_db(move |db| {
+            db.execute("INSERT INTO events (stream, event) VALUES (?1, ?2)", params![stream, event])?;
+            db.execute(
+                "DELETE FROM events WHERE stream = ?1 AND rowid <= (
+                     SELECT rowid FROM events WHERE stream = ?1 ORDER BY rowid DESC LIMIT 1 OFFSET ?2
+                 )",
+                params![stream, MAX_STREAM_EVENTS as i64],
+            )?;
+            Ok(())
+        }).await
+    }
+
+    /// Persist an instance's undo records, newest first, after one is saved or deleted
+    async fn put_bulk_undo(&self, instance: &str) -> Result<()> {
+        let records = self.memory.bulk_undo_records(instance);
+        self.put(BULK_UNDO, &keys::bulk_undo(instance), &records).await
+    }
+
+    /// Put back the working-set state captured before a change whose write failed
+    fn roll_back<T>(&self, result: Result<T>, previous: Restored) -> Result<T> {
+        if result.is_err() {
+            self.memory.restore(vec![previous]);
+        }
+        result
+    }
+
+    /// Persist an instance's identity version and changelog after a document write
+    async fn put_identity_state(&self, instance: &str) -> Result<()> {
+        let version = self.memory.get_identity_version(instance).await?;
+        let history = self.memory.get_identity_history(instance).await?;
+        self.put(IDENTITY_VERSION, &keys::identity_version(instance), &version).await?;
+        self.put(IDENTITY_HISTORY, &keys::identity_history(instance), &history).await
+    }
+}
+
+// ===== THOUGHT STORAGE IMPLEMENTATION =====
+#[async_trait]
+impl ThoughtStorage for SqliteRepository {
+    async fn save_thought(&self, thought: &ThoughtRecord) -> Result<()> {
+        // Duplicates are dropped by the working set and must not overwrite the stored record
+        let duplicate = self.memory.get_thought(&thought.instance, &thought.id).await?.is_some();
+        if !duplicate {
+            self.put(THOUGHT, &keys::thought(&thought.instance, &thought.id), thought).await?;
+        }
+        self.memory.save_thought(thought).await
+    }
+
+    async fn get_thought(&self, instance: &str, thought_id: &str) -> Result<Option<ThoughtRecord>> {
+        self.memory.get_thought(instance, thought_id).await
+    }
+
+    async fn get_chain_thoughts(&self, instance: &str, chain_id: &str) -> Result<Vec<ThoughtRecord>> {
+        self.memory.get_chain_thoughts(instance, chain_id).await
+    }
+
+    async fn get_instance_thoughts(&self, instance: &str, limit: usize) -> Result<Vec<ThoughtRecord>> {
+        self.memory.get_instance_thoughts(instance, limit).await
+    }
+
+    async fn get_all_thoughts(&self, limit: usize) -> Result<Vec<ThoughtRecord>> {
+        self.memory.get_all_thoughts(limit).await
+    }
+
+    async fn delete_thought(&self, instance: &str, thought_id: &str) -> Result<bool> {
+        if !self.memory.delete_thought(instance, thought_id).await? {
+            return Ok(false);
+        }
+        let records = [
+            keys::thought(instance, thought_id),
+            keys::thought_metadata(instance, thought_id),
+            keys::pii(instance, thought_id),
+            keys::annotations(instance, thought_id),
+        ];
+        let (boosts, feedback, thought_id) = (keys::boost_scores(instance), keys::feedback_events(instance), thought_id.to_string());
+        self.with_db(move |db| {
+            let transaction = db.transaction()?;
+            for key in &records {
+                transaction.execute("DELETE FROM records WHERE key = ?1", params![key])?;
+            }
+            transaction.execute(
+                "DELETE FROM boost_scores WHERE key = ?1 AND thought_id = ?2",
+                params![boosts, thought_id],
+            )?;
+            transaction.execute(
+                "DELETE FROM events WHERE stream = ?1 AND json_extract(event, '$.thought_id') = ?2",
+                params![feedback, thought_id],
+            )?;
+            transaction.commit()?;
+            Ok(())
+        }).await?;
+        Ok(true)
+    }
+}
+
+// ===== THOUGHT SEARCH IMPLEMENTATION =====
+#[async_trait]
+impl ThoughtSearch for SqliteRepository {
+    async fn search_thoughts(&self, instance: &str, query: &str, fields: &[SearchField], limit: usize) -> Result<Vec<ThoughtRecord>> {
+        self.memory.search_thoughts(instance, query, fields, limit).await
+    }
+
+    async fn search_thoughts_semantic(&self, instance: &str, query: &str, limit: usize, threshold: f32) -> Result<Vec<ThoughtRecord>> {
+        self.memory.search_thoughts_semantic(instance, query, limit, threshold).await
+    }
+
+    async fn search_thoughts_global(&self, query: &str, fields: &[SearchField], limit: usize) -> Result<Vec<ThoughtRecord>> {
+        self.memory.search_thoughts_global(query, fields, limit).await
+    }
+
+    async fn search_thoughts_semantic_global(&self, query: &str, limit: usize, threshold: f32) -> Result<Vec<ThoughtRecord>> {
+        self.memory.search_thoughts_semantic_global(query, limit, threshold).await
+    }
+
+    async fn generate_search_id(&self) -> Result<String> {
+        self.memory.generate_search_id().await
+    }
+}
+
+// ===== ENHANCED SEARCH IMPLEMENTATION =====
+#[async_trait]
+impl EnhancedSearch for SqliteRepository {
+    async fn search_thoughts_semantic_enhanced(
+        &self,
+        instance: &str,
+        query: &str,
+        limit: usize,
+        threshold: f32,
+        tags_filter: Option<Vec<String>>,
+        min_importance: Option<i32>,
+        min_relevance: Option<i32>,
+        category_filter: Option<String>,
+    ) -> Result<Vec<ThoughtRecord>> {
+        self.memory.search_thoughts_semantic_enhanced(instance, query, limit, threshold, tags_filter, min_importance, min_relevance, category_filter).await
+    }
+
+    async fn search_thoughts_semantic_global_enhanced(
+        &self,
+        query: &str,
+        limit: usize,
+        threshold: f32,
+        tags_filter: Option<Vec<String>>,
+        min_importance: Option<i32>,
+        min_relevance: Option<i32>,
+        category_filter: Option<String>,
+    ) -> Result<Vec<ThoughtRecord>> {
+        self.memory.search_thoughts_semantic_global_enhanced(query, limit, threshold, tags_filter, min_importance, min_relevance, category_filter).await
+    }
+
+    async fn get_thoughts_by_tags(&self, instance: &str, tags: &[String]) -> Result<Vec<String>> {
+        self.memory.get_thoughts_by_tags(instance, tags).await
+    }
+}
+
+// ===== CHAIN OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl ChainOperations for SqliteRepository {
+    async fn save_chain_metadata(&self, metadata: &ChainMetadata) -> Result<()> {
+        self.put(CHAIN_METADATA, &self.memory.chain_metadata_key(&metadata.chain_id), metadata).await?;
+        self.memory.save_chain_metadata(metadata).await
+    }
+
+    async fn chain_exists(&self, chain_id: &str) -> Result<bool> {
+        self.memory.chain_exists(chain_id).await
+    }
+
+    async fn get_chain_metadata(&self, chain_id: &str) -> Result<Option<ChainMetadata>> {
+        self.memory.get_chain_metadata(chain_id).await
+    }
+
+    async fn list_chain_metadata(&self, instance: &str) -> Result<Vec<ChainMetadata>> {
+        self.memory.list_chain_metadata(instance).await
+    }
+
+    async fn delete_chain(&self, instance: &str, chain_id: &str) -> Result<usize> {
+        let mut deleted = 0;
+        for thought in self.memory.get_chain_thoughts(instance, chain_id).await? {
+            if self.delete_thought(instance, &thought.id).await? {
+                deleted += 1;
+            }
+        }
+        self.memory.delete_chain(instance, chain_id).await?;
+        self.delete_records(vec![self.memory.chain_metadata_key(chain_id), keys::chain_sync(instance, chain_id)]).await?;
+        Ok(deleted)
+    }
+}
+
+// ===== FEEDBACK OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl FeedbackOperations for SqliteRepository {
+    async fn save_thought_metadata(&self, metadata: &ThoughtMetadata) -> Result<()> {
+        self.put(THOUGHT_METADATA, &keys::thought_metadata(&metadata.instance, &metadata.thought_id), metadata).await?;
+        self.memory.save_thought_metadata(metadata).await
+    }
+
+    async fn get_thought_metadata(&self, instance: &str, thought_id: &str) -> Result<Option<ThoughtMetadata>> {
+        self.memory.get_thought_metadata(instance, thought_id).await
+    }
+
+    async fn record_feedback(&self, feedback: &UiRecallFeedbackParams, instance: &str) -> Result<()> {
+        let feedback_event = serde_json::json!({
+            "event_type": "feedback_provided",
+            "search_id": feedback.search_id,
+            "thought_id": feedback.thought_id,
+            "instance": instance,
+            "action": feedback.action,
+            "dwell_time": feedback.dwell_time,
+            "relevance_rating": feedback.relevance_rating,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        });
+        self.publish_feedback_event(&feedback_event).await?;
+
+        self.update_boost_score(
+            instance,
+            &feedback.thought_id,
+            &feedback.action,
+            feedback.relevance_rating,
+            feedback.dwell_time,
+        ).await?;
+        Ok(())
+    }
+
+    async fn update_boost_score(&self, instance: &str, thought_id: &str, feedback_action: &str, relevance_rating: Option<i32>, dwell_time: Option<i32>) -> Result<f64> {
+        let score = self.memory.update_boost_score(instance, thought_id, feedback_action, relevance_rating, dwell_time).await?;
+        self.put_boost_score(instance, thought_id, score).await?;
+        Ok(score)
+    }
+
+    async fn get_boost_score(&self, instance: &str, thought_id: &str) -> Result<f64> {
+        self.memory.get_boost_score(instance, thought_id).await
+    }
+
+    async fn add_boost_score(&self, instance: &str, thought_id: &str, amount: f64) -> Result<f64> {
+        let score = self.memory.add_boost_score(instance, thought_id, amount).await?;
+        self.put_boost_score(instance, thought_id, score).await?;
+        Ok(score)
+    }
+
+    async fn apply_boost_scores(&self, instance: &str, thoughts: &mut Vec<ThoughtRecord>) -> Result<()> {
+        self.memory.apply_boost_scores(instance, thoughts).await
+    }
+
+    async fn get_citing_thoughts(&self, instance: &str, note_path: &str) -> Result<Vec<String>> {
+        self.memory.get_citing_thoughts(instance, note_path).await
+    }
+}
+
+// ===== IDENTITY OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl IdentityOperations for SqliteRepository {
+    async fn get_identity(&self, identity_key: &str) -> Result<Option<Identity>> {
+        self.memory.get_identity(identity_key).await
+    }
+}
+
+// ===== IDENTITY DOCUMENT OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl IdentityDocumentOperations for SqliteRepository {
+    async fn get_identity_documents_by_field(&self, instance_id: &str, field_type: &str) -> Result<Vec<IdentityDocument>> {
+        self.memory.get_identity_documents_by_field(instance_id, field_type).await
+    }
+
+    async fn save_identity_document(&self, document: &IdentityDocument) -> Result<()> {
+        self.memory.save_identity_document(document).await?;
+        self.put(IDENTITY_DOCUMENT, &document.redis_key(), document).await?;
+        self.put_identity_state(&document.instance).await
+    }
+
+    async fn delete_identity_document(&self, instance_id: &str, field_type: &str, document_id: &str) -> Result<()> {
+        self.memory.delete_identity_document(instance_id, field_type, document_id).await?;
+        self.delete_records(vec![keys::identity_document(instance_id, field_type, document_id)]).await?;
+        self.put_identity_state(instance_id).await
+    }
+
+    async fn get_all_identity_documents(&self, instance_id: &str) -> Result<Vec<IdentityDocument>> {
+        self.memory.get_all_identity_documents(instance_id).await
+    }
+
+    async fn get_identity_document_by_id(&self, instance_id: &str, document_id: &str) -> Result<Option<IdentityDocument>> {
+        self.memory.get_identity_document_by_id(instance_id, document_id).await
+    }
+
+    async fn get_identity_version(&self, instance_id: &str) -> Result<u64> {
+        self.memory.get_identity_version(instance_id).await
+    }
+
+    async fn get_identity_history(&self, instance_id: &str) -> Result<Vec<IdentityChange>> {
+        self.memory.get_identity_history(instance_id).await
+    }
+}
+
+// ===== EVENT OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl EventOperations for SqliteRepository {
+    async fn log_event(&self, instance: &str, event_type: &str, fields: Vec<(&str, &str)>) -> Result<()> {
+        self.memory.log_event(instance, event_type, fields).await
+    }
+
+    async fn publish_feedback_event(&self, event: &serde_json::Value) -> Result<()> {
+        self.memory.publish_feedback_event(event).await?;
+        let instance = event.get("instance")
+            .and_then(|v| v.as_str())
+            .unwrap_or("global");
+        self.append_event(&keys::feedback_events(instance), event).await
+    }
+
+    async fn publish_notification(&self, channel: &str, event: &serde_json::Value) -> Result<()> {
+        self.memory.publish_notification(channel, event).await
+    }
+}
+
+// ===== PURGE OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl PurgeOperations for SqliteRepository {
+    async fn purge_inventory(&self, namespace: &str) -> Result<Vec<String>> {
+        self.memory.purge_inventory(namespace).await
+    }
+
+    async fn purge_keys(&self, keys: &[String]) -> Result<usize> {
+        let removed = self.memory.purge_keys(keys).await?;
+        let keys = keys.to_vec();
+        self.with_db(move |db| {
+            let transaction = db.transaction()?;
+            for key in &keys {
+                transaction.execute("DELETE FROM records WHERE key = ?1", params![key])?;
+                transaction.execute("DELETE FROM boost_scores WHERE key = ?1", params![key])?;
+                transaction.execute("DELETE FROM events WHERE stream = ?1", params![key])?;
+            }
+            transaction.commit()?;
+            Ok(())
+        }).await?;
+        Ok(removed)
+    }
+
+    async fn save_purge_token(&self, namespace: &str, token: &str, ttl_seconds: u64) -> Result<()> {
+        self.memory.save_purge_token(namespace, token, ttl_seconds).await
+    }
+
+    async fn take_purge_token(&self, namespace: &str) -> Result<Option<String>> {
+        self.memory.take_purge_token(namespace).await
+    }
+}
+
+// ===== PII OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl PiiOperations for SqliteRepository {
+    async fn save_pii_record(&self, record: &PiiRecord) -> Result<()> {
+        self.put(PII, &keys::pii(&record.instance, &record.thought_id), record).await?;
+        self.memory.save_pii_record(record).await
+    }
+
+    async fn get_pii_records(&self, instance: &str, limit: usize) -> Result<Vec<PiiRecord>> {
+        self.memory.get_pii_records(instance, limit).await
+    }
+}
+
+// ===== CHAIN SYNC OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl ChainSyncOperations for SqliteRepository {
+    async fn save_chain_sync_state(&self, state: &ChainSyncState) -> Result<()> {
+        self.put(CHAIN_SYNC, &keys::chain_sync(&state.instance, &state.chain_id), state).await?;
+        self.memory.save_chain_sync_state(state).await
+    }
+
+    async fn get_chain_sync_state(&self, instance: &str, chain_id: &str) -> Result<Option<ChainSyncState>> {
+        self.memory.get_chain_sync_state(instance, chain_id).await
+    }
+}
+
+// ===== SEARCH INDEX OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl SearchIndexOperations for SqliteRepository {
+    async fn register_search_instance(&self, instance: &str) -> Result<bool> {
+        self.memory.register_search_instance(instance).await
+    }
+
+    async fn search_index_status(&self) -> Result<SearchIndexStatus> {
+        let mut status = self.memory.search_index_status().await?;
+        status.degraded_features = vec![format!("SQLite backend ({}): recall uses substring search", self.path.display())];
+        Ok(status)
+    }
+
+    async fn rebuild_search_index(&self) -> Result<bool> {
+        self.memory.rebuild_search_index().await
+    }
+}
+
+// ===== CLIENT OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl ClientOperations for SqliteRepository {
+    async fn record_client_session(&self, instance: &str, name: &str, version: &str, protocol_version: &str) -> Result<()> {
+        self.memory.record_client_session(instance, name, version, protocol_version).await
+    }
+
+    async fn record_client_tool_call(&self, instance: &str, name: &str, tool: &str, failed: bool) -> Result<()> {
+        self.memory.record_client_tool_call(instance, name, tool, failed).await
+    }
+
+    async fn get_client_stats(&self, instance: &str) -> Result<Vec<ClientStats>> {
+        self.memory.get_client_stats(instance).await
+    }
+}
+
+#[async_trait]
+impl CaptureOperations for SqliteRepository {
+    async fn mark_captured(&self, instance: &str, item_id: &str) -> Result<bool> {
+        self.memory.mark_captured(instance, item_id).await
+    }
+
+    async fn unmark_captured(&self, instance: &str, item_id: &str) -> Result<()> {
+        self.memory.unmark_captured(instance, item_id).await
+    }
+}
+
+#[async_trait]
+impl EmbeddingOperations for SqliteRepository {
+    async fn get_embedding_version(&self, instance: &str, thought_id: &str) -> Result<Option<EmbeddingVersion>> {
+        self.memory.get_embedding_version(instance, thought_id).await
+    }
+
+    async fn queue_reembedding(&self, instance: &str, thought_id: &str, priority: f64) -> Result<()> {
+        self.memory.queue_reembedding(instance, thought_id, priority).await
+    }
+}
+
+#[async_trait]
+impl IdentityTemplateOperations for SqliteRepository {
+    async fn get_identity_template(&self, name: &str) -> Result<Option<Identity>> {
+        self.memory.get_identity_template(name).await
+    }
+
+    async fn save_identity_template(&self, name: &str, template: &Identity) -> Result<()> {
+        self.memory.save_identity_template(name, template).await
+    }
+
+    async fn list_identity_templates(&self) -> Result<Vec<String>> {
+        self.memory.list_identity_templates().await
+    }
+}
+
+#[async_trait]
+impl DiagnosticsOperations for SqliteRepository {
+    async fn backend_diagnostics(&self) -> Result<BackendDiagnostics> {
+        Ok(BackendDiagnostics {
+            backend: "sqlite".to_string(),
+            redis_modules: None,
+            pool: None,
+        })
+    }
+
+    async fn save_crash_report(&self, instance: &str, report: &CrashReport) -> Result<()> {
+        self.memory.save_crash_report(instance, report).await
+    }
+
+    async fn get_crash_reports(&self, instance: &str, limit: usize) -> Result<Vec<CrashReport>> {
+        self.memory.get_crash_reports(instance, limit).await
+    }
+}
+
+#[async_trait]
+impl PersonaOperations for SqliteRepository {
+    async fn save_persona_snapshot(&self, instance: &str, bundle: &PersonaBundle) -> Result<()> {
+        let previous = Restored::PersonaSnapshots(keys::persona_snapshots(instance), self.memory.persona_snapshot_list(instance));
+        self.memory.save_persona_snapshot(instance, bundle).await?;
+        let written = self.put(PERSONA_SNAPSHOTS, &keys::persona_snapshots(instance), &self.memory.persona_snapshot_list(instance)).await;
+        self.roll_back(written, previous)
+    }
+
+    async fn get_persona_snapshot(&self, instance: &str, version: u64) -> Result<Option<PersonaBundle>> {
+        self.memory.get_persona_snapshot(instance, version).await
+    }
+
+    async fn list_persona_versions(&self, instance: &str) -> Result<Vec<u64>> {
+        self.memory.list_persona_versions(instance).await
+    }
+}
+
+#[async_trait]
+impl AnnotationOperations for SqliteRepository {
+    async fn save_annotation(&self, instance: &str, annotation: &Annotation) -> Result<()> {
+        let key = keys::annotations(instance, &annotation.thought_id);
+        let previous = Restored::Annotations(key.clone(), self.memory.get_annotations(instance, &annotation.thought_id).await?);
+        self.memory.save_annotation(instance, annotation).await?;
+        let written = self.put(ANNOTATIONS, &key, &self.memory.get_annotations(instance, &annotation.thought_id).await?).await;
+        self.roll_back(written, previous)
+    }
+
+    async fn get_annotations(&self, instance: &str, thought_id: &str) -> Result<Vec<Annotation>> {
+        self.memory.get_annotations(instance, thought_id).await
+    }
+}
+
+#[async_trait]
+impl TieringOperations for SqliteRepository {
+    async fn replace_thought(&self, thought: &ThoughtRecord) -> Result<()> {
+        self.put(THOUGHT, &keys::thought(&thought.instance, &thought.id), thought).await?;
+        self.memory.replace_thought(thought).await
+    }
+
+    async fn get_last_access(&self, instance: &str, thought_id: &str) -> Result<Option<i64>> {
+        self.memory.get_last_access(instance, thought_id).await
+    }
+}
+
+#[async_trait]
+impl ModeOperations for SqliteRepository {
+    async fn get_active_mode(&self, instance: &str) -> Result<Option<String>> {
+        self.memory.get_active_mode(instance).await
+    }
+
+    async fn set_active_mode(&self, instance: &str, mode: &str) -> Result<()> {
+        self.memory.set_active_mode(instance, mode).await
+    }
+
+    async fn save_mode_profile(&self, instance: &str, profile: &ModeProfile) -> Result<()> {
+        self.memory.save_mode_profile(instance, profile).await
+    }
+
+    async fn get_mode_profiles(&self, instance: &str) -> Result<Vec<ModeProfile>> {
+        self.memory.get_mode_profiles(instance).await
+    }
+}
+
+#[async_trait]
+impl PressureOperations for SqliteRepository {
+    async fn memory_usage(&self) -> Result<Option<MemoryUsage>> {
+        self.memory.memory_usage().await
+    }
+
+    async fn set_essential_ingest(&self, essential: bool) {
+        self.memory.set_essential_ingest(essential).await
+    }
+
+    async fn publish_intervention(&self, intervention: &serde_json::Value) -> Result<()> {
+        self.memory.publish_intervention(intervention).await
+    }
+}
+
+#[async_trait]
+impl MigrationOperations for SqliteRepository {
+    async fn get_applied_migrations(&self, instance: &str) -> Result<Vec<AppliedMigration>> {
+        self.memory.get_applied_migrations(instance).await
+    }
+
+    async fn record_migration(&self, instance: &str, migration: &AppliedMigration) -> Result<()> {
+        let previous = Restored::Migrations(keys::migrations(instance), self.memory.get_applied_migrations(instance).await?);
+        self.memory.record_migration(instance, migration).await?;
+        let applied = self.memory.get_applied_migrations(instance).await?;
+        let written = self.put(MIGRATIONS, &keys::migrations(instance), &applied).await;
+        self.roll_back(written, previous)
+    }
+
+    // The file belongs to a single server, so the lock only needs to live in memory
+    async fn acquire_migration_lock(&self, instance: &str, owner: &str, ttl_seconds: u64) -> Result<bool> {
+        self.memory.acquire_migration_lock(instance, owner, ttl_seconds).await
+    }
+
+    async fn release_migration_lock(&self, instance: &str, owner: &str) -> Result<()> {
+        self.memory.release_migration_lock(instance, owner).await
+    }
+
+    async fn get_migration_lock(&self, instance: &str) -> Result<Option<String>> {
+        self.memory.get_migration_lock(instance).await
+    }
+}
+
+// ===== BULK UPDATE OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl BulkUpdateOperations for SqliteRepository {
+    async fn save_bulk_undo(&self, instance: &str, record: &BulkUndoRecord, max_records: usize) -> Result<()> {
+        let previous = Restored::BulkUndo(keys::bulk_undo(instance), self.memory.bulk_undo_records(instance));
+        self.memory.save_bulk_undo(instance, record, max_records).await?;
+        let written = self.put_bulk_undo(instance).await;
+        self.roll_back(written, previous)
+    }
+
+    async fn get_bulk_undo(&self, instance: &str, undo_id: &str) -> Result<Option<BulkUndoRecord>> {
+        self.memory.get_bulk_undo(instance, undo_id).await
+    }
+
+    async fn delete_bulk_undo(&self, instance: &str, undo_id: &str) -> Result<bool> {
+        let previous = Restored::BulkUndo(keys::bulk_undo(instance), self.memory.bulk_undo_records(instance));
+        let deleted = self.memory.delete_bulk_undo(instance, undo_id).await?;
+        if deleted {
+            let written = self.put_bulk_undo(instance).await;
+            self.roll_back(written, previous)?;
+        }
+        Ok(deleted)
+    }
+
+    // The file belongs to a single server, so claims only need to live in memory
+    async fn claim_bulk_undo(&self, instance: &str, undo_id: &str, owner: &str, ttl_seconds: u64) -> Result<bool> {
+        self.memory.claim_bulk_undo(instance, undo_id, owner, ttl_seconds).await
+    }
+
+    async fn release_bulk_undo(&self, instance: &str, undo_id: &str, owner: &str) -> Result<()> {
+        self.memory.release_bulk_undo(instance, undo_id, owner).await
+    }
+}
+
+// ===== RECALL TUNING OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl RecallTuningOperations for SqliteRepository {
+    async fn get_recall_tuning(&self, instance: &str) -> Result<Option<RecallTuning>> {
+        self.memory.get_recall_tuning(instance).await
+    }
+
+    async fn save_recall_tuning(&self, instance: &str, tuning: &RecallTuning) -> Result<()> {
+        self.put(RECALL_TUNING, &keys::recall_tuning(instance), tuning).await?;
+        self.memory.save_recall_tuning(instance, tuning).await
+    }
+}
+
+// ===== STREAM OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl StreamOperations for SqliteRepository {
+    async fn read_stream(&self, key: &str) -> Result<Vec<StreamEntry>> {
+        self.memory.read_stream(key).await
+    }
+
+    async fn trim_stream(&self, key: &str, last_id: &str) -> Result<usize> {
+        let removed = self.memory.trim_stream(key, last_id).await?;
+        // The persisted rows are the same events in the same order, so the oldest ones go
+        let key = key.to_string();
+        self.with_db(move |db| {
+            db.execute(
+                "DELETE FROM events WHERE rowid IN (
+                     SELECT rowid FROM events WHERE stream = ?1 ORDER BY rowid LIMIT ?2
+                 )",
+                params![key, removed as i64],
+            )?;
+            Ok(())
+        }).await?;
+        Ok(removed)
+    }
+
+    async fn archive_stream_entries(&self, instance: &str, entries: &[ArchivedStreamEntry], max_entries: usize) -> Result<()> {
+        let previous = Restored::StreamArchive(keys::stream_archive(instance), self.memory.stream_archive_entries(instance));
+        self.memory.archive_stream_entries(instance, entries, max_entries).await?;
+        let written = self.put(STREAM_ARCHIVE, &keys::stream_archive(instance), &self.memory.stream_archive_entries(instance)).await;
+        self.roll_back(written, previous)
+    }
+
+    async fn get_stream_compaction(&self, instance: &str) -> Result<Option<StreamCompaction>> {
+        self.memory.get_stream_compaction(instance).await
+    }
+
+    async fn save_stream_compaction(&self, instance: &str, compaction: &StreamCompaction) -> Result<()> {
+        self.put(STREAM_COMPACTION, &keys::stream_compaction(instance), compaction).await?;
+        self.memory.save_stream_compaction(instance, compaction).await
+    }
+}
+
+// ===== IMPORTANCE OPERATIONS IMPLEMENTATION =====
+#[async_trait]
+impl ImportanceOperations for SqliteRepository {
+    async fn record_recalls(&self, instance: &str, thought_ids: &[String]) -> Result<()> {
+        self.memory.record_recalls(instance, thought_ids).await
+    }
+
+    async fn get_recall_count(&self, instance: &str, thought_id: &str) -> Result<u64> {
+        self.memory.get_recall_count(instance, thought_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pii::{PiiPolicy, PiiScanner};
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir().join(format!("ui_sqlite_{}.sqlite", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn test_records_survive_reopen() {
+        let path = temp_path();
+        let repo = SqliteRepository::open(&path, None).unwrap();
+
+        let first = ThoughtRecord::new("CC".to_string(), "sqlite keeps thoughts".to_string(), 1, 2, Some("c1".to_string()), true);
+        let second = ThoughtRecord::new("CC".to_string(), "in chain order".to_string(), 2, 2, Some("c1".to_string()), false);
+        repo.save_thought(&first).await.unwrap();
+        repo.save_thought(&second).await.unwrap();
+        let metadata = ThoughtMetadata::new(first.id.clone(), "CC".to_string(), None, None, Some(vec!["offline".to_string()]), None);
+        repo.save_thought_metadata(&metadata).await.unwrap();
+        repo.update_boost_score("CC", &first.id, "used", Some(5), None).await.unwrap();
+        let document = IdentityDocument::new("communication".to_string(), serde_json::json!({ "tone": "dry" }), "CC".to_string());
+        repo.save_identity_document(&document).await.unwrap();
+        let annotation = Annotation { id: "a1".to_string(), thought_id: first.id.clone(), reviewer: "human".to_string(), verdict: Some("agree".to_string()), score: Some(8), comment: None, timestamp: String::new() };
+        repo.save_annotation("CC", &annotation).await.unwrap();
+        let sync = ChainSyncState { chain_id: "c1".to_string(), instance: "CC".to_string(), note_path: "Chains/c1.md".to_string(), last_exported_thought: 2, note_hash: String::new(), exported_at: None, last_imported_at: None, annotations_imported: 0, privacy_level: None };
+        repo.save_chain_sync_state(&sync).await.unwrap();
+        drop(repo);
+
+        let repo = SqliteRepository::open(&path, None).unwrap();
+        let chain: Vec<String> = repo.get_chain_thoughts("CC", "c1").await.unwrap().into_iter().map(|t| t.thought).collect();
+        assert_eq!(chain, vec!["sqlite keeps thoughts", "in chain order"]);
+        assert_eq!(repo.get_thoughts_by_tags("CC", &["offline".to_string()]).await.unwrap(), vec![first.id.clone()]);
+        assert!(repo.get_boost_score("CC", &first.id).await.unwrap() > 0.0);
+        assert_eq!(repo.get_all_identity_documents("CC").await.unwrap().len(), 1);
+        assert_eq!(repo.get_identity_version("CC").await.unwrap(), 1);
+        assert_eq!(repo.get_identity_history("CC").await.unwrap().len(), 1);
+        assert_eq!(repo.get_annotations("CC", &first.id).await.unwrap().len(), 1);
+        assert_eq!(repo.get_chain_sync_state("CC", "c1").await.unwrap().unwrap().last_exported_thought, 2);
+
+        // Purged keys stay gone after a restart
+        let inventory = repo.purge_inventory("CC").await.unwrap();
+        repo.purge_keys(&inventory).await.unwrap();
+        drop(repo);
+        let repo = SqliteRepository::open(&path, None).unwrap();
+        assert!(repo.get_instance_thoughts("CC", 10).await.unwrap().is_empty());
+        assert!(repo.get_all_identity_documents("CC").await.unwrap().is_empty());
+        assert_eq!(repo.get_boost_score("CC", &first.id).await.unwrap(), 0.0);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_masked_original_survives_reopen() {
+        let path = temp_path();
+        let repo = SqliteRepository::open(&path, None).unwrap();
+
+        let scanner = PiiScanner::with_key(PiiPolicy::Mask, Some(&[7u8; 32])).unwrap();
+        let content = "Mail sam@example.com about the offsite";
+        let findings = scanner.scan(content);
+        let thought = ThoughtRecord::new("CC".to_string(), PiiScanner::mask(content, &findings), 1, 1, None, false);
+        let record = PiiRecord {
+            thought_id: thought.id.clone(),
+            instance: "CC".to_string(),
+            policy: PiiPolicy::Mask,
+            masked: true,
+            findings,
+            encrypted_original: scanner.encrypt(content),
+            detected_at: chrono::Utc::now().to_rfc3339(),
+        };
+        repo.save_thought(&thought).await.unwrap();
+        repo.save_pii_record(&record).await.unwrap();
+        drop(repo);
+
+        let repo = SqliteRepository::open(&path, None).unwrap();
+        let restored = repo.get_pii_records("CC", 10).await.unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].thought_id, thought.id);
+        assert!(restored[0].encrypted_original.is_some());
+        assert_eq!(restored[0].encrypted_original, record.encrypted_original);
+
+        // Deleting the thought takes its PII record with it
+        repo.delete_thought("CC", &thought.id).await.unwrap();
+        drop(repo);
+        let repo = SqliteRepository::open(&path, None).unwrap();
+        assert!(repo.get_pii_records("CC", 10).await.unwrap().is_empty());
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_failed_writes_leave_memory_unchanged() {
+        let path = temp_path();
+        let repo = SqliteRepository::open(&path, None).unwrap();
+        let kept = BulkUndoRecord { undo_id: "u1".to_string(), created_at: String::new(), summary: "+kept".to_string(), entries: Vec::new() };
+        repo.save_bulk_undo("CC", &kept, 5).await.unwrap();
+        repo.db.lock().await.execute_batch("DROP TABLE records").unwrap();
+
+        let thought = ThoughtRecord::new("CC".to_string(), "never written".to_string(), 1, 1, None, false);
+        assert!(repo.save_thought(&thought).await.is_err());
+        assert!(repo.get_thought("CC", &thought.id).await.unwrap().is_none());
+
+        let lost = BulkUndoRecord { undo_id: "u2".to_string(), ..kept.clone() };
+        assert!(repo.save_bulk_undo("CC", &lost, 5).await.is_err());
+        assert!(repo.get_bulk_undo("CC", "u2").await.unwrap().is_none());
+        assert!(repo.delete_bulk_undo("CC", "u1").await.is_err());
+        assert!(repo.get_bulk_undo("CC", "u1").await.unwrap().is_some());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
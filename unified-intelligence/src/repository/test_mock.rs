@@ -4,8 +4,10 @@ use async_trait::async_trait;
 use std::sync::Mutex;
 use std::collections::HashMap;
 use crate::error::Result;
-use crate::models::{ThoughtRecord, ChainMetadata, Identity, ThoughtMetadata, UiRecallFeedbackParams};
+use crate::models::{ThoughtRecord, ChainMetadata, Identity, ThoughtMetadata, UiRecallFeedbackParams, PiiRecord, ChainSyncState, SearchIndexStatus, ClientStats, EmbeddingVersion, BackendDiagnostics, CrashReport, PersonaBundle, Annotation, ModeProfile, MemoryUsage, AppliedMigration, BulkUndoRecord, RecallTuning, StreamEntry, ArchivedStreamEntry, StreamCompaction};
 use crate::identity_documents::IdentityDocument;
+use crate::identity_history::IdentityChange;
+use crate::search_index::{self, SearchField};
 use super::*;
 
 #[cfg(test)]
@@ -14,7 +16,46 @@ pub struct MockRepository {
     chains: Mutex<HashMap<String, ChainMetadata>>,
     identities: Mutex<HashMap<String, Identity>>,
     identity_docs: Mutex<HashMap<String, IdentityDocument>>,
+    identity_history: Mutex<Vec<(String, IdentityChange)>>,
     thought_metadata: Mutex<HashMap<String, ThoughtMetadata>>,
+    purge_tokens: Mutex<HashMap<String, String>>,
+    pii_records: Mutex<Vec<PiiRecord>>,
+    chain_sync: Mutex<HashMap<String, ChainSyncState>>,
+    search_prefixes: Mutex<Vec<String>>,
+    indexed_prefixes: Mutex<Option<Vec<String>>>,
+    clients: Mutex<HashMap<String, ClientStats>>,
+    captured: Mutex<std::collections::HashSet<String>>,
+    embeddings: Mutex<HashMap<String, EmbeddingVersion>>,
+    embedding_stale: Mutex<HashMap<String, f64>>,
+    identity_versions: Mutex<HashMap<String, u64>>,
+    identity_templates: Mutex<HashMap<String, Identity>>,
+    crash_reports: Mutex<Vec<CrashReport>>,
+    persona_snapshots: Mutex<std::collections::BTreeMap<u64, PersonaBundle>>,
+    annotations: Mutex<HashMap<String, Vec<Annotation>>>,
+    active_modes: Mutex<HashMap<String, String>>,
+    modes: Mutex<HashMap<String, ModeProfile>>,
+    memory_usage: Mutex<Option<MemoryUsage>>,
+    essential_ingest: Mutex<bool>,
+    interventions: Mutex<Vec<serde_json::Value>>,
+    notifications: Mutex<Vec<(String, serde_json::Value)>>,
+    migrations: Mutex<HashMap<String, Vec<AppliedMigration>>>,
+    migration_locks: Mutex<HashMap<String, String>>,
+    bulk_undo_claims: Mutex<HashMap<String, String>>,
+    boost_scores: Mutex<HashMap<String, f64>>,
+    bulk_undo: Mutex<Vec<BulkUndoRecord>>,
+    recall_tuning: Mutex<Option<RecallTuning>>,
+    streams: Mutex<HashMap<String, Vec<StreamEntry>>>,
+    stream_archive: Mutex<Vec<ArchivedStreamEntry>>,
+    stream_compaction: Mutex<Option<StreamCompaction>>,
+    recall_counts: Mutex<HashMap<String, u64>>,
+    failing_pii_writes: Mutex<bool>,
+}
+
+#[cfg(test)]
+impl Default for MockRepository {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[cfg(test)]
@@ -25,9 +66,91 @@ impl MockRepository {
             chains: Mutex::new(HashMap::new()),
             identities: Mutex::new(HashMap::new()),
             identity_docs: Mutex::new(HashMap::new()),
+            identity_history: Mutex::new(Vec::new()),
             thought_metadata: Mutex::new(HashMap::new()),
+            purge_tokens: Mutex::new(HashMap::new()),
+            pii_records: Mutex::new(Vec::new()),
+            chain_sync: Mutex::new(HashMap::new()),
+            search_prefixes: Mutex::new(vec!["test:Thoughts:".to_string()]),
+            indexed_prefixes: Mutex::new(Some(vec!["test:Thoughts:".to_string()])),
+            clients: Mutex::new(HashMap::new()),
+            captured: Mutex::new(std::collections::HashSet::new()),
+            embeddings: Mutex::new(HashMap::new()),
+            embedding_stale: Mutex::new(HashMap::new()),
+            identity_versions: Mutex::new(HashMap::new()),
+            identity_templates: Mutex::new(HashMap::new()),
+            crash_reports: Mutex::new(Vec::new()),
+            persona_snapshots: Mutex::new(std::collections::BTreeMap::new()),
+            annotations: Mutex::new(HashMap::new()),
+            active_modes: Mutex::new(HashMap::new()),
+            modes: Mutex::new(HashMap::new()),
+            memory_usage: Mutex::new(None),
+            essential_ingest: Mutex::new(false),
+            interventions: Mutex::new(Vec::new()),
+            notifications: Mutex::new(Vec::new()),
+            migrations: Mutex::new(HashMap::new()),
+            migration_locks: Mutex::new(HashMap::new()),
+            bulk_undo_claims: Mutex::new(HashMap::new()),
+            boost_scores: Mutex::new(HashMap::new()),
+            bulk_undo: Mutex::new(Vec::new()),
+            recall_tuning: Mutex::new(None),
+            streams: Mutex::new(HashMap::new()),
+            stream_archive: Mutex::new(Vec::new()),
+            stream_compaction: Mutex::new(None),
+            recall_counts: Mutex::new(HashMap::new()),
+            failing_pii_writes: Mutex::new(false),
         }
     }
+    
+    /// Make save_pii_record fail, as a backend going away mid-request would
+    pub fn fail_pii_writes(&self) {
+        *self.failing_pii_writes.lock().unwrap() = true;
+    }
+    
+    /// Record the model that embedded a thought, as the embedding workers would
+    pub fn set_embedding_version(&self, instance: &str, thought_id: &str, version: EmbeddingVersion) {
+        self.embeddings.lock().unwrap().insert(format!("{}:{}", instance, thought_id), version);
+    }
+    
+    /// Re-embedding priority of a queued thought
+    pub fn reembedding_priority(&self, instance: &str, thought_id: &str) -> Option<f64> {
+        self.embedding_stale.lock().unwrap().get(&format!("{}:{}", instance, thought_id)).copied()
+    }
+    
+    /// Memory figures the backend reports, as INFO memory would
+    pub fn set_memory_usage(&self, usage: MemoryUsage) {
+        *self.memory_usage.lock().unwrap() = Some(usage);
+    }
+    
+    /// Whether ingest is limited to essential work
+    pub fn essential_ingest(&self) -> bool {
+        *self.essential_ingest.lock().unwrap()
+    }
+    
+    /// Interventions published so far
+    pub fn interventions(&self) -> Vec<serde_json::Value> {
+        self.interventions.lock().unwrap().clone()
+    }
+    
+    /// Notification events published so far, with their channels
+    pub fn notifications(&self) -> Vec<(String, serde_json::Value)> {
+        self.notifications.lock().unwrap().clone()
+    }
+    
+    /// Append an entry to a stream, as the event writers would
+    pub fn push_stream_entry(&self, key: &str, entry: StreamEntry) {
+        self.streams.lock().unwrap().entry(key.to_string()).or_default().push(entry);
+    }
+    
+    /// Archived stream entries, newest first
+    pub fn stream_archive(&self) -> Vec<ArchivedStreamEntry> {
+        self.stream_archive.lock().unwrap().clone()
+    }
+    
+    /// Store a legacy monolithic identity, as older servers wrote it
+    pub fn set_identity(&self, identity_key: &str, identity: Identity) {
+        self.identities.lock().unwrap().insert(identity_key.to_string(), identity);
+    }
 }
 
 #[cfg(test)]
@@ -35,6 +158,9 @@ impl MockRepository {
 impl ThoughtStorage for MockRepository {
     async fn save_thought(&self, thought: &ThoughtRecord) -> Result<()> {
         let key = format!("{}:{}", thought.instance, thought.id);
+        if self.essential_ingest() {
+            self.embedding_stale.lock().unwrap().insert(key.clone(), crate::embedding_version::reembed_priority(None));
+        }
         self.thoughts.lock().unwrap().insert(key, thought.clone());
         Ok(())
     }
@@ -45,11 +171,13 @@ impl ThoughtStorage for MockRepository {
     }
     
     async fn get_chain_thoughts(&self, instance: &str, chain_id: &str) -> Result<Vec<ThoughtRecord>> {
-        Ok(self.thoughts.lock().unwrap()
+        let mut thoughts: Vec<ThoughtRecord> = self.thoughts.lock().unwrap()
             .values()
             .filter(|t| t.instance == instance && t.chain_id.as_deref() == Some(chain_id))
             .cloned()
-            .collect())
+            .collect();
+        thoughts.sort_by_key(|t| t.thought_number);
+        Ok(thoughts)
     }
     
     async fn get_instance_thoughts(&self, instance: &str, limit: usize) -> Result<Vec<ThoughtRecord>> {
@@ -68,15 +196,32 @@ impl ThoughtStorage for MockRepository {
             .cloned()
             .collect())
     }
+    
+    async fn delete_thought(&self, instance: &str, thought_id: &str) -> Result<bool> {
+        let key = format!("{}:{}", instance, thought_id);
+        if self.thoughts.lock().unwrap().remove(&key).is_none() {
+            return Ok(false);
+        }
+        self.thought_metadata.lock().unwrap().remove(&key);
+        self.pii_records.lock().unwrap().retain(|record| record.instance != instance || record.thought_id != thought_id);
+        self.embeddings.lock().unwrap().remove(&key);
+        self.embedding_stale.lock().unwrap().remove(&key);
+        self.annotations.lock().unwrap().remove(&key);
+        self.boost_scores.lock().unwrap().remove(&key);
+        Ok(true)
+    }
 }
 
 #[cfg(test)]
 #[async_trait]
 impl ThoughtSearch for MockRepository {
-    async fn search_thoughts(&self, instance: &str, query: &str, limit: usize) -> Result<Vec<ThoughtRecord>> {
+    async fn search_thoughts(&self, instance: &str, query: &str, fields: &[SearchField], limit: usize) -> Result<Vec<ThoughtRecord>> {
+        let fields = search_index::resolve_fields(fields);
+        let metadata = self.thought_metadata.lock().unwrap();
         Ok(self.thoughts.lock().unwrap()
             .values()
-            .filter(|t| t.instance == instance && t.thought.contains(query))
+            .filter(|t| t.instance == instance)
+            .filter(|t| search_index::matches(t, metadata.get(&format!("{}:{}", t.instance, t.id)), query, &fields))
             .take(limit)
             .cloned()
             .collect())
@@ -87,10 +232,12 @@ impl ThoughtSearch for MockRepository {
         self.get_instance_thoughts(instance, limit).await
     }
     
-    async fn search_thoughts_global(&self, query: &str, limit: usize) -> Result<Vec<ThoughtRecord>> {
+    async fn search_thoughts_global(&self, query: &str, fields: &[SearchField], limit: usize) -> Result<Vec<ThoughtRecord>> {
+        let fields = search_index::resolve_fields(fields);
+        let metadata = self.thought_metadata.lock().unwrap();
         Ok(self.thoughts.lock().unwrap()
             .values()
-            .filter(|t| t.thought.contains(query))
+            .filter(|t| search_index::matches(t, metadata.get(&format!("{}:{}", t.instance, t.id)), query, &fields))
             .take(limit)
             .cloned()
             .collect())
@@ -151,6 +298,26 @@ impl ChainOperations for MockRepository {
     async fn chain_exists(&self, chain_id: &str) -> Result<bool> {
         Ok(self.chains.lock().unwrap().contains_key(chain_id))
     }
+    
+    async fn get_chain_metadata(&self, chain_id: &str) -> Result<Option<ChainMetadata>> {
+        Ok(self.chains.lock().unwrap().get(chain_id).cloned())
+    }
+    
+    async fn list_chain_metadata(&self, instance: &str) -> Result<Vec<ChainMetadata>> {
+        Ok(self.chains.lock().unwrap().values().filter(|metadata| metadata.instance == instance).cloned().collect())
+    }
+    
+    async fn delete_chain(&self, instance: &str, chain_id: &str) -> Result<usize> {
+        let mut deleted = 0;
+        for thought in self.get_chain_thoughts(instance, chain_id).await? {
+            if self.delete_thought(instance, &thought.id).await? {
+                deleted += 1;
+            }
+        }
+        self.chains.lock().unwrap().remove(chain_id);
+        self.chain_sync.lock().unwrap().remove(&format!("{}:{}", instance, chain_id));
+        Ok(deleted)
+    }
 }
 
 #[cfg(test)]
@@ -175,9 +342,27 @@ impl FeedbackOperations for MockRepository {
         Ok(1.0)
     }
     
+    async fn get_boost_score(&self, instance: &str, thought_id: &str) -> Result<f64> {
+        Ok(self.boost_scores.lock().unwrap().get(&format!("{}:{}", instance, thought_id)).copied().unwrap_or(0.0))
+    }
+    
+    async fn add_boost_score(&self, instance: &str, thought_id: &str, amount: f64) -> Result<f64> {
+        let mut scores = self.boost_scores.lock().unwrap();
+        let score = scores.entry(format!("{}:{}", instance, thought_id)).or_insert(0.0);
+        *score += amount;
+        Ok(*score)
+    }
+    
     async fn apply_boost_scores(&self, _instance: &str, _thoughts: &mut Vec<ThoughtRecord>) -> Result<()> {
         Ok(())
     }
+    
+    async fn get_citing_thoughts(&self, instance: &str, note_path: &str) -> Result<Vec<String>> {
+        Ok(self.thought_metadata.lock().unwrap().values()
+            .filter(|metadata| metadata.instance == instance && metadata.citations.iter().any(|c| c.note_path == note_path))
+            .map(|metadata| metadata.thought_id.clone())
+            .collect())
+    }
 }
 
 #[cfg(test)]
@@ -194,18 +379,23 @@ impl IdentityDocumentOperations for MockRepository {
     async fn get_identity_documents_by_field(&self, instance_id: &str, field_type: &str) -> Result<Vec<IdentityDocument>> {
         Ok(self.identity_docs.lock().unwrap()
             .values()
-            .filter(|d| d.instance == instance_id && d.field_type == field_type)
+            .filter(|d| d.instance == instance_id
+                && (d.field_type == field_type || d.field_type.starts_with(&format!("{}:", field_type))))
             .cloned()
             .collect())
     }
     
     async fn save_identity_document(&self, document: &IdentityDocument) -> Result<()> {
+        self.identity_history.lock().unwrap().push((document.instance.clone(), IdentityChange::saved(document)));
         self.identity_docs.lock().unwrap().insert(document.id.clone(), document.clone());
+        *self.identity_versions.lock().unwrap().entry(document.instance.clone()).or_default() += 1;
         Ok(())
     }
     
-    async fn delete_identity_document(&self, _instance_id: &str, _field_type: &str, document_id: &str) -> Result<()> {
+    async fn delete_identity_document(&self, instance_id: &str, field_type: &str, document_id: &str) -> Result<()> {
+        self.identity_history.lock().unwrap().push((instance_id.to_string(), IdentityChange::deleted(field_type, document_id)));
         self.identity_docs.lock().unwrap().remove(document_id);
+        *self.identity_versions.lock().unwrap().entry(instance_id.to_string()).or_default() += 1;
         Ok(())
     }
     
@@ -220,6 +410,18 @@ impl IdentityDocumentOperations for MockRepository {
     async fn get_identity_document_by_id(&self, _instance_id: &str, document_id: &str) -> Result<Option<IdentityDocument>> {
         Ok(self.identity_docs.lock().unwrap().get(document_id).cloned())
     }
+    
+    async fn get_identity_version(&self, instance_id: &str) -> Result<u64> {
+        Ok(self.identity_versions.lock().unwrap().get(instance_id).copied().unwrap_or(0))
+    }
+    
+    async fn get_identity_history(&self, instance_id: &str) -> Result<Vec<IdentityChange>> {
+        Ok(self.identity_history.lock().unwrap()
+            .iter()
+            .filter(|(instance, _)| instance == instance_id)
+            .map(|(_, change)| change.clone())
+            .collect())
+    }
 }
 
 #[cfg(test)]
@@ -232,5 +434,453 @@ impl EventOperations for MockRepository {
     async fn publish_feedback_event(&self, _event: &serde_json::Value) -> Result<()> {
         Ok(())
     }
+    
+    async fn publish_notification(&self, channel: &str, event: &serde_json::Value) -> Result<()> {
+        self.notifications.lock().unwrap().push((channel.to_string(), event.clone()));
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+#[async_trait]
+impl PurgeOperations for MockRepository {
+    async fn purge_inventory(&self, namespace: &str) -> Result<Vec<String>> {
+        let prefix = format!("{}:", namespace);
+        Ok(self.thoughts.lock().unwrap()
+            .values()
+            .filter(|t| (t.instance == namespace || t.instance.starts_with(&prefix)) && crate::purge::same_tenant(namespace, &t.instance))
+            .map(|t| format!("{}:Thoughts:{}", t.instance, t.id))
+            .collect())
+    }
+    
+    async fn purge_keys(&self, keys: &[String]) -> Result<usize> {
+        let mut thoughts = self.thoughts.lock().unwrap();
+        let mut removed = 0;
+        for key in keys {
+            if let Some((instance, id)) = key.split_once(":Thoughts:") {
+                if thoughts.remove(&format!("{}:{}", instance, id)).is_some() {
+                    removed += 1;
+                }
+            }
+        }
+        Ok(removed)
+    }
+    
+    async fn save_purge_token(&self, namespace: &str, token: &str, _ttl_seconds: u64) -> Result<()> {
+        self.purge_tokens.lock().unwrap().insert(namespace.to_string(), token.to_string());
+        Ok(())
+    }
+    
+    async fn take_purge_token(&self, namespace: &str) -> Result<Option<String>> {
+        Ok(self.purge_tokens.lock().unwrap().remove(namespace))
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl PiiOperations for MockRepository {
+    async fn save_pii_record(&self, record: &PiiRecord) -> Result<()> {
+        if *self.failing_pii_writes.lock().unwrap() {
+            return Err(crate::error::UnifiedIntelligenceError::Internal("PII record write failed".to_string()));
+        }
+        self.pii_records.lock().unwrap().push(record.clone());
+        Ok(())
+    }
+    
+    async fn get_pii_records(&self, instance: &str, limit: usize) -> Result<Vec<PiiRecord>> {
+        Ok(self.pii_records.lock().unwrap()
+            .iter()
+            .rev()
+            .filter(|r| r.instance == instance)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl ChainSyncOperations for MockRepository {
+    async fn save_chain_sync_state(&self, state: &ChainSyncState) -> Result<()> {
+        let key = format!("{}:{}", state.instance, state.chain_id);
+        self.chain_sync.lock().unwrap().insert(key, state.clone());
+        Ok(())
+    }
+    
+    async fn get_chain_sync_state(&self, instance: &str, chain_id: &str) -> Result<Option<ChainSyncState>> {
+        let key = format!("{}:{}", instance, chain_id);
+        Ok(self.chain_sync.lock().unwrap().get(&key).cloned())
+    }
 }
 
+#[cfg(test)]
+#[async_trait]
+impl SearchIndexOperations for MockRepository {
+    async fn register_search_instance(&self, instance: &str) -> Result<bool> {
+        let prefix = format!("{}:Thoughts:", instance);
+        let mut prefixes = self.search_prefixes.lock().unwrap();
+        if prefixes.contains(&prefix) {
+            return Ok(false);
+        }
+        prefixes.push(prefix);
+        Ok(true)
+    }
+    
+    async fn search_index_status(&self) -> Result<SearchIndexStatus> {
+        let mut discovered: Vec<String> = self.thoughts.lock().unwrap()
+            .values()
+            .map(|t| t.instance.clone())
+            .collect();
+        discovered.sort();
+        discovered.dedup();
+        
+        Ok(SearchIndexStatus {
+            registered_prefixes: self.search_prefixes.lock().unwrap().clone(),
+            indexed_prefixes: self.indexed_prefixes.lock().unwrap().clone(),
+            discovered_instances: discovered,
+            degraded_features: Vec::new(),
+        })
+    }
+    
+    async fn rebuild_search_index(&self) -> Result<bool> {
+        let prefixes = self.search_prefixes.lock().unwrap().clone();
+        *self.indexed_prefixes.lock().unwrap() = Some(prefixes);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl ClientOperations for MockRepository {
+    async fn record_client_session(&self, instance: &str, name: &str, version: &str, protocol_version: &str) -> Result<()> {
+        let mut clients = self.clients.lock().unwrap();
+        let stats = clients.entry(format!("{}:{}", instance, name)).or_default();
+        stats.name = name.to_string();
+        stats.version = version.to_string();
+        stats.protocol_version = protocol_version.to_string();
+        stats.sessions += 1;
+        Ok(())
+    }
+    
+    async fn record_client_tool_call(&self, instance: &str, name: &str, tool: &str, failed: bool) -> Result<()> {
+        let mut clients = self.clients.lock().unwrap();
+        let stats = clients.entry(format!("{}:{}", instance, name)).or_default();
+        stats.name = name.to_string();
+        stats.total_calls += 1;
+        stats.errors += u64::from(failed);
+        *stats.tool_counts.entry(tool.to_string()).or_insert(0) += 1;
+        Ok(())
+    }
+    
+    async fn get_client_stats(&self, instance: &str) -> Result<Vec<ClientStats>> {
+        let prefix = format!("{}:", instance);
+        Ok(self.clients.lock().unwrap()
+            .iter()
+            .filter(|(key, _)| key.starts_with(&prefix))
+            .map(|(_, stats)| stats.clone())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl CaptureOperations for MockRepository {
+    async fn mark_captured(&self, instance: &str, item_id: &str) -> Result<bool> {
+        Ok(self.captured.lock().unwrap().insert(format!("{}:{}", instance, item_id)))
+    }
+    
+    async fn unmark_captured(&self, instance: &str, item_id: &str) -> Result<()> {
+        self.captured.lock().unwrap().remove(&format!("{}:{}", instance, item_id));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl EmbeddingOperations for MockRepository {
+    async fn get_embedding_version(&self, instance: &str, thought_id: &str) -> Result<Option<EmbeddingVersion>> {
+        Ok(self.embeddings.lock().unwrap().get(&format!("{}:{}", instance, thought_id)).cloned())
+    }
+    
+    async fn queue_reembedding(&self, instance: &str, thought_id: &str, priority: f64) -> Result<()> {
+        self.embedding_stale.lock().unwrap().insert(format!("{}:{}", instance, thought_id), priority);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl IdentityTemplateOperations for MockRepository {
+    async fn get_identity_template(&self, name: &str) -> Result<Option<Identity>> {
+        Ok(self.identity_templates.lock().unwrap().get(name).cloned())
+    }
+    
+    async fn save_identity_template(&self, name: &str, template: &Identity) -> Result<()> {
+        self.identity_templates.lock().unwrap().insert(name.to_string(), template.clone());
+        Ok(())
+    }
+    
+    async fn list_identity_templates(&self) -> Result<Vec<String>> {
+        let mut names: Vec<String> = self.identity_templates.lock().unwrap().keys().cloned().collect();
+        names.sort();
+        Ok(names)
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl DiagnosticsOperations for MockRepository {
+    async fn backend_diagnostics(&self) -> Result<BackendDiagnostics> {
+        Ok(BackendDiagnostics {
+            backend: "mock".to_string(),
+            redis_modules: None,
+            pool: None,
+        })
+    }
+    
+    async fn save_crash_report(&self, _instance: &str, report: &CrashReport) -> Result<()> {
+        self.crash_reports.lock().unwrap().insert(0, report.clone());
+        Ok(())
+    }
+    
+    async fn get_crash_reports(&self, _instance: &str, limit: usize) -> Result<Vec<CrashReport>> {
+        Ok(self.crash_reports.lock().unwrap().iter().take(limit).cloned().collect())
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl PersonaOperations for MockRepository {
+    async fn save_persona_snapshot(&self, _instance: &str, bundle: &PersonaBundle) -> Result<()> {
+        self.persona_snapshots.lock().unwrap().insert(bundle.version, bundle.clone());
+        Ok(())
+    }
+    
+    async fn get_persona_snapshot(&self, _instance: &str, version: u64) -> Result<Option<PersonaBundle>> {
+        Ok(self.persona_snapshots.lock().unwrap().get(&version).cloned())
+    }
+    
+    async fn list_persona_versions(&self, _instance: &str) -> Result<Vec<u64>> {
+        Ok(self.persona_snapshots.lock().unwrap().keys().copied().collect())
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl AnnotationOperations for MockRepository {
+    async fn save_annotation(&self, instance: &str, annotation: &Annotation) -> Result<()> {
+        self.annotations.lock().unwrap()
+            .entry(format!("{}:{}", instance, annotation.thought_id))
+            .or_default()
+            .insert(0, annotation.clone());
+        Ok(())
+    }
+    
+    async fn get_annotations(&self, instance: &str, thought_id: &str) -> Result<Vec<Annotation>> {
+        Ok(self.annotations.lock().unwrap().get(&format!("{}:{}", instance, thought_id)).cloned().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl TieringOperations for MockRepository {
+    async fn replace_thought(&self, thought: &ThoughtRecord) -> Result<()> {
+        let key = format!("{}:{}", thought.instance, thought.id);
+        self.thoughts.lock().unwrap().insert(key, thought.clone());
+        Ok(())
+    }
+    
+    async fn get_last_access(&self, _instance: &str, _thought_id: &str) -> Result<Option<i64>> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl ModeOperations for MockRepository {
+    async fn get_active_mode(&self, instance: &str) -> Result<Option<String>> {
+        Ok(self.active_modes.lock().unwrap().get(instance).cloned())
+    }
+    
+    async fn set_active_mode(&self, instance: &str, mode: &str) -> Result<()> {
+        self.active_modes.lock().unwrap().insert(instance.to_string(), mode.to_string());
+        Ok(())
+    }
+    
+    async fn save_mode_profile(&self, instance: &str, profile: &ModeProfile) -> Result<()> {
+        self.modes.lock().unwrap().insert(format!("{}:{}", instance, profile.name), profile.clone());
+        Ok(())
+    }
+    
+    async fn get_mode_profiles(&self, instance: &str) -> Result<Vec<ModeProfile>> {
+        let prefix = format!("{}:", instance);
+        let mut profiles: Vec<ModeProfile> = self.modes.lock().unwrap().iter()
+            .filter(|(key, _)| key.starts_with(&prefix))
+            .map(|(_, profile)| profile.clone())
+            .collect();
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(profiles)
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl PressureOperations for MockRepository {
+    async fn memory_usage(&self) -> Result<Option<MemoryUsage>> {
+        Ok(self.memory_usage.lock().unwrap().clone())
+    }
+    
+    async fn set_essential_ingest(&self, essential: bool) {
+        *self.essential_ingest.lock().unwrap() = essential;
+    }
+    
+    async fn publish_intervention(&self, intervention: &serde_json::Value) -> Result<()> {
+        self.interventions.lock().unwrap().push(intervention.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl MigrationOperations for MockRepository {
+    async fn get_applied_migrations(&self, instance: &str) -> Result<Vec<AppliedMigration>> {
+        let mut applied = self.migrations.lock().unwrap().get(instance).cloned().unwrap_or_default();
+        applied.sort_by_key(|migration| migration.version);
+        Ok(applied)
+    }
+    
+    async fn record_migration(&self, instance: &str, migration: &AppliedMigration) -> Result<()> {
+        let mut migrations = self.migrations.lock().unwrap();
+        let applied = migrations.entry(instance.to_string()).or_default();
+        applied.retain(|m| m.version != migration.version);
+        applied.push(migration.clone());
+        Ok(())
+    }
+    
+    async fn acquire_migration_lock(&self, instance: &str, owner: &str, _ttl_seconds: u64) -> Result<bool> {
+        let mut locks = self.migration_locks.lock().unwrap();
+        if locks.contains_key(instance) {
+            return Ok(false);
+        }
+        locks.insert(instance.to_string(), owner.to_string());
+        Ok(true)
+    }
+    
+    async fn release_migration_lock(&self, instance: &str, owner: &str) -> Result<()> {
+        let mut locks = self.migration_locks.lock().unwrap();
+        if locks.get(instance).is_some_and(|holder| holder == owner) {
+            locks.remove(instance);
+        }
+        Ok(())
+    }
+    
+    async fn get_migration_lock(&self, instance: &str) -> Result<Option<String>> {
+        Ok(self.migration_locks.lock().unwrap().get(instance).cloned())
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl BulkUpdateOperations for MockRepository {
+    async fn save_bulk_undo(&self, _instance: &str, record: &BulkUndoRecord, max_records: usize) -> Result<()> {
+        let mut records = self.bulk_undo.lock().unwrap();
+        records.insert(0, record.clone());
+        records.truncate(max_records);
+        Ok(())
+    }
+    
+    async fn get_bulk_undo(&self, _instance: &str, undo_id: &str) -> Result<Option<BulkUndoRecord>> {
+        Ok(self.bulk_undo.lock().unwrap().iter().find(|r| r.undo_id == undo_id).cloned())
+    }
+    
+    async fn delete_bulk_undo(&self, _instance: &str, undo_id: &str) -> Result<bool> {
+        let mut records = self.bulk_undo.lock().unwrap();
+        let before = records.len();
+        records.retain(|r| r.undo_id != undo_id);
+        Ok(records.len() < before)
+    }
+    
+    async fn claim_bulk_undo(&self, _instance: &str, undo_id: &str, owner: &str, _ttl_seconds: u64) -> Result<bool> {
+        let mut claims = self.bulk_undo_claims.lock().unwrap();
+        if claims.contains_key(undo_id) {
+            return Ok(false);
+        }
+        claims.insert(undo_id.to_string(), owner.to_string());
+        Ok(true)
+    }
+    
+    async fn release_bulk_undo(&self, _instance: &str, undo_id: &str, owner: &str) -> Result<()> {
+        let mut claims = self.bulk_undo_claims.lock().unwrap();
+        if claims.get(undo_id).is_some_and(|holder| holder == owner) {
+            claims.remove(undo_id);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl RecallTuningOperations for MockRepository {
+    async fn get_recall_tuning(&self, _instance: &str) -> Result<Option<RecallTuning>> {
+        Ok(self.recall_tuning.lock().unwrap().clone())
+    }
+    
+    async fn save_recall_tuning(&self, _instance: &str, tuning: &RecallTuning) -> Result<()> {
+        *self.recall_tuning.lock().unwrap() = Some(tuning.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl StreamOperations for MockRepository {
+    async fn read_stream(&self, key: &str) -> Result<Vec<StreamEntry>> {
+        Ok(self.streams.lock().unwrap().get(key).cloned().unwrap_or_default())
+    }
+    
+    async fn trim_stream(&self, key: &str, last_id: &str) -> Result<usize> {
+        let mut streams = self.streams.lock().unwrap();
+        let Some(stream) = streams.get_mut(key) else {
+            return Ok(0);
+        };
+        let Some(end) = stream.iter().position(|entry| entry.id == last_id) else {
+            return Ok(0);
+        };
+        Ok(stream.drain(..=end).count())
+    }
+    
+    async fn archive_stream_entries(&self, _instance: &str, entries: &[ArchivedStreamEntry], max_entries: usize) -> Result<()> {
+        let mut archive = self.stream_archive.lock().unwrap();
+        for entry in entries {
+            archive.insert(0, entry.clone());
+        }
+        archive.truncate(max_entries);
+        Ok(())
+    }
+    
+    async fn get_stream_compaction(&self, _instance: &str) -> Result<Option<StreamCompaction>> {
+        Ok(self.stream_compaction.lock().unwrap().clone())
+    }
+    
+    async fn save_stream_compaction(&self, _instance: &str, compaction: &StreamCompaction) -> Result<()> {
+        *self.stream_compaction.lock().unwrap() = Some(compaction.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl ImportanceOperations for MockRepository {
+    async fn record_recalls(&self, instance: &str, thought_ids: &[String]) -> Result<()> {
+        let mut counts = self.recall_counts.lock().unwrap();
+        for thought_id in thought_ids {
+            *counts.entry(format!("{}:{}", instance, thought_id)).or_default() += 1;
+        }
+        Ok(())
+    }
+    
+    async fn get_recall_count(&self, instance: &str, thought_id: &str) -> Result<u64> {
+        Ok(self.recall_counts.lock().unwrap().get(&format!("{}:{}", instance, thought_id)).copied().unwrap_or(0))
+    }
+}
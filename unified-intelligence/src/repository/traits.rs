@@ -2,9 +2,14 @@ use async_trait::async_trait;
 use crate::error::Result;
 use crate::models::{
     ThoughtRecord, ChainMetadata, Identity, ThoughtMetadata, 
-    UiRecallFeedbackParams
+    UiRecallFeedbackParams, PiiRecord, ChainSyncState, SearchIndexStatus, ClientStats,
+    EmbeddingVersion, BackendDiagnostics, CrashReport, PersonaBundle, Annotation, ModeProfile,
+    MemoryUsage, AppliedMigration, BulkUndoRecord, RecallTuning,
+    StreamEntry, ArchivedStreamEntry, StreamCompaction
 };
 use crate::identity_documents::IdentityDocument;
+use crate::identity_history::IdentityChange;
+use crate::search_index::SearchField;
 
 /// Core trait for thought storage and retrieval operations
 #[async_trait]
@@ -24,17 +29,21 @@ pub trait ThoughtStorage: Send + Sync {
     
     /// Get thoughts from all instances
     async fn get_all_thoughts(&self, limit: usize) -> Result<Vec<ThoughtRecord>>;
+    
+    /// Delete a thought with its metadata, indexes, feedback, embedding and annotations; false when it didn't exist
+    async fn delete_thought(&self, instance: &str, thought_id: &str) -> Result<bool>;
 }
 
 /// Trait for thought search operations
 #[async_trait]
 #[cfg_attr(test, mockall::automock)]
 pub trait ThoughtSearch: Send + Sync {
-    /// Search thoughts by query
+    /// Search thoughts by query in the given fields, or the weighted default fields when empty
     async fn search_thoughts(
         &self,
         instance: &str,
         query: &str,
+        fields: &[SearchField],
         limit: usize,
     ) -> Result<Vec<ThoughtRecord>>;
     
@@ -47,10 +56,11 @@ pub trait ThoughtSearch: Send + Sync {
         threshold: f32,
     ) -> Result<Vec<ThoughtRecord>>;
     
-    /// Search thoughts across all instances by query
+    /// Search thoughts across all instances by query in the given fields
     async fn search_thoughts_global(
         &self,
         query: &str,
+        fields: &[SearchField],
         limit: usize,
     ) -> Result<Vec<ThoughtRecord>>;
     
@@ -109,6 +119,15 @@ pub trait ChainOperations: Send + Sync {
     
     /// Check if chain exists
     async fn chain_exists(&self, chain_id: &str) -> Result<bool>;
+    
+    /// Get chain metadata by chain ID
+    async fn get_chain_metadata(&self, chain_id: &str) -> Result<Option<ChainMetadata>>;
+    
+    /// Metadata of every chain an instance created
+    async fn list_chain_metadata(&self, instance: &str) -> Result<Vec<ChainMetadata>>;
+    
+    /// Delete a chain's thoughts (as delete_thought does), metadata and sync state, returning how many thoughts were deleted
+    async fn delete_chain(&self, instance: &str, chain_id: &str) -> Result<usize>;
 }
 
 /// Trait for feedback and boost score operations
@@ -128,8 +147,17 @@ pub trait FeedbackOperations: Send + Sync {
     async fn update_boost_score(&self, instance: &str, thought_id: &str, feedback_action: &str, relevance_rating: Option<i32>, dwell_time: Option<i32>) -> Result<f64>;
     
     
+    /// Get the feedback boost score of a thought (0.0 when it has none)
+    async fn get_boost_score(&self, instance: &str, thought_id: &str) -> Result<f64>;
+    
+    /// Add to a thought's boost score directly, as when importing a chain; returns the new score
+    async fn add_boost_score(&self, instance: &str, thought_id: &str, amount: f64) -> Result<f64>;
+    
     /// Apply boost scores to search results for ranking
     async fn apply_boost_scores(&self, instance: &str, thoughts: &mut Vec<ThoughtRecord>) -> Result<()>;
+    
+    /// Ids of thoughts whose metadata cites a vault note
+    async fn get_citing_thoughts(&self, instance: &str, note_path: &str) -> Result<Vec<String>>;
 }
 
 /// Trait for identity management operations
@@ -159,6 +187,12 @@ pub trait IdentityDocumentOperations: Send + Sync {
     
     /// Get identity document by ID
     async fn get_identity_document_by_id(&self, instance_id: &str, document_id: &str) -> Result<Option<IdentityDocument>>;
+    
+    /// Counter bumped by every identity document save or delete; 0 before the first write
+    async fn get_identity_version(&self, instance_id: &str) -> Result<u64>;
+    
+    /// Recorded identity document changes, oldest first
+    async fn get_identity_history(&self, instance_id: &str) -> Result<Vec<IdentityChange>>;
 }
 
 /// Trait for event streaming operations
@@ -170,8 +204,276 @@ pub trait EventOperations: Send + Sync {
     
     /// Publish event to feedback stream for background processing
     async fn publish_feedback_event(&self, event: &serde_json::Value) -> Result<()>;
+    
+    /// Publish an event on a pub/sub channel for the notification bridge
+    async fn publish_notification(&self, channel: &str, event: &serde_json::Value) -> Result<()>;
+}
+
+/// Trait for GDPR-style data purge operations
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait PurgeOperations: Send + Sync {
+    /// List every key owned by a namespace (an instance, or a whole user scope)
+    async fn purge_inventory(&self, namespace: &str) -> Result<Vec<String>>;
+    
+    /// Delete the given keys, returning how many were removed
+    async fn purge_keys(&self, keys: &[String]) -> Result<usize>;
+    
+    /// Store the confirmation token issued by a dry run
+    async fn save_purge_token(&self, namespace: &str, token: &str, ttl_seconds: u64) -> Result<()>;
+    
+    /// Fetch and consume the pending confirmation token
+    async fn take_purge_token(&self, namespace: &str) -> Result<Option<String>>;
+}
+
+/// Trait for PII findings storage
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait PiiOperations: Send + Sync {
+    /// Store the PII findings (and encrypted original, if retained) for a thought
+    async fn save_pii_record(&self, record: &PiiRecord) -> Result<()>;
+    
+    /// Get PII findings recorded for an instance, most recent first
+    async fn get_pii_records(&self, instance: &str, limit: usize) -> Result<Vec<PiiRecord>>;
+}
+
+/// Trait for chain-to-note sync state
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait ChainSyncOperations: Send + Sync {
+    /// Store the sync state of a chain's Obsidian note
+    async fn save_chain_sync_state(&self, state: &ChainSyncState) -> Result<()>;
+    
+    /// Get the sync state of a chain's Obsidian note
+    async fn get_chain_sync_state(&self, instance: &str, chain_id: &str) -> Result<Option<ChainSyncState>>;
+}
+
+/// Trait for search index prefix management
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait SearchIndexOperations: Send + Sync {
+    /// Register an instance so the search index covers its thoughts, returning true if it was new
+    async fn register_search_instance(&self, instance: &str) -> Result<bool>;
+    
+    /// Registered and indexed prefixes, plus the instances that actually have thoughts
+    async fn search_index_status(&self) -> Result<SearchIndexStatus>;
+    
+    /// Recreate the search index over every registered prefix
+    async fn rebuild_search_index(&self) -> Result<bool>;
+}
+
+/// Trait for per-client usage stats
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait ClientOperations: Send + Sync {
+    /// Record that a client initialized a session
+    async fn record_client_session(&self, instance: &str, name: &str, version: &str, protocol_version: &str) -> Result<()>;
+    
+    /// Count a tool call made by a client
+    async fn record_client_tool_call(&self, instance: &str, name: &str, tool: &str, failed: bool) -> Result<()>;
+    
+    /// Usage stats of every client seen by an instance
+    async fn get_client_stats(&self, instance: &str) -> Result<Vec<ClientStats>>;
 }
 
+/// Trait for capture connector bookkeeping
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait CaptureOperations: Send + Sync {
+    /// Remember a captured item id; false if it was captured before
+    async fn mark_captured(&self, instance: &str, item_id: &str) -> Result<bool>;
+    
+    /// Forget a captured item id so a failed capture is retried
+    async fn unmark_captured(&self, instance: &str, item_id: &str) -> Result<()>;
+}
+
+/// Trait for embedding bookkeeping; vectors themselves are written by the embedding workers
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait EmbeddingOperations: Send + Sync {
+    /// Model that embedded a thought; None when the thought has no embedding
+    async fn get_embedding_version(&self, instance: &str, thought_id: &str) -> Result<Option<EmbeddingVersion>>;
+    
+    /// Queue a thought for re-embedding; higher priorities are re-embedded first
+    async fn queue_reembedding(&self, instance: &str, thought_id: &str, priority: f64) -> Result<()>;
+}
+
+/// Identity template storage, shared by all instances
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait IdentityTemplateOperations: Send + Sync {
+    /// Stored template by name
+    async fn get_identity_template(&self, name: &str) -> Result<Option<Identity>>;
+    
+    /// Store or replace a template
+    async fn save_identity_template(&self, name: &str, template: &Identity) -> Result<()>;
+    
+    /// Names of all stored templates
+    async fn list_identity_templates(&self) -> Result<Vec<String>>;
+}
+
+/// Trait for storage backend diagnostics
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait DiagnosticsOperations: Send + Sync {
+    /// Backend kind, loaded Redis modules and connection pool usage
+    async fn backend_diagnostics(&self) -> Result<BackendDiagnostics>;
+    
+    /// Keep a crash report, dropping the oldest beyond the retention limit
+    async fn save_crash_report(&self, instance: &str, report: &CrashReport) -> Result<()>;
+    
+    /// Newest crash reports first
+    async fn get_crash_reports(&self, instance: &str, limit: usize) -> Result<Vec<CrashReport>>;
+}
+
+/// Versioned persona bundles
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait PersonaOperations: Send + Sync {
+    /// Store a bundle under its version, replacing any bundle with the same version
+    async fn save_persona_snapshot(&self, instance: &str, bundle: &PersonaBundle) -> Result<()>;
+    
+    /// Bundle with the given version
+    async fn get_persona_snapshot(&self, instance: &str, version: u64) -> Result<Option<PersonaBundle>>;
+    
+    /// Stored versions, oldest first
+    async fn list_persona_versions(&self, instance: &str) -> Result<Vec<u64>>;
+}
+
+/// Review annotations on thoughts
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait AnnotationOperations: Send + Sync {
+    /// Keep an annotation, dropping the oldest beyond the per-thought limit
+    async fn save_annotation(&self, instance: &str, annotation: &Annotation) -> Result<()>;
+    
+    /// Newest annotations of a thought first
+    async fn get_annotations(&self, instance: &str, thought_id: &str) -> Result<Vec<Annotation>>;
+}
+
+/// Cold storage tiering of thoughts
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait TieringOperations: Send + Sync {
+    /// Overwrite a stored thought (stub or full record) without touching chains, counters or the bloom filter
+    async fn replace_thought(&self, thought: &ThoughtRecord) -> Result<()>;
+    
+    /// Epoch seconds of the thought's last read, None if never read or not tracked
+    async fn get_last_access(&self, instance: &str, thought_id: &str) -> Result<Option<i64>>;
+}
+
+/// Operating modes of an instance
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait ModeOperations: Send + Sync {
+    /// Name of the active mode, None if the instance never switched
+    async fn get_active_mode(&self, instance: &str) -> Result<Option<String>>;
+    
+    /// Make a mode the active one
+    async fn set_active_mode(&self, instance: &str, mode: &str) -> Result<()>;
+    
+    /// Store or replace a defined mode
+    async fn save_mode_profile(&self, instance: &str, profile: &ModeProfile) -> Result<()>;
+    
+    /// Modes defined for an instance, by name
+    async fn get_mode_profiles(&self, instance: &str) -> Result<Vec<ModeProfile>>;
+}
+
+/// Backend memory pressure, watched by the memory guard
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait PressureOperations: Send + Sync {
+    /// Backend memory figures; None when the backend doesn't report them
+    async fn memory_usage(&self) -> Result<Option<MemoryUsage>>;
+    
+    /// Limit ingest to essential work: new thoughts are queued for embedding instead of sent
+    async fn set_essential_ingest(&self, essential: bool);
+    
+    /// Publish an operator intervention
+    async fn publish_intervention(&self, intervention: &serde_json::Value) -> Result<()>;
+}
+
+/// Versioned migrations applied at startup
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait MigrationOperations: Send + Sync {
+    /// Migrations applied to an instance, in version order
+    async fn get_applied_migrations(&self, instance: &str) -> Result<Vec<AppliedMigration>>;
+    
+    /// Record a migration as applied
+    async fn record_migration(&self, instance: &str, migration: &AppliedMigration) -> Result<()>;
+    
+    /// Take the migration lock for `ttl_seconds`; false when another owner holds it
+    async fn acquire_migration_lock(&self, instance: &str, owner: &str, ttl_seconds: u64) -> Result<bool>;
+    
+    /// Release the migration lock if `owner` still holds it
+    async fn release_migration_lock(&self, instance: &str, owner: &str) -> Result<()>;
+    
+    /// Current holder of the migration lock
+    async fn get_migration_lock(&self, instance: &str) -> Result<Option<String>>;
+}
+
+/// Undo records of bulk metadata updates
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait BulkUpdateOperations: Send + Sync {
+    /// Keep an undo record, dropping the oldest beyond `max_records`
+    async fn save_bulk_undo(&self, instance: &str, record: &BulkUndoRecord, max_records: usize) -> Result<()>;
+    
+    /// Undo record with the given ID
+    async fn get_bulk_undo(&self, instance: &str, undo_id: &str) -> Result<Option<BulkUndoRecord>>;
+    
+    /// Remove an undo record; false when it was already removed, so an update is reverted at most once
+    async fn delete_bulk_undo(&self, instance: &str, undo_id: &str) -> Result<bool>;
+    
+    /// Claim an undo record for reverting for `ttl_seconds`; false when another owner holds the claim
+    async fn claim_bulk_undo(&self, instance: &str, undo_id: &str, owner: &str, ttl_seconds: u64) -> Result<bool>;
+    
+    /// Release a revert claim if `owner` still holds it
+    async fn release_bulk_undo(&self, instance: &str, undo_id: &str, owner: &str) -> Result<()>;
+}
+
+/// Recall defaults tuned from feedback
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait RecallTuningOperations: Send + Sync {
+    /// Tuning of an instance, None if it was never tuned or searched with tuned defaults
+    async fn get_recall_tuning(&self, instance: &str) -> Result<Option<RecallTuning>>;
+    
+    /// Store an instance's tuning
+    async fn save_recall_tuning(&self, instance: &str, tuning: &RecallTuning) -> Result<()>;
+}
+
+/// Compaction of the event streams
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait StreamOperations: Send + Sync {
+    /// All entries of a stream, oldest first
+    async fn read_stream(&self, key: &str) -> Result<Vec<StreamEntry>>;
+    
+    /// Remove the entries up to and including `last_id`, returning how many were removed
+    async fn trim_stream(&self, key: &str, last_id: &str) -> Result<usize>;
+    
+    /// Keep entries aside before a trim, oldest first, in a newest-first archive of at most `max_entries`
+    async fn archive_stream_entries(&self, instance: &str, entries: &[ArchivedStreamEntry], max_entries: usize) -> Result<()>;
+    
+    /// Compaction counts of an instance, None before its first run
+    async fn get_stream_compaction(&self, instance: &str) -> Result<Option<StreamCompaction>>;
+    
+    /// Store an instance's compaction counts
+    async fn save_stream_compaction(&self, instance: &str, compaction: &StreamCompaction) -> Result<()>;
+}
+
+/// Usage counts importance is computed from
+#[async_trait]
+#[cfg_attr(test, mockall::automock)]
+pub trait ImportanceOperations: Send + Sync {
+    /// Count one recall of each thought
+    async fn record_recalls(&self, instance: &str, thought_ids: &[String]) -> Result<()>;
+    
+    /// Times ui_recall returned a thought
+    async fn get_recall_count(&self, instance: &str, thought_id: &str) -> Result<u64>;
+}
 
 /// Combined repository trait that includes all operations
 /// This can be used for backwards compatibility or when all operations are needed
@@ -185,6 +487,25 @@ pub trait Repository:
     IdentityOperations + 
     IdentityDocumentOperations + 
     EventOperations + 
+    PurgeOperations + 
+    PiiOperations + 
+    ChainSyncOperations + 
+    SearchIndexOperations + 
+    ClientOperations + 
+    CaptureOperations + 
+    EmbeddingOperations + 
+    IdentityTemplateOperations + 
+    DiagnosticsOperations + 
+    PersonaOperations + 
+    AnnotationOperations + 
+    TieringOperations + 
+    ModeOperations + 
+    PressureOperations + 
+    MigrationOperations + 
+    BulkUpdateOperations + 
+    RecallTuningOperations + 
+    StreamOperations + 
+    ImportanceOperations + 
     Send + 
     Sync 
 {}
@@ -200,6 +521,25 @@ where
        IdentityOperations + 
        IdentityDocumentOperations + 
        EventOperations + 
+       PurgeOperations + 
+       PiiOperations + 
+       ChainSyncOperations + 
+       SearchIndexOperations + 
+       ClientOperations + 
+       CaptureOperations + 
+       EmbeddingOperations + 
+       IdentityTemplateOperations + 
+       DiagnosticsOperations + 
+       PersonaOperations + 
+       AnnotationOperations + 
+       TieringOperations + 
+       ModeOperations + 
+       PressureOperations + 
+       MigrationOperations + 
+       BulkUpdateOperations + 
+       RecallTuningOperations + 
+       StreamOperations + 
+       ImportanceOperations + 
        Send + 
        Sync 
 {}
\ No newline at end of file
@@ -0,0 +1,170 @@
+//! Weekly review assembly for ui_weekly_review.
+//!
+//! Decisions are thoughts tagged `decision` or phrased as one ("Decision:",
+//! "decided to", ...). Action items are checklist lines (`- [ ]`, `- [x]`) and
+//! `TODO:`/`DONE:` lines; a thought tagged `todo` or `done` without such lines
+//! counts as one item. An item seen both open and done during the week counts
+//! as completed. Topic trends compare RAKE keywords of the week with the week
+//! before.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+
+use crate::keywords::{self, Language};
+use crate::models::TopicTrend;
+
+/// Most instance thoughts scanned for the reviewed and the previous week
+pub const SCAN_LIMIT: usize = 10000;
+
+/// Topics extracted for the reviewed week
+pub const TOPIC_LIMIT: usize = 8;
+
+/// Entries listed per section of the stored review
+pub const LIST_LIMIT: usize = 25;
+
+/// Tag applied to every review thought
+pub const REVIEW_TAG: &str = "weekly-review";
+
+/// Questions returned with every review; answers are stored as reflection thoughts
+pub const REFLECTION_PROMPTS: &[&str] = &[
+    "What went well this week, and why?",
+    "What did not go as planned, and what would you change?",
+    "Which open action items still matter, and which can be dropped?",
+    "What is the one thing to focus on next week?",
+];
+
+const DECISION_PHRASES: &[&str] = &["decision:", "decided to", "we decided", "i decided", "going with", "settled on"];
+
+/// Start (inclusive) and end (exclusive) of the week ending at `week_ending`
+///
+/// Accepts RFC 3339 or a `YYYY-MM-DD` date (the week then includes that day);
+/// defaults to now.
+pub fn week_bounds(week_ending: Option<&str>) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let end = match week_ending {
+        None => Utc::now(),
+        Some(value) => match DateTime::parse_from_rfc3339(value) {
+            Ok(end) => end.with_timezone(&Utc),
+            Err(_) => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map_err(|_| format!("'{}' is neither RFC 3339 nor YYYY-MM-DD", value))?
+                .succ_opt()
+                .and_then(|day| day.and_hms_opt(0, 0, 0))
+                .ok_or_else(|| format!("'{}' is out of range", value))?
+                .and_utc(),
+        },
+    };
+    Ok((end - Duration::days(7), end))
+}
+
+/// ISO week label such as `2025-W29`, used as a tag on the review
+pub fn week_label(week_end: DateTime<Utc>) -> String {
+    // The end bound is exclusive, so label by the last instant inside the week
+    let week = (week_end - Duration::seconds(1)).iso_week();
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
+/// Whether a thought records a decision
+pub fn is_decision(text: &str, tags: &[String]) -> bool {
+    if tags.iter().any(|tag| tag == "decision") {
+        return true;
+    }
+    let lower = text.to_lowercase();
+    DECISION_PHRASES.iter().any(|phrase| lower.contains(phrase))
+}
+
+/// Action items in a thought as (text, done)
+pub fn action_items(text: &str, tags: &[String]) -> Vec<(String, bool)> {
+    let mut items = Vec::new();
+    for line in text.lines().map(str::trim) {
+        let line = line.trim_start_matches(['-', '*']).trim_start();
+        let item = if let Some(rest) = line.strip_prefix("[ ]") {
+            Some((rest, false))
+        } else if let Some(rest) = line.strip_prefix("[x]").or_else(|| line.strip_prefix("[X]")) {
+            Some((rest, true))
+        } else if let Some(rest) = line.strip_prefix("TODO:") {
+            Some((rest, false))
+        } else {
+            line.strip_prefix("DONE:").map(|rest| (rest, true))
+        };
+        if let Some((rest, done)) = item.filter(|(rest, _)| !rest.trim().is_empty()) {
+            items.push((rest.trim().to_string(), done));
+        }
+    }
+    if items.is_empty() {
+        let done = tags.iter().any(|tag| tag == "done");
+        if done || tags.iter().any(|tag| tag == "todo") {
+            items.push((snippet(text, 160), done));
+        }
+    }
+    items
+}
+
+/// Keywords of this week against the previous one: `new`, `continuing` or `faded`
+pub fn topic_trends(current: &str, previous: &str, limit: usize) -> Vec<TopicTrend> {
+    let extract = |corpus: &str, limit| keywords::extract_keywords(corpus, Language::for_text(corpus), limit);
+    let this_week = extract(current, limit);
+    let last_week = extract(previous, limit * 2);
+
+    let mut trends: Vec<TopicTrend> = this_week.iter().map(|keyword| TopicTrend {
+        topic: keyword.phrase.clone(),
+        score: keyword.score,
+        trend: if last_week.iter().any(|old| old.phrase == keyword.phrase) { "continuing" } else { "new" }.to_string(),
+    }).collect();
+    trends.extend(last_week.iter().take(limit / 2)
+        .filter(|old| !this_week.iter().any(|keyword| keyword.phrase == old.phrase))
+        .map(|old| TopicTrend { topic: old.phrase.clone(), score: old.score, trend: "faded".to_string() }));
+    trends
+}
+
+/// First line of a thought, shortened to `max_chars`
+pub fn snippet(text: &str, max_chars: usize) -> String {
+    let line = text.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or("");
+    if line.chars().count() <= max_chars {
+        return line.to_string();
+    }
+    let cut: String = line.chars().take(max_chars.saturating_sub(1)).collect();
+    format!("{}…", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_week_bounds_and_label() {
+        let (start, end) = week_bounds(Some("2025-07-20")).unwrap();
+        assert_eq!(end.to_rfc3339(), "2025-07-21T00:00:00+00:00");
+        assert_eq!(start.to_rfc3339(), "2025-07-14T00:00:00+00:00");
+        assert_eq!(week_label(end), "2025-W29");
+        assert!(week_bounds(Some("last week")).is_err());
+    }
+
+    #[test]
+    fn test_decisions_and_action_items() {
+        assert!(is_decision("After benchmarking we decided to keep Lua scripts", &[]));
+        assert!(is_decision("Redis 8 hash expiration", &["decision".to_string()]));
+        assert!(!is_decision("Thinking about caching", &[]));
+
+        let items = action_items("Plan:\n- [ ] add load test\n- [x] bench recall\nTODO: rotate keys\n* DONE: fix bloom", &[]);
+        assert_eq!(items, vec![
+            ("add load test".to_string(), false),
+            ("bench recall".to_string(), true),
+            ("rotate keys".to_string(), false),
+            ("fix bloom".to_string(), true),
+        ]);
+        assert_eq!(action_items("Ship the importer", &["done".to_string()]), vec![("Ship the importer".to_string(), true)]);
+        assert!(action_items("Just a note", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_topic_trends() {
+        let trends = topic_trends(
+            "Redis schema migration. Redis schema migration again. Bloom filter sizing.",
+            "Bloom filter sizing. Vector index tuning. Vector index tuning.",
+            4,
+        );
+        let trend = |topic: &str| trends.iter().find(|t| t.topic == topic).map(|t| t.trend.as_str());
+        assert_eq!(trend("redis schema migration"), Some("new"));
+        assert_eq!(trend("bloom filter sizing"), Some("continuing"));
+        assert_eq!(trend("vector index tuning"), Some("faded"));
+        assert_eq!(snippet("  \nfirst line that is long\nsecond", 10), "first lin…");
+    }
+}
@@ -0,0 +1,308 @@
+//! Key schema and serde stability tests.
+//!
+//! Production Redis holds months of thoughts, chains and identities written
+//! with today's key formats and JSON layouts. These tests pin both down:
+//! snapshots (src/snapshots/) record the key formats and the serialized form of
+//! every stored record, and property tests round-trip generated records through
+//! serde and the repository layer (the in-memory backend, which is keyed
+//! exactly like Redis). A failing snapshot means a change would strand or
+//! misread existing data; only accept it together with a migration.
+//!
+//! Review snapshot changes with `cargo insta review` (or rerun with
+//! `INSTA_UPDATE=always` and inspect the diff).
+
+use chrono::TimeZone;
+use proptest::prelude::*;
+
+use crate::identity_documents::IdentityDocument;
+use crate::keys;
+use crate::models::{ChainMetadata, Identity, Provenance, ThoughtMetadata, ThoughtRecord};
+use crate::repository::{ChainOperations, IdentityDocumentOperations, MemoryRepository, PurgeOperations, ThoughtStorage};
+use crate::search_index;
+
+const FIXED_ID: &str = "0b6f2c1e-8d3a-4f5b-9c7d-1e2f3a4b5c6d";
+const FIXED_TIMESTAMP: &str = "2025-07-18T12:00:00+00:00";
+
+fn fixed_thought() -> ThoughtRecord {
+    ThoughtRecord {
+        id: FIXED_ID.to_string(),
+        instance: "CC".to_string(),
+        thought: "Redis keys must stay stable".to_string(),
+        thought_number: 2,
+        total_thoughts: 3,
+        timestamp: FIXED_TIMESTAMP.to_string(),
+        chain_id: Some("chain-1".to_string()),
+        next_thought_needed: true,
+        similarity: None,
+        user_id: Some("alice".to_string()),
+        provenance: Some(Provenance {
+            author: Some("model".to_string()),
+            source_tool: Some("ui_think".to_string()),
+            source_session: Some("session-1".to_string()),
+            model_used: Some("model-x".to_string()),
+            git_commit: Some("abc123".to_string()),
+            client: Some("client/1.0".to_string()),
+        }),
+        framework: None,
+        thinking_type: None,
+        cold: None,
+        custody: None,
+    }
+}
+
+fn fixed_identity_document() -> IdentityDocument {
+    let mut document = IdentityDocument::new(
+        "work_style".to_string(),
+        serde_json::json!({ "pace": "fast" }),
+        "CC".to_string(),
+    );
+    document.id = FIXED_ID.to_string();
+    document.created_at = chrono::Utc.with_ymd_and_hms(2025, 7, 18, 12, 0, 0).unwrap();
+    document.updated_at = document.created_at;
+    document
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).expect("record serializes")
+}
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread().build().expect("test runtime")
+}
+
+#[test]
+fn snapshot_key_schema() {
+    let schema = [
+        ("thought", keys::thought("CC", "{id}")),
+        ("thought_last_access", keys::thought_last_access("CC", "{id}")),
+        ("chain", keys::chain("CC", "{chain_id}")),
+        ("chain_metadata", keys::chain_metadata(None, "{chain_id}")),
+        ("chain_metadata_scoped", keys::chain_metadata(Some("alice"), "{chain_id}")),
+        ("thought_metadata", keys::thought_metadata("CC", "{id}")),
+        ("tag", keys::tag("CC", "{tag}")),
+        ("citations", keys::citations("CC", "{note_path}")),
+        ("boost_scores", keys::boost_scores("CC")),
+        ("bloom", keys::bloom("CC")),
+        ("thought_count", keys::thought_count("CC")),
+        ("access_count", keys::access_count("CC")),
+        ("identity", keys::identity("CC")),
+        ("identity_document", keys::identity_document("CC", "{field_type}", "{id}")),
+        ("identity_version", keys::identity_version("CC")),
+        ("identity_history", keys::identity_history("CC")),
+        ("crash_reports", keys::crash_reports("CC")),
+        ("annotations", keys::annotations("CC", "{id}")),
+        ("persona_snapshots", keys::persona_snapshots("CC")),
+        ("pii", keys::pii("CC", "{id}")),
+        ("chain_sync", keys::chain_sync("CC", "{chain_id}")),
+        ("client", keys::client("CC", "{name}")),
+        ("capture_seen", keys::capture_seen("CC")),
+        ("embedding", keys::embedding("CC", "{id}")),
+        ("embedding_stale", keys::embedding_stale("CC")),
+        ("events", keys::events("CC")),
+        ("feedback_events", keys::feedback_events("CC")),
+        ("mode", keys::mode("CC")),
+        ("modes", keys::modes("CC")),
+        ("migrations", keys::migrations("CC")),
+        ("migration_lock", keys::migration_lock("CC")),
+        ("bulk_undo", keys::bulk_undo("CC")),
+        ("bulk_undo_claim", keys::bulk_undo_claim("CC", "{undo_id}")),
+        ("recall_tuning", keys::recall_tuning("CC")),
+        ("stream_archive", keys::stream_archive("CC")),
+        ("stream_compaction", keys::stream_compaction("CC")),
+        ("recall_counts", keys::recall_counts("CC")),
+        ("rate_limit", keys::rate_limit("CC", "ui_think")),
+        ("identity_template", keys::identity_template("ops_agent")),
+        ("purge_token", keys::purge_token("CC")),
+        ("search_prefix", search_index::thought_prefix("CC")),
+    ];
+    let rendered: Vec<String> = schema.iter().map(|(name, key)| format!("{} = {}", name, key)).collect();
+    insta::assert_snapshot!(rendered.join("\n"));
+}
+
+#[test]
+fn snapshot_thought_record_json() {
+    insta::assert_json_snapshot!(fixed_thought());
+}
+
+#[test]
+fn snapshot_chain_and_metadata_json() {
+    let chain = ChainMetadata {
+        chain_id: "chain-1".to_string(),
+        created_at: FIXED_TIMESTAMP.to_string(),
+        thought_count: 3,
+        instance: "CC".to_string(),
+        user_id: None,
+        related: Vec::new(),
+        planned_thoughts: None,
+        observed_thoughts: None,
+        last_updated: None,
+        next_hints: None,
+    };
+    let mut metadata = ThoughtMetadata::new(
+        FIXED_ID.to_string(),
+        "CC".to_string(),
+        Some(8),
+        Some(6),
+        Some(vec!["redis".to_string(), "schema".to_string()]),
+        Some("technical".to_string()),
+    );
+    metadata.created_at = FIXED_TIMESTAMP.to_string();
+
+    insta::assert_json_snapshot!("chain_metadata_json", chain);
+    insta::assert_json_snapshot!("thought_metadata_json", metadata);
+}
+
+#[test]
+fn snapshot_identity_json() {
+    insta::assert_json_snapshot!("identity_document_json", fixed_identity_document());
+
+    let mut identity = Identity::default_for_instance("CC");
+    identity.metadata.created_at = chrono::Utc.with_ymd_and_hms(2025, 7, 18, 12, 0, 0).unwrap();
+    identity.metadata.last_updated = identity.metadata.created_at;
+    insta::assert_json_snapshot!("identity_default_json", identity);
+}
+
+#[test]
+fn legacy_thought_json_still_deserializes() {
+    // Written before user_id and provenance existed
+    let legacy = r#"{"id":"t1","instance":"CC","thought":"old","thought_number":1,"total_thoughts":1,
+        "timestamp":"2025-01-01T00:00:00+00:00","chain_id":null,"next_thought_needed":false,"similarity":null}"#;
+    let thought: ThoughtRecord = serde_json::from_str(legacy).unwrap();
+    assert!(thought.user_id.is_none() && thought.provenance.is_none());
+}
+
+fn instance_strategy() -> impl Strategy<Value = String> {
+    "[A-Za-z][A-Za-z0-9_]{0,11}"
+}
+
+fn provenance_strategy() -> impl Strategy<Value = Option<Provenance>> {
+    proptest::option::of((
+        proptest::option::of(prop_oneof![Just("human".to_string()), Just("model".to_string())]),
+        proptest::option::of("[a-z_-]{1,16}"),
+        proptest::option::of("\\PC{0,24}"),
+    ).prop_map(|(author, source_tool, model_used)| Provenance {
+        author,
+        source_tool,
+        model_used,
+        ..Default::default()
+    }))
+}
+
+fn thought_strategy() -> impl Strategy<Value = ThoughtRecord> {
+    (
+        instance_strategy(),
+        any::<u128>(),
+        "\\PC{0,200}",
+        1..50i32,
+        proptest::option::of("[a-z0-9-]{1,36}"),
+        any::<bool>(),
+        provenance_strategy(),
+    ).prop_map(|(instance, id, text, number, chain_id, next, provenance)| ThoughtRecord {
+        id: uuid::Uuid::from_u128(id).to_string(),
+        instance,
+        thought: text,
+        thought_number: number,
+        total_thoughts: number,
+        timestamp: FIXED_TIMESTAMP.to_string(),
+        chain_id,
+        next_thought_needed: next,
+        similarity: None,
+        user_id: None,
+        provenance,
+        framework: None,
+        thinking_type: None,
+        cold: None,
+        custody: None,
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn prop_thought_serde_round_trip(thought in thought_strategy()) {
+        let json = serde_json::to_string(&thought).unwrap();
+        let parsed: ThoughtRecord = serde_json::from_str(&json).unwrap();
+        prop_assert_eq!(to_json(&parsed), to_json(&thought));
+    }
+
+    #[test]
+    fn prop_thought_key_identifies_instance(instance in instance_strategy(), id in any::<u128>()) {
+        let key = keys::thought(&instance, &uuid::Uuid::from_u128(id).to_string());
+        prop_assert!(key.starts_with(&search_index::thought_prefix(&instance)));
+        prop_assert_eq!(search_index::instance_from_key(&key), Some(instance.as_str()));
+        let last_access = keys::thought_last_access(&instance, &uuid::Uuid::from_u128(id).to_string());
+        prop_assert_eq!(search_index::instance_from_key(&last_access), Some(instance.as_str()));
+    }
+
+    #[test]
+    fn prop_thought_repository_round_trip(thought in thought_strategy()) {
+        let repo = MemoryRepository::new(None);
+        runtime().block_on(async {
+            repo.save_thought(&thought).await.unwrap();
+            let stored = repo.get_thought(&thought.instance, &thought.id).await.unwrap();
+            assert_eq!(stored.as_ref().map(to_json), Some(to_json(&thought)));
+
+            // Everything written for the thought is found by a purge of its instance
+            let inventory = repo.purge_inventory(&thought.instance).await.unwrap();
+            assert!(inventory.contains(&keys::thought(&thought.instance, &thought.id)));
+            if let Some(chain_id) = &thought.chain_id {
+                assert!(inventory.contains(&keys::chain(&thought.instance, chain_id)));
+            }
+        });
+    }
+
+    #[test]
+    fn prop_chain_round_trip_preserves_order(
+        instance in instance_strategy(),
+        chain_id in "[a-z0-9-]{1,36}",
+        texts in proptest::collection::vec("\\PC{1,80}", 1..12),
+    ) {
+        let repo = MemoryRepository::new(None);
+        let total = texts.len() as i32;
+        let thoughts: Vec<ThoughtRecord> = texts.into_iter().enumerate()
+            .map(|(i, text)| ThoughtRecord::new(instance.clone(), text, i as i32 + 1, total, Some(chain_id.clone()), i as i32 + 1 < total))
+            .collect();
+
+        runtime().block_on(async {
+            for thought in &thoughts {
+                repo.save_thought(thought).await.unwrap();
+            }
+            repo.save_chain_metadata(&ChainMetadata {
+                chain_id: chain_id.clone(),
+                created_at: FIXED_TIMESTAMP.to_string(),
+                thought_count: total,
+                instance: instance.clone(),
+                user_id: None,
+                related: Vec::new(),
+                planned_thoughts: None,
+                observed_thoughts: None,
+                last_updated: None,
+                next_hints: None,
+            }).await.unwrap();
+
+            let chain = repo.get_chain_thoughts(&instance, &chain_id).await.unwrap();
+            assert_eq!(chain.iter().map(to_json).collect::<Vec<_>>(), thoughts.iter().map(to_json).collect::<Vec<_>>());
+            assert!(repo.chain_exists(&chain_id).await.unwrap());
+        });
+    }
+
+    #[test]
+    fn prop_identity_document_round_trip(
+        instance in instance_strategy(),
+        field_type in "[a-z_]{1,16}",
+        content in "\\PC{0,80}",
+        tags in proptest::collection::vec("[a-z]{1,8}", 0..4),
+    ) {
+        let mut document = IdentityDocument::new(field_type.clone(), serde_json::json!({ "text": content }), instance.clone());
+        document.metadata.tags = tags;
+        prop_assert_eq!(document.redis_key(), keys::identity_document(&instance, &field_type, &document.id));
+
+        let repo = MemoryRepository::new(None);
+        runtime().block_on(async {
+            repo.save_identity_document(&document).await.unwrap();
+            let stored = repo.get_identity_document_by_id(&instance, &document.id).await.unwrap();
+            assert_eq!(stored.as_ref().map(to_json), Some(to_json(&document)));
+        });
+    }
+}
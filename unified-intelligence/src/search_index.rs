@@ -0,0 +1,319 @@
+//! Instance prefix registry for the idx:thoughts search index.
+//!
+//! FT.CREATE fixes the key prefixes an index covers, so thoughts of an instance
+//! that wasn't known when the index was built are silently missing from search.
+//! Every instance registers `{instance}:Thoughts:` in a Redis set on startup;
+//! when the set holds prefixes the index doesn't cover, the index is dropped
+//! (keeping documents) and recreated, and RediSearch re-indexes in the background.
+//!
+//! The index searches three text fields: `content` (the thought), and `tags`
+//! and `category`, which are mirrored onto the thought document when its
+//! metadata is saved. Each field's WEIGHT comes from UI_SEARCH_WEIGHTS, e.g.
+//! `content=1,tags=2,category=0.5`; the index is rebuilt when its weights no
+//! longer match. ui_recall searches every field with a weight above zero
+//! unless `search_fields` restricts the query.
+
+use redis::Value;
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::{ThoughtMetadata, ThoughtRecord};
+
+/// Name of the thought search index
+pub const THOUGHTS_INDEX: &str = "idx:thoughts";
+
+/// Redis set of key prefixes idx:thoughts must cover
+pub const PREFIX_REGISTRY_KEY: &str = "config:search_prefixes";
+
+/// Instances covered before the registry existed
+pub const DEFAULT_INSTANCES: &[&str] = &["Claude", "CC", "CCI"];
+
+const THOUGHTS_SEGMENT: &str = ":Thoughts:";
+
+/// Index prefix for an instance's thoughts
+pub fn thought_prefix(instance: &str) -> String {
+    format!("{}{}", instance, THOUGHTS_SEGMENT)
+}
+
+/// Instance a thought key belongs to, or None for non-thought and user-scoped keys
+pub fn instance_from_key(key: &str) -> Option<&str> {
+    if key.starts_with(crate::tenant::USER_KEY_PREFIX) {
+        return None; // Scoped tenants don't use the shared index
+    }
+    key.split_once(THOUGHTS_SEGMENT)
+        .map(|(instance, _)| instance)
+        .filter(|instance| !instance.is_empty())
+}
+
+/// Prefixes in `wanted` that `indexed` doesn't cover
+pub fn uncovered<'a>(wanted: &'a [String], indexed: &[String]) -> Vec<&'a str> {
+    wanted.iter()
+        .filter(|prefix| !indexed.iter().any(|covered| prefix.starts_with(covered.as_str())))
+        .map(String::as_str)
+        .collect()
+}
+
+/// Extract `index_definition.prefixes` from an FT.INFO reply
+pub fn prefixes_from_info(info: &Value) -> Vec<String> {
+    field(info, "index_definition")
+        .and_then(|definition| field(definition, "prefixes"))
+        .map(|prefixes| match prefixes {
+            Value::Array(items) => items.iter().filter_map(as_string).collect(),
+            other => as_string(other).into_iter().collect(),
+        })
+        .unwrap_or_default()
+}
+
+/// Text fields of the thought index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchField {
+    Content,
+    Tags,
+    Category,
+}
+
+impl SearchField {
+    pub const ALL: [SearchField; 3] = [SearchField::Content, SearchField::Tags, SearchField::Category];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SearchField::Content => "content",
+            SearchField::Tags => "tags",
+            SearchField::Category => "category",
+        }
+    }
+
+    /// JSON path the field is indexed from
+    fn path(&self) -> &'static str {
+        match self {
+            SearchField::Content => "$.thought",
+            SearchField::Tags => "$.tags[*]",
+            SearchField::Category => "$.category",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|field| field.name() == name.trim().to_lowercase())
+    }
+}
+
+/// Parse ui_recall's `search_fields`, dropping duplicates
+pub fn parse_fields(names: &[String]) -> Result<Vec<SearchField>> {
+    let mut fields = Vec::new();
+    for name in names {
+        let field = SearchField::parse(name).ok_or_else(|| UnifiedIntelligenceError::Validation {
+            field: "search_fields".to_string(),
+            reason: format!("Unknown field '{}'. Use any of: content, tags, category", name),
+        })?;
+        if !fields.contains(&field) {
+            fields.push(field);
+        }
+    }
+    Ok(fields)
+}
+
+/// WEIGHT of each text field in the thought index
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldWeights {
+    pub content: f64,
+    pub tags: f64,
+    pub category: f64,
+}
+
+impl Default for FieldWeights {
+    fn default() -> Self {
+        Self { content: 1.0, tags: 1.0, category: 1.0 }
+    }
+}
+
+impl FieldWeights {
+    pub fn from_env() -> Self {
+        std::env::var("UI_SEARCH_WEIGHTS").map(|spec| Self::parse(&spec)).unwrap_or_default()
+    }
+
+    /// Parse `field=weight` pairs; fields left out keep a weight of 1
+    pub fn parse(spec: &str) -> Self {
+        let mut weights = Self::default();
+        for pair in spec.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let parsed = pair.split_once('=').and_then(|(name, weight)| {
+                Some((SearchField::parse(name)?, weight.trim().parse::<f64>().ok().filter(|w| w.is_finite() && *w >= 0.0)?))
+            });
+            match parsed {
+                Some((field, weight)) => *weights.weight_mut(field) = weight,
+                None => tracing::warn!("Ignoring invalid UI_SEARCH_WEIGHTS entry '{}'", pair),
+            }
+        }
+        weights
+    }
+
+    pub fn weight(&self, field: SearchField) -> f64 {
+        match field {
+            SearchField::Content => self.content,
+            SearchField::Tags => self.tags,
+            SearchField::Category => self.category,
+        }
+    }
+
+    fn weight_mut(&mut self, field: SearchField) -> &mut f64 {
+        match field {
+            SearchField::Content => &mut self.content,
+            SearchField::Tags => &mut self.tags,
+            SearchField::Category => &mut self.category,
+        }
+    }
+
+    /// FT.CREATE SCHEMA arguments for the text fields
+    pub fn schema_args(&self) -> Vec<String> {
+        SearchField::ALL.iter()
+            .flat_map(|field| [
+                field.path().to_string(), "AS".to_string(), field.name().to_string(),
+                "TEXT".to_string(), "WEIGHT".to_string(), self.weight(*field).to_string(),
+            ])
+            .collect()
+    }
+
+    /// Fields searched when a query isn't restricted: those weighted above zero, or content
+    pub fn default_fields(&self) -> Vec<SearchField> {
+        let fields: Vec<SearchField> = SearchField::ALL.into_iter().filter(|field| self.weight(*field) > 0.0).collect();
+        if fields.is_empty() { vec![SearchField::Content] } else { fields }
+    }
+}
+
+/// Fields a query searches: the requested ones, or the weighted defaults
+pub fn resolve_fields(fields: &[SearchField]) -> Vec<SearchField> {
+    if fields.is_empty() {
+        FieldWeights::from_env().default_fields()
+    } else {
+        fields.to_vec()
+    }
+}
+
+/// RediSearch clause matching `query` in any of `fields`
+pub fn field_query(query: &str, fields: &[SearchField]) -> String {
+    let names: Vec<&str> = fields.iter().map(SearchField::name).collect();
+    format!("(@{}:({}))", names.join("|"), query)
+}
+
+/// Scan-based equivalent of `field_query` for backends without the index
+pub fn matches(thought: &ThoughtRecord, metadata: Option<&ThoughtMetadata>, query: &str, fields: &[SearchField]) -> bool {
+    let query = query.to_lowercase();
+    let contains = |text: &str| text.to_lowercase().contains(&query);
+    fields.iter().any(|field| match field {
+        SearchField::Content => contains(&thought.thought),
+        SearchField::Tags => metadata.and_then(|m| m.tags.as_ref()).is_some_and(|tags| tags.iter().any(|tag| contains(tag))),
+        SearchField::Category => metadata.and_then(|m| m.category.as_deref()).is_some_and(contains),
+    })
+}
+
+/// Whether an FT.INFO reply indexes every text field at its configured weight
+pub fn schema_current(info: &Value, weights: &FieldWeights) -> bool {
+    let attributes = match field(info, "attributes") {
+        Some(Value::Array(attributes)) => attributes,
+        _ => return false,
+    };
+    SearchField::ALL.iter().all(|wanted| {
+        attributes.iter().any(|attribute| {
+            field(attribute, "attribute").and_then(as_string).as_deref() == Some(wanted.name())
+                && field(attribute, "WEIGHT").and_then(as_f64).unwrap_or(1.0) == weights.weight(*wanted)
+        })
+    })
+}
+
+fn as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Double(d) => Some(*d),
+        Value::Int(i) => Some(*i as f64),
+        other => as_string(other).and_then(|s| s.parse().ok()),
+    }
+}
+
+/// Look up a field in a RESP2 flat key/value array or a RESP3 map
+pub(crate) fn field<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    match value {
+        Value::Array(items) => items.chunks(2)
+            .find(|pair| pair.len() == 2 && as_string(&pair[0]).as_deref() == Some(name))
+            .map(|pair| &pair[1]),
+        Value::Map(entries) => entries.iter()
+            .find(|(key, _)| as_string(key).as_deref() == Some(name))
+            .map(|(_, value)| value),
+        _ => None,
+    }
+}
+
+pub(crate) fn as_string(value: &Value) -> Option<String> {
+    match value {
+        Value::BulkString(bytes) => Some(String::from_utf8_lossy(bytes).into_owned()),
+        Value::SimpleString(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bulk(s: &str) -> Value {
+        Value::BulkString(s.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_instance_from_key() {
+        assert_eq!(instance_from_key("DT:Thoughts:abc"), Some("DT"));
+        assert_eq!(instance_from_key("DT:Thoughts:abc:last_access"), Some("DT"));
+        assert_eq!(instance_from_key("users:alice:CC:Thoughts:abc"), None);
+        assert_eq!(instance_from_key("CC:chains:c1"), None);
+    }
+
+    #[test]
+    fn test_uncovered() {
+        let wanted = vec![thought_prefix("CC"), thought_prefix("DT")];
+        let indexed = vec![thought_prefix("CC"), thought_prefix("Claude")];
+        assert_eq!(uncovered(&wanted, &indexed), vec!["DT:Thoughts:"]);
+    }
+
+    #[test]
+    fn test_prefixes_from_info() {
+        let info = Value::Array(vec![
+            bulk("index_name"), bulk(THOUGHTS_INDEX),
+            bulk("index_definition"), Value::Array(vec![
+                bulk("key_type"), bulk("JSON"),
+                bulk("prefixes"), Value::Array(vec![bulk("CC:Thoughts:"), bulk("CCI:Thoughts:")]),
+            ]),
+        ]);
+        assert_eq!(prefixes_from_info(&info), vec!["CC:Thoughts:", "CCI:Thoughts:"]);
+        assert!(prefixes_from_info(&Value::Nil).is_empty());
+    }
+
+    #[test]
+    fn test_field_weights() {
+        let weights = FieldWeights::parse("tags=2, category=0, bogus=3, content=x");
+        assert_eq!(weights, FieldWeights { content: 1.0, tags: 2.0, category: 0.0 });
+        assert_eq!(weights.default_fields(), vec![SearchField::Content, SearchField::Tags]);
+        assert_eq!(&weights.schema_args()[6..], ["$.tags[*]", "AS", "tags", "TEXT", "WEIGHT", "2", "$.category", "AS", "category", "TEXT", "WEIGHT", "0"]);
+        assert_eq!(field_query("redis ports", &[SearchField::Tags, SearchField::Category]), "(@tags|category:(redis ports))");
+        assert_eq!(parse_fields(&["Tags".to_string(), "tags".to_string()]).unwrap(), vec![SearchField::Tags]);
+        assert!(parse_fields(&["title".to_string()]).is_err());
+
+        let attribute = |name: &str, weight: &str| Value::Array(vec![
+            bulk("identifier"), bulk("$.x"), bulk("attribute"), bulk(name), bulk("type"), bulk("TEXT"), bulk("WEIGHT"), bulk(weight),
+        ]);
+        let info = Value::Array(vec![
+            bulk("attributes"), Value::Array(vec![attribute("content", "1"), attribute("tags", "2"), attribute("category", "0")]),
+        ]);
+        assert!(schema_current(&info, &weights));
+        assert!(!schema_current(&info, &FieldWeights::default()));
+        let legacy = Value::Array(vec![bulk("attributes"), Value::Array(vec![attribute("content", "1")])]);
+        assert!(!schema_current(&legacy, &FieldWeights::default()));
+    }
+
+    #[test]
+    fn test_matches_fields() {
+        let thought = ThoughtRecord::new("CC".to_string(), "Tuned the eviction policy".to_string(), 1, 1, None, false);
+        let mut metadata = ThoughtMetadata::new(thought.id.clone(), "CC".to_string(), None, None, Some(vec!["redis".to_string()]), Some("ops".to_string()));
+        assert!(matches(&thought, Some(&metadata), "REDIS", &[SearchField::Tags]));
+        assert!(!matches(&thought, Some(&metadata), "redis", &[SearchField::Content]));
+        assert!(matches(&thought, Some(&metadata), "eviction", &SearchField::ALL));
+        metadata.tags = None;
+        assert!(!matches(&thought, Some(&metadata), "redis", &[SearchField::Tags]));
+        assert!(!matches(&thought, None, "ops", &[SearchField::Category]));
+    }
+}
@@ -1,7 +1,12 @@
-use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use anyhow::Result;
+use moka::future::Cache;
+use moka::notification::RemovalCause;
+use moka::Expiry;
 use serde_json::Value;
-use crate::models::ThoughtRecord;
+use crate::models::{SearchCacheStats, ThoughtRecord};
 use rmcp::model::ErrorData;
 use tracing;
 
@@ -172,35 +177,169 @@ impl OptimizedSearch {
     }
 }
 
+/// Weight of the feedback boost score when added to semantic similarity
+pub const BOOST_WEIGHT: f32 = 0.1;
+
+/// Boost score change for one piece of recall feedback
+pub fn boost_increment(feedback_action: &str, relevance_rating: Option<i32>, dwell_time: Option<i32>) -> f64 {
+    let base_increment = match feedback_action {
+        "helpful" => 2.0,
+        "used" => 1.5,
+        "viewed" => {
+            // Award viewing boost based on dwell time
+            if let Some(dwell) = dwell_time {
+                if dwell >= 30 { 0.5 } else if dwell >= 15 { 0.3 } else { 0.1 }
+            } else { 0.1 }
+        },
+        "irrelevant" => -1.0,
+        _ => 0.0,
+    };
+    
+    // Apply relevance rating multiplier if provided (1-10 scales to 0.1-1.0)
+    match relevance_rating {
+        Some(rating) => base_increment * (rating as f64 / 10.0),
+        None => base_increment,
+    }
+}
+
+/// Default search cache TTL
+pub const DEFAULT_CACHE_TTL_SECS: u64 = 300;
+
+/// Default search cache size limit, in approximate bytes of cached thoughts
+pub const DEFAULT_CACHE_MAX_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Approximate per-thought overhead beyond its text, for cache weighing
+const THOUGHT_OVERHEAD_BYTES: usize = 256;
+
+#[derive(Clone)]
+struct CachedResults {
+    thoughts: Arc<Vec<ThoughtRecord>>,
+    ttl: Duration,
+}
+
+/// Expires each entry after the TTL it was inserted with
+struct PerEntryTtl;
+
+impl Expiry<String, CachedResults> for PerEntryTtl {
+    fn expire_after_create(&self, _key: &String, value: &CachedResults, _created_at: Instant) -> Option<Duration> {
+        Some(value.ttl)
+    }
+
+    fn expire_after_update(&self, _key: &String, value: &CachedResults, _updated_at: Instant, _remaining: Option<Duration>) -> Option<Duration> {
+        Some(value.ttl)
+    }
+}
+
+#[derive(Default)]
+struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    expirations: AtomicU64,
+    clears: AtomicU64,
+}
+
 /// Cache for recent search results
+///
+/// Lookups never block the async runtime. Each entry expires after its own
+/// TTL, and the least recently used entries are evicted once the cached
+/// results exceed the size limit. Clones share entries and metrics, so the
+/// repository, the handlers and the invalidation listener all see one cache.
+#[derive(Clone)]
 pub struct SearchCache {
-    cache: HashMap<String, (Vec<ThoughtRecord>, std::time::Instant)>,
-    ttl: std::time::Duration,
+    cache: Cache<String, CachedResults>,
+    ttl: Duration,
+    max_bytes: u64,
+    counters: Arc<CacheCounters>,
 }
 
 impl SearchCache {
     pub fn new(ttl_seconds: u64) -> Self {
+        Self::with_max_bytes(ttl_seconds, DEFAULT_CACHE_MAX_BYTES)
+    }
+
+    pub fn with_max_bytes(ttl_seconds: u64, max_bytes: u64) -> Self {
+        let counters = Arc::new(CacheCounters::default());
+        let listener_counters = counters.clone();
+        let cache = Cache::builder()
+            .max_capacity(max_bytes)
+            .weigher(|key: &String, value: &CachedResults| -> u32 {
+                let bytes = key.len() + value.thoughts.iter()
+                    .map(|thought| thought.thought.len() + THOUGHT_OVERHEAD_BYTES)
+                    .sum::<usize>();
+                bytes.try_into().unwrap_or(u32::MAX)
+            })
+            .expire_after(PerEntryTtl)
+            .eviction_listener(move |_key, _value, cause| match cause {
+                RemovalCause::Size => { listener_counters.evictions.fetch_add(1, Ordering::Relaxed); }
+                RemovalCause::Expired => { listener_counters.expirations.fetch_add(1, Ordering::Relaxed); }
+                RemovalCause::Explicit | RemovalCause::Replaced => {}
+            })
+            .build();
         Self {
-            cache: HashMap::new(),
-            ttl: std::time::Duration::from_secs(ttl_seconds),
+            cache,
+            ttl: Duration::from_secs(ttl_seconds),
+            max_bytes,
+            counters,
         }
     }
-    
-    pub fn get(&self, key: &str) -> Option<&Vec<ThoughtRecord>> {
-        self.cache.get(key).and_then(|(results, timestamp)| {
-            if timestamp.elapsed() < self.ttl {
-                Some(results)
-            } else {
+
+    /// Cache sized from UI_SEARCH_CACHE_TTL_SECS (default 300) and UI_SEARCH_CACHE_MAX_BYTES (default 64 MiB)
+    pub fn from_env() -> Self {
+        let env = |name: &str, default: u64| std::env::var(name).ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(default);
+        Self::with_max_bytes(
+            env("UI_SEARCH_CACHE_TTL_SECS", DEFAULT_CACHE_TTL_SECS),
+            env("UI_SEARCH_CACHE_MAX_BYTES", DEFAULT_CACHE_MAX_BYTES),
+        )
+    }
+
+    pub async fn get(&self, key: &str) -> Option<Vec<ThoughtRecord>> {
+        match self.cache.get(key).await {
+            Some(entry) => {
+                self.counters.hits.fetch_add(1, Ordering::Relaxed);
+                Some(entry.thoughts.as_ref().clone())
+            }
+            None => {
+                self.counters.misses.fetch_add(1, Ordering::Relaxed);
                 None
             }
-        })
+        }
     }
-    
-    pub fn insert(&mut self, key: String, results: Vec<ThoughtRecord>) {
-        self.cache.insert(key, (results, std::time::Instant::now()));
-        
-        // Simple cache eviction - remove expired entries
-        self.cache.retain(|_, (_, timestamp)| timestamp.elapsed() < self.ttl);
+
+    pub async fn insert(&self, key: String, results: Vec<ThoughtRecord>) {
+        self.insert_with_ttl(key, results, self.ttl).await;
+    }
+
+    /// Insert with a TTL other than the cache default
+    pub async fn insert_with_ttl(&self, key: String, results: Vec<ThoughtRecord>, ttl: Duration) {
+        self.cache.insert(key, CachedResults { thoughts: Arc::new(results), ttl }).await;
+    }
+
+    pub fn clear(&self) {
+        self.cache.invalidate_all();
+        self.counters.clears.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Current size and hit/eviction counters
+    pub async fn stats(&self) -> SearchCacheStats {
+        // Apply pending inserts, evictions and expirations so the counts are current
+        self.cache.run_pending_tasks().await;
+        let hits = self.counters.hits.load(Ordering::Relaxed);
+        let misses = self.counters.misses.load(Ordering::Relaxed);
+        SearchCacheStats {
+            entries: self.cache.entry_count(),
+            size_bytes: self.cache.weighted_size(),
+            max_bytes: self.max_bytes,
+            ttl_seconds: self.ttl.as_secs(),
+            hits,
+            misses,
+            hit_rate: if hits + misses > 0 { hits as f64 / (hits + misses) as f64 } else { 0.0 },
+            evictions: self.counters.evictions.load(Ordering::Relaxed),
+            expirations: self.counters.expirations.load(Ordering::Relaxed),
+            clears: self.counters.clears.load(Ordering::Relaxed),
+        }
     }
 }
 
@@ -208,25 +347,58 @@ impl SearchCache {
 mod tests {
     use super::*;
     
-    #[test]
-    fn test_search_cache() {
-        let mut cache = SearchCache::new(60);
-        let thoughts = vec![
-            ThoughtRecord {
-                id: "test-1".to_string(),
-                instance: "test".to_string(),
-                thought: "Test thought".to_string(),
-                thought_number: 1,
-                total_thoughts: 1,
-                timestamp: "2025-01-01T00:00:00Z".to_string(),
-                chain_id: None,
-                next_thought_needed: false,
-                similarity: None,
-            }
-        ];
+    fn thought(id: &str, text: &str) -> ThoughtRecord {
+        ThoughtRecord {
+            id: id.to_string(),
+            instance: "test".to_string(),
+            thought: text.to_string(),
+            thought_number: 1,
+            total_thoughts: 1,
+            timestamp: "2025-01-01T00:00:00Z".to_string(),
+            chain_id: None,
+            next_thought_needed: false,
+            similarity: None,
+            user_id: None,
+            provenance: None,
+            framework: None,
+            thinking_type: None,
+            cold: None,
+            custody: None,
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_search_cache() {
+        let cache = SearchCache::new(60);
+        cache.insert("test-query".to_string(), vec![thought("test-1", "Test thought")]).await;
+        assert_eq!(cache.get("test-query").await.unwrap().len(), 1);
+        assert!(cache.get("other-query").await.is_none());
+        
+        // Clones share entries and metrics
+        let shared = cache.clone();
+        assert!(shared.get("test-query").await.is_some());
+        shared.clear();
+        assert!(cache.get("test-query").await.is_none());
         
-        cache.insert("test-query".to_string(), thoughts.clone());
-        assert!(cache.get("test-query").is_some());
-        assert_eq!(cache.get("test-query").unwrap().len(), 1);
+        let stats = cache.stats().await;
+        assert_eq!((stats.hits, stats.misses, stats.clears), (2, 2, 1));
+        assert_eq!(stats.hit_rate, 0.5);
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_search_cache_ttl_and_size_eviction() {
+        let cache = SearchCache::with_max_bytes(60, 4096);
+        cache.insert_with_ttl("short".to_string(), vec![thought("t1", "expires soon")], Duration::from_millis(50)).await;
+        cache.insert("long".to_string(), vec![thought("t2", "kept")]).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(cache.get("short").await.is_none());
+        assert!(cache.get("long").await.is_some());
+        
+        for i in 0..20 {
+            cache.insert(format!("query-{}", i), vec![thought("t3", &"x".repeat(512))]).await;
+        }
+        let stats = cache.stats().await;
+        assert!(stats.size_bytes <= 4096, "size {} over limit", stats.size_bytes);
+        assert!(stats.evictions > 0);
+    }
+}
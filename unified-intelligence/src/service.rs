@@ -1,38 +1,265 @@
 use std::sync::Arc;
 use std::future::Future;
 use rmcp::{
-    handler::server::{router::tool::ToolRouter, tool::Parameters},
-    model::{CallToolResult, Content, ErrorData, ServerCapabilities, ServerInfo},
-    ServerHandler,
+    handler::server::{router::tool::ToolRouter, tool::{Parameters, ToolCallContext}},
+    model::{
+        CallToolRequestParam, CallToolResult, Content, ErrorData, InitializeRequestParam, InitializeResult,
+        ListToolsResult, Meta, PaginatedRequestParam, ProgressNotificationParam, ServerCapabilities, ServerInfo,
+        SetLevelRequestParam,
+    },
+    service::RequestContext,
+    Peer, RoleServer, ServerHandler,
 };
-use rmcp_macros::{tool, tool_handler, tool_router};
+use rmcp_macros::{tool, tool_router};
 use tracing;
 
 use crate::error::UnifiedIntelligenceError;
-use crate::models::{UiThinkParams, UiRecallParams, UiRecallFeedbackParams, UiIdentityParams, UiDebugEnvParams};
+use crate::models::{UiThinkParams, UiRecallParams, UiRecallFeedbackParams, UiIdentityParams, UiDiagnosticsParams, UiPurgeParams, UiPiiFindingsParams, UiChainSyncParams, UiSearchIndexParams, UiClientsParams, UiBraindumpParams, UiVoiceMemoParams, UiCaptureParams, UiImportBookmarksParams, UiWeeklyReviewParams, UiListChainsParams, UiEmbeddingStalenessParams, UiExportTrainingParams, UiPersonaSnapshotParams, UiPersonaDiffParams, UiAnnotateParams, UiTierColdParams, UiReplayParams, UiSubscribeParams, SubscribeResponse, UiChainStatsParams, UiCitationsParams, UiReportParams, UiModeParams, UiVerifyChainParams, UiMigrationsParams, UiDeleteThoughtParams, UiDeleteChainParams, UiExportChainParams, UiImportChainParams, UiFeedParams, UiBulkUpdateParams, UiRecallTuningParams, UiNextHintParams, UiCompactStreamsParams, UiScoreImportanceParams, UiExportToVaultParams, UiRateLimitStatusParams, RateLimitStatusResponse, UiChainDiffParams};
 use crate::redis::RedisManager;
-use crate::repository::RedisRepository;
+use crate::cache_invalidation;
+use crate::search_index;
+use crate::repository::{self, MemoryRepository, RedisRepository, Repository, SqliteRepository, StorageBackend};
 use crate::handlers::ToolHandlers;
 use crate::search_optimization::SearchCache;
 use crate::validation::InputValidator;
-use crate::rate_limit::RateLimiter;
+use crate::rate_limit::{RateLimitConfig, RateLimiter};
+use crate::tenant;
+use crate::capture;
+use crate::chain_linker;
+use crate::tiering;
+use crate::memory_guard;
+use crate::stream_compaction;
+use crate::importance;
+use crate::replay;
+use crate::notification_bridge::NotificationBridge;
+use crate::crash_report::CrashReporter;
+use crate::timeouts::{self, ToolBudgets};
 
 /// Main service struct for UnifiedIntelligence MCP server
 #[derive(Clone)]
 pub struct UnifiedIntelligenceService {
     tool_router: ToolRouter<Self>,
-    handlers: Arc<ToolHandlers<RedisRepository>>,
+    handlers: Arc<ToolHandlers<dyn Repository>>,
     rate_limiter: Arc<RateLimiter>,
     instance_id: String,
+    crash_reporter: Arc<CrashReporter>,
+    tool_budgets: Arc<ToolBudgets>,
+    bridge: Arc<NotificationBridge>,
 }
 
 impl UnifiedIntelligenceService {
     /// Create a new service instance
     pub async fn new() -> Result<Self, UnifiedIntelligenceError> {
-        // Get instance ID from environment
-        let instance_id = std::env::var("INSTANCE_ID").unwrap_or_else(|_| "test".to_string());
-        tracing::info!("Initializing UnifiedIntelligence service for instance: {}", instance_id);
+        // Get instance ID (and optional user ID for multi-tenancy) from environment
+        let user_id = tenant::user_id_from_env()?;
+        let instance_id = tenant::scoped_instance(
+            user_id.as_deref(),
+            &std::env::var("INSTANCE_ID").unwrap_or_else(|_| "test".to_string()),
+        );
+        tracing::info!("Initializing UnifiedIntelligence service for instance: {} (user: {:?})", instance_id, user_id);
         
+        // Create search cache (5 minute TTL unless configured), shared by the repository and handlers
+        let search_cache = SearchCache::from_env();
+        let search_available = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let bridge = Arc::new(NotificationBridge::from_env());
+        
+        // Create rate limiter (100 requests per minute unless configured - reasonable for AI instances)
+        let rate_limiter = Arc::new(RateLimiter::with_config(RateLimitConfig::from_env()));
+        
+        let repository: Arc<dyn Repository> = match StorageBackend::from_env() {
+            StorageBackend::Redis => match Self::redis_repository(&instance_id, user_id.clone(), &search_available, &search_cache, Some(&bridge), Some(&rate_limiter)).await {
+                Ok(repository) => repository,
+                Err(e) if repository::sqlite_fallback() => {
+                    tracing::error!("Redis is unavailable ({}); falling back to SQLite at {}", e, repository::sqlite_path().display());
+                    Arc::new(SqliteRepository::open(&repository::sqlite_path(), user_id.clone())?)
+                }
+                Err(e) => return Err(e),
+            },
+            StorageBackend::Sqlite => {
+                tracing::info!("Using SQLite storage backend at {}", repository::sqlite_path().display());
+                Arc::new(SqliteRepository::open(&repository::sqlite_path(), user_id.clone())?)
+            }
+            StorageBackend::Memory => {
+                tracing::warn!("Using in-memory storage backend: nothing is persisted and recall uses substring search");
+                Arc::new(MemoryRepository::new(user_id.clone()))
+            }
+        };
+        
+        // Create validator
+        let validator = Arc::new(InputValidator::new());
+        
+        // Create handlers
+        let handlers = Arc::new(ToolHandlers::new(
+            repository.clone(),
+            instance_id.clone(),
+            user_id,
+            validator,
+            search_cache,
+            search_available,
+        )?);
+        
+        // Bring stored records up to date before background tasks read them
+        if let Err(e) = handlers.run_migrations().await {
+            tracing::error!("Migrations for {} failed, continuing with the records as stored: {}", instance_id, e);
+        }
+        
+        if let Some(interval) = capture::poll_interval() {
+            Self::start_capture_polling(handlers.clone(), interval);
+        }
+        if let Some(interval) = chain_linker::link_interval() {
+            Self::start_chain_linking(handlers.clone(), interval);
+        }
+        if let Some(interval) = tiering::tier_interval() {
+            Self::start_cold_tiering(handlers.clone(), interval);
+        }
+        if let Some(interval) = memory_guard::guard_interval() {
+            Self::start_memory_guard(handlers.clone(), interval);
+        }
+        if let Some(interval) = stream_compaction::compact_interval() {
+            Self::start_stream_compaction(handlers.clone(), interval);
+        }
+        if let Some(interval) = importance::score_interval() {
+            Self::start_importance_scoring(handlers.clone(), interval);
+        }
+        
+        let crash_reporter = CrashReporter::start(repository, instance_id.clone(), handlers.diagnostics().clone());
+        
+        Ok(Self {
+            tool_router: Self::tool_router(),
+            handlers,
+            rate_limiter,
+            instance_id,
+            crash_reporter,
+            tool_budgets: Arc::new(ToolBudgets::from_env()),
+            bridge,
+        })
+    }
+    
+    /// Reporter for panics and fatal errors of this instance
+    pub fn crash_reporter(&self) -> Arc<CrashReporter> {
+        self.crash_reporter.clone()
+    }
+    
+    /// Poll the capture sources in the background (UI_CAPTURE_INTERVAL_SECS)
+    fn start_capture_polling(handlers: Arc<ToolHandlers<dyn Repository>>, interval: std::time::Duration) {
+        tracing::info!("Polling capture sources every {}s", interval.as_secs());
+        handlers.diagnostics().task_started("capture_polling", interval);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let error = match handlers.ui_capture(UiCaptureParams::default()).await {
+                    Ok(response) => {
+                        for error in &response.errors {
+                            tracing::warn!("Capture error: {}", error);
+                        }
+                        (!response.errors.is_empty()).then(|| response.errors.join("; "))
+                    }
+                    Err(e) => {
+                        tracing::warn!("Capture poll failed: {}", e);
+                        Some(e.to_string())
+                    }
+                };
+                handlers.diagnostics().task_finished("capture_polling", error);
+            }
+        });
+    }
+    
+    /// Link related chains in the background (UI_CHAIN_LINK_INTERVAL_SECS)
+    fn start_chain_linking(handlers: Arc<ToolHandlers<dyn Repository>>, interval: std::time::Duration) {
+        tracing::info!("Linking related chains every {}s", interval.as_secs());
+        handlers.diagnostics().task_started("chain_linking", interval);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let result = handlers.link_related_chains().await;
+                if let Err(e) = &result {
+                    tracing::warn!("Chain linking failed: {}", e);
+                }
+                handlers.diagnostics().task_finished("chain_linking", result.err().map(|e| e.to_string()));
+            }
+        });
+    }
+    
+    /// Move cold thoughts to segment files in the background (UI_TIER_INTERVAL_SECS)
+    fn start_cold_tiering(handlers: Arc<ToolHandlers<dyn Repository>>, interval: std::time::Duration) {
+        tracing::info!("Tiering cold thoughts every {}s", interval.as_secs());
+        handlers.diagnostics().task_started("cold_tiering", interval);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let result = handlers.ui_tier_cold(UiTierColdParams::default()).await;
+                if let Err(e) = &result {
+                    tracing::warn!("Cold tiering failed: {}", e);
+                }
+                handlers.diagnostics().task_finished("cold_tiering", result.err().map(|e| e.to_string()));
+            }
+        });
+    }
+    
+    /// Watch Redis memory and shed load under pressure (UI_MEMORY_GUARD_INTERVAL_SECS)
+    fn start_memory_guard(handlers: Arc<ToolHandlers<dyn Repository>>, interval: std::time::Duration) {
+        tracing::info!("Checking Redis memory pressure every {}s", interval.as_secs());
+        handlers.diagnostics().task_started("memory_guard", interval);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let result = handlers.check_memory_pressure().await;
+                if let Err(e) = &result {
+                    tracing::warn!("Memory pressure check failed: {}", e);
+                }
+                handlers.diagnostics().task_finished("memory_guard", result.err().map(|e| e.to_string()));
+            }
+        });
+    }
+    
+    /// Trim the event streams in the background (UI_STREAM_COMPACT_INTERVAL_SECS)
+    fn start_stream_compaction(handlers: Arc<ToolHandlers<dyn Repository>>, interval: std::time::Duration) {
+        tracing::info!("Compacting event streams every {}s", interval.as_secs());
+        handlers.diagnostics().task_started("stream_compaction", interval);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let result = handlers.ui_compact_streams(UiCompactStreamsParams::default()).await;
+                if let Err(e) = &result {
+                    tracing::warn!("Stream compaction failed: {}", e);
+                }
+                handlers.diagnostics().task_finished("stream_compaction", result.err().map(|e| e.to_string()));
+            }
+        });
+    }
+    
+    /// Compute importance from usage in the background (UI_IMPORTANCE_INTERVAL_SECS)
+    fn start_importance_scoring(handlers: Arc<ToolHandlers<dyn Repository>>, interval: std::time::Duration) {
+        tracing::info!("Scoring thought importance every {}s", interval.as_secs());
+        handlers.diagnostics().task_started("importance_scoring", interval);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let result = handlers.ui_score_importance(UiScoreImportanceParams::default()).await;
+                if let Err(e) = &result {
+                    tracing::warn!("Importance scoring failed: {}", e);
+                }
+                handlers.diagnostics().task_finished("importance_scoring", result.err().map(|e| e.to_string()));
+            }
+        });
+    }
+    
+    /// Connect to Redis and prepare streams, vector set and search index for the instance
+    /// (and start the pub/sub notification bridge and share rate limits when given)
+    pub async fn redis_repository(
+        instance_id: &str,
+        user_id: Option<String>,
+        search_available: &Arc<std::sync::atomic::AtomicBool>,
+        search_cache: &SearchCache,
+        bridge: Option<&Arc<NotificationBridge>>,
+        rate_limiter: Option<&Arc<RateLimiter>>,
+    ) -> Result<Arc<dyn Repository>, UnifiedIntelligenceError> {
         // Initialize Redis
         let redis_manager = Arc::new(RedisManager::new().await?);
         
@@ -50,48 +277,41 @@ impl UnifiedIntelligenceService {
         // redis_manager.init_bloom_filter(&instance_id).await?;
         
         // Initialize event stream for this instance
-        redis_manager.init_event_stream(&instance_id).await?;
+        redis_manager.init_event_stream(instance_id).await?;
         
         // Initialize vector set for semantic search
-        redis_manager.init_vector_set(&instance_id).await?;
+        redis_manager.init_vector_set(instance_id).await?;
+        
+        // Make sure idx:thoughts covers this instance (user-scoped tenants scan instead)
+        if user_id.is_none() && redis_manager.add_search_prefix(&search_index::thought_prefix(instance_id)).await? {
+            tracing::info!("Registered search prefix for new instance {}", instance_id);
+        }
         
         // Check for search capability
-        let search_available = Arc::new(std::sync::atomic::AtomicBool::new(false));
         let search_enabled = redis_manager.create_search_index().await?;
         search_available.store(search_enabled, std::sync::atomic::Ordering::SeqCst);
         
-        // Create search cache (5 minute TTL)
-        let search_cache = Arc::new(std::sync::Mutex::new(SearchCache::new(300)));
+        // Clear the search cache when thoughts change behind our back
+        cache_invalidation::start(redis_manager.clone(), search_cache.clone()).await;
+        
+        // Forward pub/sub channels to subscribed MCP clients
+        if let Some(bridge) = bridge {
+            bridge.start(redis_manager.clone());
+        }
+        
+        // Count tool calls in Redis so every process of the instance shares one budget
+        if let Some(rate_limiter) = rate_limiter {
+            rate_limiter.share_through(redis_manager.clone());
+        }
         
         // Create repository with cache
-        let repository = Arc::new(RedisRepository::new(
-            redis_manager.clone(),
+        Ok(Arc::new(RedisRepository::new(
+            redis_manager,
             search_available.clone(),
             search_cache.clone(),
-            instance_id.clone(),
-        ));
-        
-        // Create validator
-        let validator = Arc::new(InputValidator::new());
-        
-        // Create rate limiter (100 requests per minute - reasonable for AI instances)
-        let rate_limiter = Arc::new(RateLimiter::new(100, 60));
-        
-        // Create handlers
-        let handlers = Arc::new(ToolHandlers::new(
-            repository,
-            instance_id.clone(),
-            validator,
-            search_cache,
-            search_available,
-        ));
-        
-        Ok(Self {
-            tool_router: Self::tool_router(),
-            handlers,
-            rate_limiter,
-            instance_id,
-        })
+            instance_id.to_string(),
+            user_id,
+        )))
     }
 }
 
@@ -103,7 +323,7 @@ impl UnifiedIntelligenceService {
         params: Parameters<UiThinkParams>,
     ) -> std::result::Result<CallToolResult, ErrorData> {
         // Check rate limit
-        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id).await {
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_think").await {
             tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
             return Err(ErrorData::invalid_params(
                 format!("Rate limit exceeded. Please slow down your requests."), 
@@ -130,7 +350,7 @@ impl UnifiedIntelligenceService {
         params: Parameters<UiRecallParams>,
     ) -> std::result::Result<CallToolResult, ErrorData> {
         // Check rate limit
-        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id).await {
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_recall").await {
             tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
             return Err(ErrorData::invalid_params(
                 format!("Rate limit exceeded. Please slow down your requests."), 
@@ -157,7 +377,7 @@ impl UnifiedIntelligenceService {
         params: Parameters<UiRecallFeedbackParams>,
     ) -> std::result::Result<CallToolResult, ErrorData> {
         // Check rate limit
-        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id).await {
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_recall_feedback").await {
             tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
             return Err(ErrorData::invalid_params(
                 format!("Rate limit exceeded. Please slow down your requests."), 
@@ -184,7 +404,7 @@ impl UnifiedIntelligenceService {
         params: Parameters<UiIdentityParams>,
     ) -> std::result::Result<CallToolResult, ErrorData> {
         // Check rate limit
-        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id).await {
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_identity").await {
             tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
             return Err(ErrorData::invalid_params(
                 format!("Rate limit exceeded. Please slow down your requests."), 
@@ -205,39 +425,1224 @@ impl UnifiedIntelligenceService {
         }
     }
     
-    #[tool(description = "Debug tool to view masked environment variables (OPENAI_API_KEY, REDIS_PASSWORD, INSTANCE_ID)")]
-    pub async fn ui_debug_env(
+    #[tool(description = "Delete all data for this instance or user (thoughts, chains, embeddings, identity, feedback, logs). Run with dry_run=true first to get an inventory and confirmation token")]
+    pub async fn ui_purge(
         &self,
-        params: Parameters<UiDebugEnvParams>,
+        params: Parameters<UiPurgeParams>,
     ) -> std::result::Result<CallToolResult, ErrorData> {
-        match self.handlers.ui_debug_env(params.0).await {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_purge").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_purge(params.0).await {
             Ok(response) => {
                 let content = Content::json(response)
                     .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
                 Ok(CallToolResult::success(vec![content]))
             },
             Err(e) => {
-                tracing::error!("ui_debug_env error: {}", e);
+                tracing::error!("ui_purge error: {}", e);
                 Err(ErrorData::internal_error(e.to_string(), None))
             }
         }
     }
-}
-
-#[tool_handler]
-impl ServerHandler for UnifiedIntelligenceService {
-    fn get_info(&self) -> ServerInfo {
-        ServerInfo {
-            protocol_version: rmcp::model::ProtocolVersion::V_2024_11_05,
-            server_info: rmcp::model::Implementation {
-                name: "unified-intelligence".into(),
-                version: "3.0.0".into(),
+    
+    #[tool(description = "List PII (emails, phone numbers, API keys, card numbers) detected in this instance's thoughts")]
+    pub async fn ui_pii_findings(
+        &self,
+        params: Parameters<UiPiiFindingsParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        match self.handlers.ui_pii_findings(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
             },
-            capabilities: ServerCapabilities {
-                tools: Some(Default::default()),
-                ..Default::default()
+            Err(e) => {
+                tracing::error!("ui_pii_findings error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Sync a thought chain with its Obsidian note (OBSIDIAN_VAULT_PATH). Exports the chain as a note and imports human annotations made in the note back as thoughts tagged 'human-annotation'. privacy_level redacts the note and writes a redaction manifest next to it")]
+    pub async fn ui_chain_sync(
+        &self,
+        params: Parameters<UiChainSyncParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_chain_sync").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_chain_sync(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
             },
-            instructions: Some("UnifiedIntelligence MCP Server for Redis-backed thought storage".into()),
+            Err(e) => {
+                tracing::error!("ui_chain_sync error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Check that the search index (idx:thoughts) covers every instance with thoughts. action 'rebuild' registers missing instance prefixes and rebuilds the index; existing thoughts are re-indexed in the background. Status also lists features degraded by missing Redis modules")]
+    pub async fn ui_search_index(
+        &self,
+        params: Parameters<UiSearchIndexParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_search_index").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_search_index(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_search_index error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Usage stats per MCP client (name, version, sessions, tool call counts, errors, last seen), to see which client or editor generated what activity")]
+    pub async fn ui_clients(
+        &self,
+        params: Parameters<UiClientsParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        match self.handlers.ui_clients(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_clients error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Turn a long free-form brain dump into a new thought chain: splits it into discrete thoughts (paragraphs, list items and sentences, or an external LLM segmenter), stores them in order and returns the chain for review")]
+    pub async fn ui_braindump(
+        &self,
+        params: Parameters<UiBraindumpParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_braindump").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_braindump(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_braindump error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Ingest a voice memo as a thought chain: transcribes an audio file with the UI_TRANSCRIBE_COMMAND hook (or takes a Whisper transcript/segments), timestamps each thought at its point in the recording and tags speakers. Stored with source_tool 'voice'")]
+    pub async fn ui_voice_memo(
+        &self,
+        params: Parameters<UiVoiceMemoParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_voice_memo").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_voice_memo(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_voice_memo error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Poll the configured capture sources (RSS/Atom feeds, flagged IMAP mail, git repositories) and store new items as thoughts with title, excerpt, link and tags. Commits go to one chain per repository (git:{repo}) tagged repo:{name} and branch:{name}. Items already captured (same URL, Message-ID or commit) are skipped")]
+    pub async fn ui_capture(
+        &self,
+        params: Parameters<UiCaptureParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_capture").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_capture(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_capture error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Import a browser bookmarks export (Netscape HTML) or a read-later highlights JSON export as thoughts with URL, title, highlights and tags (folders and item tags). Items on the same domain are appended to a web:{domain} chain and tagged domain:{host}; URLs already imported or captured are skipped")]
+    pub async fn ui_import_bookmarks(
+        &self,
+        params: Parameters<UiImportBookmarksParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_import_bookmarks").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_import_bookmarks(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_import_bookmarks error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
         }
     }
-}
\ No newline at end of file
+    
+    #[tool(description = "Assemble a weekly review: the week's chains, decisions, completed and open action items, topic trends against the previous week and intervention stats. Returns a markdown document with reflection prompts and stores it as a chain tagged weekly-review (reflections given are appended as the last thoughts)")]
+    pub async fn ui_weekly_review(
+        &self,
+        params: Parameters<UiWeeklyReviewParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_weekly_review").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_weekly_review(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_weekly_review error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "List thought chains, most recently active first, with their related chains: chains created close in time with overlapping topics, linked by the background chain linker (relink=true refreshes the links first). Results are paged; pass next_cursor back as cursor for the next page")]
+    pub async fn ui_list_chains(
+        &self,
+        params: Parameters<UiListChainsParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_list_chains").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_list_chains(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_list_chains error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Report thoughts whose embeddings are missing or were produced by an outdated model or model version (UI_EMBEDDING_MODEL, UI_EMBEDDING_MODEL_VERSION), most important first; enqueue=true queues them for the background embedding worker to re-embed by importance")]
+    pub async fn ui_embedding_staleness(
+        &self,
+        params: Parameters<UiEmbeddingStalenessParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_embedding_staleness").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_embedding_staleness(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_embedding_staleness error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Export thought chains as fine-tuning JSONL (chat messages or prompt/completion pairs): the first thought is the prompt and the rest of the chain the completion, PII masked (privacy_level 'strict' also withholds thoughts tagged 'private'); filter by concluded chains and recall feedback")]
+    pub async fn ui_export_training(
+        &self,
+        params: Parameters<UiExportTrainingParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_export_training").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_export_training(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_export_training error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Persona bundle: identity, behavioral patterns, dominant topics, thoughts tagged 'pinned' and recent weekly reviews as one versioned artifact with a Markdown system prompt for bootstrapping a new model or instance. A new version is stored only when the content changed.")]
+    pub async fn ui_persona_snapshot(
+        &self,
+        params: Parameters<UiPersonaSnapshotParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_persona_snapshot").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_persona_snapshot(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_persona_snapshot error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Compare two stored persona snapshots (default: the latest two) and return a Markdown changelog of identity changes, behavioral pattern shifts, new and faded dominant topics and pinned thoughts.")]
+    pub async fn ui_persona_diff(
+        &self,
+        params: Parameters<UiPersonaDiffParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_persona_diff").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_persona_diff(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_persona_diff error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Attach a review annotation to a thought: agree/disagree verdict, 1-10 score and/or comment, from another instance or the human (reviewer='human'). Annotations are stored apart from the thought and returned by ui_recall with include_annotations=true.")]
+    pub async fn ui_annotate(
+        &self,
+        params: Parameters<UiAnnotateParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_annotate").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_annotate(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_annotate error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Move old, rarely read, low-importance thoughts out of Redis into compressed, indexed JSONL segment files (UI_COLD_STORAGE_DIR), leaving stubs that ui_recall rehydrates transparently. Use dry_run to preview candidates.")]
+    pub async fn ui_tier_cold(
+        &self,
+        params: Parameters<UiTierColdParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_tier_cold").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_tier_cold(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_tier_cold error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Replay a chain's thoughts in order, one step at a time. With a progress token each step is sent as a progress notification after its delay (pace_ms, or the original gaps divided by speed); without one the steps come back in chunks with next_from_thought and delay_ms so the client can pace them")]
+    pub async fn ui_replay(
+        &self,
+        params: Parameters<UiReplayParams>,
+        meta: Meta,
+        peer: Peer<RoleServer>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_replay").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_replay(params.0).await {
+            Ok(mut response) => {
+                if let Some(token) = meta.get_progress_token() {
+                    let total = response.steps.len() as u32;
+                    let sent = replay::stream(&response.steps, |index, step| {
+                        let notification = ProgressNotificationParam {
+                            progress_token: token.clone(),
+                            progress: index as u32 + 1,
+                            total: Some(total),
+                            message: serde_json::to_string(step).ok(),
+                        };
+                        let peer = peer.clone();
+                        async move {
+                            match peer.notify_progress(notification).await {
+                                Ok(()) => true,
+                                Err(e) => {
+                                    tracing::warn!("Stopping replay, progress notification failed: {}", e);
+                                    false
+                                }
+                            }
+                        }
+                    }).await;
+                    replay::finish_stream(&mut response, sent);
+                }
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_replay error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Manage this client's subscriptions to Redis pub/sub channels (intervention_events, vault_changes, reminders, ui_events by default). Messages on subscribed channels arrive as notifications/message with logger 'redis:<channel>'; low-priority events are batched into periodic 'digest:<instance>' notifications. Actions: subscribe, unsubscribe, list (default)")]
+    pub async fn ui_subscribe(
+        &self,
+        params: Parameters<UiSubscribeParams>,
+        peer: Peer<RoleServer>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_subscribe").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        let params = params.0;
+        let client = peer.peer_info().map(|info| info.client_info.name.clone()).unwrap_or_else(|| "unknown".to_string());
+        let channels = match &params.channels {
+            Some(requested) => self.bridge.resolve(requested).map_err(|e| ErrorData::invalid_params(e.to_string(), None))?,
+            None => self.bridge.channels().to_vec(),
+        };
+        let action = params.action.as_deref().map(|a| a.trim().to_lowercase()).unwrap_or_else(|| "list".to_string());
+        let channels = match action.as_str() {
+            "subscribe" => self.bridge.subscribe(&client, peer, &channels),
+            "unsubscribe" => self.bridge.unsubscribe(&client, &channels),
+            "list" => self.bridge.subscribed(&client),
+            other => return Err(ErrorData::invalid_params(
+                format!("Unknown action '{}'. Use 'subscribe', 'unsubscribe' or 'list'", other),
+                None
+            )),
+        };
+        tracing::info!("Client {} {} -> channels {:?}", client, action, channels);
+        
+        let response = SubscribeResponse {
+            client,
+            channels,
+            available: self.bridge.channels().to_vec(),
+            bridge_active: self.bridge.is_active(),
+        };
+        let content = Content::json(response)
+            .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+        Ok(CallToolResult::success(vec![content]))
+    }
+    
+    #[tool(description = "Per-chain quality metrics: thought count against the planned total, average thought length, revisions, plan changes, time span, framework usage, confidence trajectory (from wording) and completion status. Measures one chain or the most recent chains")]
+    pub async fn ui_chain_stats(
+        &self,
+        params: Parameters<UiChainStatsParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_chain_stats").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_chain_stats(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_chain_stats error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Reverse lookup of vault note citations: lists the thoughts whose ui_think citations reference a note (optionally one heading), newest first, with the cited headings and content hashes")]
+    pub async fn ui_citations(
+        &self,
+        params: Parameters<UiCitationsParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_citations").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_citations(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_citations error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Render a chain, or the thoughts matching tag and date filters, as a standalone HTML report (chain graph, decisions, timeline and thought cards, PII masked by default) written to UI_REPORT_DIR for sharing outside MCP, with a redaction manifest next to it; returns the file path")]
+    pub async fn ui_report(
+        &self,
+        params: Parameters<UiReportParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_report").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_report(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_report error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Operating modes bundling recall defaults, intervention sensitivity, capture filters and notification channels. 'get' the active mode, 'list' all, 'switch' to 'work', 'personal', 'focus', 'default' or a defined mode (re-subscribes this client to the mode's notification channels), or 'define' a custom profile. Switches are recorded in the instance event log")]
+    pub async fn ui_mode(
+        &self,
+        params: Parameters<UiModeParams>,
+        peer: Peer<RoleServer>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_mode").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_mode(params.0).await {
+            Ok(mut response) => {
+                // A switch replaces this client's subscriptions with the mode's bridged channels
+                if let (Some(_), Some(channels)) = (&response.previous, &response.profile.notification_channels) {
+                    let client = peer.peer_info().map(|info| info.client_info.name.clone()).unwrap_or_else(|| "unknown".to_string());
+                    let wanted: Vec<String> = channels.iter().filter(|c| self.bridge.channels().contains(c)).cloned().collect();
+                    self.bridge.unsubscribe(&client, &self.bridge.subscribed(&client));
+                    let subscribed = if wanted.is_empty() { Vec::new() } else { self.bridge.subscribe(&client, peer, &wanted) };
+                    tracing::info!("Client {} switched to mode '{}' -> channels {:?}", client, response.mode, subscribed);
+                    response.notification_channels = Some(subscribed);
+                }
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_mode error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Verify a chain's custody hashes (stored when UI_CHAIN_CUSTODY is enabled): recomputes each thought's hash and its link to the thought before it, reporting altered, missing, forked or unsealed thoughts")]
+    pub async fn ui_verify_chain(
+        &self,
+        params: Parameters<UiVerifyChainParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_verify_chain").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_verify_chain(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_verify_chain error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Data migrations: list the versioned migrations applied to this instance and those still pending (they run automatically at startup). Set apply=true to apply pending migrations now.")]
+    pub async fn ui_migrations(
+        &self,
+        params: Parameters<UiMigrationsParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_migrations").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_migrations(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_migrations error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Delete a thought by ID along with its metadata, tag and citation indexes, boost score, feedback events, embedding, PII findings and annotations. Its chain's stored thought count is updated. This cannot be undone.")]
+    pub async fn ui_delete_thought(
+        &self,
+        params: Parameters<UiDeleteThoughtParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_delete_thought").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_delete_thought(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_delete_thought error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Delete a chain with every thought in it (cascading as ui_delete_thought does), its metadata and sync state, and remove it from other chains' related links. This cannot be undone.")]
+    pub async fn ui_delete_chain(
+        &self,
+        params: Parameters<UiDeleteChainParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_delete_chain").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_delete_chain(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_delete_chain error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Export a chain as a versioned JSON bundle: its metadata, thoughts, thought metadata and boost scores. Returns the bundle, or writes it to output_path.")]
+    pub async fn ui_export_chain(
+        &self,
+        params: Parameters<UiExportChainParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_export_chain").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_export_chain(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_export_chain error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Import a chain bundle from ui_export_chain (inline or from input_path) under this or another instance, optionally as a new chain_id. Thoughts get ui_think's validation and PII policy and new IDs; sealed chains must pass custody verification as exported and are then resealed.")]
+    pub async fn ui_import_chain(
+        &self,
+        params: Parameters<UiImportChainParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_import_chain").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_import_chain(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_import_chain error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Write the most recent thoughts with a tag, a category or both as an Atom or JSON Feed file, so the stream can be followed in a feed reader. The file name is stable per stream, so rerunning updates the followed feed.")]
+    pub async fn ui_feed(
+        &self,
+        params: Parameters<UiFeedParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_feed").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_feed(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_feed error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Add or remove tags, set or clear the category, or set or shift the importance of every thought matching a query, chain, tag and date range. Changes are saved in batches; dry_run previews them without saving. Each update returns an undo_id; passing it back as undo_id restores the previous metadata, except on thoughts edited again since. The last 20 updates can be reverted.")]
+    pub async fn ui_bulk_update(
+        &self,
+        params: Parameters<UiBulkUpdateParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_bulk_update").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_bulk_update(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_bulk_update error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Recall defaults tuned from feedback: semantic ui_recall calls that leave out threshold and limit use the instance's tuned values, and ui_recall_feedback on their results adjusts them in small steps within fixed rails (UI_RECALL_AUTOTUNE, default on). 'status' shows the values, the feedback gathered and the adjustment history with justifications; 'tune' applies what the feedback supports now; 'revert' restores the values before adjustment 'version'; 'reset' returns to the baseline (threshold 0.5, limit 50).")]
+    pub async fn ui_recall_tuning(
+        &self,
+        params: Parameters<UiRecallTuningParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_recall_tuning").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_recall_tuning(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_recall_tuning error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Suggest 2-3 directions the next thought of a chain could take, to resume a thinking session quickly. With UI_HINT_GENERATOR set, the chain's topics, first and latest thoughts and open questions go to that command (typically a model call); otherwise, or when it fails, the chain's open questions become the hints. Hints are cached in the chain metadata until a thought is added; refresh=true generates new ones.")]
+    pub async fn ui_next_hint(
+        &self,
+        params: Parameters<UiNextHintParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_next_hint").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_next_hint(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_next_hint error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Compact the event streams: trim the thought, identity and feedback event streams by their length and age limits (UI_STREAM_MAXLEN, UI_STREAM_MAX_AGE_DAYS), archiving the entries that open searches and chains still refer to. Returns what was trimmed per stream and the running totals; dry_run reports without trimming")]
+    pub async fn ui_compact_streams(
+        &self,
+        params: Parameters<UiCompactStreamsParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_compact_streams").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_compact_streams(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_compact_streams error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Compute thought importance from usage: combines recall frequency, feedback, the 'pinned' tag and citations into a 1-10 score stored next to the manual importance. Recall ranks by the manual importance when set and the computed one otherwise. Scores the whole instance, or one thought_id; dry_run reports without storing")]
+    pub async fn ui_score_importance(
+        &self,
+        params: Parameters<UiScoreImportanceParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_score_importance").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_score_importance(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_score_importance error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Write a chain to the Obsidian vault as a readable note: frontmatter with chain, instance and timestamps, one section per thought with wikilinks to the notes it cites. Leaves notes edited in Obsidian alone unless overwrite is set")]
+    pub async fn ui_export_to_vault(
+        &self,
+        params: Parameters<UiExportToVaultParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_export_to_vault").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_export_to_vault(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_export_to_vault error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Current rate limit state of this instance: calls used and remaining in the instance-wide window and in each per-tool window (UI_RATE_LIMIT, UI_RATE_LIMIT_TOOLS), and when the oldest call leaves each window. Shared by every server process of the instance on the Redis backend. Does not count against the limits")]
+    pub async fn ui_rate_limit_status(
+        &self,
+        params: Parameters<UiRateLimitStatusParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let mut windows = self.rate_limiter.status(&self.instance_id).await;
+        if let Some(tool) = params.0.tool.as_deref().map(str::trim) {
+            let scopes = self.rate_limiter.config().scopes(tool);
+            windows.retain(|window| scopes.iter().any(|(scope, _)| *scope == window.scope));
+        }
+        let response = RateLimitStatusResponse {
+            instance: self.instance_id.clone(),
+            shared: self.rate_limiter.is_shared(),
+            windows,
+        };
+        let content = Content::json(response)
+            .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+        Ok(CallToolResult::success(vec![content]))
+    }
+    
+    #[tool(description = "Compare two chains, e.g. an original and a branch of it: thoughts both share (identical or mostly the same words), thoughts unique to each, the points where they part ways, and per chain whether it concluded and the recall feedback its thoughts earned")]
+    pub async fn ui_chain_diff(
+        &self,
+        params: Parameters<UiChainDiffParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_chain_diff").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_chain_diff(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_chain_diff error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+    
+    #[tool(description = "Troubleshooting bundle: masked environment, effective config, Redis modules, search index status, connection pool, background tasks and recent errors as one JSON document")]
+    pub async fn ui_diagnostics(
+        &self,
+        params: Parameters<UiDiagnosticsParams>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        // Check rate limit
+        if let Err(e) = self.rate_limiter.check_rate_limit(&self.instance_id, "ui_diagnostics").await {
+            tracing::warn!("Rate limit hit for instance {}: {}", self.instance_id, e);
+            return Err(ErrorData::invalid_params(
+                "Rate limit exceeded. Please slow down your requests.".to_string(), 
+                None
+            ));
+        }
+        
+        match self.handlers.ui_diagnostics(params.0).await {
+            Ok(response) => {
+                let content = Content::json(response)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                tracing::error!("ui_diagnostics error: {}", e);
+                Err(ErrorData::internal_error(e.to_string(), None))
+            }
+        }
+    }
+}
+
+// call_tool and list_tools are written out (rather than generated by #[tool_handler])
+// so every call can be attributed to the client that made it
+impl ServerHandler for UnifiedIntelligenceService {
+    async fn initialize(
+        &self,
+        request: InitializeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> std::result::Result<InitializeResult, ErrorData> {
+        let client = &request.client_info;
+        let protocol_version = request.protocol_version.to_string();
+        if let Err(e) = self.handlers.record_client(&client.name, &client.version, &protocol_version).await {
+            tracing::warn!("Failed to record MCP client {}: {}", client.name, e);
+        }
+        
+        if context.peer.peer_info().is_none() {
+            context.peer.set_peer_info(request);
+        }
+        Ok(self.get_info())
+    }
+    
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        let tool = request.name.to_string();
+        let budget = self.tool_budgets.budget(&tool);
+        let deadline = tokio::time::Instant::now() + budget;
+        let call = timeouts::with_deadline(deadline, self.tool_router.call(ToolCallContext::new(self, request, context)));
+        let result = if !timeouts::cancellable(&tool) {
+            // Abandoning a write midway would leave partial state; the handler stops itself at the deadline
+            let result = call.await;
+            if tokio::time::Instant::now() > deadline + timeouts::GRACE {
+                tracing::warn!("{} overran its {}ms budget", tool, budget.as_millis());
+            }
+            result
+        } else {
+            match tokio::time::timeout_at(deadline + timeouts::GRACE, call).await {
+                Ok(result) => result,
+                Err(_) => {
+                    tracing::error!("{} exceeded its {}ms budget", tool, budget.as_millis());
+                    Err(ErrorData::internal_error(
+                        format!("{} timed out after {}ms", tool, budget.as_millis()),
+                        Some(serde_json::json!({
+                            "error": "timeout",
+                            "tool": tool,
+                            "budget_ms": budget.as_millis() as u64,
+                        })),
+                    ))
+                }
+            }
+        };
+        
+        let failed = result.as_ref().map_or(true, |r| r.is_error.unwrap_or(false));
+        if let Err(e) = &result {
+            self.handlers.diagnostics().record_error(&tool, &e.message);
+        }
+        if let Err(e) = self.handlers.record_tool_call(&tool, failed).await {
+            tracing::warn!("Failed to record client stats for {}: {}", tool, e);
+        }
+        result
+    }
+    
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> std::result::Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult::with_all_items(self.tool_router.list_all()))
+    }
+    
+    async fn set_level(
+        &self,
+        request: SetLevelRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> std::result::Result<(), ErrorData> {
+        // Bridged pub/sub messages are the only log notifications and are sent at info; ui_subscribe controls them
+        tracing::debug!("Client requested log level {:?}", request.level);
+        Ok(())
+    }
+    
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            protocol_version: rmcp::model::ProtocolVersion::V_2024_11_05,
+            server_info: rmcp::model::Implementation {
+                name: "unified-intelligence".into(),
+                version: "3.0.0".into(),
+            },
+            capabilities: ServerCapabilities {
+                tools: Some(Default::default()),
+                logging: Some(Default::default()),
+                ..Default::default()
+            },
+            instructions: Some("UnifiedIntelligence MCP Server for Redis-backed thought storage".into()),
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Service over an in-memory repository with the given per-tool limits
+    fn test_service(tool_limits: &str) -> UnifiedIntelligenceService {
+        let instance_id = "test".to_string();
+        let repository: Arc<dyn Repository> = Arc::new(MemoryRepository::new(None));
+        let handlers = Arc::new(ToolHandlers::new(
+            repository.clone(),
+            instance_id.clone(),
+            None,
+            Arc::new(InputValidator::new()),
+            SearchCache::from_env(),
+            Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        ).unwrap());
+        UnifiedIntelligenceService {
+            tool_router: UnifiedIntelligenceService::tool_router(),
+            crash_reporter: CrashReporter::start(repository, instance_id.clone(), handlers.diagnostics().clone()),
+            handlers,
+            rate_limiter: Arc::new(RateLimiter::with_config(RateLimitConfig::parse("100/60", tool_limits))),
+            instance_id,
+            tool_budgets: Arc::new(ToolBudgets::from_env()),
+            bridge: Arc::new(NotificationBridge::from_env()),
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_capture_limit_refuses_extra_call() {
+        let service = test_service("ui_capture=1/60");
+        let capture = || Parameters(UiCaptureParams { source: None, limit: None });
+        
+        // The first call reaches the handler, which has no capture sources configured
+        let first = service.ui_capture(capture()).await.unwrap_err();
+        assert!(!first.message.contains("Rate limit exceeded"), "{}", first.message);
+        
+        // The second is refused before the handler runs
+        let second = service.ui_capture(capture()).await.unwrap_err();
+        assert!(second.message.contains("Rate limit exceeded"), "{}", second.message);
+        
+        // Other tools still have the instance budget
+        assert!(service.rate_limiter.check_rate_limit("test", "ui_think").await.is_ok());
+    }
+}
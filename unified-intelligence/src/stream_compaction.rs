@@ -0,0 +1,223 @@
+//! Compaction of the event streams, for ui_compact_streams.
+//!
+//! `{instance}:events` and `{instance}:feedback_events` are trimmed from the
+//! oldest end, by length and by age, each with its own policy:
+//! UI_STREAM_MAXLEN (`events=10000,feedback_events=50000`) and
+//! UI_STREAM_MAX_AGE_DAYS (`events=30,feedback_events=90`), where 0 turns a
+//! limit off. An entry about to be trimmed that refers to an open session (a
+//! search or chain with an entry in the last UI_STREAM_SESSION_HOURS,
+//! default 24) is first copied to `{instance}:stream_archive`, so the trim
+//! doesn't take the start of a session that is still going. Trimmed and
+//! archived counts per stream are kept in `{instance}:stream_compaction`.
+//! The job runs through ui_compact_streams, and every
+//! UI_STREAM_COMPACT_INTERVAL_SECS (default 3600, 0 disables).
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::keys;
+use crate::models::StreamEntry;
+
+/// Streams that are compacted, by name
+pub const STREAMS: &[&str] = &["events", "feedback_events"];
+
+/// Archived entries kept per instance
+pub const MAX_ARCHIVED: usize = 1_000;
+
+/// Fields naming the session an entry belongs to
+const SESSION_FIELDS: &[&str] = &["search_id", "chain_id"];
+
+const DEFAULT_SESSION_HOURS: u64 = 24;
+
+/// Limits of one stream; None means no limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamPolicy {
+    pub max_len: Option<usize>,
+    pub max_age_days: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompactionConfig {
+    policies: BTreeMap<String, StreamPolicy>,
+    pub session_hours: u64,
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        let policies = [
+            ("events", StreamPolicy { max_len: Some(10_000), max_age_days: Some(30) }),
+            ("feedback_events", StreamPolicy { max_len: Some(50_000), max_age_days: Some(90) }),
+        ];
+        Self {
+            policies: policies.into_iter().map(|(name, policy)| (name.to_string(), policy)).collect(),
+            session_hours: DEFAULT_SESSION_HOURS,
+        }
+    }
+}
+
+impl CompactionConfig {
+    /// Read UI_STREAM_MAXLEN, UI_STREAM_MAX_AGE_DAYS and UI_STREAM_SESSION_HOURS
+    pub fn from_env() -> Self {
+        let env = |name: &str| std::env::var(name).unwrap_or_default();
+        let session_hours = env("UI_STREAM_SESSION_HOURS").trim().parse().ok();
+        Self::parse(&env("UI_STREAM_MAXLEN"), &env("UI_STREAM_MAX_AGE_DAYS"), session_hours)
+    }
+
+    /// Defaults overridden by `stream=n` pairs separated by commas; malformed pairs are skipped
+    pub fn parse(max_lens: &str, max_ages: &str, session_hours: Option<u64>) -> Self {
+        let mut config = Self::default();
+        for (stream, max_len) in overrides(max_lens) {
+            config.policies.entry(stream).or_default().max_len = (max_len > 0).then_some(max_len as usize);
+        }
+        for (stream, days) in overrides(max_ages) {
+            config.policies.entry(stream).or_default().max_age_days = (days > 0).then_some(days);
+        }
+        if let Some(hours) = session_hours {
+            config.session_hours = hours;
+        }
+        config
+    }
+
+    pub fn policy(&self, stream: &str) -> StreamPolicy {
+        self.policies.get(stream).copied().unwrap_or_default()
+    }
+}
+
+fn overrides(pairs: &str) -> Vec<(String, u64)> {
+    pairs.split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .filter_map(|pair| {
+            let parsed = pair.split_once('=')
+                .and_then(|(stream, n)| Some((stream.trim().to_string(), n.trim().parse::<u64>().ok()?)));
+            if parsed.is_none() {
+                tracing::warn!("Ignoring stream limit '{}'", pair.trim());
+            }
+            parsed
+        })
+        .collect()
+}
+
+/// Compaction interval from UI_STREAM_COMPACT_INTERVAL_SECS; None when set to 0
+pub fn compact_interval() -> Option<std::time::Duration> {
+    let seconds = std::env::var("UI_STREAM_COMPACT_INTERVAL_SECS").ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(3600);
+    (seconds > 0).then(|| std::time::Duration::from_secs(seconds))
+}
+
+/// Key of a compacted stream
+pub fn stream_key(instance: &str, stream: &str) -> String {
+    match stream {
+        "events" => keys::events(instance),
+        _ => keys::feedback_events(instance),
+    }
+}
+
+/// Milliseconds part of a Redis stream ID (`1700000000000-0`)
+pub fn id_ms(id: &str) -> Option<i64> {
+    id.split_once('-')?.0.parse().ok()
+}
+
+/// The smallest Redis stream ID after `id`, for XTRIM MINID
+pub fn next_id(id: &str) -> Option<String> {
+    let (ms, seq) = id.split_once('-')?;
+    let (ms, seq) = (ms.parse::<u64>().ok()?, seq.parse::<u64>().ok()?);
+    Some(match seq.checked_add(1) {
+        Some(seq) => format!("{}-{}", ms, seq),
+        None => format!("{}-0", ms + 1),
+    })
+}
+
+/// When an event was added, from its `timestamp` (RFC 3339 or Unix seconds) or `published_at`
+pub fn timestamp_ms(fields: &serde_json::Value) -> Option<i64> {
+    ["timestamp", "published_at"].iter().find_map(|name| match fields.get(*name)? {
+        serde_json::Value::String(text) => chrono::DateTime::parse_from_rfc3339(text).ok().map(|dt| dt.timestamp_millis()),
+        serde_json::Value::Number(seconds) => seconds.as_i64().map(|s| s * 1000),
+        _ => None,
+    })
+}
+
+/// Sessions an entry refers to
+fn sessions(entry: &StreamEntry) -> impl Iterator<Item = String> + '_ {
+    SESSION_FIELDS.iter()
+        .filter_map(|field| entry.fields.get(*field)?.as_str())
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+}
+
+/// Sessions with an entry at or after `since_ms`
+pub fn open_sessions(entries: &[StreamEntry], since_ms: i64) -> BTreeSet<String> {
+    entries.iter()
+        .filter(|entry| entry.timestamp_ms.is_some_and(|ts| ts >= since_ms))
+        .flat_map(sessions)
+        .collect()
+}
+
+/// What a run does to a stream: trim its first `trim` entries, after
+/// archiving those of them listed in `archive` with their open sessions
+#[derive(Debug, Default, PartialEq)]
+pub struct Plan {
+    pub trim: usize,
+    pub archive: Vec<(usize, Vec<String>)>,
+}
+
+/// Plan the trim of a stream (entries oldest first)
+pub fn plan(policy: StreamPolicy, entries: &[StreamEntry], open: &BTreeSet<String>, now_ms: i64) -> Plan {
+    let by_len = policy.max_len.map_or(0, |max_len| entries.len().saturating_sub(max_len));
+    let by_age = policy.max_age_days.map_or(0, |days| {
+        let cutoff = now_ms - days as i64 * 86_400_000;
+        entries.iter().take_while(|entry| entry.timestamp_ms.is_some_and(|ts| ts < cutoff)).count()
+    });
+    let trim = by_len.max(by_age);
+    let archive = entries[..trim].iter().enumerate()
+        .filter_map(|(index, entry)| {
+            let referenced: Vec<String> = sessions(entry).filter(|session| open.contains(session)).collect();
+            (!referenced.is_empty()).then_some((index, referenced))
+        })
+        .collect();
+    Plan { trim, archive }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const DAY: i64 = 86_400_000;
+
+    fn entry(id: u64, age_days: i64, fields: serde_json::Value) -> StreamEntry {
+        StreamEntry { id: format!("{}-0", id), timestamp_ms: Some(100 * DAY - age_days * DAY), fields }
+    }
+
+    #[test]
+    fn test_plan_trims_by_length_and_age_and_archives_open_sessions() {
+        let now = 100 * DAY;
+        let entries = vec![
+            entry(1, 40, json!({"search_id": "s1"})),
+            entry(2, 35, json!({"search_id": "s2", "chain_id": "c1"})),
+            entry(3, 10, json!({"search_id": "s3"})),
+            entry(4, 5, json!({})),
+            entry(5, 0, json!({"chain_id": "c1"})),
+        ];
+        let open = open_sessions(&entries, now - DAY);
+        assert_eq!(open, BTreeSet::from(["c1".to_string()]));
+
+        let by_age = StreamPolicy { max_len: None, max_age_days: Some(30) };
+        assert_eq!(plan(by_age, &entries, &open, now), Plan { trim: 2, archive: vec![(1, vec!["c1".to_string()])] });
+        let by_len = StreamPolicy { max_len: Some(2), max_age_days: Some(30) };
+        assert_eq!(plan(by_len, &entries, &open, now).trim, 3);
+        assert_eq!(plan(StreamPolicy::default(), &entries, &open, now), Plan::default());
+    }
+
+    #[test]
+    fn test_config_and_ids() {
+        let config = CompactionConfig::parse("events=500, feedback_events=0, bad", "events=7", Some(2));
+        assert_eq!(config.policy("events"), StreamPolicy { max_len: Some(500), max_age_days: Some(7) });
+        assert_eq!(config.policy("feedback_events"), StreamPolicy { max_len: None, max_age_days: Some(90) });
+        assert_eq!(config.session_hours, 2);
+
+        assert_eq!(next_id("1700000000000-4").as_deref(), Some("1700000000000-5"));
+        assert_eq!(id_ms("1700000000000-4"), Some(1_700_000_000_000));
+        assert_eq!(timestamp_ms(&json!({"timestamp": "1970-01-01T00:00:01Z"})), Some(1000));
+        assert_eq!(timestamp_ms(&json!({"timestamp": 2})), Some(2000));
+    }
+}
@@ -0,0 +1,108 @@
+//! Multi-tenancy support: optional user_id dimension on top of instance_id.
+//!
+//! When a user_id is configured every key owned by this server is placed under
+//! `users:{user_id}:{instance}` so several people can share one Redis without
+//! their thoughts, chains, identities or feedback mixing. Without a user_id the
+//! legacy `{instance}:...` key schema is used unchanged.
+
+use std::env;
+
+use crate::error::{Result, UnifiedIntelligenceError};
+
+/// Root prefix for all user-scoped keys
+pub const USER_KEY_PREFIX: &str = "users:";
+
+/// Whether a user_id can name a tenant: ASCII letters, digits, '_' and '-' only,
+/// so it is always exactly one key segment and never a SCAN pattern
+pub fn valid_user_id(user_id: &str) -> bool {
+    !user_id.is_empty() && user_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Read the optional user_id from the USER_ID environment variable
+pub fn user_id_from_env() -> Result<Option<String>> {
+    let Some(user_id) = env::var("USER_ID").ok()
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty()) else {
+        return Ok(None);
+    };
+    if !valid_user_id(&user_id) {
+        return Err(UnifiedIntelligenceError::Validation {
+            field: "USER_ID".to_string(),
+            reason: format!("'{}' may only contain letters, digits, '_' and '-'", user_id),
+        });
+    }
+    Ok(Some(user_id))
+}
+
+/// Key prefix for everything owned by a user ("" when multi-tenancy is off)
+pub fn user_prefix(user_id: Option<&str>) -> String {
+    match user_id {
+        Some(user) => format!("{}{}:", USER_KEY_PREFIX, user),
+        None => String::new(),
+    }
+}
+
+/// Storage namespace for an instance, i.e. what replaces `{instance}` in key schemas
+pub fn scoped_instance(user_id: Option<&str>, instance_id: &str) -> String {
+    format!("{}{}", user_prefix(user_id), instance_id)
+}
+
+/// Strip any user scope from a namespace, returning the bare instance_id
+pub fn base_instance(namespace: &str) -> &str {
+    match namespace.strip_prefix(USER_KEY_PREFIX) {
+        Some(rest) => rest.split_once(':').map(|(_, instance)| instance).unwrap_or(rest),
+        None => namespace,
+    }
+}
+
+/// User segment of a user-scoped namespace (or key), None when it is unscoped
+pub fn user_of(namespace: &str) -> Option<&str> {
+    let rest = namespace.strip_prefix(USER_KEY_PREFIX)?;
+    Some(rest.split_once(':').map(|(user, _)| user).unwrap_or(rest))
+}
+
+/// Whether a namespace (or any key built from it) belongs to the given user.
+/// Unscoped data only belongs to the "no user" tenant.
+pub fn belongs_to(user_id: Option<&str>, namespace: &str) -> bool {
+    match user_id {
+        Some(user) => valid_user_id(user) && user_of(namespace) == Some(user),
+        None => user_of(namespace).is_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scoped_instance() {
+        assert_eq!(scoped_instance(None, "CC"), "CC");
+        assert_eq!(scoped_instance(Some("alice"), "CC"), "users:alice:CC");
+        assert_eq!(base_instance("users:alice:CC"), "CC");
+        assert_eq!(base_instance("CC"), "CC");
+    }
+
+    #[test]
+    fn test_belongs_to() {
+        assert!(belongs_to(None, "CC:Thoughts:1"));
+        assert!(!belongs_to(None, "users:alice:CC:Thoughts:1"));
+        assert!(belongs_to(Some("alice"), "users:alice:CC:Thoughts:1"));
+        assert!(!belongs_to(Some("alice"), "users:bob:CC:Thoughts:1"));
+        assert!(!belongs_to(Some("alice"), "CC:Thoughts:1"));
+        assert!(!belongs_to(Some("alice"), "users:alice-x:CC:Thoughts:1"));
+    }
+
+    #[test]
+    fn test_user_ids_are_one_segment() {
+        assert!(valid_user_id("alice"));
+        assert!(valid_user_id("alice_x-2"));
+        assert!(!valid_user_id("alice:x"));
+        assert!(!valid_user_id("alice*"));
+        assert!(!valid_user_id(""));
+        // "alice:x" can't be configured, and a namespace written under it is never "alice:x"'s
+        assert!(!belongs_to(Some("alice:x"), "users:alice:x:CC:Thoughts:1"));
+        assert_eq!(user_of("users:alice:CC"), Some("alice"));
+        assert_eq!(user_of("users:alice"), Some("alice"));
+        assert_eq!(user_of("CC"), None);
+    }
+}
@@ -0,0 +1,150 @@
+//! Thinking type taxonomy for ingested thoughts.
+//!
+//! ui_think takes an optional thinking_type saying what kind of thought it
+//! is. The known types are stored under their lowercase key; a kind outside
+//! the taxonomy is recorded as `other` or `other:<label>`, so new kinds can be
+//! captured without a schema change. ui_recall filters on the type
+//! (thinking_type_filter) and mind_cognitive_metrics reports the mix of types
+//! among the instance's recent thoughts.
+
+use std::collections::BTreeMap;
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::ThoughtRecord;
+
+/// Most recent instance thoughts counted by mind_cognitive_metrics
+pub const SCAN_LIMIT: usize = 1000;
+
+/// Longest label accepted after `other:`
+const MAX_LABEL_LEN: usize = 32;
+
+/// Kind of thought, as given to ui_think
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThinkingType {
+    Analysis,
+    Decision,
+    Question,
+    Observation,
+    Todo,
+    Reflection,
+    /// Outside the taxonomy, with an optional label
+    Other(Option<String>),
+}
+
+impl ThinkingType {
+    pub const KNOWN: [ThinkingType; 6] = [
+        ThinkingType::Analysis,
+        ThinkingType::Decision,
+        ThinkingType::Question,
+        ThinkingType::Observation,
+        ThinkingType::Todo,
+        ThinkingType::Reflection,
+    ];
+
+    /// Parse a request value, ignoring case; `field` names it in validation errors
+    pub fn parse(field: &str, value: &str) -> Result<Self> {
+        let value = value.trim().to_lowercase();
+        if let Some(known) = Self::KNOWN.into_iter().find(|t| t.category() == value) {
+            return Ok(known);
+        }
+        let label = match value.split_once(':') {
+            None if value == "other" => None,
+            Some(("other", label)) => Some(label.trim()),
+            _ => {
+                let known: Vec<&str> = Self::KNOWN.iter().map(ThinkingType::category).collect();
+                return Err(UnifiedIntelligenceError::Validation {
+                    field: field.to_string(),
+                    reason: format!("Unknown thinking type '{}'. Use one of {}, or 'other:<label>'", value, known.join(", ")),
+                });
+            }
+        };
+        let valid = label.is_none_or(|label| {
+            !label.is_empty() && label.len() <= MAX_LABEL_LEN
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        });
+        if !valid {
+            return Err(UnifiedIntelligenceError::Validation {
+                field: field.to_string(),
+                reason: format!("Labels after 'other:' must be 1-{} letters, digits, '_' or '-'", MAX_LABEL_LEN),
+            });
+        }
+        Ok(ThinkingType::Other(label.map(str::to_string)))
+    }
+
+    /// Taxonomy entry, with every `other:<label>` under "other"
+    pub fn category(&self) -> &'static str {
+        match self {
+            ThinkingType::Analysis => "analysis",
+            ThinkingType::Decision => "decision",
+            ThinkingType::Question => "question",
+            ThinkingType::Observation => "observation",
+            ThinkingType::Todo => "todo",
+            ThinkingType::Reflection => "reflection",
+            ThinkingType::Other(_) => "other",
+        }
+    }
+
+    /// Value stored on the thought
+    pub fn key(&self) -> String {
+        match self {
+            ThinkingType::Other(Some(label)) => format!("other:{}", label),
+            known => known.category().to_string(),
+        }
+    }
+
+    /// Whether a stored type passes this filter; a bare `other` filter matches every label
+    pub fn matches(&self, stored: &str) -> bool {
+        match self {
+            ThinkingType::Other(None) => stored == "other" || stored.starts_with("other:"),
+            filter => filter.key() == stored,
+        }
+    }
+}
+
+/// Number of thoughts of each taxonomy entry; untyped thoughts are left out
+pub fn mix(thoughts: &[ThoughtRecord]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for stored in thoughts.iter().filter_map(|t| t.thinking_type.as_deref()) {
+        // Records predating a taxonomy change still count, under "other"
+        let category = ThinkingType::parse("thinking_type", stored).map(|t| t.category()).unwrap_or("other");
+        *counts.entry(category.to_string()).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_known_and_other() {
+        assert_eq!(ThinkingType::parse("thinking_type", " Decision ").unwrap(), ThinkingType::Decision);
+        assert_eq!(ThinkingType::parse("thinking_type", "other").unwrap().key(), "other");
+        assert_eq!(ThinkingType::parse("thinking_type", "Other:Hypothesis").unwrap().key(), "other:hypothesis");
+        assert!(ThinkingType::parse("thinking_type", "musing").is_err());
+        assert!(ThinkingType::parse("thinking_type", "other:").is_err());
+        assert!(ThinkingType::parse("thinking_type", "other:two words").is_err());
+        assert!(ThinkingType::parse("thinking_type", "todo:soon").is_err());
+    }
+
+    #[test]
+    fn test_filters_and_mix() {
+        let other = ThinkingType::parse("thinking_type_filter", "other").unwrap();
+        assert!(other.matches("other") && other.matches("other:hypothesis"));
+        assert!(!other.matches("question"));
+        assert!(!ThinkingType::parse("thinking_type_filter", "other:a").unwrap().matches("other:b"));
+
+        let thoughts: Vec<ThoughtRecord> = [Some("question"), Some("question"), Some("other:hypothesis"), Some("legacy"), None]
+            .into_iter()
+            .map(|thinking_type| {
+                let mut thought = ThoughtRecord::new("CC".to_string(), "t".to_string(), 1, 1, None, false);
+                thought.thinking_type = thinking_type.map(str::to_string);
+                thought
+            })
+            .collect();
+        let counts = mix(&thoughts);
+        assert_eq!(counts.get("question"), Some(&2));
+        assert_eq!(counts.get("other"), Some(&2));
+        assert_eq!(counts.len(), 2);
+    }
+}
@@ -0,0 +1,214 @@
+//! Cold storage tiering for large instances.
+//!
+//! Old, rarely read, low-importance thoughts are moved out of Redis into
+//! segment files under UI_COLD_STORAGE_DIR (`{instance}/{segment}.jsonl.gz`).
+//! Each thought is its own gzip member, so a segment still decompresses as
+//! plain JSONL while a single thought can be read by seeking to its offset;
+//! `{segment}.idx.json` maps thought ids to those offsets. Redis keeps a stub
+//! with the first line of the text and a `cold` pointer, and ui_recall puts
+//! the full text back when a stub is returned. The job runs through
+//! ui_tier_cold, or every UI_TIER_INTERVAL_SECS when that is set.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::{ColdPointer, ThoughtRecord};
+use crate::review;
+
+/// Thoughts read per instance when looking for candidates
+pub const SCAN_LIMIT: usize = 1_000_000;
+
+/// Characters of the text kept on a stub
+pub const STUB_CHARS: usize = 120;
+
+#[derive(Debug, Clone)]
+pub struct TierConfig {
+    pub dir: PathBuf,
+    pub min_age_days: i64,
+    pub idle_days: i64,
+    pub max_importance: i32,
+    pub batch_size: usize,
+}
+
+impl Default for TierConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("cold_storage"),
+            min_age_days: 30,
+            idle_days: 30,
+            max_importance: crate::embedding_version::DEFAULT_IMPORTANCE,
+            batch_size: 1000,
+        }
+    }
+}
+
+impl TierConfig {
+    pub fn from_env() -> Self {
+        let env = |name: &str| std::env::var(name).ok().filter(|value| !value.trim().is_empty());
+        let defaults = Self::default();
+        Self {
+            dir: env("UI_COLD_STORAGE_DIR").map(PathBuf::from).unwrap_or(defaults.dir),
+            min_age_days: env("UI_TIER_MIN_AGE_DAYS").and_then(|v| v.trim().parse().ok()).unwrap_or(defaults.min_age_days),
+            idle_days: env("UI_TIER_IDLE_DAYS").and_then(|v| v.trim().parse().ok()).unwrap_or(defaults.idle_days),
+            max_importance: env("UI_TIER_MAX_IMPORTANCE").and_then(|v| v.trim().parse().ok()).unwrap_or(defaults.max_importance),
+            batch_size: env("UI_TIER_BATCH_SIZE").and_then(|v| v.trim().parse().ok()).filter(|n| *n > 0).unwrap_or(defaults.batch_size),
+        }
+    }
+}
+
+/// Tiering interval from UI_TIER_INTERVAL_SECS; None (the default) when disabled
+pub fn tier_interval() -> Option<std::time::Duration> {
+    let seconds = std::env::var("UI_TIER_INTERVAL_SECS").ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(0);
+    (seconds > 0).then(|| std::time::Duration::from_secs(seconds))
+}
+
+/// Whether a thought is old, idle and unimportant enough to move
+pub fn is_candidate(
+    config: &TierConfig,
+    thought: &ThoughtRecord,
+    importance: i32,
+    last_access: Option<i64>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> bool {
+    if thought.cold.is_some() || importance > config.max_importance {
+        return false;
+    }
+    let Ok(created) = chrono::DateTime::parse_from_rfc3339(&thought.timestamp) else {
+        return false;
+    };
+    if now.signed_duration_since(created) < chrono::Duration::days(config.min_age_days) {
+        return false;
+    }
+    // Never-read thoughts count as idle since they were written
+    last_access.is_none_or(|at| now.timestamp() - at >= config.idle_days * 86_400)
+}
+
+/// Directory name of an instance namespace (user scopes contain ':')
+fn instance_dir(instance: &str) -> String {
+    instance.replace([':', '/', '\\'], "_")
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> UnifiedIntelligenceError {
+    UnifiedIntelligenceError::Internal(format!("cold storage {}: {}", path.display(), e))
+}
+
+/// Write full thoughts to a new segment and its index; returns the pointer of each thought, in order
+pub fn write_segment(dir: &Path, instance: &str, thoughts: &[ThoughtRecord]) -> Result<Vec<ColdPointer>> {
+    let segment = format!(
+        "{}/{}-{}.jsonl.gz",
+        instance_dir(instance),
+        chrono::Utc::now().format("%Y%m%dT%H%M%S"),
+        &uuid::Uuid::new_v4().simple().to_string()[..8],
+    );
+    let path = dir.join(&segment);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+    }
+
+    let mut file = OpenOptions::new().write(true).create_new(true).open(&path).map_err(|e| io_error(&path, e))?;
+    let mut pointers = Vec::with_capacity(thoughts.len());
+    let mut index = BTreeMap::new();
+    let mut offset = 0u64;
+    for thought in thoughts {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        serde_json::to_writer(&mut encoder, thought)?;
+        encoder.write_all(b"\n").map_err(|e| io_error(&path, e))?;
+        let member = encoder.finish().map_err(|e| io_error(&path, e))?;
+        file.write_all(&member).map_err(|e| io_error(&path, e))?;
+
+        let pointer = ColdPointer { segment: segment.clone(), offset, length: member.len() as u64 };
+        index.insert(thought.id.clone(), (pointer.offset, pointer.length));
+        pointers.push(pointer);
+        offset += member.len() as u64;
+    }
+    file.sync_all().map_err(|e| io_error(&path, e))?;
+
+    let index_path = dir.join(segment.replace(".jsonl.gz", ".idx.json"));
+    fs::write(&index_path, serde_json::to_vec(&index)?).map_err(|e| io_error(&index_path, e))?;
+    Ok(pointers)
+}
+
+/// Read one thought back from its segment
+pub fn read_thought(dir: &Path, pointer: &ColdPointer) -> Result<ThoughtRecord> {
+    let path = dir.join(&pointer.segment);
+    let mut file = File::open(&path).map_err(|e| io_error(&path, e))?;
+    file.seek(SeekFrom::Start(pointer.offset)).map_err(|e| io_error(&path, e))?;
+    let mut line = String::new();
+    GzDecoder::new(file.take(pointer.length)).read_to_string(&mut line).map_err(|e| io_error(&path, e))?;
+    Ok(serde_json::from_str(line.trim_end())?)
+}
+
+/// Stub kept in Redis for a tiered-out thought
+pub fn stub(thought: &ThoughtRecord, pointer: ColdPointer) -> ThoughtRecord {
+    ThoughtRecord {
+        thought: review::snippet(&thought.thought, STUB_CHARS),
+        similarity: None,
+        cold: Some(pointer),
+        ..thought.clone()
+    }
+}
+
+/// Put a stub's full text back, keeping its other fields (a merged copy has its own id and chain)
+pub fn rehydrate(stub: &ThoughtRecord, stored: ThoughtRecord) -> ThoughtRecord {
+    ThoughtRecord {
+        thought: stored.thought,
+        cold: None,
+        ..stub.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thought(text: &str, days_old: i64) -> ThoughtRecord {
+        let mut thought = ThoughtRecord::new("users:alice:CC".to_string(), text.to_string(), 1, 1, None, false);
+        thought.timestamp = (chrono::Utc::now() - chrono::Duration::days(days_old)).to_rfc3339();
+        thought
+    }
+
+    #[test]
+    fn test_candidates() {
+        let config = TierConfig::default();
+        let now = chrono::Utc::now();
+        let old = thought("old note", 90);
+        assert!(is_candidate(&config, &old, 2, None, now));
+        assert!(!is_candidate(&config, &old, 8, None, now));
+        assert!(!is_candidate(&config, &old, 2, Some(now.timestamp() - 86_400), now));
+        assert!(is_candidate(&config, &old, 2, Some(now.timestamp() - 60 * 86_400), now));
+        assert!(!is_candidate(&config, &thought("new note", 2), 2, None, now));
+    }
+
+    #[test]
+    fn test_segment_round_trip() {
+        let dir = std::env::temp_dir().join(format!("ui-cold-{}", uuid::Uuid::new_v4()));
+        let thoughts = vec![thought("First archived thought\nwith a second line", 90), thought("Second archived thought", 120)];
+        let pointers = write_segment(&dir, "users:alice:CC", &thoughts).unwrap();
+        assert!(pointers[0].segment.starts_with("users_alice_CC/"));
+        assert_eq!(pointers[1].offset, pointers[0].length);
+
+        let back = read_thought(&dir, &pointers[1]).unwrap();
+        assert_eq!(back.thought, "Second archived thought");
+
+        // The segment as a whole is plain gzip JSONL
+        let mut all = String::new();
+        flate2::read::MultiGzDecoder::new(File::open(dir.join(&pointers[0].segment)).unwrap()).read_to_string(&mut all).unwrap();
+        assert_eq!(all.lines().count(), 2);
+
+        let stubbed = stub(&thoughts[0], pointers[0].clone());
+        assert_eq!(stubbed.thought, "First archived thought");
+        let restored = rehydrate(&stubbed, read_thought(&dir, stubbed.cold.as_ref().unwrap()).unwrap());
+        assert_eq!(restored.thought, thoughts[0].thought);
+        assert!(restored.cold.is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,139 @@
+//! Per-tool latency budgets.
+//!
+//! Every tool call gets a budget: UI_TOOL_TIMEOUT_SECS (default 60) or a
+//! per-tool override from UI_TOOL_TIMEOUTS (`ui_recall=15,ui_capture=300`).
+//! The deadline is visible to the handler through a task-local so long
+//! handlers can stop early and return what they have (ui_recall marks such
+//! responses `partial`). A call still running GRACE after its deadline is
+//! abandoned and the client gets a structured timeout error instead of
+//! waiting forever. Tools that write in several steps are never abandoned,
+//! since that would leave half their writes behind; they check `expired()`
+//! between items instead and report how far they got.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// Budget for tools without an override
+pub const DEFAULT_BUDGET_SECS: u64 = 60;
+
+/// Time a cooperative handler gets past its deadline to return partial results
+pub const GRACE: Duration = Duration::from_millis(500);
+
+/// Tools that write in several steps and so always run to completion
+const RUN_TO_COMPLETION: &[&str] = &[
+    "ui_think", "ui_purge", "ui_chain_sync", "ui_search_index", "ui_braindump",
+    "ui_voice_memo", "ui_capture", "ui_import_bookmarks", "ui_tier_cold",
+    "ui_migrations", "ui_delete_thought", "ui_delete_chain", "ui_import_chain",
+    "ui_bulk_update", "ui_compact_streams", "ui_export_to_vault",
+];
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// Timeout budget of each tool
+#[derive(Debug, Clone)]
+pub struct ToolBudgets {
+    default: Duration,
+    overrides: HashMap<String, Duration>,
+}
+
+impl ToolBudgets {
+    /// Read UI_TOOL_TIMEOUT_SECS and UI_TOOL_TIMEOUTS
+    pub fn from_env() -> Self {
+        let default = std::env::var("UI_TOOL_TIMEOUT_SECS").ok()
+            .and_then(|secs| secs.trim().parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(DEFAULT_BUDGET_SECS);
+        Self::parse(Duration::from_secs(default), &std::env::var("UI_TOOL_TIMEOUTS").unwrap_or_default())
+    }
+
+    /// Overrides from `tool=secs` pairs separated by commas; malformed pairs are skipped
+    pub fn parse(default: Duration, overrides: &str) -> Self {
+        let overrides = overrides.split(',')
+            .filter_map(|pair| {
+                let (tool, secs) = pair.split_once('=')?;
+                let secs = secs.trim().parse::<u64>().ok().filter(|secs| *secs > 0);
+                if secs.is_none() {
+                    tracing::warn!("Ignoring tool timeout '{}'", pair.trim());
+                }
+                Some((tool.trim().to_string(), Duration::from_secs(secs?)))
+            })
+            .collect();
+        Self { default, overrides }
+    }
+
+    pub fn budget(&self, tool: &str) -> Duration {
+        self.overrides.get(tool).copied().unwrap_or(self.default)
+    }
+}
+
+/// Whether a call still running past its deadline may be abandoned
+pub fn cancellable(tool: &str) -> bool {
+    !RUN_TO_COMPLETION.contains(&tool)
+}
+
+/// Run a future with a deadline its handler can observe
+pub async fn with_deadline<F: Future>(deadline: Instant, future: F) -> F::Output {
+    DEADLINE.scope(deadline, future).await
+}
+
+/// Deadline of the current tool call, None outside one (e.g. background tasks)
+pub fn deadline() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+/// Whether the current tool call has used up its budget
+pub fn expired() -> bool {
+    deadline().is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+/// Run a step of a tool call, None if the deadline passes first
+pub async fn within<F: Future>(future: F) -> Option<F::Output> {
+    match deadline() {
+        Some(deadline) => tokio::time::timeout_at(deadline, future).await.ok(),
+        None => Some(future.await),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_overrides() {
+        let budgets = ToolBudgets::parse(Duration::from_secs(60), "ui_recall=15, ui_capture=300,ui_think=0,bogus");
+        assert_eq!(budgets.budget("ui_recall"), Duration::from_secs(15));
+        assert_eq!(budgets.budget("ui_capture"), Duration::from_secs(300));
+        assert_eq!(budgets.budget("ui_think"), Duration::from_secs(60));
+        assert_eq!(budgets.budget("ui_identity"), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_writes_are_not_cancellable() {
+        assert!(cancellable("ui_recall"));
+        assert!(cancellable("ui_chain_stats"));
+        assert!(!cancellable("ui_purge"));
+        assert!(!cancellable("ui_bulk_update"));
+        assert!(!cancellable("ui_capture"));
+    }
+
+    #[tokio::test]
+    async fn test_within_deadline() {
+        assert!(!expired());
+        assert_eq!(within(async { 1 }).await, Some(1));
+
+        with_deadline(Instant::now(), async {
+            assert!(expired());
+            assert_eq!(within(tokio::time::sleep(Duration::from_secs(5))).await, None);
+        }).await;
+
+        with_deadline(Instant::now() + Duration::from_secs(5), async {
+            assert!(!expired());
+            assert_eq!(within(async { 2 }).await, Some(2));
+        }).await;
+    }
+}
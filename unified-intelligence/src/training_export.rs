@@ -0,0 +1,129 @@
+//! Fine-tuning data from thought chains for ui_export_training.
+//!
+//! Each chain becomes one example: its first thought is the prompt and the
+//! remaining thoughts, in order, are the completion, so a model learns to
+//! reason from a problem statement to the chain's conclusion. Examples are
+//! written as OpenAI-style chat messages or as prompt/completion pairs, one
+//! JSON object per line, redacted at the export's privacy level (PII masked
+//! by default, regardless of PII_POLICY).
+
+use serde_json::{json, Value};
+
+use crate::error::{Result, UnifiedIntelligenceError};
+use crate::models::ThoughtRecord;
+use crate::redaction::Redactor;
+
+/// System message for chat examples when none is given
+pub const DEFAULT_SYSTEM_PROMPT: &str = "Think through the problem step by step, then state your conclusion.";
+
+/// Separator between thoughts in a completion
+const STEP_SEPARATOR: &str = "\n\n";
+
+/// Output layout of an example
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Chat,
+    PromptCompletion,
+}
+
+impl ExportFormat {
+    pub fn parse(value: Option<&str>) -> Result<Self> {
+        match value.map(|v| v.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("chat") => Ok(ExportFormat::Chat),
+            Some("prompt_completion") | Some("completion") => Ok(ExportFormat::PromptCompletion),
+            Some(other) => Err(UnifiedIntelligenceError::Validation {
+                field: "format".to_string(),
+                reason: format!("Unknown format '{}'. Use 'chat' or 'prompt_completion'", other),
+            }),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Chat => "chat",
+            ExportFormat::PromptCompletion => "prompt_completion",
+        }
+    }
+}
+
+/// Prompt and completion of one chain, PII masked
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainExample {
+    pub prompt: String,
+    pub completion: String,
+    pub masked_pii: usize,
+}
+
+/// Example from a chain's thoughts, None when the chain has fewer than two
+pub fn chain_example(redactor: &mut Redactor, thoughts: &[ThoughtRecord]) -> Option<ChainExample> {
+    let mut ordered: Vec<&ThoughtRecord> = thoughts.iter().collect();
+    ordered.sort_by_key(|t| t.thought_number);
+    let (first, rest) = ordered.split_first()?;
+    if rest.is_empty() {
+        return None;
+    }
+
+    let masked_before = redactor.masked_pii();
+    let prompt = redactor.text(&first.id, first.thought.trim());
+    let completion = rest.iter().map(|t| redactor.text(&t.id, t.thought.trim())).collect::<Vec<_>>().join(STEP_SEPARATOR);
+    Some(ChainExample { prompt, completion, masked_pii: redactor.masked_pii() - masked_before })
+}
+
+/// Chain ended with a concluding thought rather than being abandoned midway
+pub fn is_concluded(thoughts: &[ThoughtRecord]) -> bool {
+    thoughts.iter().max_by_key(|t| t.thought_number).is_some_and(|last| !last.next_thought_needed)
+}
+
+/// One JSONL line for an example
+pub fn to_line(format: ExportFormat, example: &ChainExample, system_prompt: &str) -> Value {
+    match format {
+        ExportFormat::Chat => json!({
+            "messages": [
+                {"role": "system", "content": system_prompt},
+                {"role": "user", "content": example.prompt},
+                {"role": "assistant", "content": example.completion},
+            ]
+        }),
+        ExportFormat::PromptCompletion => json!({
+            "prompt": example.prompt,
+            "completion": example.completion,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redaction::PrivacyLevel;
+
+    fn thought(number: i32, text: &str, next_thought_needed: bool) -> ThoughtRecord {
+        ThoughtRecord::new("CC".to_string(), text.to_string(), number, 3, Some("c1".to_string()), next_thought_needed)
+    }
+
+    #[test]
+    fn test_chain_example_masks_pii() {
+        let thoughts = vec![
+            thought(2, "Check the logs from ops@example.com", true),
+            thought(1, "Why did the deploy fail?", true),
+            thought(3, "The migration timed out.", false),
+        ];
+        let example = chain_example(&mut Redactor::new(PrivacyLevel::Pii), &thoughts).unwrap();
+        assert_eq!(example.prompt, "Why did the deploy fail?");
+        assert_eq!(example.completion, "Check the logs from [REDACTED:email]\n\nThe migration timed out.");
+        assert_eq!(example.masked_pii, 1);
+        assert!(is_concluded(&thoughts));
+        assert!(!is_concluded(&thoughts[..2]));
+        assert!(chain_example(&mut Redactor::new(PrivacyLevel::Pii), &thoughts[1..2]).is_none());
+    }
+
+    #[test]
+    fn test_line_formats() {
+        let example = ChainExample { prompt: "Q".to_string(), completion: "A".to_string(), masked_pii: 0 };
+        let chat = to_line(ExportFormat::Chat, &example, DEFAULT_SYSTEM_PROMPT);
+        assert_eq!(chat["messages"][1], json!({"role": "user", "content": "Q"}));
+        assert_eq!(chat["messages"][2]["role"], "assistant");
+        assert_eq!(to_line(ExportFormat::PromptCompletion, &example, ""), json!({"prompt": "Q", "completion": "A"}));
+        assert_eq!(ExportFormat::parse(Some("prompt_completion")).unwrap(), ExportFormat::PromptCompletion);
+        assert!(ExportFormat::parse(Some("alpaca")).is_err());
+    }
+}
@@ -9,6 +9,9 @@ pub enum ValidationError {
     #[error("Invalid chain ID format: {chain_id}")]
     InvalidChainId { chain_id: String },
     
+    #[error("Invalid thought ID: '{thought_id}'")]
+    InvalidThoughtId { thought_id: String },
+    
     #[error("Invalid thought number: {number} (must be 1-{max})")]
     InvalidThoughtNumber { number: i32, max: i32 },
     
@@ -71,6 +74,17 @@ impl InputValidator {
         }
     }
     
+    pub fn validate_thought_id(&self, thought_id: &str) -> std::result::Result<(), ValidationError> {
+        // Thought IDs are built into keys, so patterns and separators are refused
+        if thought_id.is_empty() || thought_id.contains(|c: char| c == ':' || c == '*' || c.is_whitespace()) {
+            Err(ValidationError::InvalidThoughtId {
+                thought_id: thought_id.to_string(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+    
     pub fn validate_thought_numbers(&self, number: i32, total: i32) -> std::result::Result<(), ValidationError> {
         // Validate total_thoughts is within bounds
         if total < 1 || total > self.max_thoughts_per_chain {
@@ -80,11 +94,11 @@ impl InputValidator {
             });
         }
         
-        // Validate thought_number is within bounds
-        if number < 1 || number > total {
+        // Validate thought_number is within bounds; running past total_thoughts is reconciled, not rejected
+        if number < 1 || number > self.max_thoughts_per_chain {
             return Err(ValidationError::InvalidThoughtNumber {
                 number,
-                max: total,
+                max: self.max_thoughts_per_chain,
             });
         }
         
@@ -173,6 +187,18 @@ mod tests {
         ));
     }
     
+    #[test]
+    fn test_thought_id() {
+        let validator = InputValidator::new();
+        assert!(validator.validate_thought_id("550e8400-e29b-41d4-a716-446655440000").is_ok());
+        for invalid in ["", "CC:Thoughts:1", "*", "two words"] {
+            assert!(matches!(
+                validator.validate_thought_id(invalid),
+                Err(ValidationError::InvalidThoughtId { .. })
+            ));
+        }
+    }
+    
     #[test]
     fn test_valid_thought_numbers() {
         let validator = InputValidator::new();
@@ -188,7 +214,7 @@ mod tests {
             Err(ValidationError::InvalidThoughtNumber { .. })
         ));
         assert!(matches!(
-            validator.validate_thought_numbers(6, 5),
+            validator.validate_thought_numbers(validator.max_thoughts_per_chain + 1, 5),
             Err(ValidationError::InvalidThoughtNumber { .. })
         ));
         assert!(matches!(
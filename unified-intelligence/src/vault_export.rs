@@ -0,0 +1,96 @@
+//! One-way export of a chain into the Obsidian vault, for ui_export_to_vault.
+//!
+//! Notes are rendered by `chain_sync::render_note`, so they carry the same
+//! thought markers as ui_chain_sync's round-trip notes; exports add the
+//! instance to the frontmatter. Notes go to
+//! `{OBSIDIAN_VAULT_PATH}/{OBSIDIAN_EXPORT_FOLDER}/{chain_id}-{hash}.md`
+//! (default folder "Chain Exports"). The frontmatter's `content_hash` covers
+//! the body as written, so a later export can tell the note was edited in
+//! Obsidian and leaves it alone unless asked to overwrite.
+
+use crate::chain_sync::{self, HASH_FIELD};
+
+/// Vault folder of exported chains when OBSIDIAN_EXPORT_FOLDER is not set
+const DEFAULT_EXPORT_FOLDER: &str = "Chain Exports";
+
+/// Export folder from OBSIDIAN_EXPORT_FOLDER
+pub fn default_folder() -> String {
+    std::env::var("OBSIDIAN_EXPORT_FOLDER").ok()
+        .filter(|folder| !folder.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EXPORT_FOLDER.to_string())
+}
+
+/// Whether a vault-relative folder stays inside the vault and out of hidden folders
+pub fn valid_folder(folder: &str) -> bool {
+    let folder = folder.trim();
+    !folder.starts_with('/')
+        && !folder.contains('\\')
+        && folder.split('/').all(|part| part != ".." && !part.starts_with('.'))
+}
+
+/// Whether an exported note was changed since it was written; notes this tool didn't write count as changed
+pub fn edited(note: &str) -> bool {
+    let Some(rest) = note.strip_prefix("---\n") else {
+        return true;
+    };
+    let Some((frontmatter, body)) = rest.split_once("\n---\n\n") else {
+        return true;
+    };
+    match frontmatter.lines().find_map(|line| line.strip_prefix(HASH_FIELD)) {
+        Some(hash) => hash.trim() != chain_sync::note_hash(body),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_sync::{extract_annotations, render_note, Annotation, NoteKind, NoteThought};
+    use crate::models::{Citation, ThoughtMetadata, ThoughtRecord};
+
+    #[test]
+    fn test_render_note_links_citations() {
+        let mut first = ThoughtRecord::new("CC".to_string(), "Ports moved".to_string(), 1, 2, Some("c1".to_string()), true);
+        first.timestamp = "2025-07-18T10:00:00Z".to_string();
+        let mut second = ThoughtRecord::new("CC".to_string(), "Update [[Runbook]] too".to_string(), 2, 2, Some("c1".to_string()), false);
+        second.timestamp = "2025-07-18T11:00:00Z".to_string();
+        let mut metadata = ThoughtMetadata::new(first.id.clone(), "CC".to_string(), None, None, Some(vec!["redis ops".to_string()]), None);
+        metadata.citations = vec![
+            Citation { note_path: "Projects/Redis.md".to_string(), heading: Some("Redis > Ports".to_string()), hash: None },
+            Citation { note_path: "Infra.md".to_string(), heading: None, hash: None },
+        ];
+        let thoughts = [
+            NoteThought { thought: &first, metadata: Some(&metadata) },
+            NoteThought { thought: &second, metadata: None },
+        ];
+
+        let note = render_note("c1", NoteKind::Export { instance: "CC" }, &thoughts);
+        assert!(note.contains("instance: \"CC\"\n"));
+        assert!(note.contains("started_at: \"2025-07-18T10:00:00Z\"\nupdated_at: \"2025-07-18T11:00:00Z\"\n"));
+        assert!(note.contains("tags: [\"redis-ops\", \"thought-chain\"]\n"));
+        assert!(note.contains("cites:\n  - \"[[Infra]]\"\n  - \"[[Projects/Redis]]\"\n"));
+        assert!(note.contains(&format!("## Thought 1\n<!-- ui:thought {} -->\n*2025-07-18T10:00:00Z*\n\nPorts moved\n\nCites: [[Projects/Redis#Redis#Ports]], [[Infra]]\n", first.id)));
+        assert!(note.contains("Update [[Runbook]] too\n"));
+
+        // Exports read back like sync notes: generated lines aren't annotations
+        let records = [first.clone(), second.clone()];
+        assert!(extract_annotations(&note, &records).is_empty());
+        let annotated = note.replace("[[Infra]]\n", "[[Infra]]\n\nStill on 6379 in staging.\n");
+        assert_eq!(extract_annotations(&annotated, &records), vec![
+            Annotation { on_thought: Some(1), heading: None, text: "Still on 6379 in staging.".to_string() },
+        ]);
+
+        assert!(!edited(&note));
+        assert!(edited(&note.replace("Ports moved", "Ports moved to 6380")));
+        assert!(edited("# Written by hand\n"));
+    }
+
+    #[test]
+    fn test_valid_folder() {
+        assert!(valid_folder("Chain Exports"));
+        assert!(valid_folder("Projects/Redis/Chains"));
+        assert!(!valid_folder("../outside"));
+        assert!(!valid_folder("/etc"));
+        assert!(!valid_folder(".obsidian"));
+    }
+}
@@ -2,6 +2,7 @@ use colored::*;
 
 /// Visual output module for unified-intelligence MCP
 /// Provides colored console output similar to Sequential Thinking
+#[derive(Default)]
 pub struct VisualOutput;
 
 impl VisualOutput {
@@ -131,7 +132,8 @@ impl VisualOutput {
 
     /// Progress bar for sequential thinking
     pub fn progress_bar(&self, current: i32, total: i32) {
-        let progress = (current as f32 / total as f32 * 20.0) as usize;
+        // Chains can run past their planned total, so the bar stops at full
+        let progress = ((current as f32 / total.max(1) as f32 * 20.0) as usize).min(20);
         let filled = "█".repeat(progress);
         let empty = "░".repeat(20 - progress);
         
@@ -0,0 +1,135 @@
+//! Voice memo transcripts for ui_voice_memo.
+//!
+//! Audio is transcribed by an external hook: UI_TRANSCRIBE_COMMAND runs through
+//! `sh -c` with the audio path as `$1` and must print Whisper-style JSON, either
+//! `{"segments": [{"start": 0.0, "end": 4.2, "text": "...", "speaker": "SPEAKER_00"}]}`
+//! or a bare array of segments (`speaker` comes from diarization and is optional).
+//! For example `whisper "$1" --output_format json --output_dir /tmp >/dev/null && cat /tmp/$(basename "${1%.*}").json`.
+//!
+//! Whisper segments are a few seconds long, so consecutive segments from the
+//! same speaker are merged into thought-sized pieces that keep the offset of
+//! their first segment.
+
+use std::process::Command;
+
+use crate::models::VoiceSegment;
+
+/// Transcription hook command from UI_TRANSCRIBE_COMMAND
+pub fn transcribe_command() -> Option<String> {
+    std::env::var("UI_TRANSCRIBE_COMMAND").ok().filter(|cmd| !cmd.trim().is_empty())
+}
+
+/// Run the transcription hook on an audio file
+pub fn transcribe(command: &str, audio_path: &str) -> Result<Vec<VoiceSegment>, String> {
+    let output = Command::new("sh")
+        .args(["-c", command, "transcribe", audio_path])
+        .output()
+        .map_err(|e| format!("failed to start transcription command: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("transcription command exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    parse_whisper_json(&output.stdout)
+}
+
+/// Segments from Whisper JSON output (object with `segments` or a bare array)
+pub fn parse_whisper_json(json: &[u8]) -> Result<Vec<VoiceSegment>, String> {
+    let value: serde_json::Value = serde_json::from_slice(json)
+        .map_err(|e| format!("transcription output is not JSON: {}", e))?;
+    let segments = match value.get("segments") {
+        Some(segments) => segments.clone(),
+        None => value,
+    };
+    serde_json::from_value(segments).map_err(|e| format!("transcription output has no usable segments: {}", e))
+}
+
+/// Segments from pre-transcribed text; lines may start with a `[mm:ss]` or `[hh:mm:ss]` offset
+pub fn parse_transcript(text: &str) -> Vec<VoiceSegment> {
+    let mut segments: Vec<VoiceSegment> = Vec::new();
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        match split_offset(line) {
+            Some((start, text)) => segments.push(VoiceSegment { start, end: None, text: text.to_string(), speaker: None }),
+            // Lines without an offset continue the previous segment
+            None => match segments.last_mut() {
+                Some(last) => {
+                    last.text.push(' ');
+                    last.text.push_str(line);
+                }
+                None => segments.push(VoiceSegment { start: 0.0, end: None, text: line.to_string(), speaker: None }),
+            },
+        }
+    }
+    segments
+}
+
+fn split_offset(line: &str) -> Option<(f64, &str)> {
+    let rest = line.strip_prefix('[')?;
+    let (stamp, text) = rest.split_once(']')?;
+    let mut seconds = 0.0;
+    let parts: Vec<&str> = stamp.trim().split(':').collect();
+    if !(2..=3).contains(&parts.len()) {
+        return None;
+    }
+    for part in parts {
+        seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some((seconds, text.trim()))
+}
+
+/// Merge consecutive segments of the same speaker into pieces of at most `max_chars`
+pub fn merge_segments(segments: Vec<VoiceSegment>, max_chars: usize) -> Vec<VoiceSegment> {
+    let mut merged: Vec<VoiceSegment> = Vec::new();
+    for segment in segments {
+        let text = segment.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        match merged.last_mut() {
+            Some(last) if last.speaker == segment.speaker && last.text.len() + 1 + text.len() <= max_chars => {
+                last.text.push(' ');
+                last.text.push_str(text);
+                last.end = segment.end.or(last.end);
+            }
+            _ => merged.push(VoiceSegment { text: text.to_string(), ..segment }),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whisper_segments_merge_per_speaker() {
+        let json = br#"{"text": "...", "segments": [
+            {"start": 0.0, "end": 2.5, "text": " Redis memory is growing.", "speaker": "SPEAKER_00"},
+            {"start": 2.5, "end": 4.0, "text": " Probably the cache.", "speaker": "SPEAKER_00"},
+            {"start": 4.0, "end": 6.0, "text": " Check the TTLs.", "speaker": "SPEAKER_01"}
+        ]}"#;
+        let merged = merge_segments(parse_whisper_json(json).unwrap(), 600);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].text, "Redis memory is growing. Probably the cache.");
+        assert_eq!((merged[0].start, merged[0].end), (0.0, Some(4.0)));
+        assert_eq!(merged[1].speaker.as_deref(), Some("SPEAKER_01"));
+        assert!(parse_whisper_json(b"not json").is_err());
+    }
+
+    #[test]
+    fn test_transcribe_passes_audio_path() {
+        let path = std::env::temp_dir().join(format!("ui-voice-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, r#"[{"start": 1.5, "text": "hello"}]"#).unwrap();
+        let segments = transcribe(r#"cat "$1""#, path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((segments[0].start, segments[0].text.as_str()), (1.5, "hello"));
+        assert!(transcribe("exit 3", "missing.wav").is_err());
+    }
+
+    #[test]
+    fn test_transcript_offsets() {
+        let segments = parse_transcript("[00:05] first idea\ncontinues here\n[1:02:03] much later");
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[0].start, segments[0].text.as_str()), (5.0, "first idea continues here"));
+        assert_eq!(segments[1].start, 3723.0);
+        assert_eq!(parse_transcript("no offsets at all")[0].start, 0.0);
+    }
+}
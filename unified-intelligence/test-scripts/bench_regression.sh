@@ -0,0 +1,50 @@
+#!/bin/bash
+# Recall latency regression guard.
+#
+# Usage:
+#   ./test-scripts/bench_regression.sh save [baseline]    # record a baseline (default: main)
+#   ./test-scripts/bench_regression.sh compare [baseline] # compare against it, exit 1 on regression
+#
+# Arguments after the baseline name are passed to criterion (e.g. --quick).
+#
+# Runs benches/recall_latency.rs. Seed Redis first or set UI_STORAGE_BACKEND=memory;
+# UI_BENCH_SIZES narrows the dataset sizes (default 1000,10000,100000).
+# The report is written to target/criterion/report/index.html and the
+# summary to target/bench_regression.log.
+
+set -euo pipefail
+
+# ui_think prints visual output; keep only criterion's lines
+summary() {
+    grep -E "^thoughts_|time:|change:|regressed|improved|Skipping" "$LOG" || true
+}
+
+cd "$(dirname "$0")/.."
+
+MODE="${1:-compare}"
+BASELINE="${2:-main}"
+LOG="target/bench_regression.log"
+mkdir -p target
+
+case "$MODE" in
+    save)
+        cargo bench --bench recall_latency -- --save-baseline "$BASELINE" "${@:3}" > "$LOG" 2>&1 || { tail -20 "$LOG"; exit 1; }
+        summary
+        echo "Saved baseline '$BASELINE'"
+        ;;
+    compare)
+        cargo bench --bench recall_latency -- --baseline "$BASELINE" "${@:3}" > "$LOG" 2>&1 || { tail -20 "$LOG"; exit 1; }
+        summary
+        if grep -q "Performance has regressed" "$LOG"; then
+            echo ""
+            echo "❌ Latency regressions against baseline '$BASELINE':"
+            grep -B2 "Performance has regressed" "$LOG" | grep -E "^thoughts_|time:" || true
+            exit 1
+        fi
+        echo "✅ No latency regressions against baseline '$BASELINE'"
+        ;;
+    *)
+        echo "Usage: $0 [save|compare] [baseline]" >&2
+        exit 2
+        ;;
+esac
@@ -0,0 +1,380 @@
+//! Spending caps and usage accounting for paid API calls.
+//!
+//! Every OpenAI and Groq call is priced from its token counts and added to
+//! the day's `um:usage:{date}` hash by provider, model and purpose. Before a
+//! call the day's spend is checked against the provider's cap and the overall
+//! daily cap (see BudgetConfig); mind_usage reports the recorded days.
+//! unified-intelligence counts the OpenAI query embeddings of its semantic
+//! ui_recall against the same caps and hash (its api_budget module).
+
+use crate::error::Result;
+use crate::models::{BudgetCaps, DailyUsage, ProviderUsage, PurposeUsage, UsageParams, UsageResult};
+use crate::redis::RedisClient;
+use chrono::{Duration, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::env;
+use tracing::warn;
+
+/// Redis hash holding budget overrides (field -> value)
+pub const BUDGET_CONFIG_KEY: &str = "um:config:budget";
+
+/// Usage hashes are kept this long
+const USAGE_TTL_SECS: u64 = 86400 * 90;
+
+/// Most days reported by mind_usage
+const MAX_DAYS: usize = 90;
+
+/// USD per million input and output tokens
+const PRICES: &[(&str, f64, f64)] = &[
+    ("text-embedding-3-small", 0.02, 0.0),
+    ("text-embedding-3-large", 0.13, 0.0),
+    ("llama-3.3-70b-versatile", 0.59, 0.79),
+    ("llama-3.1-8b-instant", 0.05, 0.08),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+    OpenAi,
+    Groq,
+}
+
+impl Provider {
+    pub const ALL: [Provider; 2] = [Provider::OpenAi, Provider::Groq];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Provider::OpenAi => "openai",
+            Provider::Groq => "groq",
+        }
+    }
+}
+
+/// What a paid call was made for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Purpose {
+    Embedding,
+    QueryExpansion,
+    Synthesis,
+}
+
+impl Purpose {
+    pub const ALL: [Purpose; 3] = [Purpose::Embedding, Purpose::QueryExpansion, Purpose::Synthesis];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Purpose::Embedding => "embedding",
+            Purpose::QueryExpansion => "query_expansion",
+            Purpose::Synthesis => "synthesis",
+        }
+    }
+}
+
+/// Daily spending caps and the price table.
+///
+/// Caps come from UM_DAILY_BUDGET_USD (all providers together),
+/// UM_OPENAI_DAILY_BUDGET_USD and UM_GROQ_DAILY_BUDGET_USD; unset caps are
+/// unlimited. The `um:config:budget` hash overrides them at runtime, e.g.
+/// `HSET um:config:budget groq 0.50`, and can price other models with
+/// `price:{model}` set to `input/output` USD per million tokens.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BudgetConfig {
+    pub daily_usd: Option<f64>,
+    pub openai_usd: Option<f64>,
+    pub groq_usd: Option<f64>,
+    pub prices: HashMap<String, (f64, f64)>,
+}
+
+fn parse_cap(value: &str) -> Option<f64> {
+    value.trim().parse::<f64>().ok().filter(|cap| cap.is_finite() && *cap >= 0.0)
+}
+
+fn parse_price(value: &str) -> Option<(f64, f64)> {
+    let (input, output) = value.split_once('/').unwrap_or((value, "0"));
+    match (parse_cap(input), parse_cap(output)) {
+        (Some(input), Some(output)) => Some((input, output)),
+        _ => None,
+    }
+}
+
+impl BudgetConfig {
+    pub fn from_env() -> Self {
+        let cap = |name: &str| env::var(name).ok().and_then(|value| parse_cap(&value));
+        Self {
+            daily_usd: cap("UM_DAILY_BUDGET_USD"),
+            openai_usd: cap("UM_OPENAI_DAILY_BUDGET_USD"),
+            groq_usd: cap("UM_GROQ_DAILY_BUDGET_USD"),
+            prices: PRICES.iter().map(|(model, input, output)| (model.to_string(), (*input, *output))).collect(),
+        }
+    }
+
+    /// Apply the Redis overrides; an empty value removes a cap
+    pub fn with_overrides(mut self, fields: &HashMap<String, String>) -> Self {
+        for (field, value) in fields {
+            if let Some(model) = field.strip_prefix("price:") {
+                match parse_price(value) {
+                    Some(price) => { self.prices.insert(model.to_string(), price); }
+                    None => warn!("Ignoring invalid price '{}' for model '{}'", value, model),
+                }
+                continue;
+            }
+            let target = match field.as_str() {
+                "daily" => &mut self.daily_usd,
+                "openai" => &mut self.openai_usd,
+                "groq" => &mut self.groq_usd,
+                _ => {
+                    warn!("Unknown budget config field '{}' in {}", field, BUDGET_CONFIG_KEY);
+                    continue;
+                }
+            };
+            if value.trim().is_empty() {
+                *target = None;
+            } else {
+                match parse_cap(value) {
+                    Some(cap) => *target = Some(cap),
+                    None => warn!("Ignoring invalid value '{}' for budget field '{}'", value, field),
+                }
+            }
+        }
+        self
+    }
+
+    pub fn cap(&self, provider: Provider) -> Option<f64> {
+        match provider {
+            Provider::OpenAi => self.openai_usd,
+            Provider::Groq => self.groq_usd,
+        }
+    }
+
+    /// Cost of a call in USD; unpriced models cost nothing but are still counted
+    pub fn cost(&self, model: &str, input_tokens: u64, output_tokens: u64) -> f64 {
+        let (input, output) = self.prices.get(model).copied().unwrap_or_else(|| {
+            warn!("No price for model '{}', recording its usage at no cost", model);
+            (0.0, 0.0)
+        });
+        (input_tokens as f64 * input + output_tokens as f64 * output) / 1_000_000.0
+    }
+
+    /// Whether a provider may be called given a day's usage
+    pub fn allows(&self, provider: Provider, usage: &HashMap<String, String>) -> bool {
+        let spent = |provider: Provider| field_f64(usage, &format!("{}:usd", provider.name()));
+        let total: f64 = Provider::ALL.iter().map(|p| spent(*p)).sum();
+        self.cap(provider).is_none_or(|cap| spent(provider) < cap)
+            && self.daily_usd.is_none_or(|cap| total < cap)
+    }
+}
+
+fn field_f64(fields: &HashMap<String, String>, field: &str) -> f64 {
+    fields.get(field).and_then(|v| v.parse().ok()).unwrap_or(0.0)
+}
+
+fn field_u64(fields: &HashMap<String, String>, field: &str) -> u64 {
+    fields.get(field).and_then(|v| v.parse().ok()).unwrap_or(0)
+}
+
+/// Rough token count for responses that report no usage
+pub fn estimate_tokens(text: &str) -> u64 {
+    text.chars().count().div_ceil(4) as u64
+}
+
+fn usage_key(date: NaiveDate) -> String {
+    format!("um:usage:{}", date.format("%Y-%m-%d"))
+}
+
+/// One day's usage from its hash
+fn daily_usage(date: NaiveDate, fields: &HashMap<String, String>, config: &BudgetConfig) -> DailyUsage {
+    let providers: Vec<ProviderUsage> = Provider::ALL.iter().map(|provider| {
+        let name = provider.name();
+        let spend_usd = field_f64(fields, &format!("{}:usd", name));
+        let cap_usd = config.cap(*provider);
+        ProviderUsage {
+            provider: name.to_string(),
+            spend_usd,
+            cap_usd,
+            remaining_usd: cap_usd.map(|cap| (cap - spend_usd).max(0.0)),
+            purposes: Purpose::ALL.iter()
+                .map(|purpose| {
+                    let field = |stat: &str| format!("{}:{}:{}", name, purpose.name(), stat);
+                    PurposeUsage {
+                        purpose: purpose.name().to_string(),
+                        calls: field_u64(fields, &field("calls")),
+                        input_tokens: field_u64(fields, &field("input_tokens")),
+                        output_tokens: field_u64(fields, &field("output_tokens")),
+                        spend_usd: field_f64(fields, &field("usd")),
+                        skipped: field_u64(fields, &field("skipped")),
+                    }
+                })
+                .filter(|usage| usage.calls > 0 || usage.skipped > 0)
+                .collect(),
+        }
+    }).collect();
+    DailyUsage {
+        date: date.format("%Y-%m-%d").to_string(),
+        spend_usd: providers.iter().map(|p| p.spend_usd).sum(),
+        providers,
+    }
+}
+
+/// Per-day spend on OpenAI and Groq calls, kept in the `um:usage:{YYYY-MM-DD}`
+/// hash (UTC days) so every unified-mind process shares one budget.
+///
+/// Calls are refused once a provider or the overall total reaches its daily
+/// cap, and the caller degrades: recall searches by local text match instead
+/// of an OpenAI query embedding, and Groq query expansion and synthesis are
+/// skipped. Budget lookups that fail allow the call rather than block recall.
+pub struct BudgetManager {
+    redis_client: RedisClient,
+    base: BudgetConfig,
+}
+
+impl BudgetManager {
+    pub fn new(redis_client: RedisClient) -> Self {
+        Self { redis_client, base: BudgetConfig::from_env() }
+    }
+
+    pub async fn config(&self) -> BudgetConfig {
+        match self.redis_client.hgetall(BUDGET_CONFIG_KEY).await {
+            Ok(fields) => self.base.clone().with_overrides(&fields),
+            Err(e) => {
+                warn!("Failed to load budget config, using environment caps: {}", e);
+                self.base.clone()
+            }
+        }
+    }
+
+    pub async fn allows(&self, provider: Provider) -> bool {
+        let config = self.config().await;
+        if config.daily_usd.is_none() && config.cap(provider).is_none() {
+            return true;
+        }
+        match self.redis_client.hgetall(&usage_key(Utc::now().date_naive())).await {
+            Ok(usage) => config.allows(provider, &usage),
+            Err(e) => {
+                warn!("Failed to read API usage, allowing {} call: {}", provider.name(), e);
+                true
+            }
+        }
+    }
+
+    /// Count a completed call and its cost
+    pub async fn record(&self, provider: Provider, purpose: Purpose, model: &str, input_tokens: u64, output_tokens: u64) {
+        let cost = self.config().await.cost(model, input_tokens, output_tokens);
+        let key = usage_key(Utc::now().date_naive());
+        let prefix = format!("{}:{}", provider.name(), purpose.name());
+        let result: Result<()> = async {
+            self.redis_client.hincr_float(&key, &format!("{}:usd", provider.name()), cost).await?;
+            self.redis_client.hincr_float(&key, &format!("{}:usd", prefix), cost).await?;
+            self.redis_client.hincr(&key, &format!("{}:calls", prefix), 1).await?;
+            self.redis_client.hincr(&key, &format!("{}:input_tokens", prefix), input_tokens as i64).await?;
+            self.redis_client.hincr(&key, &format!("{}:output_tokens", prefix), output_tokens as i64).await?;
+            self.redis_client.expire(&key, USAGE_TTL_SECS).await
+        }.await;
+        if let Err(e) = result {
+            warn!("Failed to record {} usage: {}", prefix, e);
+        }
+    }
+
+    /// Count a call that was not made because the budget was spent
+    pub async fn record_skip(&self, provider: Provider, purpose: Purpose) {
+        let key = usage_key(Utc::now().date_naive());
+        let field = format!("{}:{}:skipped", provider.name(), purpose.name());
+        let result: Result<()> = async {
+            self.redis_client.hincr(&key, &field, 1).await?;
+            self.redis_client.expire(&key, USAGE_TTL_SECS).await
+        }.await;
+        if let Err(e) = result {
+            warn!("Failed to record skipped call {}: {}", field, e);
+        }
+    }
+
+    /// Usage of the last `days` days, newest first
+    pub async fn usage(&self, params: UsageParams) -> Result<UsageResult> {
+        let config = self.config().await;
+        let today = Utc::now().date_naive();
+        let mut days = Vec::new();
+        for offset in 0..params.days.clamp(1, MAX_DAYS) {
+            let date = today - Duration::days(offset as i64);
+            let fields = self.redis_client.hgetall(&usage_key(date)).await?;
+            days.push(daily_usage(date, &fields, &config));
+        }
+        let today_fields = self.redis_client.hgetall(&usage_key(today)).await?;
+        Ok(UsageResult {
+            days,
+            caps: BudgetCaps {
+                daily_usd: config.daily_usd,
+                openai_usd: config.openai_usd,
+                groq_usd: config.groq_usd,
+            },
+            over_budget: Provider::ALL.iter()
+                .filter(|provider| !config.allows(**provider, &today_fields))
+                .map(|provider| provider.name().to_string())
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(field, value)| (field.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn test_parse_cap_and_price() {
+        assert_eq!(parse_cap(" 1.5 "), Some(1.5));
+        assert_eq!(parse_cap("0"), Some(0.0));
+        assert_eq!(parse_cap("-1"), None);
+        assert_eq!(parse_cap("inf"), None);
+        assert_eq!(parse_cap("lots"), None);
+        assert_eq!(parse_price("0.59/0.79"), Some((0.59, 0.79)));
+        assert_eq!(parse_price("0.13"), Some((0.13, 0.0)));
+        assert_eq!(parse_price("0.1/free"), None);
+    }
+
+    #[test]
+    fn test_with_overrides() {
+        let config = BudgetConfig { daily_usd: Some(5.0), openai_usd: Some(1.0), ..BudgetConfig::default() }
+            .with_overrides(&fields(&[
+                ("groq", "0.50"),
+                ("openai", ""),
+                ("daily", "-2"),
+                ("price:custom-model", "1/2"),
+                ("price:broken", "x"),
+                ("unknown", "1"),
+            ]));
+        assert_eq!(config.groq_usd, Some(0.5));
+        assert_eq!(config.openai_usd, None);
+        assert_eq!(config.daily_usd, Some(5.0));
+        assert_eq!(config.prices.get("custom-model"), Some(&(1.0, 2.0)));
+        assert!(!config.prices.contains_key("broken"));
+    }
+
+    #[test]
+    fn test_cost() {
+        let config = BudgetConfig::from_env();
+        assert!((config.cost("llama-3.3-70b-versatile", 1_000_000, 500_000) - (0.59 + 0.395)).abs() < 1e-9);
+        assert_eq!(config.cost("text-embedding-3-small", 0, 1_000), 0.0);
+        assert_eq!(config.cost("unpriced-model", 1_000_000, 1_000_000), 0.0);
+    }
+
+    #[test]
+    fn test_allows_checks_provider_and_daily_caps() {
+        let config = BudgetConfig { daily_usd: Some(1.0), groq_usd: Some(0.5), ..BudgetConfig::default() };
+        let usage = fields(&[("openai:usd", "0.4"), ("groq:usd", "0.3")]);
+        assert!(config.allows(Provider::Groq, &usage));
+        assert!(config.allows(Provider::OpenAi, &usage));
+
+        // The provider cap is reached before the daily one
+        let usage = fields(&[("openai:usd", "0.1"), ("groq:usd", "0.5")]);
+        assert!(!config.allows(Provider::Groq, &usage));
+        assert!(config.allows(Provider::OpenAi, &usage));
+
+        // Together the providers reach the daily cap, which stops both
+        let usage = fields(&[("openai:usd", "0.7"), ("groq:usd", "0.3")]);
+        assert!(!config.allows(Provider::Groq, &usage));
+        assert!(!config.allows(Provider::OpenAi, &usage));
+
+        assert!(BudgetConfig::default().allows(Provider::OpenAi, &fields(&[("openai:usd", "1000")])));
+    }
+}
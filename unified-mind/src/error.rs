@@ -31,6 +31,9 @@ pub enum UnifiedMindError {
     #[error("Invalid parameter: {0}")]
     InvalidParameter(String),
     
+    #[error("Daily API budget reached for {0}")]
+    BudgetExceeded(String),
+    
     #[error("Server initialization error: {0}")]
     ServerInit(String),
     
@@ -1,16 +1,19 @@
+use crate::budget::{self, BudgetManager, Provider, Purpose};
 use crate::error::{Result, UnifiedMindError};
 use crate::models::*;
 use crate::redis::RedisClient;
+use crate::scoring::{self, ScoringConfig, SCORING_CONFIG_KEY};
 use chrono::{DateTime, Utc};
 use qdrant_client::Qdrant;
 use qdrant_client::qdrant::{
     Filter, PointId, point_id, ScrollPoints, Value, value, Condition,
-    with_payload_selector::SelectorOptions, WithPayloadSelector, SearchPoints
+    with_payload_selector::SelectorOptions, WithPayloadSelector, SearchPoints, ScoredPoint
 };
 use reqwest;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
+use std::sync::Mutex;
 use std::time::Instant;
 use tracing::{error, info, warn};
 use uuid::Uuid;
@@ -24,6 +27,13 @@ struct OpenAIEmbeddingRequest {
 #[derive(Debug, Deserialize)]
 struct OpenAIEmbeddingResponse {
     data: Vec<OpenAIEmbedding>,
+    #[serde(default)]
+    usage: Option<OpenAIUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIUsage {
+    prompt_tokens: u64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -47,6 +57,9 @@ struct GroqEmbedding {
     embedding: Vec<f32>,
 }
 
+/// Qdrant collection of vault note sections, filled by the background embedding service
+const VAULT_COLLECTION: &str = "vault_notes";
+
 pub struct RecallHandler {
     redis_client: RedisClient,
     qdrant_client: Qdrant,
@@ -54,6 +67,9 @@ pub struct RecallHandler {
     openai_api_key: String,
     groq_api_key: String,
     http_client: reqwest::Client,
+    /// Scoring config and when it was last read from Redis
+    scoring: Mutex<(ScoringConfig, Option<Instant>)>,
+    budget: BudgetManager,
 }
 
 impl RecallHandler {
@@ -83,6 +99,7 @@ impl RecallHandler {
         }
         
         let http_client = reqwest::Client::new();
+        let budget = BudgetManager::new(redis_client.clone());
         
         Ok(Self {
             redis_client,
@@ -91,6 +108,8 @@ impl RecallHandler {
             openai_api_key,
             groq_api_key,
             http_client,
+            scoring: Mutex::new((ScoringConfig::default(), None)),
+            budget,
         })
     }
     
@@ -109,6 +128,10 @@ impl RecallHandler {
             return Ok(cached_embedding);
         }
         
+        if !self.budget.allows(Provider::OpenAi).await {
+            return Err(UnifiedMindError::BudgetExceeded(Provider::OpenAi.name().to_string()));
+        }
+        
         info!("Generating new OpenAI embedding for text: {}", text);
         
         let request = OpenAIEmbeddingRequest {
@@ -134,6 +157,11 @@ impl RecallHandler {
             .await
             .map_err(|e| UnifiedMindError::Other(anyhow::anyhow!("Failed to parse OpenAI response: {}", e)))?;
         
+        let input_tokens = embedding_response.usage.as_ref()
+            .map(|usage| usage.prompt_tokens)
+            .unwrap_or_else(|| budget::estimate_tokens(text));
+        self.budget.record(Provider::OpenAi, Purpose::Embedding, &request.model, input_tokens, 0).await;
+        
         let embedding = embedding_response.data.into_iter().next()
             .ok_or_else(|| UnifiedMindError::Other(anyhow::anyhow!("No embeddings returned")))?
             .embedding;
@@ -177,6 +205,7 @@ impl RecallHandler {
                     },
                     instance_id: self.instance_id.clone(),
                     timestamp: Utc::now(),
+                    degraded: Vec::new(),
                 },
                 synthesis: cached.result.synthesis,
             });
@@ -185,13 +214,23 @@ impl RecallHandler {
         // Always use semantic search with OpenAI embeddings
         // TODO: Implement local embedding model for faster/cheaper default search
         info!("Cache miss, performing semantic search");
-        let thoughts = self.search_thoughts_semantic(params.clone()).await?;
+        let mut degraded = Vec::new();
+        let thoughts = match self.search_thoughts_semantic(params.clone(), &mut degraded).await {
+            Err(UnifiedMindError::BudgetExceeded(provider)) => {
+                warn!("Daily {} budget reached, falling back to text search", provider);
+                self.budget.record_skip(Provider::OpenAi, Purpose::Embedding).await;
+                degraded.push(format!("embedding skipped: {} daily budget reached, used text search", provider));
+                self.search_thoughts(params.clone()).await?
+            }
+            result => result?,
+        };
         info!("Search returned {} thoughts", thoughts.len());
         
         let total_count = thoughts.len();
         
         // Synthesize answer with Groq if we have results
-        let synthesis = if !thoughts.is_empty() && !self.groq_api_key.is_empty() {
+        let synthesis = if !thoughts.is_empty() && !self.groq_api_key.is_empty()
+            && self.within_budget(Provider::Groq, Purpose::Synthesis, &mut degraded).await {
             info!("Attempting Groq synthesis for {} thoughts", thoughts.len());
             match self.synthesize_with_groq(&params.query, &thoughts).await {
                 Ok(result) => {
@@ -226,28 +265,184 @@ impl RecallHandler {
                 },
                 instance_id: self.instance_id.clone(),
                 timestamp: Utc::now(),
+                degraded,
             },
             synthesis,
         };
         
-        // Cache the result
-        let cached_result = CachedResult {
-            result: result.clone(),
-            cached_at: Utc::now(),
-        };
-        
-        if let Err(e) = self.redis_client.set_cached_result(&query_hash, &cached_result, 3600).await {
-            warn!("Failed to cache result: {}", e);
+        // Cache the result; degraded results are not cached so full recall resumes once the budget allows
+        if result.metadata.degraded.is_empty() {
+            let cached_result = CachedResult {
+                result: result.clone(),
+                cached_at: Utc::now(),
+            };
+            
+            if let Err(e) = self.redis_client.set_cached_result(&query_hash, &cached_result, 3600).await {
+                warn!("Failed to cache result: {}", e);
+            }
         }
         
         Ok(result)
     }
     
     
+    /// Semantic search over thoughts and vault note sections together, ranked by similarity
+    pub async fn search(&self, params: UmSearchParams) -> Result<UmSearchResult> {
+        let start_time = Instant::now();
+        info!("Processing cross-source search: {}", params.query);
+        
+        let sources = params.sources.clone().unwrap_or_else(|| vec![SearchSource::Thoughts, SearchSource::Vault]);
+        let mut collections = Vec::new();
+        if sources.contains(&SearchSource::Thoughts) {
+            let thought_collections = if params.search_all_instances {
+                self.thought_collections().await?
+            } else {
+                params.instance_filter.clone().unwrap_or_else(|| vec![self.instance_id.clone()])
+                    .iter()
+                    .map(|inst| format!("{}_thoughts", inst))
+                    .collect()
+            };
+            collections.extend(thought_collections.into_iter().map(|collection| (SearchSource::Thoughts, collection)));
+        }
+        if sources.contains(&SearchSource::Vault) {
+            collections.push((SearchSource::Vault, VAULT_COLLECTION.to_string()));
+        }
+        
+        let mut degraded = Vec::new();
+        let results = match self.generate_openai_embedding(&params.query).await {
+            Err(UnifiedMindError::BudgetExceeded(provider)) => {
+                warn!("Daily {} budget reached, falling back to text search", provider);
+                self.budget.record_skip(Provider::OpenAi, Purpose::Embedding).await;
+                degraded.push(format!("embedding skipped: {} daily budget reached, used text search", provider));
+                self.search_sources_text(&params, collections).await?
+            }
+            result => self.search_sources_semantic(&params, collections, result?).await?,
+        };
+        let (results, total_count) = rank_hits(results, params.limit);
+        
+        Ok(UmSearchResult {
+            results,
+            total_count,
+            query: params.query,
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+            degraded,
+        })
+    }
+    
+    /// Thought collections of every instance, as listed by Qdrant
+    async fn thought_collections(&self) -> Result<Vec<String>> {
+        let mut collections: Vec<String> = self.qdrant_client.list_collections().await?
+            .collections
+            .into_iter()
+            .map(|collection| collection.name)
+            .filter(|name| name.ends_with("_thoughts"))
+            .collect();
+        collections.sort();
+        Ok(collections)
+    }
+    
+    /// Similarity search of each collection for the query embedding
+    async fn search_sources_semantic(
+        &self,
+        params: &UmSearchParams,
+        collections: Vec<(SearchSource, String)>,
+        query_embedding: Vec<f32>,
+    ) -> Result<Vec<SearchHit>> {
+        let mut results = Vec::new();
+        for (source, collection) in collections {
+            if !self.qdrant_client.collection_exists(&collection).await? {
+                info!("Collection {} does not exist, skipping", collection);
+                continue;
+            }
+            
+            let search_request = SearchPoints {
+                collection_name: collection.clone(),
+                vector: query_embedding.clone(),
+                filter: None,
+                limit: params.limit as u64,
+                with_payload: Some(WithPayloadSelector {
+                    selector_options: Some(SelectorOptions::Enable(true)),
+                }),
+                with_vectors: Some(false.into()),
+                params: None,
+                score_threshold: Some(params.threshold),
+                offset: None,
+                vector_name: None,
+                read_consistency: None,
+                timeout: None,
+                shard_key_selector: None,
+                sparse_indices: None,
+            };
+            
+            match self.qdrant_client.search_points(search_request).await {
+                Ok(response) => {
+                    info!("Search returned {} results from {}", response.result.len(), collection);
+                    results.extend(response.result.into_iter().map(|point: ScoredPoint| {
+                        search_hit(source, point.id, point.payload, point.score)
+                    }));
+                }
+                Err(e) => {
+                    error!("Failed to search collection {}: {}", collection, e);
+                }
+            }
+        }
+        Ok(results)
+    }
+    
+    /// Text search of each collection, scored by the share of query terms a point contains
+    async fn search_sources_text(
+        &self,
+        params: &UmSearchParams,
+        collections: Vec<(SearchSource, String)>,
+    ) -> Result<Vec<SearchHit>> {
+        let mut results = Vec::new();
+        for (source, collection) in collections {
+            if !self.qdrant_client.collection_exists(&collection).await? {
+                info!("Collection {} does not exist, skipping", collection);
+                continue;
+            }
+            
+            let scroll_request = ScrollPoints {
+                collection_name: collection.clone(),
+                filter: None,
+                limit: Some(1000),
+                offset: None,
+                with_payload: Some(WithPayloadSelector {
+                    selector_options: Some(SelectorOptions::Enable(true)),
+                }),
+                with_vectors: Some(false.into()),
+                order_by: None,
+                read_consistency: None,
+                shard_key_selector: None,
+                timeout: None,
+            };
+            
+            match self.qdrant_client.scroll(scroll_request).await {
+                Ok(response) => {
+                    for point in response.result {
+                        let score = point.payload.get("content")
+                            .and_then(get_string_from_value)
+                            .map(|content| text_score(&params.query, content))
+                            .unwrap_or(0.0);
+                        if score > 0.0 {
+                            results.push(search_hit(source, point.id, point.payload, score));
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to search collection {}: {}", collection, e);
+                }
+            }
+        }
+        Ok(results)
+    }
+    
     async fn search_thoughts(
         &self,
         params: UmRecallParams,
     ) -> Result<Vec<Thought>> {
+        let scoring = self.scoring_config().await;
+        
         // Determine collections to search
         let collections = if params.search_all_instances {
             // Get all instance collections
@@ -326,7 +521,7 @@ impl RecallHandler {
                             match self.point_to_thought(point.id.unwrap(), payload) {
                                 Ok(mut thought) => {
                                     // Calculate weighted scores
-                                    self.calculate_weighted_scores(&mut thought).await;
+                                    self.calculate_weighted_scores(&mut thought, &scoring).await;
                                     all_thoughts.push(thought);
                                 }
                                 Err(e) => {
@@ -348,6 +543,7 @@ impl RecallHandler {
                 .partial_cmp(&a.combined_score.unwrap_or(0.0))
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
+        all_thoughts.retain(|t| t.combined_score.unwrap_or(0.0) >= scoring.min_combined_score);
         
         // Limit results
         all_thoughts.truncate(params.limit);
@@ -406,6 +602,7 @@ impl RecallHandler {
             .as_str()
             .unwrap_or(query)
             .to_string();
+        self.record_groq_usage(Purpose::QueryExpansion, &groq_request, &groq_response, &prompt, &enhanced).await;
         
         info!("Enhanced query: {}", enhanced);
         Ok(enhanced)
@@ -462,17 +659,41 @@ impl RecallHandler {
             .as_str()
             .ok_or_else(|| UnifiedMindError::Other(anyhow::anyhow!("No synthesis content in response")))?
             .to_string();
+        self.record_groq_usage(Purpose::Synthesis, &groq_request, &groq_response, &prompt, &synthesis).await;
         
         info!("Synthesis complete, length: {} chars", synthesis.len());
         Ok(synthesis)
     }
     
+    /// Whether a paid call may go ahead; notes the skipped step when the provider's budget is spent
+    async fn within_budget(&self, provider: Provider, purpose: Purpose, degraded: &mut Vec<String>) -> bool {
+        if self.budget.allows(provider).await {
+            return true;
+        }
+        self.budget.record_skip(provider, purpose).await;
+        degraded.push(format!("{} skipped: {} daily budget reached", purpose.name(), provider.name()));
+        false
+    }
+    
+    /// Record a Groq chat call from the usage it reports, estimating when it reports none
+    async fn record_groq_usage(&self, purpose: Purpose, request: &serde_json::Value, response: &serde_json::Value, prompt: &str, output: &str) {
+        let model = request["model"].as_str().unwrap_or_default();
+        let usage = &response["usage"];
+        let input_tokens = usage["prompt_tokens"].as_u64().unwrap_or_else(|| budget::estimate_tokens(prompt));
+        let output_tokens = usage["completion_tokens"].as_u64().unwrap_or_else(|| budget::estimate_tokens(output));
+        self.budget.record(Provider::Groq, purpose, model, input_tokens, output_tokens).await;
+    }
+    
     async fn search_thoughts_semantic(
         &self,
         params: UmRecallParams,
+        degraded: &mut Vec<String>,
     ) -> Result<Vec<Thought>> {
+        let scoring = self.scoring_config().await;
+        
         // First, enhance the query with Groq
-        let enhanced_query = if self.groq_api_key.is_empty() {
+        let enhanced_query = if self.groq_api_key.is_empty()
+            || !self.within_budget(Provider::Groq, Purpose::QueryExpansion, degraded).await {
             params.query.clone()
         } else {
             self.enhance_query_with_groq(&params.query).await.unwrap_or(params.query.clone())
@@ -567,7 +788,7 @@ impl RecallHandler {
                                 // Set semantic score from Qdrant
                                 thought.semantic_score = Some(scored_point.score);
                                 // Calculate other weighted scores
-                                self.calculate_weighted_scores(&mut thought).await;
+                                self.calculate_weighted_scores(&mut thought, &scoring).await;
                                 all_thoughts.push(thought);
                             }
                             Err(e) => {
@@ -588,6 +809,7 @@ impl RecallHandler {
                 .partial_cmp(&a.combined_score.unwrap_or(0.0))
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
+        all_thoughts.retain(|t| t.combined_score.unwrap_or(0.0) >= scoring.min_combined_score);
         
         // Limit results
         all_thoughts.truncate(params.limit);
@@ -606,6 +828,8 @@ impl RecallHandler {
         &self,
         params: UmRecallParams,
     ) -> Result<Vec<Thought>> {
+        let scoring = self.scoring_config().await;
+        
         // Generate embedding for the query using Groq
         let query_embedding = self.generate_groq_embedding(&params.query).await?;
         
@@ -694,7 +918,7 @@ impl RecallHandler {
                                 // Set semantic score from Qdrant
                                 thought.semantic_score = Some(scored_point.score);
                                 // Calculate other weighted scores
-                                self.calculate_weighted_scores(&mut thought).await;
+                                self.calculate_weighted_scores(&mut thought, &scoring).await;
                                 all_thoughts.push(thought);
                             }
                             Err(e) => {
@@ -715,6 +939,7 @@ impl RecallHandler {
                 .partial_cmp(&a.combined_score.unwrap_or(0.0))
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
+        all_thoughts.retain(|t| t.combined_score.unwrap_or(0.0) >= scoring.min_combined_score);
         
         // Limit results
         all_thoughts.truncate(params.limit);
@@ -812,32 +1037,64 @@ impl RecallHandler {
         })
     }
     
-    async fn calculate_weighted_scores(&self, thought: &mut Thought) {
+    /// Current scoring config, re-read from Redis once the reload interval has passed
+    async fn scoring_config(&self) -> ScoringConfig {
+        let previous = {
+            let cached = self.scoring.lock().unwrap_or_else(|e| e.into_inner());
+            match cached.1 {
+                Some(loaded_at) if loaded_at.elapsed() < scoring::reload_interval() => return cached.0.clone(),
+                _ => cached.0.clone(),
+            }
+        };
+        
+        let config = match self.redis_client.hgetall(SCORING_CONFIG_KEY).await {
+            Ok(fields) => ScoringConfig::from_hash(&fields),
+            Err(e) => {
+                warn!("Failed to load scoring config, keeping previous values: {}", e);
+                previous.clone()
+            }
+        };
+        
+        if config != previous {
+            info!("Scoring config loaded: {:?}", config);
+        }
+        *self.scoring.lock().unwrap_or_else(|e| e.into_inner()) = (config.clone(), Some(Instant::now()));
+        config
+    }
+    
+    async fn calculate_weighted_scores(&self, thought: &mut Thought, scoring: &ScoringConfig) {
         // Temporal score (0-1, based on recency)
         let age_days = (Utc::now() - thought.updated_at).num_days() as f32;
-        let temporal_score = (-age_days / 30.0).exp(); // Exponential decay over 30 days
+        let temporal_score = scoring.temporal_score(age_days);
         thought.temporal_score = Some(temporal_score);
         
         // Usage score (0-1, based on access frequency)
         let usage_score = if let Ok(Some(metadata)) = self.redis_client.get_thought_metadata(&thought.id).await {
-            // Normalize usage count (assume 100 uses is very high)
-            (metadata.usage_count as f32 / 100.0).min(1.0)
+            scoring.usage_score(metadata.usage_count)
         } else {
             0.0
         };
         thought.usage_score = Some(usage_score);
         
         // Combined score with weights
-        let semantic_weight = 0.5;
-        let temporal_weight = 0.3;
-        let usage_weight = 0.2;
-        
-        let combined_score = 
-            thought.semantic_score.unwrap_or(0.0) * semantic_weight +
-            temporal_score * temporal_weight +
-            usage_score * usage_weight;
-        
+        let semantic_score = thought.semantic_score.unwrap_or(0.0);
+        let combined_score = scoring.combine(semantic_score, temporal_score, usage_score);
         thought.combined_score = Some(combined_score);
+        
+        if scoring.debug {
+            info!(
+                "Score breakdown for {}: semantic {:.3} x {} + temporal {:.3} x {} + usage {:.3} x {} = {:.3}",
+                thought.id,
+                semantic_score, scoring.semantic_weight,
+                temporal_score, scoring.temporal_weight,
+                usage_score, scoring.usage_weight,
+                combined_score
+            );
+        }
+    }
+    
+    pub async fn usage(&self, params: UsageParams) -> Result<UsageResult> {
+        self.budget.usage(params).await
     }
     
     pub async fn submit_feedback(&self, params: FeedbackParams) -> Result<FeedbackResult> {
@@ -925,6 +1182,63 @@ impl RecallHandler {
     }
 }
 
+/// Hits from every source ranked together, limited, with the number of matches before limiting
+fn rank_hits(mut hits: Vec<SearchHit>, limit: usize) -> (Vec<SearchHit>, usize) {
+    // Every source is embedded with the same model, so similarities rank across sources
+    hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    let total_count = hits.len();
+    hits.truncate(limit);
+    (hits, total_count)
+}
+
+/// Share of the query's words that appear in the content, ignoring case
+fn text_score(query: &str, content: &str) -> f32 {
+    let content = content.to_lowercase();
+    let terms: Vec<String> = query.split_whitespace().map(|term| term.to_lowercase()).collect();
+    if terms.is_empty() {
+        return 0.0;
+    }
+    terms.iter().filter(|term| content.contains(term.as_str())).count() as f32 / terms.len() as f32
+}
+
+/// Labelled search hit from a Qdrant point and its score
+fn search_hit(source: SearchSource, id: Option<PointId>, payload: HashMap<String, Value>, score: f32) -> SearchHit {
+    let payload: serde_json::Map<String, serde_json::Value> = payload.into_iter()
+        .map(|(key, value)| (key, convert_qdrant_value_to_json(value)))
+        .collect();
+    let text = |field: &str| payload.get(field).and_then(|v| v.as_str()).map(|s| s.to_string());
+    let point_id = match id.and_then(|id| id.point_id_options) {
+        Some(point_id::PointIdOptions::Uuid(uuid)) => uuid,
+        Some(point_id::PointIdOptions::Num(num)) => num.to_string(),
+        None => String::new(),
+    };
+    
+    match source {
+        SearchSource::Thoughts => SearchHit {
+            source,
+            id: text("thought_id").unwrap_or(point_id),
+            content: text("content").unwrap_or_default(),
+            score,
+            instance_id: text("instance"),
+            note_path: None,
+            heading: None,
+            content_hash: None,
+            timestamp: text("processed_at"),
+        },
+        SearchSource::Vault => SearchHit {
+            source,
+            id: text("chunk_id").unwrap_or(point_id),
+            content: text("content").unwrap_or_default(),
+            score,
+            instance_id: None,
+            note_path: text("note_path"),
+            heading: text("heading"),
+            content_hash: text("content_hash"),
+            timestamp: text("modified_at"),
+        },
+    }
+}
+
 fn convert_qdrant_value_to_json(value: Value) -> serde_json::Value {
     match value.kind {
         Some(value::Kind::NullValue(_)) => serde_json::Value::Null,
@@ -955,4 +1269,37 @@ fn get_string_from_value(value: &Value) -> Option<&str> {
         Some(value::Kind::StringValue(s)) => Some(s.as_str()),
         _ => None,
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(pairs: &[(&str, &str)]) -> HashMap<String, Value> {
+        pairs.iter().map(|(field, value)| (field.to_string(), Value::from(value.to_string()))).collect()
+    }
+
+    #[test]
+    fn test_rank_hits_merges_sources_by_score() {
+        let hits = vec![
+            search_hit(SearchSource::Thoughts, None, payload(&[("thought_id", "t1"), ("content", "low")]), 0.4),
+            search_hit(SearchSource::Vault, None, payload(&[("chunk_id", "Note.md#Note > Plans"), ("heading", "Note > Plans")]), 0.9),
+            search_hit(SearchSource::Thoughts, None, payload(&[("thought_id", "t2"), ("instance", "CC")]), 0.7),
+        ];
+        let (ranked, total_count) = rank_hits(hits, 2);
+
+        assert_eq!(total_count, 3);
+        let ids: Vec<&str> = ranked.iter().map(|hit| hit.id.as_str()).collect();
+        assert_eq!(ids, vec!["Note.md#Note > Plans", "t2"]);
+        assert_eq!(ranked[0].source, SearchSource::Vault);
+        assert_eq!(ranked[0].heading.as_deref(), Some("Note > Plans"));
+        assert_eq!(ranked[1].instance_id.as_deref(), Some("CC"));
+    }
+
+    #[test]
+    fn test_text_score() {
+        assert_eq!(text_score("Redis tuning", "notes on redis memory TUNING"), 1.0);
+        assert_eq!(text_score("redis qdrant", "redis only"), 0.5);
+        assert_eq!(text_score("vault", "nothing here"), 0.0);
+        assert_eq!(text_score("  ", "anything"), 0.0);
+    }
+}
@@ -1,7 +1,9 @@
+mod budget;
 mod error;
 mod handlers;
 mod models;
 mod redis;
+mod scoring;
 mod service;
 
 use error::Result;
@@ -42,6 +42,76 @@ fn default_threshold() -> f32 {
     0.35
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchSource {
+    /// Embedded thoughts of the searched instances
+    Thoughts,
+    /// Sections of Obsidian vault notes
+    Vault,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UmSearchParams {
+    /// Search query
+    pub query: String,
+    
+    /// Maximum number of results to return across all sources (default: 20)
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    
+    /// Similarity threshold (0.0-1.0, default: 0.35)
+    #[serde(default = "default_threshold")]
+    pub threshold: f32,
+    
+    /// Sources to search (default: thoughts and vault)
+    #[serde(default)]
+    pub sources: Option<Vec<SearchSource>>,
+    
+    /// Search thoughts of all instances instead of just the current instance (default: false)
+    #[serde(default)]
+    pub search_all_instances: bool,
+    
+    /// Optional instance filter for thoughts (e.g., ["CC", "DT"])
+    #[serde(default)]
+    pub instance_filter: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub source: SearchSource,
+    /// Thought ID, or `{note_path}#{heading path}` for a vault section
+    pub id: String,
+    pub content: String,
+    /// Cosine similarity to the query, or the share of query words matched when search fell back to text
+    pub score: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note_path: Option<String>,
+    /// Heading path of a vault section, e.g. "Note > Section > Subsection"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading: Option<String>,
+    /// Content hash of a vault section, for citing it from ui_think
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// When the thought was embedded or the note was last modified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UmSearchResult {
+    pub results: Vec<SearchHit>,
+    /// Matches across all sources before limiting
+    pub total_count: usize,
+    pub query: String,
+    pub execution_time_ms: u64,
+    /// Paid steps skipped because a daily API budget was reached
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub degraded: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UmRecallResult {
     /// Array of retrieved thoughts
@@ -89,6 +159,9 @@ pub struct SearchMetadata {
     pub search_type: SearchType,
     pub instance_id: String,
     pub timestamp: DateTime<Utc>,
+    /// Paid steps skipped because a daily API budget was reached
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub degraded: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -144,3 +217,58 @@ pub struct FeedbackResult {
     pub message: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UsageParams {
+    /// Number of days to report, today first (default: 1, max: 90)
+    #[serde(default = "default_usage_days")]
+    pub days: usize,
+}
+
+fn default_usage_days() -> usize {
+    1
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PurposeUsage {
+    /// embedding, query_expansion or synthesis
+    pub purpose: String,
+    pub calls: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub spend_usd: f64,
+    /// Calls not made because the budget was reached
+    pub skipped: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderUsage {
+    pub provider: String,
+    pub spend_usd: f64,
+    pub cap_usd: Option<f64>,
+    pub remaining_usd: Option<f64>,
+    pub purposes: Vec<PurposeUsage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyUsage {
+    /// UTC day (YYYY-MM-DD)
+    pub date: String,
+    pub spend_usd: f64,
+    pub providers: Vec<ProviderUsage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetCaps {
+    /// Cap on all providers together; None when unlimited
+    pub daily_usd: Option<f64>,
+    pub openai_usd: Option<f64>,
+    pub groq_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageResult {
+    pub days: Vec<DailyUsage>,
+    pub caps: BudgetCaps,
+    /// Providers currently refused because a cap was reached
+    pub over_budget: Vec<String>,
+}
@@ -7,6 +7,7 @@ use std::env;
 use tracing::{debug, info};
 use uuid::Uuid;
 
+#[derive(Clone)]
 pub struct RedisClient {
     pool: Pool,
     #[allow(dead_code)]
@@ -144,6 +145,23 @@ impl RedisClient {
         Ok(())
     }
     
+    pub async fn hgetall(&self, key: &str) -> Result<std::collections::HashMap<String, String>> {
+        let mut conn = self.get_connection().await?;
+        Ok(conn.hgetall(key).await?)
+    }
+    
+    pub async fn hincr(&self, key: &str, field: &str, delta: i64) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        conn.hincr::<_, _, _, ()>(key, field, delta).await?;
+        Ok(())
+    }
+    
+    pub async fn hincr_float(&self, key: &str, field: &str, delta: f64) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        conn.hincr::<_, _, _, ()>(key, field, delta).await?;
+        Ok(())
+    }
+    
     pub async fn expire(&self, key: &str, seconds: u64) -> Result<()> {
         let mut conn = self.get_connection().await?;
         conn.expire::<_, ()>(key, seconds as i64).await?;
@@ -161,7 +179,7 @@ impl RedisClient {
             .arg(stream)
             .arg("*")
             .arg(&fields_vec)
-            .query_async(&mut conn)
+            .query_async::<()>(&mut conn)
             .await?;
         Ok(())
     }
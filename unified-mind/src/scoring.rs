@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::warn;
+
+/// Redis hash holding scoring overrides (field -> value)
+pub const SCORING_CONFIG_KEY: &str = "um:config:scoring";
+
+/// How often the scoring config is re-read from Redis
+pub fn reload_interval() -> Duration {
+    let seconds = std::env::var("UM_CONFIG_RELOAD_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(30);
+    Duration::from_secs(seconds)
+}
+
+/// Weights and thresholds used to rank recall results.
+///
+/// Every field can be overridden in the `um:config:scoring` hash, e.g.
+/// `HSET um:config:scoring semantic_weight 0.6 debug true`. Missing or invalid
+/// fields fall back to the defaults.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoringConfig {
+    pub semantic_weight: f32,
+    pub temporal_weight: f32,
+    pub usage_weight: f32,
+    /// Days for the temporal score to decay by a factor of e
+    pub temporal_decay_days: f32,
+    /// Usage count treated as a full usage score
+    pub usage_saturation: f32,
+    /// Results with a lower combined score are dropped
+    pub min_combined_score: f32,
+    /// Log per-result score breakdowns
+    pub debug: bool,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            semantic_weight: 0.5,
+            temporal_weight: 0.3,
+            usage_weight: 0.2,
+            temporal_decay_days: 30.0,
+            usage_saturation: 100.0,
+            min_combined_score: 0.0,
+            debug: std::env::var("UM_DEBUG_SCORES").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true")),
+        }
+    }
+}
+
+impl ScoringConfig {
+    /// Build a config from the Redis hash, keeping defaults for anything unset
+    pub fn from_hash(fields: &HashMap<String, String>) -> Self {
+        let mut config = Self::default();
+
+        for (field, value) in fields {
+            let target = match field.as_str() {
+                "semantic_weight" => &mut config.semantic_weight,
+                "temporal_weight" => &mut config.temporal_weight,
+                "usage_weight" => &mut config.usage_weight,
+                "temporal_decay_days" => &mut config.temporal_decay_days,
+                "usage_saturation" => &mut config.usage_saturation,
+                "min_combined_score" => &mut config.min_combined_score,
+                "debug" => {
+                    config.debug = value == "1" || value.eq_ignore_ascii_case("true");
+                    continue;
+                }
+                _ => {
+                    warn!("Unknown scoring config field '{}' in {}", field, SCORING_CONFIG_KEY);
+                    continue;
+                }
+            };
+
+            match value.parse::<f32>() {
+                Ok(parsed) if parsed.is_finite() && parsed >= 0.0 => *target = parsed,
+                _ => warn!("Ignoring invalid value '{}' for scoring field '{}'", value, field),
+            }
+        }
+
+        // Guard divisions
+        if config.temporal_decay_days <= 0.0 {
+            config.temporal_decay_days = Self::default().temporal_decay_days;
+        }
+        if config.usage_saturation <= 0.0 {
+            config.usage_saturation = Self::default().usage_saturation;
+        }
+
+        config
+    }
+
+    /// Recency score in 0-1, decaying exponentially with age
+    pub fn temporal_score(&self, age_days: f32) -> f32 {
+        (-age_days / self.temporal_decay_days).exp()
+    }
+
+    /// Usage score in 0-1
+    pub fn usage_score(&self, usage_count: u64) -> f32 {
+        (usage_count as f32 / self.usage_saturation).min(1.0)
+    }
+
+    /// Weighted combination of the individual scores
+    pub fn combine(&self, semantic: f32, temporal: f32, usage: f32) -> f32 {
+        semantic * self.semantic_weight + temporal * self.temporal_weight + usage * self.usage_weight
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(field, value)| (field.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn test_from_hash_overrides_and_fallbacks() {
+        let config = ScoringConfig::from_hash(&fields(&[
+            ("semantic_weight", "0.6"),
+            ("usage_weight", "-1"),
+            ("temporal_weight", "heavy"),
+            ("min_combined_score", "NaN"),
+            ("debug", "TRUE"),
+            ("unknown", "1"),
+        ]));
+        let defaults = ScoringConfig::default();
+        assert_eq!(config.semantic_weight, 0.6);
+        assert_eq!(config.usage_weight, defaults.usage_weight);
+        assert_eq!(config.temporal_weight, defaults.temporal_weight);
+        assert_eq!(config.min_combined_score, defaults.min_combined_score);
+        assert!(config.debug);
+    }
+
+    #[test]
+    fn test_zero_divisors_fall_back_to_defaults() {
+        let config = ScoringConfig::from_hash(&fields(&[("temporal_decay_days", "0"), ("usage_saturation", "0")]));
+        assert_eq!(config.temporal_decay_days, 30.0);
+        assert_eq!(config.usage_saturation, 100.0);
+        assert!(config.temporal_score(10.0).is_finite());
+        assert!(config.usage_score(10).is_finite());
+    }
+
+    #[test]
+    fn test_scores() {
+        let config = ScoringConfig::from_hash(&fields(&[("temporal_decay_days", "10"), ("usage_saturation", "4")]));
+        assert_eq!(config.temporal_score(0.0), 1.0);
+        assert!((config.temporal_score(10.0) - (-1.0_f32).exp()).abs() < 1e-6);
+        assert_eq!(config.usage_score(2), 0.5);
+        assert_eq!(config.usage_score(40), 1.0);
+        assert!((config.combine(1.0, 0.5, 0.0) - (0.5 + 0.15)).abs() < 1e-6);
+    }
+}
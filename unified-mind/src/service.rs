@@ -1,6 +1,6 @@
 use crate::error::UnifiedMindError;
 use crate::handlers::RecallHandler;
-use crate::models::{UmRecallParams, FeedbackParams};
+use crate::models::{UmRecallParams, UmSearchParams, FeedbackParams, UsageParams};
 use crate::redis::RedisClient;
 use rmcp::{
     handler::server::{router::tool::ToolRouter, tool::Parameters},
@@ -54,6 +54,26 @@ impl UnifiedMindService {
         }
     }
     
+    #[tool(
+        name = "um_search",
+        description = "Semantic search across thoughts and Obsidian vault note sections together; each result is labelled with its source (thoughts or vault) and ranked by similarity. Falls back to text search when the daily embedding budget is spent"
+    )]
+    async fn um_search(&self, params: Parameters<UmSearchParams>) -> std::result::Result<CallToolResult, ErrorData> {
+        info!("Processing um_search request");
+        
+        match self.recall_handler.search(params.0).await {
+            Ok(result) => {
+                let content = Content::json(result)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                error!("Error in um_search: {}", e);
+                Err(e.into())
+            }
+        }
+    }
+    
     #[tool(
         name = "um_feedback",
         description = "Submit feedback on search results to improve future searches"
@@ -73,6 +93,26 @@ impl UnifiedMindService {
             }
         }
     }
+    
+    #[tool(
+        name = "mind_usage",
+        description = "OpenAI/Groq API usage per day: calls, tokens and spend per provider and purpose, daily caps and remaining budget; calls over a cap are skipped and recall degrades to local text search"
+    )]
+    async fn mind_usage(&self, params: Parameters<UsageParams>) -> std::result::Result<CallToolResult, ErrorData> {
+        info!("Processing mind_usage request");
+        
+        match self.recall_handler.usage(params.0).await {
+            Ok(result) => {
+                let content = Content::json(result)
+                    .map_err(|e| ErrorData::internal_error(format!("Failed to create JSON content: {}", e), None))?;
+                Ok(CallToolResult::success(vec![content]))
+            },
+            Err(e) => {
+                error!("Error in mind_usage: {}", e);
+                Err(e.into())
+            }
+        }
+    }
 }
 
 #[tool_handler]